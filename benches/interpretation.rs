@@ -0,0 +1,113 @@
+//! Timing breakdown of the editor's geometry rebuild for every preset:
+//! derivation, op-table setup (`populate_standard_symbols`), skeleton
+//! interpretation, the editor's cached interpretation (`InterpreterCache`,
+//! which skips the op-table setup between derivations), and meshing.
+//!
+//! Run with `cargo bench --bench interpretation`. Each stage reports the best
+//! of several runs, so the numbers are stable enough to compare before and
+//! after a change to the turtle hot path.
+
+use bevy_symbios::LSystemMeshBuilder;
+use lsystem_explorer::core::config::{LSystemConfig, split_source_code};
+use lsystem_explorer::core::presets::PRESETS;
+use lsystem_explorer::visuals::interpreter::PlantInterpreter;
+use lsystem_explorer::visuals::turtle::InterpreterCache;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use symbios::System;
+use symbios_turtle_3d::TurtleConfig;
+
+const RUNS: usize = 5;
+
+/// Best wall-clock time of `RUNS` calls.
+fn best_of<T>(mut f: impl FnMut() -> T) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn derive(source: &str, iterations: usize) -> Option<System> {
+    let mut sys = System::new();
+    for line in source.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with("//") {
+            continue;
+        }
+        if trimmed.starts_with('#') {
+            sys.add_directive(trimmed).ok()?;
+        } else if let Some(axiom) = trimmed.strip_prefix("omega:") {
+            sys.set_axiom(axiom.trim()).ok()?;
+        } else {
+            sys.add_rule(trimmed).ok()?;
+        }
+    }
+    sys.derive(iterations).ok()?;
+    Some(sys)
+}
+
+fn ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn main() {
+    println!(
+        "{:<48} {:>9} {:>10} {:>10} {:>10} {:>10} {:>10}",
+        "preset", "modules", "derive ms", "ops ms", "turtle ms", "cached ms", "mesh ms"
+    );
+    for preset in PRESETS {
+        let (growth, _) = split_source_code(preset.code);
+        let Some(sys) = derive(&growth, preset.iterations) else {
+            println!("{:<48} failed to derive", preset.name);
+            continue;
+        };
+        let config = || TurtleConfig {
+            default_step: preset.step,
+            default_angle: preset.angle.to_radians(),
+            initial_width: preset.width,
+            tropism: preset.tropism,
+            elasticity: preset.elasticity,
+            max_stack_depth: 1024,
+        };
+
+        let derive_time = best_of(|| derive(&growth, preset.iterations));
+        let ops_time = best_of(|| {
+            let mut interpreter = PlantInterpreter::new(config());
+            interpreter.populate_standard_symbols(&sys.interner);
+            interpreter
+        });
+        let mut interpreter = PlantInterpreter::new(config());
+        interpreter.populate_standard_symbols(&sys.interner);
+        let turtle_time = best_of(|| interpreter.build_skeleton(&sys.state));
+        let skeleton = interpreter.build_skeleton(&sys.state);
+
+        // The editor path: populated once, then reused by every rebuild
+        let editor_config = LSystemConfig {
+            step_size: preset.step,
+            default_angle: preset.angle,
+            default_width: preset.width,
+            tropism: preset.tropism,
+            elasticity: preset.elasticity,
+            ..Default::default()
+        };
+        let mut cache = InterpreterCache::default();
+        cache.skeleton(0, &sys, &editor_config);
+        let cached_time = best_of(|| cache.skeleton(0, &sys, &editor_config));
+        let mesh_time = best_of(|| LSystemMeshBuilder::new().build(&skeleton));
+
+        println!(
+            "{:<48} {:>9} {:>10.3} {:>10.3} {:>10.3} {:>10.3} {:>10.3}",
+            preset.name,
+            sys.state.len(),
+            ms(derive_time),
+            ms(ops_time),
+            ms(turtle_time),
+            ms(cached_time),
+            ms(mesh_time)
+        );
+    }
+}
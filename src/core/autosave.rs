@@ -0,0 +1,182 @@
+//! Periodic autosave of the editor session and recovery after a crash or reload.
+//!
+//! The editor state is stored as a `PlantGenotype` under [`AUTOSAVE_KEY`] via
+//! [`crate::core::storage`] (a file in the config directory natively,
+//! `localStorage` on wasm). Native builds delete the autosave on a clean exit,
+//! so one found at startup means the previous session crashed. Browsers give no
+//! reliable exit hook, so on wasm the autosave is offered after every reload.
+
+use crate::core::config::{LSystemConfig, MaterialSettingsMap, PropConfig};
+use crate::core::genotype::PlantGenotype;
+use crate::core::storage::{load_setting, save_setting};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Storage key of the autosaved session.
+pub const AUTOSAVE_KEY: &str = "autosave.json";
+
+/// Seconds between autosave checks. Unchanged state is not rewritten.
+pub const AUTOSAVE_INTERVAL_SECS: f32 = 5.0;
+
+/// A saved editor session.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AutosaveSnapshot {
+    /// Local wall-clock time of the save, for display.
+    pub saved_at: String,
+    pub genotype: PlantGenotype,
+}
+
+/// Autosave timer and the session awaiting a restore decision.
+#[derive(Resource)]
+pub struct Autosave {
+    pub timer: Timer,
+    /// Serialized genotype of the last save, to skip redundant writes.
+    last_saved: Option<String>,
+    /// Session found at startup. Autosaving pauses until the user restores or
+    /// discards it, so a second crash cannot overwrite it.
+    pub recovered: Option<AutosaveSnapshot>,
+}
+
+impl Default for Autosave {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(AUTOSAVE_INTERVAL_SECS, TimerMode::Repeating),
+            last_saved: None,
+            recovered: None,
+        }
+    }
+}
+
+impl Autosave {
+    /// Restores the recovered session into the editor.
+    pub fn restore(
+        &mut self,
+        config: &mut LSystemConfig,
+        materials: &mut MaterialSettingsMap,
+        prop_config: &mut PropConfig,
+    ) {
+        if let Some(snapshot) = self.recovered.take() {
+            snapshot
+                .genotype
+                .apply_to_editor(config, materials, prop_config);
+        }
+    }
+
+    /// Drops the recovered session; the next autosave replaces it.
+    pub fn discard(&mut self) {
+        self.recovered = None;
+    }
+}
+
+/// Startup system that picks up an autosave left by the previous session.
+/// Sessions identical to the startup state are not offered.
+pub fn load_autosave(
+    mut autosave: ResMut<Autosave>,
+    config: Res<LSystemConfig>,
+    materials: Res<MaterialSettingsMap>,
+    prop_config: Res<PropConfig>,
+) {
+    let Some(json) = load_setting(AUTOSAVE_KEY) else {
+        return;
+    };
+    let snapshot: AutosaveSnapshot = match serde_json::from_str(&json) {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            warn!("Ignoring unreadable autosave: {}", e);
+            return;
+        }
+    };
+
+    let current = PlantGenotype::from_editor(&config, &materials, &prop_config);
+    if serde_json::to_string(&snapshot.genotype).ok() != serde_json::to_string(&current).ok() {
+        info!("Found autosaved session from {}", snapshot.saved_at);
+        autosave.recovered = Some(snapshot);
+    }
+}
+
+/// Writes the editor state to storage every [`AUTOSAVE_INTERVAL_SECS`] when it changed.
+pub fn autosave_system(
+    time: Res<Time>,
+    mut autosave: ResMut<Autosave>,
+    config: Res<LSystemConfig>,
+    materials: Res<MaterialSettingsMap>,
+    prop_config: Res<PropConfig>,
+) {
+    if !autosave.timer.tick(time.delta()).just_finished() || autosave.recovered.is_some() {
+        return;
+    }
+
+    let genotype = PlantGenotype::from_editor(&config, &materials, &prop_config);
+    let Ok(key) = serde_json::to_string(&genotype) else {
+        return;
+    };
+    if autosave.last_saved.as_ref() == Some(&key) {
+        return;
+    }
+
+    let snapshot = AutosaveSnapshot {
+        saved_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        genotype,
+    };
+    let result = serde_json::to_string(&snapshot)
+        .map_err(|e| e.to_string())
+        .and_then(|json| save_setting(AUTOSAVE_KEY, &json));
+    match result {
+        Ok(()) => autosave.last_saved = Some(key),
+        Err(e) => warn!("Autosave failed: {}", e),
+    }
+}
+
+/// Deletes the autosave on a clean exit, so only crashes leave one behind.
+/// A pending recovered session is kept, since the user has not decided yet.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn clear_autosave_on_exit(mut exits: MessageReader<AppExit>, autosave: Res<Autosave>) {
+    if exits.read().last().is_none() || autosave.recovered.is_some() {
+        return;
+    }
+    if let Err(e) = crate::core::storage::remove_setting(AUTOSAVE_KEY) {
+        warn!("Failed to clear autosave: {}", e);
+    }
+}
+
+/// Browsers offer no reliable exit hook; the autosave is always kept.
+#[cfg(target_arch = "wasm32")]
+pub fn clear_autosave_on_exit() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let snapshot = AutosaveSnapshot {
+            saved_at: "2026-01-01 12:00:00".into(),
+            genotype: PlantGenotype::new("omega: A\np1: A -> F A".into()).with_seed(42),
+        };
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let loaded: AutosaveSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.saved_at, snapshot.saved_at);
+        assert_eq!(loaded.genotype.source_code, snapshot.genotype.source_code);
+        assert_eq!(loaded.genotype.seed, 42);
+    }
+
+    #[test]
+    fn test_restore_applies_and_clears() {
+        let mut autosave = Autosave {
+            recovered: Some(AutosaveSnapshot {
+                saved_at: String::new(),
+                genotype: PlantGenotype::new("omega: B".into()).with_params(3, 30.0, 1.0, 0.2),
+            }),
+            ..default()
+        };
+        let mut config = LSystemConfig::default();
+        let mut materials = MaterialSettingsMap::default();
+        let mut props = PropConfig::default();
+        autosave.restore(&mut config, &mut materials, &mut props);
+
+        assert!(autosave.recovered.is_none());
+        assert_eq!(config.source_code, "omega: B");
+        assert_eq!(config.iterations, 3);
+        assert!(config.recompile_requested);
+    }
+}
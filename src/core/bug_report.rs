@@ -0,0 +1,257 @@
+//! Bug report bundles.
+//!
+//! One click collects everything needed to reproduce a rendering problem into a
+//! single zip: the grammar, the full genotype (parameters, materials, props),
+//! a system report with versions and mesh statistics, the recent log, and a
+//! screenshot. Native builds save it next to exports; browsers download it.
+
+use crate::core::config::{
+    DerivationStatus, ExportConfig, LSystemConfig, MaterialSettingsMap, PropConfig,
+    join_source_code,
+};
+use crate::core::files::save_file_binary;
+use crate::core::genotype::PlantGenotype;
+use crate::core::log_capture::recent_logs;
+use crate::visuals::turtle::TurtleRenderState;
+use bevy::prelude::*;
+use bevy::render::view::screenshot::{Screenshot, ScreenshotCaptured};
+use std::fmt::Write as _;
+use std::io::{Cursor, Write};
+
+/// The manifest the app was built from, for dependency versions.
+const CARGO_MANIFEST: &str = include_str!("../../Cargo.toml");
+
+/// Pending request and the outcome of the last bundle.
+#[derive(Resource, Default)]
+pub struct BugReport {
+    pub requested: bool,
+    /// File name of the last bundle, or why it failed.
+    pub status: Option<Result<String, String>>,
+}
+
+/// Text files of a bundle, collected before the screenshot is taken.
+pub struct BundleContents {
+    pub report: String,
+    pub grammar: String,
+    pub genotype_json: String,
+    pub log: String,
+}
+
+/// Dependency lines of the `[dependencies]` table in the build manifest.
+pub fn manifest_dependencies() -> Vec<&'static str> {
+    CARGO_MANIFEST
+        .lines()
+        .skip_while(|line| line.trim() != "[dependencies]")
+        .skip(1)
+        .take_while(|line| !line.trim_start().starts_with('['))
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect()
+}
+
+/// Enabled optional cargo features.
+fn enabled_features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if cfg!(feature = "nursery") {
+        features.push("nursery");
+    }
+    if cfg!(feature = "export") {
+        features.push("export");
+    }
+    features
+}
+
+/// Human-readable summary of the build and the current plant.
+pub fn system_report(
+    config: &LSystemConfig,
+    status: &DerivationStatus,
+    render_state: &TurtleRenderState,
+) -> String {
+    let mut report = String::new();
+    let _ = writeln!(report, "L-System Explorer {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(
+        report,
+        "Target: {}/{} ({})",
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        if cfg!(debug_assertions) {
+            "debug"
+        } else {
+            "release"
+        }
+    );
+    let _ = writeln!(report, "Features: {}", enabled_features().join(", "));
+    let _ = writeln!(
+        report,
+        "Created: {}",
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S %z")
+    );
+
+    let _ = writeln!(report, "\n[Derivation]");
+    let _ = writeln!(report, "Iterations: {}", config.iterations);
+    let _ = writeln!(report, "Seed: {}", config.seed);
+    match &status.error {
+        Some(error) => {
+            let _ = writeln!(report, "Status: error: {}", error);
+        }
+        None => {
+            let _ = writeln!(report, "Status: ok");
+        }
+    }
+    let _ = writeln!(
+        report,
+        "Timing: derivation {:.1} ms, meshing {:.1} ms",
+        render_state.derivation_time_ms, render_state.meshing_time_ms
+    );
+
+    let _ = writeln!(report, "\n[Mesh]");
+    let _ = writeln!(report, "Vertices: {}", render_state.total_vertices);
+    let _ = writeln!(report, "Triangles: {}", render_state.total_triangles);
+    let _ = writeln!(report, "Strands: {}", render_state.strand_count);
+    let _ = writeln!(report, "Props: {}", render_state.prop_count);
+    for (material_id, stats) in &render_state.material_stats {
+        let _ = writeln!(
+            report,
+            "Material {}: {} vertices, {} triangles",
+            material_id, stats.vertices, stats.triangles
+        );
+    }
+
+    let _ = writeln!(report, "\n[Dependencies]");
+    for line in manifest_dependencies() {
+        let _ = writeln!(report, "{}", line);
+    }
+    report
+}
+
+/// Zips the bundle files, with the screenshot if one was captured.
+pub fn write_bundle(
+    contents: &BundleContents,
+    screenshot_png: Option<&[u8]>,
+) -> Result<Vec<u8>, String> {
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    let mut files: Vec<(&str, &[u8])> = vec![
+        ("report.txt", contents.report.as_bytes()),
+        ("grammar.ls", contents.grammar.as_bytes()),
+        ("genotype.json", contents.genotype_json.as_bytes()),
+        ("log.txt", contents.log.as_bytes()),
+    ];
+    if let Some(png) = screenshot_png {
+        files.push(("screenshot.png", png));
+    }
+    for (name, data) in files {
+        zip.start_file(name, options)
+            .and_then(|()| zip.write_all(data).map_err(Into::into))
+            .map_err(|e| format!("Failed to add {} to the bundle: {}", name, e))?;
+    }
+    zip.finish()
+        .map(Cursor::into_inner)
+        .map_err(|e| format!("Failed to finish the bundle: {}", e))
+}
+
+/// Encodes a captured screenshot as PNG.
+fn encode_png(image: Image) -> Result<Vec<u8>, String> {
+    let dynamic = image
+        .try_into_dynamic()
+        .map_err(|e| format!("Unsupported screenshot format: {:?}", e))?;
+    let mut png = Vec::new();
+    dynamic
+        .to_rgb8()
+        .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode screenshot: {}", e))?;
+    Ok(png)
+}
+
+/// System that gathers a bundle when requested, takes a screenshot, and saves
+/// the zip once the screenshot arrives.
+#[allow(clippy::too_many_arguments)]
+pub fn create_bug_report(
+    mut commands: Commands,
+    mut bug_report: ResMut<BugReport>,
+    config: Res<LSystemConfig>,
+    materials: Res<MaterialSettingsMap>,
+    prop_config: Res<PropConfig>,
+    status: Res<DerivationStatus>,
+    render_state: Res<TurtleRenderState>,
+    export_config: Res<ExportConfig>,
+) {
+    if !bug_report.requested {
+        return;
+    }
+    bug_report.requested = false;
+
+    let genotype = PlantGenotype::from_editor(&config, &materials, &prop_config);
+    let contents = BundleContents {
+        report: system_report(&config, &status, &render_state),
+        grammar: join_source_code(&config.source_code, &config.finalization_code),
+        genotype_json: serde_json::to_string_pretty(&genotype).unwrap_or_default(),
+        log: recent_logs()
+            .iter()
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>()
+            .join("\n"),
+    };
+
+    let export_dir = export_config.output_dir.clone();
+    commands.spawn(Screenshot::primary_window()).observe(
+        move |captured: On<ScreenshotCaptured>, mut bug_report: ResMut<BugReport>| {
+            let png = encode_png(captured.image.clone())
+                .inspect_err(|e| warn!("Bug report without screenshot: {}", e))
+                .ok();
+            let filename = format!(
+                "bug_report_{}.zip",
+                chrono::Local::now().format("%Y%m%d_%H%M%S")
+            );
+            let result = write_bundle(&contents, png.as_deref())
+                .and_then(|bundle| save_file_binary(&export_dir, &filename, &bundle))
+                .map(|()| filename);
+            if let Err(e) = &result {
+                warn!("Failed to create bug report: {}", e);
+            }
+            bug_report.status = Some(result);
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_lists_all_files() {
+        let contents = BundleContents {
+            report: system_report(
+                &LSystemConfig::default(),
+                &DerivationStatus::default(),
+                &TurtleRenderState::default(),
+            ),
+            grammar: "omega: F".into(),
+            genotype_json: "{}".into(),
+            log: String::new(),
+        };
+        assert!(contents.report.contains(env!("CARGO_PKG_VERSION")));
+        assert!(
+            manifest_dependencies()
+                .iter()
+                .any(|line| line.starts_with("symbios"))
+        );
+
+        let bundle = write_bundle(&contents, Some(&[0x89, b'P', b'N', b'G'])).unwrap();
+        let archive = zip::ZipArchive::new(Cursor::new(bundle)).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort_unstable();
+        assert_eq!(
+            names,
+            [
+                "genotype.json",
+                "grammar.ls",
+                "log.txt",
+                "report.txt",
+                "screenshot.png"
+            ]
+        );
+    }
+}
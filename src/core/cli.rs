@@ -0,0 +1,253 @@
+//! Command-line arguments for scripted launches (native only).
+//!
+//! `--preset` and `--file` pick the startup plant, `--iterations` overrides its
+//! iteration count, and `--export-and-exit` writes the plant to a file and quits,
+//! which makes the GUI binary usable for quick demonstrations and screenshots:
+//!
+//! ```text
+//! lsystem-explorer --preset "Monopodial Tree" --iterations 6 --export-and-exit out.glb
+//! ```
+
+use crate::core::config::{LSystemConfig, MaterialSettingsMap, PropConfig};
+use crate::core::genotype::PlantGenotype;
+use crate::core::presets::{LSystemPreset, PRESETS};
+use crate::ui::editor::apply_preset_to_editor;
+use bevy::prelude::*;
+use bevy_panorbit_camera::PanOrbitCamera;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[cfg(feature = "export")]
+use crate::core::config::{ExportConfig, ExportFormat, OverwritePolicy};
+#[cfg(feature = "export")]
+use crate::visuals::export::ExportStatus;
+#[cfg(feature = "export")]
+use std::path::Path;
+
+/// Arguments accepted by the explorer binary.
+#[derive(Parser, Resource, Clone, Debug, Default)]
+#[command(name = "lsystem-explorer", version, about)]
+pub struct CliArgs {
+    /// Start with the named preset (case-insensitive).
+    #[arg(long, value_name = "NAME")]
+    pub preset: Option<String>,
+    /// Start with a grammar or genotype JSON file.
+    #[arg(long, value_name = "PATH")]
+    pub file: Option<PathBuf>,
+    /// Override the number of derivation iterations.
+    #[arg(long, value_name = "N")]
+    pub iterations: Option<usize>,
+    /// Export the plant to this .obj, .glb, or skeleton .json file, then exit.
+    #[arg(long, value_name = "PATH")]
+    pub export_and_exit: Option<PathBuf>,
+}
+
+/// Parses the process arguments and registers the systems that apply them.
+pub struct CliPlugin;
+
+impl Plugin for CliPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CliArgs::parse()).add_systems(
+            Startup,
+            apply_cli_args
+                .after(crate::core::config::apply_startup_preset)
+                .before(crate::core::autosave::load_autosave),
+        );
+        #[cfg(feature = "export")]
+        app.add_systems(
+            Update,
+            export_and_exit.before(crate::visuals::export::batch_export_system),
+        );
+    }
+}
+
+/// Looks up a preset by name, ignoring case.
+pub fn find_preset(name: &str) -> Option<&'static LSystemPreset> {
+    PRESETS
+        .iter()
+        .find(|preset| preset.name.eq_ignore_ascii_case(name.trim()))
+}
+
+/// Startup system that loads the plant selected on the command line.
+/// Failures are logged; with `--export-and-exit` they also end the app.
+pub fn apply_cli_args(
+    args: Res<CliArgs>,
+    mut config: ResMut<LSystemConfig>,
+    mut materials: ResMut<MaterialSettingsMap>,
+    mut prop_config: ResMut<PropConfig>,
+    mut camera_query: Query<&mut PanOrbitCamera>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    let mut failed = false;
+
+    if let Some(name) = &args.preset {
+        match find_preset(name) {
+            Some(preset) => {
+                apply_preset_to_editor(preset, &mut config, &mut materials, &mut prop_config);
+                if let Some(cam) = preset.camera {
+                    for mut pan_orbit in camera_query.iter_mut() {
+                        pan_orbit.target_focus = cam.focus;
+                        pan_orbit.target_radius = cam.distance;
+                        pan_orbit.target_pitch = cam.pitch;
+                        pan_orbit.target_yaw = cam.yaw;
+                        pan_orbit.force_update = true;
+                    }
+                }
+            }
+            None => {
+                let names: Vec<&str> = PRESETS.iter().map(|preset| preset.name).collect();
+                error!(
+                    "Unknown preset \"{}\". Available: {}",
+                    name,
+                    names.join(", ")
+                );
+                failed = true;
+            }
+        }
+    }
+
+    if let Some(path) = &args.file {
+        let template = PlantGenotype::from_editor(&config, &materials, &prop_config);
+        match std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+            .and_then(|text| PlantGenotype::from_text(&text, &template))
+        {
+            Ok(genotype) => {
+                info!("Loaded {}", path.display());
+                genotype.apply_to_editor(&mut config, &mut materials, &mut prop_config);
+            }
+            Err(e) => {
+                error!("{}", e);
+                failed = true;
+            }
+        }
+    }
+
+    if let Some(iterations) = args.iterations {
+        config.iterations = iterations;
+        config.recompile_requested = true;
+    }
+
+    if args.export_and_exit.is_some() && !cfg!(feature = "export") {
+        error!("--export-and-exit needs a build with the `export` feature");
+        failed = true;
+    }
+    if failed && args.export_and_exit.is_some() {
+        exit.write(AppExit::error());
+    }
+}
+
+/// Points the export settings at `path`: one plant with the editor's seed, in
+/// the format given by the file extension. An existing file is replaced.
+#[cfg(feature = "export")]
+pub fn configure_export(path: &Path, export_config: &mut ExportConfig) -> Result<(), String> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+    let format = ExportFormat::ALL
+        .iter()
+        .find(|format| format.extension().eq_ignore_ascii_case(extension))
+        .ok_or_else(|| format!("Unsupported export extension \"{}\"", extension))?;
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .ok_or_else(|| format!("Invalid export path {}", path.display()))?;
+
+    export_config.format = *format;
+    export_config.base_filename = stem.to_string();
+    export_config.variation_count = 1;
+    export_config.batch_subfolder = false;
+    export_config.overwrite = OverwritePolicy::Overwrite;
+    export_config.output_dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    Ok(())
+}
+
+/// System driving `--export-and-exit`: requests the export on the first frame
+/// and exits once it has finished, with a failure code if it did not succeed.
+#[cfg(feature = "export")]
+pub fn export_and_exit(
+    args: Res<CliArgs>,
+    mut export_config: ResMut<ExportConfig>,
+    export_status: Res<ExportStatus>,
+    mut requested: Local<bool>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    let Some(path) = &args.export_and_exit else {
+        return;
+    };
+    if !*requested {
+        *requested = true;
+        match configure_export(path, &mut export_config) {
+            Ok(()) => export_config.export_requested = true,
+            Err(e) => {
+                error!("{}", e);
+                exit.write(AppExit::error());
+            }
+        }
+        return;
+    }
+    if export_config.export_requested || export_status.exporting {
+        return;
+    }
+
+    match &export_status.error {
+        Some(e) => {
+            error!("Export failed: {}", e);
+            exit.write(AppExit::error());
+        }
+        None if export_status.last_export_count == 0 => {
+            error!("Nothing was exported; check the grammar for errors");
+            exit.write(AppExit::error());
+        }
+        None => {
+            info!("Exported {}", path.display());
+            exit.write(AppExit::Success);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_args() {
+        let args = CliArgs::try_parse_from([
+            "lsystem-explorer",
+            "--preset",
+            "Monopodial Tree",
+            "--iterations",
+            "6",
+        ])
+        .unwrap();
+        assert_eq!(args.preset.as_deref(), Some("Monopodial Tree"));
+        assert_eq!(args.iterations, Some(6));
+        assert!(args.file.is_none());
+        assert!(CliArgs::try_parse_from(["lsystem-explorer", "--iterations", "many"]).is_err());
+
+        let name = PRESETS[0].name.to_uppercase();
+        assert_eq!(find_preset(&name).unwrap().name, PRESETS[0].name);
+        assert!(find_preset("No Such Plant").is_none());
+    }
+
+    #[cfg(feature = "export")]
+    #[test]
+    fn test_configure_export() {
+        let mut export_config = ExportConfig::default();
+        configure_export(Path::new("renders/tree.GLB"), &mut export_config).unwrap();
+        assert!(export_config.format == ExportFormat::Glb);
+        assert_eq!(export_config.base_filename, "tree");
+        assert_eq!(export_config.variation_count, 1);
+        assert_eq!(export_config.output_dir, PathBuf::from("renders"));
+
+        configure_export(Path::new("tree.obj"), &mut export_config).unwrap();
+        assert_eq!(export_config.output_dir, PathBuf::from("."));
+        configure_export(Path::new("tree.json"), &mut export_config).unwrap();
+        assert!(export_config.format == ExportFormat::Skeleton);
+        assert!(configure_export(Path::new("tree.fbx"), &mut export_config).is_err());
+    }
+}
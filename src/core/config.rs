@@ -1,13 +1,24 @@
+#[cfg(not(target_arch = "wasm32"))]
+use crate::core::files::FolderPick;
+use crate::core::history::HistoryEntry;
 use crate::core::presets::PRESETS;
-use bevy::platform::collections::HashMap;
+use crate::logic::describe::ModuleUsage;
+use crate::logic::lint::LintWarning;
+use crate::logic::memory::MemoryEstimate;
+use crate::visuals::branch_order::DEFAULT_BRANCH_ORDER_LEVELS;
+use crate::visuals::decimate::DecimationTarget;
+use crate::visuals::mesher::{CrossSection, Shading};
+use crate::visuals::weld::WeldSettings;
+use bevy::platform::collections::{HashMap, HashSet};
 use bevy::prelude::*;
 use bevy_panorbit_camera::PanOrbitCamera; // Added for the new system
+use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
+use std::sync::mpsc::Receiver;
 use std::sync::{Arc, Mutex};
 use symbios::System;
 
-// Re-export material and export types from bevy_symbios for convenience.
-pub use bevy_symbios::export::ExportFormat;
+// Re-export material types from bevy_symbios for convenience.
 pub use bevy_symbios::materials::{MaterialSettings, MaterialSettingsMap, TextureType};
 
 /// Geometry dirty flag for split reactivity.
@@ -28,6 +39,11 @@ pub enum PropMeshType {
     Cone,
     Cylinder,
     Cube,
+    /// A single alpha-tested card showing the leaf texture.
+    Billboard,
+    /// Two alpha-tested cards crossed at right angles, so the leaf reads from
+    /// every side.
+    CrossQuad,
 }
 
 impl PropMeshType {
@@ -37,6 +53,8 @@ impl PropMeshType {
         PropMeshType::Cone,
         PropMeshType::Cylinder,
         PropMeshType::Cube,
+        PropMeshType::Billboard,
+        PropMeshType::CrossQuad,
     ];
 
     pub fn name(&self) -> &'static str {
@@ -46,8 +64,16 @@ impl PropMeshType {
             PropMeshType::Cone => "Cone",
             PropMeshType::Cylinder => "Cylinder",
             PropMeshType::Cube => "Cube",
+            PropMeshType::Billboard => "Billboard",
+            PropMeshType::CrossQuad => "Cross Quad",
         }
     }
+
+    /// Whether the mesh is a double-sided card cut out by the leaf texture's
+    /// alpha.
+    pub fn is_cutout(&self) -> bool {
+        matches!(self, PropMeshType::Billboard | PropMeshType::CrossQuad)
+    }
 }
 
 /// Configuration for prop meshes mapped to prop IDs
@@ -55,6 +81,31 @@ impl PropMeshType {
 pub struct PropConfig {
     pub prop_meshes: HashMap<u16, PropMeshType>,
     pub prop_scale: f32,
+    /// Random variation per prop ID; see [`crate::logic::jitter::jitter_props`].
+    pub prop_jitter: HashMap<u16, PropJitter>,
+    /// Imported texture cut out by the card props, or `None` for the built-in leaf.
+    pub leaf_texture: Option<String>,
+    /// Fraction of props shown in the viewport (0–1); see
+    /// [`crate::logic::jitter::thin_props`]. Exports use
+    /// [`ExportConfig::prop_density`].
+    pub prop_density: f32,
+}
+
+/// Random variation of the props with one prop ID, so they do not all align.
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub struct PropJitter {
+    /// Largest rotation about a random axis, in degrees.
+    pub rotation: f32,
+    /// Largest relative change of the size (0.2 = ±20%).
+    pub scale: f32,
+    /// Largest offset along the prop's normal (its local Z axis), in world units.
+    pub offset: f32,
+}
+
+impl PropJitter {
+    pub fn is_zero(&self) -> bool {
+        self.rotation == 0.0 && self.scale == 0.0 && self.offset == 0.0
+    }
 }
 
 impl Default for PropConfig {
@@ -65,10 +116,66 @@ impl Default for PropConfig {
         Self {
             prop_meshes,
             prop_scale: 1.0,
+            prop_jitter: HashMap::new(),
+            leaf_texture: None,
+            prop_density: 1.0,
+        }
+    }
+}
+
+/// What a material gradient is parameterized by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum GradientMode {
+    /// Branch order: the trunk is untinted, the highest order gets the tip tint.
+    #[default]
+    BranchDepth,
+    /// Path length from the root, relative to the farthest point.
+    RootDistance,
+}
+
+impl GradientMode {
+    pub const ALL: &'static [GradientMode] =
+        &[GradientMode::BranchDepth, GradientMode::RootDistance];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GradientMode::BranchDepth => "Branch depth",
+            GradientMode::RootDistance => "Distance from root",
+        }
+    }
+}
+
+/// Color gradient of one material slot, written into the vertex colors.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct MaterialGradient {
+    pub mode: GradientMode,
+    /// Multiplier reached at the tips; the base color is kept at the root.
+    pub tip_tint: [f32; 3],
+}
+
+impl Default for MaterialGradient {
+    fn default() -> Self {
+        Self {
+            mode: GradientMode::default(),
+            tip_tint: [0.4, 0.4, 0.4],
         }
     }
 }
 
+/// Gradients of the editor's material slots; slots without one are flat.
+#[derive(Resource, Default, Clone)]
+pub struct MaterialGradients {
+    pub gradients: HashMap<u8, MaterialGradient>,
+}
+
+/// Branch cross-sections of the editor's material slots; slots without one are round.
+#[derive(Resource, Default, Clone)]
+pub struct CrossSections {
+    pub profiles: HashMap<u8, CrossSection>,
+    /// Text of the custom profile fields, by slot, while being edited.
+    pub custom_input: HashMap<u8, String>,
+}
+
 #[derive(Resource)]
 pub struct LSystemConfig {
     pub source_code: String,
@@ -76,6 +183,11 @@ pub struct LSystemConfig {
     /// Applied after the main growth phase completes.
     pub finalization_code: String,
     pub iterations: usize,
+    /// Fraction of one more derivation step, for continuous growth between
+    /// iterations; zero derives whole iterations only.
+    pub growth: f32,
+    /// Global clock of timed grammars (`#timed`); sets their step count.
+    pub clock: f32,
     pub default_angle: f32,
     pub step_size: f32,
     pub default_width: f32,
@@ -85,9 +197,25 @@ pub struct LSystemConfig {
 
     /// Random seed for stochastic L-systems.
     pub seed: u64,
+    /// Keeps `seed` when a plant or history entry is loaded, and disables the
+    /// seed controls.
+    pub seed_locked: bool,
 
     /// Resolution of procedural tube meshes (vertices per ring).
     pub mesh_resolution: u32,
+    /// Merge coincident vertices of the viewport mesh before upload.
+    pub weld_vertices: bool,
+    /// Normals of the viewport mesh; flat or auto-smooth for hard-edged shapes.
+    pub shading: Shading,
+    /// Largest distance between branch rings; long segments are subdivided
+    /// and short ones merged. `None` puts one ring at every turtle step.
+    pub ring_spacing: Option<f32>,
+
+    /// Derive `#system` sub-grammars on parallel threads. Only grammars with
+    /// sections benefit; each grammar's own rewriting stays on one thread.
+    pub parallel_derivation: bool,
+    /// Stop derivations whose estimated memory exceeds this many megabytes.
+    pub memory_cap_mb: Option<usize>,
 
     pub recompile_requested: bool,
     pub auto_update: bool,
@@ -101,13 +229,21 @@ impl Default for LSystemConfig {
                 source_code: growth,
                 finalization_code: finalization,
                 iterations: last_preset.iterations,
+                growth: 0.0,
+                clock: 0.0,
                 default_angle: last_preset.angle,
                 step_size: last_preset.step,
                 default_width: last_preset.width,
                 tropism: last_preset.tropism,
                 elasticity: last_preset.elasticity,
                 seed: 82,
+                seed_locked: false,
                 mesh_resolution: 8,
+                weld_vertices: false,
+                shading: Shading::Smooth,
+                ring_spacing: None,
+                parallel_derivation: false,
+                memory_cap_mb: None,
                 recompile_requested: true,
                 auto_update: true,
             }
@@ -117,13 +253,21 @@ impl Default for LSystemConfig {
                 source_code: "omega: F\np1: F -> F".to_string(),
                 finalization_code: String::new(),
                 iterations: 1,
+                growth: 0.0,
+                clock: 0.0,
                 default_angle: 90.0,
                 step_size: 1.0,
                 default_width: 0.1,
                 tropism: None,
                 elasticity: 0.0,
                 seed: 42,
+                seed_locked: false,
                 mesh_resolution: 8,
+                weld_vertices: false,
+                shading: Shading::Smooth,
+                ring_spacing: None,
+                parallel_derivation: false,
+                memory_cap_mb: None,
                 recompile_requested: true,
                 auto_update: true,
             }
@@ -207,6 +351,12 @@ pub struct LSystemAnalysis {
     pub uses_explicit_width: bool,
     /// Maximum material ID referenced in the source code.
     pub max_material_id: u8,
+    /// Linter findings, listed in the Problems panel.
+    pub lints: Vec<LintWarning>,
+    /// Symbol counts and rule applications of the main grammar.
+    pub usage: ModuleUsage,
+    /// Estimated memory of the derived systems.
+    pub memory: MemoryEstimate,
 }
 
 /// The persistent Symbios engine
@@ -219,6 +369,10 @@ impl Default for LSystemEngine {
     }
 }
 
+/// Derived `#system` sections of the current grammar, indexed like their call IDs.
+#[derive(Resource, Default)]
+pub struct SubSystemEngines(pub Vec<System>);
+
 /// Tracks the result of the last compilation attempt
 #[derive(Resource, Default)]
 pub struct DerivationStatus {
@@ -226,6 +380,24 @@ pub struct DerivationStatus {
     pub error: Option<String>,
     /// True while an async derivation task is running
     pub generating: bool,
+    /// Latest progress report of the running task.
+    pub progress: Option<DerivationProgress>,
+    /// Set by the UI to cancel the running task; handled by `poll_derivation`.
+    pub cancel_requested: bool,
+    /// Seed of the last successful derivation.
+    pub last_seed: Option<u64>,
+}
+
+/// Progress of a running derivation, sent after each rewriting step.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DerivationProgress {
+    /// Growth steps done so far.
+    pub iteration: usize,
+    pub iterations: usize,
+    /// Whether the finalization pass is done.
+    pub finalized: bool,
+    /// Modules in the string after the last step.
+    pub modules: usize,
 }
 
 /// Debounce timer for auto-updates
@@ -247,6 +419,8 @@ impl Default for DerivationDebounce {
 /// Result from an async derivation task
 pub struct DerivationResult {
     pub system: System,
+    /// Derived `#system` sections, in definition order.
+    pub subsystems: Vec<System>,
     pub analysis: LSystemAnalysis,
     pub derivation_time_ms: f32,
 }
@@ -264,6 +438,10 @@ pub struct DerivationTask {
     pub shared: Option<SharedDerivationResult>,
     /// Cancellation flag for the current task. Set to false to cancel.
     pub cancel_flag: Option<CancellationFlag>,
+    /// Editor inputs of the current task, recorded in the history on success.
+    pub snapshot: Option<HistoryEntry>,
+    /// Progress reports of the current task.
+    pub progress: Option<Mutex<Receiver<DerivationProgress>>>,
 }
 
 /// Scans source code for material ID usage patterns: `,(N)` where N is a number.
@@ -317,6 +495,71 @@ mod tests {
         assert_eq!(scan_max_material_id("F + F"), 0);
     }
 
+    #[test]
+    fn test_export_filter() {
+        let mut filter = ExportFilter::default();
+        assert!(filter.includes_branch(0) && filter.includes_prop(1));
+
+        filter.excluded_materials.insert(1);
+        assert!(filter.includes_branch(0));
+        assert!(!filter.includes_branch(1));
+        assert!(!filter.includes_prop(1));
+
+        filter.include_props = false;
+        assert!(!filter.includes_prop(0));
+        assert!(filter.includes_branch(0));
+    }
+
+    #[test]
+    fn test_export_sweep_values() {
+        let sweep = ExportSweep {
+            parameter: SweepParameter::Constant("angle".into()),
+            start: 20.0,
+            end: 40.0,
+        };
+        assert_eq!(sweep.value(0, 5), 20.0);
+        assert_eq!(sweep.value(4, 5), 40.0);
+        assert_eq!(sweep.suffix(sweep.value(1, 5)), "angle25");
+        assert_eq!(sweep.suffix(22.5), "angle22.5");
+        assert_eq!(sweep.value(0, 1), 20.0);
+
+        let iterations = ExportSweep {
+            parameter: SweepParameter::Iterations,
+            start: 2.0,
+            end: 5.0,
+        };
+        assert_eq!(iterations.value(1, 3), 4.0);
+        assert_eq!(iterations.suffix(4.0), "iter4");
+    }
+
+    #[test]
+    fn test_axis_conventions() {
+        let up = Vec3::Y;
+        let forward = Vec3::NEG_Z;
+        for axes in AxisConvention::ALL {
+            let converted_up = axes.convert(up);
+            match axes {
+                AxisConvention::YUpRightHanded | AxisConvention::YUpLeftHanded => {
+                    assert_eq!(converted_up, Vec3::Y)
+                }
+                _ => assert_eq!(converted_up, Vec3::Z),
+            }
+
+            // Handedness: cross product flips sign exactly when the mapping mirrors
+            let x = axes.convert(Vec3::X);
+            let y = axes.convert(Vec3::Y);
+            let z = axes.convert(Vec3::Z);
+            let det = x.cross(y).dot(z);
+            assert_eq!(det < 0.0, axes.mirrors(), "{:?}", axes);
+
+            // Rotations must agree with converting the rotated vector
+            let q = Quat::from_axis_angle(Vec3::new(1.0, 2.0, 3.0).normalize(), 0.7);
+            let expected = axes.convert(q * forward);
+            let actual = axes.convert_rotation(q) * axes.convert(forward);
+            assert!(expected.abs_diff_eq(actual, 1e-5), "{:?}", axes);
+        }
+    }
+
     #[test]
     fn test_scan_max_material_id_ignores_comments() {
         // Material ID in a comment should be ignored
@@ -328,12 +571,297 @@ mod tests {
     }
 }
 
+/// Selects which parts of the plant are written by an export.
+/// Lets foliage and wood be exported as separate files for engine draw layers.
+#[derive(Clone, Debug)]
+pub struct ExportFilter {
+    /// Include branch (tube) geometry.
+    pub include_branches: bool,
+    /// Include prop geometry (leaves, flowers, etc.).
+    pub include_props: bool,
+    /// Material IDs whose branches and props are left out of the export.
+    pub excluded_materials: HashSet<u8>,
+}
+
+impl Default for ExportFilter {
+    fn default() -> Self {
+        Self {
+            include_branches: true,
+            include_props: true,
+            excluded_materials: HashSet::new(),
+        }
+    }
+}
+
+impl ExportFilter {
+    /// Returns true if geometry using `material_id` should be exported.
+    pub fn includes_material(&self, material_id: u8) -> bool {
+        !self.excluded_materials.contains(&material_id)
+    }
+
+    /// Returns true if branch geometry in material bucket `material_id` should be exported.
+    pub fn includes_branch(&self, material_id: u8) -> bool {
+        self.include_branches && self.includes_material(material_id)
+    }
+
+    /// Returns true if a prop using `material_id` should be exported.
+    pub fn includes_prop(&self, material_id: u8) -> bool {
+        self.include_props && self.includes_material(material_id)
+    }
+}
+
+/// What a batch export does when a file of the same name already exists.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverwritePolicy {
+    /// Write to `name_2.ext`, `name_3.ext`, ... instead.
+    #[default]
+    Rename,
+    /// Replace the existing file.
+    Overwrite,
+    /// Leave the existing file alone and don't write this one.
+    Skip,
+}
+
+impl OverwritePolicy {
+    pub const ALL: &'static [OverwritePolicy] = &[
+        OverwritePolicy::Rename,
+        OverwritePolicy::Overwrite,
+        OverwritePolicy::Skip,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OverwritePolicy::Rename => "Keep both (rename)",
+            OverwritePolicy::Overwrite => "Overwrite",
+            OverwritePolicy::Skip => "Skip",
+        }
+    }
+}
+
+/// File format of batch exports.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Wavefront OBJ meshes.
+    #[default]
+    Obj,
+    /// Binary glTF meshes with materials.
+    Glb,
+    /// The unmeshed turtle skeleton as JSON, for external mesh generators.
+    Skeleton,
+}
+
+impl ExportFormat {
+    pub const ALL: &'static [ExportFormat] =
+        &[ExportFormat::Obj, ExportFormat::Glb, ExportFormat::Skeleton];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportFormat::Obj => "OBJ",
+            ExportFormat::Glb => "GLB",
+            ExportFormat::Skeleton => "Skeleton JSON",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Obj => "obj",
+            ExportFormat::Glb => "glb",
+            ExportFormat::Skeleton => "json",
+        }
+    }
+}
+
+/// Target coordinate system for exported geometry.
+/// The editor works in Bevy's convention: Y-up, right-handed, -Z forward.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AxisConvention {
+    /// Y-up, right-handed (glTF, Bevy, Godot, Maya). No conversion.
+    #[default]
+    YUpRightHanded,
+    /// Z-up, right-handed (Blender, 3ds Max).
+    ZUpRightHanded,
+    /// Z-up, left-handed (Unreal Engine).
+    ZUpLeftHanded,
+    /// Y-up, left-handed (Unity).
+    YUpLeftHanded,
+}
+
+impl AxisConvention {
+    pub const ALL: &'static [AxisConvention] = &[
+        AxisConvention::YUpRightHanded,
+        AxisConvention::ZUpRightHanded,
+        AxisConvention::ZUpLeftHanded,
+        AxisConvention::YUpLeftHanded,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AxisConvention::YUpRightHanded => "Y-up, right-handed (glTF, Godot)",
+            AxisConvention::ZUpRightHanded => "Z-up, right-handed (Blender)",
+            AxisConvention::ZUpLeftHanded => "Z-up, left-handed (Unreal)",
+            AxisConvention::YUpLeftHanded => "Y-up, left-handed (Unity)",
+        }
+    }
+
+    /// Maps a position or direction from editor space into this convention.
+    pub fn convert(&self, v: Vec3) -> Vec3 {
+        match self {
+            AxisConvention::YUpRightHanded => v,
+            AxisConvention::ZUpRightHanded => Vec3::new(v.x, -v.z, v.y),
+            AxisConvention::ZUpLeftHanded => Vec3::new(v.x, v.z, v.y),
+            AxisConvention::YUpLeftHanded => Vec3::new(-v.x, v.y, v.z),
+        }
+    }
+
+    /// Maps a rotation from editor space into this convention.
+    pub fn convert_rotation(&self, q: Quat) -> Quat {
+        let axis = self.convert(Vec3::new(q.x, q.y, q.z));
+        // A mirror flips the sense of rotation about the mapped axis
+        if self.mirrors() {
+            Quat::from_xyzw(-axis.x, -axis.y, -axis.z, q.w)
+        } else {
+            Quat::from_xyzw(axis.x, axis.y, axis.z, q.w)
+        }
+    }
+
+    /// True if the conversion flips handedness, which reverses triangle winding.
+    pub fn mirrors(&self) -> bool {
+        matches!(
+            self,
+            AxisConvention::ZUpLeftHanded | AxisConvention::YUpLeftHanded
+        )
+    }
+}
+
+/// What a batch export varies from one file to the next.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum SweepParameter {
+    /// Random seed variations of the same plant.
+    #[default]
+    Seed,
+    /// A `#define` constant, e.g. `angle`; added to the grammar if missing.
+    Constant(String),
+    /// Derivation iterations, rounded to whole steps.
+    Iterations,
+}
+
+impl SweepParameter {
+    pub fn name(&self) -> String {
+        match self {
+            SweepParameter::Seed => "Seed".to_string(),
+            SweepParameter::Constant(name) => format!("#define {}", name),
+            SweepParameter::Iterations => "Iterations".to_string(),
+        }
+    }
+}
+
+/// A range swept across the files of a batch export; the batch's variation
+/// count sets the number of steps.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportSweep {
+    pub parameter: SweepParameter,
+    pub start: f32,
+    pub end: f32,
+}
+
+impl Default for ExportSweep {
+    fn default() -> Self {
+        Self {
+            parameter: SweepParameter::Seed,
+            start: 0.0,
+            end: 1.0,
+        }
+    }
+}
+
+impl ExportSweep {
+    /// The value of step `index` of `count`, evenly spaced from `start` to
+    /// `end`, before iterations are rounded. Growth animations derive the
+    /// fraction as a partial step.
+    pub fn continuous_value(&self, index: usize, count: usize) -> f32 {
+        let t = if count > 1 {
+            index as f32 / (count - 1) as f32
+        } else {
+            0.0
+        };
+        self.start + (self.end - self.start) * t
+    }
+
+    /// The value of step `index` of `count`, with iterations rounded to whole steps.
+    pub fn value(&self, index: usize, count: usize) -> f32 {
+        let value = self.continuous_value(index, count);
+        match self.parameter {
+            SweepParameter::Iterations => value.round().max(0.0),
+            _ => value,
+        }
+    }
+
+    /// File name suffix for a swept value, e.g. `angle22.5` or `iter4`.
+    pub fn suffix(&self, value: f32) -> String {
+        let value = format!("{:.3}", value)
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string();
+        match &self.parameter {
+            SweepParameter::Seed => value,
+            SweepParameter::Constant(name) => format!("{}{}", name, value),
+            SweepParameter::Iterations => format!("iter{}", value),
+        }
+    }
+}
+
+/// Default fractional digits for OBJ coordinates (micrometre precision at metre scale).
+pub const DEFAULT_OBJ_DECIMALS: usize = 6;
+
 /// Configuration for batch export
 #[derive(Resource)]
 pub struct ExportConfig {
     pub base_filename: String,
     pub variation_count: usize,
+    /// Parameter varied across the batch; seeds unless a sweep is chosen.
+    pub sweep: ExportSweep,
     pub format: ExportFormat,
+    pub filter: ExportFilter,
+    /// Write trunk, branches, and twigs as separate objects/nodes.
+    pub split_by_branch_order: bool,
+    /// Number of branch order groups when splitting; higher orders merge into the last.
+    pub branch_order_levels: u32,
+    /// Coordinate system the exported geometry is converted into.
+    pub axes: AxisConvention,
+    /// Fractional digits written for OBJ coordinates.
+    pub obj_decimals: usize,
+    /// Use 16-bit GLB indices for meshes with fewer than 65,536 vertices.
+    pub compact_indices: bool,
+    /// Store GLB positions as 16-bit integers (`KHR_mesh_quantization`).
+    pub quantize_positions: bool,
+    /// Skin GLB branches to a joint armature so they can be animated.
+    pub skinned: bool,
+    /// Export the batch as a growth animation: a numbered OBJ or skeleton
+    /// sequence, or one GLB showing each variation in turn. Pair with an
+    /// iterations or constant sweep.
+    pub animate: bool,
+    /// Seconds each frame of a GLB growth animation is shown.
+    pub frame_duration: f32,
+    /// Merge coincident vertices before writing.
+    pub weld_vertices: bool,
+    pub weld: WeldSettings,
+    /// Decimate each material bucket before writing, for game-ready LODs.
+    pub decimate: bool,
+    pub decimation: DecimationTarget,
+    /// Directory native exports are written to; browsers always download.
+    pub output_dir: PathBuf,
+    /// Folder dialog opened from the editor, until the user picks or cancels.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub folder_pick: Option<FolderPick>,
+    /// Write each batch into a new `<date>_<base filename>` folder inside `output_dir`.
+    pub batch_subfolder: bool,
+    pub overwrite: OverwritePolicy,
+    /// Write a `.meta.json` sidecar with the genotype and export settings next
+    /// to every exported file, so it can be regenerated exactly.
+    pub write_metadata: bool,
+    /// Fraction of props exported (0–1), independent of the viewport's
+    /// [`PropConfig::prop_density`].
+    pub prop_density: f32,
     pub export_requested: bool,
 }
 
@@ -342,7 +870,29 @@ impl Default for ExportConfig {
         Self {
             base_filename: "LSystem_Variant".to_string(),
             variation_count: 5,
+            sweep: ExportSweep::default(),
             format: ExportFormat::Obj,
+            filter: ExportFilter::default(),
+            split_by_branch_order: false,
+            branch_order_levels: DEFAULT_BRANCH_ORDER_LEVELS,
+            axes: AxisConvention::default(),
+            obj_decimals: DEFAULT_OBJ_DECIMALS,
+            compact_indices: true,
+            quantize_positions: false,
+            skinned: false,
+            animate: false,
+            frame_duration: 0.5,
+            weld_vertices: false,
+            weld: WeldSettings::default(),
+            decimate: false,
+            decimation: DecimationTarget::default(),
+            output_dir: PathBuf::from("exports"),
+            #[cfg(not(target_arch = "wasm32"))]
+            folder_pick: None,
+            batch_subfolder: false,
+            overwrite: OverwritePolicy::default(),
+            write_metadata: false,
+            prop_density: 1.0,
             export_requested: false,
         }
     }
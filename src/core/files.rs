@@ -0,0 +1,266 @@
+//! Platform-specific file output.
+//!
+//! Native builds write into a directory (created on demand); wasm builds hand
+//! the bytes to the browser as a download.
+
+use bevy::prelude::*;
+use std::io::{self, Write};
+use std::path::Path;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::{Arc, Mutex};
+
+#[cfg(target_arch = "wasm32")]
+pub fn save_file(_export_dir: &Path, filename: &str, content: &str) -> Result<(), String> {
+    use wasm_bindgen::JsCast;
+
+    let window = web_sys::window().ok_or("No browser window available")?;
+    let document = window.document().ok_or("No document available")?;
+
+    let blob_parts = js_sys::Array::new();
+    blob_parts.push(&wasm_bindgen::JsValue::from_str(content));
+
+    let mut options = web_sys::BlobPropertyBag::new();
+    options.type_("text/plain");
+
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(&blob_parts, &options)
+        .map_err(|e| format!("Failed to create blob: {:?}", e))?;
+
+    let url = web_sys::Url::create_object_url_with_blob(&blob)
+        .map_err(|e| format!("Failed to create URL: {:?}", e))?;
+
+    let anchor: web_sys::HtmlAnchorElement = document
+        .create_element("a")
+        .map_err(|e| format!("Failed to create anchor: {:?}", e))?
+        .dyn_into()
+        .map_err(|_| "Element is not an anchor".to_string())?;
+
+    anchor.set_href(&url);
+    anchor.set_download(filename);
+    anchor.click();
+
+    let _ = web_sys::Url::revoke_object_url(&url);
+    Ok(())
+}
+
+/// Writes a text file into `export_dir`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_file(export_dir: &Path, filename: &str, content: &str) -> Result<(), String> {
+    save_file_binary(export_dir, filename, content.as_bytes())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save_file_binary(_export_dir: &Path, filename: &str, content: &[u8]) -> Result<(), String> {
+    use wasm_bindgen::JsCast;
+
+    let window = web_sys::window().ok_or("No browser window available")?;
+    let document = window.document().ok_or("No document available")?;
+
+    let uint8arr = js_sys::Uint8Array::new_with_length(content.len() as u32);
+    uint8arr.copy_from(content);
+
+    let parts = js_sys::Array::new();
+    parts.push(&uint8arr);
+
+    let mut options = web_sys::BlobPropertyBag::new();
+    options.type_("application/octet-stream");
+
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options)
+        .map_err(|e| format!("Failed to create blob: {:?}", e))?;
+
+    let url = web_sys::Url::create_object_url_with_blob(&blob)
+        .map_err(|e| format!("Failed to create URL: {:?}", e))?;
+
+    let anchor: web_sys::HtmlAnchorElement = document
+        .create_element("a")
+        .map_err(|e| format!("Failed to create anchor: {:?}", e))?
+        .dyn_into()
+        .map_err(|_| "Element is not an anchor".to_string())?;
+
+    anchor.set_href(&url);
+    anchor.set_download(filename);
+    anchor.click();
+
+    let _ = web_sys::Url::revoke_object_url(&url);
+    Ok(())
+}
+
+/// Writes a file into `export_dir`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_file_binary(export_dir: &Path, filename: &str, content: &[u8]) -> Result<(), String> {
+    use std::fs;
+
+    create_export_dir(export_dir)?;
+    let path = export_dir.join(filename);
+    fs::write(&path, content).map_err(|e| io_error_message("write", &path, &e))?;
+    info!("Exported: {}", path.display());
+    Ok(())
+}
+
+/// Describes a failed file operation, with a hint on how to fix it for the
+/// common causes (permissions, full disk, read-only drive).
+pub fn io_error_message(action: &str, path: &Path, error: &io::Error) -> String {
+    let hint = match error.kind() {
+        io::ErrorKind::PermissionDenied => {
+            " Check the folder's permissions or choose another export folder."
+        }
+        io::ErrorKind::StorageFull => " Free up disk space and try again.",
+        io::ErrorKind::ReadOnlyFilesystem => {
+            " The drive is read-only; choose another export folder."
+        }
+        io::ErrorKind::NotFound => " Check that the folder exists.",
+        _ => "",
+    };
+    format!(
+        "Failed to {} {}: {}.{}",
+        action,
+        path.display(),
+        error,
+        hint
+    )
+}
+
+/// Creates the export directory if it does not exist yet.
+#[cfg(not(target_arch = "wasm32"))]
+fn create_export_dir(export_dir: &Path) -> Result<(), String> {
+    if export_dir.is_dir() {
+        return Ok(());
+    }
+    std::fs::create_dir_all(export_dir)
+        .map_err(|e| io_error_message("create export folder", export_dir, &e))
+}
+
+/// Returns `name`, or the first of `stem_2.ext`, `stem_3.ext`, ... that does
+/// not exist in `dir` yet, so an export never replaces an earlier one.
+pub fn available_name(dir: &Path, name: &str) -> String {
+    if !dir.join(name).exists() {
+        return name.to_string();
+    }
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+        _ => (name, String::new()),
+    };
+    (2u32..)
+        .map(|n| format!("{}_{}{}", stem, n, extension))
+        .find(|candidate| !dir.join(candidate).exists())
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// Size of the write buffer used when streaming exports to disk.
+#[cfg(not(target_arch = "wasm32"))]
+const EXPORT_WRITE_BUFFER: usize = 1 << 20;
+
+/// Streams an export into `export_dir` through a buffered file writer, so large
+/// files are flushed to disk incrementally instead of being assembled in memory.
+/// A partially written file is removed if writing fails.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_file_streamed(
+    export_dir: &Path,
+    filename: &str,
+    write: impl FnOnce(&mut dyn Write) -> io::Result<()>,
+) -> Result<(), String> {
+    use std::fs;
+    use std::io::BufWriter;
+
+    create_export_dir(export_dir)?;
+
+    let path = export_dir.join(filename);
+    let file = fs::File::create(&path).map_err(|e| io_error_message("create", &path, &e))?;
+    let mut writer = BufWriter::with_capacity(EXPORT_WRITE_BUFFER, file);
+    if let Err(e) = write(&mut writer).and_then(|()| writer.flush()) {
+        drop(writer);
+        let _ = fs::remove_file(&path);
+        return Err(io_error_message("write", &path, &e));
+    }
+    info!("Exported: {}", path.display());
+    Ok(())
+}
+
+/// Browsers can only download complete blobs, so the export is collected in
+/// memory and handed to [`save_file_binary`].
+#[cfg(target_arch = "wasm32")]
+pub fn save_file_streamed(
+    export_dir: &Path,
+    filename: &str,
+    write: impl FnOnce(&mut dyn Write) -> io::Result<()>,
+) -> Result<(), String> {
+    let mut content = Vec::new();
+    write(&mut content).map_err(|e| format!("Failed to encode {}: {}", filename, e))?;
+    save_file_binary(export_dir, filename, &content)
+}
+
+/// A folder dialog running in the background: `None` while it is open, then
+/// the chosen folder, or `Some(None)` if the dialog was cancelled.
+#[cfg(not(target_arch = "wasm32"))]
+pub type FolderPick = Arc<Mutex<Option<Option<PathBuf>>>>;
+
+/// Opens the system folder dialog at `start` without blocking the frame.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn pick_folder(start: &Path) -> FolderPick {
+    let pick: FolderPick = Arc::new(Mutex::new(None));
+    let dialog = rfd::AsyncFileDialog::new()
+        .set_title("Export folder")
+        .set_directory(start);
+    let result = pick.clone();
+    bevy::tasks::IoTaskPool::get()
+        .spawn(async move {
+            let folder = dialog
+                .pick_folder()
+                .await
+                .map(|handle| handle.path().to_path_buf());
+            if let Ok(mut guard) = result.lock() {
+                *guard = Some(folder);
+            }
+        })
+        .detach();
+    pick
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_io_error_message_hints() {
+        let path = Path::new("exports/tree.glb");
+        let denied = io_error_message(
+            "write",
+            path,
+            &io::Error::from(io::ErrorKind::PermissionDenied),
+        );
+        assert!(denied.starts_with("Failed to write exports/tree.glb"));
+        assert!(denied.contains("choose another export folder"));
+
+        let full = io_error_message("write", path, &io::Error::from(io::ErrorKind::StorageFull));
+        assert!(full.contains("Free up disk space"));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_available_name_skips_existing() {
+        let dir = std::env::temp_dir().join(format!("lsystem_names_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(available_name(&dir, "tree.obj"), "tree.obj");
+
+        std::fs::write(dir.join("tree.obj"), b"").unwrap();
+        std::fs::write(dir.join("tree_2.obj"), b"").unwrap();
+        std::fs::create_dir_all(dir.join("2024-06-01_Tree")).unwrap();
+        let renamed = available_name(&dir, "tree.obj");
+        let folder = available_name(&dir, "2024-06-01_Tree");
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(renamed, "tree_3.obj");
+        assert_eq!(folder, "2024-06-01_Tree_2");
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_streamed_save_reports_unwritable_folder() {
+        // A regular file where the export folder should be
+        let blocker = std::env::temp_dir().join(format!("lsystem_blocker_{}", std::process::id()));
+        std::fs::write(&blocker, b"").unwrap();
+        let result = save_file_streamed(&blocker, "tree.obj", |out| out.write_all(b"v 0 0 0\n"));
+        std::fs::remove_file(&blocker).unwrap();
+        assert!(result.unwrap_err().contains("Failed to create"));
+    }
+}
@@ -0,0 +1,160 @@
+//! Garden: several plants placed together in one world.
+//!
+//! Each plant keeps its own genotype and a placement on the ground plane
+//! (position, turn about the vertical axis, and uniform scale). The plants
+//! are meshed by [`crate::visuals::garden`] and can be exported together as
+//! one GLB scene.
+
+use crate::core::genotype::PlantGenotype;
+use bevy::prelude::*;
+
+/// Distance between the default positions of new plants, as in the nursery grid.
+pub const GARDEN_SPACING: f32 = 750.0;
+
+/// Plants per row of default positions.
+const GARDEN_COLUMNS: usize = 5;
+
+/// A plant in the garden.
+#[derive(Clone)]
+pub struct GardenPlant {
+    /// Stable identifier, unique within the garden.
+    pub id: u64,
+    pub name: String,
+    pub genotype: PlantGenotype,
+    /// Base position on the ground plane.
+    pub translation: Vec3,
+    /// Turn about the vertical axis, in degrees.
+    pub yaw: f32,
+    pub scale: f32,
+}
+
+impl GardenPlant {
+    /// Placement of the plant's root.
+    pub fn transform(&self) -> Transform {
+        Transform {
+            translation: self.translation,
+            rotation: Quat::from_rotation_y(self.yaw.to_radians()),
+            scale: Vec3::splat(self.scale),
+        }
+    }
+}
+
+/// The garden's plants and panel state.
+#[derive(Resource, Default)]
+pub struct Garden {
+    pub plants: Vec<GardenPlant>,
+    /// Whether garden mode is on: the garden is shown instead of the editor plant.
+    pub enabled: bool,
+    /// Id of the plant edited in the panel.
+    pub selected: Option<u64>,
+    /// Set by the panel to export every plant as one GLB.
+    pub export_requested: bool,
+    next_id: u64,
+}
+
+impl Garden {
+    /// Adds a plant at the next free default position and selects it.
+    /// Returns its id.
+    pub fn add(&mut self, name: String, genotype: PlantGenotype) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        let translation = self.free_position();
+        self.plants.push(GardenPlant {
+            id,
+            name,
+            genotype,
+            translation,
+            yaw: 0.0,
+            scale: 1.0,
+        });
+        self.selected = Some(id);
+        id
+    }
+
+    /// Adds a copy of a plant next to it. Returns the copy's id.
+    pub fn duplicate(&mut self, id: u64) -> Option<u64> {
+        let original = self.get(id)?.clone();
+        let copy = self.add(format!("{} copy", original.name), original.genotype);
+        let plant = self.get_mut(copy)?;
+        plant.translation = original.translation + Vec3::X * GARDEN_SPACING * 0.5;
+        plant.yaw = original.yaw;
+        plant.scale = original.scale;
+        Some(copy)
+    }
+
+    /// Removes a plant. Returns `false` if no plant has this id.
+    pub fn remove(&mut self, id: u64) -> bool {
+        let before = self.plants.len();
+        self.plants.retain(|plant| plant.id != id);
+        if self.selected == Some(id) {
+            self.selected = None;
+        }
+        self.plants.len() != before
+    }
+
+    pub fn get(&self, id: u64) -> Option<&GardenPlant> {
+        self.plants.iter().find(|plant| plant.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: u64) -> Option<&mut GardenPlant> {
+        self.plants.iter_mut().find(|plant| plant.id == id)
+    }
+
+    /// First cell of the default grid without a plant near it.
+    fn free_position(&self) -> Vec3 {
+        (0..)
+            .map(|index| {
+                Vec3::new(
+                    (index % GARDEN_COLUMNS) as f32 * GARDEN_SPACING,
+                    0.0,
+                    (index / GARDEN_COLUMNS) as f32 * GARDEN_SPACING,
+                )
+            })
+            .find(|cell| {
+                self.plants.iter().all(|plant| {
+                    Vec2::new(plant.translation.x - cell.x, plant.translation.z - cell.z).length()
+                        >= GARDEN_SPACING * 0.5
+                })
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plant() -> PlantGenotype {
+        PlantGenotype::new("omega: F".into())
+    }
+
+    #[test]
+    fn test_add_fills_free_cells() {
+        let mut garden = Garden::default();
+        let a = garden.add("A".into(), plant());
+        let b = garden.add("B".into(), plant());
+        assert_eq!(garden.get(a).unwrap().translation, Vec3::ZERO);
+        assert_eq!(garden.get(b).unwrap().translation, Vec3::X * GARDEN_SPACING);
+
+        // A removed plant frees its cell
+        assert!(garden.remove(a));
+        let c = garden.add("C".into(), plant());
+        assert_eq!(garden.get(c).unwrap().translation, Vec3::ZERO);
+        assert_eq!(garden.selected, Some(c));
+    }
+
+    #[test]
+    fn test_duplicate_keeps_placement() {
+        let mut garden = Garden::default();
+        let a = garden.add("A".into(), plant());
+        let original = garden.get_mut(a).unwrap();
+        original.yaw = 45.0;
+        original.scale = 2.0;
+
+        let copy = garden.duplicate(a).unwrap();
+        let copy = garden.get(copy).unwrap();
+        assert_eq!(copy.name, "A copy");
+        assert_eq!((copy.yaw, copy.scale), (45.0, 2.0));
+        assert_eq!(copy.translation, Vec3::X * GARDEN_SPACING * 0.5);
+    }
+}
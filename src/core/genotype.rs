@@ -1,23 +1,33 @@
 //! Plant genotype representation for evolutionary L-systems.
 //!
 //! This module provides `PlantGenotype`, a wrapper around L-system source code
-//! and material settings that implements the `Genotype` trait from symbios-genetics.
+//! and material settings that implements the `Genotype` trait from symbios-genetics
+//! (with the `nursery` feature).
 //!
 //! The key design principle is that the **source code is the single source of truth**.
 //! Mutations operate on the compiled System, but the results are decompiled back to
 //! source code after each operation.
 
+use bevy::math::Vec3;
 use bevy::platform::collections::HashMap;
 use bevy_symbios::materials::{MaterialSettings, TextureType};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
+use std::hash::{DefaultHasher, Hash, Hasher};
 use symbios::System;
+#[cfg(feature = "nursery")]
 use symbios::system::crossover::CrossoverConfig;
 use symbios::system::mutate::{MutationConfig, StructuralMutationConfig};
+#[cfg(feature = "nursery")]
 use symbios_genetics::Genotype;
 
-use crate::core::config::{PropMeshType, scan_max_material_id, split_source_code};
+use crate::core::config::{
+    LSystemConfig, MaterialSettingsMap, PropConfig, PropMeshType, scan_max_material_id,
+    split_source_code,
+};
 use crate::core::presets::LSystemPreset;
+use crate::core::share::decode_share_fragment;
+use crate::logic::rule_line::RuleLine;
 
 /// Serializable version of material settings for genetic storage.
 #[derive(Clone, Serialize, Deserialize)]
@@ -71,6 +81,59 @@ impl SerializableMaterial {
     }
 }
 
+/// Which mutation operators run, and how strongly.
+///
+/// Rates scale with the nursery's overall mutation rate; disabled operators
+/// leave their part of the genotype untouched, so e.g. colors can evolve while
+/// the grammar stays frozen.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MutationSettings {
+    /// Mutate rule probabilities and constants.
+    pub grammar: bool,
+    pub rule_probability_strength: f64,
+    pub constant_strength: f64,
+    pub gaussian_jitter_scale: f64,
+    /// Mutate rule successors (insert, delete, swap symbols and bytecode).
+    pub structure: bool,
+    /// Per-successor mutation chance, multiplied by the mutation rate.
+    pub successor_rate: f64,
+    pub insert_rate: f64,
+    pub delete_rate: f64,
+    pub swap_rate: f64,
+    /// Per-expression bytecode mutation chance, multiplied by the mutation rate.
+    pub bytecode_rate: f64,
+    pub op_rate: f64,
+    pub push_perturbation: f64,
+    /// Mutate material colors, roughness, and metallic.
+    pub materials: bool,
+    /// Mutate angle, step, width, elasticity, and tropism.
+    pub parameters: bool,
+    /// Redraw the seed for stochastic rules.
+    pub seed: bool,
+}
+
+impl Default for MutationSettings {
+    fn default() -> Self {
+        Self {
+            grammar: true,
+            rule_probability_strength: 0.2,
+            constant_strength: 0.3,
+            gaussian_jitter_scale: 0.4,
+            structure: true,
+            successor_rate: 0.3,
+            insert_rate: 0.1,
+            delete_rate: 0.1,
+            swap_rate: 0.2,
+            bytecode_rate: 0.2,
+            op_rate: 0.1,
+            push_perturbation: 0.5,
+            materials: true,
+            parameters: true,
+            seed: true,
+        }
+    }
+}
+
 /// A plant genotype encoding an L-system with material settings.
 ///
 /// This struct wraps the L-system source code and associated configuration,
@@ -98,9 +161,250 @@ pub struct PlantGenotype {
     pub tropism: Option<[f32; 3]>,
     /// Random seed for stochastic rules.
     pub seed: u64,
+    /// Global clock of timed grammars (`#timed`); sets their step count.
+    #[serde(default)]
+    pub clock: f32,
     /// Prop ID to mesh type mapping, persisted so nursery champions retain their prop visuals.
     #[serde(default)]
     pub prop_mappings: HashMap<u16, PropMeshType>,
+    /// Rule keys (see [`rule_key`]) that mutation and crossover leave untouched.
+    #[serde(default)]
+    pub locked: Vec<String>,
+}
+
+/// Rule key of the axiom line.
+pub const AXIOM_KEY: &str = "omega";
+
+/// The key a grammar line is locked by: [`AXIOM_KEY`] for the axiom, or the
+/// predecessor symbol for a rule (`p1: B < A(x) > C : x > 0 -> ...` has key
+/// `A`). Returns `None` for directives, comments, and blank lines.
+pub fn rule_key(line: &str) -> Option<String> {
+    let line = line.trim();
+    if line.starts_with("//") || line.starts_with('#') {
+        return None;
+    }
+    if line.starts_with("omega:") {
+        return Some(AXIOM_KEY.to_string());
+    }
+    let lhs = RuleLine::parse(line)?.predecessor;
+    let lhs = lhs.rsplit('<').next().unwrap_or(lhs);
+    let lhs = lhs.split('>').next().unwrap_or(lhs);
+    let symbol = lhs.split('(').next().unwrap_or(lhs).trim();
+    (!symbol.is_empty()).then(|| symbol.to_string())
+}
+
+/// The distinct rule keys of `source`, in order of first appearance.
+pub fn rule_keys(source: &str) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::new();
+    for key in source.lines().filter_map(rule_key) {
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+    keys
+}
+
+/// Puts the lines of `original` with a locked key back into `changed`, each
+/// key's lines at the position of that key's first line in `changed`.
+///
+/// Falls back to `original` if the result no longer parses.
+fn restore_locked(original: &str, changed: String, locked: &[String]) -> String {
+    if locked.is_empty() || original == changed {
+        return changed;
+    }
+    let locked_key = |line: &str| rule_key(line).filter(|key| locked.contains(key));
+
+    let mut out: Vec<&str> = Vec::new();
+    let mut restored: Vec<String> = Vec::new();
+    for line in changed.lines() {
+        match locked_key(line) {
+            Some(key) => {
+                if !restored.contains(&key) {
+                    out.extend(lines_with_key(original, &key));
+                    restored.push(key);
+                }
+            }
+            None => out.push(line),
+        }
+    }
+    // Locked rules the change dropped entirely
+    for key in rule_keys(original) {
+        if locked.contains(&key) && !restored.contains(&key) {
+            out.extend(lines_with_key(original, &key));
+        }
+    }
+
+    let result = out.join("\n");
+    if System::from_source(&result).is_ok() {
+        result
+    } else {
+        original.to_string()
+    }
+}
+
+fn lines_with_key<'a>(source: &'a str, key: &str) -> impl Iterator<Item = &'a str> {
+    source
+        .lines()
+        .filter(move |line| rule_key(line).as_deref() == Some(key))
+}
+
+/// Splits a rule line into its label and the rest; other lines are all
+/// rest.
+fn split_label(line: &str) -> (Option<&str>, &str) {
+    match RuleLine::parse(line) {
+        Some(rule) => (rule.label, rule.body),
+        None => (None, line),
+    }
+}
+
+/// Splits a trailing `// comment` off a grammar line.
+fn split_inline_comment(line: &str) -> (&str, Option<&str>) {
+    match line.find("//") {
+        Some(at) if at > 0 => (line[..at].trim_end(), Some(&line[at..])),
+        _ => (line, None),
+    }
+}
+
+/// What a statement of a grammar is matched by across a round-trip: defines
+/// by name, the axiom, the `n`-th rule of a predecessor, or any other
+/// directive by its keyword.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum StatementKey {
+    Define(String),
+    Rule(String, usize),
+    Directive(String),
+}
+
+/// Key of a statement line, counting rule occurrences in `occurrences`.
+/// `None` for blank lines, comments, and anything unrecognized.
+fn statement_key(line: &str, occurrences: &mut HashMap<String, usize>) -> Option<StatementKey> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with("//") {
+        return None;
+    }
+    if let Some(rest) = line.strip_prefix("#define") {
+        let name = rest.split_whitespace().next().unwrap_or_default();
+        return Some(StatementKey::Define(name.to_string()));
+    }
+    if line.starts_with('#') {
+        let keyword = line.split([' ', ':']).next().unwrap_or(line);
+        return Some(StatementKey::Directive(keyword.to_string()));
+    }
+    let key = rule_key(line)?;
+    let count = occurrences.entry(key.clone()).or_default();
+    *count += 1;
+    Some(StatementKey::Rule(key, *count - 1))
+}
+
+/// Same statement up to whitespace, labels, and inline comments.
+fn same_statement(a: &str, b: &str) -> bool {
+    let body = |line: &'_ str| -> String {
+        let (content, _) = split_inline_comment(line);
+        split_label(content).1.split_whitespace().collect()
+    };
+    if body(a) == body(b) {
+        return true;
+    }
+    // Defines whose values only differ in notation ("2" and "2.0")
+    let value = |line: &str| -> Option<(String, f64)> {
+        let mut parts = split_inline_comment(line)
+            .0
+            .strip_prefix("#define")?
+            .split_whitespace();
+        let name = parts.next()?.to_string();
+        Some((name, parts.next()?.parse().ok()?))
+    };
+    value(a).is_some_and(|a| value(b) == Some(a))
+}
+
+/// Merges `regenerated`, a source decompiled from a mutated or crossed-over
+/// system, back into the layout of `original`; see
+/// [`PlantGenotype::reconstruct_source`].
+fn merge_source(original: &str, regenerated: &str) -> String {
+    let mut occurrences = HashMap::new();
+    let fresh_statements: Vec<(StatementKey, &str)> = regenerated
+        .lines()
+        .filter_map(|line| Some((statement_key(line, &mut occurrences)?, line.trim())))
+        .collect();
+    let mut fresh: HashMap<StatementKey, &str> = fresh_statements.iter().cloned().collect();
+
+    // Everything a statement of each kind or predecessor follows
+    let anchor = |key: &StatementKey| match key {
+        StatementKey::Define(_) => "#define".to_string(),
+        StatementKey::Rule(symbol, _) => symbol.clone(),
+        StatementKey::Directive(keyword) => keyword.clone(),
+    };
+    let mut out: Vec<String> = Vec::new();
+    // Comments directly above the next statement, dropped along with it
+    let mut comments: Vec<&str> = Vec::new();
+    // Output position after the last statement of each anchor
+    let mut anchors: HashMap<String, usize> = HashMap::new();
+    let mut occurrences = HashMap::new();
+
+    for line in original.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("//") {
+            comments.push(line);
+            continue;
+        }
+        let Some(key) = statement_key(trimmed, &mut occurrences) else {
+            out.extend(comments.drain(..).map(str::to_string));
+            out.push(line.to_string());
+            continue;
+        };
+        let merged = match fresh.remove(&key) {
+            // Directives are left alone; mutation does not touch them
+            _ if matches!(key, StatementKey::Directive(_)) => Some(line.to_string()),
+            Some(new) if same_statement(trimmed, new) => Some(line.to_string()),
+            Some(new) => {
+                let (content, inline) = split_inline_comment(trimmed);
+                let body = split_label(new).1;
+                let merged = match split_label(content).0 {
+                    Some(label) => format!("{}: {}", label, body),
+                    None => body.to_string(),
+                };
+                Some(match inline {
+                    Some(inline) => format!("{} {}", merged, inline),
+                    None => merged,
+                })
+            }
+            None => None,
+        };
+        match merged {
+            Some(merged) => {
+                out.extend(comments.drain(..).map(str::to_string));
+                out.push(merged);
+                anchors.insert(anchor(&key), out.len());
+            }
+            None => comments.clear(),
+        }
+    }
+    out.extend(comments.drain(..).map(str::to_string));
+
+    // New statements go after their relatives, or at the end
+    for (key, _) in &fresh_statements {
+        if matches!(key, StatementKey::Directive(_)) {
+            continue;
+        }
+        let Some(new) = fresh.remove(key) else {
+            continue;
+        };
+        match anchors.get(&anchor(key)).copied() {
+            Some(at) => {
+                out.insert(at, new.to_string());
+                for position in anchors.values_mut() {
+                    if *position >= at {
+                        *position += 1;
+                    }
+                }
+            }
+            None => {
+                out.push(new.to_string());
+                anchors.insert(anchor(key), out.len());
+            }
+        }
+    }
+    out.join("\n")
 }
 
 impl PlantGenotype {
@@ -117,7 +421,9 @@ impl PlantGenotype {
             elasticity: 0.0,
             tropism: None,
             seed: 42,
+            clock: 0.0,
             prop_mappings: HashMap::new(),
+            locked: Vec::new(),
         }
     }
 
@@ -151,6 +457,54 @@ impl PlantGenotype {
         self
     }
 
+    /// Captures the current editor state (grammar, parameters, materials, props).
+    pub fn from_editor(
+        config: &LSystemConfig,
+        materials: &MaterialSettingsMap,
+        prop_config: &PropConfig,
+    ) -> Self {
+        let mut genotype = Self::new(config.source_code.clone())
+            .with_finalization(config.finalization_code.clone())
+            .with_materials(&materials.settings)
+            .with_params(
+                config.iterations,
+                config.default_angle,
+                config.step_size,
+                config.default_width,
+            )
+            .with_seed(config.seed);
+        genotype.elasticity = config.elasticity;
+        genotype.tropism = config.tropism.map(|v| [v.x, v.y, v.z]);
+        genotype.clock = config.clock;
+        genotype.prop_mappings = prop_config.prop_meshes.clone();
+        genotype
+    }
+
+    /// Loads this genotype into the editor state and requests a recompile. A
+    /// locked seed is kept.
+    pub fn apply_to_editor(
+        self,
+        config: &mut LSystemConfig,
+        materials: &mut MaterialSettingsMap,
+        prop_config: &mut PropConfig,
+    ) {
+        materials.settings = self.get_material_settings();
+        config.source_code = self.source_code;
+        config.finalization_code = self.finalization_code;
+        config.iterations = self.iterations;
+        config.default_angle = self.angle;
+        config.step_size = self.step;
+        config.default_width = self.width;
+        config.elasticity = self.elasticity;
+        config.tropism = self.tropism.map(|t| Vec3::new(t[0], t[1], t[2]));
+        config.clock = self.clock;
+        if !config.seed_locked {
+            config.seed = self.seed;
+        }
+        config.recompile_requested = true;
+        prop_config.prop_meshes = self.prop_mappings;
+    }
+
     /// Creates a PlantGenotype from a static LSystemPreset.
     ///
     /// This converts a preset's configuration into an evolvable genotype,
@@ -188,10 +542,22 @@ impl PlantGenotype {
             elasticity: preset.elasticity,
             tropism: preset.tropism.map(|v| [v.x, v.y, v.z]),
             seed: 42,
+            clock: 0.0,
             prop_mappings: preset.prop_meshes.iter().copied().collect(),
+            locked: Vec::new(),
         }
     }
 
+    /// Hash of the serialized genotype. Equal genotypes hash equally, so it
+    /// identifies thumbnails and shared meshes across generations.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        serde_json::to_string(self)
+            .unwrap_or_default()
+            .hash(&mut hasher);
+        hasher.finish()
+    }
+
     /// Returns materials converted to MaterialSettings.
     pub fn get_material_settings(&self) -> HashMap<u8, MaterialSettings> {
         self.materials
@@ -200,6 +566,29 @@ impl PlantGenotype {
             .collect()
     }
 
+    /// Reads a genotype from text: genotype JSON, a share link, or a grammar
+    /// (optionally with a `/// DECOMPOSITION ///` section) that takes its
+    /// parameters and materials from `template`.
+    pub fn from_text(text: &str, template: &PlantGenotype) -> Result<Self, String> {
+        let text = text.trim();
+        if text.starts_with('{') {
+            return serde_json::from_str(text).map_err(|e| format!("Invalid genotype: {}", e));
+        }
+        if let Some(idx) = text.find("#g=") {
+            return decode_share_fragment(&text[idx..]);
+        }
+        let (growth, finalization) = split_source_code(text);
+        let genotype = Self {
+            source_code: growth,
+            finalization_code: finalization,
+            ..template.clone()
+        };
+        if genotype.parse().is_none() {
+            return Err("Grammar does not parse".to_string());
+        }
+        Ok(genotype)
+    }
+
     /// Parses the source code into a System.
     ///
     /// Returns None if parsing fails.
@@ -207,6 +596,27 @@ impl PlantGenotype {
         System::from_source(&self.source_code).ok()
     }
 
+    /// Source code of `system` laid out like `original`, the source it was
+    /// parsed from before mutation or crossover.
+    ///
+    /// The decompiler writes bare statements only, so each statement is
+    /// matched back to the original (defines by name, the axiom, and the
+    /// `n`-th rule of each predecessor) to keep its rule label, the comments
+    /// above it, its inline comment, and its position. Unchanged statements
+    /// keep their original text. Statements the original lacks are placed
+    /// after their relatives; statements the system dropped are removed with
+    /// the comments directly above them. Falls back to the decompiled source
+    /// if the merge does not parse.
+    pub fn reconstruct_source(original: &str, system: &System) -> String {
+        let regenerated = system.to_source();
+        let merged = merge_source(original, &regenerated);
+        if System::from_source(&merged).is_ok() {
+            merged
+        } else {
+            regenerated
+        }
+    }
+
     /// Mutates the material colors slightly.
     fn mutate_materials<R: Rng>(&mut self, rng: &mut R, rate: f32) {
         for settings in self.materials.values_mut() {
@@ -225,6 +635,7 @@ impl PlantGenotype {
     }
 
     /// Blends materials from two parents.
+    #[cfg(feature = "nursery")]
     fn blend_materials(
         a: &HashMap<u8, SerializableMaterial>,
         b: &HashMap<u8, SerializableMaterial>,
@@ -268,8 +679,11 @@ impl PlantGenotype {
     }
 }
 
-impl Genotype for PlantGenotype {
-    fn mutate<R: Rng>(&mut self, rng: &mut R, rate: f32) {
+impl PlantGenotype {
+    /// Mutates with only the operators enabled in `settings`.
+    ///
+    /// With every operator enabled this is the same as [`Genotype::mutate`].
+    pub fn mutate_with<R: Rng>(&mut self, rng: &mut R, rate: f32, settings: &MutationSettings) {
         // Skip mutation if rate is too low
         if rate <= 0.0 {
             return;
@@ -283,37 +697,46 @@ impl Genotype for PlantGenotype {
         // Apply parametric mutations (probabilities and constants)
         let mutation_config = MutationConfig {
             rule_probability_rate: rate as f64,
-            rule_probability_strength: 0.2,
+            rule_probability_strength: settings.rule_probability_strength,
             constant_rate: rate as f64,
-            constant_strength: 0.3,
-            gaussian_jitter_scale: 0.4,
+            constant_strength: settings.constant_strength,
+            gaussian_jitter_scale: settings.gaussian_jitter_scale,
             gaussian_jitter_rate: rate as f64,
         };
-        system.mutate_with_rng(rng, &mutation_config);
+        if settings.grammar {
+            system.mutate_with_rng(rng, &mutation_config);
+        }
 
         // Apply structural mutations at a lower rate
-        if rng.random::<f32>() < rate * 0.5 {
+        if settings.structure && rng.random::<f32>() < rate * 0.5 {
             let structural_config = StructuralMutationConfig {
-                successor_rate: rate as f64 * 0.3,
-                insert_rate: 0.1,
-                delete_rate: 0.1,
-                swap_rate: 0.2,
-                bytecode_rate: rate as f64 * 0.2,
-                op_rate: 0.1,
-                push_perturbation: 0.5,
+                successor_rate: rate as f64 * settings.successor_rate,
+                insert_rate: settings.insert_rate,
+                delete_rate: settings.delete_rate,
+                swap_rate: settings.swap_rate,
+                bytecode_rate: rate as f64 * settings.bytecode_rate,
+                op_rate: settings.op_rate,
+                push_perturbation: settings.push_perturbation,
             };
             system.structural_mutate_with_rng(rng, &structural_config);
         }
 
-        // Reconstruct source from mutated system
-        self.source_code = system.to_source();
+        // Reconstruct source from mutated system, leaving a frozen grammar verbatim
+        // and locked rules as they were
+        if settings.grammar || settings.structure {
+            let reconstructed = Self::reconstruct_source(&self.source_code, &system);
+            self.source_code = restore_locked(&self.source_code, reconstructed, &self.locked);
+        }
 
         // Mutate finalization code if present
-        if !self.finalization_code.trim().is_empty()
+        if settings.grammar
+            && !self.finalization_code.trim().is_empty()
             && let Ok(mut fin_system) = System::from_source(&self.finalization_code)
         {
             fin_system.mutate_with_rng(rng, &mutation_config);
-            self.finalization_code = fin_system.to_source();
+            let reconstructed = Self::reconstruct_source(&self.finalization_code, &fin_system);
+            self.finalization_code =
+                restore_locked(&self.finalization_code, reconstructed, &self.locked);
         }
 
         // Ensure materials map covers all material IDs referenced in source
@@ -334,9 +757,22 @@ impl Genotype for PlantGenotype {
         }
 
         // Mutate materials
-        self.mutate_materials(rng, rate);
+        if settings.materials {
+            self.mutate_materials(rng, rate);
+        }
 
-        // Occasionally mutate parameters
+        if settings.parameters {
+            self.mutate_parameters(rng, rate);
+        }
+
+        // Mutate seed for different stochastic outcomes
+        if settings.seed && rng.random::<f32>() < rate {
+            self.seed = rng.random::<u64>();
+        }
+    }
+
+    /// Occasionally nudges the turtle parameters.
+    fn mutate_parameters<R: Rng>(&mut self, rng: &mut R, rate: f32) {
         if rng.random::<f32>() < rate * 0.3 {
             self.angle = (self.angle + (rng.random::<f32>() - 0.5) * 10.0).clamp(5.0, 90.0);
         }
@@ -359,11 +795,13 @@ impl Genotype for PlantGenotype {
                 *component += (rng.random::<f32>() - 0.5) * 0.3;
             }
         }
+    }
+}
 
-        // Mutate seed for different stochastic outcomes
-        if rng.random::<f32>() < rate {
-            self.seed = rng.random::<u64>();
-        }
+#[cfg(feature = "nursery")]
+impl Genotype for PlantGenotype {
+    fn mutate<R: Rng>(&mut self, rng: &mut R, rate: f32) {
+        self.mutate_with(rng, rate, &MutationSettings::default());
     }
 
     fn crossover<R: Rng>(&self, other: &Self, rng: &mut R) -> Self {
@@ -389,8 +827,12 @@ impl Genotype for PlantGenotype {
             Err(_) => return self.clone(),
         };
 
-        // Reconstruct source from offspring
-        let source_code = offspring_system.to_source();
+        // Reconstruct source from offspring, keeping this parent's locked rules
+        let source_code = restore_locked(
+            &self.source_code,
+            Self::reconstruct_source(&self.source_code, &offspring_system),
+            &self.locked,
+        );
 
         // Blend parameters
         let blend = rng.random::<f32>();
@@ -423,11 +865,13 @@ impl Genotype for PlantGenotype {
                 (None, None) => None,
             },
             seed: rng.random::<u64>(),
+            clock: self.clock * blend + other.clock * inv_blend,
             prop_mappings: if rng.random::<bool>() {
                 self.prop_mappings.clone()
             } else {
                 other.prop_mappings.clone()
             },
+            locked: self.locked.clone(),
         }
     }
 }
@@ -446,6 +890,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "nursery")]
     fn test_mutate_preserves_structure() {
         let genotype = PlantGenotype::new("omega: F\nF -> F [ + F ] F".to_string());
         let mut mutated = genotype.clone();
@@ -458,6 +903,95 @@ mod tests {
     }
 
     #[test]
+    fn test_mutate_with_frozen_grammar() {
+        let source = "#define len 2.0\nomega: F(len)\nF(x) -> F(x) [ + F(x) ] F(x)";
+        let mut genotype = PlantGenotype::new(source.to_string());
+        genotype
+            .materials
+            .insert(0, SerializableMaterial::default());
+        let settings = MutationSettings {
+            grammar: false,
+            structure: false,
+            parameters: false,
+            seed: false,
+            ..MutationSettings::default()
+        };
+
+        let mut rng = Pcg64::seed_from_u64(42);
+        let mut mutated = genotype.clone();
+        for _ in 0..20 {
+            mutated.mutate_with(&mut rng, 1.0, &settings);
+        }
+        assert_eq!(mutated.source_code, source);
+        assert_eq!(mutated.angle, genotype.angle);
+        assert_eq!(mutated.seed, genotype.seed);
+        assert_ne!(mutated.materials[&0].base_color, [1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_from_text() {
+        let template = PlantGenotype::new("omega: F".to_string()).with_seed(9);
+
+        let grammar = PlantGenotype::from_text(
+            "omega: A\np1: A -> F A\n/// DECOMPOSITION ///\np1: A -> F",
+            &template,
+        )
+        .unwrap();
+        assert_eq!(grammar.source_code, "omega: A\np1: A -> F A");
+        assert_eq!(grammar.finalization_code, "p1: A -> F");
+        assert_eq!(grammar.seed, 9);
+
+        let json = serde_json::to_string(&grammar).unwrap();
+        let from_json = PlantGenotype::from_text(&json, &template).unwrap();
+        assert_eq!(from_json.source_code, grammar.source_code);
+
+        assert!(PlantGenotype::from_text("{ not json", &template).is_err());
+        assert!(PlantGenotype::from_text("omega: A((", &template).is_err());
+    }
+
+    #[test]
+    fn test_rule_key() {
+        assert_eq!(rule_key("omega: A(1)").as_deref(), Some(AXIOM_KEY));
+        assert_eq!(rule_key("p1: A -> F A").as_deref(), Some("A"));
+        assert_eq!(rule_key("F -> F F").as_deref(), Some("F"));
+        assert_eq!(rule_key("p2: A(t) : t > 0 -> F").as_deref(), Some("A"));
+        assert_eq!(rule_key("A(s) : 0.5 -> F").as_deref(), Some("A"));
+        assert_eq!(rule_key("A:0.3->F").as_deref(), Some("A"));
+        assert_eq!(rule_key("p3: B(x) < A > C -> B(x)").as_deref(), Some("A"));
+        assert_eq!(rule_key("#define n 5"), None);
+        assert_eq!(rule_key("// A -> B"), None);
+        assert_eq!(
+            rule_keys("omega: A\np1: A -> B\np2: B -> A\np3: A -> A"),
+            vec!["omega", "A", "B"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "nursery")]
+    fn test_mutate_keeps_locked_rules() {
+        let source = "omega: A\np1: A -> F [ + A ] [ - A ] B\np2: B -> F B";
+        let mut genotype = PlantGenotype::new(source.to_string());
+        genotype.locked = vec![AXIOM_KEY.to_string(), "A".to_string()];
+        let locked_lines: Vec<&str> = source.lines().take(2).collect();
+
+        let mut rng = Pcg64::seed_from_u64(7);
+        for _ in 0..50 {
+            let mut mutated = genotype.clone();
+            mutated.mutate(&mut rng, 1.0);
+            for line in &locked_lines {
+                assert!(
+                    mutated.source_code.lines().any(|l| l == *line),
+                    "Lost locked line '{}' in:\n{}",
+                    line,
+                    mutated.source_code
+                );
+            }
+            assert!(mutated.parse().is_some());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "nursery")]
     fn test_crossover_produces_valid_offspring() {
         let parent_a = PlantGenotype::new("omega: A\nA -> A B".to_string());
         let parent_b = PlantGenotype::new("omega: A\nA -> A A".to_string());
@@ -518,4 +1052,37 @@ mod tests {
             reconstructed
         );
     }
+
+    #[test]
+    fn test_merge_source_keeps_labels_and_comments() {
+        let original = "// Tree\n#define len 2\nomega: A\n\n\
+                        // Trunk grows\np1: A -> F(len) [+A] A // main axis\n\
+                        // Side shoot\np2: B -> F\np3: C -> B";
+        // Mutated len and p1, dropped p2, added a second C rule
+        let regenerated = "#define len 2.5\nomega: A\nA -> F(len) [-A] A\nC -> B\nC -> F";
+        assert_eq!(
+            merge_source(original, regenerated),
+            "// Tree\n#define len 2.5\nomega: A\n\n\
+             // Trunk grows\np1: A -> F(len) [-A] A // main axis\n\
+             p3: C -> B\nC -> F"
+        );
+    }
+
+    #[test]
+    fn test_merge_source_keeps_unchanged_lines_verbatim() {
+        let original = "#define len 2\nomega:  A\np1: A  ->  F(len)  A";
+        let regenerated = "#define len 2.0\nomega: A\nA -> F(len) A";
+        assert_eq!(merge_source(original, regenerated), original);
+    }
+
+    #[test]
+    fn test_reconstruct_source_roundtrip() {
+        let source = "// Binary tree\n#define a 30\nomega: A\n// Branch\np1: A -> F [+(a) A] [-(a) A] // fork";
+        let system = System::from_source(source).unwrap();
+        let reconstructed = PlantGenotype::reconstruct_source(source, &system);
+        assert!(reconstructed.contains("// Binary tree"));
+        assert!(reconstructed.contains("// Branch\np1: A ->"));
+        assert!(reconstructed.contains("// fork"));
+        assert!(System::from_source(&reconstructed).is_ok());
+    }
 }
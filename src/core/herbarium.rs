@@ -0,0 +1,235 @@
+//! Herbarium: a persistent gallery of starred plants.
+//!
+//! Each entry stores the plant's `PlantGenotype` and a small thumbnail, rendered
+//! offscreen by [`crate::visuals::thumbnail`] after the plant is starred.
+//! Thumbnails are kept as DEFLATE-compressed RGBA encoded in base64, so the
+//! whole collection persists as one JSON document through [`crate::core::storage`].
+
+use crate::core::genotype::PlantGenotype;
+use crate::core::storage::{load_setting, save_setting};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Storage key for the collection.
+const HERBARIUM_KEY: &str = "herbarium.json";
+
+/// Maximum number of entries, keeping the collection within `localStorage` quotas.
+pub const MAX_HERBARIUM_ENTRIES: usize = 100;
+
+/// Edge length of the square thumbnails, in pixels.
+pub const THUMBNAIL_SIZE: usize = 96;
+
+/// Upper bound on an inflated thumbnail, rejecting corrupted data early.
+const MAX_THUMBNAIL_BYTES: usize = THUMBNAIL_SIZE * THUMBNAIL_SIZE * 4;
+
+/// A starred plant.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HerbariumEntry {
+    /// Stable identifier, unique within the collection.
+    pub id: u64,
+    pub name: String,
+    /// Local wall-clock time the plant was starred, for display.
+    pub starred_at: String,
+    pub genotype: PlantGenotype,
+    /// Encoded thumbnail (see [`encode_thumbnail`]), once it has been rendered.
+    #[serde(default)]
+    pub thumbnail: Option<String>,
+}
+
+/// The starred plants.
+#[derive(Resource)]
+pub struct Herbarium {
+    pub entries: Vec<HerbariumEntry>,
+    /// Whether the gallery window is shown.
+    pub open: bool,
+    next_id: u64,
+}
+
+impl Default for Herbarium {
+    /// Loads the entries saved by a previous session.
+    fn default() -> Self {
+        let entries: Vec<HerbariumEntry> = load_setting(HERBARIUM_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Self::with_entries(entries)
+    }
+}
+
+impl Herbarium {
+    /// An empty collection, ignoring saved entries.
+    pub fn empty() -> Self {
+        Self::with_entries(Vec::new())
+    }
+
+    fn with_entries(entries: Vec<HerbariumEntry>) -> Self {
+        let next_id = entries.iter().map(|e| e.id + 1).max().unwrap_or(0);
+        Self {
+            entries,
+            open: false,
+            next_id,
+        }
+    }
+
+    /// Persists the collection for future sessions.
+    pub fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string(&self.entries)
+            .map_err(|e| format!("Failed to serialize herbarium: {}", e))?;
+        save_setting(HERBARIUM_KEY, &json)
+    }
+
+    /// Stars a plant. Returns the new entry's id.
+    pub fn star(&mut self, name: String, genotype: PlantGenotype) -> Result<u64, String> {
+        if self.entries.len() >= MAX_HERBARIUM_ENTRIES {
+            return Err(format!(
+                "The herbarium is full ({} plants)",
+                MAX_HERBARIUM_ENTRIES
+            ));
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.entries.push(HerbariumEntry {
+            id,
+            name,
+            starred_at: chrono::Local::now().format("%Y-%m-%d %H:%M").to_string(),
+            genotype,
+            thumbnail: None,
+        });
+        Ok(id)
+    }
+
+    /// Removes an entry. Returns `false` if no entry has this id.
+    pub fn remove(&mut self, id: u64) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|entry| entry.id != id);
+        self.entries.len() != before
+    }
+
+    pub fn get(&self, id: u64) -> Option<&HerbariumEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    /// Attaches an encoded thumbnail to an entry, if it still exists.
+    pub fn set_thumbnail(&mut self, id: u64, thumbnail: String) {
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.id == id) {
+            entry.thumbnail = Some(thumbnail);
+        }
+    }
+}
+
+/// Box-filters the centered square of an RGBA image down to a
+/// `THUMBNAIL_SIZE` × `THUMBNAIL_SIZE` thumbnail.
+pub fn downsample_center(rgba: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let side = width.min(height);
+    let mut out = vec![0u8; THUMBNAIL_SIZE * THUMBNAIL_SIZE * 4];
+    if side == 0 || rgba.len() < width * height * 4 {
+        return out;
+    }
+    let (x0, y0) = ((width - side) / 2, (height - side) / 2);
+
+    for ty in 0..THUMBNAIL_SIZE {
+        let (sy0, sy1) = (
+            ty * side / THUMBNAIL_SIZE,
+            ((ty + 1) * side / THUMBNAIL_SIZE),
+        );
+        for tx in 0..THUMBNAIL_SIZE {
+            let (sx0, sx1) = (
+                tx * side / THUMBNAIL_SIZE,
+                ((tx + 1) * side / THUMBNAIL_SIZE),
+            );
+            // Sources smaller than the thumbnail repeat pixels instead of averaging
+            let (sy1, sx1) = (sy1.max(sy0 + 1), sx1.max(sx0 + 1));
+            let mut sum = [0u32; 4];
+            for sy in sy0..sy1 {
+                let row = (y0 + sy) * width;
+                for sx in sx0..sx1 {
+                    let i = (row + x0 + sx) * 4;
+                    for (c, total) in sum.iter_mut().enumerate() {
+                        *total += rgba[i + c] as u32;
+                    }
+                }
+            }
+            let count = ((sy1 - sy0) * (sx1 - sx0)) as u32;
+            let o = (ty * THUMBNAIL_SIZE + tx) * 4;
+            for c in 0..4 {
+                out[o + c] = (sum[c] / count) as u8;
+            }
+        }
+    }
+    out
+}
+
+/// Compresses thumbnail pixels for storage.
+pub fn encode_thumbnail(rgba: &[u8]) -> String {
+    STANDARD.encode(miniz_oxide::deflate::compress_to_vec(rgba, 6))
+}
+
+/// Decodes a stored thumbnail back to `THUMBNAIL_SIZE`² RGBA pixels.
+pub fn decode_thumbnail(encoded: &str) -> Result<Vec<u8>, String> {
+    let compressed = STANDARD
+        .decode(encoded)
+        .map_err(|e| format!("Invalid thumbnail encoding: {}", e))?;
+    let rgba = miniz_oxide::inflate::decompress_to_vec_with_limit(&compressed, MAX_THUMBNAIL_BYTES)
+        .map_err(|e| format!("Invalid thumbnail data: {:?}", e))?;
+    if rgba.len() != MAX_THUMBNAIL_BYTES {
+        return Err(format!("Thumbnail has {} bytes", rgba.len()));
+    }
+    Ok(rgba)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_downsample_center_crops_and_averages() {
+        // 200×100 image: red left margin, green centered square, blue right margin
+        let (width, height) = (200, 100);
+        let mut rgba = Vec::with_capacity(width * height * 4);
+        for _ in 0..height {
+            for x in 0..width {
+                let pixel = match x {
+                    0..50 => [255, 0, 0, 255],
+                    50..150 => [0, 255, 0, 255],
+                    _ => [0, 0, 255, 255],
+                };
+                rgba.extend_from_slice(&pixel);
+            }
+        }
+        let thumbnail = downsample_center(&rgba, width, height);
+        assert_eq!(thumbnail.len(), THUMBNAIL_SIZE * THUMBNAIL_SIZE * 4);
+        assert!(thumbnail.chunks(4).all(|p| p == [0, 255, 0, 255]));
+
+        let decoded = decode_thumbnail(&encode_thumbnail(&thumbnail)).unwrap();
+        assert_eq!(decoded, thumbnail);
+        assert!(decode_thumbnail(&encode_thumbnail(&[1, 2, 3])).is_err());
+        assert!(decode_thumbnail("not base64!").is_err());
+    }
+
+    #[test]
+    fn test_star_and_remove() {
+        let mut herbarium = Herbarium::empty();
+        let genotype = PlantGenotype::new("omega: F".to_string());
+        let a = herbarium.star("A".into(), genotype.clone()).unwrap();
+        let b = herbarium.star("B".into(), genotype.clone()).unwrap();
+        assert_ne!(a, b);
+
+        herbarium.set_thumbnail(b, "thumb".into());
+        assert_eq!(
+            herbarium.get(b).unwrap().thumbnail.as_deref(),
+            Some("thumb")
+        );
+        assert!(herbarium.remove(a));
+        assert!(!herbarium.remove(a));
+
+        // Ids are not reused after reloading
+        let reloaded = Herbarium::with_entries(herbarium.entries.clone());
+        assert_eq!(reloaded.next_id, b + 1);
+
+        for i in herbarium.entries.len()..MAX_HERBARIUM_ENTRIES {
+            herbarium.star(i.to_string(), genotype.clone()).unwrap();
+        }
+        assert!(herbarium.star("Full".into(), genotype).is_err());
+    }
+}
@@ -0,0 +1,170 @@
+//! History of successful derivations, for rolling back and comparing grammars.
+
+use crate::core::config::{LSystemConfig, join_source_code};
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+/// Number of derivations kept before the oldest is dropped.
+pub const MAX_HISTORY_ENTRIES: usize = 50;
+
+/// Snapshot of the editor inputs that produced a derivation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryEntry {
+    pub source_code: String,
+    pub finalization_code: String,
+    pub iterations: usize,
+    pub default_angle: f32,
+    pub step_size: f32,
+    pub default_width: f32,
+    pub tropism: Option<Vec3>,
+    pub elasticity: f32,
+    pub seed: u64,
+    /// Local wall-clock time the derivation finished, for display.
+    pub timestamp: String,
+}
+
+impl HistoryEntry {
+    /// Captures the current editor inputs.
+    pub fn from_config(config: &LSystemConfig) -> Self {
+        Self {
+            source_code: config.source_code.clone(),
+            finalization_code: config.finalization_code.clone(),
+            iterations: config.iterations,
+            default_angle: config.default_angle,
+            step_size: config.step_size,
+            default_width: config.default_width,
+            tropism: config.tropism,
+            elasticity: config.elasticity,
+            seed: config.seed,
+            timestamp: String::new(),
+        }
+    }
+
+    /// Writes this snapshot back into the editor config and requests a recompile.
+    /// A locked seed is kept.
+    pub fn apply_to(&self, config: &mut LSystemConfig) {
+        config.source_code = self.source_code.clone();
+        config.finalization_code = self.finalization_code.clone();
+        config.iterations = self.iterations;
+        config.default_angle = self.default_angle;
+        config.step_size = self.step_size;
+        config.default_width = self.default_width;
+        config.tropism = self.tropism;
+        config.elasticity = self.elasticity;
+        if !config.seed_locked {
+            config.seed = self.seed;
+        }
+        config.recompile_requested = true;
+    }
+
+    /// Full grammar text (growth and finalization) used for diffing.
+    pub fn grammar(&self) -> String {
+        join_source_code(&self.source_code, &self.finalization_code)
+    }
+
+    /// Human-readable list of parameter changes from `self` to `other`.
+    pub fn parameter_changes(&self, other: &HistoryEntry) -> Vec<String> {
+        let mut changes = Vec::new();
+        if self.iterations != other.iterations {
+            changes.push(format!(
+                "Iterations: {} → {}",
+                self.iterations, other.iterations
+            ));
+        }
+        if self.seed != other.seed {
+            changes.push(format!("Seed: {} → {}", self.seed, other.seed));
+        }
+        for (name, a, b) in [
+            ("Angle", self.default_angle, other.default_angle),
+            ("Step", self.step_size, other.step_size),
+            ("Width", self.default_width, other.default_width),
+            ("Elasticity", self.elasticity, other.elasticity),
+        ] {
+            if a != b {
+                changes.push(format!("{}: {} → {}", name, a, b));
+            }
+        }
+        if self.tropism != other.tropism {
+            changes.push(format!("Tropism: {:?} → {:?}", self.tropism, other.tropism));
+        }
+        changes
+    }
+
+    /// True if both entries would produce the same plant.
+    fn same_inputs(&self, other: &HistoryEntry) -> bool {
+        Self {
+            timestamp: String::new(),
+            ..self.clone()
+        } == Self {
+            timestamp: String::new(),
+            ..other.clone()
+        }
+    }
+}
+
+/// Ring buffer of recent successful derivations, newest last.
+#[derive(Resource, Default)]
+pub struct DerivationHistory {
+    pub entries: VecDeque<HistoryEntry>,
+    /// Sequence number of the oldest entry, so labels stay stable as old entries drop.
+    pub first_index: usize,
+    /// Sequence number of the diff view's base ("A") entry.
+    pub diff_from: Option<usize>,
+    /// Sequence number of the diff view's target ("B") entry.
+    pub diff_to: Option<usize>,
+}
+
+impl DerivationHistory {
+    /// Records a successful derivation. Consecutive identical inputs are recorded once.
+    pub fn record(&mut self, mut entry: HistoryEntry) {
+        if self
+            .entries
+            .back()
+            .is_some_and(|last| last.same_inputs(&entry))
+        {
+            return;
+        }
+        entry.timestamp = chrono::Local::now().format("%H:%M:%S").to_string();
+        self.entries.push_back(entry);
+        while self.entries.len() > MAX_HISTORY_ENTRIES {
+            self.entries.pop_front();
+            self.first_index += 1;
+        }
+    }
+
+    /// Looks up an entry by its sequence number.
+    pub fn get(&self, index: usize) -> Option<&HistoryEntry> {
+        self.entries.get(index.checked_sub(self.first_index)?)
+    }
+
+    /// Iterates (sequence number, entry) pairs, oldest first.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (usize, &HistoryEntry)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (self.first_index + i, entry))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_ring_buffer() {
+        let mut history = DerivationHistory::default();
+        let mut entry = HistoryEntry::from_config(&LSystemConfig::default());
+        history.record(entry.clone());
+        history.record(entry.clone());
+        assert_eq!(history.entries.len(), 1, "Duplicate inputs recorded once");
+
+        for i in 0..MAX_HISTORY_ENTRIES + 5 {
+            entry.seed = i as u64 + 1000;
+            history.record(entry.clone());
+        }
+        assert_eq!(history.entries.len(), MAX_HISTORY_ENTRIES);
+        assert_eq!(history.first_index, 6);
+        assert!(history.get(5).is_none());
+        assert_eq!(history.get(6).map(|e| e.seed), Some(1005));
+    }
+}
@@ -0,0 +1,217 @@
+//! `#include "name"` support for sharing rule libraries between grammars.
+//!
+//! Includes are expanded textually before parsing. Names are looked up first in
+//! the [`IncludeLibrary`]'s named virtual buffers (the only source on wasm) and,
+//! on native builds, then as a file path relative to the including file, or to
+//! the working directory for the editor grammar itself.
+
+use crate::core::storage::{load_setting, save_setting};
+use bevy::prelude::*;
+use std::collections::BTreeMap;
+
+/// Directive keyword recognized at the start of a line.
+pub const INCLUDE_DIRECTIVE: &str = "#include";
+
+/// Maximum nesting of includes, guarding against runaway recursion.
+pub const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Storage key for the virtual buffers.
+const LIBRARY_KEY: &str = "include_library.json";
+
+/// Named grammar snippets available to `#include`.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct IncludeLibrary {
+    pub buffers: BTreeMap<String, String>,
+}
+
+impl Default for IncludeLibrary {
+    /// Loads the buffers saved by a previous session.
+    fn default() -> Self {
+        let buffers = load_setting(LIBRARY_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Self { buffers }
+    }
+}
+
+impl IncludeLibrary {
+    /// An empty library, ignoring saved buffers.
+    pub fn empty() -> Self {
+        Self {
+            buffers: BTreeMap::new(),
+        }
+    }
+
+    /// Persists the buffers for future sessions.
+    pub fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string(&self.buffers)
+            .map_err(|e| format!("Failed to serialize include library: {}", e))?;
+        save_setting(LIBRARY_KEY, &json)
+    }
+
+    /// Expands all `#include` lines in `source`, recursively.
+    pub fn expand(&self, source: &str) -> Result<String, String> {
+        if !source.contains(INCLUDE_DIRECTIVE) {
+            return Ok(source.to_string());
+        }
+        let mut out = String::with_capacity(source.len());
+        let mut stack = Vec::new();
+        self.expand_into(source, None, &mut stack, &mut out)?;
+        Ok(out)
+    }
+
+    fn expand_into(
+        &self,
+        source: &str,
+        origin: Option<&IncludeOrigin>,
+        stack: &mut Vec<String>,
+        out: &mut String,
+    ) -> Result<(), String> {
+        for (i, line) in source.lines().enumerate() {
+            let Some(name) = parse_include(line.trim()) else {
+                out.push_str(line);
+                out.push('\n');
+                continue;
+            };
+            let location = match origin {
+                Some(origin) => format!("{} line {}", origin.name, i + 1),
+                None => format!("Line {}", i + 1),
+            };
+            let name = name.map_err(|e| format!("{}: {}", location, e))?;
+
+            if stack.len() >= MAX_INCLUDE_DEPTH {
+                return Err(format!(
+                    "{}: Includes nested deeper than {}",
+                    location, MAX_INCLUDE_DEPTH
+                ));
+            }
+            let (text, included) = self
+                .load(&name, origin)
+                .map_err(|e| format!("{}: {}", location, e))?;
+            if stack.contains(&included.name) {
+                return Err(format!(
+                    "{}: Circular include of \"{}\"",
+                    location, included.name
+                ));
+            }
+
+            stack.push(included.name.clone());
+            self.expand_into(&text, Some(&included), stack, out)?;
+            stack.pop();
+        }
+        Ok(())
+    }
+
+    /// Looks up an include by name.
+    fn load(
+        &self,
+        name: &str,
+        origin: Option<&IncludeOrigin>,
+    ) -> Result<(String, IncludeOrigin), String> {
+        if let Some(text) = self.buffers.get(name) {
+            return Ok((
+                text.clone(),
+                IncludeOrigin {
+                    name: name.to_string(),
+                    #[cfg(not(target_arch = "wasm32"))]
+                    dir: None,
+                },
+            ));
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let base = origin.and_then(|origin| origin.dir.clone());
+            let path = match base {
+                Some(dir) => dir.join(name),
+                None => std::path::PathBuf::from(name),
+            };
+            if let Ok(text) = std::fs::read_to_string(&path) {
+                let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+                return Ok((
+                    text,
+                    IncludeOrigin {
+                        name: canonical.display().to_string(),
+                        dir: canonical.parent().map(|dir| dir.to_path_buf()),
+                    },
+                ));
+            }
+        }
+
+        // Wasm builds have no file system; only virtual buffers resolve
+        #[cfg(target_arch = "wasm32")]
+        let _ = origin;
+
+        Err(format!("Include \"{}\" not found", name))
+    }
+}
+
+/// Identifies an expanded include, for error messages, cycle detection,
+/// and resolving nested relative paths.
+struct IncludeOrigin {
+    name: String,
+    #[cfg(not(target_arch = "wasm32"))]
+    dir: Option<std::path::PathBuf>,
+}
+
+/// Parses an `#include "name"` line. Returns `None` for other lines.
+fn parse_include(line: &str) -> Option<Result<String, String>> {
+    let rest = line.strip_prefix(INCLUDE_DIRECTIVE)?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let name = rest
+        .trim()
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .filter(|name| !name.is_empty());
+    Some(
+        name.map(str::to_string)
+            .ok_or_else(|| "Expected #include \"name\"".to_string()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library(entries: &[(&str, &str)]) -> IncludeLibrary {
+        IncludeLibrary {
+            buffers: entries
+                .iter()
+                .map(|(name, text)| (name.to_string(), text.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_expand_nested_buffers() {
+        let lib = library(&[
+            ("leaf.ls", "#include \"consts.ls\"\nL -> [ + F ] [ - F ]"),
+            ("consts.ls", "#define LEAF 1"),
+        ]);
+        let expanded = lib
+            .expand("omega: A\n#include \"leaf.ls\"\np1: A -> F L A")
+            .unwrap();
+        assert_eq!(
+            expanded,
+            "omega: A\n#define LEAF 1\nL -> [ + F ] [ - F ]\np1: A -> F L A\n"
+        );
+    }
+
+    #[test]
+    fn test_expand_errors() {
+        let lib = library(&[("a.ls", "#include \"b.ls\""), ("b.ls", "#include \"a.ls\"")]);
+        let err = lib.expand("#include \"a.ls\"").unwrap_err();
+        assert!(err.contains("Circular include"), "{}", err);
+
+        let err = lib.expand("omega: A\n#include \"missing.ls\"").unwrap_err();
+        assert!(err.starts_with("Line 2:"), "{}", err);
+        assert!(err.contains("not found"), "{}", err);
+
+        assert!(lib.expand("#include leaf.ls").is_err());
+        // Other directives sharing the prefix are left alone
+        assert_eq!(lib.expand("#includes").unwrap(), "#includes\n");
+        assert_eq!(lib.expand("p1: A -> B").unwrap(), "p1: A -> B");
+    }
+}
@@ -0,0 +1,382 @@
+//! Rebindable keyboard shortcuts.
+//!
+//! Bindings are persisted through `core::storage` as JSON and restored on startup.
+//! Chords with Ctrl or Alt fire even while a text field has focus (so Ctrl+Enter
+//! recompiles from inside the grammar editor); plain keys only fire in the viewport.
+
+use crate::core::storage::{load_setting, save_setting};
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Storage key for persisted bindings.
+const STORAGE_KEY: &str = "keybindings.json";
+
+/// Actions that can be triggered from the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ShortcutAction {
+    Recompile,
+    IterationUp,
+    IterationDown,
+    Screenshot,
+    FramePlant,
+    ToggleNursery,
+    ToggleWireframe,
+}
+
+impl ShortcutAction {
+    #[cfg(feature = "nursery")]
+    pub const ALL: &'static [ShortcutAction] = &[
+        ShortcutAction::Recompile,
+        ShortcutAction::IterationUp,
+        ShortcutAction::IterationDown,
+        ShortcutAction::Screenshot,
+        ShortcutAction::FramePlant,
+        ShortcutAction::ToggleNursery,
+        ShortcutAction::ToggleWireframe,
+    ];
+    /// Without the nursery the toggle is neither listed nor dispatched, but the
+    /// variant stays so saved bindings from full builds still load.
+    #[cfg(not(feature = "nursery"))]
+    pub const ALL: &'static [ShortcutAction] = &[
+        ShortcutAction::Recompile,
+        ShortcutAction::IterationUp,
+        ShortcutAction::IterationDown,
+        ShortcutAction::Screenshot,
+        ShortcutAction::FramePlant,
+        ShortcutAction::ToggleWireframe,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ShortcutAction::Recompile => "Recompile",
+            ShortcutAction::IterationUp => "Iterations +1",
+            ShortcutAction::IterationDown => "Iterations -1",
+            ShortcutAction::Screenshot => "Screenshot",
+            ShortcutAction::FramePlant => "Frame Plant",
+            ShortcutAction::ToggleNursery => "Toggle Nursery",
+            ShortcutAction::ToggleWireframe => "Toggle Wireframe",
+        }
+    }
+
+    fn default_chord(&self) -> KeyChord {
+        match self {
+            ShortcutAction::Recompile => KeyChord::ctrl(KeyCode::Enter),
+            ShortcutAction::IterationUp => KeyChord::plain(KeyCode::PageUp),
+            ShortcutAction::IterationDown => KeyChord::plain(KeyCode::PageDown),
+            ShortcutAction::Screenshot => KeyChord::plain(KeyCode::F12),
+            ShortcutAction::FramePlant => KeyChord::plain(KeyCode::KeyF),
+            ShortcutAction::ToggleNursery => KeyChord::plain(KeyCode::KeyN),
+            ShortcutAction::ToggleWireframe => KeyChord::plain(KeyCode::KeyW),
+        }
+    }
+}
+
+/// Keys that can be bound, with their display/storage names.
+const BINDABLE_KEYS: &[(KeyCode, &str)] = &[
+    (KeyCode::KeyA, "A"),
+    (KeyCode::KeyB, "B"),
+    (KeyCode::KeyC, "C"),
+    (KeyCode::KeyD, "D"),
+    (KeyCode::KeyE, "E"),
+    (KeyCode::KeyF, "F"),
+    (KeyCode::KeyG, "G"),
+    (KeyCode::KeyH, "H"),
+    (KeyCode::KeyI, "I"),
+    (KeyCode::KeyJ, "J"),
+    (KeyCode::KeyK, "K"),
+    (KeyCode::KeyL, "L"),
+    (KeyCode::KeyM, "M"),
+    (KeyCode::KeyN, "N"),
+    (KeyCode::KeyO, "O"),
+    (KeyCode::KeyP, "P"),
+    (KeyCode::KeyQ, "Q"),
+    (KeyCode::KeyR, "R"),
+    (KeyCode::KeyS, "S"),
+    (KeyCode::KeyT, "T"),
+    (KeyCode::KeyU, "U"),
+    (KeyCode::KeyV, "V"),
+    (KeyCode::KeyW, "W"),
+    (KeyCode::KeyX, "X"),
+    (KeyCode::KeyY, "Y"),
+    (KeyCode::KeyZ, "Z"),
+    (KeyCode::Digit0, "0"),
+    (KeyCode::Digit1, "1"),
+    (KeyCode::Digit2, "2"),
+    (KeyCode::Digit3, "3"),
+    (KeyCode::Digit4, "4"),
+    (KeyCode::Digit5, "5"),
+    (KeyCode::Digit6, "6"),
+    (KeyCode::Digit7, "7"),
+    (KeyCode::Digit8, "8"),
+    (KeyCode::Digit9, "9"),
+    (KeyCode::F1, "F1"),
+    (KeyCode::F2, "F2"),
+    (KeyCode::F3, "F3"),
+    (KeyCode::F4, "F4"),
+    (KeyCode::F5, "F5"),
+    (KeyCode::F6, "F6"),
+    (KeyCode::F7, "F7"),
+    (KeyCode::F8, "F8"),
+    (KeyCode::F9, "F9"),
+    (KeyCode::F10, "F10"),
+    (KeyCode::F11, "F11"),
+    (KeyCode::F12, "F12"),
+    (KeyCode::Enter, "Enter"),
+    (KeyCode::Space, "Space"),
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::Backspace, "Backspace"),
+    (KeyCode::Delete, "Delete"),
+    (KeyCode::Insert, "Insert"),
+    (KeyCode::Home, "Home"),
+    (KeyCode::End, "End"),
+    (KeyCode::PageUp, "PageUp"),
+    (KeyCode::PageDown, "PageDown"),
+    (KeyCode::ArrowUp, "Up"),
+    (KeyCode::ArrowDown, "Down"),
+    (KeyCode::ArrowLeft, "Left"),
+    (KeyCode::ArrowRight, "Right"),
+    (KeyCode::Minus, "-"),
+    (KeyCode::Equal, "="),
+    (KeyCode::BracketLeft, "["),
+    (KeyCode::BracketRight, "]"),
+    (KeyCode::NumpadAdd, "Num+"),
+    (KeyCode::NumpadSubtract, "Num-"),
+    (KeyCode::NumpadEnter, "NumEnter"),
+];
+
+/// Returns the display name of a bindable key.
+pub fn key_name(key: KeyCode) -> Option<&'static str> {
+    BINDABLE_KEYS
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, name)| *name)
+}
+
+/// Parses a key from its display name.
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    BINDABLE_KEYS
+        .iter()
+        .find(|(_, n)| *n == name)
+        .map(|(k, _)| *k)
+}
+
+/// All keys that can be bound, in display order.
+pub fn bindable_keys() -> impl Iterator<Item = KeyCode> {
+    BINDABLE_KEYS.iter().map(|(k, _)| *k)
+}
+
+/// A key plus modifier state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyChord {
+    pub key: KeyCode,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl KeyChord {
+    pub fn plain(key: KeyCode) -> Self {
+        Self {
+            key,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    pub fn ctrl(key: KeyCode) -> Self {
+        Self {
+            ctrl: true,
+            ..Self::plain(key)
+        }
+    }
+
+    /// True if the chord uses Ctrl or Alt and may fire while typing in a text field.
+    pub fn is_modified(&self) -> bool {
+        self.ctrl || self.alt
+    }
+
+    /// Formats the chord as e.g. `Ctrl+Shift+Enter`.
+    pub fn label(&self) -> String {
+        let mut label = String::new();
+        if self.ctrl {
+            label.push_str("Ctrl+");
+        }
+        if self.alt {
+            label.push_str("Alt+");
+        }
+        if self.shift {
+            label.push_str("Shift+");
+        }
+        label.push_str(key_name(self.key).unwrap_or("?"));
+        label
+    }
+
+    /// Parses a chord written by [`KeyChord::label`].
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts: Vec<&str> = text.split('+').collect();
+        // A trailing "+" belongs to keys like "Num+"
+        if text.ends_with('+') && parts.len() >= 2 {
+            parts.pop();
+            let last = parts.pop()?;
+            let key = format!("{}+", last);
+            return Self::parse_parts(&parts, &key);
+        }
+        let key = parts.pop()?;
+        Self::parse_parts(&parts, key)
+    }
+
+    fn parse_parts(modifiers: &[&str], key: &str) -> Option<Self> {
+        let mut chord = Self::plain(key_from_name(key)?);
+        for modifier in modifiers {
+            match *modifier {
+                "Ctrl" => chord.ctrl = true,
+                "Alt" => chord.alt = true,
+                "Shift" => chord.shift = true,
+                _ => return None,
+            }
+        }
+        Some(chord)
+    }
+
+    /// True if the chord's key was just pressed with exactly its modifiers held.
+    pub fn just_pressed(&self, keys: &ButtonInput<KeyCode>) -> bool {
+        let ctrl = keys.any_pressed([
+            KeyCode::ControlLeft,
+            KeyCode::ControlRight,
+            KeyCode::SuperLeft,
+            KeyCode::SuperRight,
+        ]);
+        let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        let alt = keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+        keys.just_pressed(self.key) && ctrl == self.ctrl && shift == self.shift && alt == self.alt
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedBinding {
+    action: ShortcutAction,
+    chord: String,
+}
+
+/// Resource mapping shortcut actions to key chords.
+#[derive(Resource)]
+pub struct Keybindings {
+    pub bindings: HashMap<ShortcutAction, KeyChord>,
+    /// Action waiting for the next key press to be rebound (set by the settings UI).
+    pub rebinding: Option<ShortcutAction>,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        let mut bindings = Self::defaults();
+        if let Some(saved) = load_setting(STORAGE_KEY) {
+            bindings.apply_saved(&saved);
+        }
+        bindings
+    }
+}
+
+impl Keybindings {
+    /// The built-in bindings, ignoring anything persisted.
+    pub fn defaults() -> Self {
+        Self {
+            bindings: ShortcutAction::ALL
+                .iter()
+                .map(|action| (*action, action.default_chord()))
+                .collect(),
+            rebinding: None,
+        }
+    }
+
+    /// Returns the chord bound to an action.
+    pub fn chord(&self, action: ShortcutAction) -> Option<KeyChord> {
+        self.bindings.get(&action).copied()
+    }
+
+    /// Binds an action, unbinding any other action that used the same chord.
+    pub fn bind(&mut self, action: ShortcutAction, chord: KeyChord) {
+        self.bindings.retain(|a, c| *a == action || *c != chord);
+        self.bindings.insert(action, chord);
+    }
+
+    /// Serializes the bindings as JSON.
+    pub fn to_json(&self) -> String {
+        let saved: Vec<SavedBinding> = ShortcutAction::ALL
+            .iter()
+            .filter_map(|action| {
+                self.chord(*action).map(|chord| SavedBinding {
+                    action: *action,
+                    chord: chord.label(),
+                })
+            })
+            .collect();
+        serde_json::to_string_pretty(&saved).unwrap_or_default()
+    }
+
+    /// Applies bindings from JSON written by [`Keybindings::to_json`].
+    /// Unknown or malformed entries are skipped, keeping the current binding.
+    pub fn apply_saved(&mut self, json: &str) {
+        let Ok(saved) = serde_json::from_str::<Vec<SavedBinding>>(json) else {
+            warn!("Ignoring malformed keybindings file");
+            return;
+        };
+        for entry in saved {
+            if let Some(chord) = KeyChord::parse(&entry.chord) {
+                self.bind(entry.action, chord);
+            }
+        }
+    }
+
+    /// Persists the bindings for future sessions.
+    pub fn save(&self) -> Result<(), String> {
+        save_setting(STORAGE_KEY, &self.to_json())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chord_label_round_trip() {
+        for chord in [
+            KeyChord::ctrl(KeyCode::Enter),
+            KeyChord::plain(KeyCode::NumpadAdd),
+            KeyChord {
+                key: KeyCode::KeyS,
+                ctrl: true,
+                shift: true,
+                alt: true,
+            },
+        ] {
+            assert_eq!(KeyChord::parse(&chord.label()), Some(chord));
+        }
+        assert_eq!(KeyChord::parse("Hyper+F"), None);
+    }
+
+    #[test]
+    fn test_bind_steals_duplicate_chord() {
+        let mut bindings = Keybindings::defaults();
+        let f = KeyChord::plain(KeyCode::KeyF);
+        bindings.bind(ShortcutAction::Screenshot, f);
+        assert_eq!(bindings.chord(ShortcutAction::Screenshot), Some(f));
+        assert_eq!(bindings.chord(ShortcutAction::FramePlant), None);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut bindings = Keybindings::defaults();
+        bindings.bind(ShortcutAction::Recompile, KeyChord::plain(KeyCode::F5));
+        let json = bindings.to_json();
+
+        let mut restored = Keybindings::defaults();
+        restored.apply_saved(&json);
+        assert_eq!(
+            restored.chord(ShortcutAction::Recompile),
+            Some(KeyChord::plain(KeyCode::F5))
+        );
+        assert_eq!(restored.bindings.len(), ShortcutAction::ALL.len());
+    }
+}
@@ -0,0 +1,101 @@
+//! In-memory copy of recent log output.
+//!
+//! A tracing layer installed through `LogPlugin::custom_layer` keeps the last
+//! [`MAX_LOG_LINES`] events in a ring buffer, so logs can be attached to bug
+//! reports even on wasm, where there is no terminal to copy them from.
+
+use bevy::log::BoxedLayer;
+use bevy::log::tracing::field::{Field, Visit};
+use bevy::log::tracing::{Event, Level, Subscriber};
+use bevy::log::tracing_subscriber::Layer;
+use bevy::log::tracing_subscriber::layer::Context;
+use bevy::prelude::*;
+use std::collections::VecDeque;
+use std::fmt::{self, Write as _};
+use std::sync::Mutex;
+
+/// Number of log lines kept; older lines are dropped.
+pub const MAX_LOG_LINES: usize = 500;
+
+/// One captured log event.
+#[derive(Clone, Debug)]
+pub struct LogLine {
+    pub level: Level,
+    /// Module path the event was logged from.
+    pub target: String,
+    pub message: String,
+    /// Local wall-clock time, for display.
+    pub timestamp: String,
+}
+
+impl fmt::Display for LogLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:>5} {}: {}",
+            self.timestamp, self.level, self.target, self.message
+        )
+    }
+}
+
+static LOG_BUFFER: Mutex<VecDeque<LogLine>> = Mutex::new(VecDeque::new());
+
+/// `LogPlugin::custom_layer` hook that installs the capture layer.
+pub fn capture_layer(_app: &mut App) -> Option<BoxedLayer> {
+    Some(Box::new(CaptureLayer))
+}
+
+/// The captured lines, oldest first.
+pub fn recent_logs() -> Vec<LogLine> {
+    LOG_BUFFER
+        .lock()
+        .map(|buffer| buffer.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Drops all captured lines.
+pub fn clear_logs() {
+    if let Ok(mut buffer) = LOG_BUFFER.lock() {
+        buffer.clear();
+    }
+}
+
+/// Appends a line to the buffer, dropping the oldest when full.
+pub fn push_log(line: LogLine) {
+    if let Ok(mut buffer) = LOG_BUFFER.lock() {
+        if buffer.len() >= MAX_LOG_LINES {
+            buffer.pop_front();
+        }
+        buffer.push_back(line);
+    }
+}
+
+struct CaptureLayer;
+
+impl<S: Subscriber> Layer<S> for CaptureLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        push_log(LogLine {
+            level: *event.metadata().level(),
+            target: event.metadata().target().to_string(),
+            message: visitor.0,
+            timestamp: chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+        });
+    }
+}
+
+/// Formats the `message` field followed by any other fields as `name=value`.
+#[derive(Default)]
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let fields = std::mem::take(&mut self.0);
+            let _ = write!(self.0, "{:?}{}", value, fields);
+        } else {
+            let _ = write!(self.0, " {}={:?}", field.name(), value);
+        }
+    }
+}
@@ -1,3 +1,19 @@
+pub mod autosave;
+pub mod bug_report;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cli;
 pub mod config;
+pub mod files;
+pub mod garden;
 pub mod genotype;
+pub mod herbarium;
+pub mod history;
+pub mod includes;
+pub mod keybindings;
+pub mod log_capture;
+pub mod palettes;
 pub mod presets;
+pub mod scatter;
+pub mod share;
+pub mod storage;
+pub mod terrain;
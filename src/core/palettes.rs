@@ -0,0 +1,239 @@
+//! Material palettes: the material slots of a plant, saved under a name and
+//! applied independently of the grammar.
+//!
+//! A few palettes are bundled; palettes the user saves persist through
+//! [`crate::core::storage`], and any palette can be exported to or imported
+//! from a JSON file. Slots are stored like genotype materials (see
+//! [`SerializableMaterial`]), so procedural textures are not part of a palette:
+//! applying one keeps the texture already assigned to each slot.
+
+use crate::core::config::{MaterialSettingsMap, TextureType};
+use crate::core::genotype::SerializableMaterial;
+use crate::core::storage::{load_setting, save_setting};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Storage key for the saved palettes.
+const PALETTES_KEY: &str = "material_palettes.json";
+
+/// Material settings of every slot, by slot ID.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MaterialPalette {
+    pub name: String,
+    pub materials: BTreeMap<u8, SerializableMaterial>,
+}
+
+impl MaterialPalette {
+    /// Captures the current material slots.
+    pub fn from_settings(name: impl Into<String>, settings: &MaterialSettingsMap) -> Self {
+        Self {
+            name: name.into(),
+            materials: settings
+                .settings
+                .iter()
+                .map(|(&id, material)| (id, SerializableMaterial::from(material)))
+                .collect(),
+        }
+    }
+
+    /// Replaces the material slots with this palette, keeping each slot's
+    /// texture. Slots the palette lacks are removed.
+    pub fn apply(&self, settings: &mut MaterialSettingsMap) {
+        let textures: Vec<(u8, TextureType)> = settings
+            .settings
+            .iter()
+            .map(|(&id, material)| (id, material.texture))
+            .collect();
+        settings.settings.clear();
+        for (&id, material) in &self.materials {
+            let mut material = material.to_material_settings();
+            if let Some(&(_, texture)) = textures.iter().find(|(slot, _)| *slot == id) {
+                material.texture = texture;
+            }
+            settings.settings.insert(id, material);
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize palette: {}", e))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let palette: Self =
+            serde_json::from_str(json).map_err(|e| format!("Invalid palette file: {}", e))?;
+        if palette.materials.is_empty() {
+            return Err("Palette has no materials".to_string());
+        }
+        Ok(palette)
+    }
+
+    /// File name for exporting the palette, derived from its name.
+    pub fn filename(&self) -> String {
+        let stem: String = self
+            .name
+            .trim()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let stem = if stem.is_empty() { "palette" } else { &stem };
+        format!("{}.palette.json", stem)
+    }
+}
+
+fn material(
+    base_color: [f32; 3],
+    roughness: f32,
+    metallic: f32,
+    emission_color: [f32; 3],
+    emission_strength: f32,
+) -> SerializableMaterial {
+    SerializableMaterial {
+        base_color,
+        emission_color,
+        emission_strength,
+        roughness,
+        metallic,
+        uv_scale: 1.0,
+    }
+}
+
+/// The palettes shipped with the app: slot 0 for wood, 1 for foliage, and 2
+/// for flowers or fruit.
+pub fn bundled_palettes() -> Vec<MaterialPalette> {
+    let palette = |name: &str, slots: [SerializableMaterial; 3]| MaterialPalette {
+        name: name.to_string(),
+        materials: (0u8..).zip(slots).collect(),
+    };
+    vec![
+        palette(
+            "Bark & Foliage",
+            [
+                material([0.36, 0.24, 0.15], 0.9, 0.0, [0.0; 3], 0.0),
+                material([0.22, 0.48, 0.16], 0.6, 0.0, [0.0; 3], 0.0),
+                material([0.92, 0.86, 0.62], 0.5, 0.0, [0.0; 3], 0.0),
+            ],
+        ),
+        palette(
+            "Neon Tech-Tree",
+            [
+                material([0.08, 0.08, 0.1], 0.3, 0.9, [0.0, 0.8, 1.0], 1.5),
+                material([0.1, 0.9, 0.6], 0.2, 0.4, [0.1, 1.0, 0.6], 3.0),
+                material([1.0, 0.2, 0.8], 0.2, 0.4, [1.0, 0.2, 0.8], 4.0),
+            ],
+        ),
+        palette(
+            "Autumn",
+            [
+                material([0.3, 0.2, 0.16], 0.95, 0.0, [0.0; 3], 0.0),
+                material([0.85, 0.42, 0.08], 0.7, 0.0, [0.0; 3], 0.0),
+                material([0.7, 0.12, 0.08], 0.6, 0.0, [0.0; 3], 0.0),
+            ],
+        ),
+    ]
+}
+
+/// Palettes saved by the user, plus the palette window's inputs.
+#[derive(Resource)]
+pub struct PaletteLibrary {
+    pub palettes: Vec<MaterialPalette>,
+    /// Name for the next saved palette.
+    pub new_name: String,
+    /// File path (native) or pasted JSON (web) for importing a palette.
+    pub import_input: String,
+    /// Outcome of the last import or export.
+    pub status: Option<Result<String, String>>,
+}
+
+impl Default for PaletteLibrary {
+    /// Loads the palettes saved by a previous session.
+    fn default() -> Self {
+        let palettes = load_setting(PALETTES_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Self::with_palettes(palettes)
+    }
+}
+
+impl PaletteLibrary {
+    /// An empty library, ignoring saved palettes.
+    pub fn empty() -> Self {
+        Self::with_palettes(Vec::new())
+    }
+
+    fn with_palettes(palettes: Vec<MaterialPalette>) -> Self {
+        Self {
+            palettes,
+            new_name: String::new(),
+            import_input: String::new(),
+            status: None,
+        }
+    }
+
+    /// Persists the saved palettes for future sessions.
+    pub fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string(&self.palettes)
+            .map_err(|e| format!("Failed to serialize palettes: {}", e))?;
+        save_setting(PALETTES_KEY, &json)
+    }
+
+    /// Adds `palette`, replacing a saved palette of the same name.
+    pub fn insert(&mut self, palette: MaterialPalette) {
+        match self.palettes.iter_mut().find(|p| p.name == palette.name) {
+            Some(existing) => *existing = palette,
+            None => self.palettes.push(palette),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::MaterialSettings;
+
+    #[test]
+    fn test_palette_roundtrip_keeps_textures() {
+        let mut settings = MaterialSettingsMap::default();
+        settings.settings.insert(
+            0,
+            MaterialSettings {
+                base_color: [0.1, 0.2, 0.3],
+                roughness: 0.4,
+                ..default()
+            },
+        );
+        let palette = MaterialPalette::from_settings("Mine", &settings);
+        let loaded = MaterialPalette::from_json(&palette.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.name, "Mine");
+        assert_eq!(loaded.materials[&0].base_color, [0.1, 0.2, 0.3]);
+
+        let texture = settings.settings[&0].texture;
+        let autumn = &bundled_palettes()[2];
+        autumn.apply(&mut settings);
+        assert_eq!(settings.settings.len(), 3);
+        assert_eq!(
+            settings.settings[&1].base_color,
+            autumn.materials[&1].base_color
+        );
+        assert!(settings.settings[&0].texture == texture);
+
+        assert!(MaterialPalette::from_json("{\"name\":\"x\",\"materials\":{}}").is_err());
+        assert!(MaterialPalette::from_json("not json").is_err());
+    }
+
+    #[test]
+    fn test_insert_replaces_by_name() {
+        let mut library = PaletteLibrary::empty();
+        let mut palette = bundled_palettes().remove(0);
+        library.insert(palette.clone());
+        palette.materials.remove(&2);
+        library.insert(palette);
+        assert_eq!(library.palettes.len(), 1);
+        assert_eq!(library.palettes[0].materials.len(), 2);
+        assert_eq!(
+            library.palettes[0].filename(),
+            "Bark___Foliage.palette.json"
+        );
+    }
+}
@@ -50,6 +50,8 @@ impl Default for PresetCamera {
 
 pub struct LSystemPreset {
     pub name: &'static str,
+    /// Gallery section of the preset browser.
+    pub category: PresetCategory,
     pub code: &'static str,
     pub iterations: usize,
     pub angle: f32,
@@ -67,9 +69,35 @@ pub struct LSystemPreset {
     pub prop_meshes: &'static [(u16, PropMeshType)],
 }
 
+/// Sections of the preset browser.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PresetCategory {
+    /// Figures reproduced from *The Algorithmic Beauty of Plants*.
+    Abop,
+    Trees,
+    /// Space-filling curves and fractals.
+    Curves,
+    /// Plants starred into the herbarium.
+    User,
+}
+
+impl PresetCategory {
+    pub const ALL: [Self; 4] = [Self::Abop, Self::Trees, Self::Curves, Self::User];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Abop => "ABOP Figures",
+            Self::Trees => "Trees",
+            Self::Curves => "Curves",
+            Self::User => "User",
+        }
+    }
+}
+
 pub const PRESETS: &[LSystemPreset] = &[
     LSystemPreset {
         name: "Quadratic Koch Island (ABOP Fig 1.6)",
+        category: PresetCategory::Curves,
         code: "omega: F(100)-F(100)-F(100)-F(100)\n\
                F(s) -> F(s/3)+F(s/3)-F(s/3)-F(s/3)F(s/3)+F(s/3)+F(s/3)-F(s/3)",
         iterations: 3,
@@ -101,6 +129,7 @@ pub const PRESETS: &[LSystemPreset] = &[
     },
     LSystemPreset {
         name: "Sierpinski gasket (ABOP Fig 1.10 (b))",
+        category: PresetCategory::Curves,
         code: "omega: Fr\n\
                Fl -> Fr+Fl+Fr\n\
                Fr -> Fl-Fr-Fl\n\
@@ -137,6 +166,7 @@ pub const PRESETS: &[LSystemPreset] = &[
     },
     LSystemPreset {
         name: "Branching pattern (ABOP Fig 1.39)",
+        category: PresetCategory::Abop,
         code: "#define R 1.456\n\
                omega: A(150)\n\
                A(s) -> F(s)[+A(s/R)][-A(s/R)]",
@@ -169,6 +199,7 @@ pub const PRESETS: &[LSystemPreset] = &[
     },
     LSystemPreset {
         name: "Monopodial Tree (ABOP Fig 2.6)",
+        category: PresetCategory::Abop,
         code: "#define r1 0.9\n\
                #define r2 0.6\n\
                #define a0 45\n\
@@ -208,6 +239,7 @@ pub const PRESETS: &[LSystemPreset] = &[
     },
     LSystemPreset {
         name: "Sympodial Tree (ABOP Fig 2.7)",
+        category: PresetCategory::Abop,
         code: "#define r1 0.9\n\
                #define r2 0.7\n\
                #define a1 10\n\
@@ -245,6 +277,7 @@ pub const PRESETS: &[LSystemPreset] = &[
     },
     LSystemPreset {
         name: "Ternary Tree (Gravity) (ABOP Fig 2.8)",
+        category: PresetCategory::Abop,
         code: "#define d1 180\n\
                #define d2 252\n\
                #define a 36\n\
@@ -282,8 +315,290 @@ pub const PRESETS: &[LSystemPreset] = &[
         }),
         prop_meshes: &[],
     },
+    LSystemPreset {
+        name: "Row of Trees (ABOP Ch. 1)",
+        category: PresetCategory::Abop,
+        code: "#define p 0.3\n\
+               #define q 0.7\n\
+               #define h 0.458\n\
+               omega: -(90)F(600)T(0)\n\
+               p1: F(x) > T(t) : t = 0 -> F(x*p)T(2)+F(x*h)T(1)--F(x*h)T(1)+F(x*q)T(0)\n\
+               p2: T(t) : t > 0 -> T(t-1)\n\
+               p3: T(t) : t = 0 ->",
+        iterations: 10,
+        angle: 86.0,
+        step: 10.0,
+        width: 1.0,
+        elasticity: 0.0,
+        tropism: None,
+        initial_color: Some([1.0, 1.0, 1.0]),
+        materials: &[(
+            0,
+            PresetMaterial {
+                base_color: [0.3, 0.55, 0.25],
+                roughness: 1.0,
+                metallic: 0.0,
+                emission_color: [0.0, 0.0, 0.0],
+                emission_strength: 0.0,
+                uv_scale: 1.0,
+                texture_type: TextureType::None,
+            },
+        )],
+        camera: Some(PresetCamera {
+            focus: Vec3::new(300.0, 60.0, 0.0),
+            distance: 700.0,
+            pitch: 0.0,
+            yaw: 0.0,
+        }),
+        prop_meshes: &[],
+    },
+    LSystemPreset {
+        name: "Anabaena Catenula (ABOP Ch. 1)",
+        category: PresetCategory::Abop,
+        code: "#define la 6\n\
+               #define lb 3\n\
+               omega: !(2.5)X(0)\n\
+               p1: F(l) : * ->\n\
+               p2: ,(m) : * ->\n\
+               p3: +(a) : * ->\n\
+               p4: X(t) : t = 0 -> ,(1)+(4)F(la)X(1),(2)+(4)F(lb)X(2)\n\
+               p5: X(t) : t = 1 -> ,(2)+(4)F(lb)X(3),(1)+(4)F(la)X(0)\n\
+               p6: X(t) : t = 2 -> ,(1)+(4)F(la)X(0)\n\
+               p7: X(t) : t = 3 -> ,(1)+(4)F(la)X(1)",
+        iterations: 12,
+        angle: 4.0,
+        step: 1.0,
+        width: 2.5,
+        elasticity: 0.0,
+        tropism: None,
+        initial_color: None,
+        materials: &[
+            (
+                0,
+                PresetMaterial {
+                    base_color: [0.2, 0.5, 0.3],
+                    roughness: 0.6,
+                    metallic: 0.0,
+                    emission_color: [0.0, 0.0, 0.0],
+                    emission_strength: 0.0,
+                    uv_scale: 1.0,
+                    texture_type: TextureType::None,
+                },
+            ),
+            (
+                1,
+                PresetMaterial {
+                    base_color: [0.15, 0.55, 0.35],
+                    roughness: 0.5,
+                    metallic: 0.0,
+                    emission_color: [0.0, 0.0, 0.0],
+                    emission_strength: 0.0,
+                    uv_scale: 1.0,
+                    texture_type: TextureType::None,
+                },
+            ),
+            (
+                2,
+                PresetMaterial {
+                    base_color: [0.55, 0.8, 0.4],
+                    roughness: 0.5,
+                    metallic: 0.0,
+                    emission_color: [0.0, 0.0, 0.0],
+                    emission_strength: 0.0,
+                    uv_scale: 1.0,
+                    texture_type: TextureType::None,
+                },
+            ),
+        ],
+        camera: Some(PresetCamera {
+            focus: Vec3::new(0.0, 120.0, 0.0),
+            distance: 500.0,
+            pitch: 0.0,
+            yaw: 0.0,
+        }),
+        prop_meshes: &[],
+    },
+    LSystemPreset {
+        name: "Mycelis Muralis (ABOP Ch. 3)",
+        category: PresetCategory::Abop,
+        code: "#define a 45\n\
+               #define d 137.5\n\
+               omega: !(1.5),(0)F(20)A(0)\n\
+               p1: A(t) : t < 7 -> F(10)[&(a)L(14-2*t)][,(1)^(60)~(0,12)]/(d)A(t+1)\n\
+               p2: A(t) : t > 6 -> F(10)K(0)\n\
+               p3: L(t) : t > 0 -> L(t-1)\n\
+               p4: L(t) : t = 0 -> !(0.6)F(8)[-(30)F(6)K(0)][+(30)F(6)K(0)]F(6)K(0)\n\
+               p5: K(s) : s < 3 -> K(s+1)\n\
+               p6: K(s) : s = 3 -> [,(2)~(1,5)]\n\
+               p7: F(l) : l < 25 -> F(l*1.1)",
+        iterations: 21,
+        angle: 45.0,
+        step: 1.0,
+        width: 1.0,
+        elasticity: 0.0,
+        tropism: None,
+        initial_color: None,
+        materials: &[
+            (
+                0,
+                PresetMaterial {
+                    base_color: [0.3, 0.45, 0.15],
+                    roughness: 0.8,
+                    metallic: 0.0,
+                    emission_color: [0.0, 0.0, 0.0],
+                    emission_strength: 0.0,
+                    uv_scale: 1.0,
+                    texture_type: TextureType::None,
+                },
+            ),
+            (
+                1,
+                PresetMaterial {
+                    base_color: [0.2, 0.55, 0.15],
+                    roughness: 0.6,
+                    metallic: 0.0,
+                    emission_color: [0.0, 0.0, 0.0],
+                    emission_strength: 0.0,
+                    uv_scale: 1.0,
+                    texture_type: TextureType::None,
+                },
+            ),
+            (
+                2,
+                PresetMaterial {
+                    base_color: [0.95, 0.8, 0.15],
+                    roughness: 0.5,
+                    metallic: 0.0,
+                    emission_color: [0.0, 0.0, 0.0],
+                    emission_strength: 0.0,
+                    uv_scale: 1.0,
+                    texture_type: TextureType::None,
+                },
+            ),
+        ],
+        camera: Some(PresetCamera {
+            focus: Vec3::new(0.0, 120.0, 0.0),
+            distance: 450.0,
+            pitch: std::f32::consts::TAU / 64.0,
+            yaw: std::f32::consts::TAU / 5.0,
+        }),
+        prop_meshes: &[(0, PropMeshType::Leaf), (1, PropMeshType::Sphere)],
+    },
+    LSystemPreset {
+        name: "Rose Leaf (ABOP Ch. 5)",
+        category: PresetCategory::Abop,
+        code: "#define n 3\n\
+               omega: ,(0)&(60)!(0.5)A(n)\n\
+               p1: A(k) : k > 0 -> F(5)[+(55)!(0.25)F(0.6),(1)~(0,1)][-(55)!(0.25)F(0.6),(1)~(0,1)]A(k-1)\n\
+               p2: A(k) : k = 0 -> F(1.5),(1)~(0,1.2)\n\
+               p3: ~(i,s) : s < 4 -> ~(i,s*1.25)",
+        iterations: 10,
+        angle: 55.0,
+        step: 1.0,
+        width: 0.5,
+        elasticity: 0.0,
+        tropism: None,
+        initial_color: None,
+        materials: &[
+            (
+                0,
+                PresetMaterial {
+                    base_color: [0.35, 0.5, 0.15],
+                    roughness: 0.7,
+                    metallic: 0.0,
+                    emission_color: [0.0, 0.0, 0.0],
+                    emission_strength: 0.0,
+                    uv_scale: 1.0,
+                    texture_type: TextureType::None,
+                },
+            ),
+            (
+                1,
+                PresetMaterial {
+                    base_color: [0.15, 0.4, 0.12],
+                    roughness: 0.45,
+                    metallic: 0.0,
+                    emission_color: [0.0, 0.0, 0.0],
+                    emission_strength: 0.0,
+                    uv_scale: 1.0,
+                    texture_type: TextureType::None,
+                },
+            ),
+        ],
+        camera: Some(PresetCamera {
+            focus: Vec3::new(0.0, 12.0, 0.0),
+            distance: 45.0,
+            pitch: std::f32::consts::TAU / 16.0,
+            yaw: 0.0,
+        }),
+        prop_meshes: &[(0, PropMeshType::Leaf)],
+    },
+    LSystemPreset {
+        name: "Lilac Inflorescence (ABOP Ch. 3)",
+        category: PresetCategory::Abop,
+        code: "#define n 5\n\
+               omega: ,(0)!(1.2)F(30)[,(1)&(70)~(0,20)][,(1)/(180)&(70)~(0,20)]A(n)\n\
+               p1: A(k) : k > 0 -> F(8)[&(50)!(0.5)B(k)][/(180)&(50)!(0.5)B(k)]/(90)A(k-1)\n\
+               p2: A(k) : k = 0 -> F(4)K\n\
+               p3: B(k) : k > 0 -> F(2+k)[&(40)K][^(40)K]B(k-1)\n\
+               p4: B(k) : k = 0 -> K\n\
+               p5: K -> ,(2)~(1,2.5)",
+        iterations: 10,
+        angle: 45.0,
+        step: 1.0,
+        width: 1.2,
+        elasticity: 0.0,
+        tropism: None,
+        initial_color: None,
+        materials: &[
+            (
+                0,
+                PresetMaterial {
+                    base_color: [0.35, 0.45, 0.2],
+                    roughness: 0.8,
+                    metallic: 0.0,
+                    emission_color: [0.0, 0.0, 0.0],
+                    emission_strength: 0.0,
+                    uv_scale: 1.0,
+                    texture_type: TextureType::None,
+                },
+            ),
+            (
+                1,
+                PresetMaterial {
+                    base_color: [0.2, 0.5, 0.15],
+                    roughness: 0.6,
+                    metallic: 0.0,
+                    emission_color: [0.0, 0.0, 0.0],
+                    emission_strength: 0.0,
+                    uv_scale: 1.0,
+                    texture_type: TextureType::None,
+                },
+            ),
+            (
+                2,
+                PresetMaterial {
+                    base_color: [0.7, 0.5, 0.85],
+                    roughness: 0.5,
+                    metallic: 0.0,
+                    emission_color: [0.0, 0.0, 0.0],
+                    emission_strength: 0.0,
+                    uv_scale: 1.0,
+                    texture_type: TextureType::None,
+                },
+            ),
+        ],
+        camera: Some(PresetCamera {
+            focus: Vec3::new(0.0, 50.0, 0.0),
+            distance: 160.0,
+            pitch: std::f32::consts::TAU / 64.0,
+            yaw: std::f32::consts::TAU / 5.0,
+        }),
+        prop_meshes: &[(0, PropMeshType::Leaf), (1, PropMeshType::Sphere)],
+    },
     LSystemPreset {
         name: "Ternary Tree (+Props +Materials +Variations)",
+        category: PresetCategory::Trees,
         code: "#define d1 180\n\
                #define th 2.5\n\
                #define d2 252\n\
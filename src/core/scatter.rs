@@ -0,0 +1,217 @@
+//! Forest scatter: seed variations of one plant spread over a square region.
+//!
+//! A few distinct variations are derived once and instanced many times, so a
+//! forest of hundreds of plants costs only a handful of derivations. The
+//! layout is deterministic for a given [`ScatterSettings::layout_seed`].
+
+use crate::core::genotype::PlantGenotype;
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use std::hash::{Hash, Hasher};
+
+/// Upper bound on scattered plants, whatever the density and region.
+pub const MAX_SCATTER_PLANTS: usize = 400;
+
+/// Upper bound on distinct seed variations.
+pub const MAX_SCATTER_VARIATIONS: usize = 16;
+
+/// Seed of the `index`-th variation of a plant. The first variation keeps the
+/// plant's own seed, so it matches the editor.
+pub fn variation_seed(seed: u64, index: usize) -> u64 {
+    if index == 0 {
+        return seed;
+    }
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    seed.hash(&mut hasher);
+    index.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Layout of a scattered forest.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScatterSettings {
+    /// Center of the region on the ground plane.
+    pub center: Vec3,
+    /// Side length of the square region.
+    pub region_size: f32,
+    /// Plants per 1000 × 1000 square units.
+    pub density: f32,
+    /// Random offset from the grid cell center, as a fraction of the cell size.
+    pub jitter: f32,
+    /// Random scale deviation: scales fall in `1 ± scale_variance`.
+    pub scale_variance: f32,
+    /// Distinct seed variations to derive and instance.
+    pub variations: usize,
+    pub layout_seed: u64,
+}
+
+impl Default for ScatterSettings {
+    fn default() -> Self {
+        Self {
+            center: Vec3::ZERO,
+            region_size: 4000.0,
+            density: 10.0,
+            jitter: 0.7,
+            scale_variance: 0.2,
+            variations: 4,
+            layout_seed: 0,
+        }
+    }
+}
+
+impl ScatterSettings {
+    /// Number of plants in the region, capped at [`MAX_SCATTER_PLANTS`].
+    pub fn plant_count(&self) -> usize {
+        let count = (self.density.max(0.0) * self.region_size * self.region_size / 1.0e6).round();
+        (count as usize).min(MAX_SCATTER_PLANTS)
+    }
+
+    /// Places every plant: cells of a square grid over the region are picked
+    /// at random and jittered, with a random turn and scale and a random
+    /// variation each.
+    pub fn layout(&self) -> Vec<ScatterInstance> {
+        let count = self.plant_count();
+        if count == 0 {
+            return Vec::new();
+        }
+        let mut rng = Pcg64::seed_from_u64(self.layout_seed);
+        let columns = (count as f32).sqrt().ceil() as usize;
+        let cell = self.region_size / columns as f32;
+        let origin = self.center - Vec3::new(self.region_size, 0.0, self.region_size) * 0.5;
+
+        let mut cells: Vec<usize> = (0..columns * columns).collect();
+        cells.shuffle(&mut rng);
+        cells.truncate(count);
+        cells.sort_unstable();
+
+        let variations = self.variations.clamp(1, MAX_SCATTER_VARIATIONS);
+        cells
+            .into_iter()
+            .map(|index| {
+                let offset = Vec3::new(rng.random::<f32>() - 0.5, 0.0, rng.random::<f32>() - 0.5)
+                    * self.jitter
+                    * cell;
+                let translation = origin
+                    + Vec3::new(
+                        ((index % columns) as f32 + 0.5) * cell,
+                        0.0,
+                        ((index / columns) as f32 + 0.5) * cell,
+                    )
+                    + offset;
+                let deviation = (rng.random::<f32>() * 2.0 - 1.0) * self.scale_variance;
+                ScatterInstance {
+                    translation,
+                    yaw: rng.random::<f32>() * 360.0,
+                    scale: (1.0 + deviation).max(0.05),
+                    variation: rng.random_range(0..variations),
+                }
+            })
+            .collect()
+    }
+}
+
+/// One scattered plant.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScatterInstance {
+    pub translation: Vec3,
+    /// Turn about the vertical axis, in degrees.
+    pub yaw: f32,
+    pub scale: f32,
+    /// Index of the seed variation shown.
+    pub variation: usize,
+}
+
+impl ScatterInstance {
+    pub fn transform(&self) -> Transform {
+        Transform {
+            translation: self.translation,
+            rotation: Quat::from_rotation_y(self.yaw.to_radians()),
+            scale: Vec3::splat(self.scale),
+        }
+    }
+}
+
+/// The scattered forest shown in garden mode.
+#[derive(Resource, Default)]
+pub struct Scatter {
+    pub settings: ScatterSettings,
+    /// Plant being scattered, or `None` when no forest is shown.
+    pub genotype: Option<PlantGenotype>,
+}
+
+impl Scatter {
+    /// The seed variations of the scattered plant.
+    pub fn variation_genotypes(&self) -> Vec<PlantGenotype> {
+        let Some(genotype) = &self.genotype else {
+            return Vec::new();
+        };
+        (0..self.settings.variations.clamp(1, MAX_SCATTER_VARIATIONS))
+            .map(|index| {
+                genotype
+                    .clone()
+                    .with_seed(variation_seed(genotype.seed, index))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_fills_region() {
+        let settings = ScatterSettings {
+            region_size: 2000.0,
+            density: 25.0,
+            jitter: 1.0,
+            ..default()
+        };
+        assert_eq!(settings.plant_count(), 100);
+
+        let layout = settings.layout();
+        assert_eq!(layout.len(), 100);
+        for instance in &layout {
+            assert!(instance.translation.x.abs() <= 1000.0);
+            assert!(instance.translation.z.abs() <= 1000.0);
+            assert!((0.8..=1.2).contains(&instance.scale));
+            assert!(instance.variation < settings.variations);
+        }
+
+        // Deterministic for a layout seed
+        assert_eq!(layout, settings.layout());
+        let reseeded = ScatterSettings {
+            layout_seed: 1,
+            ..settings.clone()
+        };
+        assert_ne!(layout, reseeded.layout());
+    }
+
+    #[test]
+    fn test_plant_count_is_capped() {
+        let settings = ScatterSettings {
+            region_size: 100_000.0,
+            ..default()
+        };
+        assert_eq!(settings.plant_count(), MAX_SCATTER_PLANTS);
+        assert_eq!(settings.layout().len(), MAX_SCATTER_PLANTS);
+    }
+
+    #[test]
+    fn test_first_variation_keeps_seed() {
+        let scatter = Scatter {
+            settings: ScatterSettings::default(),
+            genotype: Some(PlantGenotype::new("omega: F".into()).with_seed(7)),
+        };
+        let seeds: Vec<u64> = scatter
+            .variation_genotypes()
+            .iter()
+            .map(|genotype| genotype.seed)
+            .collect();
+        assert_eq!(seeds.len(), 4);
+        assert_eq!(seeds[0], 7);
+        assert_ne!(seeds[1], 7);
+    }
+}
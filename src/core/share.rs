@@ -0,0 +1,138 @@
+//! Shareable links: the editor state encoded into a URL fragment.
+//!
+//! The state is serialized as a `PlantGenotype` (grammar, parameters, materials,
+//! prop mappings), compressed with DEFLATE, and encoded as URL-safe base64 behind
+//! a `g=` key, e.g. `https://host/explorer/#g=...`. Fragments never reach the
+//! server, so links work with any static host.
+
+use crate::core::config::{LSystemConfig, MaterialSettingsMap, PropConfig};
+use crate::core::genotype::PlantGenotype;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use bevy::prelude::*;
+
+/// Fragment key identifying an encoded genotype.
+const FRAGMENT_KEY: &str = "g=";
+
+/// Upper bound on the decompressed payload, guarding against decompression bombs.
+const MAX_DECODED_BYTES: usize = 1 << 20;
+
+/// Encodes a genotype as a URL fragment (without the leading `#`).
+pub fn encode_share_fragment(genotype: &PlantGenotype) -> Result<String, String> {
+    let json = serde_json::to_vec(genotype).map_err(|e| format!("Failed to serialize: {}", e))?;
+    let compressed = miniz_oxide::deflate::compress_to_vec(&json, 9);
+    Ok(format!(
+        "{}{}",
+        FRAGMENT_KEY,
+        URL_SAFE_NO_PAD.encode(compressed)
+    ))
+}
+
+/// Decodes a URL fragment (with or without the leading `#`) into a genotype.
+pub fn decode_share_fragment(fragment: &str) -> Result<PlantGenotype, String> {
+    let payload = fragment
+        .trim_start_matches('#')
+        .strip_prefix(FRAGMENT_KEY)
+        .ok_or("Link does not contain a shared plant")?;
+    let compressed = URL_SAFE_NO_PAD
+        .decode(payload)
+        .map_err(|e| format!("Invalid link encoding: {}", e))?;
+    let json = miniz_oxide::inflate::decompress_to_vec_with_limit(&compressed, MAX_DECODED_BYTES)
+        .map_err(|e| format!("Invalid link data: {:?}", e.status))?;
+    serde_json::from_slice(&json).map_err(|e| format!("Invalid shared plant: {}", e))
+}
+
+/// Builds a full share URL for the current page.
+#[cfg(target_arch = "wasm32")]
+pub fn share_url(fragment: &str) -> Result<String, String> {
+    let location = web_sys::window()
+        .ok_or("No browser window available")?
+        .location();
+    let href = location
+        .href()
+        .map_err(|e| format!("Failed to read location: {:?}", e))?;
+    let base = href.split('#').next().unwrap_or(&href);
+    Ok(format!("{}#{}", base, fragment))
+}
+
+/// Copies a share link for the given genotype to the clipboard and updates the
+/// address bar, so the link can also be bookmarked.
+#[cfg(target_arch = "wasm32")]
+pub fn copy_share_link(genotype: &PlantGenotype) -> Result<String, String> {
+    let fragment = encode_share_fragment(genotype)?;
+    let url = share_url(&fragment)?;
+    let window = web_sys::window().ok_or("No browser window available")?;
+    let _ = window.location().set_hash(&fragment);
+    // The clipboard write resolves asynchronously; failures surface as a console warning
+    let _ = window.navigator().clipboard().write_text(&url);
+    Ok(url)
+}
+
+/// Returns the page URL's fragment (including `#`), if any.
+#[cfg(target_arch = "wasm32")]
+fn page_fragment() -> Option<String> {
+    let hash = web_sys::window()?.location().hash().ok()?;
+    (hash.len() > 1).then_some(hash)
+}
+
+/// Native builds have no page URL.
+#[cfg(not(target_arch = "wasm32"))]
+fn page_fragment() -> Option<String> {
+    None
+}
+
+/// Startup system that loads a shared plant from the page URL, if present.
+/// Runs after the startup preset so the shared plant takes precedence.
+pub fn load_shared_url(
+    mut config: ResMut<LSystemConfig>,
+    mut materials: ResMut<MaterialSettingsMap>,
+    mut prop_config: ResMut<PropConfig>,
+) {
+    let Some(fragment) = page_fragment() else {
+        return;
+    };
+    match decode_share_fragment(&fragment) {
+        Ok(genotype) => {
+            info!("Loaded shared plant from URL");
+            genotype.apply_to_editor(&mut config, &mut materials, &mut prop_config);
+        }
+        Err(e) => warn!("Ignoring URL fragment: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_share_fragment_round_trip() {
+        let mut genotype = PlantGenotype::new("omega: A\np1: A -> F [ + A ] A".to_string())
+            .with_params(7, 22.5, 3.0, 0.4)
+            .with_seed(1234);
+        genotype.tropism = Some([0.0, -1.0, 0.0]);
+
+        let fragment = encode_share_fragment(&genotype).unwrap();
+        assert!(fragment.starts_with("g="));
+        assert!(
+            fragment
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "=-_".contains(c)),
+            "Fragment must be URL-safe: {}",
+            fragment
+        );
+
+        let decoded = decode_share_fragment(&format!("#{}", fragment)).unwrap();
+        assert_eq!(decoded.source_code, genotype.source_code);
+        assert_eq!(decoded.iterations, 7);
+        assert_eq!(decoded.seed, 1234);
+        assert_eq!(decoded.tropism, Some([0.0, -1.0, 0.0]));
+    }
+
+    #[test]
+    fn test_invalid_fragments() {
+        assert!(decode_share_fragment("").is_err());
+        assert!(decode_share_fragment("#section-2").is_err());
+        assert!(decode_share_fragment("#g=not*base64").is_err());
+        assert!(decode_share_fragment("#g=AAAA").is_err());
+    }
+}
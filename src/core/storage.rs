@@ -0,0 +1,95 @@
+//! Small key/value persistence for user settings.
+//!
+//! Native builds store each key as a file in the user's config directory
+//! (`$XDG_CONFIG_HOME`, `~/.config`, or `%APPDATA%`, under `lsystem-explorer/`).
+//! Wasm builds use the browser's `localStorage`.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+
+/// Application folder name inside the platform config directory.
+#[cfg(not(target_arch = "wasm32"))]
+const APP_DIR: &str = "lsystem-explorer";
+
+/// Prefix for localStorage keys, avoiding clashes with other apps on the same origin.
+#[cfg(target_arch = "wasm32")]
+const KEY_PREFIX: &str = "lsystem-explorer.";
+
+/// Returns the directory used for persisted settings, if one can be determined.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn settings_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join(APP_DIR))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn setting_path(key: &str) -> Option<PathBuf> {
+    settings_dir().map(|dir| dir.join(key))
+}
+
+/// Loads a stored setting, or `None` if it was never saved or cannot be read.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_setting(key: &str) -> Option<String> {
+    std::fs::read_to_string(setting_path(key)?).ok()
+}
+
+/// Stores a setting, replacing any previous value.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_setting(key: &str, value: &str) -> Result<(), String> {
+    let path = setting_path(key).ok_or("No config directory available")?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    std::fs::write(&path, value).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Removes a stored setting. Missing settings are not an error.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn remove_setting(key: &str) -> Result<(), String> {
+    let Some(path) = setting_path(key) else {
+        return Ok(());
+    };
+    match std::fs::remove_file(&path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to remove {}: {}", path.display(), e)),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Result<web_sys::Storage, String> {
+    web_sys::window()
+        .ok_or("No browser window available")?
+        .local_storage()
+        .map_err(|e| format!("localStorage unavailable: {:?}", e))?
+        .ok_or_else(|| "localStorage unavailable".to_string())
+}
+
+/// Loads a stored setting, or `None` if it was never saved or cannot be read.
+#[cfg(target_arch = "wasm32")]
+pub fn load_setting(key: &str) -> Option<String> {
+    local_storage()
+        .ok()?
+        .get_item(&format!("{}{}", KEY_PREFIX, key))
+        .ok()?
+}
+
+/// Stores a setting, replacing any previous value.
+#[cfg(target_arch = "wasm32")]
+pub fn save_setting(key: &str, value: &str) -> Result<(), String> {
+    local_storage()?
+        .set_item(&format!("{}{}", KEY_PREFIX, key), value)
+        .map_err(|e| format!("Failed to write localStorage: {:?}", e))
+}
+
+/// Removes a stored setting. Missing settings are not an error.
+#[cfg(target_arch = "wasm32")]
+pub fn remove_setting(key: &str) -> Result<(), String> {
+    local_storage()?
+        .remove_item(&format!("{}{}", KEY_PREFIX, key))
+        .map_err(|e| format!("Failed to write localStorage: {:?}", e))
+}
@@ -0,0 +1,284 @@
+//! Heightmap terrain under the garden and the scattered forest.
+//!
+//! The heightmap is either fractal value noise or an imported grayscale
+//! image, stretched over a square centered on the origin. Garden and forest
+//! plants are snapped onto its surface through [`Terrain::place`].
+
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+
+/// Largest imported heightmap side, in samples; bigger images are resampled.
+pub const MAX_HEIGHTMAP_SIZE: u32 = 256;
+
+/// Samples per side of noise heightmaps.
+const NOISE_RESOLUTION: usize = 129;
+
+/// Noise octaves summed into a heightmap.
+const NOISE_OCTAVES: u32 = 5;
+
+/// Heights in `0..=1` on a regular grid.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Heightmap {
+    /// Samples per row.
+    pub width: usize,
+    /// Number of rows.
+    pub depth: usize,
+    /// Row-major samples, `width * depth` of them.
+    pub heights: Vec<f32>,
+}
+
+impl Heightmap {
+    /// Fractal value noise: `NOISE_OCTAVES` octaves starting at `features`
+    /// hills across the map, each octave at half the amplitude.
+    pub fn from_noise(seed: u64, features: f32) -> Self {
+        let size = NOISE_RESOLUTION;
+        let mut heights = vec![0.0; size * size];
+        let mut amplitude = 1.0;
+        let mut frequency = features.max(0.5);
+        let mut total = 0.0;
+        for octave in 0..NOISE_OCTAVES {
+            let lattice = frequency.ceil() as usize + 1;
+            let mut rng = Pcg64::seed_from_u64(seed.wrapping_add(octave as u64));
+            let values: Vec<f32> = (0..lattice * lattice).map(|_| rng.random()).collect();
+            for z in 0..size {
+                for x in 0..size {
+                    let u = x as f32 / (size - 1) as f32 * frequency;
+                    let v = z as f32 / (size - 1) as f32 * frequency;
+                    heights[z * size + x] += amplitude * smooth_lattice(&values, lattice, u, v);
+                }
+            }
+            total += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+        for height in &mut heights {
+            *height /= total;
+        }
+        Self {
+            width: size,
+            depth: size,
+            heights,
+        }
+    }
+
+    /// Decodes a PNG or JPEG into a heightmap of its luminance, resampled to
+    /// at most [`MAX_HEIGHTMAP_SIZE`] samples per side.
+    pub fn from_image(bytes: &[u8]) -> Result<Self, String> {
+        let decoded = image::load_from_memory(bytes)
+            .map_err(|e| format!("Failed to decode heightmap: {}", e))?;
+        let decoded =
+            if decoded.width() > MAX_HEIGHTMAP_SIZE || decoded.height() > MAX_HEIGHTMAP_SIZE {
+                decoded.resize(
+                    MAX_HEIGHTMAP_SIZE,
+                    MAX_HEIGHTMAP_SIZE,
+                    image::imageops::FilterType::Triangle,
+                )
+            } else {
+                decoded
+            };
+        let luma = decoded.to_luma16();
+        let (width, depth) = luma.dimensions();
+        if width < 2 || depth < 2 {
+            return Err("A heightmap needs at least 2 × 2 pixels".into());
+        }
+        Ok(Self {
+            width: width as usize,
+            depth: depth as usize,
+            heights: luma
+                .pixels()
+                .map(|pixel| pixel.0[0] as f32 / u16::MAX as f32)
+                .collect(),
+        })
+    }
+
+    /// Bilinear height at `(u, v)` in `0..=1` across the map, clamped at the edges.
+    pub fn sample(&self, u: f32, v: f32) -> f32 {
+        let x = u.clamp(0.0, 1.0) * (self.width - 1) as f32;
+        let z = v.clamp(0.0, 1.0) * (self.depth - 1) as f32;
+        let (x0, z0) = (x.floor() as usize, z.floor() as usize);
+        let (x1, z1) = ((x0 + 1).min(self.width - 1), (z0 + 1).min(self.depth - 1));
+        let (tx, tz) = (x - x0 as f32, z - z0 as f32);
+        let at = |x: usize, z: usize| self.heights[z * self.width + x];
+        let near = at(x0, z0) + (at(x1, z0) - at(x0, z0)) * tx;
+        let far = at(x0, z1) + (at(x1, z1) - at(x0, z1)) * tx;
+        near + (far - near) * tz
+    }
+}
+
+/// Smoothstep-interpolated lattice value at `(u, v)` in lattice units.
+fn smooth_lattice(values: &[f32], lattice: usize, u: f32, v: f32) -> f32 {
+    let (x0, z0) = (u.floor() as usize, v.floor() as usize);
+    let (x1, z1) = ((x0 + 1).min(lattice - 1), (z0 + 1).min(lattice - 1));
+    let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+    let (tx, tz) = (smooth(u.fract()), smooth(v.fract()));
+    let at = |x: usize, z: usize| values[z.min(lattice - 1) * lattice + x.min(lattice - 1)];
+    let near = at(x0, z0) + (at(x1, z0) - at(x0, z0)) * tx;
+    let far = at(x0, z1) + (at(x1, z1) - at(x0, z1)) * tx;
+    near + (far - near) * tz
+}
+
+/// Where the heightmap comes from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TerrainSource {
+    Noise {
+        seed: u64,
+        /// Hills across the terrain at the coarsest octave.
+        features: f32,
+    },
+    /// An imported image, by file name.
+    Image(String),
+}
+
+/// The terrain and how plants sit on it.
+#[derive(Resource)]
+pub struct Terrain {
+    pub enabled: bool,
+    pub source: TerrainSource,
+    pub heightmap: Heightmap,
+    /// Side length of the square terrain.
+    pub size: f32,
+    /// Height of the brightest heightmap value; the darkest is at zero.
+    pub height: f32,
+    /// Tilt plants to the surface normal instead of keeping them upright.
+    pub align_to_normal: bool,
+    /// Heightmap file path typed into the import field (native only).
+    pub path_input: String,
+    /// Result of the last image import.
+    pub status: Option<Result<String, String>>,
+}
+
+impl Default for Terrain {
+    fn default() -> Self {
+        let source = TerrainSource::Noise {
+            seed: 0,
+            features: 3.0,
+        };
+        Self {
+            enabled: false,
+            heightmap: Heightmap::from_noise(0, 3.0),
+            source,
+            size: 12_000.0,
+            height: 800.0,
+            align_to_normal: false,
+            path_input: String::new(),
+            status: None,
+        }
+    }
+}
+
+impl Terrain {
+    /// Regenerates the noise heightmap from the current noise source.
+    pub fn regenerate(&mut self) {
+        if let TerrainSource::Noise { seed, features } = self.source {
+            self.heightmap = Heightmap::from_noise(seed, features);
+        }
+    }
+
+    /// Replaces the heightmap with an imported image.
+    pub fn import_image(&mut self, name: String, bytes: &[u8]) {
+        self.status = Some(match Heightmap::from_image(bytes) {
+            Ok(heightmap) => {
+                let message = format!(
+                    "Imported {} ({} × {})",
+                    name, heightmap.width, heightmap.depth
+                );
+                self.heightmap = heightmap;
+                self.source = TerrainSource::Image(name);
+                Ok(message)
+            }
+            Err(e) => Err(e),
+        });
+    }
+
+    /// Surface height at a point of the ground plane; zero when the terrain
+    /// is off.
+    pub fn height_at(&self, x: f32, z: f32) -> f32 {
+        if !self.enabled {
+            return 0.0;
+        }
+        let u = x / self.size + 0.5;
+        let v = z / self.size + 0.5;
+        self.heightmap.sample(u, v) * self.height
+    }
+
+    /// Upward surface normal at a point, from central differences of the
+    /// height across one heightmap cell.
+    pub fn normal_at(&self, x: f32, z: f32) -> Vec3 {
+        if !self.enabled {
+            return Vec3::Y;
+        }
+        let dx = 0.5 * self.size / (self.heightmap.width - 1) as f32;
+        let dz = 0.5 * self.size / (self.heightmap.depth - 1) as f32;
+        let slope_x = (self.height_at(x + dx, z) - self.height_at(x - dx, z)) / (2.0 * dx);
+        let slope_z = (self.height_at(x, z + dz) - self.height_at(x, z - dz)) / (2.0 * dz);
+        Vec3::new(-slope_x, 1.0, -slope_z).normalize()
+    }
+
+    /// Moves a plant's base onto the surface and, with
+    /// [`Terrain::align_to_normal`], tilts it to the surface normal.
+    pub fn place(&self, mut transform: Transform) -> Transform {
+        let (x, z) = (transform.translation.x, transform.translation.z);
+        transform.translation.y = self.height_at(x, z);
+        if self.enabled && self.align_to_normal {
+            transform.rotation =
+                Quat::from_rotation_arc(Vec3::Y, self.normal_at(x, z)) * transform.rotation;
+        }
+        transform
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_noise_is_deterministic_and_normalized() {
+        let a = Heightmap::from_noise(7, 3.0);
+        assert_eq!(a, Heightmap::from_noise(7, 3.0));
+        assert_ne!(a, Heightmap::from_noise(8, 3.0));
+        assert!(a.heights.iter().all(|h| (0.0..=1.0).contains(h)));
+    }
+
+    #[test]
+    fn test_place_snaps_to_slope() {
+        // A ramp rising along +X
+        let mut terrain = Terrain {
+            enabled: true,
+            heightmap: Heightmap {
+                width: 2,
+                depth: 2,
+                heights: vec![0.0, 1.0, 0.0, 1.0],
+            },
+            size: 100.0,
+            height: 100.0,
+            ..default()
+        };
+        let placed = terrain.place(Transform::from_xyz(25.0, 0.0, 0.0));
+        assert!((placed.translation.y - 75.0).abs() < 1e-3);
+        assert_eq!(placed.rotation, Quat::IDENTITY);
+
+        terrain.align_to_normal = true;
+        let placed = terrain.place(Transform::from_xyz(0.0, 0.0, 0.0));
+        let up = placed.rotation * Vec3::Y;
+        let expected = Vec3::new(-1.0, 1.0, 0.0).normalize();
+        assert!(up.distance(expected) < 1e-3);
+
+        // Off: plants stay on the ground plane
+        terrain.enabled = false;
+        let placed = terrain.place(Transform::from_xyz(25.0, 5.0, 0.0));
+        assert_eq!(placed.translation.y, 0.0);
+    }
+
+    #[test]
+    fn test_image_heightmap() {
+        let mut png = Vec::new();
+        image::GrayImage::from_fn(4, 2, |x, _| image::Luma([(x * 85) as u8]))
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let heightmap = Heightmap::from_image(&png).unwrap();
+        assert_eq!((heightmap.width, heightmap.depth), (4, 2));
+        assert_eq!(heightmap.sample(0.0, 0.0), 0.0);
+        assert!((heightmap.sample(1.0, 1.0) - 1.0).abs() < 1e-3);
+    }
+}
@@ -1,4 +1,5 @@
 pub mod core;
 pub mod logic;
+pub mod plugins;
 pub mod ui;
 pub mod visuals;
@@ -0,0 +1,417 @@
+//! Named sub-L-systems composed into one plant.
+//!
+//! A project may define extra grammars after its main grammar, each introduced
+//! by a `#system NAME [ITERATIONS]` line and running until the next one:
+//!
+//! ```text
+//! omega: A
+//! p1: A -> F [ + A ] [ - @flower(0.5) ] A
+//!
+//! #system flower 3
+//! omega: P
+//! p1: P -> [ & F ] / P
+//! ```
+//!
+//! Each grammar is derived independently. A call symbol `@name` or
+//! `@name(scale)` is rewritten before parsing into a prop module with a
+//! reserved ID (`~(CALL_PROP_BASE + index, scale)`), so the turtle records the
+//! position and orientation of the call like any prop. After interpretation,
+//! [`graft_calls`] replaces those props with the called grammar's skeleton,
+//! transformed into the caller's frame.
+
+use crate::logic::jitter::rewrite_jitter;
+use crate::logic::scope::{IGNORE_MARKERS, modules_start, rewrite_brackets, uses_branch_scopes};
+use crate::logic::strand_pool::StrandPool;
+use symbios_turtle_3d::Skeleton;
+
+/// Directive keyword starting a named grammar section.
+pub const SYSTEM_DIRECTIVE: &str = "#system";
+
+/// Prefix of a call symbol.
+pub const CALL_PREFIX: char = '@';
+
+/// First prop ID reserved for calls; grammar `i` is called with `CALL_PROP_BASE + i`.
+pub const CALL_PROP_BASE: u16 = 60_000;
+
+/// Maximum nesting of calls between grammars, guarding against runaway recursion.
+pub const MAX_GRAFT_DEPTH: usize = 8;
+
+/// A named grammar defined with `#system`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GrammarSection {
+    pub name: String,
+    /// Iteration count; `None` uses the editor's count.
+    pub iterations: Option<usize>,
+    /// Grammar text with calls already rewritten.
+    pub source: String,
+}
+
+/// A project split into its main grammar and named sections.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ComposedGrammar {
+    /// Main grammar text with calls already rewritten.
+    pub main: String,
+    pub sections: Vec<GrammarSection>,
+    /// Whether brackets are marked for branch-scoped commands (see
+    /// [`scope`](crate::logic::scope)).
+    pub scoped: bool,
+}
+
+impl ComposedGrammar {
+    /// Rewrites calls in other text (such as the finalization rules) against
+    /// this project's section names.
+    pub fn rewrite(&self, text: &str) -> Result<String, String> {
+        let names: Vec<&str> = self.sections.iter().map(|s| s.name.as_str()).collect();
+        rewrite_lines(text, &names, 0, self.scoped || uses_branch_scopes(text))
+    }
+}
+
+/// Returns the section index called by a prop ID, if it is a reserved call ID.
+pub fn call_index(prop_id: u16) -> Option<usize> {
+    prop_id.checked_sub(CALL_PROP_BASE).map(usize::from)
+}
+
+/// Splits `source` into the main grammar and its `#system` sections, rewriting
+/// all calls. Errors report line numbers of `source`.
+pub fn split_systems(source: &str) -> Result<ComposedGrammar, String> {
+    let lines: Vec<&str> = source.lines().collect();
+
+    // Collect section headers first so calls may refer to later sections
+    let mut headers = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if let Some(header) = parse_system_header(line.trim()) {
+            let (name, iterations) = header.map_err(|e| format!("Line {}: {}", i + 1, e))?;
+            if headers
+                .iter()
+                .any(|(_, n, _): &(usize, String, _)| *n == name)
+            {
+                return Err(format!("Line {}: Duplicate system @{}", i + 1, name));
+            }
+            headers.push((i, name, iterations));
+        }
+    }
+    let scoped = uses_branch_scopes(source);
+    if headers.is_empty() && !source.contains(CALL_PREFIX) && !scoped {
+        return Ok(ComposedGrammar {
+            main: source.to_string(),
+            sections: Vec::new(),
+            scoped,
+        });
+    }
+    if headers.len() > usize::from(u16::MAX - CALL_PROP_BASE) {
+        return Err("Too many #system sections".to_string());
+    }
+    let names: Vec<&str> = headers.iter().map(|(_, name, _)| name.as_str()).collect();
+
+    let main_end = headers.first().map_or(lines.len(), |(line, _, _)| *line);
+    let main = rewrite_lines(&lines[..main_end].join("\n"), &names, 0, scoped)?;
+
+    let mut sections = Vec::with_capacity(headers.len());
+    for (index, (start, name, iterations)) in headers.iter().enumerate() {
+        let end = headers
+            .get(index + 1)
+            .map_or(lines.len(), |(line, _, _)| *line);
+        let body = lines[start + 1..end].join("\n");
+        sections.push(GrammarSection {
+            name: name.clone(),
+            iterations: *iterations,
+            source: rewrite_lines(&body, &names, start + 1, scoped)?,
+        });
+    }
+    Ok(ComposedGrammar {
+        main,
+        sections,
+        scoped,
+    })
+}
+
+/// Whether `source` defines any `#system` section, the unit that parallel
+/// derivation spreads across threads.
+pub fn has_sections(source: &str) -> bool {
+    source
+        .lines()
+        .any(|line| parse_system_header(line.trim()).is_some())
+}
+
+/// Parses a `#system NAME [ITERATIONS]` line. Returns `None` for other lines.
+fn parse_system_header(line: &str) -> Option<Result<(String, Option<usize>), String>> {
+    let rest = line.strip_prefix(SYSTEM_DIRECTIVE)?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let mut parts = rest.split_whitespace();
+    let parsed = (|| {
+        let name = parts
+            .next()
+            .filter(|name| is_identifier(name))
+            .ok_or_else(|| "Expected #system NAME [ITERATIONS]".to_string())?;
+        let iterations = parts
+            .next()
+            .map(|n| {
+                n.parse::<usize>()
+                    .map_err(|_| format!("Invalid iteration count '{}'", n))
+            })
+            .transpose()?;
+        if parts.next().is_some() {
+            return Err("Expected #system NAME [ITERATIONS]".to_string());
+        }
+        Ok((name.to_string(), iterations))
+    })();
+    Some(parsed)
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Rewrites the calls on every non-comment line and the jitter symbols in
+/// successors and axioms, and marks the brackets if `scoped`. Text that ends
+/// up with markers also gets [`IGNORE_MARKERS`]. `line_offset` is the number
+/// of source lines preceding `text`, for error messages.
+fn rewrite_lines(
+    text: &str,
+    names: &[&str],
+    line_offset: usize,
+    scoped: bool,
+) -> Result<String, String> {
+    let mut out = String::with_capacity(text.len());
+    let mut marked = false;
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().starts_with("//") {
+            out.push_str(line);
+        } else {
+            let (head, modules) = line.split_at(modules_start(line).unwrap_or(line.len()));
+            let jittered = rewrite_jitter(modules)
+                .map_err(|e| format!("Line {}: {}", line_offset + i + 1, e))?;
+            marked |= jittered != modules;
+            let mut rewritten = rewrite_calls(&format!("{}{}", head, jittered), names)
+                .map_err(|e| format!("Line {}: {}", line_offset + i + 1, e))?;
+            if scoped {
+                let bracketed = rewrite_brackets(&rewritten);
+                marked |= bracketed != rewritten;
+                rewritten = bracketed;
+            }
+            out.push_str(&rewritten);
+        }
+        out.push('\n');
+    }
+    if marked {
+        out.push_str(IGNORE_MARKERS);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Rewrites `@name` and `@name(scale)` into reserved prop modules.
+fn rewrite_calls(line: &str, names: &[&str]) -> Result<String, String> {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(at) = rest.find(CALL_PREFIX) {
+        out.push_str(&rest[..at]);
+        let after = &rest[at + CALL_PREFIX.len_utf8()..];
+        let name_len = after
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(after.len());
+        let name = &after[..name_len];
+        let index = names
+            .iter()
+            .position(|n| *n == name)
+            .ok_or_else(|| format!("Unknown system @{}", name))?;
+        let id = CALL_PROP_BASE + index as u16;
+
+        rest = &after[name_len..];
+        match rest.strip_prefix('(') {
+            Some(args) => {
+                // Scale argument may itself contain parentheses
+                let mut depth = 1;
+                let close = args
+                    .char_indices()
+                    .find(|&(_, c)| {
+                        match c {
+                            '(' => depth += 1,
+                            ')' => depth -= 1,
+                            _ => {}
+                        }
+                        depth == 0
+                    })
+                    .map(|(i, _)| i)
+                    .ok_or_else(|| format!("Unclosed argument list for @{}", name))?;
+                out.push_str(&format!("~({}, {})", id, args[..close].trim()));
+                rest = &args[close + 1..];
+            }
+            None => out.push_str(&format!("~({}, 1)", id)),
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Replaces every call prop in `skeleton` with the called grammar's skeleton,
+/// placed at the call's position and orientation and scaled by its scale.
+///
+/// `sections[i]` is the interpreted skeleton of section `i`, or `None` if it
+/// failed to derive. Calls nested inside sections are resolved up to
+/// [`MAX_GRAFT_DEPTH`] levels deep; deeper calls and calls to missing sections
+/// are dropped.
+pub fn graft_calls(skeleton: &mut Skeleton, sections: &[Option<Skeleton>]) {
+    graft_calls_pooled(skeleton, sections, &mut StrandPool::default());
+}
+
+/// [`graft_calls`], copying grafted strands into buffers from `pool`.
+pub fn graft_calls_pooled(
+    skeleton: &mut Skeleton,
+    sections: &[Option<Skeleton>],
+    pool: &mut StrandPool,
+) {
+    if !skeleton
+        .props
+        .iter()
+        .any(|prop| call_index(prop.prop_id).is_some())
+    {
+        return;
+    }
+    let (calls, props): (Vec<_>, Vec<_>) = std::mem::take(&mut skeleton.props)
+        .into_iter()
+        .partition(|prop| call_index(prop.prop_id).is_some());
+    skeleton.props = props;
+
+    for call in calls {
+        let frame = GraftFrame {
+            position: call.position,
+            rotation: call.rotation,
+            scale: call.scale.max_element(),
+        };
+        graft_section(skeleton, sections, call.prop_id, frame, 1, pool);
+    }
+}
+
+/// Placement of a grafted skeleton in the caller's space.
+#[derive(Clone, Copy)]
+struct GraftFrame {
+    position: bevy::math::Vec3,
+    rotation: bevy::math::Quat,
+    scale: f32,
+}
+
+impl GraftFrame {
+    fn point(&self, local: bevy::math::Vec3) -> bevy::math::Vec3 {
+        self.position + self.rotation * (local * self.scale)
+    }
+}
+
+fn graft_section(
+    target: &mut Skeleton,
+    sections: &[Option<Skeleton>],
+    prop_id: u16,
+    frame: GraftFrame,
+    depth: usize,
+    pool: &mut StrandPool,
+) {
+    let Some(section) = call_index(prop_id).and_then(|i| sections.get(i)?.as_ref()) else {
+        return;
+    };
+    if depth > MAX_GRAFT_DEPTH {
+        return;
+    }
+
+    for strand in &section.strands {
+        let mut strand = pool.copy_of(strand);
+        for point in &mut strand {
+            point.position = frame.point(point.position);
+            point.rotation = frame.rotation * point.rotation;
+            point.radius *= frame.scale;
+        }
+        target.strands.push(strand);
+    }
+
+    for prop in &section.props {
+        let position = frame.point(prop.position);
+        let rotation = frame.rotation * prop.rotation;
+        let scale = prop.scale * frame.scale;
+        if call_index(prop.prop_id).is_some() {
+            let nested = GraftFrame {
+                position,
+                rotation,
+                scale: scale.max_element(),
+            };
+            graft_section(target, sections, prop.prop_id, nested, depth + 1, pool);
+        } else {
+            let mut prop = *prop;
+            prop.position = position;
+            prop.rotation = rotation;
+            prop.scale = scale;
+            target.props.push(prop);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_systems_rewrites_calls() {
+        let grammar = split_systems(
+            "omega: A\np1: A -> F @leaf [ + @flower(0.5 * 2) ]\n\
+             #system flower 3\nomega: P\np1: P -> F @leaf\n\
+             #system leaf\n// @ not a call\nomega: L",
+        )
+        .unwrap();
+        assert_eq!(
+            grammar.main,
+            "omega: A\np1: A -> F ~(60001, 1) [ + ~(60000, 0.5 * 2) ]\n"
+        );
+        assert_eq!(grammar.sections.len(), 2);
+        assert_eq!(grammar.sections[0].name, "flower");
+        assert_eq!(grammar.sections[0].iterations, Some(3));
+        assert_eq!(
+            grammar.sections[0].source,
+            "omega: P\np1: P -> F ~(60001, 1)\n"
+        );
+        assert_eq!(grammar.sections[1].iterations, None);
+        assert_eq!(grammar.sections[1].source, "// @ not a call\nomega: L\n");
+        assert_eq!(call_index(60001), Some(1));
+        assert_eq!(call_index(3), None);
+    }
+
+    #[test]
+    fn test_split_systems_errors() {
+        let err = split_systems("omega: A\np1: A -> @missing").unwrap_err();
+        assert!(err.starts_with("Line 2:"), "{}", err);
+        assert!(err.contains("Unknown system @missing"), "{}", err);
+
+        let err = split_systems("#system a\n#system a").unwrap_err();
+        assert!(err.contains("Duplicate"), "{}", err);
+        assert!(split_systems("#system 9lives").is_err());
+        assert!(split_systems("#system a x").is_err());
+        assert!(split_systems("#system a\np1: A -> @a(1").is_err());
+
+        // Sources without sections or calls pass through untouched
+        let plain = "omega: A\n#define step 1";
+        assert_eq!(split_systems(plain).unwrap().main, plain);
+        assert!(!has_sections(plain));
+        assert!(has_sections("omega: @a\n#system a\nomega: F"));
+        assert!(!has_sections("#systemic x"));
+        // Jitter symbols are rewritten even without sections, and scope the
+        // brackets
+        assert_eq!(
+            split_systems("omega: F ?(0.5) F").unwrap().main,
+            "omega: F ~(59999, 0.5) F\n#ignore: ~\n"
+        );
+        assert_eq!(
+            split_systems("omega: F [ ?(0.5) F ]").unwrap().main,
+            "omega: F [~(59998, 1) ~(59999, 0.5) F ~(59997, 1)]\n#ignore: ~\n"
+        );
+        // Only successors and axioms are rewritten
+        assert_eq!(
+            split_systems("p1: A(x) : x > 0 -> F ?(x) A(x)\n// Why? [ ]")
+                .unwrap()
+                .main,
+            "p1: A(x) : x > 0 -> F ~(59999, x) A(x)\n// Why? [ ]\n#ignore: ~\n"
+        );
+        assert!(!split_systems("// Why? [ ]\nomega: F").unwrap().scoped);
+    }
+}
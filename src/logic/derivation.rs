@@ -1,10 +1,20 @@
 use crate::core::config::{
-    CancellationFlag, DerivationResult, DerivationStatus, DerivationTask, DirtyFlags,
-    LSystemAnalysis, LSystemConfig, LSystemEngine, MaterialSettingsMap, scan_max_material_id,
+    CancellationFlag, DerivationProgress, DerivationResult, DerivationStatus, DerivationTask,
+    DirtyFlags, LSystemAnalysis, LSystemConfig, LSystemEngine, MaterialSettingsMap,
+    SubSystemEngines, scan_max_material_id,
 };
+use crate::core::history::{DerivationHistory, HistoryEntry};
+use crate::core::includes::IncludeLibrary;
+use crate::logic::composition::split_systems;
+use crate::logic::describe::module_usage;
+use crate::logic::growth::{GrowthStep, interpolate_rules};
+use crate::logic::lint::{LintWarning, lint_grammar};
+use crate::logic::memory::{MemoryModel, memory_cap_error};
+use crate::logic::timed;
 use bevy::prelude::*;
 use bevy::tasks::AsyncComputeTaskPool;
 use std::sync::atomic::Ordering;
+use std::sync::mpsc::{self, Sender};
 use std::sync::{Arc, Mutex};
 use symbios::System;
 
@@ -14,6 +24,7 @@ pub fn start_derivation(
     mut config: ResMut<LSystemConfig>,
     mut task: ResMut<DerivationTask>,
     mut status: ResMut<DerivationStatus>,
+    includes: Res<IncludeLibrary>,
 ) {
     if !config.recompile_requested {
         return;
@@ -21,6 +32,7 @@ pub fn start_derivation(
     config.recompile_requested = false;
     status.error = None;
     status.generating = true;
+    status.progress = None;
 
     // Signal any in-progress task to cancel
     if let Some(old_flag) = &task.cancel_flag {
@@ -33,15 +45,46 @@ pub fn start_derivation(
 
     task.shared = Some(shared.clone());
     task.cancel_flag = Some(cancel_flag.clone());
-
-    let source = config.source_code.clone();
-    let finalization = config.finalization_code.clone();
-    let iterations = config.iterations;
+    task.snapshot = Some(HistoryEntry::from_config(&config));
+    let (progress, progress_receiver) = mpsc::channel();
+    task.progress = Some(Mutex::new(progress_receiver));
+
+    let (source, finalization, iterations) = match timed::resolve(
+        &config.source_code,
+        &config.finalization_code,
+        config.iterations,
+        config.clock,
+    ) {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            status.error = Some(e);
+            status.generating = false;
+            return;
+        }
+    };
+    let growth = (config.growth > 0.0).then_some(GrowthStep {
+        fraction: config.growth,
+        default_step: config.step_size,
+    });
     let seed = config.seed;
+    let parallel = config.parallel_derivation;
+    let memory_cap_mb = config.memory_cap_mb;
+    let includes = includes.clone();
 
     let pool = AsyncComputeTaskPool::get();
     pool.spawn(async move {
-        let result = perform_derivation(&source, &finalization, iterations, seed, &cancel_flag);
+        let result = perform_derivation(
+            &source,
+            &finalization,
+            &includes,
+            iterations,
+            growth,
+            seed,
+            &cancel_flag,
+            Some(&progress),
+            parallel,
+            memory_cap_mb,
+        );
         // Only store result if not cancelled
         if cancel_flag.load(Ordering::Relaxed)
             && let Ok(mut guard) = shared.lock()
@@ -54,14 +97,40 @@ pub fn start_derivation(
 
 /// Polls the async derivation task for completion.
 /// When done, updates the engine state and sets the geometry dirty flag.
+#[allow(clippy::too_many_arguments)]
 pub fn poll_derivation(
     mut engine: ResMut<LSystemEngine>,
+    mut subsystems: ResMut<SubSystemEngines>,
     mut task: ResMut<DerivationTask>,
     mut status: ResMut<DerivationStatus>,
     mut analysis: ResMut<LSystemAnalysis>,
     mut dirty: ResMut<DirtyFlags>,
     mut render_state: ResMut<crate::visuals::turtle::TurtleRenderState>,
+    mut history: ResMut<DerivationHistory>,
 ) {
+    if status.cancel_requested {
+        status.cancel_requested = false;
+        if let Some(flag) = task.cancel_flag.take() {
+            flag.store(false, Ordering::Relaxed);
+            info!("Derivation cancelled");
+        }
+        task.shared = None;
+        task.progress = None;
+        task.snapshot = None;
+        status.generating = false;
+        status.progress = None;
+        return;
+    }
+
+    // Keep only the latest progress report
+    if let Some(latest) = task
+        .progress
+        .as_ref()
+        .and_then(|receiver| receiver.lock().ok()?.try_iter().last())
+    {
+        status.progress = Some(latest);
+    }
+
     let Some(shared) = &task.shared else {
         return;
     };
@@ -73,14 +142,22 @@ pub fn poll_derivation(
     };
     drop(guard);
     task.shared = None;
+    task.progress = None;
     status.generating = false;
+    status.progress = None;
 
     match result {
         Ok(derivation) => {
             engine.0 = derivation.system;
+            subsystems.0 = derivation.subsystems;
             *analysis = derivation.analysis;
             render_state.derivation_time_ms = derivation.derivation_time_ms;
+            info!("Derived in {:.1} ms", derivation.derivation_time_ms);
             dirty.geometry = true;
+            if let Some(snapshot) = task.snapshot.take() {
+                status.last_seed = Some(snapshot.seed);
+                history.record(snapshot);
+            }
         }
         Err(err) => {
             status.error = Some(err);
@@ -113,6 +190,29 @@ pub fn ensure_material_palette_size(
     }
 }
 
+/// Derives a grammar on the calling thread, without `#include` buffers,
+/// progress reports, or a memory cap. For headless use outside the ECS.
+pub fn derive_blocking(
+    source: &str,
+    finalization: &str,
+    iterations: usize,
+    seed: u64,
+) -> Result<DerivationResult, String> {
+    let never_cancelled: CancellationFlag = Arc::new(std::sync::atomic::AtomicBool::new(true));
+    perform_derivation(
+        source,
+        finalization,
+        &IncludeLibrary::empty(),
+        iterations,
+        None,
+        seed,
+        &never_cancelled,
+        None,
+        false,
+        None,
+    )
+}
+
 /// Performs L-system parsing and derivation. Runs on a background thread.
 /// Checks the cancellation flag periodically and aborts early if cancelled.
 /// Implements two-pass derivation: growth phase followed by optional finalization/decomposition.
@@ -120,14 +220,42 @@ pub fn ensure_material_palette_size(
 /// NOTE: Always creates a fresh `System::new()` to guarantee clean derivation state.
 /// This prevents cumulative derivation issues where calling `sys.derive(n)` on an
 /// already-derived system would result in double-growth.
+///
+/// `#include` lines are expanded first, so parse errors report line numbers of
+/// the expanded text. `#system` sections are then split off and each derived
+/// on its own, with its own iteration count if given and a seed offset by its
+/// index.
+///
+/// With `growth`, a partial step follows the growth phase; see
+/// [`crate::logic::growth`].
+///
+/// After each growth step and the finalization pass, the main grammar's
+/// progress is sent to `progress`, if given. With `parallel`, the `#system`
+/// sections are derived on threads of their own. With `memory_cap_mb`, a
+/// derivation whose estimated memory exceeds the cap stops with an error.
+#[allow(clippy::too_many_arguments)]
 fn perform_derivation(
     source: &str,
     finalization: &str,
+    includes: &IncludeLibrary,
     iterations: usize,
+    growth: Option<GrowthStep>,
     seed: u64,
     cancel_flag: &CancellationFlag,
+    progress: Option<&Sender<DerivationProgress>>,
+    parallel: bool,
+    memory_cap_mb: Option<usize>,
 ) -> Result<DerivationResult, String> {
     let start_time = chrono::Utc::now();
+    let source = &includes.expand(source)?;
+    let finalization = &includes
+        .expand(finalization)
+        .map_err(|e| format!("Finalization: {}", e))?;
+    let grammar = split_systems(source)?;
+    let finalization = &grammar
+        .rewrite(finalization)
+        .map_err(|e| format!("Finalization: {}", e))?;
+    let source = &grammar.main;
     let mut sys = System::new();
     sys.set_seed(seed);
     let mut analysis = LSystemAnalysis::default();
@@ -136,6 +264,18 @@ fn perform_derivation(
     // Helper to check if we should abort
     let is_cancelled = || !cancel_flag.load(Ordering::Relaxed);
 
+    // A closed channel only means nobody is listening anymore
+    let report = |iteration: usize, finalized: bool, sys: &System| {
+        if let Some(progress) = progress {
+            let _ = progress.send(DerivationProgress {
+                iteration,
+                iterations,
+                finalized,
+                modules: sys.state.len(),
+            });
+        }
+    };
+
     let mut check_module = |symbol: &str, param_count: usize| {
         let step_syms = ["F", "f"];
         let turn_syms = ["+", "-", "&", "^", "/", "\\", "|"];
@@ -214,6 +354,48 @@ fn perform_derivation(
         }
     }
 
+    analysis.lints = lint_grammar(source, finalization);
+    analysis.usage = module_usage(source, finalization, iterations);
+    let memory = MemoryModel::new(
+        analysis
+            .usage
+            .rules
+            .iter()
+            .map(|rule| rule.rule.successor.as_str())
+            .chain(
+                source
+                    .lines()
+                    .filter_map(|line| line.trim().strip_prefix("omega:")),
+            ),
+        &analysis.usage,
+    );
+    let cap_bytes = memory_cap_mb.map(|mb| mb * 1024 * 1024);
+
+    // Deterministic grammars are known to outgrow the cap before deriving
+    if let Some(cap) = cap_bytes
+        && analysis.usage.exact
+    {
+        let modules: f64 = analysis.usage.symbols.iter().map(|(_, count)| count).sum();
+        let expected = memory.estimate(modules as usize);
+        if expected.total_bytes() > cap {
+            return Err(memory_cap_error(
+                &expected,
+                cap / (1024 * 1024),
+                iterations,
+                true,
+            ));
+        }
+    }
+    let check_cap = |sys: &System, iteration: usize| match cap_bytes {
+        Some(cap) if memory.estimate(sys.state.len()).total_bytes() > cap => Err(memory_cap_error(
+            &memory.estimate(sys.state.len()),
+            cap / (1024 * 1024),
+            iteration,
+            false,
+        )),
+        _ => Ok(()),
+    };
+
     if axiom_set {
         // Check cancellation before expensive derivation
         if is_cancelled() {
@@ -221,12 +403,30 @@ fn perform_derivation(
         }
 
         // === PHASE 1: Growth derivation ===
-        for _ in 0..iterations {
+        for iteration in 1..=iterations {
             if is_cancelled() {
                 return Err("Cancelled".to_string());
             }
             sys.derive(1)
                 .map_err(|e| format!("Derivation error: {}", e))?;
+            report(iteration, false, &sys);
+            check_cap(&sys, iteration)?;
+        }
+
+        // Partial step with the rules rewritten to grow in part
+        if let Some(growth) = growth {
+            if is_cancelled() {
+                return Err("Cancelled".to_string());
+            }
+            sys.rules.clear();
+            for rule in interpolate_rules(source, growth) {
+                sys.add_rule(&rule)
+                    .map_err(|e| format!("Growth rule error: {}", e))?;
+            }
+            sys.derive(1)
+                .map_err(|e| format!("Growth derivation error: {}", e))?;
+            report(iterations, false, &sys);
+            check_cap(&sys, iterations + 1)?;
         }
 
         // === PHASE 2: Finalization/Decomposition (if provided) ===
@@ -293,13 +493,81 @@ fn perform_derivation(
             // Execute single decomposition pass
             sys.derive(1)
                 .map_err(|e| format!("Finalization derivation error: {}", e))?;
+            report(iterations, true, &sys);
+            check_cap(&sys, iterations)?;
         }
     } else {
         return Err("No axiom defined".to_string());
     }
 
+    // Each sub-grammar is seeded by its index, so the results do not depend
+    // on the order they are derived in
+    let derive_section = |i: usize| {
+        let section = &grammar.sections[i];
+        perform_derivation(
+            &section.source,
+            "",
+            includes,
+            section.iterations.unwrap_or(iterations),
+            // Sections with their own iteration count do not grow along
+            growth.filter(|_| section.iterations.is_none()),
+            seed.wrapping_add(i as u64 + 1),
+            cancel_flag,
+            None,
+            false,
+            memory_cap_mb,
+        )
+        .map_err(|e| format!("System @{}: {}", section.name, e))
+    };
+    #[cfg(not(target_arch = "wasm32"))]
+    let derived_sections: Vec<Result<DerivationResult, String>> = if parallel {
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..grammar.sections.len())
+                .map(|i| scope.spawn(move || derive_section(i)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|_| Err("Derivation thread panicked".to_string()))
+                })
+                .collect()
+        })
+    } else {
+        (0..grammar.sections.len()).map(derive_section).collect()
+    };
+    // No threads on the web
+    #[cfg(target_arch = "wasm32")]
+    let derived_sections: Vec<Result<DerivationResult, String>> = {
+        let _ = parallel;
+        (0..grammar.sections.len()).map(derive_section).collect()
+    };
+
+    analysis.memory = memory.estimate(sys.state.len());
+
+    let mut subsystems = Vec::with_capacity(grammar.sections.len());
+    for (section, derived) in grammar.sections.iter().zip(derived_sections) {
+        let derived = derived?;
+        analysis.uses_implicit_step |= derived.analysis.uses_implicit_step;
+        analysis.uses_implicit_angle |= derived.analysis.uses_implicit_angle;
+        analysis.uses_explicit_width |= derived.analysis.uses_explicit_width;
+        analysis.max_material_id = analysis
+            .max_material_id
+            .max(derived.analysis.max_material_id);
+        analysis
+            .lints
+            .extend(derived.analysis.lints.into_iter().map(|lint| LintWarning {
+                message: format!("System @{}: {}", section.name, lint.message),
+                ..lint
+            }));
+        analysis.memory += derived.analysis.memory;
+        subsystems.push(derived.system);
+    }
+
     Ok(DerivationResult {
         system: sys,
+        subsystems,
         analysis,
         derivation_time_ms: (chrono::Utc::now() - start_time).num_milliseconds() as f32,
     })
@@ -0,0 +1,726 @@
+//! Human-readable documentation of a grammar.
+//!
+//! [`describe_grammar`] summarizes the axiom, rules, constants, and symbols of
+//! a grammar, and estimates how large it grows, for the "Describe" panel and
+//! its Markdown export. Works on the source text, so it also describes
+//! grammars that do not compile yet.
+
+use crate::core::genotype::rule_key;
+use crate::logic::rule_line::RuleLine;
+use bevy::platform::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+
+/// One production rule, split into its parts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleSummary {
+    /// Label such as `p1`, if the rule has one.
+    pub label: Option<String>,
+    /// Predecessor with its context and parameters, e.g. `B(x) < A`.
+    pub predecessor: String,
+    /// Non-stochastic condition, e.g. `x > 0`.
+    pub condition: Option<String>,
+    /// Chance of the rule applying; `None` when it depends on a condition.
+    pub probability: Option<f64>,
+    pub successor: String,
+}
+
+/// A symbol used by the grammar and what it does.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SymbolSummary {
+    pub symbol: String,
+    /// Occurrences in the axiom and rule successors.
+    pub count: usize,
+    pub meaning: String,
+}
+
+/// A `#system` sub-grammar.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SectionSummary {
+    pub name: String,
+    /// Iteration count; `None` uses the editor's count.
+    pub iterations: Option<usize>,
+    pub rules: usize,
+}
+
+/// Summary of a grammar, see [`describe_grammar`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GrammarDescription {
+    pub axiom: Option<String>,
+    pub rules: Vec<RuleSummary>,
+    pub finalization_rules: Vec<RuleSummary>,
+    /// `#define` constants as written, in order.
+    pub constants: Vec<(String, String)>,
+    /// Symbols skipped during context matching (`#ignore`).
+    pub ignored: Option<String>,
+    pub symbols: Vec<SymbolSummary>,
+    pub sections: Vec<SectionSummary>,
+    pub iterations: usize,
+    /// Expected number of modules after derivation and finalization.
+    pub estimated_modules: f64,
+    /// Expected number of drawn segments (`F`).
+    pub estimated_segments: f64,
+}
+
+/// Meaning of the built-in turtle symbols.
+pub(crate) fn turtle_meaning(symbol: &str) -> Option<&'static str> {
+    Some(match symbol {
+        "F" => "Move forward, drawing a branch segment",
+        "f" => "Move forward without drawing",
+        "+" => "Turn left (yaw)",
+        "-" => "Turn right (yaw)",
+        "&" => "Pitch down",
+        "^" => "Pitch up",
+        "/" => "Roll clockwise",
+        "\\" => "Roll counter-clockwise",
+        "|" => "Turn around (180 degrees)",
+        "$" => "Roll to vertical",
+        "[" => "Push state (start a branch)",
+        "]" => "Pop state (end a branch)",
+        "!" => "Set branch width",
+        "?" => "Jitter later angles and lengths",
+        "," => "Switch material",
+        "'" => "Set vertex color",
+        "~" => "Spawn a prop",
+        _ if symbol.starts_with('@') => "Graft a #system sub-grammar",
+        _ => return None,
+    })
+}
+
+/// Symbol names of the modules in a successor or axiom, parameters dropped.
+/// Identifiers (`Fr`, `apex`) and `@name` calls are single symbols.
+pub fn module_symbols(text: &str) -> Vec<String> {
+    module_arities(text)
+        .into_iter()
+        .map(|(symbol, _)| symbol)
+        .collect()
+}
+
+/// Modules in a successor or axiom with their number of parameters.
+pub fn module_arities(text: &str) -> Vec<(String, usize)> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut modules = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        i += 1;
+        if c == '@' || c.is_alphabetic() {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+        }
+        let symbol = chars[start..i].iter().collect();
+
+        // Count the top-level arguments of the parameter list
+        let mut arity = 0;
+        if i < chars.len() && chars[i] == '(' {
+            let mut depth = 0;
+            let mut empty = true;
+            while i < chars.len() {
+                match chars[i] {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    ',' if depth == 1 => arity += 1,
+                    c if !c.is_whitespace() => empty = false,
+                    _ => {}
+                }
+                i += 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            if !empty {
+                arity += 1;
+            }
+        }
+        modules.push((symbol, arity));
+    }
+    modules
+}
+
+/// One module of a successor, as byte ranges of the text.
+pub(crate) struct ModuleSpan<'a> {
+    pub symbol: &'a str,
+    /// Parameter list without the parentheses.
+    pub args: Option<&'a str>,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Splits a successor, axiom, or predecessor into modules with their spans. Identifiers such as
+/// `Fr` are one symbol, like in the parser.
+pub(crate) fn module_spans(text: &str) -> Vec<ModuleSpan<'_>> {
+    let bytes = text.as_bytes();
+    let mut spans = Vec::new();
+    let mut i = 0;
+    while i < text.len() {
+        let c = text[i..].chars().next().unwrap_or(' ');
+        if c.is_whitespace() {
+            i += c.len_utf8();
+            continue;
+        }
+        let start = i;
+        i += c.len_utf8();
+        if c == '@' || c.is_alphabetic() {
+            while i < text.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                i += 1;
+            }
+        }
+        let symbol = &text[start..i];
+
+        let mut args = None;
+        if bytes.get(i) == Some(&b'(') {
+            let mut depth = 0;
+            let open = i;
+            while i < text.len() {
+                match bytes[i] {
+                    b'(' => depth += 1,
+                    b')' => depth -= 1,
+                    _ => {}
+                }
+                i += 1;
+                if depth == 0 {
+                    args = Some(&text[open + 1..i - 1]);
+                    break;
+                }
+            }
+            // Unclosed list: leave the rest to the parser's error
+            if args.is_none() {
+                i = text.len();
+            }
+        }
+        spans.push(ModuleSpan {
+            symbol,
+            args,
+            start,
+            end: i,
+        });
+    }
+    spans
+}
+
+/// First argument of a parameter list and the rest, with its leading comma.
+pub(crate) fn split_first_arg(args: &str) -> (&str, &str) {
+    let mut depth = 0;
+    for (i, c) in args.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => return (&args[..i], &args[i..]),
+            _ => {}
+        }
+    }
+    (args, "")
+}
+
+/// Splits a rule line into its parts; `None` for anything but a rule.
+pub(crate) fn parse_rule(line: &str) -> Option<RuleSummary> {
+    let rule = RuleLine::parse(line)?;
+    // Rules without a condition or probability always apply
+    let probability = match (rule.condition, &rule.probability) {
+        (Some(_), _) => None,
+        (None, Some((p, _))) => Some(*p),
+        (None, None) => Some(1.0),
+    };
+    Some(RuleSummary {
+        label: rule.label.map(str::to_string),
+        predecessor: rule.predecessor.to_string(),
+        condition: rule.condition.map(str::to_string),
+        probability,
+        successor: rule.successor.to_string(),
+    })
+}
+
+/// Parses `#system NAME [N]`.
+fn parse_section_header(line: &str) -> Option<SectionSummary> {
+    let mut parts = line.strip_prefix("#system")?.split_whitespace();
+    Some(SectionSummary {
+        name: parts.next()?.to_string(),
+        iterations: parts.next().and_then(|n| n.parse().ok()),
+        rules: 0,
+    })
+}
+
+/// Successor symbols of each predecessor, with the index and chance of each
+/// rule.
+type RuleTable = HashMap<String, Vec<(usize, f64, Vec<String>)>>;
+
+/// Groups rules by predecessor symbol. Stochastic rules apply with their
+/// probability; the other rules of a symbol (conditional or not) share
+/// whatever chance is left over.
+fn rule_table(rules: &[RuleSummary]) -> RuleTable {
+    let mut grouped: HashMap<String, Vec<(usize, &RuleSummary)>> = HashMap::new();
+    for (index, rule) in rules.iter().enumerate() {
+        let key = rule_key(&format!("{} -> {}", rule.predecessor, rule.successor));
+        if let Some(key) = key {
+            grouped.entry(key).or_default().push((index, rule));
+        }
+    }
+    let stochastic = |rule: &RuleSummary| rule.probability.filter(|&p| p < 1.0);
+    grouped
+        .into_iter()
+        .map(|(key, rules)| {
+            let chance: f64 = rules.iter().filter_map(|(_, rule)| stochastic(rule)).sum();
+            let others = rules
+                .iter()
+                .filter(|(_, rule)| stochastic(rule).is_none())
+                .count();
+            let share = (1.0 - chance).max(0.0) / others.max(1) as f64;
+            let weighted = rules
+                .iter()
+                .map(|&(index, rule)| {
+                    (
+                        index,
+                        stochastic(rule).unwrap_or(share),
+                        module_symbols(&rule.successor),
+                    )
+                })
+                .collect();
+            (key, weighted)
+        })
+        .collect()
+}
+
+/// Applies one expected rewriting step to symbol counts, adding the expected
+/// applications of each rule to `firings`. Symbols without rules, and the
+/// chance left over by their rules, are copied unchanged.
+fn rewrite_counts(
+    counts: &HashMap<String, f64>,
+    table: &RuleTable,
+    firings: &mut [f64],
+) -> HashMap<String, f64> {
+    let mut next: HashMap<String, f64> = HashMap::new();
+    for (symbol, &count) in counts {
+        let rules = table.get(symbol).map(Vec::as_slice).unwrap_or_default();
+        let mut remaining = 1.0;
+        for (index, weight, successor) in rules {
+            remaining -= weight;
+            firings[*index] += count * weight;
+            for produced in successor {
+                *next.entry(produced.clone()).or_default() += count * weight;
+            }
+        }
+        if remaining > 1e-9 {
+            *next.entry(symbol.clone()).or_default() += count * remaining;
+        }
+    }
+    next
+}
+
+/// Documents a grammar: `source` is the growth grammar, `finalization` the
+/// optional decomposition rules applied once after `iterations` steps.
+///
+/// The size estimate follows expected symbol counts: stochastic rules are
+/// weighted by their probability, while parametric conditions and contexts
+/// are assumed to match as often as each other.
+pub fn describe_grammar(source: &str, finalization: &str, iterations: usize) -> GrammarDescription {
+    let mut description = GrammarDescription {
+        iterations,
+        ..Default::default()
+    };
+
+    for line in source.lines().map(str::trim) {
+        if let Some(section) = parse_section_header(line) {
+            description.sections.push(section);
+        } else if let Some(section) = description.sections.last_mut() {
+            // Lines after a #system header belong to that sub-grammar
+            if parse_rule(line).is_some() {
+                section.rules += 1;
+            }
+        } else if let Some(axiom) = line.strip_prefix("omega:") {
+            description.axiom = Some(axiom.trim().to_string());
+        } else if let Some(define) = line.strip_prefix("#define") {
+            let mut parts = define.trim().splitn(2, char::is_whitespace);
+            if let Some(name) = parts.next().filter(|name| !name.is_empty()) {
+                let value = parts.next().unwrap_or_default().trim();
+                description
+                    .constants
+                    .push((name.to_string(), value.to_string()));
+            }
+        } else if let Some(ignored) = line.strip_prefix("#ignore:") {
+            description.ignored = Some(ignored.trim().to_string());
+        } else if let Some(rule) = parse_rule(line) {
+            description.rules.push(rule);
+        }
+    }
+    description.finalization_rules = finalization.lines().filter_map(parse_rule).collect();
+
+    // Symbols in order of first use
+    let growth = rule_table(&description.rules);
+    let decomposition = rule_table(&description.finalization_rules);
+    let axiom = description.axiom.as_deref().unwrap_or_default();
+    let used = std::iter::once(axiom)
+        .chain(description.rules.iter().map(|rule| rule.successor.as_str()))
+        .chain(
+            description
+                .finalization_rules
+                .iter()
+                .map(|rule| rule.successor.as_str()),
+        )
+        .flat_map(module_symbols);
+    for symbol in used {
+        match description.symbols.iter_mut().find(|s| s.symbol == symbol) {
+            Some(summary) => summary.count += 1,
+            None => {
+                let meaning = match turtle_meaning(&symbol) {
+                    Some(meaning) => meaning.to_string(),
+                    None if growth.contains_key(&symbol) => "Rewritten by the rules".to_string(),
+                    None if decomposition.contains_key(&symbol) => {
+                        "Decomposed by the finalization rules".to_string()
+                    }
+                    None => "No turtle action (marker)".to_string(),
+                };
+                description.symbols.push(SymbolSummary {
+                    symbol,
+                    count: 1,
+                    meaning,
+                });
+            }
+        }
+    }
+
+    let counts = expected_usage(&description, &growth, &decomposition).symbols;
+    description.estimated_modules = counts.values().sum();
+    description.estimated_segments = counts.get("F").copied().unwrap_or_default();
+    description
+}
+
+/// Expected symbol counts after derivation, and applications of each growth
+/// and finalization rule (in that order) over the whole derivation.
+struct ExpectedUsage {
+    symbols: HashMap<String, f64>,
+    firings: Vec<f64>,
+}
+
+fn expected_usage(
+    description: &GrammarDescription,
+    growth: &RuleTable,
+    decomposition: &RuleTable,
+) -> ExpectedUsage {
+    let growth_rules = description.rules.len();
+    let mut firings = vec![0.0; growth_rules + description.finalization_rules.len()];
+    let mut counts: HashMap<String, f64> = HashMap::new();
+    for symbol in module_symbols(description.axiom.as_deref().unwrap_or_default()) {
+        *counts.entry(symbol).or_default() += 1.0;
+    }
+    for _ in 0..description.iterations {
+        counts = rewrite_counts(&counts, growth, &mut firings[..growth_rules]);
+    }
+    if !decomposition.is_empty() {
+        counts = rewrite_counts(&counts, decomposition, &mut firings[growth_rules..]);
+    }
+    ExpectedUsage {
+        symbols: counts,
+        firings,
+    }
+}
+
+/// How often one rule applies during a derivation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleUsage {
+    pub rule: RuleSummary,
+    /// Whether this is a finalization rule.
+    pub finalization: bool,
+    pub firings: f64,
+}
+
+/// Symbol counts of a derived string and rule applications that led to it,
+/// see [`module_usage`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ModuleUsage {
+    /// Symbols by descending count.
+    pub symbols: Vec<(String, f64)>,
+    pub rules: Vec<RuleUsage>,
+    /// Whether the counts are exact rather than expected values: only when
+    /// no rule is stochastic, conditional, or context-sensitive.
+    pub exact: bool,
+}
+
+/// Counts the symbols after `iterations` steps and finalization, and how
+/// often each rule applies on the way, following the same expected counts as
+/// the size estimate of [`describe_grammar`].
+pub fn module_usage(source: &str, finalization: &str, iterations: usize) -> ModuleUsage {
+    let description = describe_grammar(source, finalization, iterations);
+    let growth = rule_table(&description.rules);
+    let decomposition = rule_table(&description.finalization_rules);
+    let usage = expected_usage(&description, &growth, &decomposition);
+
+    let mut symbols: Vec<(String, f64)> = usage
+        .symbols
+        .into_iter()
+        .filter(|(_, count)| *count > 1e-9)
+        .collect();
+    symbols.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let growth_rules = description.rules.len();
+    let all_rules = description
+        .rules
+        .into_iter()
+        .chain(description.finalization_rules);
+    let exact = |rule: &RuleSummary| {
+        rule.probability == Some(1.0)
+            && !rule.predecessor.contains('<')
+            && !rule.predecessor.contains('>')
+    };
+    let mut keys = HashSet::new();
+    let mut rules = Vec::new();
+    let mut all_exact = true;
+    for (index, (rule, firings)) in all_rules.zip(usage.firings).enumerate() {
+        let finalization = index >= growth_rules;
+        // A second rule for the same symbol shares its applications
+        let key = rule_key(&format!("{} -> {}", rule.predecessor, rule.successor));
+        all_exact &= exact(&rule) && keys.insert((finalization, key));
+        rules.push(RuleUsage {
+            rule,
+            finalization,
+            firings,
+        });
+    }
+    ModuleUsage {
+        symbols,
+        rules,
+        exact: all_exact,
+    }
+}
+
+/// Formats a large count compactly, e.g. `1,234` or `3.4e9`.
+pub fn format_count(count: f64) -> String {
+    if count >= 1e9 {
+        return format!("{:.1e}", count);
+    }
+    let digits = format!("{:.0}", count);
+    let mut grouped = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// Escapes text for a Markdown table cell inside backticks.
+fn code_cell(text: &str) -> String {
+    if text.is_empty() {
+        return "*(empty)*".to_string();
+    }
+    format!("`{}`", text.replace('|', "\\|"))
+}
+
+fn write_rule_table(out: &mut String, rules: &[RuleSummary]) {
+    let _ = writeln!(
+        out,
+        "| Rule | Predecessor | Condition | Chance | Successor |"
+    );
+    let _ = writeln!(
+        out,
+        "|------|-------------|-----------|--------|-----------|"
+    );
+    for rule in rules {
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} | {} |",
+            rule.label.as_deref().unwrap_or("—"),
+            code_cell(&rule.predecessor),
+            rule.condition.as_deref().map_or("—".to_string(), code_cell),
+            rule.probability
+                .map_or("—".to_string(), |p| format!("{:.0}%", p * 100.0)),
+            code_cell(&rule.successor),
+        );
+    }
+}
+
+impl GrammarDescription {
+    /// The description as a Markdown document.
+    pub fn to_markdown(&self, title: &str) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# {}\n", title);
+
+        let _ = writeln!(out, "## Axiom\n");
+        match &self.axiom {
+            Some(axiom) => {
+                let _ = writeln!(out, "{}\n", code_cell(axiom));
+            }
+            None => {
+                let _ = writeln!(out, "*No axiom (`omega:` line) defined.*\n");
+            }
+        }
+
+        let _ = writeln!(out, "## Rules\n");
+        if self.rules.is_empty() {
+            let _ = writeln!(out, "*No rules.*\n");
+        } else {
+            write_rule_table(&mut out, &self.rules);
+            let _ = writeln!(out);
+        }
+        if !self.finalization_rules.is_empty() {
+            let _ = writeln!(out, "## Finalization Rules\n");
+            let _ = writeln!(out, "Applied once after the growth iterations.\n");
+            write_rule_table(&mut out, &self.finalization_rules);
+            let _ = writeln!(out);
+        }
+
+        if !self.constants.is_empty() {
+            let _ = writeln!(out, "## Constants\n");
+            let _ = writeln!(out, "| Name | Value |");
+            let _ = writeln!(out, "|------|-------|");
+            for (name, value) in &self.constants {
+                let _ = writeln!(out, "| `{}` | {} |", name, code_cell(value));
+            }
+            let _ = writeln!(out);
+        }
+
+        let _ = writeln!(out, "## Symbols\n");
+        if let Some(ignored) = &self.ignored {
+            let _ = writeln!(out, "Context matching ignores {}.\n", code_cell(ignored));
+        }
+        let _ = writeln!(out, "| Symbol | Uses | Meaning |");
+        let _ = writeln!(out, "|--------|------|---------|");
+        for symbol in &self.symbols {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} |",
+                code_cell(&symbol.symbol),
+                symbol.count,
+                symbol.meaning
+            );
+        }
+        let _ = writeln!(out);
+
+        if !self.sections.is_empty() {
+            let _ = writeln!(out, "## Sub-systems\n");
+            for section in &self.sections {
+                let iterations = section
+                    .iterations
+                    .map_or("editor iterations".to_string(), |n| {
+                        format!("{} iterations", n)
+                    });
+                let _ = writeln!(
+                    out,
+                    "- `{}` — {} rule{}, {}",
+                    section.name,
+                    section.rules,
+                    if section.rules == 1 { "" } else { "s" },
+                    iterations
+                );
+            }
+            let _ = writeln!(out);
+        }
+
+        let _ = writeln!(out, "## Estimated Size\n");
+        let _ = writeln!(
+            out,
+            "After {} iterations: ≈ {} modules, ≈ {} drawn segments.",
+            self.iterations,
+            format_count(self.estimated_modules),
+            format_count(self.estimated_segments)
+        );
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_unlabelled_stochastic_rule() {
+        let rule = parse_rule("A:0.3->F").unwrap();
+        assert_eq!(rule.label, None);
+        assert_eq!(rule.predecessor, "A");
+        assert_eq!(rule.probability, Some(0.3));
+        assert_eq!(rule.successor, "F");
+
+        let rule = parse_rule("p1: A -> F").unwrap();
+        assert_eq!(rule.label.as_deref(), Some("p1"));
+        assert_eq!(rule.probability, Some(1.0));
+    }
+
+    #[test]
+    fn test_describe_rules_and_symbols() {
+        let source = "#define angle 25\n\
+                      #ignore: + -\n\
+                      omega: !(1)Fr A\n\
+                      p1: A : 0.7 -> F [ + A ] A\n\
+                      p2: A : 0.3 -> F A\n\
+                      p3: B(x) : x > 0 -> B(x-1)\n\
+                      #system leaf 2\n\
+                      omega: F\n\
+                      F -> FF";
+        let description = describe_grammar(source, "Fr -> F", 3);
+
+        assert_eq!(description.axiom.as_deref(), Some("!(1)Fr A"));
+        assert_eq!(
+            description.constants,
+            [("angle".to_string(), "25".to_string())]
+        );
+        assert_eq!(description.ignored.as_deref(), Some("+ -"));
+        assert_eq!(description.rules.len(), 3);
+        assert_eq!(description.rules[0].probability, Some(0.7));
+        assert_eq!(description.rules[2].condition.as_deref(), Some("x > 0"));
+        assert_eq!(description.rules[2].probability, None);
+        assert_eq!(
+            description.sections,
+            [SectionSummary {
+                name: "leaf".into(),
+                iterations: Some(2),
+                rules: 1
+            }]
+        );
+
+        let meaning = |symbol: &str| {
+            description
+                .symbols
+                .iter()
+                .find(|s| s.symbol == symbol)
+                .map(|s| s.meaning.as_str())
+        };
+        assert_eq!(meaning("A"), Some("Rewritten by the rules"));
+        assert_eq!(meaning("Fr"), Some("Decomposed by the finalization rules"));
+        assert_eq!(meaning("["), Some("Push state (start a branch)"));
+
+        let markdown = description.to_markdown("Test");
+        assert!(markdown.contains("| p1 | `A` | — | 70% | `F [ + A ] A` |"));
+        assert!(markdown.contains("- `leaf` — 1 rule, 2 iterations"));
+    }
+
+    #[test]
+    fn test_size_estimate() {
+        // F doubles each step, with one + between each pair
+        let description = describe_grammar("omega: F\np1: F -> F+F", "", 3);
+        assert_eq!(description.estimated_segments, 8.0);
+        assert_eq!(description.estimated_modules, 15.0);
+
+        // A grows with probability one half per step
+        let description = describe_grammar("omega: A\nA : 0.5 -> A F", "", 4);
+        assert!((description.estimated_segments - 2.0).abs() < 1e-9);
+
+        assert_eq!(
+            module_symbols("!(w)F(l)[&(a)B(l*r,w)]@leaf(2)"),
+            ["!", "F", "[", "&", "B", "]", "@leaf"]
+        );
+        assert_eq!(format_count(1234567.0), "1,234,567");
+    }
+
+    #[test]
+    fn test_module_usage() {
+        // F fires on 1 + 2 + 4 modules, then Fr decomposes the 8 left
+        let usage = module_usage("omega: F\np1: F -> F+F", "F -> Fr", 3);
+        assert!(usage.exact);
+        assert_eq!(
+            usage.symbols,
+            [("Fr".to_string(), 8.0), ("+".to_string(), 7.0)]
+        );
+        let firings: Vec<f64> = usage.rules.iter().map(|rule| rule.firings).collect();
+        assert_eq!(firings, [7.0, 8.0]);
+        assert!(usage.rules[1].finalization);
+
+        let usage = module_usage("omega: A\nA : 0.5 -> A F\nA : 0.5 -> A", "", 2);
+        assert!(!usage.exact);
+        assert_eq!(usage.rules[0].firings, 1.0);
+    }
+}
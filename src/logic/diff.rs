@@ -0,0 +1,146 @@
+//! Line diff of grammar sources, shown by the nursery inspector and the
+//! history panel.
+
+/// One line of a line-based diff.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffLine<'a> {
+    Same(&'a str),
+    Added(&'a str),
+    Removed(&'a str),
+}
+
+/// Line diff of `old` against `new` via longest common subsequence.
+pub fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+
+    // lcs[i][j] = length of the LCS of a[i..] and b[j..]
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = Vec::with_capacity(a.len().max(b.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            out.push(DiffLine::Same(a[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            out.push(DiffLine::Removed(a[i]));
+            i += 1;
+        } else {
+            out.push(DiffLine::Added(b[j]));
+            j += 1;
+        }
+    }
+    out.extend(a[i..].iter().map(|l| DiffLine::Removed(l)));
+    out.extend(b[j..].iter().map(|l| DiffLine::Added(l)));
+    out
+}
+
+/// One row of a side-by-side diff: the old line on the left, the new line on
+/// the right. Unchanged lines fill both sides; a changed line sits next to its
+/// replacement, and added or removed lines leave the other side empty.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DiffRow<'a> {
+    pub old: Option<&'a str>,
+    pub new: Option<&'a str>,
+    pub changed: bool,
+}
+
+/// Pairs the lines of `diff` into side-by-side rows: each run of removed lines
+/// is matched line by line with the added lines that follow it.
+pub fn side_by_side<'a>(diff: &[DiffLine<'a>]) -> Vec<DiffRow<'a>> {
+    let mut rows = Vec::with_capacity(diff.len());
+    let mut removed: Vec<&str> = Vec::new();
+    let mut added: Vec<&str> = Vec::new();
+    let flush =
+        |rows: &mut Vec<DiffRow<'a>>, removed: &mut Vec<&'a str>, added: &mut Vec<&'a str>| {
+            for k in 0..removed.len().max(added.len()) {
+                rows.push(DiffRow {
+                    old: removed.get(k).copied(),
+                    new: added.get(k).copied(),
+                    changed: true,
+                });
+            }
+            removed.clear();
+            added.clear();
+        };
+    for line in diff {
+        match *line {
+            DiffLine::Same(text) => {
+                flush(&mut rows, &mut removed, &mut added);
+                rows.push(DiffRow {
+                    old: Some(text),
+                    new: Some(text),
+                    changed: false,
+                });
+            }
+            DiffLine::Removed(text) => {
+                // A removal after additions starts a new change
+                if !added.is_empty() {
+                    flush(&mut rows, &mut removed, &mut added);
+                }
+                removed.push(text);
+            }
+            DiffLine::Added(text) => added.push(text),
+        }
+    }
+    flush(&mut rows, &mut removed, &mut added);
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines() {
+        let diff = diff_lines(
+            "omega: A\np1: A -> F A\n#define x 1",
+            "omega: A\np1: A -> F [ + A ] A\n#define x 1\n",
+        );
+        assert_eq!(
+            diff,
+            vec![
+                DiffLine::Same("omega: A"),
+                DiffLine::Removed("p1: A -> F A"),
+                DiffLine::Added("p1: A -> F [ + A ] A"),
+                DiffLine::Same("#define x 1"),
+            ]
+        );
+        assert_eq!(diff_lines("", "a"), vec![DiffLine::Added("a")]);
+        assert_eq!(diff_lines("a", ""), vec![DiffLine::Removed("a")]);
+    }
+
+    #[test]
+    fn test_side_by_side_pairs_changes() {
+        let diff = diff_lines(
+            "omega: A\np1: A -> F A\np2: B -> F\np3: C -> C",
+            "omega: A\np1: A -> F [+A] A\np3: C -> C\np4: D -> F",
+        );
+        let rows = side_by_side(&diff);
+        let pairs: Vec<_> = rows
+            .iter()
+            .map(|row| (row.old, row.new, row.changed))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                (Some("omega: A"), Some("omega: A"), false),
+                (Some("p1: A -> F A"), Some("p1: A -> F [+A] A"), true),
+                (Some("p2: B -> F"), None, true),
+                (Some("p3: C -> C"), Some("p3: C -> C"), false),
+                (None, Some("p4: D -> F"), true),
+            ]
+        );
+    }
+}
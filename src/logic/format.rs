@@ -0,0 +1,238 @@
+//! Grammar formatter behind the editor's **Format** button.
+//!
+//! Each grammar section (the main grammar and every `#system` section) is
+//! formatted on its own:
+//!
+//! - Rules are relabeled `p1`, `p2`, … in order, their whitespace is collapsed,
+//!   and their arrows are aligned.
+//! - `#define` lines are sorted by name where the first one stood, each with
+//!   the comments directly above it (such as `// @range` metadata). Defines
+//!   whose values are expressions keep their order, as they may refer to each
+//!   other.
+//! - Runs of blank lines collapse into one. Other lines keep their place, so
+//!   comments stay next to the rules they describe.
+//!
+//! The result is parsed again and must have the same rules, constants, and
+//! axiom as the input.
+
+use crate::logic::composition::SYSTEM_DIRECTIVE;
+use crate::logic::describe::parse_rule;
+
+/// Formats `source`; see the module docs. Fails if formatting would change the
+/// grammar.
+pub fn format_grammar(source: &str) -> Result<String, String> {
+    let mut out: Vec<String> = Vec::new();
+    let mut section: Vec<&str> = Vec::new();
+    for line in source.lines() {
+        if line.trim().starts_with(SYSTEM_DIRECTIVE) {
+            out.extend(format_section(&section));
+            section.clear();
+            if out.last().is_some_and(|last| !last.is_empty()) {
+                out.push(String::new());
+            }
+            out.push(squash(line));
+        } else {
+            section.push(line);
+        }
+    }
+    out.extend(format_section(&section));
+
+    let mut formatted = out.join("\n");
+    if source.ends_with('\n') && !formatted.is_empty() {
+        formatted.push('\n');
+    }
+    if signature(&formatted) != signature(source) {
+        return Err("Formatting would change the grammar".to_string());
+    }
+    Ok(formatted)
+}
+
+/// A `#define` line with the comments attached above it.
+struct Define {
+    name: String,
+    numeric: bool,
+    lines: Vec<String>,
+}
+
+fn parse_define(line: &str) -> Option<(String, bool)> {
+    let mut parts = line.trim().strip_prefix("#define")?.split_whitespace();
+    let name = parts.next()?.to_string();
+    let value: Vec<&str> = parts.collect();
+    Some((name, value.len() == 1 && value[0].parse::<f64>().is_ok()))
+}
+
+fn format_section(lines: &[&str]) -> Vec<String> {
+    enum Item {
+        Line(String),
+        Rule(String, String),
+        Defines,
+    }
+
+    let mut items: Vec<Item> = Vec::new();
+    let mut defines: Vec<Define> = Vec::new();
+    // Comments waiting to see whether a define follows them
+    let mut pending: Vec<String> = Vec::new();
+    let mut label = 0;
+
+    for line in lines {
+        let trimmed = line.trim();
+        if trimmed.starts_with("//") {
+            // Inside the define block comments belong to the next define;
+            // elsewhere only slider metadata does
+            let in_block = matches!(items.last(), Some(Item::Defines));
+            if in_block || trimmed.starts_with("// @") || !pending.is_empty() {
+                pending.push(trimmed.to_string());
+            } else {
+                items.push(Item::Line(trimmed.to_string()));
+            }
+            continue;
+        }
+        if let Some((name, numeric)) = parse_define(trimmed) {
+            if defines.is_empty() {
+                items.push(Item::Defines);
+            }
+            let mut define_lines = std::mem::take(&mut pending);
+            define_lines.push(squash(trimmed));
+            defines.push(Define {
+                name,
+                numeric,
+                lines: define_lines,
+            });
+            continue;
+        }
+        items.extend(pending.drain(..).map(Item::Line));
+
+        if trimmed.is_empty() {
+            if !matches!(items.last(), Some(Item::Line(last)) if last.is_empty()) {
+                items.push(Item::Line(String::new()));
+            }
+        } else if let Some(axiom) = trimmed.strip_prefix("omega:") {
+            items.push(Item::Line(format!("omega: {}", squash(axiom))));
+        } else if let Some((predecessor, condition, successor)) = rule_parts(trimmed) {
+            label += 1;
+            let lhs = match condition {
+                Some(condition) => format!("p{}: {} : {}", label, predecessor, condition),
+                None => format!("p{}: {}", label, predecessor),
+            };
+            items.push(Item::Rule(lhs, successor));
+        } else {
+            items.push(Item::Line(squash(trimmed)));
+        }
+    }
+    items.extend(pending.drain(..).map(Item::Line));
+
+    if defines.iter().all(|define| define.numeric) {
+        defines.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+    let width = items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Rule(lhs, _) => Some(lhs.chars().count()),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+
+    let mut out: Vec<String> = Vec::new();
+    for item in items {
+        match item {
+            Item::Line(line) => out.push(line),
+            Item::Rule(lhs, successor) => {
+                let line = format!("{:<width$} -> {}", lhs, successor, width = width);
+                out.push(line.trim_end().to_string());
+            }
+            Item::Defines => {
+                for define in &defines {
+                    out.extend(define.lines.iter().cloned());
+                }
+            }
+        }
+    }
+    while out.first().is_some_and(|line| line.is_empty()) {
+        out.remove(0);
+    }
+    while out.last().is_some_and(|line| line.is_empty()) {
+        out.pop();
+    }
+    out
+}
+
+/// Predecessor (with contexts), raw condition, and successor of a rule line,
+/// whitespace collapsed.
+fn rule_parts(line: &str) -> Option<(String, Option<String>, String)> {
+    let rule = parse_rule(line)?;
+    let (lhs, successor) = line.split_once("->")?;
+    let lhs = match &rule.label {
+        Some(label) => lhs
+            .trim_start()
+            .strip_prefix(label.as_str())?
+            .trim_start()
+            .strip_prefix(':')?,
+        None => lhs,
+    };
+    let (predecessor, condition) = match lhs.split_once(':') {
+        Some((predecessor, condition)) => (predecessor, Some(squash(condition))),
+        None => (lhs, None),
+    };
+    Some((squash(predecessor), condition, squash(successor)))
+}
+
+fn squash(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Rules (without labels), constants, and axioms of `source`, whitespace
+/// removed, for checking that formatting kept the grammar intact.
+fn signature(source: &str) -> (Vec<String>, Vec<String>) {
+    let strip = |text: &str| text.split_whitespace().collect::<String>();
+    let mut statements = Vec::new();
+    let mut defines = Vec::new();
+    for line in source.lines() {
+        let trimmed = line.trim();
+        if let Some(rule) = parse_rule(trimmed) {
+            statements.push(format!(
+                "{}:{:?}:{:?}->{}",
+                strip(&rule.predecessor),
+                rule.condition.as_deref().map(strip),
+                rule.probability,
+                strip(&rule.successor)
+            ));
+        } else if trimmed.starts_with("#define") {
+            defines.push(squash(trimmed));
+        } else if trimmed.starts_with("omega:") || trimmed.starts_with(SYSTEM_DIRECTIVE) {
+            statements.push(strip(trimmed));
+        }
+    }
+    defines.sort();
+    (statements, defines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_aligns_and_relabels() {
+        let source = "// Binary tree\n#define   step 2\n// @range 0..90\n#define angle 30\n\n\n\
+                      omega:   A(1)\n// Grow\nA(x) : x < 5->F(x)[+A(x+1)]\np7:B  ->  F";
+        let formatted = format_grammar(source).unwrap();
+        assert_eq!(
+            formatted,
+            "// Binary tree\n// @range 0..90\n#define angle 30\n#define step 2\n\n\
+             omega: A(1)\n// Grow\np1: A(x) : x < 5 -> F(x)[+A(x+1)]\np2: B            -> F"
+        );
+        assert_eq!(format_grammar(&formatted).unwrap(), formatted, "idempotent");
+    }
+
+    #[test]
+    fn test_format_keeps_sections_and_expression_defines() {
+        let source = "#define b 2\n#define a b*2\nomega: A\np1: A -> B\n\
+                      #system  leaf 3\nomega: L\np4: L : 0.5 -> F\n";
+        let formatted = format_grammar(source).unwrap();
+        assert_eq!(
+            formatted,
+            "#define b 2\n#define a b*2\nomega: A\np1: A -> B\n\n\
+             #system leaf 3\nomega: L\np1: L : 0.5 -> F\n"
+        );
+    }
+}
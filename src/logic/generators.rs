@@ -0,0 +1,169 @@
+//! Grammar source for common botanical patterns, as starting points.
+//!
+//! Each generator writes its settings as `#define` constants, so they stay
+//! adjustable in the **Defined Constants** panel after the grammar is loaded.
+//! Props use the default mapping: 0 is a leaf, 1 a sphere.
+
+/// Pattern a generator emits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pattern {
+    /// Florets packed on a disc, as in a sunflower head (Vogel's model).
+    SpiralHead,
+    /// Leaves spiralling up a stem.
+    SpiralStem,
+    /// Flowers on pedicels along an indeterminate axis, oldest at the bottom.
+    Raceme,
+    /// Terminal flowers with two lateral axes continuing below each.
+    Cyme,
+}
+
+impl Pattern {
+    pub const ALL: [Self; 4] = [Self::SpiralHead, Self::SpiralStem, Self::Raceme, Self::Cyme];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::SpiralHead => "Spiral head (phyllotaxis)",
+            Self::SpiralStem => "Spiral stem (phyllotaxis)",
+            Self::Raceme => "Raceme",
+            Self::Cyme => "Cyme (dichasium)",
+        }
+    }
+
+    /// Sensible organ counts; cymes double with every order.
+    pub fn count_range(self) -> std::ops::RangeInclusive<u32> {
+        match self {
+            Self::SpiralHead => 10..=1000,
+            Self::SpiralStem => 3..=100,
+            Self::Raceme => 3..=60,
+            Self::Cyme => 1..=8,
+        }
+    }
+}
+
+/// Settings shared by the generators; each pattern reads the ones it needs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GeneratorSettings {
+    pub pattern: Pattern,
+    /// Florets, leaves, or flowers; branching order for cymes.
+    pub count: u32,
+    /// Angle between successive organs around the axis, in degrees.
+    pub divergence: f32,
+    /// Angle of leaves, pedicels, or branches from the axis, in degrees.
+    pub branch_angle: f32,
+    /// Internode length, or floret spacing of spiral heads.
+    pub spacing: f32,
+    /// Prop scale of leaves, florets, and flowers.
+    pub organ_size: f32,
+}
+
+impl Default for GeneratorSettings {
+    fn default() -> Self {
+        Self {
+            pattern: Pattern::SpiralHead,
+            count: 200,
+            // The golden angle
+            divergence: 137.5,
+            branch_angle: 50.0,
+            spacing: 1.0,
+            organ_size: 1.0,
+        }
+    }
+}
+
+/// A generated grammar and the iterations it needs to develop fully.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneratedGrammar {
+    pub source: String,
+    pub iterations: usize,
+}
+
+/// Writes the grammar for `settings`.
+pub fn generate(settings: &GeneratorSettings) -> GeneratedGrammar {
+    let range = settings.pattern.count_range();
+    let count = settings.count.clamp(*range.start(), *range.end());
+    let defines = format!(
+        "#define N {}\n#define d {}\n#define a {}\n#define l {}\n#define s {}\n",
+        count, settings.divergence, settings.branch_angle, settings.spacing, settings.organ_size
+    );
+    let (rules, iterations) = match settings.pattern {
+        // Floret n sits at angle n*d and radius l*sqrt(n) on the disc
+        Pattern::SpiralHead => (
+            "omega: ,(2)A(1)\n\
+             p1: A(n) : n < N + 1 -> [/(n*d)&(90)f(l*n^0.5)~(1,s)]A(n+1)\n"
+                .to_string(),
+            count as usize,
+        ),
+        Pattern::SpiralStem => (
+            "omega: ,(0)!(0.3)A(1)\n\
+             p1: A(n) : n < N + 1 -> F(l)[,(1)&(a)~(0,s)]/(d)A(n+1)\n"
+                .to_string(),
+            count as usize,
+        ),
+        // Flower buds open from the bottom up, growing for four steps
+        Pattern::Raceme => (
+            "omega: ,(0)!(0.3)A(1)\n\
+             p1: A(n) : n < N + 1 -> F(l)[&(a)!(0.1)F(l*0.6),(2)~(1,s*0.25)]/(d)A(n+1)\n\
+             p2: ~(i,k) : k < s -> ~(i,k*1.4)\n"
+                .to_string(),
+            count as usize + 4,
+        ),
+        Pattern::Cyme => (
+            "omega: ,(0)!(0.3)A(N)\n\
+             p1: A(k) : k > 0 -> F(l)[&(a)A(k-1)][/(180)&(a)A(k-1)]/(d)F(l*0.4),(2)~(1,s)\n\
+             p2: A(k) : k = 0 -> F(l),(2)~(1,s)\n"
+                .to_string(),
+            count as usize + 1,
+        ),
+    };
+    GeneratedGrammar {
+        source: format!("// {}\n{}{}", settings.pattern.name(), defines, rules),
+        iterations,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::describe::parse_rule;
+
+    #[test]
+    fn test_generated_grammars_have_axiom_and_rules() {
+        for pattern in Pattern::ALL {
+            let count = *pattern.count_range().start();
+            let generated = generate(&GeneratorSettings {
+                pattern,
+                count,
+                ..Default::default()
+            });
+            let lines: Vec<&str> = generated.source.lines().collect();
+            let define = format!("#define N {}", count);
+            assert!(lines.contains(&define.as_str()), "{}", generated.source);
+            assert!(lines.iter().any(|line| line.starts_with("omega:")));
+            assert!(lines.iter().any(|line| parse_rule(line).is_some()));
+            assert!(generated.iterations >= count as usize);
+        }
+    }
+
+    #[test]
+    fn test_settings_become_constants() {
+        let generated = generate(&GeneratorSettings {
+            pattern: Pattern::SpiralStem,
+            count: 0,
+            divergence: 99.5,
+            ..Default::default()
+        });
+        assert!(generated.source.contains("#define d 99.5\n"));
+        assert!(
+            generated.source.contains("#define N 3\n"),
+            "count is clamped"
+        );
+        assert_eq!(generated.iterations, 3);
+
+        let cyme = generate(&GeneratorSettings {
+            pattern: Pattern::Cyme,
+            count: 200,
+            ..Default::default()
+        });
+        assert!(cyme.source.contains("#define N 8\n"));
+    }
+}
@@ -0,0 +1,164 @@
+//! Continuous growth between two derivation steps.
+//!
+//! A growth fraction `t` derives one more step with every rule rewritten so
+//! the step only happens partly, after the timed L-systems of ABOP chapter 6:
+//!
+//! - Segments (`F`, `f`) a rule creates inside a branch grow from zero to
+//!   their full length.
+//! - Segments along the rewritten axis blend from the predecessor's length,
+//!   shared among them, to their own. `F -> FF` at `t = 0` is still one
+//!   segment long, at `t = 1` two.
+//! - Widths (`!`) set inside new branches grow from zero.
+//!
+//! So `t = 0` looks like the plant before the step and `t = 1` like the plant
+//! after it. Lengths must live in `F` parameters or the default step; growth
+//! driven by other modules (e.g. scaling props) still jumps.
+
+use crate::logic::describe::{module_spans, parse_rule, split_first_arg};
+
+/// A partial derivation step.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GrowthStep {
+    /// How much of the step happens, in `0..=1`.
+    pub fraction: f32,
+    /// Length of `F` and `f` without a parameter.
+    pub default_step: f32,
+}
+
+fn is_step(symbol: &str) -> bool {
+    symbol == "F" || symbol == "f"
+}
+
+/// Rewrites one rule line for a partial step; `None` for lines that are not
+/// rules.
+pub fn interpolate_rule(line: &str, growth: GrowthStep) -> Option<String> {
+    let rule = parse_rule(line)?;
+    let (lhs, _) = line.split_once("->")?;
+    let t = growth.fraction.clamp(0.0, 1.0);
+    let default_step = growth.default_step.to_string();
+
+    // Length the predecessor stood for, if it is a segment
+    let strict = rule.predecessor.rsplit('<').next().unwrap_or_default();
+    let strict = strict.split('>').next().unwrap_or_default();
+    let predecessor_length = module_spans(strict)
+        .first()
+        .filter(|module| is_step(module.symbol))
+        .map(|module| match module.args {
+            Some(args) => split_first_arg(args).0.trim().to_string(),
+            None => default_step.clone(),
+        });
+
+    let successor = rule.successor.as_str();
+    let spans = module_spans(successor);
+    let mut depth = 0usize;
+    let axis_steps = spans
+        .iter()
+        .filter(|module| {
+            match module.symbol {
+                "[" => depth += 1,
+                "]" => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            depth == 0 && is_step(module.symbol)
+        })
+        .count();
+
+    let mut out = String::with_capacity(successor.len() * 2);
+    let mut copied = 0;
+    depth = 0;
+    for module in &spans {
+        match module.symbol {
+            "[" => depth += 1,
+            "]" => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        let (first, rest) = match module.args {
+            Some(args) => split_first_arg(args),
+            None => ("", ""),
+        };
+        let replacement = if is_step(module.symbol) {
+            let length = if module.args.is_some() {
+                first.trim()
+            } else {
+                default_step.as_str()
+            };
+            match &predecessor_length {
+                Some(inherited) if depth == 0 => format!(
+                    "{}(({})*{}+({})*{}{})",
+                    module.symbol,
+                    length,
+                    t,
+                    inherited,
+                    (1.0 - t) / axis_steps as f32,
+                    rest
+                ),
+                _ => format!("{}(({})*{}{})", module.symbol, length, t, rest),
+            }
+        } else if module.symbol == "!" && depth > 0 && module.args.is_some() {
+            format!("!(({})*{}{})", first.trim(), t, rest)
+        } else {
+            continue;
+        };
+        out.push_str(&successor[copied..module.start]);
+        out.push_str(&replacement);
+        copied = module.end;
+    }
+    out.push_str(&successor[copied..]);
+    Some(format!("{}-> {}", lhs, out))
+}
+
+/// The rules of a grammar rewritten for a partial step, in order.
+pub fn interpolate_rules(source: &str, growth: GrowthStep) -> Vec<String> {
+    source
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| interpolate_rule(line, growth))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(fraction: f32) -> GrowthStep {
+        GrowthStep {
+            fraction,
+            default_step: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_axis_blends_from_predecessor_length() {
+        assert_eq!(
+            interpolate_rule("p1: F -> F[+F]F", step(0.5)).unwrap(),
+            "p1: F -> F((1)*0.5+(1)*0.25)[+F((1)*0.5)]F((1)*0.5+(1)*0.25)"
+        );
+        assert_eq!(
+            interpolate_rule("F(l) -> F(l*1.5)", step(0.25)).unwrap(),
+            "F(l) -> F((l*1.5)*0.25+(l)*0.75)"
+        );
+    }
+
+    #[test]
+    fn test_new_segments_grow_from_zero() {
+        assert_eq!(
+            interpolate_rule("A(l) : l > 1 -> F(l, 2)[&!(0.5)A(l/2)]", step(0.5)).unwrap(),
+            "A(l) : l > 1 -> F((l)*0.5, 2)[&!((0.5)*0.5)A(l/2)]"
+        );
+        // Context stays on the left-hand side
+        assert_eq!(
+            interpolate_rule("B < F > C -> F F", step(0.0)).unwrap(),
+            "B < F > C -> F((1)*0+(1)*0.5) F((1)*0+(1)*0.5)"
+        );
+    }
+
+    #[test]
+    fn test_non_rules_are_skipped() {
+        let rules = interpolate_rules(
+            "#define r 2\nomega: A\n// F -> FF\np1: A -> F[+A]A\n",
+            step(1.0),
+        );
+        assert_eq!(rules, vec!["p1: A -> F((1)*1)[+A]A".to_string()]);
+    }
+}
@@ -0,0 +1,370 @@
+//! Seeded jitter of the turtle geometry.
+//!
+//! The jitter symbol `?(amount)` marks the point from which a plant grows
+//! irregularly: every later segment of its branch up to the closing `]`, and
+//! every branch sprouting from those, has its direction tilted by up to
+//! `amount ×` [`MAX_ANGLE_JITTER`] and its length varied by up to `amount ×`
+//! [`MAX_LENGTH_JITTER`]. Sibling branches and the continuation after the `]`
+//! are left alone, and a later `?(0)` switches it off again. Like calls (see
+//! [`composition`](crate::logic::composition)), the symbol is rewritten before
+//! parsing into a prop module with a reserved ID, so the turtle records where
+//! it sits, and the brackets are marked (see [`scope`](crate::logic::scope));
+//! [`jitter_skeleton`] then perturbs the skeleton and drops the markers. Each
+//! grammar of a composed plant is jittered before the calls are grafted, so a
+//! section's own markers apply to it and the caller's do not. The noise is
+//! drawn from the plant's seed, so a plant keeps its shape until the seed
+//! changes.
+//!
+//! Props are varied separately, per prop ID, by [`jitter_props`], and thinned
+//! for heavy plants by [`thin_props`].
+
+use crate::core::config::PropJitter;
+use crate::logic::scope::{is_scope_marker, scoped_states};
+use crate::visuals::branch_order::{CellKey, cell_key, strand_attachments};
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use symbios_turtle_3d::{Skeleton, SkeletonProp};
+
+/// The jitter symbol.
+pub const JITTER_SYMBOL: char = '?';
+
+/// Prop ID reserved for jitter markers, just below the call IDs.
+pub const JITTER_PROP_ID: u16 = 59_999;
+
+/// Largest tilt of a segment at amount 1, in degrees.
+pub const MAX_ANGLE_JITTER: f32 = 30.0;
+
+/// Largest relative length change of a segment at amount 1.
+pub const MAX_LENGTH_JITTER: f32 = 0.3;
+
+/// Rewrites `?` and `?(amount)` in a module string (a successor or an axiom)
+/// into jitter marker props.
+pub fn rewrite_jitter(modules: &str) -> Result<String, String> {
+    if !modules.contains(JITTER_SYMBOL) {
+        return Ok(modules.to_string());
+    }
+    let mut out = String::with_capacity(modules.len());
+    let mut rest = modules;
+    while let Some(at) = rest.find(JITTER_SYMBOL) {
+        out.push_str(&rest[..at]);
+        rest = &rest[at + JITTER_SYMBOL.len_utf8()..];
+        match rest.strip_prefix('(') {
+            Some(args) => {
+                // Amount may itself contain parentheses
+                let mut depth = 1;
+                let close = args
+                    .char_indices()
+                    .find(|&(_, c)| {
+                        match c {
+                            '(' => depth += 1,
+                            ')' => depth -= 1,
+                            _ => {}
+                        }
+                        depth == 0
+                    })
+                    .map(|(i, _)| i)
+                    .ok_or_else(|| format!("Unclosed argument list for {}", JITTER_SYMBOL))?;
+                out.push_str(&format!("~({}, {})", JITTER_PROP_ID, args[..close].trim()));
+                rest = &args[close + 1..];
+            }
+            None => out.push_str(&format!("~({}, 1)", JITTER_PROP_ID)),
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Perturbs the segments after each jitter marker and removes the markers,
+/// along with the bracket markers of [`scope`](crate::logic::scope); see the
+/// module docs. Props sitting on a perturbed point move with it.
+pub fn jitter_skeleton(skeleton: &mut Skeleton, seed: u64) {
+    if !skeleton
+        .props
+        .iter()
+        .any(|prop| prop.prop_id == JITTER_PROP_ID)
+    {
+        skeleton.props.retain(|prop| !is_scope_marker(prop.prop_id));
+        return;
+    }
+    // Per point: jitter amount of the segment leaving it
+    let amounts = scoped_states(skeleton, 0.0_f32, |amount, prop| {
+        let marker = prop.prop_id == JITTER_PROP_ID;
+        if marker {
+            *amount = prop.scale.max_element().clamp(0.0, 1.0);
+        }
+        marker
+    });
+    skeleton
+        .props
+        .retain(|prop| prop.prop_id != JITTER_PROP_ID && !is_scope_marker(prop.prop_id));
+
+    let original: Vec<Vec<Vec3>> = skeleton
+        .strands
+        .iter()
+        .map(|strand| strand.iter().map(|point| point.position).collect())
+        .collect();
+    let extent = original
+        .iter()
+        .flatten()
+        .fold(0.0_f32, |acc, p| acc.max(p.abs().max_element()));
+    let cell = (extent * 1e-5).max(1e-6);
+
+    let attachments = strand_attachments(&original);
+    let mut rng = Pcg64::seed_from_u64(seed);
+    // Per point: offset from its original position, and rotation of the
+    // segment reaching it
+    let mut offsets: Vec<Vec<Vec3>> = Vec::with_capacity(original.len());
+    let mut turns: Vec<Vec<Quat>> = Vec::with_capacity(original.len());
+
+    for (i, strand) in skeleton.strands.iter_mut().enumerate() {
+        let mut offset = match attachments[i] {
+            Some((parent, k)) => offsets[parent][k],
+            None => Vec3::ZERO,
+        };
+        let mut strand_offsets = Vec::with_capacity(strand.len());
+        let mut strand_turns = Vec::with_capacity(strand.len());
+        for (k, point) in strand.iter_mut().enumerate() {
+            let mut turn = Quat::IDENTITY;
+            if k > 0 {
+                let segment = original[i][k] - original[i][k - 1];
+                let mut jittered = segment;
+                let amount = amounts[i][k - 1];
+                if amount > 0.0 && segment.length_squared() > f32::EPSILON {
+                    let axis = segment
+                        .cross(Vec3::new(
+                            rng.random_range(-1.0..=1.0),
+                            rng.random_range(-1.0..=1.0),
+                            rng.random_range(-1.0..=1.0),
+                        ))
+                        .normalize_or_zero();
+                    let angle =
+                        amount * MAX_ANGLE_JITTER.to_radians() * rng.random_range(-1.0..=1.0);
+                    if axis != Vec3::ZERO {
+                        turn = Quat::from_axis_angle(axis, angle);
+                    }
+                    let stretch = 1.0 + amount * MAX_LENGTH_JITTER * rng.random_range(-1.0..=1.0);
+                    jittered = turn * segment * stretch;
+                }
+                offset += jittered - segment;
+            }
+            point.position += offset;
+            point.rotation = turn * point.rotation;
+            strand_offsets.push(offset);
+            strand_turns.push(turn);
+        }
+        // The first ring faces along the first segment
+        if let (Some(first), Some(&turn)) = (strand.first_mut(), strand_turns.get(1)) {
+            first.rotation = turn * first.rotation;
+        }
+        offsets.push(strand_offsets);
+        turns.push(strand_turns);
+    }
+
+    let mut points: HashMap<CellKey, (usize, usize)> = HashMap::new();
+    for (i, strand) in original.iter().enumerate() {
+        for (k, &position) in strand.iter().enumerate() {
+            points.entry(cell_key(position, cell)).or_insert((i, k));
+        }
+    }
+    for prop in &mut skeleton.props {
+        if let Some(&(i, k)) = points.get(&cell_key(prop.position, cell)) {
+            prop.position += offsets[i][k];
+            prop.rotation = turns[i][k] * prop.rotation;
+        }
+    }
+}
+
+/// Rotates, scales, and offsets each prop by the jitter of its prop ID. Each
+/// prop draws its noise from the seed and its index, so a prop keeps its
+/// variation when props are added after it.
+pub fn jitter_props(props: &mut [SkeletonProp], jitter: &HashMap<u16, PropJitter>, seed: u64) {
+    if jitter.values().all(PropJitter::is_zero) {
+        return;
+    }
+    for (index, prop) in props.iter_mut().enumerate() {
+        let Some(jitter) = jitter.get(&prop.prop_id).filter(|j| !j.is_zero()) else {
+            continue;
+        };
+        let mut rng =
+            Pcg64::seed_from_u64(seed ^ (index as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        let axis = Vec3::new(
+            rng.random_range(-1.0..=1.0),
+            rng.random_range(-1.0..=1.0),
+            rng.random_range(-1.0..=1.0),
+        )
+        .normalize_or(Vec3::Y);
+        let angle = jitter.rotation.to_radians() * rng.random_range(-1.0..=1.0);
+        let scale = (1.0 + jitter.scale * rng.random_range(-1.0..=1.0)).max(0.05);
+        let offset = jitter.offset * rng.random_range(-1.0..=1.0);
+
+        prop.position += prop.rotation * Vec3::Z * offset;
+        prop.rotation *= Quat::from_axis_angle(axis, angle);
+        prop.scale *= scale;
+    }
+}
+
+/// Keeps about `density` (0–1) of the props. Whether a prop is kept depends
+/// only on the seed and its index, so the props kept at a lower density are
+/// kept at every higher one, and the preview does not flicker while dragging.
+pub fn thin_props(props: &mut Vec<SkeletonProp>, density: f32, seed: u64) {
+    if density >= 1.0 {
+        return;
+    }
+    let mut index = 0u64;
+    props.retain(|_| {
+        index += 1;
+        prop_rank(seed, index) < density
+    });
+}
+
+/// A uniform value in 0..1 per (seed, index), from a SplitMix64 step.
+fn prop_rank(seed: u64, index: u64) -> f32 {
+    let mut z = seed.wrapping_add(index.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 40) as f32 / (1u64 << 24) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::scope::mark_brackets;
+    use crate::visuals::turtle::axiom_skeleton;
+
+    fn skeleton(axiom: &str) -> Skeleton {
+        let axiom = mark_brackets(&rewrite_jitter(axiom).unwrap());
+        axiom_skeleton(&axiom, 45.0, 0.2, None, 0.0)
+    }
+
+    fn positions(skeleton: &Skeleton) -> Vec<Vec3> {
+        skeleton
+            .strands
+            .iter()
+            .flatten()
+            .map(|point| point.position)
+            .collect()
+    }
+
+    #[test]
+    fn test_rewrite_jitter() {
+        assert_eq!(
+            rewrite_jitter("F ?(a * (1 + b)) [ + ? A ]").unwrap(),
+            "F ~(59999, a * (1 + b)) [ + ~(59999, 1) A ]"
+        );
+        assert_eq!(rewrite_jitter("F").unwrap(), "F");
+        assert!(rewrite_jitter("?(1").is_err());
+    }
+
+    #[test]
+    fn test_jitter_starts_at_marker_and_follows_seed() {
+        let plain = skeleton("F ?(0) F [ + F F ] F F F");
+        let mut jittered = skeleton("F ?(1) F [ + F F ] F F F");
+        jitter_skeleton(&mut jittered, 7);
+        assert!(jittered.props.is_empty(), "markers are removed");
+        assert_eq!(plain.props.len(), 3, "a jitter marker and two brackets");
+
+        let (before, after) = (positions(&plain), positions(&jittered));
+        assert_eq!(before.len(), after.len());
+        // The segment before the marker keeps its place
+        assert!(
+            before[..2]
+                .iter()
+                .zip(&after[..2])
+                .all(|(a, b)| a.distance(*b) < 1e-5)
+        );
+        assert!(
+            before
+                .iter()
+                .zip(&after)
+                .any(|(a, b)| a.distance(*b) > 1e-3)
+        );
+
+        let mut again = skeleton("F ?(1) F [ + F F ] F F F");
+        jitter_skeleton(&mut again, 7);
+        assert_eq!(positions(&again), after);
+
+        let mut off = skeleton("F ?(0) F [ + F F ] F F F");
+        jitter_skeleton(&mut off, 7);
+        assert_eq!(positions(&off), before);
+    }
+
+    #[test]
+    fn test_jitter_stays_in_its_branch() {
+        // Neither a later sibling nor the continuation after `]` is jittered
+        // Strands: trunk, the branch, and the point `]` returns to
+        let branch_tip = skeleton("F [ + F ]").strands[1].last().unwrap().position;
+        for axiom in ["F [ + ?(1) F ] [ - F ]", "F [ + ?(1) F ] F"] {
+            let plain = skeleton(&axiom.replace("?(1)", "?(0)"));
+            let mut jittered = skeleton(axiom);
+            jitter_skeleton(&mut jittered, 7);
+            assert!(jittered.props.is_empty(), "{}", axiom);
+
+            let (before, after) = (positions(&plain), positions(&jittered));
+            assert_eq!(before.len(), after.len());
+            let moved: Vec<Vec3> = before
+                .iter()
+                .zip(&after)
+                .filter(|(a, b)| a.distance(**b) > 1e-4)
+                .map(|(a, _)| *a)
+                .collect();
+            assert!(!moved.is_empty(), "{}", axiom);
+            assert!(
+                moved.iter().all(|p| p.distance(branch_tip) < 1e-4),
+                "only the marked branch moves in {}",
+                axiom
+            );
+        }
+    }
+
+    #[test]
+    fn test_jitter_props_by_id() {
+        let mut props = skeleton("F ~(0, 1) F ~(1, 1) F ~(0, 1)").props;
+        let before = props.clone();
+        let mut jitter = HashMap::new();
+        jitter.insert(
+            0,
+            PropJitter {
+                rotation: 45.0,
+                scale: 0.5,
+                offset: 1.0,
+            },
+        );
+        jitter_props(&mut props, &jitter, 3);
+        let moved = |i: usize| {
+            props[i].position.distance(before[i].position) > 1e-4
+                && props[i].rotation.angle_between(before[i].rotation) > 1e-4
+        };
+        assert!(moved(0) && moved(2));
+        assert_eq!(props[1].position, before[1].position, "other IDs are kept");
+        assert_ne!(props[0].scale, props[2].scale);
+
+        let mut again = before.clone();
+        jitter_props(&mut again, &jitter, 3);
+        assert_eq!(
+            again.iter().map(|p| p.position).collect::<Vec<_>>(),
+            props.iter().map(|p| p.position).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_thin_props_is_nested() {
+        let all = skeleton("F ~(0, 1) F ~(0, 1) F ~(0, 1) F ~(0, 1) F ~(0, 1) F ~(0, 1)").props;
+        let kept = |density: f32| {
+            let mut props = all.clone();
+            thin_props(&mut props, density, 7);
+            props.iter().map(|p| p.position).collect::<Vec<_>>()
+        };
+        assert_eq!(kept(1.0).len(), all.len());
+        assert!(kept(0.0).is_empty());
+        let half = kept(0.5);
+        assert!(
+            kept(0.8).len() >= half.len()
+                && half.iter().all(|position| kept(0.8).contains(position)),
+            "props kept at a lower density stay at a higher one"
+        );
+        assert_eq!(kept(0.5), half, "deterministic");
+    }
+}
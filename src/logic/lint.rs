@@ -0,0 +1,304 @@
+//! Grammar linter.
+//!
+//! [`lint_grammar`] looks for grammars that compile but probably don't do what
+//! was meant: rules that can never fire, symbols that are neither rewritten nor
+//! drawn, unbalanced branches, unused constants, and stochastic rules whose
+//! probabilities don't add up. Works on the source text after `#include`
+//! expansion, so line numbers match those of parse errors.
+
+use crate::core::genotype::rule_key;
+use crate::logic::describe::{RuleSummary, module_symbols, parse_rule, turtle_meaning};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+/// Constants the turtle reads by name, used even when no rule mentions them.
+const TURTLE_CONSTANTS: [&str; 3] = ["step", "angle", "width"];
+
+/// Allowed deviation of a probability group's sum from one.
+const PROBABILITY_TOLERANCE: f64 = 1e-3;
+
+/// One finding of the linter.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LintWarning {
+    /// 1-based line in the grammar, or `None` for the grammar as a whole.
+    pub line: Option<usize>,
+    /// Whether the line is in the finalization rules.
+    pub finalization: bool,
+    pub message: String,
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.line, self.finalization) {
+            (Some(line), false) => write!(f, "Line {}: {}", line, self.message),
+            (Some(line), true) => write!(f, "Finalization line {}: {}", line, self.message),
+            (None, _) => f.write_str(&self.message),
+        }
+    }
+}
+
+/// A parsed rule with its location.
+struct LintRule {
+    line: usize,
+    finalization: bool,
+    key: String,
+    rule: RuleSummary,
+}
+
+/// Lints a grammar: `source` is the growth grammar (its `#system` sections
+/// already split off) and `finalization` the decomposition rules.
+pub fn lint_grammar(source: &str, finalization: &str) -> Vec<LintWarning> {
+    let mut warnings = Vec::new();
+    let mut axiom: Option<(usize, String)> = None;
+    let mut defines: Vec<(usize, bool, String)> = Vec::new();
+    let mut rules: Vec<LintRule> = Vec::new();
+    // Identifiers in expressions: axiom, rules, and define values
+    let mut identifiers: HashSet<String> = HashSet::new();
+
+    for (finalization_text, text) in [(false, source), (true, finalization)] {
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            let number = i + 1;
+            if let Some(define) = line.strip_prefix("#define") {
+                let mut parts = define.split_whitespace();
+                if let Some(name) = parts.next() {
+                    defines.push((number, finalization_text, name.to_string()));
+                    identifiers.extend(parts.flat_map(identifier_tokens));
+                }
+            } else if let Some(text) = line.strip_prefix("omega:") {
+                identifiers.extend(identifier_tokens(text));
+                if !finalization_text {
+                    axiom = Some((number, text.trim().to_string()));
+                }
+            } else if let Some(rule) = parse_rule(line) {
+                identifiers.extend(identifier_tokens(
+                    line.split_once("->").map_or("", |(l, _)| l),
+                ));
+                identifiers.extend(identifier_tokens(&rule.successor));
+                if let Some(key) = rule_key(line) {
+                    rules.push(LintRule {
+                        line: number,
+                        finalization: finalization_text,
+                        key,
+                        rule,
+                    });
+                }
+            }
+        }
+    }
+
+    // Unbalanced branches
+    let successors = rules
+        .iter()
+        .map(|r| (Some(r.line), r.finalization, r.rule.successor.as_str()));
+    let axiom_text = axiom
+        .as_ref()
+        .map(|(line, text)| (Some(*line), false, text.as_str()));
+    for (line, finalization, text) in axiom_text.into_iter().chain(successors) {
+        if let Some(problem) = bracket_problem(text) {
+            warnings.push(LintWarning {
+                line,
+                finalization,
+                message: problem.to_string(),
+            });
+        }
+    }
+
+    // Symbols produced by the axiom and each phase's successors
+    let axiom_symbols = axiom
+        .as_ref()
+        .map(|(_, text)| module_symbols(text))
+        .unwrap_or_default();
+    let produced = |finalization: bool| -> HashSet<String> {
+        axiom_symbols
+            .iter()
+            .cloned()
+            .chain(
+                rules
+                    .iter()
+                    .filter(|r| finalization || !r.finalization)
+                    .flat_map(|r| module_symbols(&r.rule.successor)),
+            )
+            .collect()
+    };
+    let (growth_symbols, all_symbols) = (produced(false), produced(true));
+
+    // Rules that can never fire
+    for rule in &rules {
+        let reachable = if rule.finalization {
+            &all_symbols
+        } else {
+            &growth_symbols
+        };
+        if !reachable.contains(&rule.key) {
+            warnings.push(LintWarning {
+                line: Some(rule.line),
+                finalization: rule.finalization,
+                message: format!(
+                    "Unused rule: `{}` never appears in the axiom or a successor",
+                    rule.key
+                ),
+            });
+        }
+    }
+
+    // Symbols that are neither rewritten nor drawn
+    let rewritten: HashSet<&str> = rules.iter().map(|r| r.key.as_str()).collect();
+    let mut inert: Vec<&String> = all_symbols
+        .iter()
+        .filter(|symbol| !rewritten.contains(symbol.as_str()) && turtle_meaning(symbol).is_none())
+        .collect();
+    inert.sort();
+    for symbol in inert {
+        warnings.push(LintWarning {
+            line: None,
+            finalization: false,
+            message: format!(
+                "Symbol `{}` has no rule and no turtle meaning, so it does nothing",
+                symbol
+            ),
+        });
+    }
+
+    // Constants nobody reads
+    for (line, finalization, name) in &defines {
+        if !identifiers.contains(name) && !TURTLE_CONSTANTS.contains(&name.as_str()) {
+            warnings.push(LintWarning {
+                line: Some(*line),
+                finalization: *finalization,
+                message: format!("Constant `{}` is defined but never used", name),
+            });
+        }
+    }
+
+    // Stochastic rules of one predecessor should add up to one
+    let mut groups: BTreeMap<(bool, String), Vec<&LintRule>> = BTreeMap::new();
+    for rule in &rules {
+        groups
+            .entry((rule.finalization, rule.rule.predecessor.clone()))
+            .or_default()
+            .push(rule);
+    }
+    for ((finalization, predecessor), group) in groups {
+        let probabilities: Option<Vec<f64>> = group.iter().map(|r| r.rule.probability).collect();
+        // Conditional rules make the split depend on parameters
+        let Some(probabilities) = probabilities else {
+            continue;
+        };
+        if probabilities.iter().all(|&p| p >= 1.0) {
+            continue;
+        }
+        let sum: f64 = probabilities.iter().sum();
+        if (sum - 1.0).abs() > PROBABILITY_TOLERANCE {
+            warnings.push(LintWarning {
+                line: Some(group[0].line),
+                finalization,
+                message: format!(
+                    "Probabilities of the {} `{}` rules sum to {}, not 1",
+                    group.len(),
+                    predecessor,
+                    (sum * 1000.0).round() / 1000.0
+                ),
+            });
+        }
+    }
+
+    warnings.sort_by_key(|w| (w.finalization, w.line.is_none(), w.line));
+    warnings
+}
+
+/// Describes unbalanced `[` `]` in a successor or axiom, if any.
+fn bracket_problem(text: &str) -> Option<&'static str> {
+    let mut depth = 0i32;
+    for symbol in module_symbols(text) {
+        match symbol.as_str() {
+            "[" => depth += 1,
+            "]" => {
+                depth -= 1;
+                if depth < 0 {
+                    return Some("Unbalanced brackets: `]` without a matching `[`");
+                }
+            }
+            _ => {}
+        }
+    }
+    (depth > 0).then_some("Unbalanced brackets: `[` is never closed")
+}
+
+/// Identifier tokens of an expression or module list.
+fn identifier_tokens(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    for c in text.chars().chain(std::iter::once(' ')) {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            current.push(c);
+        } else if !current.is_empty() {
+            if !current.starts_with(|c: char| c.is_ascii_digit()) {
+                tokens.push(current.clone());
+            }
+            current.clear();
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(source: &str, finalization: &str) -> Vec<String> {
+        lint_grammar(source, finalization)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_clean_grammar_has_no_warnings() {
+        let source = "#define angle 25\n#define s 2\nomega: A\np1: A : 0.6 -> F(s) [ + A ] A\np2: A : 0.4 -> F(s) A";
+        assert_eq!(messages(source, ""), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_reports_problems() {
+        let source = "#define unused 3\nomega: A\np1: A -> F [ + A\np2: B -> F\np3: A : 0.5 -> X";
+        let warnings = messages(source, "");
+        assert!(warnings.contains(&"Line 1: Constant `unused` is defined but never used".into()));
+        assert!(warnings.contains(&"Line 3: Unbalanced brackets: `[` is never closed".into()));
+        assert!(warnings.contains(
+            &"Line 4: Unused rule: `B` never appears in the axiom or a successor".into()
+        ));
+        assert!(
+            warnings.contains(
+                &"Symbol `X` has no rule and no turtle meaning, so it does nothing".into()
+            )
+        );
+        // Mixing an unconditional rule into a stochastic group skews the split
+        assert!(
+            warnings.contains(&"Line 3: Probabilities of the 2 `A` rules sum to 1.5, not 1".into())
+        );
+    }
+
+    #[test]
+    fn test_probability_groups() {
+        let source = "omega: A\np1: A : 0.5 -> F A\np2: A : 0.3 -> F F A";
+        assert_eq!(
+            messages(source, ""),
+            vec!["Line 2: Probabilities of the 2 `A` rules sum to 0.8, not 1".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_finalization_rules() {
+        // A is only decomposed by the finalization, which is not an unused rule
+        let source = "omega: A\np1: A -> A B";
+        let warnings = messages(source, "p1: B -> F\np2: C -> F");
+        assert_eq!(
+            warnings,
+            vec![
+                "Finalization line 2: Unused rule: `C` never appears in the axiom or a successor"
+                    .to_string()
+            ]
+        );
+    }
+}
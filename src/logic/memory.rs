@@ -0,0 +1,165 @@
+//! Memory estimates for derived systems.
+//!
+//! symbios does not report the size of its state, so the estimate is built
+//! from what is known: the number of modules in the derived string, the
+//! parameters per module implied by the grammar text, and the symbol names
+//! held by the interner. Used for the Statistics panel and the optional
+//! memory cap of `perform_derivation`.
+
+use crate::logic::describe::{ModuleUsage, module_arities};
+use bevy::platform::collections::HashMap;
+
+/// Bytes per module: symbol id, parameter range, and vector slack.
+const MODULE_BYTES: usize = 16;
+/// Bytes per parameter value.
+const PARAM_BYTES: usize = 8;
+/// Bytes of interner bookkeeping per symbol, besides its name.
+const SYMBOL_BYTES: usize = 32;
+
+/// Estimated memory use of a derived system.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MemoryEstimate {
+    pub modules: usize,
+    pub params: usize,
+    pub interner_symbols: usize,
+    pub module_bytes: usize,
+    pub param_bytes: usize,
+    pub interner_bytes: usize,
+}
+
+impl MemoryEstimate {
+    pub fn total_bytes(&self) -> usize {
+        self.module_bytes + self.param_bytes + self.interner_bytes
+    }
+}
+
+impl std::ops::AddAssign for MemoryEstimate {
+    fn add_assign(&mut self, other: Self) {
+        self.modules += other.modules;
+        self.params += other.params;
+        self.interner_symbols += other.interner_symbols;
+        self.module_bytes += other.module_bytes;
+        self.param_bytes += other.param_bytes;
+        self.interner_bytes += other.interner_bytes;
+    }
+}
+
+/// Parameters per module and the symbols a grammar uses, from its text.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MemoryModel {
+    /// Expected parameters per module in the derived string.
+    pub params_per_module: f64,
+    /// Distinct symbol names and their total length.
+    pub symbols: usize,
+    pub symbol_name_bytes: usize,
+}
+
+impl MemoryModel {
+    /// Builds the model from the texts of a grammar (axiom and successors,
+    /// growth and finalization) and the expected symbol counts of its
+    /// derivation.
+    pub fn new<'a>(texts: impl IntoIterator<Item = &'a str>, usage: &ModuleUsage) -> Self {
+        let mut arities: HashMap<String, usize> = HashMap::new();
+        for (symbol, arity) in texts.into_iter().flat_map(module_arities) {
+            let entry = arities.entry(symbol).or_default();
+            *entry = (*entry).max(arity);
+        }
+
+        let modules: f64 = usage.symbols.iter().map(|(_, count)| count).sum();
+        let params: f64 = usage
+            .symbols
+            .iter()
+            .map(|(symbol, count)| count * arities.get(symbol).copied().unwrap_or(0) as f64)
+            .sum();
+        Self {
+            params_per_module: if modules > 0.0 { params / modules } else { 0.0 },
+            symbols: arities.len(),
+            symbol_name_bytes: arities.keys().map(String::len).sum(),
+        }
+    }
+
+    /// Estimate for a string of `modules` modules.
+    pub fn estimate(&self, modules: usize) -> MemoryEstimate {
+        let params = (modules as f64 * self.params_per_module).round() as usize;
+        MemoryEstimate {
+            modules,
+            params,
+            interner_symbols: self.symbols,
+            module_bytes: modules * MODULE_BYTES,
+            param_bytes: params * PARAM_BYTES,
+            interner_bytes: self.symbols * SYMBOL_BYTES + self.symbol_name_bytes,
+        }
+    }
+
+    /// Modules that fit in `bytes`.
+    pub fn modules_within(&self, bytes: usize) -> usize {
+        let per_module = MODULE_BYTES as f64 + self.params_per_module * PARAM_BYTES as f64;
+        (bytes as f64 / per_module) as usize
+    }
+}
+
+/// Formats a byte count with a binary unit, e.g. `12.3 MB`.
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Error for a derivation exceeding the memory cap after `iteration` steps,
+/// with ways out. `projected` when the size is predicted rather than reached.
+pub fn memory_cap_error(
+    estimate: &MemoryEstimate,
+    cap_mb: usize,
+    iteration: usize,
+    projected: bool,
+) -> String {
+    format!(
+        "Memory cap exceeded: {} about {} ({} modules) after iteration {}, over the {} MB cap. \
+         Try fewer iterations, less branching or lower rule probabilities, \
+         or raise the cap.",
+        if projected { "would need" } else { "using" },
+        format_bytes(estimate.total_bytes()),
+        estimate.modules,
+        iteration,
+        cap_mb
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::describe::module_usage;
+
+    #[test]
+    fn test_estimate_counts_params() {
+        // After one step: F(1) F(1) +, two parameters over three modules
+        let source = "omega: F(1)\np1: F(x) -> F(x) + F(x)";
+        let usage = module_usage(source, "", 1);
+        let model = MemoryModel::new(["F(1)", "F(x) + F(x)"], &usage);
+        assert!((model.params_per_module - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(model.symbols, 2);
+
+        let estimate = model.estimate(3);
+        assert_eq!(estimate.params, 2);
+        assert_eq!(
+            estimate.total_bytes(),
+            3 * MODULE_BYTES + 2 * PARAM_BYTES + 2 * SYMBOL_BYTES + 2
+        );
+        assert!(model.modules_within(estimate.total_bytes()) >= 3);
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(3 * 1024 * 1024 / 2), "1.5 MB");
+    }
+}
@@ -1 +1,18 @@
+pub mod composition;
 pub mod derivation;
+pub mod describe;
+pub mod diff;
+pub mod format;
+pub mod generators;
+pub mod growth;
+pub mod jitter;
+pub mod lint;
+pub mod memory;
+pub mod rule_line;
+pub mod scope;
+pub mod shortcuts;
+pub mod strand_pool;
+pub mod taper;
+pub mod timed;
+pub mod watch;
+pub mod wizard;
@@ -0,0 +1,136 @@
+//! Splitting of rule lines (`p1: A(x) : x > 0 -> B`) into their parts.
+//!
+//! The describe panel, the probability sliders, and the genotype's source
+//! merge all read rule lines through [`RuleLine::parse`], so they agree on
+//! what is a label, a predecessor, and a probability.
+
+use std::ops::Range;
+
+/// The parts of one rule line, borrowed from it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleLine<'a> {
+    /// Label such as `p1`, if the rule has one.
+    pub label: Option<&'a str>,
+    /// Everything after the label, from the predecessor to the end of the
+    /// line.
+    pub body: &'a str,
+    /// Predecessor with its context and parameters, e.g. `B(x) < A`.
+    pub predecessor: &'a str,
+    /// Non-stochastic condition, e.g. `x > 0`.
+    pub condition: Option<&'a str>,
+    /// Probability of a stochastic rule with its byte range in the line.
+    pub probability: Option<(f64, Range<usize>)>,
+    pub successor: &'a str,
+}
+
+impl<'a> RuleLine<'a> {
+    /// Splits `line`; `None` for comments, directives, the axiom, and lines
+    /// without an arrow.
+    ///
+    /// A leading `identifier:` is a label only if the predecessor after it
+    /// has its own `:` before the arrow, or if what follows the colon is not
+    /// a predecessor at all: in `A:0.3 -> F`, `A` is the predecessor and
+    /// `0.3` its probability, while `p1: A -> F` and `p1: A : 0.3 -> F` are
+    /// labelled.
+    pub fn parse(line: &'a str) -> Option<Self> {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("//") || trimmed.starts_with('#') || trimmed.starts_with("omega:") {
+            return None;
+        }
+        let arrow = line.find("->")?;
+        let lhs = &line[..arrow];
+
+        let mut label = None;
+        let mut body_start = line.len() - trimmed.len();
+        if let Some(colon) = lhs.find(':') {
+            let head = lhs[..colon].trim_start();
+            let after = &lhs[colon + 1..];
+            let rest = after.trim();
+            let identifier =
+                !head.is_empty() && head.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if identifier && !rest.is_empty() && (rest.contains(':') || !is_probability(rest)) {
+                label = Some(head);
+                body_start = colon + 1 + (after.len() - after.trim_start().len());
+            }
+        }
+
+        let (predecessor, guard) = match lhs[body_start..].find(':') {
+            Some(colon) => {
+                let colon = body_start + colon;
+                let raw = &line[colon + 1..arrow];
+                let start = colon + 1 + (raw.len() - raw.trim_start().len());
+                (
+                    &line[body_start..colon],
+                    Some(start..start + raw.trim().len()),
+                )
+            }
+            None => (lhs[body_start..].trim(), None),
+        };
+
+        let mut condition = None;
+        let mut probability = None;
+        if let Some(span) = guard {
+            let text = &line[span.clone()];
+            match text.parse::<f64>() {
+                Ok(p) if (0.0..=1.0).contains(&p) => probability = Some((p, span)),
+                _ if text.is_empty() || text == "*" => {}
+                _ => condition = Some(text),
+            }
+        }
+        Some(RuleLine {
+            label,
+            body: &line[body_start..],
+            predecessor: predecessor.trim(),
+            condition,
+            probability,
+            successor: line[arrow + 2..].trim(),
+        })
+    }
+}
+
+/// Whether the text after a colon reads as a rule probability rather than a
+/// predecessor.
+fn is_probability(text: &str) -> bool {
+    text == "*" || text.parse::<f64>().is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_labels_only_before_a_predecessor() {
+        let rule = RuleLine::parse("A:0.3->F").unwrap();
+        assert_eq!(rule.label, None);
+        assert_eq!(rule.predecessor, "A");
+        assert_eq!(rule.probability, Some((0.3, 2..5)));
+        assert_eq!(rule.successor, "F");
+
+        let rule = RuleLine::parse("p1: A -> F").unwrap();
+        assert_eq!(rule.label, Some("p1"));
+        assert_eq!(rule.body, "A -> F");
+        assert_eq!(rule.predecessor, "A");
+        assert_eq!(rule.probability, None);
+
+        let line = "  p2: A : 0.7 -> F A";
+        let rule = RuleLine::parse(line).unwrap();
+        assert_eq!(rule.label, Some("p2"));
+        assert_eq!(rule.predecessor, "A");
+        let (p, span) = rule.probability.unwrap();
+        assert_eq!((p, &line[span]), (0.7, "0.7"));
+
+        let rule = RuleLine::parse("p3: B(x) : x > 0 -> B(x-1)").unwrap();
+        assert_eq!(rule.label, Some("p3"));
+        assert_eq!(rule.predecessor, "B(x)");
+        assert_eq!(rule.condition, Some("x > 0"));
+        assert_eq!(rule.probability, None);
+    }
+
+    #[test]
+    fn test_non_rules() {
+        assert_eq!(RuleLine::parse("omega: F"), None);
+        assert_eq!(RuleLine::parse("// A -> B"), None);
+        assert_eq!(RuleLine::parse("#define a 1"), None);
+        assert_eq!(RuleLine::parse("F F"), None);
+    }
+}
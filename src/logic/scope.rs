@@ -0,0 +1,242 @@
+//! Branch scopes for commands applied after interpretation.
+//!
+//! Jitter (`?`) is not a turtle command: it is recorded as a marker prop and
+//! applied to the finished skeleton. To limit a marker to the branch that
+//! emitted it, projects using such commands also get their brackets marked:
+//! `[` becomes `[~(SCOPE_OPEN_ID, 1)` and `]` becomes `~(SCOPE_CLOSE_ID, 1)]`
+//! in every successor and axiom. [`scoped_states`] replays the props with
+//! these markers to find, for every skeleton point, the state of the branch
+//! that drew it.
+//!
+//! The markers are ordinary modules to the grammar, so every grammar they are
+//! inserted into also gets [`IGNORE_MARKERS`], keeping context rules blind to
+//! them (a rule with its own `{ ignore: ... }` list needs to name `~` itself).
+
+use crate::logic::jitter::JITTER_SYMBOL;
+use crate::visuals::branch_order::strand_attachments;
+use bevy::prelude::*;
+use symbios_turtle_3d::{Skeleton, SkeletonProp};
+
+/// Prop ID reserved for the marker after each `[`.
+pub const SCOPE_OPEN_ID: u16 = 59_998;
+
+/// Prop ID reserved for the marker before each `]`.
+pub const SCOPE_CLOSE_ID: u16 = 59_997;
+
+/// Directive appended to grammars with markers.
+pub const IGNORE_MARKERS: &str = "#ignore: ~";
+
+/// Whether a prop ID is one of the bracket markers.
+pub fn is_scope_marker(prop_id: u16) -> bool {
+    prop_id == SCOPE_OPEN_ID || prop_id == SCOPE_CLOSE_ID
+}
+
+/// Whether `source` uses a command scoped to its branch, so its brackets must
+/// be marked.
+pub fn uses_branch_scopes(source: &str) -> bool {
+    source.lines().any(|line| {
+        !line.trim_start().starts_with("//")
+            && modules_start(line).is_some_and(|start| line[start..].contains(JITTER_SYMBOL))
+    })
+}
+
+/// Marks the brackets in the successor of a rule or in an axiom; other lines
+/// are returned unchanged.
+pub fn rewrite_brackets(line: &str) -> String {
+    let Some(start) = modules_start(line) else {
+        return line.to_string();
+    };
+    let (head, body) = line.split_at(start);
+    format!("{}{}", head, mark_brackets(body))
+}
+
+/// Byte offset of the modules the turtle draws in a line: the successor of a
+/// rule or the axiom. `None` for other lines.
+pub fn modules_start(line: &str) -> Option<usize> {
+    if let Some(arrow) = line.find("->") {
+        Some(arrow + 2)
+    } else {
+        let axiom = line.trim_start().strip_prefix("omega:")?;
+        Some(line.len() - axiom.len())
+    }
+}
+
+/// Marks every bracket in a module string.
+pub fn mark_brackets(modules: &str) -> String {
+    let mut out = String::with_capacity(modules.len() + 16);
+    for c in modules.chars() {
+        match c {
+            '[' => out.push_str(&format!("[~({}, 1)", SCOPE_OPEN_ID)),
+            ']' => out.push_str(&format!("~({}, 1)]", SCOPE_CLOSE_ID)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// A stretch of the turtle's path between two consecutive props.
+struct Interval<S> {
+    start: Vec3,
+    /// Position of the next prop; `None` for the last stretch.
+    end: Option<Vec3>,
+    /// Branch the turtle is in, numbered by its `[`; 0 is the root.
+    branch: usize,
+    state: S,
+    /// Index of the first prop after the stretch starts.
+    next_prop: usize,
+}
+
+/// For every strand point, the state of its branch for the segment leaving
+/// it.
+///
+/// Starting from `root`, `update` is called with every prop other than the
+/// bracket markers, in emission order, and returns whether it changed the
+/// state. A `[` marker saves the state and the matching `]` restores it, so a
+/// change never reaches a sibling branch or the continuation after `]`.
+///
+/// Strands are matched to the branch that drew them by replaying the
+/// turtle's path, relying on the turtle listing strands in the order they
+/// start. A strand that cannot be matched takes the state of the point it
+/// grows from.
+pub fn scoped_states<S: Clone>(
+    skeleton: &Skeleton,
+    root: S,
+    mut update: impl FnMut(&mut S, &SkeletonProp) -> bool,
+) -> Vec<Vec<S>> {
+    let extent = skeleton
+        .strands
+        .iter()
+        .flatten()
+        .map(|point| &point.position)
+        .chain(skeleton.props.iter().map(|prop| &prop.position))
+        .fold(0.0_f32, |acc, p| acc.max(p.abs().max_element()));
+    let cell = (extent * 1e-5).max(1e-6);
+    let near = |a: Vec3, b: Vec3| a.distance_squared(b) <= cell * cell;
+
+    // Replay the props, splitting the path at each one
+    let mut intervals = vec![Interval {
+        start: Vec3::ZERO,
+        end: None,
+        branch: 0,
+        state: root.clone(),
+        next_prop: 0,
+    }];
+    // Per branch: prop index, position, and state after each change
+    let mut changes: Vec<Vec<(usize, Vec3, S)>> = vec![Vec::new()];
+    let mut stack: Vec<(usize, S, Vec3)> = Vec::new();
+    let (mut branch, mut state) = (0, root.clone());
+    for (index, prop) in skeleton.props.iter().enumerate() {
+        if let Some(last) = intervals.last_mut() {
+            last.end = Some(prop.position);
+        }
+        let mut start = prop.position;
+        match prop.prop_id {
+            SCOPE_OPEN_ID => {
+                stack.push((branch, state.clone(), prop.position));
+                branch = changes.len();
+                changes.push(Vec::new());
+            }
+            SCOPE_CLOSE_ID => {
+                if let Some((parent, saved, opened)) = stack.pop() {
+                    (branch, state, start) = (parent, saved, opened);
+                }
+            }
+            _ => {
+                if update(&mut state, prop) {
+                    changes[branch].push((index, prop.position, state.clone()));
+                }
+            }
+        }
+        intervals.push(Interval {
+            start,
+            end: None,
+            branch,
+            state: state.clone(),
+            next_prop: index + 1,
+        });
+    }
+
+    // Strands start where the turtle moves off; one the replay misses started
+    // inside the stretch before
+    let strands = &skeleton.strands;
+    let mut assigned: Vec<Option<usize>> = vec![None; strands.len()];
+    let (mut cursor, mut previous) = (0, None);
+    for (i, interval) in intervals.iter().enumerate() {
+        if interval.end.is_some_and(|end| near(interval.start, end)) {
+            continue;
+        }
+        let found = (cursor..strands.len()).find(|&s| {
+            strands[s]
+                .first()
+                .is_some_and(|first| near(first.position, interval.start))
+        });
+        if let Some(found) = found {
+            assigned[cursor..found].fill(previous);
+            assigned[found] = Some(i);
+            cursor = found + 1;
+        }
+        previous = Some(i);
+    }
+    assigned[cursor..].fill(previous);
+
+    let attachments = assigned.contains(&None).then(|| {
+        let positions: Vec<Vec<Vec3>> = strands
+            .iter()
+            .map(|strand| strand.iter().map(|point| point.position).collect())
+            .collect();
+        strand_attachments(&positions)
+    });
+    let mut states: Vec<Vec<S>> = Vec::with_capacity(strands.len());
+    for (s, strand) in strands.iter().enumerate() {
+        let Some(i) = assigned[s] else {
+            let inherited = attachments
+                .as_ref()
+                .and_then(|attachments| attachments[s])
+                .map_or_else(|| root.clone(), |(parent, k)| states[parent][k].clone());
+            states.push(vec![inherited; strand.len()]);
+            continue;
+        };
+        let interval = &intervals[i];
+        let changes = &changes[interval.branch];
+        let mut next = changes.partition_point(|(index, _, _)| *index < interval.next_prop);
+        let mut state = interval.state.clone();
+        let mut strand_states = Vec::with_capacity(strand.len());
+        for point in strand {
+            while let Some((_, position, changed)) = changes.get(next) {
+                if !near(*position, point.position) {
+                    break;
+                }
+                state = changed.clone();
+                next += 1;
+            }
+            strand_states.push(state.clone());
+        }
+        states.push(strand_states);
+    }
+    states
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_brackets() {
+        assert_eq!(
+            rewrite_brackets("p1: A -> F [ + A ] A"),
+            "p1: A -> F [~(59998, 1) + A ~(59997, 1)] A"
+        );
+        assert_eq!(
+            rewrite_brackets("omega: [ A ]"),
+            "omega: [~(59998, 1) A ~(59997, 1)]"
+        );
+        assert_eq!(rewrite_brackets("#ignore: [ ]"), "#ignore: [ ]");
+        assert!(uses_branch_scopes("omega: F ?(1) F"));
+        assert!(uses_branch_scopes("p1: A : x > 1 -> F ?(x) A"));
+        assert!(!uses_branch_scopes("omega: F [ + F ]"));
+        // Only successors and axioms count
+        assert!(!uses_branch_scopes("// Why? p1: A -> ?"));
+        assert!(!uses_branch_scopes("p1: A(x) : x > 1? -> F"));
+        assert!(!uses_branch_scopes("#define what? 1"));
+    }
+}
@@ -0,0 +1,174 @@
+use crate::core::config::{DerivationDebounce, ExportConfig, LSystemConfig};
+#[cfg(feature = "nursery")]
+use crate::core::config::{MaterialSettingsMap, PropConfig};
+use crate::core::keybindings::{KeyChord, Keybindings, ShortcutAction, bindable_keys};
+#[cfg(feature = "nursery")]
+use crate::ui::nursery::{NurseryMode, NurseryState};
+use crate::visuals::camera::frame_bounds;
+use crate::visuals::debug::{DebugViewConfig, wireframe_supported};
+use crate::visuals::turtle::TurtleRenderState;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::render::view::screenshot::{Screenshot, save_to_disk};
+use bevy_egui::input::EguiWantsInput;
+use bevy_panorbit_camera::PanOrbitCamera;
+
+/// Editor state that shortcut actions operate on.
+#[derive(SystemParam)]
+pub struct ShortcutTargets<'w, 's> {
+    config: ResMut<'w, LSystemConfig>,
+    debounce: ResMut<'w, DerivationDebounce>,
+    #[cfg(feature = "nursery")]
+    nursery: ResMut<'w, NurseryState>,
+    #[cfg(feature = "nursery")]
+    materials: Res<'w, MaterialSettingsMap>,
+    #[cfg(feature = "nursery")]
+    prop_config: Res<'w, PropConfig>,
+    render_state: Res<'w, TurtleRenderState>,
+    debug_view: ResMut<'w, DebugViewConfig>,
+    export_config: Res<'w, ExportConfig>,
+    camera_query: Query<'w, 's, &'static mut PanOrbitCamera>,
+}
+
+/// Dispatches keyboard shortcuts, or captures the next key press while the
+/// settings UI is waiting to rebind an action.
+pub fn handle_shortcuts(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    egui_wants: Res<EguiWantsInput>,
+    mut keybindings: ResMut<Keybindings>,
+    mut targets: ShortcutTargets,
+) {
+    if let Some(action) = keybindings.rebinding {
+        capture_rebinding(&keys, &mut keybindings, action);
+        return;
+    }
+
+    let typing = egui_wants.wants_any_keyboard_input();
+    let triggered: Vec<ShortcutAction> = ShortcutAction::ALL
+        .iter()
+        .copied()
+        .filter(|action| {
+            keybindings
+                .chord(*action)
+                .is_some_and(|chord| (!typing || chord.is_modified()) && chord.just_pressed(&keys))
+        })
+        .collect();
+
+    for action in triggered {
+        run_action(action, &mut commands, &mut targets);
+    }
+}
+
+fn capture_rebinding(
+    keys: &ButtonInput<KeyCode>,
+    keybindings: &mut Keybindings,
+    action: ShortcutAction,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        keybindings.rebinding = None;
+        return;
+    }
+    let Some(key) = bindable_keys().find(|key| keys.just_pressed(*key)) else {
+        return;
+    };
+
+    let chord = KeyChord {
+        key,
+        ctrl: keys.any_pressed([
+            KeyCode::ControlLeft,
+            KeyCode::ControlRight,
+            KeyCode::SuperLeft,
+            KeyCode::SuperRight,
+        ]),
+        shift: keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]),
+        alt: keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]),
+    };
+    keybindings.bind(action, chord);
+    keybindings.rebinding = None;
+    if let Err(e) = keybindings.save() {
+        warn!("Failed to save keybindings: {}", e);
+    }
+}
+
+fn run_action(action: ShortcutAction, commands: &mut Commands, targets: &mut ShortcutTargets) {
+    match action {
+        ShortcutAction::Recompile => {
+            targets.config.recompile_requested = true;
+            targets.debounce.pending = false;
+        }
+        ShortcutAction::IterationUp => {
+            targets.config.iterations += 1;
+            targets.config.recompile_requested = true;
+            targets.debounce.pending = false;
+        }
+        ShortcutAction::IterationDown => {
+            if targets.config.iterations > 0 {
+                targets.config.iterations -= 1;
+                targets.config.recompile_requested = true;
+                targets.debounce.pending = false;
+            }
+        }
+        ShortcutAction::Screenshot => take_screenshot(commands, &targets.export_config.output_dir),
+        ShortcutAction::FramePlant => {
+            #[cfg(feature = "nursery")]
+            if targets.nursery.mode != NurseryMode::Disabled {
+                return;
+            }
+            let Some((min, max)) = targets.render_state.bounds else {
+                return;
+            };
+            for mut camera in &mut targets.camera_query {
+                frame_bounds(&mut camera, min, max);
+            }
+        }
+        #[cfg(feature = "nursery")]
+        ShortcutAction::ToggleNursery => {
+            let nursery = &mut targets.nursery;
+            if nursery.mode == NurseryMode::Enabled {
+                nursery.mode = NurseryMode::Disabled;
+            } else {
+                nursery.initialize_from_editor(
+                    &targets.config,
+                    &targets.materials,
+                    &targets.prop_config,
+                );
+                nursery.needs_3d_rebuild = true;
+                nursery.mode = NurseryMode::Enabled;
+            }
+        }
+        #[cfg(not(feature = "nursery"))]
+        ShortcutAction::ToggleNursery => {}
+        ShortcutAction::ToggleWireframe => {
+            if wireframe_supported() {
+                targets.debug_view.wireframe = !targets.debug_view.wireframe;
+            }
+        }
+    }
+}
+
+/// Captures the primary window to a timestamped PNG.
+/// Native builds write into the export folder; wasm builds trigger a browser download.
+#[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+fn take_screenshot(commands: &mut Commands, export_dir: &std::path::Path) {
+    let filename = format!(
+        "screenshot_{}.png",
+        chrono::Local::now().format("%Y%m%d_%H%M%S")
+    );
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let path = {
+        if let Err(e) = std::fs::create_dir_all(export_dir) {
+            error!("Failed to create exports directory: {}", e);
+            return;
+        }
+        export_dir.join(filename)
+    };
+    #[cfg(target_arch = "wasm32")]
+    let path = filename;
+
+    info!("Saving screenshot to {:?}", path);
+    commands
+        .spawn(Screenshot::primary_window())
+        .observe(save_to_disk(path));
+}
@@ -0,0 +1,80 @@
+//! Reusable strand buffers for skeleton rebuilds.
+//!
+//! During live editing the plant is rebuilt after every debounced keystroke.
+//! Grafting sub-systems and chunked meshing copy strands into new skeletons,
+//! and each copy used to allocate a fresh `Vec`. The pool keeps the buffers of
+//! retired skeletons (cleared, capacity intact) and hands them out again.
+
+use bevy::prelude::*;
+use symbios_turtle_3d::{Skeleton, SkeletonPoint};
+
+/// Upper bound on pooled buffers, so one huge plant does not pin its memory forever.
+pub const MAX_POOLED_STRANDS: usize = 16_384;
+
+/// Free list of cleared strand buffers.
+#[derive(Resource, Default)]
+pub struct StrandPool {
+    free: Vec<Vec<SkeletonPoint>>,
+}
+
+impl StrandPool {
+    /// An empty strand buffer, reused when one is available.
+    pub fn take(&mut self) -> Vec<SkeletonPoint> {
+        self.free.pop().unwrap_or_default()
+    }
+
+    /// A pooled buffer holding a copy of `points`.
+    pub fn copy_of(&mut self, points: &[SkeletonPoint]) -> Vec<SkeletonPoint> {
+        let mut strand = self.take();
+        strand.extend_from_slice(points);
+        strand
+    }
+
+    /// Returns a buffer to the pool. Unallocated buffers are dropped.
+    pub fn give(&mut self, mut strand: Vec<SkeletonPoint>) {
+        if strand.capacity() == 0 || self.free.len() >= MAX_POOLED_STRANDS {
+            return;
+        }
+        strand.clear();
+        self.free.push(strand);
+    }
+
+    /// Moves all of `skeleton`'s strand buffers into the pool.
+    pub fn recycle(&mut self, skeleton: &mut Skeleton) {
+        for strand in skeleton.strands.drain(..) {
+            self.give(strand);
+        }
+    }
+
+    /// Number of buffers ready for reuse.
+    pub fn len(&self) -> usize {
+        self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.free.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool_reuses_buffers() {
+        let mut pool = StrandPool::default();
+        assert!(pool.take().capacity() == 0);
+
+        let buffer: Vec<SkeletonPoint> = Vec::with_capacity(32);
+        let ptr = buffer.as_ptr();
+        pool.give(buffer);
+        pool.give(Vec::new());
+        assert_eq!(pool.len(), 1);
+
+        let reused = pool.take();
+        assert_eq!(reused.as_ptr(), ptr);
+        assert!(reused.is_empty());
+        assert!(reused.capacity() >= 32);
+        assert!(pool.is_empty());
+    }
+}
@@ -0,0 +1,80 @@
+//! Smooth width changes along strands.
+//!
+//! The interpreter records a `!(w)` width change mid-branch as a second point
+//! at the same position with the new radius, which the mesher turns into a flat
+//! step between two rings. Merging that duplicate into the point before it
+//! makes the following segment run from the old radius at its start to the new
+//! one at its end, the ABOP-style taper.
+
+use symbios_turtle_3d::{Skeleton, SkeletonPoint};
+
+/// Points closer than this are considered the same ring position.
+const SAME_POSITION_EPSILON: f32 = 1e-5;
+
+/// Merges width-change duplicates on every strand; see the module docs. The
+/// merged point keeps the earlier radius and takes everything else from the
+/// later point, so a color or heading set at the joint still applies.
+///
+/// Duplicates that also switch material are kept, as the material change needs
+/// the split.
+pub fn taper_width_changes(skeleton: &mut Skeleton) {
+    for strand in &mut skeleton.strands {
+        taper_strand(strand);
+    }
+}
+
+fn taper_strand(strand: &mut Vec<SkeletonPoint>) {
+    if strand.len() < 3 {
+        return;
+    }
+    let mut merged: Vec<SkeletonPoint> = Vec::with_capacity(strand.len());
+    for point in strand.drain(..) {
+        match merged.last_mut() {
+            Some(last)
+                if last.material_id == point.material_id
+                    && last.position.distance_squared(point.position)
+                        <= SAME_POSITION_EPSILON * SAME_POSITION_EPSILON =>
+            {
+                // The joint keeps the old radius for the taper; color,
+                // rotation, and the rest start the next segment
+                let radius = last.radius;
+                *last = point;
+                last.radius = radius;
+            }
+            _ => merged.push(point),
+        }
+    }
+    *strand = merged;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visuals::turtle::axiom_skeleton;
+    use bevy::math::{Quat, Vec4};
+
+    #[test]
+    fn test_merged_joint_keeps_later_color() {
+        let mut skeleton = axiom_skeleton("F F", 90.0, 0.5, None, 0.0);
+        // A width change recorded as a second point at the joint, with the
+        // color and heading set there
+        let strand = &mut skeleton.strands[0];
+        let joint = 1;
+        let red = Vec4::new(1.0, 0.0, 0.0, 1.0);
+        let later_rotation = Quat::from_rotation_z(0.3);
+        let mut changed = strand[joint];
+        changed.radius = 0.05;
+        changed.color = red;
+        changed.rotation = later_rotation;
+        strand.insert(joint + 1, changed);
+        let old_radius = strand[joint].radius;
+        let points = strand.len();
+
+        taper_width_changes(&mut skeleton);
+        let strand = &skeleton.strands[0];
+        assert_eq!(strand.len(), points - 1);
+        assert_eq!(strand[joint].color, red);
+        assert_eq!(strand[joint].rotation, later_rotation);
+        assert_eq!(strand[joint].radius, old_radius);
+    }
+}
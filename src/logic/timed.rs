@@ -0,0 +1,397 @@
+//! Timed L-systems (ABOP chapter 6.2): modules with an age and a lifetime.
+//!
+//! A grammar becomes timed with `#timed SYMBOL LIFETIME` lines. Every module
+//! of a timed symbol carries a hidden last parameter, its age: modules are
+//! born with age zero, age by the clock tick each derivation step (`#tick DT`,
+//! default [`DEFAULT_TICK`]), and their production fires once the age reaches
+//! the lifetime. Modules without a production stop aging there. Successors
+//! and finalization rules can read the age of their predecessor as `age`,
+//! e.g. `I -> F(age * 10)` in the finalization to draw internodes that
+//! lengthen as they mature.
+//!
+//! The timed grammar is compiled into a plain parametric one before
+//! derivation, and the global clock in [`LSystemConfig::clock`] sets the
+//! number of steps.
+
+use crate::core::config::{DerivationStatus, LSystemConfig};
+use crate::logic::describe::{module_arities, module_spans, parse_rule};
+use bevy::prelude::*;
+use std::collections::BTreeMap;
+
+/// Clock advance per derivation step when the grammar has no `#tick`.
+pub const DEFAULT_TICK: f32 = 0.1;
+
+/// Largest number of derivation steps a clock value may ask for.
+pub const MAX_TICKS: usize = 10_000;
+
+/// Name of the age readable in successors.
+const AGE: &str = "age";
+
+/// Name of the hidden age parameter in the compiled grammar; symbios
+/// reserves `age` for its own module ages.
+const AGE_PARAM: &str = "timed_age";
+
+/// The `#timed` declarations of a grammar.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimedGrammar {
+    /// Lifetime of each timed symbol.
+    pub lifetimes: BTreeMap<String, f32>,
+    /// Clock advance per derivation step.
+    pub tick: f32,
+}
+
+impl TimedGrammar {
+    /// Reads the `#timed` and `#tick` lines; `Ok(None)` for untimed grammars.
+    pub fn parse(source: &str) -> Result<Option<Self>, String> {
+        let mut lifetimes = BTreeMap::new();
+        let mut tick = DEFAULT_TICK;
+        for (i, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if let Some(rest) = line.strip_prefix("#timed") {
+                let mut parts = rest.split_whitespace();
+                let (Some(symbol), Some(lifetime), None) =
+                    (parts.next(), parts.next(), parts.next())
+                else {
+                    return Err(format!("Line {}: expected `#timed SYMBOL LIFETIME`", i + 1));
+                };
+                let lifetime = lifetime
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|lifetime| *lifetime > 0.0)
+                    .ok_or_else(|| format!("Line {}: lifetime must be above zero", i + 1))?;
+                lifetimes.insert(symbol.to_string(), lifetime);
+            } else if let Some(rest) = line.strip_prefix("#tick") {
+                tick = rest
+                    .trim()
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|tick| *tick > 0.0)
+                    .ok_or_else(|| format!("Line {}: `#tick` must be above zero", i + 1))?;
+            }
+        }
+        Ok((!lifetimes.is_empty()).then_some(Self { lifetimes, tick }))
+    }
+
+    /// Derivation steps that bring the clock to `time`.
+    pub fn ticks(&self, time: f32) -> usize {
+        ((time.max(0.0) / self.tick).round() as usize).min(MAX_TICKS)
+    }
+
+    fn is_timed(&self, symbol: &str) -> bool {
+        self.lifetimes.contains_key(symbol)
+    }
+
+    /// Appends the age to every timed module of a successor or axiom:
+    /// `expression` for modules without parameters, `, expression` otherwise.
+    fn with_ages(&self, text: &str, expression: impl Fn(usize) -> String) -> String {
+        let mut out = String::with_capacity(text.len() + 16);
+        let mut copied = 0;
+        for (index, module) in module_spans(text).iter().enumerate() {
+            if !self.is_timed(module.symbol) {
+                continue;
+            }
+            out.push_str(&text[copied..module.start]);
+            match module.args {
+                Some(args) if !args.trim().is_empty() => out.push_str(&format!(
+                    "{}({}, {})",
+                    module.symbol,
+                    args,
+                    expression(index)
+                )),
+                _ => out.push_str(&format!("{}({})", module.symbol, expression(index))),
+            }
+            copied = module.end;
+        }
+        out.push_str(&text[copied..]);
+        out
+    }
+
+    /// Compiles one rule. Growth rules of timed symbols fire once the age
+    /// reaches the lifetime; `finalization` rules fire at any age.
+    fn compile_rule(&self, line: &str, finalization: bool) -> Result<String, String> {
+        let Some(rule) = parse_rule(line) else {
+            return Ok(line.to_string());
+        };
+
+        // The strict predecessor binds the age; context modules bind ages of
+        // their own that nobody reads
+        let text = rule.predecessor.as_str();
+        let (start, end) = strict_predecessor(text).unwrap_or((0, text.len()));
+        let symbol = module_symbol(&text[start..end]);
+        let predecessor = format!(
+            "{}{}{}",
+            self.with_ages(&text[..start], |i| format!("{}_l{}", AGE_PARAM, i)),
+            self.with_ages(&text[start..end], |_| AGE_PARAM.into()),
+            self.with_ages(&text[end..], |i| format!("{}_r{}", AGE_PARAM, i)),
+        );
+
+        let condition = match (&rule.condition, rule.probability) {
+            (None, Some(p)) if p >= 1.0 => None,
+            (Some(condition), _) => Some(condition.clone()),
+            (None, Some(p)) => Some(p.to_string()),
+            (None, None) => None,
+        };
+        let condition = if self.is_timed(symbol) && !finalization {
+            if condition.is_some() {
+                return Err(format!(
+                    "Timed rule `{}` cannot also have a condition or probability",
+                    rule.predecessor
+                ));
+            }
+            Some(format!("{} > {}", AGE_PARAM, self.threshold(symbol)))
+        } else {
+            condition
+        };
+
+        let mut successor = self.with_ages(&rule.successor, |_| "0".into());
+        // Only timed predecessors have an age of ours to read
+        let condition = if self.is_timed(symbol) {
+            successor = read_age(&successor);
+            condition.as_deref().map(read_age)
+        } else {
+            condition
+        };
+        let label = rule
+            .label
+            .map(|label| format!("{}: ", label))
+            .unwrap_or_default();
+        Ok(match condition {
+            Some(condition) => format!("{}{} : {} -> {}", label, predecessor, condition, successor),
+            None => format!("{}{} -> {}", label, predecessor, successor),
+        })
+    }
+
+    /// Age past which a timed symbol's production fires: its lifetime, less
+    /// half a tick so rounding in the summed ticks cannot skip it.
+    fn threshold(&self, symbol: &str) -> f32 {
+        self.lifetimes.get(symbol).copied().unwrap_or_default() - self.tick * 0.5
+    }
+
+    /// Compiles the main grammar: declarations become comments, timed
+    /// modules get their age, and every timed symbol gets an aging rule.
+    pub fn compile(&self, source: &str) -> Result<String, String> {
+        let mut out = String::with_capacity(source.len() * 2);
+        let mut arities: BTreeMap<String, usize> = BTreeMap::new();
+        for (i, line) in source.lines().enumerate() {
+            let trimmed = line.trim();
+            let compiled = if trimmed.starts_with("#timed") || trimmed.starts_with("#tick") {
+                // Kept as comments so line numbers in errors still match
+                format!("// {}", trimmed)
+            } else if let Some(axiom) = trimmed.strip_prefix("omega:") {
+                record_arities(&mut arities, axiom, self);
+                format!("omega: {}", self.with_ages(axiom.trim(), |_| "0".into()))
+            } else {
+                if let Some(rule) = parse_rule(trimmed) {
+                    record_arities(&mut arities, &rule.successor, self);
+                    record_arities(&mut arities, &rule.predecessor, self);
+                }
+                self.compile_rule(trimmed, false)
+                    .map_err(|e| format!("Line {}: {}", i + 1, e))?
+            };
+            out.push_str(&compiled);
+            out.push('\n');
+        }
+
+        for (symbol, arity) in arities {
+            let params: Vec<String> = (0..arity).map(|i| format!("p{}", i)).collect();
+            let with_age = |age: &str| {
+                let mut all = params.clone();
+                all.push(age.to_string());
+                format!("{}({})", symbol, all.join(", "))
+            };
+            out.push_str(&format!(
+                "{} : {} < {} -> {}\n",
+                with_age(AGE_PARAM),
+                AGE_PARAM,
+                self.threshold(&symbol),
+                with_age(&format!("{} + {}", AGE_PARAM, self.tick))
+            ));
+        }
+        Ok(out)
+    }
+
+    /// Compiles the finalization rules: timed modules get their age, and
+    /// rules read it without waiting for the lifetime.
+    pub fn compile_finalization(&self, finalization: &str) -> Result<String, String> {
+        finalization
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                self.compile_rule(line.trim(), true)
+                    .map_err(|e| format!("Finalization line {}: {}", i + 1, e))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(|lines| lines.join("\n"))
+    }
+}
+
+/// The grammar to derive at `clock`: timed grammars compiled, with the
+/// step count their clock asks for; other grammars unchanged.
+pub fn resolve(
+    source: &str,
+    finalization: &str,
+    iterations: usize,
+    clock: f32,
+) -> Result<(String, String, usize), String> {
+    match TimedGrammar::parse(source)? {
+        Some(timed) => Ok((
+            timed.compile(source)?,
+            timed.compile_finalization(finalization)?,
+            timed.ticks(clock),
+        )),
+        None => Ok((source.to_string(), finalization.to_string(), iterations)),
+    }
+}
+
+/// Symbol of a module's text, e.g. `A` of `A(x)`.
+fn module_symbol(text: &str) -> &str {
+    module_spans(text)
+        .first()
+        .map_or("", |module| module.symbol)
+}
+
+/// Byte range of the strict predecessor in `L < P > R`.
+fn strict_predecessor(predecessor: &str) -> Option<(usize, usize)> {
+    let start = predecessor.rfind('<').map_or(0, |i| i + 1);
+    let end = predecessor[start..]
+        .find('>')
+        .map_or(predecessor.len(), |i| start + i);
+    let text = &predecessor[start..end];
+    let leading = text.len() - text.trim_start().len();
+    let trimmed = text.trim();
+    (!trimmed.is_empty()).then_some((start + leading, start + leading + trimmed.len()))
+}
+
+/// Points every `age` read in `expression` at the hidden age parameter.
+fn read_age(expression: &str) -> String {
+    let mut out = String::with_capacity(expression.len() + 8);
+    let mut rest = expression;
+    while let Some(start) = rest.find(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        out.push_str(&rest[..start]);
+        let word = &rest[start..];
+        let len = word
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(word.len());
+        out.push_str(if &word[..len] == AGE {
+            AGE_PARAM
+        } else {
+            &word[..len]
+        });
+        rest = &word[len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Records the user-visible parameter count of each timed symbol in `text`.
+fn record_arities(arities: &mut BTreeMap<String, usize>, text: &str, grammar: &TimedGrammar) {
+    for (symbol, arity) in module_arities(text) {
+        if grammar.is_timed(&symbol) {
+            arities.entry(symbol).or_insert(arity);
+        }
+    }
+}
+
+/// Playback of the timed-grammar clock.
+#[derive(Resource)]
+pub struct ClockPlayback {
+    pub playing: bool,
+    /// Clock units per second of playback.
+    pub speed: f32,
+}
+
+impl Default for ClockPlayback {
+    fn default() -> Self {
+        Self {
+            playing: false,
+            speed: 1.0,
+        }
+    }
+}
+
+/// Advances the clock while playing. Waits for each derivation to finish
+/// before the next, so playback never outruns the derivation.
+pub fn advance_clock(
+    time: Res<Time>,
+    mut playback: ResMut<ClockPlayback>,
+    mut config: ResMut<LSystemConfig>,
+    status: Res<DerivationStatus>,
+) {
+    if !playback.playing || status.generating || config.recompile_requested {
+        return;
+    }
+    let Ok(Some(timed)) = TimedGrammar::parse(&config.source_code) else {
+        playback.playing = false;
+        return;
+    };
+    let before = timed.ticks(config.clock);
+    config.clock += time.delta_secs() * playback.speed;
+    if timed.ticks(config.clock) >= MAX_TICKS {
+        playback.playing = false;
+    }
+    if timed.ticks(config.clock) != before {
+        config.recompile_requested = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grammar(source: &str) -> TimedGrammar {
+        TimedGrammar::parse(source).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_parse_declarations() {
+        assert_eq!(TimedGrammar::parse("omega: A\nA -> AB"), Ok(None));
+        let timed = grammar("#timed A 1.5\n#tick 0.5\nomega: A");
+        assert_eq!(timed.lifetimes["A"], 1.5);
+        assert_eq!(timed.tick, 0.5);
+        assert_eq!(timed.ticks(2.0), 4);
+        assert!(TimedGrammar::parse("#timed A").is_err());
+        assert!(TimedGrammar::parse("#timed A 0").is_err());
+    }
+
+    #[test]
+    fn test_compile_adds_ages_and_aging_rules() {
+        let source = "#timed A 1\n#tick 0.5\nomega: A\np1: A -> F(age)[+A]A";
+        let compiled = grammar(source).compile(source).unwrap();
+        assert_eq!(
+            compiled,
+            "// #timed A 1\n// #tick 0.5\nomega: A(0)\n\
+             p1: A(timed_age) : timed_age > 0.75 -> F(timed_age)[+A(0)]A(0)\n\
+             A(timed_age) : timed_age < 0.75 -> A(timed_age + 0.5)\n"
+        );
+    }
+
+    #[test]
+    fn test_compile_keeps_parameters() {
+        let source = "#timed I 2\nomega: I(3)\nI(l) -> F(l)I(l*2)\nB -> B";
+        let compiled = grammar(source).compile(source).unwrap();
+        assert!(compiled.contains("omega: I(3, 0)\n"));
+        assert!(compiled.contains("I(l, timed_age) : timed_age > 1.95 -> F(l)I(l*2, 0)\n"));
+        assert!(compiled.contains("B -> B\n"));
+        assert!(
+            compiled.ends_with("I(p0, timed_age) : timed_age < 1.95 -> I(p0, timed_age + 0.1)\n")
+        );
+    }
+
+    #[test]
+    fn test_timed_rules_reject_conditions() {
+        let source = "#timed A 1\nomega: A\nA : 0.5 -> AA";
+        assert!(grammar(source).compile(source).is_err());
+        // Finalization rules may have conditions and fire at any age
+        assert_eq!(
+            grammar(source)
+                .compile_finalization("A : age > 0.5 -> F(age)")
+                .unwrap(),
+            "A(timed_age) : timed_age > 0.5 -> F(timed_age)"
+        );
+        // Untimed predecessors keep symbios' own `age`
+        assert_eq!(
+            grammar(source).compile_finalization("B -> F(age)").unwrap(),
+            "B -> F(age)"
+        );
+    }
+}
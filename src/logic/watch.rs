@@ -0,0 +1,102 @@
+//! Evaluation of watch expressions against the grammar's constants.
+//!
+//! The expression is evaluated by symbios itself, the same way a module
+//! parameter is: it becomes the length of a single `F` in an axiom compiled
+//! with the grammar's `#define` constants, and the turtle reads the value back
+//! as the distance travelled along its heading. Operators, functions, and
+//! precedence therefore match the rules exactly.
+
+use crate::visuals::turtle::interpret_skeleton;
+use bevy::math::Vec3;
+use symbios::System;
+use symbios_turtle_3d::TurtleConfig;
+
+/// Evaluates `expression` with the given `(name, value)` constants.
+pub fn evaluate_expression(constants: &[(String, f64)], expression: &str) -> Result<f64, String> {
+    let expression = expression.trim();
+    if expression.is_empty() {
+        return Err("Empty expression".into());
+    }
+    if !balanced_single_argument(expression) {
+        return Err("Enter a single expression".into());
+    }
+
+    let mut sys = System::new();
+    for (name, value) in constants {
+        sys.add_directive(&format!("#define {} {}", name, value))
+            .map_err(|e| format!("Constant {}: {}", name, e))?;
+    }
+    sys.set_axiom(&format!("F({})", expression))
+        .map_err(|e| e.to_string())?;
+    sys.derive(0)
+        .map_err(|e| format!("Evaluation error: {}", e))?;
+
+    let skeleton = interpret_skeleton(
+        &sys,
+        TurtleConfig {
+            default_step: 1.0,
+            default_angle: 0.0,
+            initial_width: 0.1,
+            tropism: None,
+            elasticity: 0.0,
+            max_stack_depth: 1024,
+        },
+    );
+    // A zero-length step leaves no strand
+    let Some(strand) = skeleton.strands.first().filter(|strand| strand.len() >= 2) else {
+        return Ok(0.0);
+    };
+    let (first, last) = (&strand[0], &strand[strand.len() - 1]);
+    let heading = first.rotation * Vec3::Y;
+    Ok((last.position - first.position).dot(heading) as f64)
+}
+
+/// Whether the brackets of `expression` are balanced and no comma separates
+/// top-level arguments, so it stays one parameter of the probe module.
+fn balanced_single_argument(expression: &str) -> bool {
+    let mut depth = 0i32;
+    for c in expression.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => return false,
+            '[' | ']' => return false,
+            _ => {}
+        }
+        if depth < 0 {
+            return false;
+        }
+    }
+    depth == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn constants() -> Vec<(String, f64)> {
+        vec![("s".into(), 16.0), ("R".into(), 2.0)]
+    }
+
+    #[test]
+    fn test_evaluates_with_constants() {
+        let value = evaluate_expression(&constants(), "s/R^3").unwrap();
+        assert!((value - 2.0).abs() < 1e-4, "got {}", value);
+        let value = evaluate_expression(&constants(), "(s - 1) * 0.5").unwrap();
+        assert!((value - 7.5).abs() < 1e-4, "got {}", value);
+    }
+
+    #[test]
+    fn test_negative_and_zero_values() {
+        let value = evaluate_expression(&constants(), "R - s").unwrap();
+        assert!((value + 14.0).abs() < 1e-3, "got {}", value);
+        assert_eq!(evaluate_expression(&constants(), "s - s * 1").unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_rejects_multiple_arguments() {
+        assert!(evaluate_expression(&constants(), "s, R").is_err());
+        assert!(evaluate_expression(&constants(), "s) F(R").is_err());
+        assert!(evaluate_expression(&constants(), "  ").is_err());
+    }
+}
@@ -0,0 +1,195 @@
+//! Plant wizard: a parameterized grammar synthesized from high-level traits.
+//!
+//! Every internode `A(l,w)` draws a segment, spawns `branches` laterals with
+//! branch angles spread across the angle range, and continues as a leader.
+//! Lengths and widths shrink by a ratio per order. Thin internodes carry leaf
+//! buds `L`, which the finalization pass turns into leaves with the chosen
+//! density.
+
+/// High-level traits the wizard asks for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlantTraits {
+    /// Lateral branches per node.
+    pub branches: u32,
+    /// Smallest and largest branch angle from the parent axis, in degrees.
+    pub angle_min: f32,
+    pub angle_max: f32,
+    /// Width ratio of a child axis to its parent; lower tapers faster.
+    pub taper: f32,
+    /// Length ratio of a child axis to its parent.
+    pub length_ratio: f32,
+    /// Branching orders, i.e. derivation steps.
+    pub depth: u32,
+    /// Chance that a leaf bud grows a leaf, `0..=1`.
+    pub leaf_density: f32,
+    /// Prop scale of the leaves.
+    pub leaf_size: f32,
+}
+
+impl Default for PlantTraits {
+    fn default() -> Self {
+        Self {
+            branches: 2,
+            angle_min: 25.0,
+            angle_max: 45.0,
+            taper: 0.7,
+            length_ratio: 0.8,
+            depth: 6,
+            leaf_density: 0.6,
+            leaf_size: 2.0,
+        }
+    }
+}
+
+/// Most lateral branches per node; node counts grow as `(branches + 1)^depth`.
+pub const MAX_BRANCHES: u32 = 4;
+/// Most branching orders.
+pub const MAX_DEPTH: u32 = 8;
+
+/// Trunk length and width of the first internode.
+const TRUNK_LENGTH: f32 = 10.0;
+const TRUNK_WIDTH: f32 = 1.0;
+
+/// The synthesized grammar.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WizardGrammar {
+    pub source: String,
+    pub finalization: String,
+    pub iterations: usize,
+}
+
+/// Synthesizes the grammar for `traits`.
+pub fn synthesize(traits: &PlantTraits) -> WizardGrammar {
+    let branches = traits.branches.clamp(1, MAX_BRANCHES);
+    let depth = traits.depth.clamp(1, MAX_DEPTH);
+    let (angle_min, angle_max) = if traits.angle_min <= traits.angle_max {
+        (traits.angle_min, traits.angle_max)
+    } else {
+        (traits.angle_max, traits.angle_min)
+    };
+    // Internodes from the second branching order on carry leaves; the margin
+    // keeps their width clear of the threshold
+    let leaf_width = round(TRUNK_WIDTH * traits.taper.powi(2) * 1.01);
+
+    let mut source = format!(
+        "// Plant wizard: {} branches per node, {}°–{}°\n\
+         #define amin {}\n\
+         #define amax {}\n\
+         #define lr {}\n\
+         #define wr {}\n\
+         #define lw {}\n\
+         #define ls {}\n\
+         omega: ,(0)A({}, {})\n",
+        branches,
+        angle_min,
+        angle_max,
+        angle_min,
+        angle_max,
+        traits.length_ratio,
+        traits.taper,
+        leaf_width,
+        traits.leaf_size,
+        TRUNK_LENGTH,
+        TRUNK_WIDTH
+    );
+
+    // Laterals evenly around the axis, angles spread across the range
+    let roll = 360.0 / branches as f32;
+    let laterals: Vec<String> = (0..branches)
+        .map(|i| {
+            let angle = if branches == 1 {
+                "amin".to_string()
+            } else {
+                format!("amin+(amax-amin)*{}", i as f32 / (branches - 1) as f32)
+            };
+            format!("[&({})A(l*lr,w*wr)]/({})", angle, roll)
+        })
+        .collect();
+    let node = format!("{}/(137.5)A(l*lr,w*wr)", laterals.concat());
+    source.push_str(&format!("p1: A(l,w) : w > lw -> !(w)F(l){}\n", node));
+    source.push_str(&format!("p2: A(l,w) : w < lw -> !(w)F(l)L{}\n", node));
+
+    let density = round(traits.leaf_density.clamp(0.0, 1.0));
+    let leaf = "[,(1)&(60)~(0,ls)]";
+    let finalization = if density >= 1.0 {
+        format!("p1: L -> {}", leaf)
+    } else if density <= 0.0 {
+        "p1: L ->".to_string()
+    } else {
+        format!(
+            "p1: L : {} -> {}\np2: L : {} -> ",
+            density,
+            leaf,
+            round(1.0 - density)
+        )
+        .trim_end()
+        .to_string()
+    };
+
+    WizardGrammar {
+        source,
+        finalization,
+        iterations: depth as usize,
+    }
+}
+
+/// Rounds to four decimals, so the source stays readable.
+fn round(value: f32) -> f32 {
+    (value * 1e4).round() / 1e4
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::describe::parse_rule;
+
+    #[test]
+    fn test_branches_spread_across_angle_range() {
+        let grammar = synthesize(&PlantTraits {
+            branches: 3,
+            angle_min: 40.0,
+            angle_max: 20.0,
+            ..Default::default()
+        });
+        assert!(grammar.source.contains("#define amin 20\n"));
+        assert!(grammar.source.contains("#define amax 40\n"));
+        let rule = grammar
+            .source
+            .lines()
+            .find_map(parse_rule)
+            .expect("growth rule");
+        assert_eq!(rule.successor.matches("[&(").count(), 3);
+        assert!(
+            rule.successor
+                .contains("[&(amin+(amax-amin)*0.5)A(l*lr,w*wr)]/(120)")
+        );
+        assert_eq!(grammar.iterations, 6);
+    }
+
+    #[test]
+    fn test_leaf_density_sets_probabilities() {
+        let grammar = synthesize(&PlantTraits {
+            leaf_density: 0.25,
+            ..Default::default()
+        });
+        assert_eq!(
+            grammar.finalization,
+            "p1: L : 0.25 -> [,(1)&(60)~(0,ls)]\np2: L : 0.75 ->"
+        );
+        let bare = synthesize(&PlantTraits {
+            leaf_density: 0.0,
+            branches: 9,
+            ..Default::default()
+        });
+        assert_eq!(bare.finalization, "p1: L ->");
+        assert_eq!(bare.source.lines().filter_map(parse_rule).count(), 2);
+        assert_eq!(
+            parse_rule(bare.source.lines().last().unwrap())
+                .unwrap()
+                .successor
+                .matches("[&(")
+                .count(),
+            MAX_BRANCHES as usize
+        );
+    }
+}
@@ -0,0 +1,289 @@
+//! Bevy plugins that assemble the explorer.
+//!
+//! [`LSystemExplorerPlugin`] registers the whole application. Apps embedding
+//! only part of it can add the pieces instead: [`CorePlugin`] and
+//! [`DerivationPlugin`] derive the grammar in [`LSystemConfig`], and
+//! [`MeshingPlugin`] turns the result into the plant entities, and
+//! [`GardenPlugin`] meshes the plants placed in the garden. [`UiPlugin`]
+//! adds the editor window, the scene, and the session systems; it expects all
+//! the other plugins.
+
+use crate::core::autosave::Autosave;
+use crate::core::bug_report::BugReport;
+use crate::core::config::{
+    CrossSections, DerivationDebounce, DerivationStatus, DerivationTask, DirtyFlags, ExportConfig,
+    LSystemAnalysis, LSystemConfig, LSystemEngine, MaterialGradients, MaterialSettingsMap,
+    PropConfig, SubSystemEngines,
+};
+use crate::core::garden::Garden;
+use crate::core::herbarium::Herbarium;
+use crate::core::history::DerivationHistory;
+use crate::core::includes::IncludeLibrary;
+use crate::core::keybindings::Keybindings;
+use crate::core::palettes::PaletteLibrary;
+use crate::core::scatter::Scatter;
+use crate::core::terrain::Terrain;
+use crate::logic::strand_pool::StrandPool;
+use crate::logic::timed::ClockPlayback;
+use crate::ui::describe::GrammarDocs;
+use crate::ui::generator::GeneratorDialog;
+use crate::ui::log_console::LogConsole;
+use crate::ui::preset_browser::PresetBrowser;
+use crate::ui::rule_graph::RuleGraph;
+use crate::ui::toasts::Toasts;
+use crate::ui::variations::VariationPanel;
+use crate::ui::watch::WatchPanel;
+use crate::ui::wizard::PlantWizard;
+use crate::visuals::debug::{DebugViewConfig, WireframeSupportPlugin};
+use crate::visuals::garden::GardenRender;
+use crate::visuals::intersections::IntersectionCheck;
+use crate::visuals::mesh_check::ManifoldCheck;
+use crate::visuals::scatter::ScatterRender;
+use crate::visuals::textures::TextureLibrary;
+use crate::visuals::thumbnail::ThumbnailPlugin;
+use crate::visuals::tropism::TropismGizmo;
+use crate::visuals::turtle::{
+    InterpreterCache, MeshingTask, PropMaterialCache, PropSpawnQueue, TurtleRenderState,
+};
+use crate::{core, logic, ui, visuals};
+use bevy::prelude::*;
+use bevy_egui::{EguiGlobalSettings, EguiPlugin, EguiPrimaryContextPass};
+use bevy_panorbit_camera::PanOrbitCameraPlugin;
+
+#[cfg(feature = "export")]
+pub use crate::visuals::export::ExportPlugin;
+#[cfg(feature = "physics")]
+pub use crate::visuals::physics::PhysicsPreviewPlugin;
+
+/// The complete explorer: every plugin of this module, the optional ones as
+/// enabled by the crate features.
+pub struct LSystemExplorerPlugin;
+
+impl Plugin for LSystemExplorerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            CorePlugin,
+            DerivationPlugin,
+            MeshingPlugin,
+            GardenPlugin,
+            UiPlugin,
+        ));
+        #[cfg(feature = "nursery")]
+        app.add_plugins(NurseryPlugin);
+        #[cfg(feature = "export")]
+        app.add_plugins(ExportPlugin);
+        #[cfg(feature = "physics")]
+        app.add_plugins(PhysicsPreviewPlugin);
+    }
+}
+
+/// Grammar, material, and prop settings shared by every other plugin, and
+/// the material and prop assets. Applies the startup preset.
+pub struct CorePlugin;
+
+impl Plugin for CorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LSystemConfig>()
+            .init_resource::<LSystemEngine>()
+            .init_resource::<SubSystemEngines>()
+            .init_resource::<LSystemAnalysis>()
+            .init_resource::<DirtyFlags>()
+            .init_resource::<IncludeLibrary>()
+            .init_resource::<DerivationHistory>()
+            .init_resource::<PropConfig>()
+            .init_resource::<MaterialSettingsMap>()
+            .init_resource::<MaterialGradients>()
+            .init_resource::<CrossSections>()
+            .init_resource::<ExportConfig>()
+            .init_resource::<Garden>()
+            .init_resource::<Terrain>()
+            .add_systems(
+                Startup,
+                (
+                    bevy_symbios::materials::setup_material_assets,
+                    visuals::assets::setup_prop_assets,
+                    // Frames the camera of the scene, when there is one
+                    core::config::apply_startup_preset.after(visuals::scene::setup_scene),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Background derivation of [`LSystemConfig`] into [`LSystemEngine`] when a
+/// recompile is requested.
+pub struct DerivationPlugin;
+
+impl Plugin for DerivationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DerivationStatus>()
+            .init_resource::<DerivationDebounce>()
+            .init_resource::<DerivationTask>()
+            .init_resource::<ClockPlayback>()
+            // Also holds the derivation time poll_derivation reports
+            .init_resource::<TurtleRenderState>()
+            .add_systems(
+                Update,
+                (
+                    logic::timed::advance_clock,
+                    logic::derivation::start_derivation,
+                    logic::derivation::poll_derivation,
+                    logic::derivation::ensure_material_palette_size,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Meshing of the derived system into the plant entities, props, textures,
+/// and the debug and mesh check overlays.
+pub struct MeshingPlugin;
+
+impl Plugin for MeshingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(WireframeSupportPlugin)
+            .init_resource::<TurtleRenderState>()
+            .init_resource::<MeshingTask>()
+            .init_resource::<InterpreterCache>()
+            .init_resource::<StrandPool>()
+            .init_resource::<PropSpawnQueue>()
+            .init_resource::<PropMaterialCache>()
+            .init_resource::<TextureLibrary>()
+            .init_resource::<DebugViewConfig>()
+            .init_resource::<ManifoldCheck>()
+            .init_resource::<IntersectionCheck>()
+            .init_resource::<TropismGizmo>()
+            .add_systems(
+                Update,
+                (
+                    bevy_symbios::materials::sync_material_properties,
+                    visuals::textures::import_textures,
+                    visuals::textures::apply_texture_maps,
+                    visuals::assets::sync_leaf_texture,
+                    visuals::turtle::render_turtle,
+                    visuals::turtle::poll_meshing,
+                    visuals::textures::generate_mesh_tangents,
+                    visuals::turtle::sync_prop_materials,
+                    visuals::debug::sync_wireframe,
+                    visuals::debug::draw_normal_gizmos,
+                )
+                    .chain()
+                    .after(logic::derivation::ensure_material_palette_size),
+            )
+            // Throttled prop spawning after the plant is replaced
+            .add_systems(
+                Update,
+                visuals::turtle::drain_prop_queue.after(visuals::turtle::poll_meshing),
+            )
+            // Mesh analysis
+            .add_systems(
+                Update,
+                (
+                    visuals::mesh_check::run_manifold_check,
+                    visuals::mesh_check::draw_manifold_gizmos,
+                    visuals::intersections::run_intersection_check,
+                    visuals::intersections::draw_intersection_gizmos,
+                    visuals::tropism::drag_tropism_gizmo,
+                    visuals::tropism::draw_tropism_gizmo,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Background meshing of the garden's plants at their placements and of the
+/// scattered forest, the terrain they stand on, and hiding the editor plant
+/// while garden mode is on.
+pub struct GardenPlugin;
+
+impl Plugin for GardenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Garden>()
+            .init_resource::<Terrain>()
+            .init_resource::<GardenRender>()
+            .init_resource::<Scatter>()
+            .init_resource::<ScatterRender>()
+            .add_systems(
+                Update,
+                (
+                    visuals::garden::sync_garden,
+                    visuals::garden::poll_garden_derivations,
+                    visuals::scatter::sync_scatter,
+                    visuals::scatter::poll_scatter_derivations,
+                    visuals::scatter::layout_scatter,
+                    visuals::scene::sync_terrain,
+                    visuals::garden::toggle_editor_visibility_in_garden,
+                )
+                    .chain()
+                    .after(visuals::turtle::poll_meshing)
+                    .after(visuals::turtle::drain_prop_queue),
+            );
+    }
+}
+
+/// Population grid and side-by-side comparison. Only built with the
+/// `nursery` feature.
+#[cfg(feature = "nursery")]
+pub struct NurseryPlugin;
+
+#[cfg(feature = "nursery")]
+impl Plugin for NurseryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            visuals::nursery_render::NurseryRenderPlugin,
+            visuals::comparison::ComparisonPlugin,
+        ));
+    }
+}
+
+/// The editor window, the scene with its cameras, keyboard shortcuts,
+/// thumbnails, and the session autosave and bug reports.
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin::default());
+        }
+        if !app.is_plugin_added::<PanOrbitCameraPlugin>() {
+            app.add_plugins(PanOrbitCameraPlugin);
+        }
+        app.add_plugins(ThumbnailPlugin)
+            // The egui context is attached to the UI camera spawned in setup_scene
+            .insert_resource(EguiGlobalSettings {
+                auto_create_primary_context: false,
+                ..default()
+            })
+            .init_resource::<Keybindings>()
+            .init_resource::<Autosave>()
+            .init_resource::<Herbarium>()
+            .init_resource::<BugReport>()
+            .init_resource::<LogConsole>()
+            .init_resource::<GrammarDocs>()
+            .init_resource::<RuleGraph>()
+            .init_resource::<PresetBrowser>()
+            .init_resource::<GeneratorDialog>()
+            .init_resource::<PlantWizard>()
+            .init_resource::<VariationPanel>()
+            .init_resource::<PaletteLibrary>()
+            .init_resource::<WatchPanel>()
+            .init_resource::<Toasts>()
+            .add_systems(Startup, visuals::scene::setup_scene)
+            .add_systems(
+                Startup,
+                (core::share::load_shared_url, core::autosave::load_autosave)
+                    .chain()
+                    .after(core::config::apply_startup_preset),
+            )
+            .add_systems(EguiPrimaryContextPass, ui::editor::ui_system)
+            .add_systems(
+                Update,
+                logic::shortcuts::handle_shortcuts.after(visuals::debug::draw_normal_gizmos),
+            )
+            // Session autosave
+            .add_systems(Update, core::autosave::autosave_system)
+            // Bug report bundles requested from the Debug panel
+            .add_systems(Update, core::bug_report::create_bug_report)
+            .add_systems(Last, core::autosave::clear_autosave_on_exit);
+    }
+}
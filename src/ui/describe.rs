@@ -0,0 +1,218 @@
+//! "Describe" window: generated documentation of the editor grammar.
+
+use crate::core::config::LSystemConfig;
+use crate::core::files::save_file;
+use crate::logic::describe::{GrammarDescription, RuleSummary, describe_grammar, format_count};
+use bevy::prelude::*;
+use bevy_egui::egui;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::Path;
+
+/// Title of the exported Markdown document.
+const DOCUMENT_TITLE: &str = "L-System Grammar";
+
+/// Describe window state, with the description of the last grammar shown.
+#[derive(Resource, Default)]
+pub struct GrammarDocs {
+    pub open: bool,
+    /// Hash of the grammar inputs and their description.
+    cached: Option<(u64, GrammarDescription)>,
+    /// File name of the last saved document, or why saving failed.
+    pub status: Option<Result<String, String>>,
+}
+
+impl GrammarDocs {
+    /// Describes the editor grammar, unless it is unchanged since last time.
+    pub fn refresh(&mut self, config: &LSystemConfig) {
+        let mut hasher = DefaultHasher::new();
+        (
+            &config.source_code,
+            &config.finalization_code,
+            config.iterations,
+        )
+            .hash(&mut hasher);
+        let key = hasher.finish();
+
+        if self
+            .cached
+            .as_ref()
+            .is_none_or(|(cached, _)| *cached != key)
+        {
+            let description = describe_grammar(
+                &config.source_code,
+                &config.finalization_code,
+                config.iterations,
+            );
+            self.cached = Some((key, description));
+        }
+    }
+
+    /// The description from the last [`GrammarDocs::refresh`].
+    pub fn description(&self) -> Option<&GrammarDescription> {
+        self.cached.as_ref().map(|(_, description)| description)
+    }
+}
+
+fn rule_grid(ui: &mut egui::Ui, id: &str, rules: &[RuleSummary]) {
+    egui::Grid::new(id)
+        .striped(true)
+        .num_columns(5)
+        .show(ui, |ui| {
+            for header in ["Rule", "Predecessor", "Condition", "Chance", "Successor"] {
+                ui.strong(header);
+            }
+            ui.end_row();
+            for rule in rules {
+                ui.label(rule.label.as_deref().unwrap_or("—"));
+                ui.monospace(&rule.predecessor);
+                ui.monospace(rule.condition.as_deref().unwrap_or("—"));
+                ui.label(
+                    rule.probability
+                        .map_or("—".to_string(), |p| format!("{:.0}%", p * 100.0)),
+                );
+                ui.monospace(&rule.successor);
+                ui.end_row();
+            }
+        });
+}
+
+/// Renders the description window while `docs.open` is set.
+pub fn grammar_docs_ui(
+    ctx: &egui::Context,
+    docs: &mut GrammarDocs,
+    config: &LSystemConfig,
+    export_dir: &Path,
+) {
+    if !docs.open {
+        return;
+    }
+    docs.refresh(config);
+    let Some(description) = docs.description() else {
+        return;
+    };
+    let mut open = true;
+    let mut save = false;
+    egui::Window::new("📖 Grammar Description")
+        .open(&mut open)
+        .default_width(520.0)
+        .default_height(480.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("📋 Copy Markdown").clicked() {
+                    ui.ctx().copy_text(description.to_markdown(DOCUMENT_TITLE));
+                }
+                if ui.button("💾 Save .md").clicked() {
+                    save = true;
+                }
+            });
+            match &docs.status {
+                Some(Ok(filename)) => {
+                    ui.colored_label(egui::Color32::GREEN, format!("Saved {}", filename));
+                }
+                Some(Err(e)) => {
+                    ui.colored_label(egui::Color32::RED, e);
+                }
+                None => {}
+            }
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .id_salt("grammar_description")
+                .show(ui, |ui| {
+                    ui.heading("Axiom");
+                    match &description.axiom {
+                        Some(axiom) => {
+                            ui.monospace(axiom);
+                        }
+                        None => {
+                            ui.colored_label(egui::Color32::YELLOW, "No omega: line defined");
+                        }
+                    }
+
+                    ui.heading("Rules");
+                    if description.rules.is_empty() {
+                        ui.weak("No rules");
+                    } else {
+                        rule_grid(ui, "describe_rules", &description.rules);
+                    }
+                    if !description.finalization_rules.is_empty() {
+                        ui.heading("Finalization Rules");
+                        rule_grid(ui, "describe_finalization", &description.finalization_rules);
+                    }
+
+                    if !description.constants.is_empty() {
+                        ui.heading("Constants");
+                        egui::Grid::new("describe_constants")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for (name, value) in &description.constants {
+                                    ui.monospace(name);
+                                    ui.monospace(value);
+                                    ui.end_row();
+                                }
+                            });
+                    }
+
+                    ui.heading("Symbols");
+                    if let Some(ignored) = &description.ignored {
+                        ui.label(format!("Context matching ignores: {}", ignored));
+                    }
+                    egui::Grid::new("describe_symbols")
+                        .striped(true)
+                        .num_columns(3)
+                        .show(ui, |ui| {
+                            for header in ["Symbol", "Uses", "Meaning"] {
+                                ui.strong(header);
+                            }
+                            ui.end_row();
+                            for symbol in &description.symbols {
+                                ui.monospace(&symbol.symbol);
+                                ui.label(symbol.count.to_string());
+                                ui.label(&symbol.meaning);
+                                ui.end_row();
+                            }
+                        });
+
+                    if !description.sections.is_empty() {
+                        ui.heading("Sub-systems");
+                        for section in &description.sections {
+                            let iterations = section
+                                .iterations
+                                .map_or("editor iterations".to_string(), |n| {
+                                    format!("{} iterations", n)
+                                });
+                            ui.label(format!(
+                                "{} — {} rules, {}",
+                                section.name, section.rules, iterations
+                            ));
+                        }
+                    }
+
+                    ui.heading("Estimated Size");
+                    ui.label(format!(
+                        "After {} iterations: ≈ {} modules, ≈ {} drawn segments",
+                        description.iterations,
+                        format_count(description.estimated_modules),
+                        format_count(description.estimated_segments)
+                    ))
+                    .on_hover_text(
+                        "Expected counts: stochastic rules are weighted by their probability, \
+                         conditions and contexts are assumed to match",
+                    );
+                });
+        });
+    let markdown = save.then(|| description.to_markdown(DOCUMENT_TITLE));
+    docs.open = open;
+
+    if let Some(markdown) = markdown {
+        let filename = format!(
+            "grammar_{}.md",
+            chrono::Local::now().format("%Y%m%d_%H%M%S")
+        );
+        let result = save_file(export_dir, &filename, &markdown).map(|()| filename);
+        if let Err(e) = &result {
+            warn!("Failed to save grammar description: {}", e);
+        }
+        docs.status = Some(result);
+    }
+}
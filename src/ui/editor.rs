@@ -1,15 +1,138 @@
+use crate::core::autosave::Autosave;
+use crate::core::bug_report::BugReport;
+#[cfg(all(feature = "export", not(target_arch = "wasm32")))]
+use crate::core::config::OverwritePolicy;
+#[cfg(feature = "export")]
+use crate::core::config::{AxisConvention, ExportFormat, SweepParameter};
 use crate::core::config::{
-    DerivationDebounce, DerivationStatus, DirtyFlags, ExportConfig, ExportFormat, LSystemAnalysis,
-    LSystemConfig, LSystemEngine, MaterialSettingsMap, PropConfig, PropMeshType, split_source_code,
+    CrossSections, DerivationDebounce, DerivationStatus, DirtyFlags, ExportConfig, GradientMode,
+    LSystemAnalysis, LSystemConfig, LSystemEngine, MaterialGradient, MaterialGradients,
+    MaterialSettingsMap, PropConfig, PropJitter, PropMeshType, split_source_code,
 };
+#[cfg(all(feature = "export", not(target_arch = "wasm32")))]
+use crate::core::files::pick_folder;
+use crate::core::garden::Garden;
 use crate::core::genotype::PlantGenotype;
-use crate::core::presets::PRESETS;
-use crate::ui::editor_utils::{highlight_lsystem, smart_slider_range, update_define_in_source};
+use crate::core::herbarium::{Herbarium, MAX_HERBARIUM_ENTRIES};
+use crate::core::history::DerivationHistory;
+use crate::core::includes::IncludeLibrary;
+use crate::core::keybindings::{Keybindings, ShortcutAction};
+use crate::core::palettes::PaletteLibrary;
+use crate::core::presets::LSystemPreset;
+use crate::core::scatter::Scatter;
+use crate::core::terrain::Terrain;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::logic::composition::has_sections;
+use crate::logic::describe::{ModuleUsage, format_count};
+use crate::logic::format::format_grammar;
+use crate::logic::memory::{MemoryEstimate, format_bytes};
+use crate::logic::timed::ClockPlayback;
+use crate::ui::describe::{GrammarDocs, grammar_docs_ui};
+use crate::ui::editor_utils::{
+    constant_metadata, constant_slider_range, highlight_lsystem, rule_probabilities, rule_rows,
+    set_rule_enabled, update_define_in_source, update_probability_in_source,
+};
+use crate::ui::garden::{GardenAction, garden_ui};
+use crate::ui::generator::{GeneratorDialog, generator_ui};
+use crate::ui::herbarium::{HerbariumAction, herbarium_ui, star_plant};
+use crate::ui::history::history_ui;
+use crate::ui::includes::include_library_ui;
+use crate::ui::log_console::{LogConsole, log_console_ui};
+#[cfg(feature = "nursery")]
 use crate::ui::nursery::{NurseryMode, NurseryState, nursery_ui};
+use crate::ui::palettes::palettes_ui;
+use crate::ui::preset_browser::{PresetBrowser, PresetPick, preset_browser_ui};
+use crate::ui::rule_graph::{RuleGraph, rule_graph_ui};
+use crate::ui::textures::{queue_dropped_textures, texture_maps_ui};
+use crate::ui::toasts::{Toasts, toasts_ui};
+use crate::ui::variations::{VariationPanel, variations_ui};
+use crate::ui::watch::{WatchPanel, watch_ui};
+use crate::ui::wizard::{PlantWizard, plant_wizard_ui};
+use crate::visuals::camera::frame_bounds;
+#[cfg(feature = "nursery")]
+use crate::visuals::comparison::{ComparisonSource, ComparisonState};
+use crate::visuals::debug::{DebugViewConfig, wireframe_supported};
+use crate::visuals::decimate::DecimationTarget;
+#[cfg(feature = "export")]
 use crate::visuals::export::ExportStatus;
-use crate::visuals::turtle::TurtleRenderState;
+use crate::visuals::intersections::IntersectionCheck;
+use crate::visuals::mesh_check::{ManifoldCheck, MeshTopology, ProblemKind};
+use crate::visuals::mesher::{CrossSection, Shading, format_profile, parse_profile};
+#[cfg(feature = "physics")]
+use crate::visuals::physics::PhysicsPreview;
+use crate::visuals::textures::TextureLibrary;
+use crate::visuals::thumbnail::ThumbnailRenderer;
+use crate::visuals::tropism::{TropismGizmo, TropismPreset};
+use crate::visuals::turtle::{MeshingTask, TurtleRenderState};
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy_egui::{EguiContexts, egui};
+use std::collections::BTreeMap;
+
+/// Nursery and comparison state, bundled to stay within the system parameter limit.
+#[cfg(feature = "nursery")]
+#[derive(SystemParam)]
+pub struct EvolutionPanel<'w> {
+    nursery: ResMut<'w, NurseryState>,
+    comparison: ResMut<'w, ComparisonState>,
+    thumbnails: Res<'w, ThumbnailRenderer>,
+}
+
+/// Editor tool and panel state, bundled to stay within the system parameter
+/// limit.
+#[derive(SystemParam)]
+pub struct EditorResources<'w> {
+    debug_view: ResMut<'w, DebugViewConfig>,
+    keybindings: ResMut<'w, Keybindings>,
+    history: ResMut<'w, DerivationHistory>,
+    meshing: ResMut<'w, MeshingTask>,
+    autosave: ResMut<'w, Autosave>,
+    includes: ResMut<'w, IncludeLibrary>,
+    manifold_check: ResMut<'w, ManifoldCheck>,
+    intersection_check: ResMut<'w, IntersectionCheck>,
+    herbarium: ResMut<'w, Herbarium>,
+    bug_report: ResMut<'w, BugReport>,
+    log_console: ResMut<'w, LogConsole>,
+    toasts: ResMut<'w, Toasts>,
+    grammar_docs: ResMut<'w, GrammarDocs>,
+    textures: ResMut<'w, TextureLibrary>,
+    gradients: ResMut<'w, MaterialGradients>,
+    cross_sections: ResMut<'w, CrossSections>,
+}
+
+/// Recompile scheduling and the state of the current derivation.
+#[derive(SystemParam)]
+pub struct DerivationResources<'w> {
+    debounce: ResMut<'w, DerivationDebounce>,
+    dirty: ResMut<'w, DirtyFlags>,
+    status: ResMut<'w, DerivationStatus>,
+}
+
+/// Side panels and dialogs opened from the editor.
+#[derive(SystemParam)]
+pub struct PanelResources<'w> {
+    watch: ResMut<'w, WatchPanel>,
+    garden: ResMut<'w, Garden>,
+    scatter: ResMut<'w, Scatter>,
+    terrain: ResMut<'w, Terrain>,
+    playback: ResMut<'w, ClockPlayback>,
+    rule_graph: ResMut<'w, RuleGraph>,
+    preset_browser: ResMut<'w, PresetBrowser>,
+    preset_thumbnails: Res<'w, ThumbnailRenderer>,
+    generator: ResMut<'w, GeneratorDialog>,
+    wizard: ResMut<'w, PlantWizard>,
+    tropism_gizmo: ResMut<'w, TropismGizmo>,
+    variations: ResMut<'w, VariationPanel>,
+    palettes: ResMut<'w, PaletteLibrary>,
+}
+
+/// Export settings and the status of the last export.
+#[cfg(feature = "export")]
+#[derive(SystemParam)]
+pub struct ExportResources<'w> {
+    export_config: ResMut<'w, ExportConfig>,
+    export_status: Res<'w, ExportStatus>,
+}
 
 #[allow(clippy::too_many_arguments)]
 pub fn ui_system(
@@ -18,17 +141,67 @@ pub fn ui_system(
     engine: ResMut<LSystemEngine>,
     mut prop_config: ResMut<PropConfig>,
     mut material_settings: ResMut<MaterialSettingsMap>,
-    mut export_config: ResMut<ExportConfig>,
-    export_status: Res<ExportStatus>,
-    mut debounce: ResMut<DerivationDebounce>,
-    mut dirty: ResMut<DirtyFlags>,
-    status: Res<DerivationStatus>,
+    #[cfg(feature = "export")] export: ExportResources,
+    derivation: DerivationResources,
     analysis: Res<LSystemAnalysis>,
     render_state: Res<TurtleRenderState>,
     time: Res<Time>,
     mut camera_query: Query<&mut bevy_panorbit_camera::PanOrbitCamera>,
-    mut nursery: ResMut<NurseryState>,
+    #[cfg(feature = "nursery")] evolution: EvolutionPanel,
+    #[cfg(feature = "physics")] mut physics_preview: ResMut<PhysicsPreview>,
+    editor: EditorResources,
+    panels: PanelResources,
 ) {
+    let DerivationResources {
+        mut debounce,
+        mut dirty,
+        mut status,
+    } = derivation;
+    #[cfg(feature = "nursery")]
+    let EvolutionPanel {
+        mut nursery,
+        mut comparison,
+        thumbnails,
+    } = evolution;
+    let EditorResources {
+        mut debug_view,
+        mut keybindings,
+        mut history,
+        mut meshing,
+        mut autosave,
+        mut includes,
+        mut manifold_check,
+        mut intersection_check,
+        mut herbarium,
+        mut bug_report,
+        mut log_console,
+        mut toasts,
+        mut grammar_docs,
+        mut textures,
+        mut gradients,
+        mut cross_sections,
+    } = editor;
+    let PanelResources {
+        mut watch,
+        mut garden,
+        mut scatter,
+        mut terrain,
+        mut playback,
+        mut rule_graph,
+        mut preset_browser,
+        preset_thumbnails,
+        mut generator,
+        mut wizard,
+        mut tropism_gizmo,
+        mut variations,
+        mut palettes,
+    } = panels;
+    #[cfg(feature = "export")]
+    let ExportResources {
+        mut export_config,
+        export_status,
+    } = export;
+
     // Handle Debounce
     if debounce.pending {
         debounce.timer.tick(time.delta());
@@ -39,87 +212,148 @@ pub fn ui_system(
     }
 
     if let Ok(ctx) = contexts.ctx_mut() {
+        #[cfg(feature = "export")]
+        let export_dir = export_config.output_dir.clone();
+        #[cfg(not(feature = "export"))]
+        let export_dir = ExportConfig::default().output_dir;
+        let picked_preset =
+            match preset_browser_ui(ctx, &mut preset_browser, &preset_thumbnails, &herbarium) {
+                Some(PresetPick::Preset(preset)) => Some(preset),
+                Some(PresetPick::Plant(genotype)) => {
+                    genotype.apply_to_editor(&mut config, &mut material_settings, &mut prop_config);
+                    None
+                }
+                None => None,
+            };
+
         egui::Window::new("Symbios Lab")
             .default_width(350.0)
             .show(ctx, |ui| {
                 // --- PRESETS ---
-                ui.horizontal(|ui| {
-                    ui.label("Load Preset:");
-                    ui.with_layout(egui::Layout::right_to_left(egui::Align::LEFT), |ui| {
-                        egui::ComboBox::from_id_salt("preset_combo")
-                            .selected_text("Select...")
-                            .width(ui.available_width())
-                            .show_ui(ui, |ui| {
-                                for preset in PRESETS {
-                                    if ui.selectable_label(false, preset.name).clicked() {
-                                        // Check if nursery is active with selections - inject preset
-                                        if nursery.mode == NurseryMode::Enabled
-                                            && !nursery.selected.is_empty()
-                                        {
-                                            let genotype = PlantGenotype::from_preset(preset);
-                                            nursery.replace_selected(genotype);
-                                        } else {
-                                            // Standard behavior: load into editor
-                                            let (growth, finalization) =
-                                                split_source_code(preset.code);
-                                            config.source_code = growth;
-                                            config.finalization_code = finalization;
-                                            config.iterations = preset.iterations;
-                                            config.default_angle = preset.angle;
-                                            config.step_size = preset.step;
-                                            config.default_width = preset.width;
-                                            config.elasticity = preset.elasticity;
-                                            config.tropism = preset.tropism;
-
-                                            // Apply preset material settings
-                                            material_settings.settings.clear();
-                                            for (slot_id, mat) in preset.materials.iter() {
-                                                material_settings.settings.insert(
-                                                    *slot_id,
-                                                    bevy_symbios::materials::MaterialSettings {
-                                                        base_color: mat.base_color,
-                                                        roughness: mat.roughness,
-                                                        metallic: mat.metallic,
-                                                        emission_color: mat.emission_color,
-                                                        emission_strength: mat.emission_strength,
-                                                        uv_scale: mat.uv_scale,
-                                                        texture: mat.texture_type,
-                                                    },
-                                                );
-                                            }
+                preset_picker_ui(ui, &mut preset_browser);
+                if let Some(preset) = picked_preset {
+                    // Inject into the selected nursery individuals while the nursery is open
+                    #[cfg(feature = "nursery")]
+                    let injected = nursery.inject_preset(preset);
+                    #[cfg(not(feature = "nursery"))]
+                    let injected = false;
+                    if !injected {
+                        apply_preset_to_editor(
+                            preset,
+                            &mut config,
+                            &mut material_settings,
+                            &mut prop_config,
+                        );
+                        // Apply preset camera settings
+                        if let Some(cam) = preset.camera {
+                            for mut pan_orbit in camera_query.iter_mut() {
+                                pan_orbit.target_focus = cam.focus;
+                                pan_orbit.target_radius = cam.distance;
+                                pan_orbit.target_pitch = cam.pitch;
+                                pan_orbit.target_yaw = cam.yaw;
+                                pan_orbit.force_update = true;
+                            }
+                        }
+                        debounce.pending = false;
+                    }
+                }
 
-                                            // Apply preset camera settings
-                                            if let Some(cam) = preset.camera {
-                                                for mut pan_orbit in camera_query.iter_mut() {
-                                                    pan_orbit.target_focus = cam.focus;
-                                                    pan_orbit.target_radius = cam.distance;
-                                                    pan_orbit.target_pitch = cam.pitch;
-                                                    pan_orbit.target_yaw = cam.yaw;
-                                                    pan_orbit.force_update = true;
-                                                }
-                                            }
+                // Editor sections are hidden while the nursery grid is shown
+                #[cfg(feature = "nursery")]
+                let editor_visible = nursery.mode == NurseryMode::Disabled;
+                #[cfg(not(feature = "nursery"))]
+                let editor_visible = true;
 
-                                            // Apply preset prop configuration
-                                            prop_config.prop_meshes =
-                                                preset.prop_meshes.iter().copied().collect();
+                ui.horizontal(|ui| {
+                    if editor_visible {
+                        let frame_button = ui.add_enabled(
+                            render_state.bounds.is_some(),
+                            egui::Button::new("🎯 Frame Plant"),
+                        );
+                        if frame_button
+                            .on_hover_text("Fit the whole plant in view (F)")
+                            .clicked()
+                            && let Some((min, max)) = render_state.bounds
+                        {
+                            for mut pan_orbit in camera_query.iter_mut() {
+                                frame_bounds(&mut pan_orbit, min, max);
+                            }
+                        }
 
-                                            config.recompile_requested = true;
-                                            debounce.pending = false;
-                                        }
-                                    }
-                                }
-                            });
-                    });
+                        let room = herbarium.entries.len() < MAX_HERBARIUM_ENTRIES;
+                        if ui
+                            .add_enabled(room, egui::Button::new("⭐ Star"))
+                            .on_hover_text("Keep this plant in the herbarium")
+                            .clicked()
+                        {
+                            let name = format!("Plant {}", herbarium.entries.len() + 1);
+                            let genotype = PlantGenotype::from_editor(
+                                &config,
+                                &material_settings,
+                                &prop_config,
+                            );
+                            star_plant(&mut herbarium, name, genotype);
+                        }
+                    }
+                    let label = format!("🌿 Herbarium ({})", herbarium.entries.len());
+                    ui.toggle_value(&mut herbarium.open, label);
+                    // Only a click marks the garden changed
+                    let mut garden_enabled = garden.enabled;
+                    let label = format!("🌳 Garden ({})", garden.plants.len());
+                    if ui
+                        .toggle_value(&mut garden_enabled, label)
+                        .on_hover_text("Place several plants together in one scene")
+                        .changed()
+                    {
+                        garden.enabled = garden_enabled;
+                    }
+                    ui.toggle_value(&mut log_console.open, "📜 Log")
+                        .on_hover_text("Show recent log output");
+                    ui.toggle_value(&mut grammar_docs.open, "📖 Describe")
+                        .on_hover_text("Summarize the grammar as a document you can export");
+                    ui.toggle_value(&mut rule_graph.open, "🕸 Graph")
+                        .on_hover_text("Edit the rules as a node graph (experimental)");
+                    ui.toggle_value(&mut generator.open, "🌼 Generate")
+                        .on_hover_text("Start a grammar from a phyllotaxis or inflorescence pattern");
+                    ui.toggle_value(&mut wizard.open, "🌱 Wizard")
+                        .on_hover_text("Build a branching plant from a few traits");
+                    ui.toggle_value(&mut variations.open, "🎲 Variations")
+                        .on_hover_text("Compare the plant grown with nine different seeds");
                 });
 
                 ui.separator();
 
                 // --- Editor sections hidden in nursery mode (Issue #60) ---
-                if nursery.mode == NurseryMode::Disabled {
+                if editor_visible {
                     // --- GRAMMAR (Collapsible) ---
                     egui::CollapsingHeader::new("Grammar")
                         .default_open(false)
                         .show(ui, |ui| {
+                            if ui
+                                .button("✨ Format")
+                                .on_hover_text(
+                                    "Align arrows, relabel rules, sort #define lines, and \
+                                     normalize whitespace in the grammar and finalization",
+                                )
+                                .clicked()
+                            {
+                                let formatted = format_grammar(&config.source_code).and_then(
+                                    |source| Ok((source, format_grammar(&config.finalization_code)?)),
+                                );
+                                match formatted {
+                                    Ok((source, finalization)) => {
+                                        if source != config.source_code
+                                            || finalization != config.finalization_code
+                                        {
+                                            config.source_code = source;
+                                            config.finalization_code = finalization;
+                                            debounce.timer.reset();
+                                            debounce.pending = true;
+                                        }
+                                    }
+                                    Err(e) => toasts.error("Could not format the grammar", e),
+                                }
+                            }
                             // Editor with full available width
                             egui::ScrollArea::vertical()
                                 .min_scrolled_height(200.0)
@@ -181,20 +415,49 @@ pub fn ui_system(
                                 });
                         });
 
+                    // --- PROBLEMS (Collapsible) ---
+                    if !analysis.lints.is_empty() {
+                        egui::CollapsingHeader::new(format!(
+                            "Problems ({})",
+                            analysis.lints.len()
+                        ))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            for lint in &analysis.lints {
+                                ui.colored_label(egui::Color32::YELLOW, format!("⚠ {}", lint));
+                            }
+                        });
+                    }
+
                     // --- DEFINED CONSTANTS (Collapsible) ---
                     let sys = &engine.0;
                     if !sys.constants.is_empty() {
                         egui::CollapsingHeader::new("Defined Constants")
                             .default_open(false)
                             .show(ui, |ui| {
-                                let mut keys: Vec<String> = sys.constants.keys().cloned().collect();
-                                keys.sort();
+                                let metadata = constant_metadata(&config.source_code);
+                                // Ungrouped constants first, then each `@group` under its heading
+                                let mut groups: BTreeMap<Option<String>, Vec<String>> =
+                                    BTreeMap::new();
+                                for key in sys.constants.keys() {
+                                    let group = metadata.get(key).and_then(|m| m.group.clone());
+                                    groups.entry(group).or_default().push(key.clone());
+                                }
 
                                 let mut constants_changed = false;
                                 let available_width = ui.available_width();
 
-                                for key in keys {
-                                    if let Some(&current_val) = sys.constants.get(&key) {
+                                for (group, mut keys) in groups {
+                                    keys.sort();
+                                    if let Some(group) = group {
+                                        ui.add_space(4.0);
+                                        ui.label(egui::RichText::new(group).strong());
+                                    }
+                                    for key in keys {
+                                        let Some(&current_val) = sys.constants.get(&key) else {
+                                            continue;
+                                        };
+                                        let meta = metadata.get(&key);
                                         let mut val_f32 = current_val as f32;
 
                                         // Generate a persistent ID for this constant's state
@@ -204,18 +467,27 @@ pub fn ui_system(
                                         let anchor =
                                             ui.ctx().data(|d| d.get_temp::<f32>(slider_id));
 
-                                        // If we have an anchor, calculate range based on THAT.
-                                        // Otherwise, use the current value.
+                                        // A declared range is fixed; otherwise guess one around
+                                        // the anchor, or the current value when not dragging.
                                         let base_val = anchor.unwrap_or(val_f32);
-                                        let (lo, hi) = smart_slider_range(base_val);
+                                        let (lo, hi) = constant_slider_range(meta, base_val);
+                                        let declared = meta.is_some_and(|m| m.range.is_some());
 
                                         ui.horizontal(|ui| {
                                             ui.set_min_width(available_width);
+                                            let mut slider = egui::Slider::new(&mut val_f32, lo..=hi)
+                                                .text(&key)
+                                                .clamping(if declared {
+                                                    egui::SliderClamping::Always
+                                                } else {
+                                                    egui::SliderClamping::Never
+                                                });
+                                            if meta.is_some_and(|m| m.integer) {
+                                                slider = slider.integer();
+                                            }
                                             let response = ui.add_sized(
                                                 [available_width, ui.spacing().interact_size.y],
-                                                egui::Slider::new(&mut val_f32, lo..=hi)
-                                                    .text(&key)
-                                                    .clamping(egui::SliderClamping::Never),
+                                                slider,
                                             );
 
                                             // Store anchor on drag start
@@ -259,6 +531,124 @@ pub fn ui_system(
                             });
                     }
 
+                    // --- RULE PROBABILITIES (Collapsible) ---
+                    let stochastic_rules = rule_probabilities(&config.source_code);
+                    if !stochastic_rules.is_empty() {
+                        egui::CollapsingHeader::new("Rule Probabilities")
+                            .default_open(false)
+                            .show(ui, |ui| {
+                                let mut probabilities_changed = false;
+                                let available_width = ui.available_width();
+
+                                for rule in &stochastic_rules {
+                                    let mut probability = rule.probability;
+                                    let response = ui.add_sized(
+                                        [available_width, ui.spacing().interact_size.y],
+                                        egui::Slider::new(&mut probability, 0.0..=1.0)
+                                            .text(&rule.name)
+                                            .step_by(0.01),
+                                    );
+                                    if response.changed() {
+                                        config.source_code = update_probability_in_source(
+                                            &config.source_code,
+                                            rule.line,
+                                            probability,
+                                        );
+                                        probabilities_changed = true;
+                                    }
+                                }
+
+                                if probabilities_changed {
+                                    // Same hybrid debounce as the constants above
+                                    if !status.generating {
+                                        config.recompile_requested = true;
+                                        debounce.pending = false;
+                                    } else {
+                                        debounce.timer.reset();
+                                        debounce.pending = true;
+                                    }
+                                }
+                            });
+                    }
+
+                    // --- RULE TABLE (Collapsible) ---
+                    let rules = rule_rows(&config.source_code);
+                    if !rules.is_empty() {
+                        egui::CollapsingHeader::new("Rule Table")
+                            .default_open(false)
+                            .show(ui, |ui| {
+                                let mut toggled = None;
+                                egui::Grid::new("rule_table")
+                                    .striped(true)
+                                    .num_columns(5)
+                                    .show(ui, |ui| {
+                                        ui.label("");
+                                        ui.strong("Predecessor");
+                                        ui.strong("Condition");
+                                        ui.strong("P");
+                                        ui.strong("Successor");
+                                        ui.end_row();
+
+                                        for rule in &rules {
+                                            let mut enabled = rule.enabled;
+                                            if ui
+                                                .checkbox(&mut enabled, "")
+                                                .on_hover_text(
+                                                    "Switch the rule off by commenting it out",
+                                                )
+                                                .changed()
+                                            {
+                                                toggled = Some((rule.line, enabled));
+                                            }
+                                            let text = |text: String| {
+                                                let text = egui::RichText::new(text).monospace();
+                                                if rule.enabled { text } else { text.weak() }
+                                            };
+                                            let predecessor = match &rule.label {
+                                                Some(label) => {
+                                                    format!("{}: {}", label, rule.predecessor)
+                                                }
+                                                None => rule.predecessor.clone(),
+                                            };
+                                            ui.label(text(predecessor));
+                                            ui.label(text(rule.condition.clone()));
+                                            ui.label(text(
+                                                rule.probability
+                                                    .map(|p| format!("{:.2}", p))
+                                                    .unwrap_or_default(),
+                                            ));
+                                            ui.label(text(rule.successor.clone()));
+                                            ui.end_row();
+                                        }
+                                    });
+
+                                if let Some((line, enabled)) = toggled {
+                                    config.source_code =
+                                        set_rule_enabled(&config.source_code, line, enabled);
+                                    config.recompile_requested = true;
+                                    debounce.pending = false;
+                                }
+                            });
+                    }
+
+                    // --- WATCH EXPRESSIONS (Collapsible) ---
+                    egui::CollapsingHeader::new("Watch")
+                        .default_open(false)
+                        .show(ui, |ui| {
+                            let mut constants: Vec<(String, f64)> = engine
+                                .0
+                                .constants
+                                .iter()
+                                .map(|(name, &value)| (name.clone(), value))
+                                .collect();
+                            constants.sort_by(|a, b| a.0.cmp(&b.0));
+                            watch_ui(ui, &mut watch, &constants);
+                        })
+                        .header_response
+                        .on_hover_text(
+                            "Evaluate expressions with the grammar's constants, e.g. to check a parameter's value",
+                        );
+
                     // --- INTERPRETATION SETTINGS (Collapsible) ---
                     egui::CollapsingHeader::new("Interpretation")
                         .default_open(true)
@@ -296,34 +686,18 @@ pub fn ui_system(
                                 config.recompile_requested = true;
                             }
 
-                            ui.horizontal(|ui| {
-                                ui.label("Iterations:");
-                                if ui.button("➖").clicked() && config.iterations > 0 {
-                                    config.iterations -= 1;
-                                    config.recompile_requested = true;
-                                    debounce.pending = false;
-                                }
-                                ui.label(
-                                    egui::RichText::new(format!("{}", config.iterations))
-                                        .strong()
-                                        .size(16.0),
-                                );
-                                if ui.button("➕").clicked() {
-                                    config.iterations += 1;
-                                    config.recompile_requested = true;
-                                    debounce.pending = false;
-                                }
-                            });
+                            if iterations_ui(ui, &mut config) {
+                                debounce.pending = false;
+                            }
+                            if config.source_code.contains("#timed")
+                                && clock_ui(ui, &mut config, &mut playback)
+                            {
+                                debounce.pending = false;
+                            }
 
-                            ui.horizontal(|ui| {
-                                ui.label("Random Seed:");
-                                if ui
-                                    .add(egui::DragValue::new(&mut config.seed).speed(1.0))
-                                    .changed()
-                                {
-                                    config.recompile_requested = true;
-                                }
-                            });
+                            if seed_ui(ui, &mut config) {
+                                debounce.pending = false;
+                            }
 
                             if ui
                                 .add(
@@ -334,6 +708,73 @@ pub fn ui_system(
                             {
                                 dirty.geometry = true;
                             }
+                            if ui
+                                .checkbox(&mut config.weld_vertices, "Weld Vertices")
+                                .on_hover_text(
+                                    "Merge coincident ring vertices and drop collapsed \
+                                     triangles before upload; about halves the vertex count",
+                                )
+                                .changed()
+                            {
+                                dirty.geometry = true;
+                            }
+                            ui.horizontal(|ui| {
+                                ui.label("Shading:");
+                                let before = config.shading;
+                                egui::ComboBox::from_id_salt("mesh_shading")
+                                    .selected_text(before.name())
+                                    .show_ui(ui, |ui| {
+                                        for shading in Shading::presets() {
+                                            let selected = before.name() == shading.name();
+                                            if ui
+                                                .selectable_label(selected, shading.name())
+                                                .clicked()
+                                                && !selected
+                                            {
+                                                config.shading = shading;
+                                            }
+                                        }
+                                    });
+                                if let Shading::AutoSmooth(angle) = &mut config.shading {
+                                    ui.add(
+                                        egui::Slider::new(angle, 0.0..=180.0)
+                                            .suffix("°")
+                                            .text("Angle"),
+                                    )
+                                    .on_hover_text(
+                                        "Edges sharper than this angle are split and \
+                                         shaded hard",
+                                    );
+                                }
+                                if config.shading != before {
+                                    dirty.geometry = true;
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                let mut spaced = config.ring_spacing.is_some();
+                                if ui
+                                    .checkbox(&mut spaced, "Ring Spacing")
+                                    .on_hover_text(
+                                        "Subdivide long segments and merge short ones so rings \
+                                         sit about this far apart; smoother wind and skinning",
+                                    )
+                                    .changed()
+                                {
+                                    config.ring_spacing = spaced.then_some(0.5);
+                                    dirty.geometry = true;
+                                }
+                                if let Some(spacing) = &mut config.ring_spacing
+                                    && ui
+                                        .add(
+                                            egui::DragValue::new(spacing)
+                                                .range(0.01..=100.0)
+                                                .speed(0.01),
+                                        )
+                                        .changed()
+                                {
+                                    dirty.geometry = true;
+                                }
+                            });
                         });
 
                     ui.collapsing("Physics & Tropism", |ui| {
@@ -369,16 +810,62 @@ pub fn ui_system(
                                     ui.add(egui::DragValue::new(&mut t.z).speed(0.1)).changed();
                             });
                         }
+                        ui.horizontal(|ui| {
+                            ui.label("Presets:");
+                            for preset in TropismPreset::ALL {
+                                if ui.button(preset.name()).clicked() {
+                                    tropism_gizmo.apply_preset(preset, &mut config.tropism);
+                                    tropism_changed = true;
+                                }
+                            }
+                        });
+                        if tropism_gizmo.follow_light {
+                            ui.horizontal(|ui| {
+                                ui.label("Light:");
+                                let light = &mut tropism_gizmo.light;
+                                let mut moved = false;
+                                moved |= ui.add(egui::DragValue::new(&mut light.x)).changed();
+                                moved |= ui.add(egui::DragValue::new(&mut light.y)).changed();
+                                moved |= ui.add(egui::DragValue::new(&mut light.z)).changed();
+                                if moved {
+                                    tropism_gizmo.apply_preset(
+                                        TropismPreset::Phototropism,
+                                        &mut config.tropism,
+                                    );
+                                    tropism_changed = true;
+                                }
+                            });
+                        }
                         if tropism_changed {
                             config.recompile_requested = true;
                         }
+                        ui.checkbox(&mut tropism_gizmo.show, "Show Gizmo").on_hover_text(
+                            "Draw the tropism as an arrow above the plant; drag its tip \
+                             (or the light) with the left mouse button to edit it",
+                        );
                     });
 
                     ui.collapsing("Material Palette", |ui| {
+                        ui.collapsing("Palettes", |ui| {
+                            palettes_ui(ui, &mut palettes, &mut material_settings, &export_dir);
+                        });
                         bevy_symbios::ui::material_palette_editor(
                             ui,
                             &mut material_settings.settings,
                         );
+                        ui.collapsing("Texture Maps", |ui| {
+                            texture_maps_ui(ui, &mut textures, &material_settings);
+                        });
+                        ui.collapsing("Gradients", |ui| {
+                            if gradients_ui(ui, &mut gradients, &material_settings) {
+                                dirty.geometry = true;
+                            }
+                        });
+                        ui.collapsing("Cross Sections", |ui| {
+                            if cross_sections_ui(ui, &mut cross_sections, &material_settings) {
+                                dirty.geometry = true;
+                            }
+                        });
                     });
 
                     ui.collapsing("Prop Settings", |ui| {
@@ -389,6 +876,17 @@ pub fn ui_system(
                                     .text("Prop Scale"),
                             )
                             .changed();
+                        let mut local_density = prop_config.prop_density * 100.0;
+                        let density_changed = ui
+                            .add(
+                                egui::Slider::new(&mut local_density, 0.0..=100.0)
+                                    .text("Prop Density")
+                                    .suffix("%"),
+                            )
+                            .on_hover_text(
+                                "Show only part of the props, chosen by the seed, to keep heavy plants interactive; exports use the density under Batch Export",
+                            )
+                            .changed();
 
                         ui.separator();
                         ui.label("Prop ID Mappings:");
@@ -423,16 +921,104 @@ pub fn ui_system(
                             });
                         }
 
+                        let mut leaf_texture_change = None;
+                        ui.horizontal(|ui| {
+                            ui.label("Leaf Texture:").on_hover_text(
+                                "Alpha-tested texture of Billboard and Cross Quad props; import textures under Material Palette",
+                            );
+                            let current = prop_config.leaf_texture.as_deref();
+                            egui::ComboBox::from_id_salt("leaf_texture")
+                                .selected_text(current.unwrap_or("Built-in"))
+                                .show_ui(ui, |ui| {
+                                    if ui
+                                        .selectable_label(current.is_none(), "Built-in")
+                                        .clicked()
+                                    {
+                                        leaf_texture_change = Some(None);
+                                    }
+                                    for texture in &textures.textures {
+                                        if ui
+                                            .selectable_label(
+                                                current == Some(texture.name.as_str()),
+                                                &texture.name,
+                                            )
+                                            .clicked()
+                                        {
+                                            leaf_texture_change = Some(Some(texture.name.clone()));
+                                        }
+                                    }
+                                });
+                        });
+                        if let Some(texture) = leaf_texture_change {
+                            prop_config.leaf_texture = texture;
+                        }
+
+                        ui.separator();
+                        ui.label("Prop Jitter:")
+                            .on_hover_text("Random rotation, size, and offset along the normal, drawn from the seed");
+                        let mut jitter_changes: Vec<(u16, PropJitter)> = Vec::new();
+                        egui::Grid::new("prop_jitter_grid").show(ui, |ui| {
+                            ui.label("");
+                            ui.label("Rotation°");
+                            ui.label("Scale ±");
+                            ui.label("Offset");
+                            ui.end_row();
+                            for prop_id in 0u16..4 {
+                                let mut jitter = prop_config
+                                    .prop_jitter
+                                    .get(&prop_id)
+                                    .copied()
+                                    .unwrap_or_default();
+                                ui.label(format!("~{}", prop_id));
+                                let changed = ui
+                                    .add(
+                                        egui::DragValue::new(&mut jitter.rotation)
+                                            .range(0.0..=180.0)
+                                            .speed(0.5),
+                                    )
+                                    .changed()
+                                    | ui.add(
+                                        egui::DragValue::new(&mut jitter.scale)
+                                            .range(0.0..=0.9)
+                                            .speed(0.01),
+                                    )
+                                    .changed()
+                                    | ui.add(
+                                        egui::DragValue::new(&mut jitter.offset)
+                                            .range(0.0..=1.0)
+                                            .speed(0.01),
+                                    )
+                                    .changed();
+                                if changed {
+                                    jitter_changes.push((prop_id, jitter));
+                                }
+                                ui.end_row();
+                            }
+                        });
+
                         if scale_changed {
                             prop_config.prop_scale = local_prop_scale;
                             dirty.geometry = true;
                         }
+                        if density_changed {
+                            prop_config.prop_density = local_density / 100.0;
+                            dirty.geometry = true;
+                        }
+                        for (prop_id, jitter) in jitter_changes {
+                            if jitter.is_zero() {
+                                prop_config.prop_jitter.remove(&prop_id);
+                            } else {
+                                prop_config.prop_jitter.insert(prop_id, jitter);
+                            }
+                            dirty.geometry = true;
+                        }
                         for (prop_id, mesh_type) in mesh_changes {
                             prop_config.prop_meshes.insert(prop_id, mesh_type);
                             dirty.geometry = true;
                         }
                     });
 
+                    #[cfg(feature = "export")]
                     ui.collapsing("Batch Export", |ui| {
                         ui.horizontal(|ui| {
                             ui.label("Base Name:");
@@ -446,6 +1032,87 @@ pub fn ui_system(
                                     .range(1..=100)
                                     .speed(0.5),
                             );
+                            ui.label("Vary:");
+                            let mut constants: Vec<&String> = engine.0.constants.keys().collect();
+                            constants.sort();
+                            let mut choices = vec![SweepParameter::Seed, SweepParameter::Iterations];
+                            choices.extend(
+                                constants
+                                    .into_iter()
+                                    .map(|name| SweepParameter::Constant(name.clone())),
+                            );
+                            let selected = export_config.sweep.parameter.name();
+                            egui::ComboBox::from_id_salt("export_sweep")
+                                .selected_text(selected)
+                                .show_ui(ui, |ui| {
+                                    for choice in choices {
+                                        let name = choice.name();
+                                        if ui
+                                            .selectable_label(
+                                                export_config.sweep.parameter == choice,
+                                                name,
+                                            )
+                                            .clicked()
+                                            && export_config.sweep.parameter != choice
+                                        {
+                                            // Start the range around the current value
+                                            let current = match &choice {
+                                                SweepParameter::Seed => 0.0,
+                                                SweepParameter::Iterations => {
+                                                    config.iterations as f32
+                                                }
+                                                SweepParameter::Constant(name) => engine
+                                                    .0
+                                                    .constants
+                                                    .get(name)
+                                                    .map_or(0.0, |&v| v as f32),
+                                            };
+                                            export_config.sweep.start = current;
+                                            export_config.sweep.end = current * 2.0;
+                                            export_config.sweep.parameter = choice;
+                                        }
+                                    }
+                                })
+                                .response
+                                .on_hover_text(
+                                    "Vary a constant or the iterations from the first to the last file instead of the seed",
+                                );
+                        });
+                        if export_config.sweep.parameter != SweepParameter::Seed {
+                            ui.horizontal(|ui| {
+                                ui.label("From:");
+                                ui.add(egui::DragValue::new(&mut export_config.sweep.start).speed(0.1));
+                                ui.label("To:");
+                                ui.add(egui::DragValue::new(&mut export_config.sweep.end).speed(0.1));
+                                let count = export_config.variation_count;
+                                let first = export_config.sweep.suffix(export_config.sweep.value(0, count));
+                                let last = export_config
+                                    .sweep
+                                    .suffix(export_config.sweep.value(count.saturating_sub(1), count));
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "{0}_{1} … {0}_{2}",
+                                        export_config.base_filename, first, last
+                                    ))
+                                    .small()
+                                    .color(egui::Color32::GRAY),
+                                );
+                            });
+                        }
+
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut export_config.animate, "Growth animation")
+                                .on_hover_text(
+                                    "Export the variations as frames: one animated GLB, or a numbered OBJ sequence. Sweep the iterations or a growth constant.",
+                                );
+                            if export_config.animate && export_config.format == ExportFormat::Glb {
+                                ui.add(
+                                    egui::DragValue::new(&mut export_config.frame_duration)
+                                        .range(0.01..=10.0)
+                                        .speed(0.01)
+                                        .suffix(" s/frame"),
+                                );
+                            }
                         });
 
                         ui.horizontal(|ui| {
@@ -467,23 +1134,281 @@ pub fn ui_system(
                                 });
                         });
 
-                        if export_status.exporting {
-                            // Show progress bar while exporting
-                            let completed = export_status
-                                .progress
-                                .as_ref()
-                                .map(|p| p.load(std::sync::atomic::Ordering::Relaxed))
-                                .unwrap_or(0);
-                            let total = export_status.total.max(1);
-                            let fraction = completed as f32 / total as f32;
+                        ui.horizontal(|ui| {
+                            ui.label("Axes:");
+                            egui::ComboBox::from_id_salt("export_axes")
+                                .selected_text(export_config.axes.name())
+                                .show_ui(ui, |ui| {
+                                    for axes in AxisConvention::ALL {
+                                        ui.selectable_value(
+                                            &mut export_config.axes,
+                                            *axes,
+                                            axes.name(),
+                                        );
+                                    }
+                                })
+                                .response
+                                .on_hover_text(
+                                    "glTF importers already convert from Y-up; use Z-up for GLB only if your importer skips that step",
+                                );
+                        });
 
-                            ui.add(
-                                egui::ProgressBar::new(fraction)
-                                    .text(format!("Exporting {}/{}...", completed, total)),
-                            );
-                        } else {
-                            if ui
-                                .button(format!("Export {} Files", export_config.format.name()))
+                        ui.horizontal(|ui| match export_config.format {
+                            ExportFormat::Obj => {
+                                ui.label("Precision:");
+                                ui.add(
+                                    egui::DragValue::new(&mut export_config.obj_decimals)
+                                        .range(1..=9)
+                                        .suffix(" decimals"),
+                                )
+                                .on_hover_text(
+                                    "Digits after the decimal point; 4 is plenty for most plants and much smaller",
+                                );
+                            }
+                            ExportFormat::Glb => {
+                                ui.checkbox(&mut export_config.compact_indices, "16-bit indices")
+                                    .on_hover_text(
+                                        "Use 16-bit indices for meshes with fewer than 65,536 vertices",
+                                    );
+                                ui.checkbox(&mut export_config.quantize_positions, "Quantize")
+                                    .on_hover_text(
+                                        "Store positions as 16-bit integers (KHR_mesh_quantization); requires importer support",
+                                    );
+                                ui.checkbox(&mut export_config.skinned, "Armature")
+                                    .on_hover_text(
+                                        "Skin the branches to a joint per strand point for wind and interaction rigs; orders beyond the levels below follow their parent joint",
+                                    );
+                            }
+                            ExportFormat::Skeleton => {
+                                ui.label(
+                                    egui::RichText::new(
+                                        "Strand points with position, rotation, radius, and material; no meshes",
+                                    )
+                                    .small()
+                                    .color(egui::Color32::GRAY),
+                                );
+                            }
+                        });
+
+                        let mut export_density = export_config.prop_density * 100.0;
+                        if ui
+                            .add(
+                                egui::Slider::new(&mut export_density, 0.0..=100.0)
+                                    .text("Prop Density")
+                                    .suffix("%"),
+                            )
+                            .on_hover_text(
+                                "Fraction of props exported, chosen by the seed like the viewport's Prop Density",
+                            )
+                            .changed()
+                        {
+                            export_config.prop_density = export_density / 100.0;
+                        }
+
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut export_config.weld_vertices, "Weld")
+                                .on_hover_text(
+                                    "Merge duplicate vertices where rings and strands meet",
+                                );
+                            ui.add_enabled_ui(export_config.weld_vertices, |ui| {
+                                ui.label("Dist:");
+                                ui.add(
+                                    egui::DragValue::new(&mut export_config.weld.position_tolerance)
+                                        .range(0.000001..=0.1)
+                                        .speed(0.0001),
+                                )
+                                .on_hover_text("Maximum distance between merged positions");
+                                ui.label("Normal:");
+                                ui.add(
+                                    egui::DragValue::new(&mut export_config.weld.normal_tolerance)
+                                        .range(0.0001..=1.0)
+                                        .speed(0.001),
+                                )
+                                .on_hover_text("Maximum normal difference; raise to smooth hard edges");
+                            });
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut export_config.decimate, "Decimate")
+                                .on_hover_text(
+                                    "Simplify each material mesh by quadric-error edge collapses, \
+                                     for game-ready LODs; skinned meshes are kept whole",
+                                );
+                            ui.add_enabled_ui(export_config.decimate, |ui| {
+                                let before = export_config.decimation;
+                                egui::ComboBox::from_id_salt("export_decimation")
+                                    .selected_text(before.name())
+                                    .show_ui(ui, |ui| {
+                                        for target in DecimationTarget::presets() {
+                                            let selected = before.name() == target.name();
+                                            if ui.selectable_label(selected, target.name()).clicked()
+                                                && !selected
+                                            {
+                                                export_config.decimation = target;
+                                            }
+                                        }
+                                    });
+                                match &mut export_config.decimation {
+                                    DecimationTarget::Triangles(max) => {
+                                        ui.add(
+                                            egui::DragValue::new(max)
+                                                .range(1..=10_000_000)
+                                                .suffix(" tris"),
+                                        )
+                                        .on_hover_text("Most triangles kept per material mesh");
+                                    }
+                                    DecimationTarget::Error(max) => {
+                                        ui.add(
+                                            egui::DragValue::new(max)
+                                                .range(0.00001..=10.0)
+                                                .speed(0.001),
+                                        )
+                                        .on_hover_text(
+                                            "Largest allowed deviation from the full mesh, in world units",
+                                        );
+                                    }
+                                }
+                            });
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label("Include:");
+                            ui.checkbox(&mut export_config.filter.include_branches, "Branches");
+                            ui.checkbox(&mut export_config.filter.include_props, "Props");
+                        });
+
+                        let mut material_ids: Vec<u8> =
+                            material_settings.settings.keys().copied().collect();
+                        material_ids.sort_unstable();
+                        ui.horizontal_wrapped(|ui| {
+                            ui.label("Materials:");
+                            for material_id in material_ids {
+                                let mut included =
+                                    export_config.filter.includes_material(material_id);
+                                if ui
+                                    .checkbox(&mut included, format!(",({})", material_id))
+                                    .changed()
+                                {
+                                    if included {
+                                        export_config
+                                            .filter
+                                            .excluded_materials
+                                            .remove(&material_id);
+                                    } else {
+                                        export_config.filter.excluded_materials.insert(material_id);
+                                    }
+                                }
+                            }
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.checkbox(
+                                &mut export_config.split_by_branch_order,
+                                "Split by branch order",
+                            )
+                            .on_hover_text(
+                                "Write trunk, branches, and twigs as separate OBJ objects for LOD stripping. GLB exports always nest them as named nodes.",
+                            );
+                            if export_config.split_by_branch_order
+                                || export_config.format == ExportFormat::Glb
+                            {
+                                ui.add(
+                                    egui::DragValue::new(&mut export_config.branch_order_levels)
+                                        .range(2..=6)
+                                        .prefix("levels: "),
+                                );
+                            }
+                        });
+
+                        ui.checkbox(&mut export_config.write_metadata, "Metadata")
+                            .on_hover_text(
+                                "Write a .meta.json sidecar with the grammar, parameters, seed, materials, and app version next to each file",
+                            );
+
+                        #[cfg(not(target_arch = "wasm32"))]
+                        if let Some(pick) = &export_config.folder_pick
+                            && let Some(picked) = pick.lock().ok().and_then(|mut guard| guard.take())
+                        {
+                            if let Some(folder) = picked {
+                                export_config.output_dir = folder;
+                            }
+                            export_config.folder_pick = None;
+                        }
+
+                        #[cfg(not(target_arch = "wasm32"))]
+                        ui.horizontal(|ui| {
+                            ui.label("Folder:");
+                            let mut folder = export_config.output_dir.display().to_string();
+                            if ui
+                                .add(
+                                    egui::TextEdit::singleline(&mut folder)
+                                        .desired_width(ui.available_width() - 70.0),
+                                )
+                                .changed()
+                            {
+                                export_config.output_dir = std::path::PathBuf::from(folder);
+                            }
+                            let picking = export_config.folder_pick.is_some();
+                            if ui
+                                .add_enabled(!picking, egui::Button::new("Browse…"))
+                                .on_hover_text("Choose the folder exports are written to")
+                                .clicked()
+                            {
+                                export_config.folder_pick = Some(pick_folder(&export_config.output_dir));
+                            }
+                        });
+
+                        #[cfg(not(target_arch = "wasm32"))]
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut export_config.batch_subfolder, "Batch folder")
+                                .on_hover_text(
+                                    "Write each batch into a new dated folder, e.g. exports/2024-06-01_LSystem_Variant/",
+                                );
+                            ui.label("Existing:");
+                            egui::ComboBox::from_id_salt("export_overwrite")
+                                .selected_text(export_config.overwrite.name())
+                                .show_ui(ui, |ui| {
+                                    for policy in OverwritePolicy::ALL {
+                                        ui.selectable_value(
+                                            &mut export_config.overwrite,
+                                            *policy,
+                                            policy.name(),
+                                        );
+                                    }
+                                })
+                                .response
+                                .on_hover_text(
+                                    "What to do when a file of the same name already exists",
+                                );
+                        });
+
+                        if export_status.exporting {
+                            // Show progress bar while exporting
+                            let completed = export_status
+                                .progress
+                                .as_ref()
+                                .map(|p| p.load(std::sync::atomic::Ordering::Relaxed))
+                                .unwrap_or(0);
+                            let total = export_status.total.max(1);
+                            let fraction = completed as f32 / total as f32;
+
+                            ui.horizontal(|ui| {
+                                ui.add(
+                                    egui::ProgressBar::new(fraction)
+                                        .text(format!("Exporting {}/{}...", completed, total))
+                                        .desired_width(ui.available_width() - 80.0),
+                                );
+                                if ui
+                                    .button("✖ Cancel")
+                                    .on_hover_text("Stop after the plant being written")
+                                    .clicked()
+                                {
+                                    export_status.cancel();
+                                }
+                            });
+                        } else {
+                            if ui
+                                .button(format!("Export {} Files", export_config.format.name()))
                                 .clicked()
                             {
                                 export_config.export_requested = true;
@@ -494,32 +1419,324 @@ pub fn ui_system(
                                     egui::Color32::RED,
                                     format!("Export failed: {}", err),
                                 );
-                            } else if export_status.last_export_count > 0 {
+                            } else if export_status.cancelled {
                                 ui.colored_label(
-                                    egui::Color32::GREEN,
-                                    format!("Exported {} files", export_status.last_export_count),
+                                    egui::Color32::YELLOW,
+                                    format!(
+                                        "⚠ Export cancelled after {} files",
+                                        export_status.last_export_count
+                                    ),
+                                );
+                            } else if export_status.last_export_count > 0 {
+                                let message = match &export_status.output_dir {
+                                    Some(dir) if cfg!(not(target_arch = "wasm32")) => format!(
+                                        "Exported {} files to {}",
+                                        export_status.last_export_count,
+                                        dir.display()
+                                    ),
+                                    _ => format!(
+                                        "Exported {} files",
+                                        export_status.last_export_count
+                                    ),
+                                };
+                                ui.colored_label(egui::Color32::GREEN, message);
+                                if let Some(weld) = export_status.weld_stats {
+                                    ui.label(
+                                        egui::RichText::new(format!(
+                                            "Welded {} → {} vertices (−{:.0}%), {} collapsed triangles removed",
+                                            weld.vertices_before,
+                                            weld.vertices_after,
+                                            weld.reduction() * 100.0,
+                                            weld.degenerate_triangles
+                                        ))
+                                        .small(),
+                                    );
+                                }
+                                if let Some(decimation) = export_status.decimation_stats {
+                                    ui.label(
+                                        egui::RichText::new(format!(
+                                            "Decimated {} → {} triangles (−{:.0}%)",
+                                            decimation.triangles_before,
+                                            decimation.triangles_after,
+                                            decimation.reduction() * 100.0
+                                        ))
+                                        .small(),
+                                    );
+                                }
+                            }
+                            if export_status.skipped > 0 && export_status.error.is_none() {
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "{} existing files skipped",
+                                        export_status.skipped
+                                    ))
+                                    .small()
+                                    .color(egui::Color32::GRAY),
+                                );
+                            }
+                            if !export_status.failures.is_empty()
+                                && export_status.error.is_none()
+                            {
+                                ui.collapsing(
+                                    egui::RichText::new(format!(
+                                        "⚠ {} plants failed",
+                                        export_status.failures.len()
+                                    ))
+                                    .color(egui::Color32::YELLOW),
+                                    |ui| {
+                                        for failure in &export_status.failures {
+                                            ui.label(
+                                                egui::RichText::new(format!(
+                                                    "{}: {}",
+                                                    failure.file_stem, failure.error
+                                                ))
+                                                .small(),
+                                            );
+                                        }
+                                    },
                                 );
                             }
                         }
 
-                        #[cfg(not(target_arch = "wasm32"))]
+                        #[cfg(target_arch = "wasm32")]
                         ui.label(
-                            egui::RichText::new("Files saved to ./exports/")
+                            egui::RichText::new("Files download via browser")
                                 .small()
                                 .color(egui::Color32::GRAY),
                         );
+                    });
 
-                        #[cfg(target_arch = "wasm32")]
+                    #[cfg(target_arch = "wasm32")]
+                    ui.collapsing("Share", |ui| {
+                        let status_id = egui::Id::new("share_link_status");
+                        if ui
+                            .button("🔗 Copy Share Link")
+                            .on_hover_text("Encode the grammar and settings into a URL and copy it")
+                            .clicked()
+                        {
+                            let genotype = PlantGenotype::from_editor(
+                                &config,
+                                &material_settings,
+                                &prop_config,
+                            );
+                            let status = crate::core::share::copy_share_link(&genotype)
+                                .map(|url| format!("Link copied ({} characters)", url.len()));
+                            ui.data_mut(|data| data.insert_temp(status_id, status));
+                        }
+                        match ui.data(|data| data.get_temp::<Result<String, String>>(status_id)) {
+                            Some(Ok(message)) => {
+                                ui.colored_label(egui::Color32::GREEN, message);
+                            }
+                            Some(Err(err)) => {
+                                ui.colored_label(
+                                    egui::Color32::RED,
+                                    format!("Share failed: {}", err),
+                                );
+                            }
+                            None => {}
+                        }
                         ui.label(
-                            egui::RichText::new("Files download via browser")
+                            egui::RichText::new("Opening the link restores this plant")
                                 .small()
                                 .color(egui::Color32::GRAY),
                         );
                     });
 
+                    #[cfg(feature = "nursery")]
+                    ui.collapsing("Compare", |ui| {
+                        ui.horizontal(|ui| {
+                            if ui
+                                .button("📌 Pin Current as B")
+                                .on_hover_text("Snapshot the editor plant for side-by-side comparison")
+                                .clicked()
+                            {
+                                comparison.pin(
+                                    PlantGenotype::from_editor(
+                                        &config,
+                                        &material_settings,
+                                        &prop_config,
+                                    ),
+                                    ComparisonSource::Snapshot,
+                                );
+                            }
+
+                            if !nursery.population.is_empty() {
+                                egui::ComboBox::from_id_salt("compare_nursery")
+                                    .selected_text("Pin Nursery…")
+                                    .show_ui(ui, |ui| {
+                                        for index in 0..nursery.population.len() {
+                                            if ui
+                                                .selectable_label(false, format!("#{}", index + 1))
+                                                .clicked()
+                                                && let Some(genotype) =
+                                                    nursery.get_genotype(index)
+                                            {
+                                                comparison.pin(
+                                                    genotype,
+                                                    ComparisonSource::NurseryIndividual {
+                                                        generation: nursery.generation,
+                                                        index,
+                                                    },
+                                                );
+                                            }
+                                        }
+                                    });
+                            }
+                        });
+
+                        let Some((_, source)) = &comparison.pinned else {
+                            ui.label(
+                                egui::RichText::new("Pin a variant to compare it with the editor")
+                                    .small()
+                                    .color(egui::Color32::GRAY),
+                            );
+                            return;
+                        };
+                        ui.label(format!("A: Editor (left)   B: {} (right)", source.label()));
+                        if let Some(err) = &comparison.error {
+                            ui.colored_label(egui::Color32::RED, err);
+                        }
+
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut comparison.enabled, "Split View");
+                            ui.checkbox(&mut comparison.sync_cameras, "Sync Cameras")
+                                .on_hover_text("Drive the right camera from the left one");
+                            if ui.button("Unpin").clicked() {
+                                comparison.pinned = None;
+                                comparison.enabled = false;
+                            }
+                        });
+                    });
+
+                    ui.collapsing("Include Library", |ui| {
+                        if include_library_ui(ui, &mut includes) {
+                            if let Err(e) = includes.save() {
+                                warn!("Failed to save include library: {}", e);
+                            }
+                            debounce.timer.reset();
+                            debounce.pending = true;
+                        }
+                    });
+
+                    ui.collapsing("History", |ui| {
+                        if let Some(entry) = history_ui(ui, &mut history) {
+                            entry.apply_to(&mut config);
+                            debounce.pending = false;
+                        }
+                    });
+
+                    ui.collapsing("Statistics", |ui| {
+                        statistics_ui(ui, &render_state, &analysis.memory);
+                    });
+
+                    ui.collapsing("Analysis", |ui| {
+                        analysis_ui(ui, &analysis.usage, engine.0.state.len());
+                    });
+
+                    ui.collapsing("Mesh Check", |ui| {
+                        mesh_check_ui(ui, &mut manifold_check);
+                    });
+
+                    ui.collapsing("Intersections", |ui| {
+                        intersection_check_ui(ui, &mut intersection_check);
+                    });
+
+                    #[cfg(feature = "physics")]
+                    ui.collapsing("Physics Preview", |ui| {
+                        physics_preview_ui(ui, &mut physics_preview);
+                    });
+
+                    ui.collapsing("Debug", |ui| {
+                        if wireframe_supported() {
+                            ui.checkbox(&mut debug_view.wireframe, "Wireframe");
+                        } else {
+                            ui.label(
+                                egui::RichText::new("Wireframe is unavailable in the browser")
+                                    .small()
+                                    .color(egui::Color32::GRAY),
+                            );
+                        }
+
+                        ui.checkbox(&mut debug_view.show_normals, "Show Normals");
+                        if debug_view.show_normals {
+                            ui.add(
+                                egui::Slider::new(&mut debug_view.normal_length, 0.1..=100.0)
+                                    .text("Normal Length")
+                                    .logarithmic(true),
+                            );
+                        }
+
+                        ui.separator();
+                        if ui
+                            .button("🐞 Create Bug Report Bundle")
+                            .on_hover_text(
+                                "Zip the grammar, settings, versions, recent log and a screenshot",
+                            )
+                            .clicked()
+                        {
+                            bug_report.requested = true;
+                        }
+                        match &bug_report.status {
+                            Some(Ok(filename)) => {
+                                ui.colored_label(
+                                    egui::Color32::GREEN,
+                                    format!("✓ Saved {}", filename),
+                                );
+                            }
+                            Some(Err(e)) => {
+                                ui.colored_label(egui::Color32::RED, format!("❌ {}", e));
+                            }
+                            None => {}
+                        }
+                    });
+
+                    ui.collapsing("Keyboard Shortcuts", |ui| {
+                        keybindings_ui(ui, &mut keybindings);
+                    });
+
                     // --- STATUS ---
-                    if status.generating {
-                        ui.colored_label(egui::Color32::YELLOW, "⏳ Generating...");
+                    if meshing.is_running() {
+                        ui.horizontal(|ui| {
+                            let done = meshing.completed_strands();
+                            let total = meshing.total_strands.max(1);
+                            ui.add(
+                                egui::ProgressBar::new(done as f32 / total as f32)
+                                    .text(format!("Meshing {}/{} strands", done, total))
+                                    .desired_width(ui.available_width() - 80.0),
+                            );
+                            if ui.button("✖ Cancel").clicked() {
+                                meshing.cancel();
+                                meshing.cancelled = true;
+                                info!("Meshing cancelled");
+                            }
+                        });
+                    } else if status.generating {
+                        ui.horizontal(|ui| {
+                            match status.progress {
+                                Some(progress) => {
+                                    // The finalization pass counts as one more step
+                                    let steps = progress.iterations
+                                        + usize::from(!config.finalization_code.trim().is_empty());
+                                    let done = progress.iteration + usize::from(progress.finalized);
+                                    ui.add(
+                                        egui::ProgressBar::new(done as f32 / steps.max(1) as f32)
+                                            .text(format!(
+                                                "Iteration {}/{}: {} modules",
+                                                progress.iteration,
+                                                progress.iterations,
+                                                format_count(progress.modules as f64)
+                                            ))
+                                            .desired_width(ui.available_width() - 80.0),
+                                    );
+                                }
+                                None => {
+                                    ui.colored_label(egui::Color32::YELLOW, "⏳ Generating...");
+                                }
+                            }
+                            if ui.button("✖ Cancel").clicked() {
+                                status.cancel_requested = true;
+                            }
+                        });
                     } else if let Some(err) = &status.error {
                         ui.group(|ui| {
                             ui.colored_label(egui::Color32::RED, "❌ Parse Error:");
@@ -531,22 +1748,56 @@ pub fn ui_system(
                         });
                     } else if debounce.pending {
                         ui.colored_label(egui::Color32::YELLOW, "⏳ Typing...");
+                    } else if meshing.cancelled {
+                        ui.colored_label(
+                            egui::Color32::YELLOW,
+                            "⚠ Meshing cancelled (showing previous mesh)",
+                        );
                     } else {
                         ui.horizontal(|ui| {
                             ui.colored_label(egui::Color32::GREEN, "✅ Mesh Ready");
                             let total_ms =
                                 render_state.derivation_time_ms + render_state.meshing_time_ms;
                             ui.label(format!(
-                                "| {} Verts | {:.1}ms (D:{:.1} M:{:.1})",
-                                render_state.total_vertices,
+                                "| {:.1}ms (D:{:.1} M:{:.1})",
                                 total_ms,
                                 render_state.derivation_time_ms,
                                 render_state.meshing_time_ms,
                             ));
+                            if let Some(seed) = status.last_seed {
+                                ui.label(format!("| seed {}", seed))
+                                    .on_hover_text("Seed of the last derivation");
+                            }
                         });
                     }
 
                     ui.checkbox(&mut config.auto_update, "Live Update");
+                    #[cfg(not(target_arch = "wasm32"))]
+                    ui.add_enabled(
+                        has_sections(&config.source_code),
+                        egui::Checkbox::new(
+                            &mut config.parallel_derivation,
+                            "Parallel Sub-Systems",
+                        ),
+                    )
+                    .on_hover_text("Derive #system sub-grammars on separate threads")
+                    .on_disabled_hover_text(
+                        "Only grammars with #system sections derive in parallel; \
+                         a single grammar is rewritten on one thread",
+                    );
+                    ui.horizontal(|ui| {
+                        let mut capped = config.memory_cap_mb.is_some();
+                        if ui
+                            .checkbox(&mut capped, "Memory Cap")
+                            .on_hover_text("Stop derivations estimated to need more memory")
+                            .changed()
+                        {
+                            config.memory_cap_mb = capped.then_some(512);
+                        }
+                        if let Some(cap) = &mut config.memory_cap_mb {
+                            ui.add(egui::DragValue::new(cap).range(1..=65536).suffix(" MB"));
+                        }
+                    });
                     if !config.auto_update && ui.button("▶ Run / Recompile").clicked() {
                         config.recompile_requested = true;
                         debounce.pending = false;
@@ -557,26 +1808,810 @@ pub fn ui_system(
                 // Pass immutable refs to avoid triggering DerefMut change
                 // detection on ResMut<MaterialSettingsMap> every frame.
                 // Mutations are applied only when the user loads a genotype.
+                #[cfg(feature = "nursery")]
                 if let Some(genotype) =
-                    nursery_ui(ui, &mut nursery, &config, &material_settings, &prop_config)
+                    nursery_ui(
+                        ui,
+                        &mut nursery,
+                        &thumbnails,
+                        &config,
+                        &material_settings,
+                        &prop_config,
+                    )
+                {
+                    genotype.apply_to_editor(&mut config, &mut material_settings, &mut prop_config);
+                }
+            });
+
+        #[cfg(feature = "nursery")]
+        if let Some(index) = nursery.star_requested {
+            nursery.star_requested = None;
+            if let Some(genotype) = nursery.get_genotype(index) {
+                let name = format!("Nursery gen {} #{}", nursery.generation, index + 1);
+                star_plant(&mut herbarium, name, genotype);
+            }
+        }
+
+        #[cfg(feature = "nursery")]
+        let can_inject = nursery.mode == NurseryMode::Enabled && !nursery.selected.is_empty();
+        #[cfg(not(feature = "nursery"))]
+        let can_inject = false;
+        log_console_ui(ctx, &mut log_console);
+        grammar_docs_ui(ctx, &mut grammar_docs, &config, &export_dir);
+        if let Some(generated) = generator_ui(ctx, &mut generator) {
+            config.source_code = generated.source;
+            config.finalization_code.clear();
+            config.iterations = generated.iterations;
+            config.recompile_requested = true;
+            debounce.pending = false;
+        }
+        if let Some(grammar) = plant_wizard_ui(ctx, &mut wizard) {
+            config.source_code = grammar.source;
+            config.finalization_code = grammar.finalization;
+            config.iterations = grammar.iterations;
+            config.recompile_requested = true;
+            debounce.pending = false;
+        }
+        if let Some(seed) = variations_ui(ctx, &mut variations, &preset_thumbnails, &config) {
+            config.seed = seed;
+            config.recompile_requested = true;
+        }
+        if rule_graph_ui(ctx, &mut rule_graph, &mut config.source_code) {
+            debounce.timer.reset();
+            debounce.pending = true;
+        }
+        queue_dropped_textures(ctx, &mut textures);
+        toasts_ui(ctx, &mut toasts);
+
+        match herbarium_ui(ctx, &mut herbarium, can_inject) {
+            Some(HerbariumAction::Load(genotype)) => {
+                genotype.apply_to_editor(&mut config, &mut material_settings, &mut prop_config);
+            }
+            #[cfg(feature = "nursery")]
+            Some(HerbariumAction::Inject(genotype)) => nursery.replace_selected(genotype),
+            _ => {}
+        }
+
+        #[cfg(feature = "nursery")]
+        let can_add_nursery = !nursery.selected.is_empty();
+        #[cfg(not(feature = "nursery"))]
+        let can_add_nursery = false;
+        // Edited on a copy so an untouched panel doesn't relayout the forest
+        let mut scatter_settings = scatter.settings.clone();
+        let garden_action = if garden.enabled {
+            garden_ui(
+                ctx,
+                &mut garden,
+                &mut scatter_settings,
+                scatter.genotype.is_some(),
+                terrain.bypass_change_detection(),
+                can_add_nursery,
+            )
+        } else {
+            None
+        };
+        if scatter_settings != scatter.settings {
+            scatter.settings = scatter_settings;
+        }
+        match garden_action {
+            Some(GardenAction::AddEditor) => {
+                let name = format!("Plant {}", garden.plants.len() + 1);
+                let genotype =
+                    PlantGenotype::from_editor(&config, &material_settings, &prop_config);
+                garden.add(name, genotype);
+            }
+            Some(GardenAction::AddPreset(preset)) => {
+                garden.add(preset.name.to_string(), PlantGenotype::from_preset(preset));
+            }
+            Some(GardenAction::ScatterEditor) => {
+                scatter.genotype = Some(PlantGenotype::from_editor(
+                    &config,
+                    &material_settings,
+                    &prop_config,
+                ));
+            }
+            Some(GardenAction::ClearScatter) => scatter.genotype = None,
+            Some(GardenAction::TerrainChanged) => terrain.set_changed(),
+            #[cfg(feature = "nursery")]
+            Some(GardenAction::AddNurserySelection) => {
+                let mut selected: Vec<usize> = nursery.selected.iter().copied().collect();
+                selected.sort_unstable();
+                for index in selected {
+                    if let Some(genotype) = nursery.get_genotype(index) {
+                        let name = format!("Nursery gen {} #{}", nursery.generation, index + 1);
+                        garden.add(name, genotype);
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(snapshot) = &autosave.recovered {
+            let mut restore = false;
+            let mut discard = false;
+            egui::Window::new("Restore Session?")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    ui.label(format!(
+                        "An autosaved session from {} was found.",
+                        snapshot.saved_at
+                    ));
+                    ui.label(
+                        egui::RichText::new("The previous session may have closed unexpectedly.")
+                            .small()
+                            .color(egui::Color32::GRAY),
+                    );
+                    ui.horizontal(|ui| {
+                        restore = ui.button("Restore").clicked();
+                        discard = ui.button("Discard").clicked();
+                    });
+                });
+            if restore {
+                autosave.restore(&mut config, &mut material_settings, &mut prop_config);
+            } else if discard {
+                autosave.discard();
+            }
+        }
+    }
+}
+
+/// Renders the manifold check controls and its per-material report.
+fn mesh_check_ui(ui: &mut egui::Ui, check: &mut ManifoldCheck) {
+    ui.horizontal(|ui| {
+        if ui
+            .button("Analyze Mesh")
+            .on_hover_text("Find open edges, non-manifold edges, and degenerate triangles")
+            .clicked()
+        {
+            check.requested = true;
+        }
+        ui.checkbox(&mut check.highlight, "Highlight");
+    });
+    ui.horizontal(|ui| {
+        for (kind, label) in [
+            (ProblemKind::OpenEdge, "Open"),
+            (ProblemKind::NonManifoldEdge, "Non-manifold"),
+            (ProblemKind::DegenerateTriangle, "Degenerate"),
+        ] {
+            let [r, g, b, _] = kind.color().to_srgba().to_u8_array();
+            ui.colored_label(egui::Color32::from_rgb(r, g, b), format!("━ {}", label));
+        }
+    });
+
+    let (Some(report), Some(totals)) = (&check.report, check.totals()) else {
+        ui.label(
+            egui::RichText::new("No report for the current mesh")
+                .small()
+                .color(egui::Color32::GRAY),
+        );
+        return;
+    };
+
+    if totals.is_watertight() {
+        ui.colored_label(egui::Color32::GREEN, "Watertight: no defects found");
+    } else {
+        ui.colored_label(
+            egui::Color32::from_rgb(255, 170, 60),
+            "Not watertight (tube ends and leaves are usually open)",
+        );
+    }
+
+    let row = |ui: &mut egui::Ui, name: String, topology: &MeshTopology| {
+        ui.label(name);
+        ui.label(topology.triangles.to_string());
+        ui.label(topology.open_edges.to_string());
+        ui.label(topology.non_manifold_edges.to_string());
+        ui.label(topology.degenerate_triangles.to_string());
+        ui.end_row();
+    };
+    egui::Grid::new("mesh_check_report")
+        .num_columns(5)
+        .striped(true)
+        .show(ui, |ui| {
+            for header in ["Material", "Tris", "Open", "Non-manifold", "Degenerate"] {
+                ui.label(egui::RichText::new(header).strong());
+            }
+            ui.end_row();
+            for (material_id, topology) in report {
+                row(ui, format!("Mat {}", material_id), topology);
+            }
+            row(ui, "Total".to_string(), &totals);
+        });
+}
+
+/// Renders the self-intersection check controls and its summary.
+fn intersection_check_ui(ui: &mut egui::Ui, check: &mut IntersectionCheck) {
+    ui.horizontal(|ui| {
+        if ui
+            .button("Find Intersections")
+            .on_hover_text("Find branch segments that pass through each other")
+            .clicked()
+        {
+            check.requested = true;
+        }
+        ui.checkbox(&mut check.heatmap, "Heatmap")
+            .on_hover_text("Color intersecting segments from yellow (one hit) to red (most hits)");
+    });
+
+    let Some(report) = &check.report else {
+        ui.label(
+            egui::RichText::new("No report for the current plant")
+                .small()
+                .color(egui::Color32::GRAY),
+        );
+        return;
+    };
+
+    if report.pairs == 0 {
+        ui.colored_label(egui::Color32::GREEN, "No self-intersections");
+        return;
+    }
+    ui.colored_label(
+        egui::Color32::from_rgb(255, 170, 60),
+        format!(
+            "{} intersecting pairs; {} of {} segments affected",
+            report.pairs,
+            report.intersecting_segments(),
+            report.capsules.len()
+        ),
+    );
+    ui.label(
+        egui::RichText::new(format!("Deepest overlap: {:.3}", report.max_depth))
+            .small()
+            .color(egui::Color32::GRAY),
+    );
+}
+
+/// Renders the physics preview toggle and the joint and gravity settings.
+/// Settings apply on the next restart.
+#[cfg(feature = "physics")]
+fn physics_preview_ui(ui: &mut egui::Ui, preview: &mut PhysicsPreview) {
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut preview.enabled, "Simulate")
+            .on_hover_text("Replace the plant with jointed rigid bodies that settle under gravity");
+        if ui
+            .add_enabled(preview.enabled, egui::Button::new("⟲ Restart"))
+            .on_hover_text("Rebuild the bodies from the current plant and settings")
+            .clicked()
+        {
+            preview.restart_requested = true;
+        }
+    });
+    egui::Grid::new("physics_preview_grid")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label("Stiffness");
+            ui.add(
+                egui::DragValue::new(&mut preview.stiffness)
+                    .speed(100.0)
+                    .range(0.0..=1.0e7),
+            )
+            .on_hover_text("How strongly joints pull back to their grown angle");
+            ui.end_row();
+            ui.label("Damping");
+            ui.add(
+                egui::DragValue::new(&mut preview.damping)
+                    .speed(10.0)
+                    .range(0.0..=1.0e6),
+            );
+            ui.end_row();
+            ui.label("Gravity");
+            ui.add(
+                egui::Slider::new(&mut preview.gravity_scale, 0.0..=100.0)
+                    .suffix("×")
+                    .logarithmic(true),
+            );
+            ui.end_row();
+        });
+
+    match &preview.status {
+        Some(Ok(segments)) if preview.enabled => {
+            ui.label(
+                egui::RichText::new(format!("{} segments simulated", segments))
+                    .small()
+                    .color(egui::Color32::GRAY),
+            );
+        }
+        Some(Err(e)) => {
+            ui.colored_label(egui::Color32::from_rgb(255, 170, 60), e);
+        }
+        _ => {}
+    }
+}
+
+/// Renders the gradient of each material slot. Returns true if one changed,
+/// which needs a remesh since gradients are baked into the vertex colors.
+fn gradients_ui(
+    ui: &mut egui::Ui,
+    gradients: &mut MaterialGradients,
+    material_settings: &MaterialSettingsMap,
+) -> bool {
+    let mut changed = false;
+    let mut material_ids: Vec<u8> = material_settings.settings.keys().copied().collect();
+    material_ids.sort_unstable();
+    egui::Grid::new("material_gradients")
+        .num_columns(4)
+        .show(ui, |ui| {
+            for material_id in material_ids {
+                let mut enabled = gradients.gradients.contains_key(&material_id);
+                if ui
+                    .checkbox(&mut enabled, format!(",({})", material_id))
+                    .on_hover_text("Tint this slot from the root toward the tips")
+                    .changed()
                 {
-                    let new_materials = genotype.get_material_settings();
-                    config.source_code = genotype.source_code;
-                    config.finalization_code = genotype.finalization_code;
-                    config.iterations = genotype.iterations;
-                    config.default_angle = genotype.angle;
-                    config.step_size = genotype.step;
-                    config.default_width = genotype.width;
-                    config.elasticity = genotype.elasticity;
-                    config.tropism = genotype.tropism.map(|t| Vec3::new(t[0], t[1], t[2]));
-                    config.seed = genotype.seed;
-                    config.recompile_requested = true;
-                    material_settings.settings.clear();
-                    for (slot, mat) in new_materials {
-                        material_settings.settings.insert(slot, mat);
+                    if enabled {
+                        gradients
+                            .gradients
+                            .insert(material_id, MaterialGradient::default());
+                    } else {
+                        gradients.gradients.remove(&material_id);
                     }
-                    prop_config.prop_meshes = genotype.prop_mappings;
+                    changed = true;
+                }
+                match gradients.gradients.get_mut(&material_id) {
+                    Some(gradient) => {
+                        egui::ComboBox::from_id_salt(("gradient_mode", material_id))
+                            .selected_text(gradient.mode.name())
+                            .show_ui(ui, |ui| {
+                                for mode in GradientMode::ALL {
+                                    changed |= ui
+                                        .selectable_value(&mut gradient.mode, *mode, mode.name())
+                                        .changed();
+                                }
+                            });
+                        ui.label("Tip tint:");
+                        changed |= ui.color_edit_button_rgb(&mut gradient.tip_tint).changed();
+                    }
+                    None => {
+                        ui.label("");
+                        ui.label("");
+                        ui.label("");
+                    }
+                }
+                ui.end_row();
+            }
+        });
+    changed
+}
+
+/// Renders the cross-section picker of each material slot. Returns true if a
+/// profile changed, which needs a remesh.
+fn cross_sections_ui(
+    ui: &mut egui::Ui,
+    cross_sections: &mut CrossSections,
+    material_settings: &MaterialSettingsMap,
+) -> bool {
+    let mut changed = false;
+    let mut material_ids: Vec<u8> = material_settings.settings.keys().copied().collect();
+    material_ids.sort_unstable();
+    for material_id in material_ids {
+        let before = cross_sections
+            .profiles
+            .get(&material_id)
+            .cloned()
+            .unwrap_or_default();
+        let mut profile = before.clone();
+        ui.horizontal(|ui| {
+            ui.label(format!(",({})", material_id));
+            egui::ComboBox::from_id_salt(("cross_section", material_id))
+                .selected_text(profile.name())
+                .show_ui(ui, |ui| {
+                    for preset in CrossSection::presets() {
+                        let selected = std::mem::discriminant(&profile)
+                            == std::mem::discriminant(&preset)
+                            && (!matches!(preset, CrossSection::NGon(_)) || profile == preset);
+                        if ui.selectable_label(selected, preset.name()).clicked() {
+                            profile = preset;
+                        }
+                    }
+                });
+            match &mut profile {
+                CrossSection::NGon(sides) => {
+                    ui.add(egui::DragValue::new(sides).range(3..=32).suffix(" sides"));
+                }
+                CrossSection::Star { points, inner } => {
+                    ui.add(egui::DragValue::new(points).range(2..=16).suffix(" points"));
+                    ui.add(
+                        egui::DragValue::new(inner)
+                            .range(0.05..=1.0)
+                            .speed(0.01)
+                            .prefix("inner "),
+                    );
+                }
+                _ => {}
+            }
+        });
+        if let CrossSection::Custom(points) = &mut profile {
+            let input = cross_sections
+                .custom_input
+                .entry(material_id)
+                .or_insert_with(|| format_profile(points));
+            let response = ui
+                .add(egui::TextEdit::singleline(input).hint_text("x,y; x,y; x,y"))
+                .on_hover_text("Counter-clockwise polygon in units of the branch radius");
+            match parse_profile(input) {
+                Ok(parsed) if response.changed() => *points = parsed,
+                Ok(_) => {}
+                Err(e) => {
+                    ui.colored_label(egui::Color32::from_rgb(255, 100, 100), e);
+                }
+            }
+        } else {
+            cross_sections.custom_input.remove(&material_id);
+        }
+
+        if profile != before {
+            if profile == CrossSection::Circle {
+                cross_sections.profiles.remove(&material_id);
+            } else {
+                cross_sections.profiles.insert(material_id, profile);
+            }
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Renders mesh totals, per-material counts, the bounding box of the current
+/// plant, and the estimated memory of the derived system.
+fn statistics_ui(ui: &mut egui::Ui, render_state: &TurtleRenderState, memory: &MemoryEstimate) {
+    egui::Grid::new("mesh_stats_totals")
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("Vertices:");
+            ui.label(render_state.total_vertices.to_string());
+            ui.end_row();
+            ui.label("Triangles:");
+            ui.label(render_state.total_triangles.to_string());
+            ui.end_row();
+            ui.label("Strands:");
+            ui.label(render_state.strand_count.to_string());
+            ui.end_row();
+            ui.label("Props:");
+            ui.label(render_state.prop_count.to_string());
+            ui.end_row();
+        });
+
+    if !render_state.material_stats.is_empty() {
+        ui.separator();
+        egui::Grid::new("mesh_stats_materials")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Material");
+                ui.strong("Verts");
+                ui.strong("Tris");
+                ui.end_row();
+                for (material_id, stats) in &render_state.material_stats {
+                    ui.label(format!(",({})", material_id));
+                    ui.label(stats.vertices.to_string());
+                    ui.label(stats.triangles.to_string());
+                    ui.end_row();
                 }
             });
     }
+
+    if let Some((min, max)) = render_state.bounds {
+        ui.separator();
+        let size = max - min;
+        ui.label(format!(
+            "Bounds: {:.1} x {:.1} x {:.1}",
+            size.x, size.y, size.z
+        ));
+        ui.label(
+            egui::RichText::new(format!(
+                "min ({:.1}, {:.1}, {:.1})  max ({:.1}, {:.1}, {:.1})",
+                min.x, min.y, min.z, max.x, max.y, max.z
+            ))
+            .small()
+            .color(egui::Color32::GRAY),
+        );
+    }
+
+    if memory.modules > 0 {
+        ui.separator();
+        egui::Grid::new("memory_stats")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Modules:");
+                ui.label(format!(
+                    "{} ({})",
+                    format_count(memory.modules as f64),
+                    format_bytes(memory.module_bytes)
+                ));
+                ui.end_row();
+                ui.label("Parameters:");
+                ui.label(format!(
+                    "{} ({})",
+                    format_count(memory.params as f64),
+                    format_bytes(memory.param_bytes)
+                ));
+                ui.end_row();
+                ui.label("Interner:");
+                ui.label(format!(
+                    "{} symbols ({})",
+                    memory.interner_symbols,
+                    format_bytes(memory.interner_bytes)
+                ));
+                ui.end_row();
+                ui.label("Memory:");
+                ui.label(format!("≈ {}", format_bytes(memory.total_bytes())));
+                ui.end_row();
+            });
+    }
+}
+
+/// Renders symbol counts of the derived string and how often each rule fired.
+fn analysis_ui(ui: &mut egui::Ui, usage: &ModuleUsage, module_count: usize) {
+    if usage.symbols.is_empty() {
+        ui.label("Nothing derived yet.");
+        return;
+    }
+    let prefix = if usage.exact { "" } else { "≈ " };
+    ui.label(format!("Modules: {}", module_count));
+    if !usage.exact {
+        ui.label(
+            egui::RichText::new(
+                "Stochastic, conditional, or context rules: counts are expected values",
+            )
+            .small()
+            .color(egui::Color32::GRAY),
+        );
+    }
+
+    ui.separator();
+    egui::ScrollArea::vertical()
+        .max_height(200.0)
+        .id_salt("analysis_symbols_scroll")
+        .show(ui, |ui| {
+            egui::Grid::new("analysis_symbols")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong("Symbol");
+                    ui.strong("Count");
+                    ui.end_row();
+                    for (symbol, count) in &usage.symbols {
+                        ui.monospace(symbol);
+                        ui.label(format!("{}{}", prefix, format_count(*count)));
+                        ui.end_row();
+                    }
+                });
+        });
+
+    if !usage.rules.is_empty() {
+        ui.separator();
+        egui::Grid::new("analysis_rules")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Rule");
+                ui.strong("Fired");
+                ui.end_row();
+                for usage_rule in &usage.rules {
+                    let rule = &usage_rule.rule;
+                    let name = match &rule.label {
+                        Some(label) => format!("{}: {}", label, rule.predecessor),
+                        None => rule.predecessor.clone(),
+                    };
+                    let name = if usage_rule.finalization {
+                        format!("{} (final)", name)
+                    } else {
+                        name
+                    };
+                    ui.monospace(name)
+                        .on_hover_text(format!("{} -> {}", rule.predecessor, rule.successor));
+                    ui.label(format!("{}{}", prefix, format_count(usage_rule.firings)));
+                    ui.end_row();
+                }
+            });
+    }
+}
+
+/// Lists shortcut bindings; clicking a binding waits for the next key press
+/// (captured by `logic::shortcuts::handle_shortcuts`).
+fn keybindings_ui(ui: &mut egui::Ui, keybindings: &mut Keybindings) {
+    egui::Grid::new("keybindings_grid")
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            for action in ShortcutAction::ALL {
+                ui.label(action.name());
+                let label = if keybindings.rebinding == Some(*action) {
+                    "Press a key…".to_string()
+                } else {
+                    keybindings
+                        .chord(*action)
+                        .map(|chord| chord.label())
+                        .unwrap_or_else(|| "Unbound".to_string())
+                };
+                if ui
+                    .button(label)
+                    .on_hover_text("Click, then press the new key (Esc cancels)")
+                    .clicked()
+                {
+                    keybindings.rebinding = Some(*action);
+                }
+                ui.end_row();
+            }
+        });
+
+    if ui.button("Reset to Defaults").clicked() {
+        *keybindings = Keybindings::defaults();
+        if let Err(e) = keybindings.save() {
+            warn!("Failed to save keybindings: {}", e);
+        }
+    }
+}
+
+/// Button opening the preset browser; the preset it picks goes into the
+/// editor or into the nursery selection.
+pub fn preset_picker_ui(ui: &mut egui::Ui, browser: &mut PresetBrowser) {
+    ui.horizontal(|ui| {
+        ui.label("Load Preset:");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::LEFT), |ui| {
+            ui.toggle_value(&mut browser.open, "📚 Browse…")
+                .on_hover_text("Browse presets and starred plants by category");
+        });
+    });
+}
+
+/// Loads a preset's grammar, parameters, materials, and prop mapping into the
+/// editor and requests a recompile.
+pub fn apply_preset_to_editor(
+    preset: &LSystemPreset,
+    config: &mut LSystemConfig,
+    material_settings: &mut MaterialSettingsMap,
+    prop_config: &mut PropConfig,
+) {
+    let (growth, finalization) = split_source_code(preset.code);
+    config.source_code = growth;
+    config.finalization_code = finalization;
+    config.iterations = preset.iterations;
+    config.default_angle = preset.angle;
+    config.step_size = preset.step;
+    config.default_width = preset.width;
+    config.elasticity = preset.elasticity;
+    config.tropism = preset.tropism;
+
+    // Apply preset material settings
+    material_settings.settings.clear();
+    for (slot_id, mat) in preset.materials.iter() {
+        material_settings.settings.insert(
+            *slot_id,
+            bevy_symbios::materials::MaterialSettings {
+                base_color: mat.base_color,
+                roughness: mat.roughness,
+                metallic: mat.metallic,
+                emission_color: mat.emission_color,
+                emission_strength: mat.emission_strength,
+                uv_scale: mat.uv_scale,
+                texture: mat.texture_type,
+            },
+        );
+    }
+
+    // Apply preset prop configuration
+    prop_config.prop_meshes = preset.prop_meshes.iter().copied().collect();
+
+    config.recompile_requested = true;
+}
+
+/// Iteration stepper and growth slider. Returns `true` when either changed (a recompile is
+/// requested immediately, without debouncing).
+pub fn iterations_ui(ui: &mut egui::Ui, config: &mut LSystemConfig) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label("Iterations:");
+        if ui.button("➖").clicked() && config.iterations > 0 {
+            config.iterations -= 1;
+            changed = true;
+        }
+        ui.label(
+            egui::RichText::new(format!("{}", config.iterations))
+                .strong()
+                .size(16.0),
+        );
+        if ui.button("➕").clicked() {
+            config.iterations += 1;
+            changed = true;
+        }
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut config.growth, 0.0..=1.0)
+                    .text("growth")
+                    .fixed_decimals(2),
+            )
+            .on_hover_text(
+                "Grow part of the way to the next iteration: new segments lengthen and new branches sprout smoothly",
+            )
+            .changed();
+    });
+    if changed {
+        config.recompile_requested = true;
+    }
+    changed
+}
+
+/// Seed field with step and randomize buttons and the lock toggle. Returns
+/// `true` when the seed changed (a recompile is requested immediately).
+pub fn seed_ui(ui: &mut egui::Ui, config: &mut LSystemConfig) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label("Random Seed:");
+        ui.add_enabled_ui(!config.seed_locked, |ui| {
+            if ui.button("◀").on_hover_text("Previous seed").clicked() {
+                config.seed = config.seed.wrapping_sub(1);
+                changed = true;
+            }
+            changed |= ui
+                .add(egui::DragValue::new(&mut config.seed).speed(1.0))
+                .changed();
+            if ui.button("▶").on_hover_text("Next seed").clicked() {
+                config.seed = config.seed.wrapping_add(1);
+                changed = true;
+            }
+            if ui.button("🎲").on_hover_text("Random seed").clicked() {
+                config.seed = u64::from(rand::random::<u32>());
+                changed = true;
+            }
+        });
+        let icon = if config.seed_locked { "🔒" } else { "🔓" };
+        ui.toggle_value(&mut config.seed_locked, icon)
+            .on_hover_text("Lock the seed: loading a plant or history entry keeps it");
+    });
+    if changed {
+        config.recompile_requested = true;
+    }
+    changed
+}
+
+/// Clock of timed grammars: time, play/pause, and reset. Returns `true` when
+/// the time was set by hand (a recompile is requested immediately).
+pub fn clock_ui(
+    ui: &mut egui::Ui,
+    config: &mut LSystemConfig,
+    playback: &mut ClockPlayback,
+) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label("Clock:");
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut config.clock)
+                    .speed(0.05)
+                    .range(0.0..=f32::MAX)
+                    .fixed_decimals(2),
+            )
+            .on_hover_text("Time of the timed grammar; replaces the iteration count")
+            .changed();
+        let play = if playback.playing { "⏸" } else { "▶" };
+        if ui
+            .button(play)
+            .on_hover_text("Play the growth clock")
+            .clicked()
+        {
+            playback.playing = !playback.playing;
+        }
+        if ui.button("⏮").on_hover_text("Reset the clock").clicked() {
+            playback.playing = false;
+            config.clock = 0.0;
+            changed = true;
+        }
+        ui.add(
+            egui::DragValue::new(&mut playback.speed)
+                .speed(0.05)
+                .range(0.05..=10.0)
+                .suffix("×"),
+        )
+        .on_hover_text("Clock units per second of playback");
+    });
+    if changed {
+        config.recompile_requested = true;
+    }
+    changed
 }
@@ -1,4 +1,7 @@
+use crate::logic::describe::parse_rule;
+use crate::logic::rule_line::RuleLine;
 use bevy_egui::egui;
+use std::collections::HashMap;
 
 /// Compute a slider range centered on the current value.
 ///
@@ -37,6 +40,196 @@ pub fn update_define_in_source(source: &str, key: &str, new_value: f32) -> Strin
     new_lines.join("\n")
 }
 
+/// Slider settings of a `#define` constant, declared in the comment lines
+/// directly above it, e.g. `// @range 0..90 @int @group Branching`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConstantMeta {
+    /// Slider bounds from `@range LO..HI`.
+    pub range: Option<(f32, f32)>,
+    /// `@int` snaps the slider to whole numbers.
+    pub integer: bool,
+    /// `@group NAME` lists the constant under a heading.
+    pub group: Option<String>,
+}
+
+impl ConstantMeta {
+    /// Reads the `@` tags of one comment line into `self`.
+    fn parse_tags(&mut self, comment: &str) {
+        let mut tokens = comment.split_whitespace().peekable();
+        while let Some(token) = tokens.next() {
+            match token {
+                "@range" => {
+                    self.range = tokens.next().and_then(|range| {
+                        let (lo, hi) = range.split_once("..")?;
+                        let hi = hi.strip_prefix('=').unwrap_or(hi);
+                        let (lo, hi) = (lo.parse::<f32>().ok()?, hi.parse::<f32>().ok()?);
+                        (lo < hi).then_some((lo, hi))
+                    });
+                }
+                "@int" => self.integer = true,
+                "@group" => {
+                    let mut words = Vec::new();
+                    while let Some(word) = tokens.next_if(|word| !word.starts_with('@')) {
+                        words.push(word);
+                    }
+                    self.group = (!words.is_empty()).then(|| words.join(" "));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The slider settings of each annotated `#define` constant in `source`.
+pub fn constant_metadata(source: &str) -> HashMap<String, ConstantMeta> {
+    let mut metadata = HashMap::new();
+    let mut pending: Option<ConstantMeta> = None;
+    for line in source.lines() {
+        let trimmed = line.trim();
+        if let Some(comment) = trimmed.strip_prefix("//") {
+            if comment.contains('@') {
+                pending.get_or_insert_default().parse_tags(comment);
+            }
+            continue;
+        }
+        let mut parts = trimmed.split_whitespace();
+        if parts.next() == Some("#define")
+            && let (Some(name), Some(meta)) = (parts.next(), pending.take())
+        {
+            metadata.insert(name.to_string(), meta);
+        }
+        pending = None;
+    }
+    metadata
+}
+
+/// Slider bounds for a constant: the declared range, or one guessed from
+/// `value` by [`smart_slider_range`].
+pub fn constant_slider_range(meta: Option<&ConstantMeta>, value: f32) -> (f32, f32) {
+    meta.and_then(|meta| meta.range)
+        .unwrap_or_else(|| smart_slider_range(value))
+}
+
+/// A stochastic rule's probability (`A : 0.7 -> ...`) found in the source.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleProbability {
+    /// Line of the rule in the source.
+    pub line: usize,
+    /// The rule's label and predecessor, e.g. `p0: A`.
+    pub name: String,
+    pub probability: f32,
+}
+
+/// Byte range of the probability on a rule line, with its value and the
+/// rule's name; `None` for lines that are not stochastic rules.
+fn probability_span(line: &str) -> Option<(std::ops::Range<usize>, f32, String)> {
+    let rule = RuleLine::parse(line)?;
+    let (probability, span) = rule.probability?;
+    let name = match rule.label {
+        Some(label) => format!("{}: {}", label, rule.predecessor),
+        None => rule.predecessor.to_string(),
+    };
+    Some((span, probability as f32, name))
+}
+
+/// The stochastic rules of `source`, in order.
+pub fn rule_probabilities(source: &str) -> Vec<RuleProbability> {
+    source
+        .lines()
+        .enumerate()
+        .filter_map(|(line, text)| {
+            let (_, probability, name) = probability_span(text)?;
+            Some(RuleProbability {
+                line,
+                name,
+                probability,
+            })
+        })
+        .collect()
+}
+
+/// Helper to update the probability of the stochastic rule on `line`.
+pub fn update_probability_in_source(source: &str, line: usize, new_value: f32) -> String {
+    let value = (new_value.clamp(0.0, 1.0) * 1000.0).round() / 1000.0;
+    source
+        .lines()
+        .enumerate()
+        .map(|(index, text)| match probability_span(text) {
+            Some((range, _, _)) if index == line => {
+                format!("{}{}{}", &text[..range.start], value, &text[range.end..])
+            }
+            _ => text.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Prefix of a rule switched off in the rule table; the rule stays in the
+/// source as a comment and switching it on removes the prefix again.
+pub const DISABLED_RULE_PREFIX: &str = "// [off] ";
+
+/// A production in the rule table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleRow {
+    /// Line of the rule in the source.
+    pub line: usize,
+    pub enabled: bool,
+    pub label: Option<String>,
+    pub predecessor: String,
+    /// Condition, or empty for unconditional and stochastic rules.
+    pub condition: String,
+    /// Probability of stochastic rules, `None` for conditional ones.
+    pub probability: Option<f64>,
+    pub successor: String,
+}
+
+/// The productions of `source` in order, including ones switched off with
+/// [`DISABLED_RULE_PREFIX`].
+pub fn rule_rows(source: &str) -> Vec<RuleRow> {
+    source
+        .lines()
+        .enumerate()
+        .filter_map(|(line, text)| {
+            let trimmed = text.trim_start();
+            let (enabled, rule) = match trimmed.strip_prefix(DISABLED_RULE_PREFIX) {
+                Some(rest) => (false, parse_rule(rest)?),
+                None => (true, parse_rule(trimmed)?),
+            };
+            Some(RuleRow {
+                line,
+                enabled,
+                label: rule.label,
+                predecessor: rule.predecessor,
+                condition: rule.condition.unwrap_or_default(),
+                probability: rule.probability,
+                successor: rule.successor,
+            })
+        })
+        .collect()
+}
+
+/// Switches the rule on `line` on or off by commenting it out, keeping its
+/// indentation.
+pub fn set_rule_enabled(source: &str, line: usize, enabled: bool) -> String {
+    source
+        .lines()
+        .enumerate()
+        .map(|(index, text)| {
+            if index != line {
+                return text.to_string();
+            }
+            let indent = &text[..text.len() - text.trim_start().len()];
+            let body = text.trim_start();
+            match (enabled, body.strip_prefix(DISABLED_RULE_PREFIX)) {
+                (true, Some(rule)) => format!("{}{}", indent, rule),
+                (false, None) => format!("{}{}{}", indent, DISABLED_RULE_PREFIX, body),
+                _ => text.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 // --- Syntax Highlighting ---
 
 const HL_COMMENT: egui::Color32 = egui::Color32::from_rgb(0x6A, 0x99, 0x55);
@@ -238,3 +431,81 @@ pub fn push_hl(
         format: egui::TextFormat::simple(font_id.clone(), color),
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constant_metadata() {
+        let source = "// Branching\n// @range 0..90 @int\n// @group Branch shape\n#define angle 25\n\
+                      #define plain 3\n// @range 1..=0\n#define bad 1\n// @range 0.5..2\nomega: A\n#define late 1";
+        let metadata = constant_metadata(source);
+        assert_eq!(
+            metadata["angle"],
+            ConstantMeta {
+                range: Some((0.0, 90.0)),
+                integer: true,
+                group: Some("Branch shape".into()),
+            }
+        );
+        assert!(!metadata.contains_key("plain"));
+        assert_eq!(metadata["bad"].range, None, "empty ranges are ignored");
+        assert!(
+            !metadata.contains_key("late"),
+            "tags only apply to the define right below them"
+        );
+        assert_eq!(
+            constant_slider_range(metadata.get("angle"), 25.0),
+            (0.0, 90.0)
+        );
+        assert_eq!(constant_slider_range(None, 3.0), (0.0, 6.0));
+    }
+
+    #[test]
+    fn test_rule_probabilities() {
+        let source = "#define p 0.5\nomega: A\np0: A : 0.7 -> F A\nA:0.3->F\nB : t > 1 -> F";
+        let rules = rule_probabilities(source);
+        assert_eq!(
+            rules,
+            vec![
+                RuleProbability {
+                    line: 2,
+                    name: "p0: A".into(),
+                    probability: 0.7,
+                },
+                RuleProbability {
+                    line: 3,
+                    name: "A".into(),
+                    probability: 0.3,
+                },
+            ]
+        );
+        assert_eq!(
+            update_probability_in_source(source, 2, 0.65),
+            "#define p 0.5\nomega: A\np0: A : 0.65 -> F A\nA:0.3->F\nB : t > 1 -> F"
+        );
+        assert_eq!(
+            update_probability_in_source(source, 3, 0.12345),
+            "#define p 0.5\nomega: A\np0: A : 0.7 -> F A\nA:0.123->F\nB : t > 1 -> F"
+        );
+    }
+
+    #[test]
+    fn test_rule_rows_toggle() {
+        let source = "omega: A\np1: A : 0.5 -> F A\n  p2: A : 0.5 -> A\nB : t > 1 -> F\n// A -> B";
+        let rows = rule_rows(source);
+        assert_eq!(rows.len(), 3, "plain comments are not rules");
+        assert_eq!(rows[0].label.as_deref(), Some("p1"));
+        assert_eq!(rows[0].probability, Some(0.5));
+        assert_eq!(rows[2].condition, "t > 1");
+
+        let disabled = set_rule_enabled(source, 2, false);
+        assert_eq!(disabled.lines().nth(2), Some("  // [off] p2: A : 0.5 -> A"));
+        let rows = rule_rows(&disabled);
+        assert!(!rows[1].enabled);
+        assert_eq!(rows[1].successor, "A");
+        assert_eq!(set_rule_enabled(&disabled, 2, true), source);
+        assert_eq!(set_rule_enabled(source, 1, true), source);
+    }
+}
@@ -0,0 +1,387 @@
+//! Garden window: the list of placed plants and the selected plant's placement.
+
+use crate::core::garden::Garden;
+use crate::core::presets::{LSystemPreset, PRESETS};
+use crate::core::scatter::{MAX_SCATTER_PLANTS, MAX_SCATTER_VARIATIONS, ScatterSettings};
+use crate::core::terrain::{Terrain, TerrainSource};
+use bevy_egui::egui;
+
+/// Plants the user asked to add to the garden.
+pub enum GardenAction {
+    /// Add the plant in the editor.
+    AddEditor,
+    /// Add a built-in preset.
+    AddPreset(&'static LSystemPreset),
+    /// Add the selected nursery individuals.
+    AddNurserySelection,
+    /// Scatter seed variations of the editor plant as a forest.
+    ScatterEditor,
+    /// Remove the forest.
+    ClearScatter,
+    /// The terrain settings were edited.
+    TerrainChanged,
+}
+
+/// Renders the garden window while garden mode is on. Closing the window
+/// turns garden mode off.
+///
+/// `can_add_nursery` enables adding the selected nursery individuals.
+/// `scatter` is the forest layout, edited in place; `scattered` tells whether
+/// a forest is shown. Edits to `terrain` are reported as
+/// [`GardenAction::TerrainChanged`].
+pub fn garden_ui(
+    ctx: &egui::Context,
+    garden: &mut Garden,
+    scatter: &mut ScatterSettings,
+    scattered: bool,
+    terrain: &mut Terrain,
+    can_add_nursery: bool,
+) -> Option<GardenAction> {
+    if !garden.enabled {
+        return None;
+    }
+    let mut action = None;
+    let mut open = true;
+    let mut remove = None;
+    let mut duplicate = None;
+
+    egui::Window::new("🌳 Garden")
+        .open(&mut open)
+        .default_width(320.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui
+                    .button("➕ Editor Plant")
+                    .on_hover_text("Add the plant in the editor")
+                    .clicked()
+                {
+                    action = Some(GardenAction::AddEditor);
+                }
+                egui::ComboBox::from_id_salt("garden_add_preset")
+                    .selected_text("➕ Preset")
+                    .show_ui(ui, |ui| {
+                        for preset in PRESETS {
+                            if ui.selectable_label(false, preset.name).clicked() {
+                                action = Some(GardenAction::AddPreset(preset));
+                            }
+                        }
+                    });
+                if ui
+                    .add_enabled(can_add_nursery, egui::Button::new("➕ Nursery"))
+                    .on_hover_text("Add the selected nursery individuals")
+                    .clicked()
+                {
+                    action = Some(GardenAction::AddNurserySelection);
+                }
+            });
+
+            egui::CollapsingHeader::new("Terrain")
+                .default_open(false)
+                .show(ui, |ui| {
+                    if terrain_ui(ui, terrain) {
+                        action = Some(GardenAction::TerrainChanged);
+                    }
+                });
+
+            egui::CollapsingHeader::new("Scatter Forest")
+                .default_open(false)
+                .show(ui, |ui| {
+                    if let Some(scatter_action) = scatter_ui(ui, scatter, scattered) {
+                        action = Some(scatter_action);
+                    }
+                });
+
+            ui.separator();
+            if garden.plants.is_empty() {
+                ui.label(
+                    egui::RichText::new("Add plants to place them together in one scene")
+                        .small()
+                        .color(egui::Color32::GRAY),
+                );
+                return;
+            }
+
+            egui::ScrollArea::vertical()
+                .id_salt("garden_plants")
+                .max_height(240.0)
+                .show(ui, |ui| {
+                    for plant in &garden.plants {
+                        ui.horizontal(|ui| {
+                            let selected = garden.selected == Some(plant.id);
+                            if ui.selectable_label(selected, &plant.name).clicked() {
+                                garden.selected = (!selected).then_some(plant.id);
+                            }
+                            ui.with_layout(
+                                egui::Layout::right_to_left(egui::Align::Center),
+                                |ui| {
+                                    if ui.small_button("🗑").on_hover_text("Remove").clicked() {
+                                        remove = Some(plant.id);
+                                    }
+                                    if ui.small_button("⧉").on_hover_text("Duplicate").clicked() {
+                                        duplicate = Some(plant.id);
+                                    }
+                                },
+                            );
+                        });
+                    }
+                });
+
+            if let Some(plant) = garden.selected.and_then(|id| garden.get_mut(id)) {
+                ui.separator();
+                ui.label(egui::RichText::new(&plant.name).strong());
+                egui::Grid::new("garden_placement")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("X:");
+                        ui.add(egui::DragValue::new(&mut plant.translation.x).speed(5.0));
+                        ui.end_row();
+                        ui.label("Z:");
+                        ui.add(egui::DragValue::new(&mut plant.translation.z).speed(5.0));
+                        ui.end_row();
+                        ui.label("Rotation:");
+                        ui.add(
+                            egui::DragValue::new(&mut plant.yaw)
+                                .speed(1.0)
+                                .range(-180.0..=180.0)
+                                .suffix("°"),
+                        );
+                        ui.end_row();
+                        ui.label("Scale:");
+                        ui.add(
+                            egui::DragValue::new(&mut plant.scale)
+                                .speed(0.01)
+                                .range(0.05..=20.0),
+                        );
+                        ui.end_row();
+                    });
+            }
+
+            #[cfg(feature = "export")]
+            {
+                ui.separator();
+                if ui
+                    .button("💾 Export Garden")
+                    .on_hover_text("Export every plant at its placement as one GLB scene")
+                    .clicked()
+                {
+                    garden.export_requested = true;
+                }
+            }
+        });
+
+    if let Some(id) = remove {
+        garden.remove(id);
+    }
+    if let Some(id) = duplicate {
+        garden.duplicate(id);
+    }
+    if !open {
+        garden.enabled = false;
+    }
+    action
+}
+
+/// Layout controls of the scattered forest.
+fn scatter_ui(
+    ui: &mut egui::Ui,
+    settings: &mut ScatterSettings,
+    scattered: bool,
+) -> Option<GardenAction> {
+    let mut action = None;
+    egui::Grid::new("scatter_settings")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label("Center X:");
+            ui.add(egui::DragValue::new(&mut settings.center.x).speed(10.0));
+            ui.end_row();
+            ui.label("Center Z:");
+            ui.add(egui::DragValue::new(&mut settings.center.z).speed(10.0));
+            ui.end_row();
+            ui.label("Region Size:");
+            ui.add(
+                egui::DragValue::new(&mut settings.region_size)
+                    .speed(10.0)
+                    .range(100.0..=50_000.0),
+            );
+            ui.end_row();
+            ui.label("Density:")
+                .on_hover_text("Plants per 1000 × 1000 square units");
+            ui.add(
+                egui::DragValue::new(&mut settings.density)
+                    .speed(0.1)
+                    .range(0.0..=1000.0),
+            );
+            ui.end_row();
+            ui.label("Jitter:");
+            ui.add(egui::Slider::new(&mut settings.jitter, 0.0..=1.0));
+            ui.end_row();
+            ui.label("Scale Variance:");
+            ui.add(egui::Slider::new(&mut settings.scale_variance, 0.0..=0.9));
+            ui.end_row();
+            ui.label("Variations:")
+                .on_hover_text("Distinct seeds derived and instanced across the forest");
+            ui.add(egui::Slider::new(
+                &mut settings.variations,
+                1..=MAX_SCATTER_VARIATIONS,
+            ));
+            ui.end_row();
+            ui.label("Layout Seed:");
+            ui.horizontal(|ui| {
+                ui.add(egui::DragValue::new(&mut settings.layout_seed));
+                if ui.small_button("🎲").on_hover_text("New layout").clicked() {
+                    settings.layout_seed = settings.layout_seed.wrapping_add(1);
+                }
+            });
+            ui.end_row();
+        });
+
+    let count = settings.plant_count();
+    let capped = if count == MAX_SCATTER_PLANTS {
+        " (maximum)"
+    } else {
+        ""
+    };
+    ui.label(
+        egui::RichText::new(format!("{} plants{}", count, capped))
+            .small()
+            .color(egui::Color32::GRAY),
+    );
+    ui.horizontal(|ui| {
+        if ui
+            .button("🌲 Scatter Editor Plant")
+            .on_hover_text("Fill the region with seed variations of the editor plant")
+            .clicked()
+        {
+            action = Some(GardenAction::ScatterEditor);
+        }
+        if ui
+            .add_enabled(scattered, egui::Button::new("Clear"))
+            .clicked()
+        {
+            action = Some(GardenAction::ClearScatter);
+        }
+    });
+    action
+}
+
+/// Terrain controls. Returns whether anything was edited.
+fn terrain_ui(ui: &mut egui::Ui, terrain: &mut Terrain) -> bool {
+    let mut changed = ui
+        .checkbox(&mut terrain.enabled, "Show Terrain")
+        .on_hover_text("Stand garden and forest plants on a heightmap")
+        .changed();
+    ui.add_enabled_ui(terrain.enabled, |ui| {
+        egui::Grid::new("terrain_settings")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Size:");
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut terrain.size)
+                            .speed(10.0)
+                            .range(100.0..=100_000.0),
+                    )
+                    .changed();
+                ui.end_row();
+                ui.label("Height:");
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut terrain.height)
+                            .speed(5.0)
+                            .range(0.0..=10_000.0),
+                    )
+                    .changed();
+                ui.end_row();
+            });
+        changed |= ui
+            .checkbox(&mut terrain.align_to_normal, "Align to Slope")
+            .on_hover_text("Tilt plants to the surface normal instead of keeping them upright")
+            .changed();
+
+        ui.separator();
+        let mut noise = match terrain.source {
+            TerrainSource::Noise { seed, features } => (seed, features),
+            TerrainSource::Image(_) => (0, 3.0),
+        };
+        let mut regenerate = false;
+        ui.horizontal(|ui| {
+            ui.label("Noise Seed:");
+            regenerate |= ui.add(egui::DragValue::new(&mut noise.0)).changed();
+            ui.label("Hills:");
+            regenerate |= ui
+                .add(
+                    egui::DragValue::new(&mut noise.1)
+                        .speed(0.1)
+                        .range(0.5..=32.0),
+                )
+                .changed();
+        });
+        let is_image = matches!(terrain.source, TerrainSource::Image(_));
+        let label = if is_image {
+            "Use Noise"
+        } else {
+            "🎲 New Noise"
+        };
+        if ui.button(label).clicked() {
+            if !is_image {
+                noise.0 = noise.0.wrapping_add(1);
+            }
+            regenerate = true;
+        }
+        if regenerate {
+            terrain.source = TerrainSource::Noise {
+                seed: noise.0,
+                features: noise.1,
+            };
+            terrain.regenerate();
+            changed = true;
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut terrain.path_input)
+                    .hint_text("Heightmap PNG or JPEG path")
+                    .desired_width(200.0),
+            );
+            if ui.button("Import").clicked() {
+                let path = std::path::PathBuf::from(terrain.path_input.trim());
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                match std::fs::read(&path) {
+                    Ok(bytes) => {
+                        terrain.import_image(name, &bytes);
+                        changed = true;
+                    }
+                    Err(e) => {
+                        terrain.status =
+                            Some(Err(format!("Failed to read {}: {}", path.display(), e)));
+                    }
+                }
+            }
+        });
+        if let TerrainSource::Image(name) = &terrain.source {
+            ui.label(
+                egui::RichText::new(format!("Heightmap: {}", name))
+                    .small()
+                    .color(egui::Color32::GRAY),
+            );
+        }
+        match &terrain.status {
+            Some(Ok(message)) => {
+                ui.label(
+                    egui::RichText::new(message)
+                        .small()
+                        .color(egui::Color32::GRAY),
+                );
+            }
+            Some(Err(e)) => {
+                ui.colored_label(egui::Color32::RED, e);
+            }
+            None => {}
+        }
+    });
+    changed
+}
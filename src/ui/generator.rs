@@ -0,0 +1,96 @@
+//! "Pattern Generator" window: phyllotaxis and inflorescence grammars from a
+//! few sliders.
+
+use crate::logic::generators::{GeneratedGrammar, GeneratorSettings, Pattern, generate};
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+/// Generator window state.
+#[derive(Resource, Default)]
+pub struct GeneratorDialog {
+    pub open: bool,
+    pub settings: GeneratorSettings,
+}
+
+/// Renders the generator while `dialog.open` is set. Returns the grammar when
+/// the user loads it into the editor.
+pub fn generator_ui(ctx: &egui::Context, dialog: &mut GeneratorDialog) -> Option<GeneratedGrammar> {
+    let mut loaded = None;
+    let mut open = dialog.open;
+    egui::Window::new("🌼 Pattern Generator")
+        .open(&mut open)
+        .default_width(380.0)
+        .show(ctx, |ui| {
+            let settings = &mut dialog.settings;
+            egui::ComboBox::from_label("Pattern")
+                .selected_text(settings.pattern.name())
+                .show_ui(ui, |ui| {
+                    for pattern in Pattern::ALL {
+                        ui.selectable_value(&mut settings.pattern, pattern, pattern.name());
+                    }
+                });
+
+            let count_label = match settings.pattern {
+                Pattern::SpiralHead => "Florets",
+                Pattern::SpiralStem => "Leaves",
+                Pattern::Raceme => "Flowers",
+                Pattern::Cyme => "Branching order",
+            };
+            ui.add(
+                egui::Slider::new(&mut settings.count, settings.pattern.count_range())
+                    .text(count_label),
+            );
+            ui.add(
+                egui::Slider::new(&mut settings.divergence, 0.0..=360.0)
+                    .text("Divergence")
+                    .suffix("°"),
+            )
+            .on_hover_text("Angle between successive organs; 137.5° is the golden angle");
+            if settings.pattern != Pattern::SpiralHead {
+                ui.add(
+                    egui::Slider::new(&mut settings.branch_angle, 0.0..=120.0)
+                        .text("Branch angle")
+                        .suffix("°"),
+                );
+            }
+            ui.add(
+                egui::Slider::new(&mut settings.spacing, 0.1..=20.0)
+                    .text(if settings.pattern == Pattern::SpiralHead {
+                        "Floret spacing"
+                    } else {
+                        "Internode"
+                    })
+                    .logarithmic(true),
+            );
+            ui.add(
+                egui::Slider::new(&mut settings.organ_size, 0.1..=20.0)
+                    .text("Organ size")
+                    .logarithmic(true),
+            );
+
+            let generated = generate(settings);
+            ui.separator();
+            egui::ScrollArea::vertical()
+                .id_salt("generator_preview")
+                .max_height(180.0)
+                .show(ui, |ui| {
+                    ui.add(
+                        egui::TextEdit::multiline(&mut generated.source.as_str())
+                            .code_editor()
+                            .desired_width(f32::INFINITY),
+                    );
+                });
+            ui.horizontal(|ui| {
+                ui.label(format!("{} iterations", generated.iterations));
+                if ui
+                    .button("Load into editor")
+                    .on_hover_text("Replace the grammar with this pattern as a starting point")
+                    .clicked()
+                {
+                    loaded = Some(generated);
+                }
+            });
+        });
+    dialog.open = open;
+    loaded
+}
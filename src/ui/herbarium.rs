@@ -0,0 +1,160 @@
+//! Gallery window for the herbarium of starred plants.
+
+use crate::core::genotype::PlantGenotype;
+use crate::core::herbarium::{Herbarium, THUMBNAIL_SIZE, decode_thumbnail};
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+/// Width of one gallery card.
+const CARD_WIDTH: f32 = THUMBNAIL_SIZE as f32 + 16.0;
+
+/// What the user asked to do with a starred plant.
+pub enum HerbariumAction {
+    /// Load the plant into the editor.
+    Load(PlantGenotype),
+    /// Replace the selected nursery individuals with the plant.
+    Inject(PlantGenotype),
+}
+
+/// Stars a plant and saves the collection, logging failures.
+pub fn star_plant(herbarium: &mut Herbarium, name: String, genotype: PlantGenotype) {
+    let result = herbarium
+        .star(name, genotype)
+        .and_then(|_| herbarium.save());
+    if let Err(e) = result {
+        warn!("Failed to star plant: {}", e);
+    }
+}
+
+/// Renders the herbarium gallery while `herbarium.open` is set.
+///
+/// `can_inject` enables the "Inject" buttons (the nursery is open with a
+/// selection). Removing an entry saves the collection.
+pub fn herbarium_ui(
+    ctx: &egui::Context,
+    herbarium: &mut Herbarium,
+    can_inject: bool,
+) -> Option<HerbariumAction> {
+    let mut action = None;
+    let mut remove = None;
+    let mut open = herbarium.open;
+
+    egui::Window::new("🌿 Herbarium")
+        .open(&mut open)
+        .default_width(4.0 * CARD_WIDTH)
+        .show(ctx, |ui| {
+            if herbarium.entries.is_empty() {
+                ui.label(
+                    egui::RichText::new("Star a plant with ⭐ to keep it here")
+                        .small()
+                        .color(egui::Color32::GRAY),
+                );
+                return;
+            }
+            egui::ScrollArea::vertical()
+                .id_salt("herbarium_gallery")
+                .max_height(480.0)
+                .show(ui, |ui| {
+                    ui.horizontal_wrapped(|ui| {
+                        for entry in herbarium.entries.iter().rev() {
+                            ui.group(|ui| {
+                                ui.set_width(CARD_WIDTH);
+                                ui.vertical(|ui| {
+                                    thumbnail_ui(ui, entry.id, entry.thumbnail.as_deref());
+                                    ui.label(egui::RichText::new(&entry.name).strong())
+                                        .on_hover_text(&entry.genotype.source_code);
+                                    ui.label(
+                                        egui::RichText::new(&entry.starred_at)
+                                            .small()
+                                            .color(egui::Color32::GRAY),
+                                    );
+                                    ui.horizontal(|ui| {
+                                        if ui
+                                            .small_button("Load")
+                                            .on_hover_text("Load into the editor")
+                                            .clicked()
+                                        {
+                                            action =
+                                                Some(HerbariumAction::Load(entry.genotype.clone()));
+                                        }
+                                        if ui
+                                            .add_enabled(
+                                                can_inject,
+                                                egui::Button::new("Inject").small(),
+                                            )
+                                            .on_hover_text(
+                                                "Replace the selected nursery individuals",
+                                            )
+                                            .clicked()
+                                        {
+                                            action = Some(HerbariumAction::Inject(
+                                                entry.genotype.clone(),
+                                            ));
+                                        }
+                                        if ui.small_button("🗑").on_hover_text("Remove").clicked()
+                                        {
+                                            remove = Some(entry.id);
+                                        }
+                                    });
+                                });
+                            });
+                        }
+                    });
+                });
+        });
+    herbarium.open = open;
+
+    if let Some(id) = remove
+        && herbarium.remove(id)
+        && let Err(e) = herbarium.save()
+    {
+        warn!("Failed to save herbarium: {}", e);
+    }
+    action
+}
+
+/// Returns the egui texture for a `THUMBNAIL_SIZE`² thumbnail, uploading the
+/// pixels from `rgba` when `key` has no texture yet or one of another `revision`.
+pub fn thumbnail_texture(
+    ctx: &egui::Context,
+    key: egui::Id,
+    revision: u64,
+    rgba: impl FnOnce() -> Option<Vec<u8>>,
+) -> Option<egui::TextureHandle> {
+    let cached: Option<(u64, egui::TextureHandle)> = ctx.data(|data| data.get_temp(key));
+    if let Some((cached_revision, texture)) = cached
+        && cached_revision == revision
+    {
+        return Some(texture);
+    }
+    let image = egui::ColorImage::from_rgba_unmultiplied([THUMBNAIL_SIZE; 2], &rgba()?);
+    let texture = ctx.load_texture(
+        format!("thumbnail_{:?}", key),
+        image,
+        egui::TextureOptions::LINEAR,
+    );
+    ctx.data_mut(|data| data.insert_temp(key, (revision, texture.clone())));
+    Some(texture)
+}
+
+/// Draws an entry's thumbnail, or a placeholder while it is being rendered.
+pub(crate) fn thumbnail_ui(ui: &mut egui::Ui, id: u64, encoded: Option<&str>) {
+    let size = egui::vec2(THUMBNAIL_SIZE as f32, THUMBNAIL_SIZE as f32);
+    let key = egui::Id::new(("herbarium_thumbnail", id));
+    let texture = thumbnail_texture(ui.ctx(), key, 0, || {
+        encoded.and_then(|encoded| decode_thumbnail(encoded).ok())
+    });
+
+    match texture {
+        Some(texture) => {
+            ui.image(egui::load::SizedTexture::new(texture.id(), size));
+        }
+        None => {
+            ui.allocate_ui(size, |ui| {
+                ui.centered_and_justified(|ui| {
+                    ui.label(egui::RichText::new("🌿").size(40.0));
+                });
+            });
+        }
+    }
+}
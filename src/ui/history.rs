@@ -0,0 +1,128 @@
+//! Derivation history timeline with rollback and grammar diff.
+
+use crate::core::history::{DerivationHistory, HistoryEntry};
+use crate::logic::diff::{DiffLine, diff_lines};
+use bevy_egui::egui;
+
+/// Renders the history timeline and diff view.
+///
+/// Returns `Some(HistoryEntry)` when the user clicks "Restore", so the caller
+/// can apply it to the editor config.
+pub fn history_ui(ui: &mut egui::Ui, history: &mut DerivationHistory) -> Option<HistoryEntry> {
+    let mut restore = None;
+
+    if history.entries.is_empty() {
+        ui.label(
+            egui::RichText::new("No successful derivations yet")
+                .small()
+                .color(egui::Color32::GRAY),
+        );
+        return None;
+    }
+
+    let newest = history.first_index + history.entries.len() - 1;
+    egui::ScrollArea::vertical()
+        .id_salt("history_timeline")
+        .max_height(160.0)
+        .show(ui, |ui| {
+            let rows: Vec<(usize, String)> = history
+                .iter()
+                .rev()
+                .map(|(index, entry)| {
+                    (
+                        index,
+                        format!(
+                            "#{} {}  iter {}  seed {}",
+                            index, entry.timestamp, entry.iterations, entry.seed
+                        ),
+                    )
+                })
+                .collect();
+
+            for (index, label) in rows {
+                ui.horizontal(|ui| {
+                    let is_from = history.diff_from == Some(index);
+                    let is_to = history.diff_to == Some(index);
+                    if ui
+                        .selectable_label(is_from, "A")
+                        .on_hover_text("Diff base")
+                        .clicked()
+                    {
+                        history.diff_from = (!is_from).then_some(index);
+                    }
+                    if ui
+                        .selectable_label(is_to, "B")
+                        .on_hover_text("Diff target")
+                        .clicked()
+                    {
+                        history.diff_to = (!is_to).then_some(index);
+                    }
+
+                    let text = egui::RichText::new(label).monospace();
+                    if index == newest {
+                        ui.label(text.strong());
+                    } else {
+                        ui.label(text);
+                    }
+
+                    if ui
+                        .small_button("↺")
+                        .on_hover_text("Restore this grammar and its parameters")
+                        .clicked()
+                    {
+                        restore = history.get(index).cloned();
+                    }
+                });
+            }
+        });
+
+    let (Some(from), Some(to)) = (history.diff_from, history.diff_to) else {
+        ui.label(
+            egui::RichText::new("Select A and B to compare two entries")
+                .small()
+                .color(egui::Color32::GRAY),
+        );
+        return restore;
+    };
+    let (Some(a), Some(b)) = (history.get(from), history.get(to)) else {
+        return restore;
+    };
+
+    ui.separator();
+    ui.label(egui::RichText::new(format!("Diff #{} → #{}", from, to)).strong());
+
+    for change in a.parameter_changes(b) {
+        ui.label(egui::RichText::new(change).small());
+    }
+
+    let (old, new) = (a.grammar(), b.grammar());
+    let diff = diff_lines(&old, &new);
+    if diff.iter().all(|line| matches!(line, DiffLine::Same(_))) {
+        ui.label(
+            egui::RichText::new("Grammar unchanged")
+                .small()
+                .color(egui::Color32::GRAY),
+        );
+        return restore;
+    }
+
+    egui::ScrollArea::vertical()
+        .id_salt("history_diff")
+        .max_height(200.0)
+        .show(ui, |ui| {
+            for line in diff {
+                let (prefix, text, color) = match line {
+                    DiffLine::Same(text) => (" ", text, egui::Color32::GRAY),
+                    DiffLine::Removed(text) => ("-", text, egui::Color32::from_rgb(255, 110, 110)),
+                    DiffLine::Added(text) => ("+", text, egui::Color32::from_rgb(110, 220, 110)),
+                };
+                ui.label(
+                    egui::RichText::new(format!("{} {}", prefix, text))
+                        .monospace()
+                        .color(color),
+                );
+            }
+        });
+
+    restore
+}
@@ -0,0 +1,94 @@
+//! Editor for the named virtual buffers available to `#include`.
+
+use crate::core::includes::IncludeLibrary;
+use bevy_egui::egui;
+
+/// Renders the include library: a buffer list, an editor for the selected
+/// buffer, and controls to add or remove buffers.
+///
+/// Returns `true` when any buffer changed, so the caller can save the library
+/// and recompile.
+pub fn include_library_ui(ui: &mut egui::Ui, library: &mut IncludeLibrary) -> bool {
+    let selected_id = egui::Id::new("include_library_selected");
+    let new_name_id = egui::Id::new("include_library_new_name");
+    let mut selected: Option<String> = ui.data(|data| data.get_temp(selected_id)).flatten();
+    let mut new_name: String = ui
+        .data(|data| data.get_temp(new_name_id))
+        .unwrap_or_default();
+    let mut changed = false;
+
+    ui.label(
+        egui::RichText::new("Reference a buffer with #include \"name\"")
+            .small()
+            .color(egui::Color32::GRAY),
+    );
+    #[cfg(not(target_arch = "wasm32"))]
+    ui.label(
+        egui::RichText::new(
+            "Names not found here are read as files relative to the working directory",
+        )
+        .small()
+        .color(egui::Color32::GRAY),
+    );
+
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(&mut new_name)
+                .hint_text("leaves.ls")
+                .desired_width(140.0),
+        );
+        let name = new_name.trim();
+        let valid = !name.is_empty() && !name.contains('"') && !library.buffers.contains_key(name);
+        if ui.add_enabled(valid, egui::Button::new("➕ Add")).clicked() {
+            library.buffers.insert(name.to_string(), String::new());
+            selected = Some(name.to_string());
+            new_name.clear();
+            changed = true;
+        }
+    });
+
+    let mut remove = None;
+    for name in library.buffers.keys() {
+        ui.horizontal(|ui| {
+            let is_selected = selected.as_ref() == Some(name);
+            if ui.selectable_label(is_selected, name).clicked() {
+                selected = (!is_selected).then(|| name.clone());
+            }
+            if ui
+                .small_button("🗑")
+                .on_hover_text("Delete buffer")
+                .clicked()
+            {
+                remove = Some(name.clone());
+            }
+        });
+    }
+    if let Some(name) = remove {
+        library.buffers.remove(&name);
+        if selected.as_ref() == Some(&name) {
+            selected = None;
+        }
+        changed = true;
+    }
+
+    if let Some(text) = selected
+        .as_ref()
+        .and_then(|name| library.buffers.get_mut(name))
+    {
+        ui.separator();
+        changed |= ui
+            .add(
+                egui::TextEdit::multiline(text)
+                    .code_editor()
+                    .desired_rows(8)
+                    .desired_width(f32::INFINITY),
+            )
+            .changed();
+    }
+
+    ui.data_mut(|data| {
+        data.insert_temp(selected_id, selected);
+        data.insert_temp(new_name_id, new_name);
+    });
+    changed
+}
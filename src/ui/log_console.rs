@@ -0,0 +1,165 @@
+//! In-app log console.
+//!
+//! Shows the lines kept by [`crate::core::log_capture`], so browser users (who
+//! have no terminal) and desktop users launched from a file manager can still
+//! see export results, timings, and warnings.
+
+use crate::core::log_capture::{LogLine, clear_logs, recent_logs};
+use bevy::log::tracing::Level;
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+/// Levels offered by the filter, from least to most verbose.
+const FILTER_LEVELS: &[Level] = &[Level::ERROR, Level::WARN, Level::INFO, Level::DEBUG];
+
+/// Log console window state.
+#[derive(Resource)]
+pub struct LogConsole {
+    pub open: bool,
+    /// Most verbose level shown.
+    pub max_level: Level,
+    /// Case-insensitive text the message or target must contain.
+    pub filter: String,
+}
+
+impl Default for LogConsole {
+    fn default() -> Self {
+        Self {
+            open: false,
+            max_level: Level::INFO,
+            filter: String::new(),
+        }
+    }
+}
+
+impl LogConsole {
+    /// True if the line passes the level and text filters.
+    pub fn matches(&self, line: &LogLine) -> bool {
+        if line.level > self.max_level {
+            return false;
+        }
+        let filter = self.filter.trim().to_lowercase();
+        filter.is_empty()
+            || line.message.to_lowercase().contains(&filter)
+            || line.target.to_lowercase().contains(&filter)
+    }
+}
+
+fn level_color(level: Level) -> egui::Color32 {
+    match level {
+        Level::ERROR => egui::Color32::from_rgb(255, 100, 100),
+        Level::WARN => egui::Color32::YELLOW,
+        Level::INFO => egui::Color32::LIGHT_GRAY,
+        _ => egui::Color32::GRAY,
+    }
+}
+
+/// Renders the log console while `console.open` is set.
+pub fn log_console_ui(ctx: &egui::Context, console: &mut LogConsole) {
+    let mut open = console.open;
+    egui::Window::new("📜 Log")
+        .open(&mut open)
+        .default_width(560.0)
+        .default_height(320.0)
+        .show(ctx, |ui| {
+            let lines: Vec<LogLine> = recent_logs()
+                .into_iter()
+                .filter(|line| console.matches(line))
+                .collect();
+
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt("log_level")
+                    .selected_text(console.max_level.as_str())
+                    .show_ui(ui, |ui| {
+                        for level in FILTER_LEVELS {
+                            ui.selectable_value(&mut console.max_level, *level, level.as_str());
+                        }
+                    })
+                    .response
+                    .on_hover_text("Show this level and more severe ones");
+                ui.add(
+                    egui::TextEdit::singleline(&mut console.filter)
+                        .hint_text("Filter")
+                        .desired_width(160.0),
+                );
+                if ui
+                    .button("📋 Copy")
+                    .on_hover_text("Copy the shown lines to the clipboard")
+                    .clicked()
+                {
+                    let text: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+                    ui.ctx().copy_text(text.join("\n"));
+                }
+                if ui.button("🗑 Clear").clicked() {
+                    clear_logs();
+                }
+            });
+            ui.separator();
+
+            if lines.is_empty() {
+                ui.label(
+                    egui::RichText::new("No matching log lines")
+                        .small()
+                        .color(egui::Color32::GRAY),
+                );
+                return;
+            }
+            egui::ScrollArea::vertical()
+                .id_salt("log_lines")
+                .auto_shrink([false, false])
+                .stick_to_bottom(true)
+                .show_rows(
+                    ui,
+                    ui.text_style_height(&egui::TextStyle::Monospace),
+                    lines.len(),
+                    |ui, rows| {
+                        for line in &lines[rows] {
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    egui::RichText::new(&line.timestamp)
+                                        .monospace()
+                                        .color(egui::Color32::GRAY),
+                                );
+                                ui.label(
+                                    egui::RichText::new(format!("{:>5}", line.level))
+                                        .monospace()
+                                        .color(level_color(line.level)),
+                                );
+                                ui.label(egui::RichText::new(&line.message).monospace())
+                                    .on_hover_text(&line.target);
+                            });
+                        }
+                    },
+                );
+        });
+    console.open = open;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_by_level_and_text() {
+        let line = |level, message: &str| LogLine {
+            level,
+            target: "lsystem_explorer::visuals::export".to_string(),
+            message: message.to_string(),
+            timestamp: "12:00:00.000".to_string(),
+        };
+        let mut console = LogConsole::default();
+        assert!(console.matches(&line(Level::WARN, "Export failed")));
+        assert!(console.matches(&line(Level::INFO, "Exported 3 plants")));
+        assert!(!console.matches(&line(Level::DEBUG, "Frame")));
+
+        console.max_level = Level::WARN;
+        assert!(!console.matches(&line(Level::INFO, "Exported 3 plants")));
+
+        console.max_level = Level::INFO;
+        console.filter = "EXPORTED".to_string();
+        assert!(console.matches(&line(Level::INFO, "Exported 3 plants")));
+        assert!(!console.matches(&line(Level::WARN, "Export failed")));
+        console.filter = "visuals::export".to_string();
+        assert!(console.matches(&line(Level::WARN, "Export failed")));
+    }
+}
@@ -1,3 +1,19 @@
+pub mod describe;
 pub mod editor;
 pub mod editor_utils;
+pub mod garden;
+pub mod generator;
+pub mod herbarium;
+pub mod history;
+pub mod includes;
+pub mod log_console;
+#[cfg(feature = "nursery")]
 pub mod nursery;
+pub mod palettes;
+pub mod preset_browser;
+pub mod rule_graph;
+pub mod textures;
+pub mod toasts;
+pub mod variations;
+pub mod watch;
+pub mod wizard;
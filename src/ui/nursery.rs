@@ -4,16 +4,23 @@
 //! populations of plant genotypes using genetic algorithms.
 
 use crate::core::config::{
-    LSystemConfig, MaterialSettings, MaterialSettingsMap, PropConfig, PropMeshType,
+    CancellationFlag, LSystemConfig, MaterialSettingsMap, PropConfig, join_source_code,
 };
-use crate::core::genotype::PlantGenotype;
+use crate::core::genotype::{AXIOM_KEY, MutationSettings, PlantGenotype, rule_keys};
+use crate::core::presets::{LSystemPreset, PRESETS};
+use crate::logic::diff::{DiffRow, diff_lines, side_by_side};
+use crate::ui::herbarium::thumbnail_texture;
+use crate::visuals::genotype_render::{CachedGenotypeMesh, PlantAssets, PlantDetail};
+use crate::visuals::thumbnail::{ThumbnailRenderer, ThumbnailSlot};
 use bevy::platform::collections::{HashMap, HashSet};
 use bevy::prelude::*;
+use bevy::tasks::AsyncComputeTaskPool;
 use bevy_egui::egui;
 use rand::{Rng, SeedableRng};
 use rand_pcg::Pcg64;
 use std::hash::{Hash, Hasher};
-use symbios::System;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use symbios_genetics::{Genotype, Phenotype};
 
 /// Combines a base seed with additional discriminants into a statistically distinct u64.
@@ -30,14 +37,14 @@ fn mix_seed(base_seed: u64, generation: usize, index: usize) -> u64 {
 pub const GRID_SPACING: f32 = 750.0;
 
 /// Component tag for nursery 3D meshes (branches).
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct NurseryMeshTag {
     /// Index in the population (0-8).
     pub index: usize,
 }
 
 /// Component tag for nursery 3D props (leaves, etc.).
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct NurseryPropTag {
     /// Index in the population (0-8).
     pub index: usize,
@@ -50,40 +57,26 @@ pub struct NurseryLabelTag {
     pub index: usize,
 }
 
-/// Cached derived state for a single genotype in the population.
-pub struct CachedGenotypeMesh {
-    /// The derived L-system state (None if derivation failed).
-    pub system: Option<System>,
-    /// Fitness value for display.
-    pub fitness: f32,
-    /// Individual's default turn angle in degrees.
-    pub angle: f32,
-    /// Individual's step size.
-    pub step: f32,
-    /// Individual's default branch width.
-    pub width: f32,
-    /// Individual's elasticity factor for tropism bending.
-    pub elasticity: f32,
-    /// Individual's tropism direction vector.
-    pub tropism: Option<Vec3>,
-    /// Individual's material settings by slot ID.
-    pub materials: HashMap<u8, MaterialSettings>,
-    /// Individual's prop ID to mesh type mapping.
-    pub prop_mappings: HashMap<u16, PropMeshType>,
-    /// Error message if derivation failed.
-    pub error: Option<String>,
-}
-
 /// Resource caching the derived meshes for the nursery population.
 /// This prevents re-derivation every frame.
+///
+/// Entries are keyed by [`PlantGenotype::content_hash`], so identical
+/// individuals (such as elites carried over between generations) are derived
+/// and meshed once and share their `Mesh` handles.
 #[derive(Resource, Default)]
 pub struct PopulationMeshCache {
-    /// Cached systems for each population index.
-    pub entries: HashMap<usize, CachedGenotypeMesh>,
+    /// Derived systems by genotype hash.
+    pub derived: HashMap<u64, CachedGenotypeMesh>,
+    /// Genotype hash of each population index.
+    pub cells: HashMap<usize, u64>,
+    /// Meshed plants by genotype hash, built at `detail`.
+    pub assets: HashMap<u64, PlantAssets>,
     /// Generation number when this cache was built.
     pub cached_generation: usize,
     /// Whether the cache needs to be rebuilt.
     pub dirty: bool,
+    /// Level of detail the spawned meshes were built with.
+    pub detail: Option<PlantDetail>,
 }
 
 impl PopulationMeshCache {
@@ -94,9 +87,62 @@ impl PopulationMeshCache {
 
     /// Clears all cached entries.
     pub fn clear(&mut self) {
-        self.entries.clear();
+        self.derived.clear();
+        self.cells.clear();
+        self.assets.clear();
         self.dirty = true;
     }
+
+    /// The derived entry shown at a population index.
+    pub fn entry(&self, index: usize) -> Option<&CachedGenotypeMesh> {
+        self.derived.get(self.cells.get(&index)?)
+    }
+
+    /// Points the cells at a new population, keeping only the entries still
+    /// in it. Returns the hashes that have not been derived yet.
+    pub fn retain_population(&mut self, hashes: &[u64]) -> HashSet<u64> {
+        self.cells = hashes.iter().copied().enumerate().collect();
+        let live: HashSet<u64> = hashes.iter().copied().collect();
+        self.derived.retain(|hash, _| live.contains(hash));
+        self.assets.retain(|hash, _| live.contains(hash));
+        live.into_iter()
+            .filter(|hash| !self.derived.contains_key(hash))
+            .collect()
+    }
+}
+
+/// Reduced detail for nursery plants, which are viewed from far away.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NurseryLod {
+    pub enabled: bool,
+    /// Vertices per branch ring, capped at the editor's resolution.
+    pub mesh_resolution: u32,
+    /// Props smaller than this (world units) are not spawned.
+    pub min_prop_size: f32,
+}
+
+impl Default for NurseryLod {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            mesh_resolution: 4,
+            min_prop_size: 2.0,
+        }
+    }
+}
+
+impl NurseryLod {
+    /// Detail to spawn nursery plants with, given the editor's ring resolution.
+    pub fn detail(&self, editor_resolution: u32) -> PlantDetail {
+        if self.enabled {
+            PlantDetail {
+                mesh_resolution: self.mesh_resolution.min(editor_resolution),
+                min_prop_size: self.min_prop_size,
+            }
+        } else {
+            PlantDetail::full(editor_resolution)
+        }
+    }
 }
 
 /// Nursery mode state.
@@ -109,6 +155,96 @@ pub enum NurseryMode {
     Enabled,
 }
 
+/// How `NurseryState::breed` picks parents for offspring.
+///
+/// Selected champions are always carried over unchanged; the strategy only
+/// decides which individuals parent the remaining slots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelectionStrategy {
+    /// Parents drawn uniformly from the selected champions.
+    #[default]
+    Manual,
+    /// Fitness-proportional: parents drawn with probability proportional to fitness.
+    Roulette,
+    /// Best of `tournament_size` individuals drawn uniformly.
+    Tournament,
+    /// Probability proportional to fitness rank, insensitive to fitness scale.
+    Rank,
+}
+
+impl SelectionStrategy {
+    pub const ALL: &'static [SelectionStrategy] = &[
+        SelectionStrategy::Manual,
+        SelectionStrategy::Roulette,
+        SelectionStrategy::Tournament,
+        SelectionStrategy::Rank,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SelectionStrategy::Manual => "Manual (champions)",
+            SelectionStrategy::Roulette => "Roulette (fitness)",
+            SelectionStrategy::Tournament => "Tournament",
+            SelectionStrategy::Rank => "Rank",
+        }
+    }
+}
+
+/// Population state after a background auto-evolve run.
+struct AutoEvolveResult {
+    population: Vec<Phenotype<PlantGenotype>>,
+    parents: HashMap<usize, Vec<PlantGenotype>>,
+    selected: HashSet<usize>,
+    generation: usize,
+}
+
+/// Background run of several breeding generations without user interaction.
+pub struct AutoEvolveTask {
+    /// Number of generations per run, chosen in the UI.
+    pub generations: usize,
+    shared: Option<Arc<Mutex<Option<AutoEvolveResult>>>>,
+    cancel_flag: Option<CancellationFlag>,
+    progress: Option<Arc<AtomicUsize>>,
+    total: usize,
+}
+
+impl Default for AutoEvolveTask {
+    fn default() -> Self {
+        Self {
+            generations: 10,
+            shared: None,
+            cancel_flag: None,
+            progress: None,
+            total: 0,
+        }
+    }
+}
+
+impl AutoEvolveTask {
+    /// True while a run is in progress.
+    pub fn is_running(&self) -> bool {
+        self.shared.is_some()
+    }
+
+    /// Generations completed by the running task, and the run's total.
+    pub fn progress(&self) -> (usize, usize) {
+        let done = self
+            .progress
+            .as_ref()
+            .map_or(0, |p| p.load(Ordering::Relaxed));
+        (done, self.total)
+    }
+
+    /// Stops the running task, discarding its generations.
+    pub fn cancel(&mut self) {
+        if let Some(flag) = self.cancel_flag.take() {
+            flag.store(false, Ordering::Relaxed);
+        }
+        self.shared = None;
+        self.progress = None;
+    }
+}
+
 /// Manages the evolutionary population of plant genotypes.
 #[derive(Resource)]
 pub struct NurseryState {
@@ -120,6 +256,10 @@ pub struct NurseryState {
     pub selected: HashSet<usize>,
     /// Mutation rate for breeding operations.
     pub mutation_rate: f32,
+    /// Enabled mutation operators and their strengths.
+    pub mutation: MutationSettings,
+    /// Rule keys protected from mutation and crossover, applied to every individual.
+    pub locked: Vec<String>,
     /// RNG seed for reproducibility.
     pub seed: u64,
     /// Generation counter.
@@ -130,8 +270,36 @@ pub struct NurseryState {
     pub grid_spacing: f32,
     /// Grid size (NxN grid, default 3 for 9 individuals).
     pub grid_size: usize,
+    /// Level of detail of the 3D grid.
+    pub lod: NurseryLod,
     /// Derivation errors by population index (for UI display).
     pub errors: HashMap<usize, String>,
+    /// Set by the UI to export the selected individuals with the export settings.
+    pub export_requested: bool,
+    /// Set by the UI to export the whole population as one GLB scene.
+    pub scene_export_requested: bool,
+    /// Parent selection strategy for breeding.
+    pub selection: SelectionStrategy,
+    /// Number of contestants per tournament in `SelectionStrategy::Tournament`.
+    pub tournament_size: usize,
+    /// Background multi-generation run.
+    pub auto_evolve: AutoEvolveTask,
+    /// Genotypes each individual was bred or mutated from, by population
+    /// index: one for a mutant, both for a crossover of two different parents.
+    /// Missing for individuals taken as-is from the editor or a preset.
+    pub parents: HashMap<usize, Vec<PlantGenotype>>,
+    /// Individual shown in the inspector window.
+    pub inspected: Option<usize>,
+    /// Individual the "Inject from file" window replaces.
+    pub inject_target: Option<usize>,
+    /// File path (native) or pasted text (web) for the "Inject from file" window.
+    pub inject_input: String,
+    /// Error from the last "Inject from file" attempt.
+    pub inject_error: Option<String>,
+    /// Set by the cell menu to star an individual in the herbarium.
+    pub star_requested: Option<usize>,
+    /// Individual shown alone in the 3D view, opened by double-clicking a cell.
+    pub solo: Option<usize>,
 }
 
 impl Default for NurseryState {
@@ -141,12 +309,27 @@ impl Default for NurseryState {
             population: Vec::new(),
             selected: HashSet::new(),
             mutation_rate: 0.15,
+            mutation: MutationSettings::default(),
+            locked: Vec::new(),
             seed: 42,
             generation: 0,
             needs_3d_rebuild: false,
             grid_spacing: GRID_SPACING,
             grid_size: 3,
+            lod: NurseryLod::default(),
             errors: HashMap::new(),
+            export_requested: false,
+            scene_export_requested: false,
+            selection: SelectionStrategy::Manual,
+            tournament_size: 3,
+            auto_evolve: AutoEvolveTask::default(),
+            parents: HashMap::new(),
+            inspected: None,
+            inject_target: None,
+            inject_input: String::new(),
+            inject_error: None,
+            star_requested: None,
+            solo: None,
         }
     }
 }
@@ -157,6 +340,23 @@ impl NurseryState {
         self.grid_size * self.grid_size
     }
 
+    /// World position of a cell's plant in the 3D grid (NxN in the XZ plane).
+    pub fn cell_position(&self, index: usize) -> Vec3 {
+        let grid_offset = (self.grid_size as f32 - 1.0) * self.grid_spacing / 2.0;
+        let row = index / self.grid_size;
+        let col = index % self.grid_size;
+        Vec3::new(
+            col as f32 * self.grid_spacing - grid_offset,
+            0.0,
+            row as f32 * self.grid_spacing - grid_offset,
+        )
+    }
+
+    /// Whether a cell is shown in the 3D view; all are, unless one is solo.
+    pub fn is_cell_visible(&self, index: usize) -> bool {
+        self.solo.is_none_or(|solo| solo == index)
+    }
+
     /// Initializes the population from the current editor state.
     pub fn initialize_from_editor(
         &mut self,
@@ -165,19 +365,8 @@ impl NurseryState {
         prop_config: &PropConfig,
     ) {
         // Create base genotype from current editor state
-        let mut base = PlantGenotype::new(config.source_code.clone())
-            .with_finalization(config.finalization_code.clone())
-            .with_materials(&materials.settings)
-            .with_params(
-                config.iterations,
-                config.default_angle,
-                config.step_size,
-                config.default_width,
-            )
-            .with_seed(config.seed);
-        base.elasticity = config.elasticity;
-        base.tropism = config.tropism.map(|v| [v.x, v.y, v.z]);
-        base.prop_mappings = prop_config.prop_meshes.clone();
+        let mut base = PlantGenotype::from_editor(config, materials, prop_config);
+        base.locked = self.locked.clone();
 
         let pop_size = self.population_size();
         let mut rng = Pcg64::seed_from_u64(mix_seed(self.seed, 0, 0));
@@ -192,10 +381,12 @@ impl NurseryState {
         });
 
         // Rest are mutated variants
+        self.parents.clear();
         for i in 1..pop_size {
+            self.parents.insert(i, vec![base.clone()]);
             let mut variant = base.clone();
             variant.seed = mix_seed(self.seed, 0, i);
-            variant.mutate(&mut rng, self.mutation_rate);
+            variant.mutate_with(&mut rng, self.mutation_rate, &self.mutation);
             let fitness = evaluate_genotype(&variant);
             new_population.push(Phenotype {
                 genotype: variant,
@@ -209,6 +400,8 @@ impl NurseryState {
         self.generation = 0;
         self.selected.clear();
         self.selected.insert(0);
+        self.inspected = None;
+        self.solo = None;
     }
 
     /// Resizes the population when grid size changes.
@@ -231,9 +424,10 @@ impl NurseryState {
                     .get(source_idx)
                     .map(|p| p.genotype.clone())
                     .unwrap_or_else(|| PlantGenotype::new("omega: F\nF -> F".to_string()));
-                let mut variant = source;
+                let mut variant = source.clone();
+                self.parents.insert(i, vec![source]);
                 variant.seed = mix_seed(self.seed, self.generation, i);
-                variant.mutate(&mut rng, self.mutation_rate);
+                variant.mutate_with(&mut rng, self.mutation_rate, &self.mutation);
                 let fitness = evaluate_genotype(&variant);
                 self.population.push(Phenotype {
                     genotype: variant,
@@ -247,11 +441,16 @@ impl NurseryState {
             self.population.truncate(new_pop_size);
             // Remove invalid selections
             self.selected.retain(|&idx| idx < new_pop_size);
+            self.parents.retain(|&idx, _| idx < new_pop_size);
+            self.inspected = self.inspected.filter(|&idx| idx < new_pop_size);
+            self.solo = self.solo.filter(|&idx| idx < new_pop_size);
         }
     }
 
     /// Breeds the next generation using Interactive Evolutionary Computation (IEC).
-    /// Champions (selected individuals) are preserved and used as parents.
+    /// Champions (selected individuals) are preserved and, with manual selection,
+    /// used as the only parents; other strategies draw parents from the whole
+    /// population by fitness.
     pub fn breed(&mut self) {
         if self.population.is_empty() {
             return;
@@ -261,16 +460,19 @@ impl NurseryState {
         let mut rng = Pcg64::seed_from_u64(mix_seed(self.seed, self.generation, 0));
 
         // Identify champions (selected individuals)
-        let champions: Vec<usize> = self.selected.iter().copied().collect();
+        let mut champions: Vec<usize> = self.selected.iter().copied().collect();
+        champions.sort_unstable();
 
         let mut new_population = Vec::with_capacity(pop_size);
+        let mut new_parents = HashMap::new();
 
-        if champions.is_empty() {
+        if champions.is_empty() && self.selection == SelectionStrategy::Manual {
             // Fallback: mutate all individuals randomly
             for (i, phenotype) in self.population.iter().enumerate() {
+                new_parents.insert(i, vec![phenotype.genotype.clone()]);
                 let mut offspring = phenotype.genotype.clone();
                 offspring.seed = mix_seed(self.seed, self.generation, i);
-                offspring.mutate(&mut rng, self.mutation_rate);
+                offspring.mutate_with(&mut rng, self.mutation_rate, &self.mutation);
                 let fitness = evaluate_genotype(&offspring);
                 new_population.push(Phenotype {
                     genotype: offspring,
@@ -283,16 +485,18 @@ impl NurseryState {
             // Elitism: preserve champions first
             for &idx in &champions {
                 if let Some(phenotype) = self.population.get(idx) {
+                    if let Some(parent) = self.parents.get(&idx) {
+                        new_parents.insert(new_population.len(), parent.clone());
+                    }
                     new_population.push(phenotype.clone());
                 }
             }
 
-            // Fill remaining slots with offspring from champions
+            // Fill remaining slots with offspring of the chosen parents
             let remaining = pop_size.saturating_sub(new_population.len());
             for i in 0..remaining {
-                // Randomly select two parents from champions
-                let parent_a_idx = champions[rng.random_range(0..champions.len())];
-                let parent_b_idx = champions[rng.random_range(0..champions.len())];
+                let parent_a_idx = self.select_parent(&champions, &mut rng);
+                let parent_b_idx = self.select_parent(&champions, &mut rng);
 
                 let parent_a = &self.population[parent_a_idx].genotype;
                 let parent_b = &self.population[parent_b_idx].genotype;
@@ -302,9 +506,15 @@ impl NurseryState {
 
                 // Mutation
                 offspring.seed = mix_seed(self.seed, self.generation, champions.len() + i);
-                offspring.mutate(&mut rng, self.mutation_rate);
+                offspring.mutate_with(&mut rng, self.mutation_rate, &self.mutation);
 
                 let fitness = evaluate_genotype(&offspring);
+                let lineage = if parent_a_idx == parent_b_idx {
+                    vec![parent_a.clone()]
+                } else {
+                    vec![parent_a.clone(), parent_b.clone()]
+                };
+                new_parents.insert(new_population.len(), lineage);
                 new_population.push(Phenotype {
                     genotype: offspring,
                     fitness,
@@ -315,6 +525,7 @@ impl NurseryState {
         }
 
         self.population = new_population;
+        self.parents = new_parents;
         self.generation += 1;
 
         // Update selection to point to preserved champions (now at start of population)
@@ -324,6 +535,132 @@ impl NurseryState {
         }
     }
 
+    /// Crosses the individual at `index` with `editor` (the live editor state),
+    /// replacing every other unselected individual with an offspring.
+    ///
+    /// Offspring alternate which parent leads the crossover, so both grammars
+    /// contribute first-parent traits; the selection is kept.
+    pub fn breed_with_editor(&mut self, index: usize, mut editor: PlantGenotype) {
+        let Some(individual) = self.get_genotype(index) else {
+            return;
+        };
+        editor.locked = self.locked.clone();
+
+        self.generation += 1;
+        let mut rng = Pcg64::seed_from_u64(mix_seed(self.seed, self.generation, 0));
+
+        let targets: Vec<usize> = (0..self.population.len())
+            .filter(|&i| i != index && !self.selected.contains(&i))
+            .collect();
+        for (n, &i) in targets.iter().enumerate() {
+            let (parent_a, parent_b) = if n % 2 == 0 {
+                (&individual, &editor)
+            } else {
+                (&editor, &individual)
+            };
+            let mut offspring = parent_a.crossover(parent_b, &mut rng);
+            offspring.seed = mix_seed(self.seed, self.generation, i);
+            offspring.mutate_with(&mut rng, self.mutation_rate, &self.mutation);
+
+            self.parents
+                .insert(i, vec![parent_a.clone(), parent_b.clone()]);
+            self.population[i] = Phenotype {
+                fitness: evaluate_genotype(&offspring),
+                genotype: offspring,
+                objectives: vec![],
+                descriptor: vec![],
+            };
+        }
+    }
+
+    /// Picks the population index of one parent according to the selection strategy.
+    ///
+    /// `champions` are the selected indices used by manual selection; with none
+    /// selected, manual selection falls back to a uniform draw from the population.
+    pub fn select_parent<R: Rng>(&self, champions: &[usize], rng: &mut R) -> usize {
+        let len = self.population.len();
+        let fitness = |i: usize| self.population[i].fitness.max(0.0);
+        match self.selection {
+            SelectionStrategy::Manual if !champions.is_empty() => {
+                champions[rng.random_range(0..champions.len())]
+            }
+            SelectionStrategy::Manual => rng.random_range(0..len),
+            SelectionStrategy::Roulette => {
+                let weights: Vec<f32> = (0..len).map(fitness).collect();
+                weighted_index(&weights, rng)
+            }
+            SelectionStrategy::Tournament => (0..self.tournament_size.max(1))
+                .map(|_| rng.random_range(0..len))
+                .max_by(|&a, &b| fitness(a).total_cmp(&fitness(b)))
+                .unwrap_or(0),
+            SelectionStrategy::Rank => {
+                let mut order: Vec<usize> = (0..len).collect();
+                order.sort_by(|&a, &b| fitness(a).total_cmp(&fitness(b)));
+                // The weakest individual has rank 1, the fittest rank `len`
+                let mut weights = vec![0.0; len];
+                for (rank, &i) in order.iter().enumerate() {
+                    weights[i] = (rank + 1) as f32;
+                }
+                weighted_index(&weights, rng)
+            }
+        }
+    }
+
+    /// Breeds `generations` generations on a background thread, fitness being
+    /// evaluated automatically for each offspring. The result replaces the
+    /// population once `poll_auto_evolve` picks it up.
+    pub fn start_auto_evolve(&mut self, generations: usize) {
+        self.auto_evolve.cancel();
+        if self.population.is_empty() || generations == 0 {
+            return;
+        }
+
+        let mut snapshot = NurseryState {
+            population: self.population.clone(),
+            parents: self.parents.clone(),
+            selected: self.selected.clone(),
+            mutation_rate: self.mutation_rate,
+            mutation: self.mutation.clone(),
+            locked: self.locked.clone(),
+            seed: self.seed,
+            generation: self.generation,
+            grid_size: self.grid_size,
+            selection: self.selection,
+            tournament_size: self.tournament_size,
+            ..default()
+        };
+
+        let shared: Arc<Mutex<Option<AutoEvolveResult>>> = Arc::new(Mutex::new(None));
+        let cancel_flag: CancellationFlag = Arc::new(AtomicBool::new(true));
+        let progress = Arc::new(AtomicUsize::new(0));
+        self.auto_evolve.shared = Some(shared.clone());
+        self.auto_evolve.cancel_flag = Some(cancel_flag.clone());
+        self.auto_evolve.progress = Some(progress.clone());
+        self.auto_evolve.total = generations;
+
+        AsyncComputeTaskPool::get()
+            .spawn(async move {
+                for _ in 0..generations {
+                    if !cancel_flag.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    snapshot.breed();
+                    progress.fetch_add(1, Ordering::Relaxed);
+                }
+                if cancel_flag.load(Ordering::Relaxed)
+                    && let Ok(mut guard) = shared.lock()
+                {
+                    *guard = Some(AutoEvolveResult {
+                        population: snapshot.population,
+                        parents: snapshot.parents,
+                        selected: snapshot.selected,
+                        generation: snapshot.generation,
+                    });
+                }
+            })
+            .detach();
+    }
+
     /// Mutates all individuals in the population (except selected champions).
     pub fn mutate_all(&mut self) {
         if self.population.is_empty() {
@@ -340,11 +677,25 @@ impl NurseryState {
             if self.selected.contains(&i) {
                 continue;
             }
-            phenotype.genotype.mutate(&mut rng, self.mutation_rate);
+            self.parents.insert(i, vec![phenotype.genotype.clone()]);
+            phenotype
+                .genotype
+                .mutate_with(&mut rng, self.mutation_rate, &self.mutation);
             phenotype.fitness = evaluate_genotype(&phenotype.genotype);
         }
     }
 
+    /// Locks or unlocks a rule key in every individual.
+    pub fn set_locked(&mut self, key: &str, locked: bool) {
+        self.locked.retain(|k| k != key);
+        if locked {
+            self.locked.push(key.to_string());
+        }
+        for phenotype in &mut self.population {
+            phenotype.genotype.locked = self.locked.clone();
+        }
+    }
+
     /// Gets the genotype at the specified index.
     pub fn get_genotype(&self, index: usize) -> Option<PlantGenotype> {
         self.population.get(index).map(|p| p.genotype.clone())
@@ -359,14 +710,45 @@ impl NurseryState {
         }
     }
 
+    /// Replaces the individual at `index` with `genotype`.
+    pub fn inject(&mut self, index: usize, mut genotype: PlantGenotype) {
+        genotype.locked = self.locked.clone();
+        if let Some(phenotype) = self.population.get_mut(index) {
+            phenotype.fitness = evaluate_genotype(&genotype);
+            phenotype.genotype = genotype;
+            self.parents.remove(&index);
+            self.needs_3d_rebuild = true;
+        }
+    }
+
+    /// Copies the individual at `index` unchanged into every other selected cell.
+    pub fn duplicate_to_selected(&mut self, index: usize) {
+        let Some(phenotype) = self.population.get(index).cloned() else {
+            return;
+        };
+        let parent = self.parents.get(&index).cloned();
+        for &idx in &self.selected {
+            if idx == index || idx >= self.population.len() {
+                continue;
+            }
+            self.population[idx] = phenotype.clone();
+            match &parent {
+                Some(parent) => self.parents.insert(idx, parent.clone()),
+                None => self.parents.remove(&idx),
+            };
+        }
+        self.needs_3d_rebuild = true;
+    }
+
     /// Replaces selected individuals with a new genotype.
     ///
     /// Each selected cell receives a copy of the genotype with a unique seed,
     /// and their fitness is recalculated. This is used for preset injection.
-    pub fn replace_selected(&mut self, genotype: PlantGenotype) {
+    pub fn replace_selected(&mut self, mut genotype: PlantGenotype) {
         if self.selected.is_empty() {
             return;
         }
+        genotype.locked = self.locked.clone();
 
         for (i, &idx) in self.selected.iter().enumerate() {
             if let Some(phenotype) = self.population.get_mut(idx) {
@@ -375,22 +757,464 @@ impl NurseryState {
                 variant.seed = mix_seed(self.seed, self.generation, idx + i);
                 phenotype.fitness = evaluate_genotype(&variant);
                 phenotype.genotype = variant;
+                self.parents.remove(&idx);
             }
         }
 
         self.needs_3d_rebuild = true;
     }
+
+    /// Injects a preset into the selected individuals while the nursery is open.
+    ///
+    /// Returns `false` (leaving the population untouched) when the nursery is
+    /// closed or nothing is selected, so the caller can load it into the editor.
+    pub fn inject_preset(&mut self, preset: &LSystemPreset) -> bool {
+        if self.mode != NurseryMode::Enabled || self.selected.is_empty() {
+            return false;
+        }
+        self.replace_selected(PlantGenotype::from_preset(preset));
+        true
+    }
+}
+
+/// Applies a finished auto-evolve run to the population and rebuilds the grid.
+pub fn poll_auto_evolve(mut nursery: ResMut<NurseryState>) {
+    let Some(shared) = &nursery.auto_evolve.shared else {
+        return;
+    };
+    let Some(result) = shared.lock().ok().and_then(|mut guard| guard.take()) else {
+        return;
+    };
+    nursery.auto_evolve.shared = None;
+    nursery.auto_evolve.cancel_flag = None;
+    nursery.auto_evolve.progress = None;
+
+    nursery.population = result.population;
+    nursery.parents = result.parents;
+    nursery.selected = result.selected;
+    nursery.generation = result.generation;
+    nursery.needs_3d_rebuild = true;
+}
+
+/// Draws an index with probability proportional to its weight, or uniformly
+/// when all weights are zero.
+fn weighted_index<R: Rng>(weights: &[f32], rng: &mut R) -> usize {
+    let total: f32 = weights.iter().sum();
+    if total <= 0.0 || !total.is_finite() {
+        return rng.random_range(0..weights.len());
+    }
+    let mut target = rng.random::<f32>() * total;
+    for (i, &weight) in weights.iter().enumerate() {
+        if target < weight {
+            return i;
+        }
+        target -= weight;
+    }
+    // Rounding can leave the target just past the last non-zero weight
+    weights.iter().rposition(|&w| w > 0.0).unwrap_or(0)
 }
 
 /// Evaluates a genotype's fitness based on rule complexity and material variety.
 fn evaluate_genotype(genotype: &PlantGenotype) -> f32 {
+    fitness_breakdown(genotype)
+        .iter()
+        .map(|(_, score)| score)
+        .sum()
+}
+
+/// The terms of a genotype's fitness, as (description, score) pairs.
+pub fn fitness_breakdown(genotype: &PlantGenotype) -> Vec<(String, f32)> {
     let rule_count = genotype
         .source_code
         .lines()
         .filter(|l| l.contains("->"))
         .count();
     let material_count = genotype.materials.len();
-    (rule_count as f32 * 10.0) + (material_count as f32 * 5.0)
+    vec![
+        (
+            format!("{} rules × 10", rule_count),
+            rule_count as f32 * 10.0,
+        ),
+        (
+            format!("{} materials × 5", material_count),
+            material_count as f32 * 5.0,
+        ),
+    ]
+}
+
+/// Renders the per-operator mutation toggles and strengths.
+fn mutation_settings_ui(ui: &mut egui::Ui, settings: &mut MutationSettings) {
+    egui::CollapsingHeader::new("Mutation Operators")
+        .id_salt("nursery_mutation_operators")
+        .show(ui, |ui| {
+            ui.checkbox(&mut settings.grammar, "Grammar parameters")
+                .on_hover_text("Rule probabilities and #define constants");
+            ui.add_enabled_ui(settings.grammar, |ui| {
+                ui.add(
+                    egui::Slider::new(&mut settings.rule_probability_strength, 0.0..=1.0)
+                        .text("Probability strength"),
+                );
+                ui.add(
+                    egui::Slider::new(&mut settings.constant_strength, 0.0..=1.0)
+                        .text("Constant strength"),
+                );
+                ui.add(
+                    egui::Slider::new(&mut settings.gaussian_jitter_scale, 0.0..=1.0)
+                        .text("Jitter scale"),
+                );
+            });
+
+            ui.checkbox(&mut settings.structure, "Grammar structure")
+                .on_hover_text("Insert, delete, and swap symbols in rule successors");
+            ui.add_enabled_ui(settings.structure, |ui| {
+                ui.add(
+                    egui::Slider::new(&mut settings.successor_rate, 0.0..=1.0)
+                        .text("Successor rate"),
+                );
+                ui.add(egui::Slider::new(&mut settings.insert_rate, 0.0..=1.0).text("Insert"));
+                ui.add(egui::Slider::new(&mut settings.delete_rate, 0.0..=1.0).text("Delete"));
+                ui.add(egui::Slider::new(&mut settings.swap_rate, 0.0..=1.0).text("Swap"));
+                ui.add(
+                    egui::Slider::new(&mut settings.bytecode_rate, 0.0..=1.0)
+                        .text("Expression rate"),
+                );
+                ui.add(egui::Slider::new(&mut settings.op_rate, 0.0..=1.0).text("Operator"));
+                ui.add(
+                    egui::Slider::new(&mut settings.push_perturbation, 0.0..=1.0)
+                        .text("Constant perturbation"),
+                );
+            });
+
+            ui.checkbox(&mut settings.materials, "Materials")
+                .on_hover_text("Base color, roughness, and metallic");
+            ui.checkbox(&mut settings.parameters, "Turtle parameters")
+                .on_hover_text("Angle, step, width, elasticity, and tropism");
+            ui.checkbox(&mut settings.seed, "Seed")
+                .on_hover_text("Redraw the seed for stochastic rules");
+
+            if ui.small_button("Reset").clicked() {
+                *settings = MutationSettings::default();
+            }
+        });
+}
+
+/// Renders a lock checkbox for every rule key in the population.
+fn locked_rules_ui(ui: &mut egui::Ui, nursery: &mut NurseryState) {
+    egui::CollapsingHeader::new("Locked Rules")
+        .id_salt("nursery_locked_rules")
+        .show(ui, |ui| {
+            let mut keys = nursery.locked.clone();
+            for phenotype in &nursery.population {
+                let genotype = &phenotype.genotype;
+                for source in [&genotype.source_code, &genotype.finalization_code] {
+                    for key in rule_keys(source) {
+                        if !keys.contains(&key) {
+                            keys.push(key);
+                        }
+                    }
+                }
+            }
+            if keys.is_empty() {
+                ui.label(egui::RichText::new("No rules").small().weak());
+                return;
+            }
+            ui.label(
+                egui::RichText::new("Locked rules are kept verbatim by mutation and crossover")
+                    .small()
+                    .weak(),
+            );
+            ui.horizontal_wrapped(|ui| {
+                for key in keys {
+                    let mut locked = nursery.locked.contains(&key);
+                    let label = if key == AXIOM_KEY {
+                        "Axiom".to_string()
+                    } else {
+                        format!("{} →", key)
+                    };
+                    if ui.checkbox(&mut locked, label).changed() {
+                        nursery.set_locked(&key, locked);
+                    }
+                }
+            });
+        });
+}
+
+/// Renders the right-click menu of the nursery cell at `index`.
+/// The rendered thumbnail of a population cell, if it is ready.
+fn cell_thumbnail(
+    ctx: &egui::Context,
+    thumbnails: &ThumbnailRenderer,
+    index: usize,
+) -> Option<egui::TextureHandle> {
+    let thumbnail = thumbnails.get(ThumbnailSlot::Nursery(index))?;
+    thumbnail_texture(
+        ctx,
+        egui::Id::new(("nursery_thumbnail", index)),
+        thumbnail.revision,
+        || Some(thumbnail.rgba.clone()),
+    )
+}
+
+fn cell_menu_ui(ui: &mut egui::Ui, nursery: &mut NurseryState, index: usize) {
+    if ui.button("🔍 Inspect").clicked() {
+        nursery.inspected = Some(index);
+        ui.close();
+    }
+    if ui.button("🔎 View alone").clicked() {
+        nursery.solo = Some(index);
+        ui.close();
+    }
+    ui.menu_button("Inject preset…", |ui| {
+        for preset in PRESETS {
+            if ui.button(preset.name).clicked() {
+                nursery.inject(index, PlantGenotype::from_preset(preset));
+                ui.close();
+            }
+        }
+    });
+    if ui.button("Inject from file…").clicked() {
+        nursery.inject_target = Some(index);
+        nursery.inject_error = None;
+        ui.close();
+    }
+    let others = nursery.selected.iter().filter(|&&i| i != index).count();
+    if ui
+        .add_enabled(
+            others > 0,
+            egui::Button::new(format!("Duplicate into selected ({})", others)),
+        )
+        .clicked()
+    {
+        nursery.duplicate_to_selected(index);
+        ui.close();
+    }
+    if ui.button("⭐ Add to herbarium").clicked() {
+        nursery.star_requested = Some(index);
+        ui.close();
+    }
+}
+
+/// Renders the "Inject from file" window for `nursery.inject_target`.
+fn inject_file_ui(ctx: &egui::Context, nursery: &mut NurseryState) {
+    let Some(index) = nursery.inject_target else {
+        return;
+    };
+    let mut open = true;
+    let mut submitted = false;
+
+    egui::Window::new(format!("Inject into #{}", index + 1))
+        .id(egui::Id::new("nursery_inject_file"))
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                ui.label("Genotype JSON or grammar file:");
+                let response = ui.text_edit_singleline(&mut nursery.inject_input);
+                submitted |= response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            }
+            #[cfg(target_arch = "wasm32")]
+            {
+                ui.label("Paste a share link, genotype JSON, or grammar:");
+                ui.add(
+                    egui::TextEdit::multiline(&mut nursery.inject_input)
+                        .code_editor()
+                        .desired_rows(6),
+                );
+            }
+            if let Some(err) = &nursery.inject_error {
+                ui.colored_label(egui::Color32::from_rgb(255, 100, 100), err);
+            }
+            submitted |= ui.button("Inject").clicked();
+        });
+
+    if submitted {
+        let template = nursery
+            .get_genotype(index)
+            .unwrap_or_else(|| PlantGenotype::new(String::new()));
+        match read_inject_input(&nursery.inject_input)
+            .and_then(|text| PlantGenotype::from_text(&text, &template))
+        {
+            Ok(genotype) => {
+                nursery.inject(index, genotype);
+                nursery.inject_target = None;
+                nursery.inject_error = None;
+            }
+            Err(err) => nursery.inject_error = Some(err),
+        }
+    }
+    if !open {
+        nursery.inject_target = None;
+    }
+}
+
+/// The text to inject: the named file's contents on native builds.
+#[cfg(not(target_arch = "wasm32"))]
+fn read_inject_input(input: &str) -> Result<String, String> {
+    let path = input.trim();
+    std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))
+}
+
+/// The text to inject: pasted directly, as the web build has no file system.
+#[cfg(target_arch = "wasm32")]
+fn read_inject_input(input: &str) -> Result<String, String> {
+    Ok(input.to_string())
+}
+
+/// Renders the inspector window for `nursery.inspected`: parameters, fitness
+/// terms, and side-by-side source diffs against the individual's parents.
+fn inspector_ui(
+    ctx: &egui::Context,
+    nursery: &mut NurseryState,
+    config: &LSystemConfig,
+    materials: &MaterialSettingsMap,
+    prop_config: &PropConfig,
+) {
+    let Some(index) = nursery.inspected else {
+        return;
+    };
+    let Some(genotype) = nursery.get_genotype(index) else {
+        nursery.inspected = None;
+        return;
+    };
+    let parents = nursery.parents.get(&index).cloned().unwrap_or_default();
+    let mut open = true;
+    let mut breed_with_editor = false;
+
+    egui::Window::new(format!("Individual #{}", index + 1))
+        .id(egui::Id::new("nursery_inspector"))
+        .open(&mut open)
+        .default_width(560.0)
+        .show(ctx, |ui| {
+            egui::Grid::new("nursery_inspector_params")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Iterations:");
+                    ui.label(genotype.iterations.to_string());
+                    ui.end_row();
+                    ui.label("Angle:");
+                    ui.label(format!("{:.2}°", genotype.angle));
+                    ui.end_row();
+                    ui.label("Step:");
+                    ui.label(format!("{:.3}", genotype.step));
+                    ui.end_row();
+                    ui.label("Width:");
+                    ui.label(format!("{:.3}", genotype.width));
+                    ui.end_row();
+                    ui.label("Elasticity:");
+                    ui.label(format!("{:.3}", genotype.elasticity));
+                    ui.end_row();
+                    ui.label("Seed:");
+                    ui.label(genotype.seed.to_string());
+                    ui.end_row();
+                });
+
+            if ui
+                .button("🧬 Breed with editor")
+                .on_hover_text(
+                    "Cross this individual with the current editor grammar, replacing all \
+                     unselected individuals with offspring",
+                )
+                .clicked()
+            {
+                breed_with_editor = true;
+            }
+
+            ui.separator();
+            ui.label(egui::RichText::new("Fitness").strong());
+            let breakdown = fitness_breakdown(&genotype);
+            let total: f32 = breakdown.iter().map(|(_, score)| score).sum();
+            for (term, score) in &breakdown {
+                ui.label(format!("{}: {:.1}", term, score));
+            }
+            ui.label(format!("Total: {:.1}", total));
+
+            ui.separator();
+            let source = join_source_code(&genotype.source_code, &genotype.finalization_code);
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("Source").strong());
+                if ui
+                    .small_button("📋 Copy")
+                    .on_hover_text("Copy growth and finalization code to the clipboard")
+                    .clicked()
+                {
+                    ui.ctx().copy_text(source.clone());
+                }
+            });
+            if parents.is_empty() {
+                ui.label(
+                    egui::RichText::new("No parent: taken unchanged from the editor")
+                        .small()
+                        .weak(),
+                );
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new(&source).monospace());
+                    });
+            }
+            for (n, parent) in parents.iter().enumerate() {
+                let parent_source =
+                    join_source_code(&parent.source_code, &parent.finalization_code);
+                let diff = diff_lines(&parent_source, &source);
+                let rows = side_by_side(&diff);
+                let changes = rows.iter().filter(|row| row.changed).count();
+                let title = if parents.len() > 1 {
+                    format!("Diff against parent {} ({} changed)", n + 1, changes)
+                } else {
+                    format!("Diff against parent ({} changed)", changes)
+                };
+                egui::CollapsingHeader::new(title)
+                    .id_salt(("nursery_inspector_diff", n))
+                    .default_open(n == 0)
+                    .show(ui, |ui| {
+                        egui::ScrollArea::both()
+                            .id_salt(("nursery_inspector_diff_scroll", n))
+                            .max_height(300.0)
+                            .show(ui, |ui| diff_grid(ui, &rows, n));
+                    });
+            }
+        });
+
+    if breed_with_editor {
+        let editor = PlantGenotype::from_editor(config, materials, prop_config);
+        nursery.breed_with_editor(index, editor);
+        nursery.needs_3d_rebuild = true;
+    }
+    if !open {
+        nursery.inspected = None;
+    }
+}
+
+/// Side-by-side diff table: the parent on the left, the individual on the
+/// right, with removed lines in red and added lines in green.
+fn diff_grid(ui: &mut egui::Ui, rows: &[DiffRow], salt: usize) {
+    const REMOVED: egui::Color32 = egui::Color32::from_rgb(255, 100, 100);
+    const ADDED: egui::Color32 = egui::Color32::from_rgb(100, 200, 100);
+    let cell = |ui: &mut egui::Ui, text: Option<&str>, changed: bool, color: egui::Color32| {
+        let text = egui::RichText::new(text.unwrap_or("")).monospace();
+        if changed {
+            ui.label(
+                text.color(color)
+                    .background_color(color.gamma_multiply(0.12)),
+            );
+        } else {
+            ui.label(text);
+        }
+    };
+    egui::Grid::new(("nursery_diff_grid", salt))
+        .num_columns(2)
+        .spacing([12.0, 0.0])
+        .show(ui, |ui| {
+            ui.label(egui::RichText::new("Parent").small().weak());
+            ui.label(egui::RichText::new("Individual").small().weak());
+            ui.end_row();
+            for row in rows {
+                cell(ui, row.old, row.changed, REMOVED);
+                cell(ui, row.new, row.changed, ADDED);
+                ui.end_row();
+            }
+        });
 }
 
 /// Renders the nursery UI panel.
@@ -402,6 +1226,7 @@ fn evaluate_genotype(genotype: &PlantGenotype) -> f32 {
 pub fn nursery_ui(
     ui: &mut egui::Ui,
     nursery: &mut NurseryState,
+    thumbnails: &ThumbnailRenderer,
     config: &LSystemConfig,
     materials: &MaterialSettingsMap,
     prop_config: &PropConfig,
@@ -409,35 +1234,71 @@ pub fn nursery_ui(
     let mut load_action = None;
 
     if nursery.mode == NurseryMode::Enabled {
-        ui.horizontal(|ui| {
-            ui.label(format!("Generation: {}", nursery.generation));
-            ui.separator();
+        let auto_running = nursery.auto_evolve.is_running();
+        ui.add_enabled_ui(!auto_running, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("Generation: {}", nursery.generation));
+                ui.separator();
+
+                if ui
+                    .button("Breed")
+                    .on_hover_text("Breed next generation from selected champions")
+                    .clicked()
+                {
+                    nursery.breed();
+                    nursery.needs_3d_rebuild = true;
+                }
 
-            if ui
-                .button("Breed")
-                .on_hover_text("Breed next generation from selected champions")
-                .clicked()
-            {
-                nursery.breed();
-                nursery.needs_3d_rebuild = true;
-            }
+                if ui
+                    .button("Mutate")
+                    .on_hover_text("Mutate all non-elite individuals")
+                    .clicked()
+                {
+                    nursery.mutate_all();
+                    nursery.needs_3d_rebuild = true;
+                }
 
-            if ui
-                .button("Mutate")
-                .on_hover_text("Mutate all non-elite individuals")
-                .clicked()
-            {
-                nursery.mutate_all();
-                nursery.needs_3d_rebuild = true;
-            }
+                if ui
+                    .button("Reset")
+                    .on_hover_text("Reset population from current editor")
+                    .clicked()
+                {
+                    nursery.initialize_from_editor(config, materials, prop_config);
+                    nursery.needs_3d_rebuild = true;
+                }
+            });
+        });
 
-            if ui
-                .button("Reset")
-                .on_hover_text("Reset population from current editor")
-                .clicked()
-            {
-                nursery.initialize_from_editor(config, materials, prop_config);
-                nursery.needs_3d_rebuild = true;
+        ui.horizontal(|ui| {
+            if auto_running {
+                let (done, total) = nursery.auto_evolve.progress();
+                ui.add(
+                    egui::ProgressBar::new(done as f32 / total.max(1) as f32)
+                        .desired_width(140.0)
+                        .text(format!("Generation {}/{}", done, total)),
+                );
+                if ui.button("Cancel").clicked() {
+                    nursery.auto_evolve.cancel();
+                    info!("Auto-evolve cancelled");
+                }
+                ui.ctx().request_repaint();
+            } else {
+                if ui
+                    .button("⏩ Run")
+                    .on_hover_text(
+                        "Breed several generations in the background with automatic fitness \
+                         evaluation, using the selection strategy below",
+                    )
+                    .clicked()
+                {
+                    let generations = nursery.auto_evolve.generations;
+                    nursery.start_auto_evolve(generations);
+                }
+                ui.add(
+                    egui::DragValue::new(&mut nursery.auto_evolve.generations)
+                        .range(1..=1000)
+                        .suffix(" generations"),
+                );
             }
         });
 
@@ -445,6 +1306,29 @@ pub fn nursery_ui(
             ui.label("Mutation Rate:");
             ui.add(egui::Slider::new(&mut nursery.mutation_rate, 0.01..=0.5));
         });
+        mutation_settings_ui(ui, &mut nursery.mutation);
+        locked_rules_ui(ui, nursery);
+
+        ui.horizontal(|ui| {
+            ui.label("Selection:");
+            egui::ComboBox::from_id_salt("nursery_selection")
+                .selected_text(nursery.selection.name())
+                .show_ui(ui, |ui| {
+                    for strategy in SelectionStrategy::ALL {
+                        ui.selectable_value(&mut nursery.selection, *strategy, strategy.name());
+                    }
+                })
+                .response
+                .on_hover_text(
+                    "How parents are chosen when breeding; selected champions are always kept",
+                );
+            if nursery.selection == SelectionStrategy::Tournament {
+                let max_size = nursery.population_size().max(2);
+                ui.add(
+                    egui::Slider::new(&mut nursery.tournament_size, 2..=max_size).prefix("k = "),
+                );
+            }
+        });
 
         ui.horizontal(|ui| {
             ui.label("Grid Spacing:");
@@ -467,7 +1351,52 @@ pub fn nursery_ui(
             }
         });
 
+        // Level of detail (the grid re-meshes when these change)
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut nursery.lod.enabled, "LOD").on_hover_text(
+                "Mesh grid plants with fewer ring vertices and skip tiny props, \
+                     keeping large grids interactive",
+            );
+            ui.add_enabled_ui(nursery.lod.enabled, |ui| {
+                ui.add(egui::Slider::new(&mut nursery.lod.mesh_resolution, 3..=16).text("Ring"))
+                    .on_hover_text("Vertices per branch ring, capped at the editor's resolution");
+                ui.add(
+                    egui::DragValue::new(&mut nursery.lod.min_prop_size)
+                        .range(0.0..=100.0)
+                        .speed(0.1)
+                        .prefix("Props ≥ "),
+                )
+                .on_hover_text("Props smaller than this are not shown in the grid");
+            });
+        });
+
         ui.separator();
+        ui.label(
+            egui::RichText::new(
+                "Click to select · double-click to view alone · 📥 to load · right-click for more",
+            )
+            .small()
+            .weak(),
+        );
+
+        if let Some(solo) = nursery.solo {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(format!("🔎 Viewing #{}", solo + 1)).strong());
+                if ui
+                    .button("📥 Adopt into editor")
+                    .on_hover_text("Load this individual into the editor and close the nursery")
+                    .clicked()
+                    && let Some(genotype) = nursery.get_genotype(solo)
+                {
+                    load_action = Some(genotype);
+                    nursery.mode = NurseryMode::Disabled;
+                    nursery.solo = None;
+                }
+                if ui.button("↩ Back to nursery").clicked() {
+                    nursery.solo = None;
+                }
+            });
+        }
 
         // Population Grid
         let grid_size = nursery.grid_size;
@@ -479,7 +1408,10 @@ pub fn nursery_ui(
             .collect();
 
         if !pop_data.is_empty() {
-            let cell_size = 40.0;
+            // Cells share the panel width, within a size that keeps thumbnails legible
+            let cell_size = ((ui.available_width() - 4.0 * (grid_size as f32 - 1.0))
+                / grid_size as f32)
+                .clamp(40.0, 80.0);
 
             egui::Grid::new("nursery_grid")
                 .num_columns(grid_size)
@@ -542,8 +1474,28 @@ pub fn nursery_ui(
                             if let Some(err_msg) = error.filter(|_| response.hovered()) {
                                 response.show_tooltip_text(err_msg);
                             }
+                        } else if let Some(texture) = cell_thumbnail(ui.ctx(), thumbnails, *i) {
+                            // Rendered preview, with a trophy badge for champions
+                            ui.painter().image(
+                                texture.id(),
+                                rect.shrink(2.0),
+                                egui::Rect::from_min_max(
+                                    egui::pos2(0.0, 0.0),
+                                    egui::pos2(1.0, 1.0),
+                                ),
+                                egui::Color32::WHITE,
+                            );
+                            if is_selected {
+                                ui.painter().text(
+                                    rect.left_top() + egui::vec2(3.0, 3.0),
+                                    egui::Align2::LEFT_TOP,
+                                    "🏆",
+                                    egui::FontId::proportional(12.0),
+                                    egui::Color32::WHITE,
+                                );
+                            }
                         } else {
-                            // Normal display: icon and fitness
+                            // Placeholder icon until the thumbnail is rendered
                             ui.painter().text(
                                 center,
                                 egui::Align2::CENTER_CENTER,
@@ -601,12 +1553,18 @@ pub fn nursery_ui(
                                 if let Some(genotype) = nursery.get_genotype(*i) {
                                     load_action = Some(genotype);
                                     nursery.mode = NurseryMode::Disabled;
+                                    nursery.solo = None;
                                 }
                             } else {
                                 // Toggle selection (visuals updated by sync_nursery_selection_visuals)
                                 nursery.toggle_selection(*i);
                             }
                         }
+                        // Both clicks of a double-click toggle, leaving the selection as it was
+                        if response.double_clicked() && !load_hovered {
+                            nursery.solo = Some(*i);
+                        }
+                        response.context_menu(|ui| cell_menu_ui(ui, nursery, *i));
 
                         // End row after grid_size items
                         if (i + 1) % grid_size == 0 {
@@ -617,14 +1575,41 @@ pub fn nursery_ui(
 
             // Show selection count
             let selected_count = nursery.selected.len();
-            if selected_count > 0 {
-                ui.label(
-                    egui::RichText::new(format!("Champions: {} selected", selected_count))
-                        .small()
-                        .color(egui::Color32::from_rgb(100, 200, 100)),
-                );
-            }
+            ui.horizontal(|ui| {
+                if selected_count > 0 {
+                    ui.label(
+                        egui::RichText::new(format!("Champions: {} selected", selected_count))
+                            .small()
+                            .color(egui::Color32::from_rgb(100, 200, 100)),
+                    );
+                    #[cfg(feature = "export")]
+                    if ui
+                        .small_button("💾 Export selected")
+                        .on_hover_text(
+                            "Export each champion with its own parameters, materials, and seed, \
+                             using the format and options of the Export panel",
+                        )
+                        .clicked()
+                    {
+                        nursery.export_requested = true;
+                    }
+                }
+                #[cfg(feature = "export")]
+                if ui
+                    .small_button("🎬 Export scene")
+                    .on_hover_text(
+                        "Export the whole population as one GLB, each plant at its grid \
+                         position with its own materials",
+                    )
+                    .clicked()
+                {
+                    nursery.scene_export_requested = true;
+                }
+            });
         }
+
+        inspector_ui(ui.ctx(), nursery, config, materials, prop_config);
+        inject_file_ui(ui.ctx(), nursery);
     } else {
         ui.horizontal(|ui| {
             let button = egui::Button::new(egui::RichText::new("🌱 Open Nursery").size(16.0))
@@ -0,0 +1,164 @@
+//! Palette controls in the "Material Palette" section: bundled and saved
+//! palettes, saving the current slots, and JSON import/export.
+
+use crate::core::config::MaterialSettingsMap;
+use crate::core::files::save_file;
+use crate::core::palettes::{MaterialPalette, PaletteLibrary, bundled_palettes};
+use bevy::prelude::*;
+use bevy_egui::egui;
+use std::path::Path;
+
+/// Renders the palette controls. Exports are written into `export_dir`
+/// (downloaded on the web).
+pub fn palettes_ui(
+    ui: &mut egui::Ui,
+    library: &mut PaletteLibrary,
+    materials: &mut MaterialSettingsMap,
+    export_dir: &Path,
+) {
+    let mut applied = None;
+    let mut export = None;
+    let mut saved_changed = false;
+
+    ui.label(egui::RichText::new("Bundled").small().weak());
+    ui.horizontal_wrapped(|ui| {
+        for palette in bundled_palettes() {
+            if ui
+                .button(&palette.name)
+                .on_hover_text("Apply to the material slots")
+                .clicked()
+            {
+                applied = Some(palette);
+            }
+        }
+    });
+
+    if !library.palettes.is_empty() {
+        ui.label(egui::RichText::new("Saved").small().weak());
+    }
+    let mut remove = None;
+    for (index, palette) in library.palettes.iter().enumerate() {
+        ui.horizontal(|ui| {
+            if ui
+                .button(&palette.name)
+                .on_hover_text("Apply to the material slots")
+                .clicked()
+            {
+                applied = Some(palette.clone());
+            }
+            if ui
+                .small_button("📤")
+                .on_hover_text("Export as JSON")
+                .clicked()
+            {
+                export = Some(palette.clone());
+            }
+            if ui.small_button("🗑").on_hover_text("Delete").clicked() {
+                remove = Some(index);
+            }
+        });
+    }
+    if let Some(index) = remove {
+        library.palettes.remove(index);
+        saved_changed = true;
+    }
+
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(&mut library.new_name)
+                .hint_text("Palette name")
+                .desired_width(140.0),
+        );
+        let name = library.new_name.trim().to_string();
+        if ui
+            .add_enabled(!name.is_empty(), egui::Button::new("💾 Save"))
+            .on_hover_text("Save the current slots; a palette of the same name is replaced")
+            .clicked()
+        {
+            library.insert(MaterialPalette::from_settings(&name, materials));
+            library.new_name.clear();
+            saved_changed = true;
+        }
+        if ui
+            .button("📤 Export")
+            .on_hover_text("Write the current slots to a JSON file")
+            .clicked()
+        {
+            let name = if name.is_empty() { "palette" } else { &name };
+            export = Some(MaterialPalette::from_settings(name, materials));
+        }
+    });
+
+    ui.horizontal(|ui| {
+        #[cfg(not(target_arch = "wasm32"))]
+        let hint = "path/to/file.palette.json";
+        #[cfg(target_arch = "wasm32")]
+        let hint = "Paste palette JSON";
+        ui.add(
+            egui::TextEdit::singleline(&mut library.import_input)
+                .hint_text(hint)
+                .desired_width(180.0),
+        );
+        if ui
+            .add_enabled(
+                !library.import_input.trim().is_empty(),
+                egui::Button::new("📥 Import"),
+            )
+            .on_hover_text("Apply the palette and add it to the saved palettes")
+            .clicked()
+        {
+            match read_import_input(&library.import_input)
+                .and_then(|json| MaterialPalette::from_json(&json))
+            {
+                Ok(palette) => {
+                    library.status = Some(Ok(format!("Imported {}", palette.name)));
+                    library.import_input.clear();
+                    library.insert(palette.clone());
+                    applied = Some(palette);
+                    saved_changed = true;
+                }
+                Err(e) => library.status = Some(Err(e)),
+            }
+        }
+    });
+
+    if let Some(palette) = export {
+        let filename = palette.filename();
+        library.status = Some(
+            palette
+                .to_json()
+                .and_then(|json| save_file(export_dir, &filename, &json))
+                .map(|()| format!("Saved {}", filename)),
+        );
+    }
+    match &library.status {
+        Some(Ok(message)) => {
+            ui.label(egui::RichText::new(message).small().weak());
+        }
+        Some(Err(e)) => {
+            ui.colored_label(egui::Color32::from_rgb(255, 100, 100), e);
+        }
+        None => {}
+    }
+
+    if saved_changed && let Err(e) = library.save() {
+        warn!("Failed to save palettes: {}", e);
+    }
+    if let Some(palette) = applied {
+        palette.apply(materials);
+    }
+}
+
+/// The palette JSON to import: the named file's contents on native builds.
+#[cfg(not(target_arch = "wasm32"))]
+fn read_import_input(input: &str) -> Result<String, String> {
+    let path = input.trim();
+    std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))
+}
+
+/// The palette JSON to import: pasted directly, as the web build has no file
+/// system.
+#[cfg(target_arch = "wasm32")]
+fn read_import_input(input: &str) -> Result<String, String> {
+    Ok(input.to_string())
+}
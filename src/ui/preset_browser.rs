@@ -0,0 +1,200 @@
+//! Gallery window for browsing presets by category, with search and
+//! offscreen-rendered thumbnails.
+
+use crate::core::genotype::PlantGenotype;
+use crate::core::herbarium::{Herbarium, THUMBNAIL_SIZE};
+use crate::core::presets::{LSystemPreset, PRESETS, PresetCategory};
+use crate::ui::herbarium::{thumbnail_texture, thumbnail_ui};
+use crate::visuals::thumbnail::{ThumbnailRenderer, ThumbnailSlot};
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+/// Width of one gallery card.
+const CARD_WIDTH: f32 = THUMBNAIL_SIZE as f32 + 16.0;
+
+/// Preset browser window state.
+#[derive(Resource, Default)]
+pub struct PresetBrowser {
+    pub open: bool,
+    /// Case-insensitive text the name must contain.
+    pub search: String,
+    /// Section shown; `None` shows every section.
+    pub category: Option<PresetCategory>,
+}
+
+impl PresetBrowser {
+    fn shows(&self, category: PresetCategory, name: &str) -> bool {
+        self.category.is_none_or(|shown| shown == category)
+            && name
+                .to_lowercase()
+                .contains(&self.search.trim().to_lowercase())
+    }
+}
+
+/// What the user picked in the browser.
+pub enum PresetPick {
+    Preset(&'static LSystemPreset),
+    /// A starred plant from the "User" section.
+    Plant(PlantGenotype),
+}
+
+/// Renders the preset browser while `browser.open` is set. Returns the card
+/// clicked this frame; the browser closes on a pick.
+pub fn preset_browser_ui(
+    ctx: &egui::Context,
+    browser: &mut PresetBrowser,
+    thumbnails: &ThumbnailRenderer,
+    herbarium: &Herbarium,
+) -> Option<PresetPick> {
+    let mut pick = None;
+    let mut open = browser.open;
+
+    egui::Window::new("📚 Presets")
+        .open(&mut open)
+        .default_width(5.0 * CARD_WIDTH)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut browser.search)
+                        .hint_text("🔍 Search")
+                        .desired_width(160.0),
+                );
+                ui.selectable_value(&mut browser.category, None, "All");
+                for category in PresetCategory::ALL {
+                    ui.selectable_value(&mut browser.category, Some(category), category.name());
+                }
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .id_salt("preset_gallery")
+                .max_height(520.0)
+                .show(ui, |ui| {
+                    for category in PresetCategory::ALL {
+                        if category == PresetCategory::User {
+                            let plants: Vec<_> = herbarium
+                                .entries
+                                .iter()
+                                .filter(|entry| browser.shows(category, &entry.name))
+                                .collect();
+                            if plants.is_empty() {
+                                continue;
+                            }
+                            ui.heading(category.name());
+                            ui.horizontal_wrapped(|ui| {
+                                for entry in plants {
+                                    let clicked = card_ui(ui, &entry.name, |ui| {
+                                        thumbnail_ui(ui, entry.id, entry.thumbnail.as_deref())
+                                    });
+                                    if clicked {
+                                        pick = Some(PresetPick::Plant(entry.genotype.clone()));
+                                    }
+                                }
+                            });
+                            continue;
+                        }
+
+                        let presets: Vec<(usize, &'static LSystemPreset)> = PRESETS
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, preset)| {
+                                preset.category == category && browser.shows(category, preset.name)
+                            })
+                            .collect();
+                        if presets.is_empty() {
+                            continue;
+                        }
+                        ui.heading(category.name());
+                        ui.horizontal_wrapped(|ui| {
+                            for (index, preset) in presets {
+                                let clicked = card_ui(ui, preset.name, |ui| {
+                                    preset_thumbnail_ui(ui, thumbnails, index)
+                                });
+                                if clicked {
+                                    pick = Some(PresetPick::Preset(preset));
+                                }
+                            }
+                        });
+                    }
+                });
+        });
+
+    browser.open = open && pick.is_none();
+    pick
+}
+
+/// A clickable card with a thumbnail and a name. Returns `true` when clicked.
+fn card_ui(ui: &mut egui::Ui, name: &str, thumbnail: impl FnOnce(&mut egui::Ui)) -> bool {
+    let response = ui
+        .group(|ui| {
+            ui.set_width(CARD_WIDTH);
+            ui.vertical(|ui| {
+                thumbnail(ui);
+                ui.add(egui::Label::new(egui::RichText::new(name).small()).wrap());
+            });
+        })
+        .response;
+    ui.interact(
+        response.rect,
+        response.id.with("card"),
+        egui::Sense::click(),
+    )
+    .on_hover_text("Load into the editor")
+    .clicked()
+}
+
+/// Draws a preset's rendered thumbnail, or a placeholder until it is ready.
+fn preset_thumbnail_ui(ui: &mut egui::Ui, thumbnails: &ThumbnailRenderer, index: usize) {
+    let size = egui::vec2(THUMBNAIL_SIZE as f32, THUMBNAIL_SIZE as f32);
+    let texture = thumbnails
+        .get(ThumbnailSlot::Preset(index))
+        .and_then(|thumbnail| {
+            thumbnail_texture(
+                ui.ctx(),
+                egui::Id::new(("preset_thumbnail", index)),
+                thumbnail.revision,
+                || Some(thumbnail.rgba.clone()),
+            )
+        });
+    match texture {
+        Some(texture) => {
+            ui.image(egui::load::SizedTexture::new(texture.id(), size));
+        }
+        None => {
+            ui.allocate_ui(size, |ui| {
+                ui.centered_and_justified(|ui| {
+                    ui.spinner();
+                });
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_and_category_filter() {
+        let mut browser = PresetBrowser::default();
+        assert!(browser.shows(PresetCategory::Trees, "Monopodial Tree"));
+        browser.search = " monopodial ".into();
+        assert!(browser.shows(PresetCategory::Abop, "Monopodial Tree (ABOP Fig 2.6)"));
+        assert!(!browser.shows(PresetCategory::Abop, "Sympodial Tree"));
+        browser.category = Some(PresetCategory::Curves);
+        assert!(!browser.shows(PresetCategory::Abop, "Monopodial Tree (ABOP Fig 2.6)"));
+    }
+
+    #[test]
+    fn test_every_section_has_presets() {
+        for category in PresetCategory::ALL {
+            if category != PresetCategory::User {
+                assert!(
+                    PRESETS.iter().any(|preset| preset.category == category),
+                    "{} is empty",
+                    category.name()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,501 @@
+//! "Rule Graph" window: an experimental node editor for the grammar.
+//!
+//! Symbols and productions are nodes. Edges run from a symbol to the rules
+//! rewriting it, and from a rule to the symbols its successor produces. Rule
+//! nodes edit their predecessor, condition, and successor in place; dragging
+//! from a rule's port onto a symbol appends that symbol to the successor.
+//! Every edit rewrites the rule's line in the source, so the text stays the
+//! single source of truth.
+
+use crate::logic::describe::{module_arities, parse_rule};
+use bevy::prelude::*;
+use bevy_egui::egui;
+use std::collections::HashMap;
+
+const SYMBOL_SIZE: egui::Vec2 = egui::vec2(56.0, 28.0);
+const RULE_SIZE: egui::Vec2 = egui::vec2(250.0, 76.0);
+const PORT_RADIUS: f32 = 6.0;
+
+/// A node of the graph, keyed so its position survives edits.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NodeKey {
+    Axiom,
+    Symbol(String),
+    /// A production, by its line in the source.
+    Rule(usize),
+}
+
+/// A production as a node.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleNode {
+    /// Line of the rule in the source.
+    pub line: usize,
+    pub label: Option<String>,
+    /// Full predecessor, with context.
+    pub predecessor: String,
+    /// Symbol the rule rewrites.
+    pub symbol: String,
+    /// Condition, or the probability of stochastic rules; empty otherwise.
+    pub condition: String,
+    pub successor: String,
+    /// Symbols the successor produces.
+    pub produces: Vec<String>,
+}
+
+/// The grammar as nodes and edges.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GraphModel {
+    /// Symbols of the axiom.
+    pub axiom: Vec<String>,
+    /// Every named symbol, in order of first appearance.
+    pub symbols: Vec<String>,
+    pub rules: Vec<RuleNode>,
+}
+
+/// Symbols worth a node: letters, not turtle commands or brackets.
+fn is_named(symbol: &str) -> bool {
+    symbol.chars().next().is_some_and(char::is_alphabetic)
+}
+
+fn named_symbols(text: &str) -> Vec<String> {
+    let mut symbols: Vec<String> = Vec::new();
+    for (symbol, _) in module_arities(text) {
+        if is_named(&symbol) && !symbols.contains(&symbol) {
+            symbols.push(symbol);
+        }
+    }
+    symbols
+}
+
+/// Builds the graph of the rules and axiom in `source`.
+pub fn graph_model(source: &str) -> GraphModel {
+    fn add_symbols(model: &mut GraphModel, symbols: &[String]) {
+        for symbol in symbols {
+            if !model.symbols.contains(symbol) {
+                model.symbols.push(symbol.clone());
+            }
+        }
+    }
+
+    let mut model = GraphModel::default();
+    for (line, text) in source.lines().enumerate() {
+        let text = text.trim();
+        if let Some(axiom) = text.strip_prefix("omega:") {
+            model.axiom = named_symbols(axiom);
+            let axiom = model.axiom.clone();
+            add_symbols(&mut model, &axiom);
+            continue;
+        }
+        let Some(rule) = parse_rule(text) else {
+            continue;
+        };
+        let strict = rule.predecessor.rsplit('<').next().unwrap_or_default();
+        let strict = strict.split('>').next().unwrap_or_default();
+        let symbol = named_symbols(strict).into_iter().next().unwrap_or_default();
+        let produces = named_symbols(&rule.successor);
+        add_symbols(&mut model, std::slice::from_ref(&symbol));
+        add_symbols(&mut model, &produces);
+        let condition = match (rule.condition, rule.probability) {
+            (Some(condition), _) => condition,
+            (None, Some(p)) if p < 1.0 => p.to_string(),
+            _ => String::new(),
+        };
+        model.rules.push(RuleNode {
+            line,
+            label: rule.label,
+            predecessor: rule.predecessor,
+            symbol,
+            condition,
+            successor: rule.successor,
+            produces,
+        });
+    }
+    model.symbols.retain(|symbol| !symbol.is_empty());
+    model
+}
+
+/// Source text of a rule.
+pub fn rule_text(
+    label: Option<&str>,
+    predecessor: &str,
+    condition: &str,
+    successor: &str,
+) -> String {
+    let label = label
+        .map(|label| format!("{}: ", label))
+        .unwrap_or_default();
+    if condition.trim().is_empty() {
+        format!("{}{} -> {}", label, predecessor.trim(), successor.trim())
+    } else {
+        format!(
+            "{}{} : {} -> {}",
+            label,
+            predecessor.trim(),
+            condition.trim(),
+            successor.trim()
+        )
+    }
+}
+
+/// Replaces line `line` of `source`, or removes it with `None`.
+pub fn replace_line(source: &str, line: usize, text: Option<&str>) -> String {
+    source
+        .lines()
+        .enumerate()
+        .filter_map(|(index, old)| {
+            if index == line {
+                text.map(str::to_string)
+            } else {
+                Some(old.to_string())
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Appends a rule after the last rule of `source`, or at the end.
+pub fn add_rule(source: &str, rule: &str) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+    let after = lines
+        .iter()
+        .rposition(|line| parse_rule(line).is_some() || line.trim().starts_with("omega:"))
+        .map_or(lines.len(), |index| index + 1);
+    let mut out: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+    out.insert(after, rule.to_string());
+    out.join("\n")
+}
+
+/// Rule graph window state.
+#[derive(Resource, Default)]
+pub struct RuleGraph {
+    pub open: bool,
+    /// Node positions on the canvas; nodes without one are laid out in columns.
+    positions: HashMap<NodeKey, egui::Vec2>,
+    /// Rule whose port an edge is being dragged from.
+    connecting: Option<usize>,
+    /// Symbol of the rule being added.
+    new_symbol: String,
+}
+
+impl RuleGraph {
+    fn position(&mut self, key: &NodeKey, fallback: egui::Vec2) -> egui::Vec2 {
+        *self.positions.entry(key.clone()).or_insert(fallback)
+    }
+}
+
+/// Shows the rule graph window. Returns `true` when it edited `source`.
+pub fn rule_graph_ui(ctx: &egui::Context, graph: &mut RuleGraph, source: &mut String) -> bool {
+    let mut open = graph.open;
+    let mut changed = false;
+    egui::Window::new("🕸 Rule Graph")
+        .open(&mut open)
+        .default_width(720.0)
+        .default_height(480.0)
+        .show(ctx, |ui| {
+            changed = graph_canvas(ui, graph, source);
+        });
+    graph.open = open;
+    changed
+}
+
+fn graph_canvas(ui: &mut egui::Ui, graph: &mut RuleGraph, source: &mut String) -> bool {
+    let model = graph_model(source);
+    let mut edit: Option<(usize, Option<String>)> = None;
+    let mut new_rule: Option<String> = None;
+
+    ui.horizontal(|ui| {
+        ui.label("New rule for");
+        ui.add(
+            egui::TextEdit::singleline(&mut graph.new_symbol)
+                .hint_text("symbol")
+                .desired_width(60.0),
+        );
+        let symbol = graph.new_symbol.trim().to_string();
+        if ui
+            .add_enabled(!symbol.is_empty(), egui::Button::new("➕ Add"))
+            .clicked()
+        {
+            new_rule = Some(rule_text(None, &symbol, "", &symbol));
+            graph.new_symbol.clear();
+        }
+        if ui
+            .button("Re-layout")
+            .on_hover_text("Forget dragged node positions")
+            .clicked()
+        {
+            graph.positions.clear();
+        }
+        ui.weak("Drag nodes by their title; drag from a rule's ● onto a symbol to append it");
+    });
+    ui.separator();
+
+    egui::ScrollArea::both()
+        .scroll_source(egui::containers::scroll_area::ScrollSource {
+            drag: false,
+            ..Default::default()
+        })
+        .show(ui, |ui| {
+            let height = (model.symbols.len() as f32 * 44.0)
+                .max(model.rules.len() as f32 * (RULE_SIZE.y + 16.0))
+                + 60.0;
+            let (canvas, _) = ui.allocate_exact_size(
+                egui::vec2(ui.available_width().max(640.0), height.max(300.0)),
+                egui::Sense::hover(),
+            );
+            let origin = canvas.min.to_vec2();
+
+            // Default layout: axiom, symbols, then rules in columns
+            let axiom_at = graph.position(&NodeKey::Axiom, egui::vec2(12.0, 12.0));
+            let mut symbol_rects = HashMap::new();
+            for (i, symbol) in model.symbols.iter().enumerate() {
+                let at = graph.position(
+                    &NodeKey::Symbol(symbol.clone()),
+                    egui::vec2(120.0, 12.0 + i as f32 * 44.0),
+                );
+                symbol_rects.insert(
+                    symbol.clone(),
+                    egui::Rect::from_min_size((origin + at).to_pos2(), SYMBOL_SIZE),
+                );
+            }
+            let rule_rects: Vec<egui::Rect> = model
+                .rules
+                .iter()
+                .enumerate()
+                .map(|(i, rule)| {
+                    let at = graph.position(
+                        &NodeKey::Rule(rule.line),
+                        egui::vec2(260.0, 12.0 + i as f32 * (RULE_SIZE.y + 16.0)),
+                    );
+                    egui::Rect::from_min_size((origin + at).to_pos2(), RULE_SIZE)
+                })
+                .collect();
+            let axiom_rect = egui::Rect::from_min_size((origin + axiom_at).to_pos2(), SYMBOL_SIZE);
+
+            // Edges under the nodes
+            let painter = ui.painter_at(canvas);
+            let stroke = ui.visuals().widgets.noninteractive.fg_stroke;
+            let produced = egui::Stroke::new(1.5, ui.visuals().selection.bg_fill);
+            for symbol in &model.axiom {
+                if let Some(to) = symbol_rects.get(symbol) {
+                    edge(
+                        &painter,
+                        axiom_rect.right_center(),
+                        to.left_center(),
+                        stroke,
+                    );
+                }
+            }
+            for (rule, rect) in model.rules.iter().zip(&rule_rects) {
+                if let Some(from) = symbol_rects.get(&rule.symbol) {
+                    edge(&painter, from.right_center(), rect.left_center(), stroke);
+                }
+                for symbol in &rule.produces {
+                    if let Some(to) = symbol_rects.get(symbol) {
+                        edge(&painter, rect.right_center(), to.center_top(), produced);
+                    }
+                }
+            }
+
+            // Symbol and axiom nodes
+            let node_fill = ui.visuals().widgets.inactive.bg_fill;
+            let text_color = ui.visuals().text_color();
+            for (key, label, rect) in std::iter::once((NodeKey::Axiom, "ω".to_string(), axiom_rect))
+                .chain(model.symbols.iter().map(|symbol| {
+                    (
+                        NodeKey::Symbol(symbol.clone()),
+                        symbol.clone(),
+                        symbol_rects[symbol],
+                    )
+                }))
+            {
+                let response = ui.interact(
+                    rect,
+                    egui::Id::new(("rule_graph", &key)),
+                    egui::Sense::drag(),
+                );
+                drag_node(graph, &key, &response);
+                painter.rect_filled(rect, 12.0, node_fill);
+                painter.rect_stroke(rect, 12.0, stroke, egui::StrokeKind::Inside);
+                painter.text(
+                    rect.center(),
+                    egui::Align2::CENTER_CENTER,
+                    label,
+                    egui::FontId::monospace(14.0),
+                    text_color,
+                );
+            }
+
+            // Rule nodes
+            for (rule, rect) in model.rules.iter().zip(&rule_rects) {
+                let key = NodeKey::Rule(rule.line);
+                let title = egui::Rect::from_min_size(rect.min, egui::vec2(rect.width(), 18.0));
+                let response = ui.interact(
+                    title,
+                    egui::Id::new(("rule_graph", &key)),
+                    egui::Sense::drag(),
+                );
+                drag_node(graph, &key, &response);
+                painter.rect_filled(*rect, 4.0, ui.visuals().extreme_bg_color);
+                painter.rect_stroke(*rect, 4.0, stroke, egui::StrokeKind::Inside);
+
+                let mut predecessor = rule.predecessor.clone();
+                let mut condition = rule.condition.clone();
+                let mut successor = rule.successor.clone();
+                let mut remove = false;
+                ui.scope_builder(egui::UiBuilder::new().max_rect(rect.shrink(4.0)), |ui| {
+                    ui.horizontal(|ui| {
+                        ui.weak(rule.label.as_deref().unwrap_or("rule"));
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            remove = ui.small_button("✖").on_hover_text("Delete rule").clicked();
+                        });
+                    });
+                    let mut edited = false;
+                    ui.horizontal(|ui| {
+                        edited |= ui
+                            .add(egui::TextEdit::singleline(&mut predecessor).desired_width(70.0))
+                            .changed();
+                        ui.label(":");
+                        edited |= ui
+                            .add(
+                                egui::TextEdit::singleline(&mut condition)
+                                    .hint_text("condition")
+                                    .desired_width(120.0),
+                            )
+                            .changed();
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("→");
+                        edited |= ui
+                            .add(egui::TextEdit::singleline(&mut successor).desired_width(210.0))
+                            .changed();
+                    });
+                    if edited {
+                        edit = Some((
+                            rule.line,
+                            Some(rule_text(
+                                rule.label.as_deref(),
+                                &predecessor,
+                                &condition,
+                                &successor,
+                            )),
+                        ));
+                    }
+                });
+                if remove {
+                    edit = Some((rule.line, None));
+                    graph.positions.remove(&key);
+                }
+
+                // Output port: drag onto a symbol to append it to the successor
+                let port = rect.right_center();
+                let port_response = ui.interact(
+                    egui::Rect::from_center_size(port, egui::Vec2::splat(PORT_RADIUS * 2.0)),
+                    egui::Id::new(("rule_graph_port", rule.line)),
+                    egui::Sense::drag(),
+                );
+                painter.circle_filled(port, PORT_RADIUS, produced.color);
+                if port_response.drag_started() {
+                    graph.connecting = Some(rule.line);
+                }
+                if graph.connecting == Some(rule.line)
+                    && let Some(pointer) = ui.ctx().pointer_interact_pos()
+                {
+                    if port_response.dragged() {
+                        edge(&painter, port, pointer, produced);
+                    }
+                    if port_response.drag_stopped() {
+                        graph.connecting = None;
+                        if let Some((symbol, _)) =
+                            symbol_rects.iter().find(|(_, rect)| rect.contains(pointer))
+                        {
+                            let successor = format!("{} {}", rule.successor, symbol);
+                            edit = Some((
+                                rule.line,
+                                Some(rule_text(
+                                    rule.label.as_deref(),
+                                    &rule.predecessor,
+                                    &rule.condition,
+                                    &successor,
+                                )),
+                            ));
+                        }
+                    }
+                }
+            }
+        });
+
+    let mut changed = false;
+    if let Some((line, text)) = edit {
+        *source = replace_line(source, line, text.as_deref());
+        changed = true;
+    }
+    if let Some(rule) = new_rule {
+        *source = add_rule(source, &rule);
+        changed = true;
+    }
+    changed
+}
+
+fn drag_node(graph: &mut RuleGraph, key: &NodeKey, response: &egui::Response) {
+    if response.dragged()
+        && let Some(position) = graph.positions.get_mut(key)
+    {
+        *position += response.drag_delta();
+    }
+}
+
+/// A curved edge with an arrow head at `to`.
+fn edge(painter: &egui::Painter, from: egui::Pos2, to: egui::Pos2, stroke: egui::Stroke) {
+    let bend = ((to.x - from.x).abs() * 0.5).max(30.0);
+    let curve = egui::epaint::CubicBezierShape::from_points_stroke(
+        [
+            from,
+            from + egui::vec2(bend, 0.0),
+            to - egui::vec2(bend, 0.0),
+            to,
+        ],
+        false,
+        egui::Color32::TRANSPARENT,
+        stroke,
+    );
+    painter.add(curve);
+    painter.circle_filled(to, 3.0, stroke.color);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graph_model() {
+        let source = "#define r 2\nomega: A(1)B\np1: A(x) : x < r -> F(x)[+A(x+1)]B\nB < C -> C\nB : 0.5 -> B";
+        let model = graph_model(source);
+        assert_eq!(model.axiom, vec!["A", "B"]);
+        assert_eq!(model.symbols, vec!["A", "B", "F", "C"]);
+        assert_eq!(model.rules.len(), 3);
+        assert_eq!(model.rules[0].line, 2);
+        assert_eq!(model.rules[0].produces, vec!["F", "A", "B"]);
+        assert_eq!(model.rules[0].condition, "x < r");
+        assert_eq!(
+            model.rules[1].symbol, "C",
+            "context is not the rewritten symbol"
+        );
+        assert_eq!(model.rules[2].condition, "0.5");
+    }
+
+    #[test]
+    fn test_edits_regenerate_source() {
+        let source = "omega: A\np1: A -> AB\n// end";
+        let rule = rule_text(Some("p1"), "A", "0.5", "A[+B]");
+        assert_eq!(
+            replace_line(source, 1, Some(&rule)),
+            "omega: A\np1: A : 0.5 -> A[+B]\n// end"
+        );
+        assert_eq!(replace_line(source, 1, None), "omega: A\n// end");
+        assert_eq!(
+            add_rule(source, "B -> B"),
+            "omega: A\np1: A -> AB\nB -> B\n// end"
+        );
+        assert_eq!(add_rule("", "B -> B"), "B -> B");
+    }
+}
@@ -0,0 +1,149 @@
+//! Texture import and per-slot map assignment, below the material palette.
+
+use crate::core::config::MaterialSettingsMap;
+use crate::visuals::textures::{TEXTURE_EXTENSIONS, TextureLibrary, TextureMapKind};
+use bevy_egui::egui;
+
+/// Queues image files dropped onto the window for import.
+///
+/// Native builds read the dropped path; the web build receives the bytes.
+pub fn queue_dropped_textures(ctx: &egui::Context, library: &mut TextureLibrary) {
+    let dropped = ctx.input(|i| i.raw.dropped_files.clone());
+    for file in dropped {
+        let name = file
+            .path
+            .as_deref()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or(file.name);
+        if !TextureLibrary::accepts(&name) {
+            continue;
+        }
+        let bytes = match (file.bytes, file.path) {
+            (Some(bytes), _) => Ok(bytes.to_vec()),
+            (None, Some(path)) => std::fs::read(&path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e)),
+            (None, None) => Err(format!("No data for dropped file {}", name)),
+        };
+        match bytes {
+            Ok(bytes) => library.pending.push((name, bytes)),
+            Err(e) => library.status = Some(Err(e)),
+        }
+    }
+}
+
+/// Reads the file named in the import field and queues it.
+#[cfg(not(target_arch = "wasm32"))]
+fn queue_path_input(library: &mut TextureLibrary) {
+    let path = std::path::PathBuf::from(library.path_input.trim());
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if !TextureLibrary::accepts(&name) {
+        library.status = Some(Err(format!(
+            "Expected a {} file",
+            TEXTURE_EXTENSIONS.join("/")
+        )));
+        return;
+    }
+    match std::fs::read(&path) {
+        Ok(bytes) => library.pending.push((name, bytes)),
+        Err(e) => {
+            library.status = Some(Err(format!("Failed to read {}: {}", path.display(), e)));
+        }
+    }
+}
+
+/// Renders the imported textures and the map combos of each material slot.
+pub fn texture_maps_ui(
+    ui: &mut egui::Ui,
+    library: &mut TextureLibrary,
+    material_settings: &MaterialSettingsMap,
+) {
+    #[cfg(not(target_arch = "wasm32"))]
+    ui.horizontal(|ui| {
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut library.path_input)
+                .hint_text("path/to/bark.png")
+                .desired_width(160.0),
+        );
+        let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        if ui.button("📂 Import").clicked() || submitted {
+            queue_path_input(library);
+        }
+    });
+    ui.label(
+        egui::RichText::new("Drop PNG/JPG files onto the window to import them")
+            .small()
+            .color(egui::Color32::GRAY),
+    );
+    match &library.status {
+        Some(Ok(name)) => {
+            ui.colored_label(egui::Color32::GREEN, format!("Imported {}", name));
+        }
+        Some(Err(e)) => {
+            ui.colored_label(egui::Color32::RED, e);
+        }
+        None => {}
+    }
+
+    let mut remove = None;
+    for texture in &library.textures {
+        ui.horizontal(|ui| {
+            ui.label(format!(
+                "{} ({}×{})",
+                texture.name, texture.width, texture.height
+            ));
+            if ui.small_button("🗑").on_hover_text("Remove").clicked() {
+                remove = Some(texture.name.clone());
+            }
+        });
+    }
+    if let Some(name) = remove {
+        library.remove(&name);
+    }
+    if library.textures.is_empty() {
+        return;
+    }
+
+    ui.separator();
+    let mut material_ids: Vec<u8> = material_settings.settings.keys().copied().collect();
+    material_ids.sort_unstable();
+    let names: Vec<String> = library.textures.iter().map(|t| t.name.clone()).collect();
+    egui::Grid::new("texture_maps")
+        .striped(true)
+        .num_columns(1 + TextureMapKind::ALL.len())
+        .show(ui, |ui| {
+            ui.strong("Slot");
+            for kind in TextureMapKind::ALL {
+                ui.strong(kind.name());
+            }
+            ui.end_row();
+            for material_id in material_ids {
+                ui.label(format!(",({})", material_id));
+                let before = library.slots.get(&material_id).cloned().unwrap_or_default();
+                let mut maps = before.clone();
+                for &kind in TextureMapKind::ALL {
+                    let map = maps.get_mut(kind);
+                    egui::ComboBox::from_id_salt(("texture_map", material_id, kind.name()))
+                        .width(110.0)
+                        .selected_text(map.clone().unwrap_or_else(|| "—".into()))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(map, None, "—");
+                            for name in &names {
+                                ui.selectable_value(map, Some(name.clone()), name);
+                            }
+                        });
+                }
+                if maps != before {
+                    if maps.is_empty() {
+                        library.slots.remove(&material_id);
+                    } else {
+                        library.slots.insert(material_id, maps);
+                    }
+                }
+                ui.end_row();
+            }
+        });
+}
@@ -0,0 +1,107 @@
+//! Transient notifications in the corner of the window.
+//!
+//! Background operations (exports in particular) report failures here, so
+//! they are seen even when the panel that started them is collapsed.
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use std::collections::VecDeque;
+
+/// Seconds an error stays on screen unless dismissed.
+const TOAST_SECONDS: f64 = 10.0;
+
+/// Maximum number of toasts shown at once; older ones are dropped.
+const MAX_TOASTS: usize = 4;
+
+struct Toast {
+    title: String,
+    message: String,
+    /// egui time the toast was first drawn, starting its countdown.
+    shown_at: Option<f64>,
+}
+
+/// Pending notifications.
+#[derive(Resource, Default)]
+pub struct Toasts {
+    toasts: VecDeque<Toast>,
+}
+
+impl Toasts {
+    /// Shows an error notification.
+    pub fn error(&mut self, title: impl Into<String>, message: impl Into<String>) {
+        if self.toasts.len() >= MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+        self.toasts.push_back(Toast {
+            title: title.into(),
+            message: message.into(),
+            shown_at: None,
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.toasts.is_empty()
+    }
+}
+
+/// Draws the toasts stacked in the bottom-right corner, dropping expired and
+/// dismissed ones.
+pub fn toasts_ui(ctx: &egui::Context, toasts: &mut Toasts) {
+    if toasts.is_empty() {
+        return;
+    }
+    let now = ctx.input(|input| input.time);
+    toasts.toasts.retain(|toast| {
+        toast
+            .shown_at
+            .is_none_or(|shown| now - shown < TOAST_SECONDS)
+    });
+
+    let mut dismissed = None;
+    egui::Area::new(egui::Id::new("toasts"))
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-12.0, -12.0])
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            ui.set_max_width(360.0);
+            for (i, toast) in toasts.toasts.iter_mut().enumerate() {
+                toast.shown_at.get_or_insert(now);
+                egui::Frame::popup(ui.style())
+                    .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(255, 80, 80)))
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.colored_label(
+                                egui::Color32::from_rgb(255, 100, 100),
+                                format!("❌ {}", toast.title),
+                            );
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
+                                if ui.small_button("✖").clicked() {
+                                    dismissed = Some(i);
+                                }
+                            });
+                        });
+                        ui.label(&toast.message);
+                    });
+                ui.add_space(4.0);
+            }
+        });
+    if let Some(i) = dismissed {
+        toasts.toasts.remove(i);
+    }
+    // Keep repainting so toasts expire without user input
+    ctx.request_repaint_after(std::time::Duration::from_millis(500));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oldest_toast_dropped_when_full() {
+        let mut toasts = Toasts::default();
+        for i in 0..MAX_TOASTS + 2 {
+            toasts.error("Export failed", i.to_string());
+        }
+        assert_eq!(toasts.toasts.len(), MAX_TOASTS);
+        assert_eq!(toasts.toasts[0].message, "2");
+    }
+}
@@ -0,0 +1,167 @@
+//! "Variations" window: the editor grammar rendered with nine different seeds,
+//! to pick a favorite seed without opening the nursery.
+
+use crate::core::config::LSystemConfig;
+use crate::core::herbarium::THUMBNAIL_SIZE;
+use crate::ui::herbarium::thumbnail_texture;
+use crate::visuals::thumbnail::{ThumbnailRenderer, ThumbnailSlot};
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+/// Number of seeds shown at once, as a 3×3 grid.
+pub const VARIATION_COUNT: usize = 9;
+
+const GRID_COLUMNS: usize = 3;
+
+/// Variations window state.
+#[derive(Resource, Default)]
+pub struct VariationPanel {
+    pub open: bool,
+    /// Batch of seeds shown; **🎲 More** moves to the next batch.
+    pub batch: u64,
+}
+
+impl VariationPanel {
+    /// Seed of the `index`-th card: batch 0 shows seeds 1–9, batch 1 seeds
+    /// 10–18, and so on, so a seed is easy to note down.
+    pub fn seed(&self, index: usize) -> u64 {
+        self.batch * VARIATION_COUNT as u64 + index as u64 + 1
+    }
+}
+
+/// Renders the variations grid while `panel.open` is set. Returns the seed
+/// the user picked this frame; nothing can be picked while the editor seed is
+/// locked.
+pub fn variations_ui(
+    ctx: &egui::Context,
+    panel: &mut VariationPanel,
+    thumbnails: &ThumbnailRenderer,
+    config: &LSystemConfig,
+) -> Option<u64> {
+    let mut picked = None;
+    let mut open = panel.open;
+    egui::Window::new("🎲 Variations")
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(panel.batch > 0, egui::Button::new("◀"))
+                    .on_hover_text("Previous seeds")
+                    .clicked()
+                {
+                    panel.batch -= 1;
+                }
+                ui.label(format!(
+                    "Seeds {}–{}",
+                    panel.seed(0),
+                    panel.seed(VARIATION_COUNT - 1)
+                ));
+                if ui
+                    .button("🎲 More")
+                    .on_hover_text("Show the next nine seeds")
+                    .clicked()
+                {
+                    panel.batch += 1;
+                }
+            });
+            let hint = if config.seed_locked {
+                "Unlock the seed in the editor to pick one"
+            } else {
+                "Click a plant to use its seed in the editor"
+            };
+            ui.label(egui::RichText::new(hint).small().weak());
+            ui.separator();
+
+            egui::Grid::new("variations_grid")
+                .num_columns(GRID_COLUMNS)
+                .show(ui, |ui| {
+                    for index in 0..VARIATION_COUNT {
+                        let seed = panel.seed(index);
+                        let current = seed == config.seed;
+                        if variation_card_ui(ui, thumbnails, index, seed, current)
+                            && !config.seed_locked
+                        {
+                            picked = Some(seed);
+                        }
+                        if (index + 1) % GRID_COLUMNS == 0 {
+                            ui.end_row();
+                        }
+                    }
+                });
+        });
+    panel.open = open;
+    picked
+}
+
+/// One card of the grid: the thumbnail (or a spinner while it renders) and
+/// the seed. Returns `true` when clicked.
+fn variation_card_ui(
+    ui: &mut egui::Ui,
+    thumbnails: &ThumbnailRenderer,
+    index: usize,
+    seed: u64,
+    current: bool,
+) -> bool {
+    let size = egui::vec2(THUMBNAIL_SIZE as f32, THUMBNAIL_SIZE as f32);
+    let response = ui
+        .group(|ui| {
+            ui.vertical(|ui| {
+                let texture =
+                    thumbnails
+                        .get(ThumbnailSlot::Variation(index))
+                        .and_then(|thumbnail| {
+                            thumbnail_texture(
+                                ui.ctx(),
+                                egui::Id::new(("variation_thumbnail", index)),
+                                thumbnail.revision,
+                                || Some(thumbnail.rgba.clone()),
+                            )
+                        });
+                match texture {
+                    Some(texture) => {
+                        ui.image(egui::load::SizedTexture::new(texture.id(), size));
+                    }
+                    None => {
+                        ui.allocate_ui(size, |ui| {
+                            ui.centered_and_justified(|ui| {
+                                ui.spinner();
+                            });
+                        });
+                    }
+                }
+                let label = egui::RichText::new(format!("Seed {}", seed)).small();
+                ui.label(if current { label.strong() } else { label });
+            });
+        })
+        .response;
+    if current {
+        ui.painter().rect_stroke(
+            response.rect,
+            4.0,
+            ui.visuals().selection.stroke,
+            egui::StrokeKind::Inside,
+        );
+    }
+    ui.interact(
+        response.rect,
+        response.id.with("variation_card"),
+        egui::Sense::click(),
+    )
+    .on_hover_text("Use this seed")
+    .clicked()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batches_cover_distinct_seeds() {
+        let mut panel = VariationPanel::default();
+        let first: Vec<u64> = (0..VARIATION_COUNT).map(|i| panel.seed(i)).collect();
+        assert_eq!(first, (1..=9).collect::<Vec<_>>());
+        panel.batch = 1;
+        assert_eq!(panel.seed(0), 10);
+    }
+}
@@ -0,0 +1,98 @@
+//! "Watch" section: expressions evaluated live against the grammar's constants.
+
+use crate::logic::watch::evaluate_expression;
+use bevy::prelude::*;
+use bevy_egui::egui;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// Watched expressions, with their values for the last constants shown.
+#[derive(Resource, Default)]
+pub struct WatchPanel {
+    pub expressions: Vec<String>,
+    /// Text of the expression being added.
+    pub draft: String,
+    /// Hash of the expressions and constants, and the values computed for them.
+    cached: Option<(u64, Vec<Result<f64, String>>)>,
+}
+
+impl WatchPanel {
+    /// Evaluates every expression, unless neither they nor the constants
+    /// changed since last time.
+    pub fn refresh(&mut self, constants: &[(String, f64)]) -> &[Result<f64, String>] {
+        let mut hasher = DefaultHasher::new();
+        self.expressions.hash(&mut hasher);
+        for (name, value) in constants {
+            (name, value.to_bits()).hash(&mut hasher);
+        }
+        let key = hasher.finish();
+
+        if self
+            .cached
+            .as_ref()
+            .is_none_or(|(cached, _)| *cached != key)
+        {
+            let values = self
+                .expressions
+                .iter()
+                .map(|expression| evaluate_expression(constants, expression))
+                .collect();
+            self.cached = Some((key, values));
+        }
+        self.cached.as_ref().map_or(&[], |(_, values)| values)
+    }
+}
+
+/// Lists the watched expressions with their values, and a field to add more.
+pub fn watch_ui(ui: &mut egui::Ui, panel: &mut WatchPanel, constants: &[(String, f64)]) {
+    let values = panel.refresh(constants).to_vec();
+    let mut remove = None;
+    egui::Grid::new("watch_grid")
+        .num_columns(3)
+        .striped(true)
+        .show(ui, |ui| {
+            for (index, (expression, value)) in panel.expressions.iter().zip(&values).enumerate() {
+                ui.monospace(expression);
+                match value {
+                    Ok(value) => ui.monospace(format!("= {}", format_value(*value))),
+                    Err(e) => ui
+                        .colored_label(egui::Color32::RED, "error")
+                        .on_hover_text(e),
+                };
+                if ui
+                    .small_button("✖")
+                    .on_hover_text("Stop watching")
+                    .clicked()
+                {
+                    remove = Some(index);
+                }
+                ui.end_row();
+            }
+        });
+    if let Some(index) = remove {
+        panel.expressions.remove(index);
+    }
+
+    ui.horizontal(|ui| {
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut panel.draft)
+                .hint_text("e.g. s/R^3")
+                .desired_width(ui.available_width() - 50.0),
+        );
+        let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        if (ui.button("Add").clicked() || submitted) && !panel.draft.trim().is_empty() {
+            panel.expressions.push(panel.draft.trim().to_string());
+            panel.draft.clear();
+        }
+    });
+}
+
+/// Up to four decimals (values pass through `f32`), without trailing zeros.
+fn format_value(value: f64) -> String {
+    let text = format!("{:.4}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".into()
+    } else {
+        text.into()
+    }
+}
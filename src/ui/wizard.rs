@@ -0,0 +1,130 @@
+//! "Plant Wizard" window: a few questions about the plant's shape, answered
+//! with a parameterized grammar.
+
+use crate::logic::wizard::{MAX_BRANCHES, MAX_DEPTH, PlantTraits, WizardGrammar, synthesize};
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+/// Titles of the wizard steps, in order.
+const STEPS: [&str; 4] = ["Branching", "Trunk", "Leaves", "Review"];
+
+/// Wizard window state.
+#[derive(Resource, Default)]
+pub struct PlantWizard {
+    pub open: bool,
+    /// Index into the wizard steps.
+    pub step: usize,
+    pub traits: PlantTraits,
+}
+
+/// Renders the wizard while `wizard.open` is set. Returns the grammar when
+/// the user loads it into the editor.
+pub fn plant_wizard_ui(ctx: &egui::Context, wizard: &mut PlantWizard) -> Option<WizardGrammar> {
+    let mut loaded = None;
+    let mut open = wizard.open;
+    egui::Window::new("🌱 Plant Wizard")
+        .open(&mut open)
+        .default_width(380.0)
+        .show(ctx, |ui| {
+            wizard.step = wizard.step.min(STEPS.len() - 1);
+            ui.horizontal(|ui| {
+                for (index, title) in STEPS.iter().enumerate() {
+                    if index > 0 {
+                        ui.label("›");
+                    }
+                    ui.selectable_value(&mut wizard.step, index, *title);
+                }
+            });
+            ui.separator();
+
+            let traits = &mut wizard.traits;
+            match wizard.step {
+                0 => {
+                    ui.add(
+                        egui::Slider::new(&mut traits.branches, 1..=MAX_BRANCHES)
+                            .text("Branches per node"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut traits.angle_min, 0.0..=90.0)
+                            .text("Smallest angle")
+                            .suffix("°"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut traits.angle_max, 0.0..=90.0)
+                            .text("Largest angle")
+                            .suffix("°"),
+                    )
+                    .on_hover_text("Branch angles are spread evenly across the range");
+                }
+                1 => {
+                    ui.add(egui::Slider::new(&mut traits.taper, 0.3..=1.0).text("Width ratio"))
+                        .on_hover_text("Child width relative to its parent; lower tapers faster");
+                    ui.add(
+                        egui::Slider::new(&mut traits.length_ratio, 0.3..=1.0).text("Length ratio"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut traits.depth, 1..=MAX_DEPTH)
+                            .text("Branching orders"),
+                    );
+                }
+                2 => {
+                    ui.add(
+                        egui::Slider::new(&mut traits.leaf_density, 0.0..=1.0).text("Leaf density"),
+                    )
+                    .on_hover_text("Chance that a bud on a thin branch grows a leaf");
+                    ui.add(
+                        egui::Slider::new(&mut traits.leaf_size, 0.1..=10.0)
+                            .text("Leaf size")
+                            .logarithmic(true),
+                    );
+                }
+                _ => {
+                    let grammar = synthesize(traits);
+                    egui::ScrollArea::vertical()
+                        .id_salt("wizard_preview")
+                        .max_height(220.0)
+                        .show(ui, |ui| {
+                            ui.add(
+                                egui::TextEdit::multiline(&mut grammar.source.as_str())
+                                    .code_editor()
+                                    .desired_width(f32::INFINITY),
+                            );
+                            ui.label("Finalization");
+                            ui.add(
+                                egui::TextEdit::multiline(&mut grammar.finalization.as_str())
+                                    .code_editor()
+                                    .desired_width(f32::INFINITY),
+                            );
+                        });
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{} iterations", grammar.iterations));
+                        if ui
+                            .button("Load into editor")
+                            .on_hover_text("Replace the grammar and finalization with this plant")
+                            .clicked()
+                        {
+                            loaded = Some(grammar);
+                        }
+                    });
+                }
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(wizard.step > 0, egui::Button::new("◀ Back"))
+                    .clicked()
+                {
+                    wizard.step -= 1;
+                }
+                if ui
+                    .add_enabled(wizard.step + 1 < STEPS.len(), egui::Button::new("Next ▶"))
+                    .clicked()
+                {
+                    wizard.step += 1;
+                }
+            });
+        });
+    wizard.open = open;
+    loaded
+}
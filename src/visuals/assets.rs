@@ -1,17 +1,37 @@
-use crate::core::config::PropMeshType;
+use crate::core::config::{DirtyFlags, PropConfig, PropMeshType};
+use crate::visuals::textures::TextureLibrary;
+use bevy::asset::RenderAssetUsages;
+use bevy::image::{ImageAddressMode, ImageSampler, ImageSamplerDescriptor};
+use bevy::mesh::{Indices, PrimitiveTopology};
 use bevy::platform::collections::HashMap;
 use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
 
 // Material-related resources (MaterialPalette, ProceduralTextures, texture generation)
 // are now provided by bevy_symbios::materials.
 
+/// Width and height of the built-in leaf texture, in pixels.
+pub const LEAF_TEXTURE_SIZE: u32 = 128;
+
+/// Width and height of the leaf card props, matching the [`PropMeshType::Leaf`] cuboid.
+const LEAF_CARD_SIZE: Vec2 = Vec2::new(0.5, 0.8);
+
 /// Stores base meshes for each PropMeshType
 #[derive(Resource)]
 pub struct PropMeshAssets {
     pub meshes: HashMap<PropMeshType, Handle<Mesh>>,
+    /// Alpha-tested texture of the card props ([`PropMeshType::is_cutout`]):
+    /// the imported texture picked in [`PropConfig::leaf_texture`], or the
+    /// built-in leaf.
+    pub leaf_texture: Handle<Image>,
+    builtin_leaf_texture: Handle<Image>,
 }
 
-pub fn setup_prop_assets(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+pub fn setup_prop_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut images: ResMut<Assets<Image>>,
+) {
     let mut prop_meshes = HashMap::new();
 
     // Leaf: Flattened cuboid
@@ -38,7 +58,159 @@ pub fn setup_prop_assets(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>
     // Cube
     prop_meshes.insert(PropMeshType::Cube, meshes.add(Cuboid::new(0.3, 0.3, 0.3)));
 
+    // Cards: one textured quad, or two crossed at right angles
+    prop_meshes.insert(PropMeshType::Billboard, meshes.add(leaf_card_mesh(false)));
+    prop_meshes.insert(PropMeshType::CrossQuad, meshes.add(leaf_card_mesh(true)));
+
+    let builtin_leaf_texture = images.add(builtin_leaf_image());
     commands.insert_resource(PropMeshAssets {
         meshes: prop_meshes,
+        leaf_texture: builtin_leaf_texture.clone(),
+        builtin_leaf_texture,
     });
 }
+
+/// A leaf card centered on the prop: a quad facing local Z, plus with
+/// `crossed` a second quad facing local X. Both share the full texture.
+pub fn leaf_card_mesh(crossed: bool) -> Mesh {
+    let half = LEAF_CARD_SIZE / 2.0;
+    let mut positions = vec![
+        [-half.x, half.y, 0.0],
+        [half.x, half.y, 0.0],
+        [half.x, -half.y, 0.0],
+        [-half.x, -half.y, 0.0],
+    ];
+    let mut normals = vec![[0.0, 0.0, 1.0]; 4];
+    if crossed {
+        positions.extend([
+            [0.0, half.y, half.x],
+            [0.0, half.y, -half.x],
+            [0.0, -half.y, -half.x],
+            [0.0, -half.y, half.x],
+        ]);
+        normals.extend([[1.0, 0.0, 0.0]; 4]);
+    }
+    let quads = positions.len() as u32 / 4;
+    let uvs: Vec<[f32; 2]> = (0..quads)
+        .flat_map(|_| [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]])
+        .collect();
+    let indices: Vec<u32> = (0..quads)
+        .flat_map(|quad| [0, 3, 2, 0, 2, 1].map(|i| quad * 4 + i))
+        .collect();
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// RGBA pixels of the built-in leaf: an opaque white blade with a darker
+/// midrib, tip at the top, on a transparent background. White lets the
+/// material and prop colors tint it.
+pub fn builtin_leaf_rgba(size: u32) -> Vec<u8> {
+    let mut rgba = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        // 0 at the stem (bottom row), 1 at the tip (top row)
+        let t = 1.0 - (y as f32 + 0.5) / size as f32;
+        let half_width = 0.9 * (std::f32::consts::PI * t).sin().powf(0.8);
+        for x in 0..size {
+            let u = ((x as f32 + 0.5) / size as f32 * 2.0 - 1.0).abs();
+            let stem = t < 0.1 && u < 0.05;
+            let blade = u < half_width;
+            let shade = if u < 0.04 { 190 } else { 255 };
+            let alpha = if stem || blade { 255 } else { 0 };
+            rgba.extend([shade, shade, shade, alpha]);
+        }
+    }
+    rgba
+}
+
+fn builtin_leaf_image() -> Image {
+    let mut image = Image::new(
+        Extent3d {
+            width: LEAF_TEXTURE_SIZE,
+            height: LEAF_TEXTURE_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        builtin_leaf_rgba(LEAF_TEXTURE_SIZE),
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    // Clamped, so the transparent border does not bleed into the opposite edge
+    image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+        address_mode_u: ImageAddressMode::ClampToEdge,
+        address_mode_v: ImageAddressMode::ClampToEdge,
+        ..ImageSamplerDescriptor::linear()
+    });
+    image
+}
+
+/// Encodes an RGBA8 image as PNG, for embedding the leaf texture in exports.
+pub fn encode_png(image: &Image) -> Result<Vec<u8>, String> {
+    let size = image.texture_descriptor.size;
+    let data = image
+        .data
+        .clone()
+        .ok_or_else(|| "The leaf texture has no pixel data".to_string())?;
+    let pixels = image::RgbaImage::from_raw(size.width, size.height, data)
+        .ok_or_else(|| "The leaf texture is not RGBA8".to_string())?;
+    let mut png = Vec::new();
+    pixels
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode the leaf texture: {}", e))?;
+    Ok(png)
+}
+
+/// System that points [`PropMeshAssets::leaf_texture`] at the texture picked
+/// in [`PropConfig::leaf_texture`], falling back to the built-in leaf while
+/// the pick is not imported. Respawns the plant when the texture changes, so
+/// its card props get the new material.
+pub fn sync_leaf_texture(
+    prop_config: Res<PropConfig>,
+    library: Res<TextureLibrary>,
+    mut prop_assets: ResMut<PropMeshAssets>,
+    mut dirty: ResMut<DirtyFlags>,
+) {
+    if !prop_config.is_changed() && !library.is_changed() {
+        return;
+    }
+    let texture = prop_config
+        .leaf_texture
+        .as_deref()
+        .and_then(|name| library.get(name))
+        .map(|texture| texture.srgb.clone())
+        .unwrap_or_else(|| prop_assets.builtin_leaf_texture.clone());
+    if texture != prop_assets.leaf_texture {
+        prop_assets.leaf_texture = texture;
+        dirty.geometry = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_leaf_is_cut_out() {
+        let size = 32;
+        let rgba = builtin_leaf_rgba(size);
+        assert_eq!(rgba.len(), (size * size * 4) as usize);
+        let alpha = |x: u32, y: u32| rgba[((y * size + x) * 4 + 3) as usize];
+        assert_eq!(alpha(size / 2, size / 2), 255, "blade is opaque");
+        assert_eq!(alpha(0, 0), 0, "corners are transparent");
+        assert_eq!(alpha(size - 1, size - 1), 0);
+    }
+
+    #[test]
+    fn test_cross_quad_has_two_faces() {
+        let single = leaf_card_mesh(false);
+        let crossed = leaf_card_mesh(true);
+        assert_eq!(single.count_vertices(), 4);
+        assert_eq!(crossed.count_vertices(), 8);
+        assert_eq!(crossed.indices().unwrap().len(), 12);
+    }
+}
@@ -0,0 +1,293 @@
+//! Branch order inference for turtle skeletons.
+//!
+//! The skeleton only stores strands (unbroken polylines), so the branch order of
+//! each strand is reconstructed from how strands attach to each other:
+//!
+//! - A strand that starts on the interior of another strand is a lateral branch
+//!   and gets its parent's order + 1.
+//! - Strands that start at the end point of another strand compete for being its
+//!   continuation: the one whose first segment is best aligned with the parent's
+//!   last segment keeps the parent's order, the others are lateral branches.
+//! - Strands attached to nothing are roots (order 0).
+
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use symbios_turtle_3d::Skeleton;
+
+/// Number of order levels used by default when splitting exports
+/// (trunk / primary branches / twigs).
+pub const DEFAULT_BRANCH_ORDER_LEVELS: u32 = 3;
+
+pub(crate) type CellKey = (i64, i64, i64);
+
+pub(crate) fn cell_key(p: Vec3, cell: f32) -> CellKey {
+    (
+        (p.x / cell).round() as i64,
+        (p.y / cell).round() as i64,
+        (p.z / cell).round() as i64,
+    )
+}
+
+fn start_direction(strand: &[Vec3]) -> Option<Vec3> {
+    strand
+        .get(1)
+        .map(|&p| p - strand[0])
+        .and_then(Vec3::try_normalize)
+}
+
+fn end_direction(strand: &[Vec3]) -> Option<Vec3> {
+    let n = strand.len();
+    if n < 2 {
+        return None;
+    }
+    (strand[n - 1] - strand[n - 2]).try_normalize()
+}
+
+/// Computes the branch order of each strand from its polyline positions.
+pub fn branch_orders(strands: &[Vec<Vec3>]) -> Vec<u32> {
+    // Snap tolerance relative to the plant size, so float noise doesn't split joints
+    let extent = strands
+        .iter()
+        .flatten()
+        .fold(0.0_f32, |acc, p| acc.max(p.abs().max_element()));
+    let cell = (extent * 1e-5).max(1e-6);
+
+    let mut points: HashMap<CellKey, Vec<(usize, usize)>> = HashMap::new();
+    let mut starts: HashMap<CellKey, Vec<usize>> = HashMap::new();
+    for (s, strand) in strands.iter().enumerate() {
+        for (k, &p) in strand.iter().enumerate() {
+            points.entry(cell_key(p, cell)).or_default().push((s, k));
+        }
+        if let Some(&first) = strand.first() {
+            starts.entry(cell_key(first, cell)).or_default().push(s);
+        }
+    }
+
+    let mut orders = vec![0u32; strands.len()];
+    for (i, strand) in strands.iter().enumerate() {
+        let Some(&first) = strand.first() else {
+            continue;
+        };
+        let key = cell_key(first, cell);
+
+        // Earlier strands passing through (or ending at) this strand's start point
+        let Some(&(parent, point_idx)) = points
+            .get(&key)
+            .and_then(|hits| hits.iter().find(|&&(s, k)| s < i && k > 0))
+        else {
+            continue;
+        };
+
+        let parent_order = orders[parent];
+        let parent_strand = &strands[parent];
+        if point_idx + 1 < parent_strand.len() {
+            orders[i] = parent_order + 1;
+            continue;
+        }
+
+        // Attached at the parent's end: the straightest follower is the continuation
+        let parent_dir = end_direction(parent_strand);
+        let alignment = |s: usize| match (parent_dir, start_direction(&strands[s])) {
+            (Some(a), Some(b)) => a.dot(b),
+            _ => -1.0,
+        };
+        let continuation = starts
+            .get(&key)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|&s| s != parent)
+            .fold(None::<(usize, f32)>, |best, s| {
+                let score = alignment(s);
+                match best {
+                    Some((_, best_score)) if best_score >= score => best,
+                    _ => Some((s, score)),
+                }
+            })
+            .map(|(s, _)| s);
+
+        orders[i] = if continuation == Some(i) {
+            parent_order
+        } else {
+            parent_order + 1
+        };
+    }
+
+    orders
+}
+
+/// Computes the path length from the root to every point of every strand.
+///
+/// A strand starting on a point of an earlier strand continues from that
+/// point's distance; strands attached to nothing start at zero.
+pub fn root_distances(strands: &[Vec<Vec3>]) -> Vec<Vec<f32>> {
+    let extent = strands
+        .iter()
+        .flatten()
+        .fold(0.0_f32, |acc, p| acc.max(p.abs().max_element()));
+    let cell = (extent * 1e-5).max(1e-6);
+
+    let mut reached: HashMap<CellKey, f32> = HashMap::new();
+    strands
+        .iter()
+        .map(|strand| {
+            let mut distance = strand
+                .first()
+                .and_then(|&first| reached.get(&cell_key(first, cell)).copied())
+                .unwrap_or(0.0);
+            let distances: Vec<f32> = strand
+                .iter()
+                .enumerate()
+                .map(|(k, &p)| {
+                    if k > 0 {
+                        distance += p.distance(strand[k - 1]);
+                    }
+                    distance
+                })
+                .collect();
+            for (&p, &d) in strand.iter().zip(&distances) {
+                reached.entry(cell_key(p, cell)).or_insert(d);
+            }
+            distances
+        })
+        .collect()
+}
+
+/// Finds the point each strand starts on, as `(strand, point)` of an earlier
+/// strand, or `None` for strands attached to nothing.
+///
+/// Interior and end points are preferred over another strand's start, so a
+/// branch sharing its origin with a sibling still hangs off the parent.
+pub fn strand_attachments(strands: &[Vec<Vec3>]) -> Vec<Option<(usize, usize)>> {
+    let extent = strands
+        .iter()
+        .flatten()
+        .fold(0.0_f32, |acc, p| acc.max(p.abs().max_element()));
+    let cell = (extent * 1e-5).max(1e-6);
+
+    let mut points: HashMap<CellKey, Vec<(usize, usize)>> = HashMap::new();
+    let mut attachments = Vec::with_capacity(strands.len());
+    for (i, strand) in strands.iter().enumerate() {
+        let hits = strand
+            .first()
+            .and_then(|&first| points.get(&cell_key(first, cell)));
+        attachments.push(hits.and_then(|hits| {
+            hits.iter()
+                .find(|&&(_, k)| k > 0)
+                .or_else(|| hits.first())
+                .copied()
+        }));
+        for (k, &p) in strand.iter().enumerate() {
+            points.entry(cell_key(p, cell)).or_default().push((i, k));
+        }
+    }
+    attachments
+}
+
+/// Computes the branch order of each strand in a turtle skeleton.
+pub fn skeleton_branch_orders(skeleton: &Skeleton) -> Vec<u32> {
+    let strands: Vec<Vec<Vec3>> = skeleton
+        .strands
+        .iter()
+        .map(|strand| strand.iter().map(|point| point.position).collect())
+        .collect();
+    branch_orders(&strands)
+}
+
+/// Clamps a branch order into one of `levels` groups; the last group collects
+/// every higher order.
+pub fn order_level(order: u32, levels: u32) -> u32 {
+    order.min(levels.max(1) - 1)
+}
+
+/// Human-readable name for a branch order group, used for export object names.
+pub fn level_name(level: u32, levels: u32) -> String {
+    let levels = levels.max(1);
+    if level == 0 {
+        "Trunk".to_string()
+    } else if level == levels - 1 && levels >= 3 {
+        "Twigs".to_string()
+    } else if levels <= 3 {
+        "Branches".to_string()
+    } else {
+        format!("Branches{}", level)
+    }
+}
+
+/// Splits a skeleton into one skeleton per order level, without props.
+/// Levels that contain no strands are omitted.
+pub fn split_skeleton_by_level(skeleton: &Skeleton, levels: u32) -> Vec<(u32, Skeleton)> {
+    let orders = skeleton_branch_orders(skeleton);
+    (0..levels.max(1))
+        .filter_map(|level| {
+            let mut part = skeleton.clone();
+            part.props.clear();
+            part.strands = skeleton
+                .strands
+                .iter()
+                .zip(&orders)
+                .filter(|(_, order)| order_level(**order, levels) == level)
+                .map(|(strand, _)| strand.clone())
+                .collect();
+            (!part.strands.is_empty()).then_some((level, part))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lateral_branch_from_interior() {
+        // Trunk passes through the branch point; branch starts mid-trunk
+        let trunk = vec![Vec3::ZERO, Vec3::Y, Vec3::Y * 2.0];
+        let branch = vec![Vec3::Y, Vec3::new(1.0, 2.0, 0.0)];
+        let twig = vec![Vec3::new(0.5, 1.5, 0.0), Vec3::new(0.5, 2.5, 0.0)];
+        let twig_parent = vec![Vec3::Y, Vec3::new(0.5, 1.5, 0.0), Vec3::new(1.0, 2.0, 0.0)];
+        assert_eq!(branch_orders(&[trunk.clone(), branch]), vec![0, 1]);
+        assert_eq!(branch_orders(&[trunk, twig_parent, twig]), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_continuation_after_pop() {
+        // Trunk strand ends at the branch point and resumes as a new strand
+        let trunk_a = vec![Vec3::ZERO, Vec3::Y];
+        let branch = vec![Vec3::Y, Vec3::new(1.0, 2.0, 0.0)];
+        let trunk_b = vec![Vec3::Y, Vec3::Y * 2.0];
+        assert_eq!(branch_orders(&[trunk_a, branch, trunk_b]), vec![0, 1, 0]);
+    }
+
+    #[test]
+    fn test_root_distances_continue_from_parent() {
+        let trunk = vec![Vec3::ZERO, Vec3::Y, Vec3::Y * 2.0];
+        let branch = vec![Vec3::Y, Vec3::new(1.0, 1.0, 0.0)];
+        let loose = vec![Vec3::X * 5.0, Vec3::new(5.0, 3.0, 0.0)];
+        let distances = root_distances(&[trunk, branch, loose]);
+        assert_eq!(distances[0], vec![0.0, 1.0, 2.0]);
+        assert_eq!(distances[1], vec![1.0, 2.0]);
+        assert_eq!(distances[2], vec![0.0, 3.0]);
+    }
+
+    #[test]
+    fn test_strand_attachments() {
+        let trunk = vec![Vec3::ZERO, Vec3::Y, Vec3::Y * 2.0];
+        let branch = vec![Vec3::Y, Vec3::new(1.0, 2.0, 0.0)];
+        let loose = vec![Vec3::X * 5.0, Vec3::new(5.0, 3.0, 0.0)];
+        let twig = vec![Vec3::new(1.0, 2.0, 0.0), Vec3::new(1.0, 3.0, 0.0)];
+        assert_eq!(
+            strand_attachments(&[trunk, branch, loose, twig]),
+            vec![None, Some((0, 1)), None, Some((1, 1))]
+        );
+    }
+
+    #[test]
+    fn test_order_levels() {
+        assert_eq!(order_level(0, 3), 0);
+        assert_eq!(order_level(5, 3), 2);
+        assert_eq!(level_name(0, 3), "Trunk");
+        assert_eq!(level_name(1, 3), "Branches");
+        assert_eq!(level_name(2, 3), "Twigs");
+        assert_eq!(level_name(1, 2), "Branches");
+    }
+}
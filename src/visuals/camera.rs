@@ -0,0 +1,48 @@
+//! Camera helpers for framing the generated plant.
+
+use bevy::prelude::*;
+use bevy_panorbit_camera::PanOrbitCamera;
+
+/// Vertical field of view of the default perspective projection.
+const CAMERA_FOV: f32 = std::f32::consts::FRAC_PI_4;
+
+/// Extra distance so the plant does not touch the viewport edges.
+const FRAME_MARGIN: f32 = 1.15;
+
+/// Returns the (focus, radius) that fits the bounding sphere of an AABB in view.
+pub fn framing_for_bounds(min: Vec3, max: Vec3) -> (Vec3, f32) {
+    let center = (min + max) * 0.5;
+    let sphere_radius = ((max - min).length() * 0.5).max(0.01);
+    let distance = sphere_radius / (CAMERA_FOV * 0.5).sin() * FRAME_MARGIN;
+    (center, distance)
+}
+
+/// Animates the orbit camera so the AABB fills the view.
+/// Yaw and pitch are kept so the user's viewing angle is preserved.
+pub fn frame_bounds(camera: &mut PanOrbitCamera, min: Vec3, max: Vec3) {
+    let (focus, radius) = framing_for_bounds(min, max);
+    camera.target_focus = focus;
+    camera.target_radius = radius;
+    camera.force_update = true;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_framing_centers_on_bounds() {
+        let (focus, radius) = framing_for_bounds(Vec3::new(-10.0, 0.0, -10.0), Vec3::splat(10.0));
+        assert_eq!(focus, Vec3::new(0.0, 5.0, 0.0));
+        assert!(
+            radius > 15.0,
+            "Camera should back off beyond the bounding sphere"
+        );
+    }
+
+    #[test]
+    fn test_framing_degenerate_bounds() {
+        let (_, radius) = framing_for_bounds(Vec3::ONE, Vec3::ONE);
+        assert!(radius.is_finite() && radius > 0.0);
+    }
+}
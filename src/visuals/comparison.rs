@@ -0,0 +1,291 @@
+//! A/B comparison split view.
+//!
+//! The editor plant (A) renders in the left half of the window through the main
+//! camera; a pinned variant (B) renders in the right half through a second camera.
+//! B lives on its own render layer at the same origin as A, so with synchronized
+//! cameras both plants are seen from exactly the same viewpoint.
+
+use crate::core::config::{LSystemConfig, PropConfig};
+use crate::core::genotype::PlantGenotype;
+use crate::core::includes::IncludeLibrary;
+use crate::ui::nursery::{NurseryMode, NurseryState};
+use crate::visuals::assets::PropMeshAssets;
+use crate::visuals::genotype_render::{
+    CachedGenotypeMesh, PendingDerivation, PlantDetail, spawn_genotype_derivation,
+    spawn_genotype_plant,
+};
+use bevy::camera::Viewport;
+use bevy::camera::visibility::RenderLayers;
+use bevy::post_process::bloom::Bloom;
+use bevy::prelude::*;
+use bevy_panorbit_camera::PanOrbitCamera;
+use bevy_symbios::materials::ProceduralTextures;
+
+/// Render layer holding the pinned (B) plant.
+pub const COMPARISON_LAYER: usize = 1;
+
+/// Registers the comparison state and the systems that derive the pinned plant
+/// and drive the split-view cameras.
+pub struct ComparisonPlugin;
+
+impl Plugin for ComparisonPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ComparisonState>().add_systems(
+            Update,
+            (
+                start_comparison_derivation,
+                poll_comparison_derivation,
+                sync_comparison_cameras,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// Where the pinned plant came from, for labelling.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ComparisonSource {
+    /// Snapshot of the editor state at the time it was pinned.
+    Snapshot,
+    /// Individual from the nursery population.
+    NurseryIndividual { generation: usize, index: usize },
+}
+
+impl ComparisonSource {
+    pub fn label(&self) -> String {
+        match self {
+            ComparisonSource::Snapshot => "Pinned snapshot".to_string(),
+            ComparisonSource::NurseryIndividual { generation, index } => {
+                format!("Nursery gen {} #{}", generation, index + 1)
+            }
+        }
+    }
+}
+
+/// State of the A/B comparison view.
+#[derive(Resource)]
+pub struct ComparisonState {
+    /// Show the split view (only while the nursery is closed).
+    pub enabled: bool,
+    /// The pinned B variant.
+    pub pinned: Option<(PlantGenotype, ComparisonSource)>,
+    /// Drive the B camera from the A camera.
+    pub sync_cameras: bool,
+    /// Set when the pinned plant must be re-derived and re-meshed.
+    pub needs_rebuild: bool,
+    /// Error from the last pinned derivation, for display.
+    pub error: Option<String>,
+    pending: Option<PendingDerivation>,
+}
+
+impl Default for ComparisonState {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            pinned: None,
+            sync_cameras: true,
+            needs_rebuild: false,
+            error: None,
+            pending: None,
+        }
+    }
+}
+
+impl ComparisonState {
+    /// Pins a variant as B and schedules it for rendering.
+    pub fn pin(&mut self, genotype: PlantGenotype, source: ComparisonSource) {
+        self.pinned = Some((genotype, source));
+        self.needs_rebuild = true;
+        self.enabled = true;
+    }
+
+    /// True if the split view should currently be shown.
+    pub fn is_active(&self, nursery: &NurseryState) -> bool {
+        self.enabled && self.pinned.is_some() && nursery.mode == NurseryMode::Disabled
+    }
+}
+
+/// Marks the camera rendering the pinned plant.
+#[derive(Component)]
+pub struct ComparisonCamera;
+
+/// Marks meshes and props of the pinned plant.
+#[derive(Component, Clone)]
+pub struct ComparisonMeshTag;
+
+/// Dispatches the pinned plant's derivation to the async thread pool.
+pub fn start_comparison_derivation(
+    mut comparison: ResMut<ComparisonState>,
+    includes: Res<IncludeLibrary>,
+) {
+    if !comparison.needs_rebuild {
+        return;
+    }
+    comparison.needs_rebuild = false;
+    comparison.error = None;
+
+    let Some((genotype, _)) = comparison.pinned.clone() else {
+        return;
+    };
+
+    comparison.pending = Some(spawn_genotype_derivation(&genotype, &includes));
+}
+
+/// Spawns the pinned plant once its derivation finishes, replacing the previous one.
+#[allow(clippy::too_many_arguments)]
+pub fn poll_comparison_derivation(
+    mut commands: Commands,
+    mut comparison: ResMut<ComparisonState>,
+    config: Res<LSystemConfig>,
+    prop_config: Res<PropConfig>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    proc_textures: Res<ProceduralTextures>,
+    prop_assets: Res<PropMeshAssets>,
+    old_entities: Query<Entity, With<ComparisonMeshTag>>,
+) {
+    if comparison.pinned.is_none() {
+        for entity in &old_entities {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    let Some(shared) = &comparison.pending else {
+        return;
+    };
+    let Some(derived) = shared.lock().ok().and_then(|mut guard| guard.take()) else {
+        return;
+    };
+    comparison.pending = None;
+
+    for entity in &old_entities {
+        commands.entity(entity).despawn();
+    }
+
+    let Some((genotype, _)) = &comparison.pinned else {
+        return;
+    };
+    let error = derived
+        .is_none()
+        .then(|| "Derivation failed: invalid L-system syntax".to_string());
+    let cached = CachedGenotypeMesh::new(genotype, derived, 0.0, error.clone());
+    comparison.error = error;
+
+    let tag = (ComparisonMeshTag, RenderLayers::layer(COMPARISON_LAYER));
+    spawn_genotype_plant(
+        &mut commands,
+        &cached,
+        Vec3::ZERO,
+        PlantDetail::full(config.mesh_resolution),
+        &prop_config,
+        &mut meshes,
+        &mut materials,
+        &proc_textures,
+        &prop_assets,
+        tag.clone(),
+        tag,
+    );
+}
+
+/// Spawns/despawns the B camera, splits the window between the two cameras,
+/// and mirrors the A camera onto B when synchronization is enabled.
+pub fn sync_comparison_cameras(
+    mut commands: Commands,
+    comparison: Res<ComparisonState>,
+    nursery: Res<NurseryState>,
+    windows: Query<&Window>,
+    mut main_camera: Query<(&mut Camera, &PanOrbitCamera), Without<ComparisonCamera>>,
+    mut comparison_camera: Query<
+        (Entity, &mut Camera, &mut PanOrbitCamera),
+        With<ComparisonCamera>,
+    >,
+) {
+    let Ok((mut camera_a, orbit_a)) = main_camera.single_mut() else {
+        return;
+    };
+
+    if !comparison.is_active(&nursery) {
+        if camera_a.viewport.is_some() {
+            camera_a.viewport = None;
+        }
+        for (entity, _, _) in &comparison_camera {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let size = window.physical_size();
+    if size.x < 2 || size.y == 0 {
+        return;
+    }
+    let half = size.x / 2;
+    let left = Viewport {
+        physical_position: UVec2::ZERO,
+        physical_size: UVec2::new(half, size.y),
+        ..default()
+    };
+    let right = Viewport {
+        physical_position: UVec2::new(half, 0),
+        physical_size: UVec2::new(size.x - half, size.y),
+        ..default()
+    };
+    if !same_viewport(camera_a.viewport.as_ref(), &left) {
+        camera_a.viewport = Some(left);
+    }
+
+    let Ok((_, mut camera_b, mut orbit_b)) = comparison_camera.single_mut() else {
+        // First active frame: spawn B matching A's current view
+        commands.spawn((
+            PanOrbitCamera {
+                focus: orbit_a.focus,
+                yaw: orbit_a.yaw,
+                pitch: orbit_a.pitch,
+                radius: orbit_a.radius,
+                button_orbit: orbit_a.button_orbit,
+                button_pan: orbit_a.button_pan,
+                ..default()
+            },
+            Camera3d::default(),
+            Camera {
+                order: 1,
+                viewport: Some(right),
+                ..default()
+            },
+            RenderLayers::layer(COMPARISON_LAYER),
+            Bloom::NATURAL,
+            ComparisonCamera,
+        ));
+        return;
+    };
+
+    if !same_viewport(camera_b.viewport.as_ref(), &right) {
+        camera_b.viewport = Some(right);
+    }
+
+    orbit_b.enabled = !comparison.sync_cameras;
+    if comparison.sync_cameras {
+        orbit_b.focus = orbit_a.focus;
+        orbit_b.target_focus = orbit_a.target_focus;
+        orbit_b.yaw = orbit_a.yaw;
+        orbit_b.target_yaw = orbit_a.target_yaw;
+        orbit_b.pitch = orbit_a.pitch;
+        orbit_b.target_pitch = orbit_a.target_pitch;
+        orbit_b.radius = orbit_a.radius;
+        orbit_b.target_radius = orbit_a.target_radius;
+        orbit_b.force_update = true;
+    }
+}
+
+/// Whether a camera already covers `viewport`, so it is not reassigned (and
+/// change-detected) every frame. `Viewport` has no `PartialEq`.
+fn same_viewport(current: Option<&Viewport>, viewport: &Viewport) -> bool {
+    current.is_some_and(|current| {
+        current.physical_position == viewport.physical_position
+            && current.physical_size == viewport.physical_size
+            && current.depth == viewport.depth
+    })
+}
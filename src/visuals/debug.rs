@@ -0,0 +1,114 @@
+//! Viewport debug overlays for inspecting mesher output.
+//!
+//! Provides a wireframe toggle for the editor meshes and per-vertex normal
+//! gizmos. Wireframes rely on Bevy's `WireframePlugin`, which is native-only.
+
+use crate::visuals::turtle::LSystemMeshTag;
+use bevy::mesh::VertexAttributeValues;
+use bevy::pbr::wireframe::Wireframe;
+use bevy::prelude::*;
+
+/// Upper bound on normal gizmos drawn per frame.
+/// Dense meshes are sampled with a stride to stay under this budget.
+pub const MAX_NORMAL_GIZMOS: usize = 20_000;
+
+/// User-facing toggles for viewport debug overlays.
+#[derive(Resource)]
+pub struct DebugViewConfig {
+    /// Render the editor meshes as wireframes.
+    pub wireframe: bool,
+    /// Draw a gizmo line along each vertex normal.
+    pub show_normals: bool,
+    /// Length of the normal gizmo lines in world units.
+    pub normal_length: f32,
+}
+
+impl Default for DebugViewConfig {
+    fn default() -> Self {
+        Self {
+            wireframe: false,
+            show_normals: false,
+            normal_length: 5.0,
+        }
+    }
+}
+
+/// Returns true if wireframe rendering is available on this platform.
+pub fn wireframe_supported() -> bool {
+    cfg!(not(target_arch = "wasm32"))
+}
+
+/// Adds Bevy's `WireframePlugin` on platforms that support line polygon mode.
+/// On wasm this is a no-op and the wireframe toggle is hidden in the UI.
+pub struct WireframeSupportPlugin;
+
+impl Plugin for WireframeSupportPlugin {
+    fn build(&self, _app: &mut App) {
+        #[cfg(not(target_arch = "wasm32"))]
+        _app.add_plugins(bevy::pbr::wireframe::WireframePlugin::default());
+    }
+}
+
+/// System that adds or removes the `Wireframe` component on editor meshes
+/// so it tracks `DebugViewConfig::wireframe`, including freshly spawned meshes.
+pub fn sync_wireframe(
+    mut commands: Commands,
+    debug_view: Res<DebugViewConfig>,
+    meshes: Query<(Entity, Has<Wireframe>), With<LSystemMeshTag>>,
+) {
+    for (entity, has_wireframe) in &meshes {
+        if debug_view.wireframe && !has_wireframe {
+            commands.entity(entity).insert(Wireframe);
+        } else if !debug_view.wireframe && has_wireframe {
+            commands.entity(entity).remove::<Wireframe>();
+        }
+    }
+}
+
+/// System that draws vertex normals of the editor meshes as gizmo lines.
+pub fn draw_normal_gizmos(
+    debug_view: Res<DebugViewConfig>,
+    mesh_assets: Res<Assets<Mesh>>,
+    meshes: Query<(&Mesh3d, &GlobalTransform, &Visibility), With<LSystemMeshTag>>,
+    mut gizmos: Gizmos,
+) {
+    if !debug_view.show_normals {
+        return;
+    }
+
+    let total_vertices: usize = meshes
+        .iter()
+        .filter_map(|(handle, _, _)| mesh_assets.get(&handle.0))
+        .map(|mesh| mesh.count_vertices())
+        .sum();
+    let stride = total_vertices.div_ceil(MAX_NORMAL_GIZMOS).max(1);
+
+    for (handle, transform, visibility) in &meshes {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+        let Some(mesh) = mesh_assets.get(&handle.0) else {
+            continue;
+        };
+        let (
+            Some(VertexAttributeValues::Float32x3(positions)),
+            Some(VertexAttributeValues::Float32x3(normals)),
+        ) = (
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION),
+            mesh.attribute(Mesh::ATTRIBUTE_NORMAL),
+        )
+        else {
+            continue;
+        };
+
+        for (pos, normal) in positions.iter().zip(normals.iter()).step_by(stride) {
+            let start = transform.transform_point(Vec3::from_array(*pos));
+            let direction = transform.rotation() * Vec3::from_array(*normal);
+            gizmos.ray(
+                start,
+                direction * debug_view.normal_length,
+                Color::srgb(0.2, 0.6, 1.0),
+            );
+        }
+    }
+}
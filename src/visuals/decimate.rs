@@ -0,0 +1,545 @@
+//! Quadric-error mesh decimation before export.
+//!
+//! Each bucket is simplified by collapsing edges in order of the quadric error
+//! metric (Garland and Heckbert): every vertex accumulates the planes of its
+//! triangles, and collapsing a vertex into a neighbour costs the summed squared
+//! distance of the neighbour's position to both vertices' planes. Vertices only
+//! move onto existing ones, so UVs, colors, and normals are never interpolated.
+//!
+//! Vertices sharing a position form one node, so duplicated ring and seam
+//! vertices move together. A collapse is skipped when it would flip a
+//! triangle, pinch the surface, or tear a UV or normal seam, and open borders
+//! such as branch ends are held in place by extra planes along their edges.
+
+use crate::visuals::weld::{float_components, select_vertices};
+use bevy::math::DVec3;
+use bevy::mesh::{Indices, MeshVertexAttribute, PrimitiveTopology, VertexAttributeValues};
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+/// Vertices closer than this share a node.
+const POSITION_TOLERANCE: f32 = 1e-5;
+
+/// Weight of the planes holding open borders in place, relative to the
+/// triangle planes.
+const BORDER_WEIGHT: f64 = 10.0;
+
+/// When decimation stops, per material bucket.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DecimationTarget {
+    /// Collapse until at most this many triangles remain.
+    Triangles(u32),
+    /// Collapse while the geometric error stays below this distance, in world
+    /// units.
+    Error(f32),
+}
+
+impl Default for DecimationTarget {
+    fn default() -> Self {
+        DecimationTarget::Triangles(5_000)
+    }
+}
+
+impl DecimationTarget {
+    /// Kinds offered in the UI, with default values.
+    pub fn presets() -> [DecimationTarget; 2] {
+        [DecimationTarget::default(), DecimationTarget::Error(0.01)]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DecimationTarget::Triangles(_) => "Triangle Count",
+            DecimationTarget::Error(_) => "Error Budget",
+        }
+    }
+}
+
+/// Triangle counts before and after decimation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DecimationStats {
+    pub triangles_before: usize,
+    pub triangles_after: usize,
+}
+
+impl DecimationStats {
+    pub fn add(&mut self, other: DecimationStats) {
+        self.triangles_before += other.triangles_before;
+        self.triangles_after += other.triangles_after;
+    }
+
+    /// Fraction of triangles removed, in `0.0..=1.0`.
+    pub fn reduction(&self) -> f32 {
+        if self.triangles_before == 0 {
+            0.0
+        } else {
+            1.0 - self.triangles_after as f32 / self.triangles_before as f32
+        }
+    }
+}
+
+/// Decimates a triangle-list mesh in place.
+///
+/// Meshes with vertex attributes in formats other than 32-bit float vectors,
+/// such as the joint indices of skinned meshes, are left untouched, like in
+/// [`weld_mesh`](crate::visuals::weld::weld_mesh).
+pub fn decimate_mesh(mesh: &mut Mesh, target: DecimationTarget) -> DecimationStats {
+    let triangles_before = match mesh.indices() {
+        Some(indices) => indices.len() / 3,
+        None => mesh.count_vertices() / 3,
+    };
+    let unchanged = DecimationStats {
+        triangles_before,
+        triangles_after: triangles_before,
+    };
+    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+        return unchanged;
+    }
+    let attributes: Vec<(MeshVertexAttribute, VertexAttributeValues)> = mesh
+        .attributes()
+        .map(|(attribute, values)| (*attribute, values.clone()))
+        .collect();
+    if attributes
+        .iter()
+        .any(|(_, values)| float_components(values).is_none())
+    {
+        return unchanged;
+    }
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return unchanged;
+    };
+    let indices: Vec<usize> = match mesh.indices() {
+        Some(indices) => indices.iter().collect(),
+        None => (0..positions.len()).collect(),
+    };
+
+    let mut simplifier = Simplifier::new(positions, &indices);
+    simplifier.run(target);
+
+    // Keep the vertices still referenced, in first-use order
+    let mut remap = vec![u32::MAX; positions.len()];
+    let mut kept = Vec::new();
+    let mut new_indices = Vec::with_capacity(simplifier.live * 3);
+    for triangle in simplifier.live_triangles() {
+        for vertex in triangle {
+            if remap[vertex] == u32::MAX {
+                remap[vertex] = kept.len() as u32;
+                kept.push(vertex);
+            }
+            new_indices.push(remap[vertex]);
+        }
+    }
+    let triangles_after = new_indices.len() / 3;
+    for (attribute, values) in attributes {
+        mesh.insert_attribute(attribute, select_vertices(&values, &kept));
+    }
+    mesh.insert_indices(Indices::U32(new_indices));
+
+    DecimationStats {
+        triangles_before,
+        triangles_after,
+    }
+}
+
+/// Symmetric 4x4 error quadric, upper triangle stored row by row.
+#[derive(Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    /// Squared distance to the plane `normal · p + d = 0`, scaled by `weight`.
+    fn from_plane(normal: DVec3, d: f64, weight: f64) -> Self {
+        let [a, b, c] = normal.to_array();
+        Quadric(
+            [
+                a * a,
+                a * b,
+                a * c,
+                a * d,
+                b * b,
+                b * c,
+                b * d,
+                c * c,
+                c * d,
+                d * d,
+            ]
+            .map(|v| v * weight),
+        )
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        for (value, other) in self.0.iter_mut().zip(other.0) {
+            *value += other;
+        }
+    }
+
+    fn error(&self, p: DVec3) -> f64 {
+        let [a2, ab, ac, ad, b2, bc, bd, c2, cd, d2] = self.0;
+        let (x, y, z) = (p.x, p.y, p.z);
+        a2 * x * x
+            + b2 * y * y
+            + c2 * z * z
+            + 2.0 * (ab * x * y + ac * x * z + bc * y * z + ad * x + bd * y + cd * z)
+            + d2
+    }
+}
+
+/// A candidate collapse of node `from` into node `to`, valid while neither
+/// node has changed since it was queued.
+struct Collapse {
+    cost: f64,
+    from: usize,
+    to: usize,
+    from_version: u32,
+    to_version: u32,
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cost.total_cmp(&other.cost)
+    }
+}
+
+struct Simplifier {
+    /// Node of each vertex.
+    node_of: Vec<usize>,
+    node_positions: Vec<DVec3>,
+    /// Vertices of each node; emptied once the node is collapsed.
+    node_vertices: Vec<Vec<usize>>,
+    quadrics: Vec<Quadric>,
+    versions: Vec<u32>,
+    triangles: Vec<[usize; 3]>,
+    alive: Vec<bool>,
+    /// Triangles using each vertex, including ones since removed.
+    vertex_triangles: Vec<Vec<usize>>,
+    /// Number of live triangles.
+    live: usize,
+}
+
+impl Simplifier {
+    fn new(positions: &[[f32; 3]], indices: &[usize]) -> Self {
+        let mut node_by_key: HashMap<[i64; 3], usize> = HashMap::default();
+        let mut node_of = Vec::with_capacity(positions.len());
+        let mut node_positions = Vec::new();
+        let mut node_vertices: Vec<Vec<usize>> = Vec::new();
+        for (vertex, p) in positions.iter().enumerate() {
+            let key = p.map(|v| (v / POSITION_TOLERANCE).round() as i64);
+            let node = *node_by_key.entry(key).or_insert_with(|| {
+                node_positions.push(Vec3::from_array(*p).as_dvec3());
+                node_vertices.push(Vec::new());
+                node_positions.len() - 1
+            });
+            node_vertices[node].push(vertex);
+            node_of.push(node);
+        }
+
+        // Triangles that are already degenerate are dropped
+        let triangles: Vec<[usize; 3]> = indices
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]])
+            .filter(|t| {
+                let [a, b, c] = t.map(|v| node_of[v]);
+                a != b && b != c && a != c
+            })
+            .collect();
+        let mut vertex_triangles = vec![Vec::new(); positions.len()];
+        for (index, triangle) in triangles.iter().enumerate() {
+            for &vertex in triangle {
+                vertex_triangles[vertex].push(index);
+            }
+        }
+
+        let mut quadrics = vec![Quadric::default(); node_positions.len()];
+        let mut edges: HashMap<(usize, usize), (u32, usize)> = HashMap::default();
+        for (index, triangle) in triangles.iter().enumerate() {
+            let nodes = triangle.map(|v| node_of[v]);
+            let [p0, p1, p2] = nodes.map(|n| node_positions[n]);
+            let normal = (p1 - p0).cross(p2 - p0).normalize_or_zero();
+            let plane = Quadric::from_plane(normal, -normal.dot(p0), 1.0);
+            for node in nodes {
+                quadrics[node].add(&plane);
+            }
+            for k in 0..3 {
+                let (a, b) = (nodes[k], nodes[(k + 1) % 3]);
+                edges.entry((a.min(b), a.max(b))).or_insert((0, index)).0 += 1;
+            }
+        }
+        // Planes through open edges, perpendicular to their triangle
+        for ((a, b), (count, index)) in edges {
+            if count != 1 {
+                continue;
+            }
+            let [p0, p1, p2] = triangles[index].map(|v| node_positions[node_of[v]]);
+            let face = (p1 - p0).cross(p2 - p0).normalize_or_zero();
+            let (pa, pb) = (node_positions[a], node_positions[b]);
+            let normal = (pb - pa).cross(face).normalize_or_zero();
+            let plane = Quadric::from_plane(normal, -normal.dot(pa), BORDER_WEIGHT);
+            quadrics[a].add(&plane);
+            quadrics[b].add(&plane);
+        }
+
+        let live = triangles.len();
+        Self {
+            node_of,
+            versions: vec![0; node_positions.len()],
+            node_positions,
+            node_vertices,
+            quadrics,
+            alive: vec![true; triangles.len()],
+            triangles,
+            vertex_triangles,
+            live,
+        }
+    }
+
+    fn live_triangles(&self) -> impl Iterator<Item = [usize; 3]> + '_ {
+        self.triangles
+            .iter()
+            .zip(&self.alive)
+            .filter(|&(_, &alive)| alive)
+            .map(|(triangle, _)| *triangle)
+    }
+
+    /// Nodes sharing a live triangle with `node`.
+    fn neighbors(&self, node: usize) -> Vec<usize> {
+        let mut neighbors: Vec<usize> = self.node_vertices[node]
+            .iter()
+            .flat_map(|&vertex| &self.vertex_triangles[vertex])
+            .filter(|&&t| self.alive[t])
+            .flat_map(|&t| self.triangles[t])
+            .map(|vertex| self.node_of[vertex])
+            .filter(|&other| other != node)
+            .collect();
+        neighbors.sort_unstable();
+        neighbors.dedup();
+        neighbors
+    }
+
+    fn candidate(&self, from: usize, to: usize) -> Reverse<Collapse> {
+        let mut quadric = self.quadrics[from];
+        quadric.add(&self.quadrics[to]);
+        Reverse(Collapse {
+            cost: quadric.error(self.node_positions[to]).max(0.0),
+            from,
+            to,
+            from_version: self.versions[from],
+            to_version: self.versions[to],
+        })
+    }
+
+    fn run(&mut self, target: DecimationTarget) {
+        let mut heap = BinaryHeap::new();
+        for node in 0..self.node_positions.len() {
+            for neighbor in self.neighbors(node) {
+                heap.push(self.candidate(node, neighbor));
+            }
+        }
+        while let Some(Reverse(collapse)) = heap.pop() {
+            let done = match target {
+                DecimationTarget::Triangles(max) => self.live <= max as usize,
+                DecimationTarget::Error(max) => collapse.cost > f64::from(max).powi(2),
+            };
+            if done {
+                break;
+            }
+            if self.versions[collapse.from] != collapse.from_version
+                || self.versions[collapse.to] != collapse.to_version
+                || self.node_vertices[collapse.from].is_empty()
+            {
+                continue;
+            }
+            let Some(mapping) = self.collapse_mapping(collapse.from, collapse.to) else {
+                continue;
+            };
+            self.collapse(collapse.from, collapse.to, &mapping);
+            for neighbor in self.neighbors(collapse.to) {
+                heap.push(self.candidate(collapse.to, neighbor));
+                heap.push(self.candidate(neighbor, collapse.to));
+            }
+        }
+    }
+
+    /// The vertex of `to` each vertex of `from` merges into, or `None` when
+    /// the collapse would flip a triangle, pinch the surface, or tear a seam.
+    fn collapse_mapping(&self, from: usize, to: usize) -> Option<Vec<(usize, usize)>> {
+        // More than two shared neighbours means the edge is not a simple
+        // interior or border edge, and collapsing it would pinch the mesh
+        let to_neighbors = self.neighbors(to);
+        let shared = self
+            .neighbors(from)
+            .iter()
+            .filter(|node| to_neighbors.binary_search(node).is_ok())
+            .count();
+        if shared > 2 {
+            return None;
+        }
+
+        let target = self.node_positions[to];
+        let mut mapping = Vec::new();
+        for &vertex in &self.node_vertices[from] {
+            let mut partner = None;
+            let mut used = false;
+            for &t in &self.vertex_triangles[vertex] {
+                if !self.alive[t] {
+                    continue;
+                }
+                used = true;
+                let triangle = self.triangles[t];
+                if let Some(&other) = triangle.iter().find(|&&v| self.node_of[v] == to) {
+                    partner = Some(other);
+                    continue;
+                }
+                let corners = triangle.map(|v| self.node_positions[self.node_of[v]]);
+                let moved = triangle.map(|v| {
+                    if v == vertex {
+                        target
+                    } else {
+                        self.node_positions[self.node_of[v]]
+                    }
+                });
+                let normal = |[p0, p1, p2]: [DVec3; 3]| (p1 - p0).cross(p2 - p0);
+                if normal(corners).dot(normal(moved)) <= 0.0 {
+                    return None;
+                }
+            }
+            // A vertex not connected to `to` lies across a seam from it
+            match partner {
+                Some(other) => mapping.push((vertex, other)),
+                None if used => return None,
+                None => {}
+            }
+        }
+        Some(mapping)
+    }
+
+    fn collapse(&mut self, from: usize, to: usize, mapping: &[(usize, usize)]) {
+        for &(vertex, other) in mapping {
+            for t in std::mem::take(&mut self.vertex_triangles[vertex]) {
+                if !self.alive[t] {
+                    continue;
+                }
+                let triangle = &mut self.triangles[t];
+                for corner in triangle.iter_mut() {
+                    if *corner == vertex {
+                        *corner = other;
+                    }
+                }
+                let [a, b, c] = triangle.map(|v| self.node_of[v]);
+                if a == b || b == c || a == c {
+                    self.alive[t] = false;
+                    self.live -= 1;
+                } else {
+                    self.vertex_triangles[other].push(t);
+                }
+            }
+        }
+        let absorbed = self.quadrics[from];
+        self.quadrics[to].add(&absorbed);
+        self.node_vertices[from].clear();
+        self.versions[from] += 1;
+        self.versions[to] += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::asset::RenderAssetUsages;
+
+    /// A flat `size` x `size` grid of unit quads in the XY plane.
+    fn grid(size: u32) -> Mesh {
+        let side = size + 1;
+        let mut positions = Vec::new();
+        let mut uvs = Vec::new();
+        for y in 0..side {
+            for x in 0..side {
+                positions.push([x as f32, y as f32, 0.0]);
+                uvs.push([x as f32 / size as f32, y as f32 / size as f32]);
+            }
+        }
+        let mut indices = Vec::new();
+        for y in 0..size {
+            for x in 0..size {
+                let i = y * side + x;
+                indices.extend([i, i + 1, i + side + 1, i, i + side + 1, i + side]);
+            }
+        }
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        );
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_NORMAL,
+            vec![[0.0, 0.0, 1.0]; positions.len()],
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+        mesh.insert_indices(Indices::U32(indices));
+        mesh
+    }
+
+    fn area(mesh: &Mesh) -> f32 {
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("mesh has positions");
+        };
+        let indices: Vec<usize> = mesh.indices().unwrap().iter().collect();
+        indices
+            .chunks_exact(3)
+            .map(|t| {
+                let [a, b, c] = [t[0], t[1], t[2]].map(|i| Vec3::from_array(positions[i]));
+                (b - a).cross(c - a).length() / 2.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_flat_grid_collapses_without_folding() {
+        let mut mesh = grid(8);
+        let stats = decimate_mesh(&mut mesh, DecimationTarget::Error(1e-3));
+        assert_eq!(stats.triangles_before, 128);
+        assert!(
+            stats.triangles_after < 32,
+            "a plane needs few triangles, got {}",
+            stats.triangles_after
+        );
+        assert_eq!(mesh.indices().unwrap().len(), stats.triangles_after * 3);
+        // Borders stay put and nothing overlaps, so the area is unchanged
+        assert!((area(&mesh) - 64.0).abs() < 1e-3, "area {}", area(&mesh));
+    }
+
+    #[test]
+    fn test_triangle_target_and_unsupported_meshes() {
+        let mut mesh = grid(8);
+        let stats = decimate_mesh(&mut mesh, DecimationTarget::Triangles(50));
+        assert!(stats.triangles_after <= 50 && stats.triangles_after > 0);
+        assert!(mesh.count_vertices() < 81);
+
+        let mut skinned = grid(2);
+        let vertices = skinned.count_vertices();
+        skinned.insert_attribute(
+            Mesh::ATTRIBUTE_JOINT_INDEX,
+            VertexAttributeValues::Uint16x4(vec![[0u16; 4]; vertices]),
+        );
+        let stats = decimate_mesh(&mut skinned, DecimationTarget::Triangles(1));
+        assert_eq!(stats.triangles_after, stats.triangles_before);
+        assert_eq!(skinned.count_vertices(), vertices);
+    }
+}
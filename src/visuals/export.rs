@@ -4,123 +4,63 @@ use bevy::prelude::*;
 use bevy::tasks::AsyncComputeTaskPool;
 
 use crate::core::config::{
-    ExportConfig, ExportFormat, LSystemConfig, MaterialSettingsMap, PropConfig, PropMeshType,
+    AxisConvention, CancellationFlag, CrossSections, ExportConfig, ExportFilter, ExportFormat,
+    LSystemConfig, MaterialGradient, MaterialGradients, MaterialSettingsMap, OverwritePolicy,
+    PropConfig, PropJitter, PropMeshType, SweepParameter,
 };
-use crate::visuals::assets::PropMeshAssets;
+use crate::core::files::{available_name, save_file_streamed};
+use crate::core::garden::Garden;
+use crate::core::genotype::PlantGenotype;
+use crate::core::includes::IncludeLibrary;
+use crate::core::scatter::variation_seed;
+use crate::core::terrain::Terrain;
+use crate::logic::composition::{GrammarSection, graft_calls, split_systems};
+use crate::logic::growth::{GrowthStep, interpolate_rules};
+use crate::logic::jitter::{jitter_props, jitter_skeleton, thin_props};
+use crate::logic::taper::taper_width_changes;
+use crate::logic::timed;
+use crate::ui::editor_utils::update_define_in_source;
+#[cfg(feature = "nursery")]
+use crate::ui::nursery::NurseryState;
+use crate::ui::toasts::Toasts;
+use crate::visuals::assets::{PropMeshAssets, encode_png};
+use crate::visuals::branch_order::{level_name, split_skeleton_by_level};
+use crate::visuals::decimate::{DecimationStats, DecimationTarget, decimate_mesh};
+use crate::visuals::gltf::{GlbBuilder, GlbOptions};
+use crate::visuals::gradient::apply_material_gradients;
+use crate::visuals::mesher::{CrossSection, SkeletonMesher};
+use crate::visuals::skeleton_json::{self, skeleton_json, write_skeleton_json};
+use crate::visuals::skinning::{PlantRig, build_skinned};
+use crate::visuals::turtle::{interpret_skeleton, turtle_config_with_defaults};
+use crate::visuals::weld::{WeldSettings, WeldStats, weld_mesh};
 
-use bevy_symbios::LSystemMeshBuilder;
-use bevy_symbios::export::{mesh_to_obj, meshes_to_glb};
 use bevy_symbios::materials::MaterialSettings;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use symbios::System;
-use symbios_turtle_3d::{SkeletonProp, TurtleConfig, TurtleInterpreter};
-
-// ---------------------------------------------------------------------------
-// Platform-specific file I/O
-// ---------------------------------------------------------------------------
-
-#[cfg(target_arch = "wasm32")]
-pub fn save_file(filename: &str, content: &str) -> Result<(), String> {
-    use wasm_bindgen::JsCast;
-
-    let window = web_sys::window().ok_or("No browser window available")?;
-    let document = window.document().ok_or("No document available")?;
-
-    let blob_parts = js_sys::Array::new();
-    blob_parts.push(&wasm_bindgen::JsValue::from_str(content));
-
-    let mut options = web_sys::BlobPropertyBag::new();
-    options.type_("text/plain");
-
-    let blob = web_sys::Blob::new_with_str_sequence_and_options(&blob_parts, &options)
-        .map_err(|e| format!("Failed to create blob: {:?}", e))?;
-
-    let url = web_sys::Url::create_object_url_with_blob(&blob)
-        .map_err(|e| format!("Failed to create URL: {:?}", e))?;
-
-    let anchor: web_sys::HtmlAnchorElement = document
-        .create_element("a")
-        .map_err(|e| format!("Failed to create anchor: {:?}", e))?
-        .dyn_into()
-        .map_err(|_| "Element is not an anchor".to_string())?;
-
-    anchor.set_href(&url);
-    anchor.set_download(filename);
-    anchor.click();
-
-    let _ = web_sys::Url::revoke_object_url(&url);
-    Ok(())
-}
-
-#[cfg(not(target_arch = "wasm32"))]
-pub fn save_file(filename: &str, content: &str) -> Result<(), String> {
-    use std::fs;
-    use std::path::Path;
-
-    let export_dir = Path::new("exports");
-    if !export_dir.exists() {
-        fs::create_dir_all(export_dir)
-            .map_err(|e| format!("Failed to create exports directory: {}", e))?;
+use symbios_turtle_3d::{Skeleton, SkeletonProp};
+
+/// Registers the batch export systems. Only built with the `export` feature.
+pub struct ExportPlugin;
+
+impl Plugin for ExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ExportStatus>()
+            .init_resource::<Toasts>()
+            .init_resource::<Garden>()
+            .init_resource::<Terrain>()
+            .add_systems(
+                Update,
+                (batch_export_system, poll_export_status)
+                    .chain()
+                    .after(crate::visuals::turtle::sync_prop_materials)
+                    .before(crate::visuals::debug::sync_wireframe),
+            );
     }
-
-    let path = export_dir.join(filename);
-    fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
-    info!("Exported: {}", path.display());
-    Ok(())
-}
-
-#[cfg(target_arch = "wasm32")]
-pub fn save_file_binary(filename: &str, content: &[u8]) -> Result<(), String> {
-    use wasm_bindgen::JsCast;
-
-    let window = web_sys::window().ok_or("No browser window available")?;
-    let document = window.document().ok_or("No document available")?;
-
-    let uint8arr = js_sys::Uint8Array::new_with_length(content.len() as u32);
-    uint8arr.copy_from(content);
-
-    let parts = js_sys::Array::new();
-    parts.push(&uint8arr);
-
-    let mut options = web_sys::BlobPropertyBag::new();
-    options.type_("application/octet-stream");
-
-    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options)
-        .map_err(|e| format!("Failed to create blob: {:?}", e))?;
-
-    let url = web_sys::Url::create_object_url_with_blob(&blob)
-        .map_err(|e| format!("Failed to create URL: {:?}", e))?;
-
-    let anchor: web_sys::HtmlAnchorElement = document
-        .create_element("a")
-        .map_err(|e| format!("Failed to create anchor: {:?}", e))?
-        .dyn_into()
-        .map_err(|_| "Element is not an anchor".to_string())?;
-
-    anchor.set_href(&url);
-    anchor.set_download(filename);
-    anchor.click();
-
-    let _ = web_sys::Url::revoke_object_url(&url);
-    Ok(())
-}
-
-#[cfg(not(target_arch = "wasm32"))]
-pub fn save_file_binary(filename: &str, content: &[u8]) -> Result<(), String> {
-    use std::fs;
-    use std::path::Path;
-
-    let export_dir = Path::new("exports");
-    if !export_dir.exists() {
-        fs::create_dir_all(export_dir)
-            .map_err(|e| format!("Failed to create exports directory: {}", e))?;
-    }
-
-    let path = export_dir.join(filename);
-    fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
-    info!("Exported: {}", path.display());
-    Ok(())
 }
 
 /// Tracks the result and progress of export operations for UI feedback.
@@ -130,20 +70,55 @@ pub struct ExportStatus {
     pub error: Option<String>,
     /// Number of files successfully exported in the last batch.
     pub last_export_count: usize,
+    /// Files of the last batch left out because they already existed.
+    pub skipped: usize,
     /// Whether a background export is currently running.
     pub exporting: bool,
     /// Progress counter shared with background thread.
     pub progress: Option<Arc<AtomicUsize>>,
     /// Total number of variants being exported.
     pub total: usize,
+    /// Vertex welding totals of the last batch, if welding was enabled.
+    pub weld_stats: Option<WeldStats>,
+    /// Triangle totals of the last batch, if decimation was enabled.
+    pub decimation_stats: Option<DecimationStats>,
+    /// Folder the last batch was written to.
+    pub output_dir: Option<PathBuf>,
+    /// Plants of the last batch that could not be exported, with the reason.
+    pub failures: Vec<ExportFailure>,
+    /// Whether the last batch was stopped before all plants were written.
+    pub cancelled: bool,
+    /// Cleared to stop the running export after the current plant.
+    cancel_flag: Option<CancellationFlag>,
     /// Shared result container for the background export task.
     pending_result: Option<Arc<Mutex<Option<ExportResult>>>>,
 }
 
+impl ExportStatus {
+    /// Stops the running export once the plant being written is finished.
+    pub fn cancel(&self) {
+        if let Some(flag) = &self.cancel_flag {
+            flag.store(false, Ordering::Relaxed);
+        }
+    }
+}
+
+/// A plant that failed to export.
+#[derive(Clone, Debug)]
+pub struct ExportFailure {
+    pub file_stem: String,
+    pub error: String,
+}
+
 /// Result from a background batch export.
 struct ExportResult {
     count: usize,
+    skipped: usize,
     error: Option<String>,
+    failures: Vec<ExportFailure>,
+    cancelled: bool,
+    weld_stats: Option<WeldStats>,
+    decimation_stats: Option<DecimationStats>,
 }
 
 // ---------------------------------------------------------------------------
@@ -291,39 +266,160 @@ fn merge_prop_into_bucket(
 // Batch Export System
 // ---------------------------------------------------------------------------
 
-/// Captures all data needed for a batch export, cloned from ECS resources
-/// so the export can run on a background thread.
-struct BatchExportParams {
+/// One plant to export: its grammar, interpretation parameters, and materials.
+struct ExportPlant {
+    /// Output file name without extension; also the root object name.
+    file_stem: String,
+    /// Description written to the OBJ header.
+    description: String,
+    /// Main grammar, with `#system` sections split off and calls rewritten.
     source_code: String,
+    sections: Vec<GrammarSection>,
     iterations: usize,
+    /// Fraction of a partial step after the iterations, see [`crate::logic::growth`].
+    growth: f32,
     seed: u64,
     step_size: f32,
     default_angle: f32,
     default_width: f32,
     tropism: Option<Vec3>,
     elasticity: f32,
-    variation_count: usize,
-    base_filename: String,
-    format: ExportFormat,
     material_settings: HashMap<u8, MaterialSettings>,
+    /// Vertex color gradients by material slot.
+    gradients: HashMap<u8, MaterialGradient>,
+    /// Branch cross-sections by material slot.
+    profiles: HashMap<u8, CrossSection>,
     prop_meshes: HashMap<u16, PropMeshType>,
+    /// Root position in a scene export; zero for single-plant files.
+    translation: Vec3,
+    /// Root orientation in a scene export.
+    rotation: Quat,
+    /// Uniform root scale in a scene export.
+    scale: f32,
+    /// Everything needed to regenerate the plant, written to metadata sidecars.
+    genotype: PlantGenotype,
+}
+
+impl ExportPlant {
+    /// Exports a nursery individual with its own genotype parameters, materials, and seed.
+    fn from_genotype(
+        genotype: &PlantGenotype,
+        includes: &IncludeLibrary,
+        file_stem: String,
+        description: String,
+    ) -> Result<Self, String> {
+        // Timed grammars are compiled for the genotype's clock, as in the editor
+        let (grammar, iterations) = timed::resolve(
+            &genotype.source_code,
+            "",
+            genotype.iterations,
+            genotype.clock,
+        )
+        .and_then(|(source, _, iterations)| {
+            Ok((split_systems(&includes.expand(&source)?)?, iterations))
+        })
+        .map_err(|e| format!("{}: {}", file_stem, e))?;
+        Ok(Self {
+            file_stem,
+            description,
+            source_code: grammar.main,
+            sections: grammar.sections,
+            iterations,
+            growth: 0.0,
+            seed: genotype.seed,
+            step_size: genotype.step,
+            default_angle: genotype.angle,
+            default_width: genotype.width,
+            tropism: genotype.tropism.map(Vec3::from_array),
+            elasticity: genotype.elasticity,
+            material_settings: genotype.get_material_settings(),
+            gradients: HashMap::new(),
+            profiles: HashMap::new(),
+            prop_meshes: genotype.prop_mappings.clone(),
+            translation: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            scale: 1.0,
+            genotype: genotype.clone(),
+        })
+    }
+}
+
+/// Captures all data needed for a batch export, cloned from ECS resources
+/// so the export can run on a background thread.
+struct BatchExportParams {
+    plants: Vec<ExportPlant>,
+    format: ExportFormat,
+    filter: ExportFilter,
+    split_by_branch_order: bool,
+    branch_order_levels: u32,
+    axes: AxisConvention,
+    obj_decimals: usize,
+    glb_options: GlbOptions,
+    /// Bind GLB branches to a joint rig instead of splitting them by order.
+    skinned: bool,
+    /// Weld tolerances, or `None` to export meshes as generated.
+    weld: Option<WeldSettings>,
+    /// Decimation of every material bucket, after welding, or `None` to
+    /// keep the full mesh.
+    decimation: Option<DecimationTarget>,
+    /// Ring spacing of the branch meshes, see [`SkeletonMesher::ring_spacing`].
+    ring_spacing: Option<f32>,
+    /// Write a `.meta.json` sidecar next to every exported file.
+    write_metadata: bool,
     prop_scale: f32,
+    prop_jitter: HashMap<u16, PropJitter>,
+    /// Fraction of props kept, see [`thin_props`].
+    prop_density: f32,
+    /// PNG of the leaf texture, embedded in GLBs with card props.
+    leaf_texture_png: Option<Vec<u8>>,
+    output_dir: PathBuf,
+    overwrite: OverwritePolicy,
+    /// File stem of a single GLB holding every plant at its translation,
+    /// or `None` to write one file per plant.
+    scene: Option<String>,
+    /// File stem of a single GLB showing the plants one after another as a
+    /// growth animation, or `None` to write one file per plant.
+    animation: Option<String>,
+    /// Seconds each plant of the animation is shown.
+    frame_duration: f32,
     /// Pre-extracted prop mesh data (cloned from Assets<Mesh>), keyed by PropMeshType.
     extracted_prop_meshes: HashMap<PropMeshType, Mesh>,
 }
 
 /// System that dispatches batch export to a background thread when requested.
+///
+/// Exports either seed variations of the editor plant or the selected nursery
+/// champions with their own genotypes, one file per plant, or the whole nursery
+/// population or the garden as one GLB scene.
 #[allow(clippy::too_many_arguments)]
 pub fn batch_export_system(
     mut export_config: ResMut<ExportConfig>,
     mut export_status: ResMut<ExportStatus>,
+    #[cfg(feature = "nursery")] mut nursery: ResMut<NurseryState>,
+    mut garden: ResMut<Garden>,
+    terrain: Res<Terrain>,
     lsystem_config: Res<LSystemConfig>,
     material_settings: Res<MaterialSettingsMap>,
+    gradients: Res<MaterialGradients>,
+    cross_sections: Res<CrossSections>,
     prop_config: Res<PropConfig>,
     prop_assets: Res<PropMeshAssets>,
     mesh_assets: Res<Assets<Mesh>>,
+    images: Res<Assets<Image>>,
+    includes: Res<IncludeLibrary>,
 ) {
-    if !export_config.export_requested {
+    #[cfg(feature = "nursery")]
+    let (export_selected, export_scene) =
+        (nursery.export_requested, nursery.scene_export_requested);
+    #[cfg(not(feature = "nursery"))]
+    let (export_selected, export_scene) = (false, false);
+    #[cfg(feature = "nursery")]
+    {
+        nursery.export_requested = false;
+        nursery.scene_export_requested = false;
+    }
+    let export_garden = std::mem::take(&mut garden.export_requested);
+    if !export_config.export_requested && !export_selected && !export_scene && !export_garden {
         return;
     }
     export_config.export_requested = false;
@@ -334,12 +430,89 @@ pub fn batch_export_system(
 
     export_status.error = None;
     export_status.last_export_count = 0;
+    export_status.skipped = 0;
+    export_status.weld_stats = None;
+    export_status.decimation_stats = None;
+    export_status.failures.clear();
+    export_status.cancelled = false;
+
+    let plants = match (export_scene, export_selected) {
+        _ if export_garden => {
+            garden_plants(&garden, &terrain, &export_config.base_filename, &includes)
+        }
+        #[cfg(feature = "nursery")]
+        (true, _) => nursery_scene_plants(&nursery, &export_config.base_filename, &includes),
+        #[cfg(feature = "nursery")]
+        (false, true) => nursery_plants(&nursery, &export_config.base_filename, &includes),
+        _ => editor_plants(
+            &lsystem_config,
+            &material_settings,
+            &gradients,
+            &cross_sections,
+            &prop_config,
+            &export_config,
+            &includes,
+        ),
+    };
+    let plants = match plants {
+        Ok(plants) if plants.is_empty() && export_garden => {
+            export_status.error = Some("The garden is empty".into());
+            return;
+        }
+        Ok(plants) if plants.is_empty() && export_scene => {
+            export_status.error = Some("The nursery population is empty".into());
+            return;
+        }
+        Ok(plants) if plants.is_empty() && export_selected => {
+            export_status.error = Some("No individuals selected".into());
+            return;
+        }
+        Ok(plants) => plants,
+        Err(e) => {
+            export_status.error = Some(e);
+            return;
+        }
+    };
+
+    // A scene is one GLB named after the generation, whatever the export format
+    #[cfg(feature = "nursery")]
+    let scene = export_scene.then(|| {
+        format!(
+            "{}_gen{:03}_scene",
+            export_config.base_filename, nursery.generation
+        )
+    });
+    #[cfg(not(feature = "nursery"))]
+    let scene = None;
+    let scene = if export_garden {
+        Some(format!("{}_garden", export_config.base_filename))
+    } else {
+        scene
+    };
+    let animation = (export_config.animate
+        && export_config.format == ExportFormat::Glb
+        && scene.is_none()
+        && !export_selected)
+        .then(|| format!("{}_growth", export_config.base_filename));
+
     export_status.exporting = true;
-    export_status.total = export_config.variation_count;
+    export_status.total = plants.len();
+
+    // A batch subfolder is always new, so runs never mix
+    let mut output_dir = export_config.output_dir.clone();
+    if export_config.batch_subfolder {
+        let folder = format!(
+            "{}_{}",
+            chrono::Local::now().format("%Y-%m-%d"),
+            export_config.base_filename
+        );
+        output_dir = output_dir.join(available_name(&output_dir, &folder));
+    }
+    export_status.output_dir = Some(output_dir.clone());
 
     // Pre-extract prop mesh data from assets so the background thread has it
     let mut extracted_prop_meshes = HashMap::new();
-    for (_, mesh_type) in prop_config.prop_meshes.iter() {
+    for mesh_type in plants.iter().flat_map(|plant| plant.prop_meshes.values()) {
         if !extracted_prop_meshes.contains_key(mesh_type)
             && let Some(handle) = prop_assets.meshes.get(mesh_type)
             && let Some(mesh) = mesh_assets.get(handle)
@@ -347,40 +520,70 @@ pub fn batch_export_system(
             extracted_prop_meshes.insert(*mesh_type, mesh.clone());
         }
     }
+    // Card props carry the leaf texture into GLBs
+    let leaf_texture_png = extracted_prop_meshes
+        .keys()
+        .any(PropMeshType::is_cutout)
+        .then(|| images.get(&prop_assets.leaf_texture))
+        .flatten()
+        .and_then(|image| {
+            encode_png(image)
+                .inspect_err(|e| warn!("Exporting card props without texture: {}", e))
+                .ok()
+        });
 
     let params = BatchExportParams {
-        source_code: lsystem_config.source_code.clone(),
-        iterations: lsystem_config.iterations,
-        seed: lsystem_config.seed,
-        step_size: lsystem_config.step_size,
-        default_angle: lsystem_config.default_angle,
-        default_width: lsystem_config.default_width,
-        tropism: lsystem_config.tropism,
-        elasticity: lsystem_config.elasticity,
-        variation_count: export_config.variation_count,
-        base_filename: export_config.base_filename.clone(),
+        plants,
         format: export_config.format,
-        material_settings: material_settings.settings.clone(),
-        prop_meshes: prop_config.prop_meshes.clone(),
+        filter: export_config.filter.clone(),
+        split_by_branch_order: export_config.split_by_branch_order,
+        branch_order_levels: export_config.branch_order_levels,
+        axes: export_config.axes,
+        obj_decimals: export_config.obj_decimals,
+        glb_options: GlbOptions {
+            compact_indices: export_config.compact_indices,
+            quantize_positions: export_config.quantize_positions,
+        },
+        skinned: export_config.skinned,
+        weld: export_config.weld_vertices.then_some(export_config.weld),
+        decimation: export_config.decimate.then_some(export_config.decimation),
+        ring_spacing: lsystem_config.ring_spacing,
+        write_metadata: export_config.write_metadata,
         prop_scale: prop_config.prop_scale,
+        prop_jitter: prop_config.prop_jitter.clone(),
+        prop_density: export_config.prop_density,
+        leaf_texture_png,
+        output_dir,
+        overwrite: export_config.overwrite,
+        scene,
+        animation,
+        frame_duration: export_config.frame_duration,
         extracted_prop_meshes,
     };
 
     let progress = Arc::new(AtomicUsize::new(0));
+    let running: CancellationFlag = Arc::new(AtomicBool::new(true));
     let result: Arc<Mutex<Option<ExportResult>>> = Arc::new(Mutex::new(None));
 
     export_status.progress = Some(progress.clone());
+    export_status.cancel_flag = Some(running.clone());
     export_status.pending_result = Some(result.clone());
 
     info!(
-        "Starting async batch export: {} variations as {}",
-        params.variation_count,
-        params.format.name()
+        "Starting async batch export: {} plants as {}",
+        params.plants.len(),
+        if params.scene.is_some() {
+            "one GLB scene"
+        } else if params.animation.is_some() {
+            "one animated GLB"
+        } else {
+            params.format.name()
+        }
     );
 
     let pool = AsyncComputeTaskPool::get();
     pool.spawn(async move {
-        let export_result = perform_batch_export(&params, &progress);
+        let export_result = perform_batch_export(&params, &progress, &running);
         if let Ok(mut guard) = result.lock() {
             *guard = Some(export_result);
         }
@@ -388,165 +591,1216 @@ pub fn batch_export_system(
     .detach();
 }
 
-/// Performs the full batch export on a background thread.
-fn perform_batch_export(params: &BatchExportParams, progress: &Arc<AtomicUsize>) -> ExportResult {
-    let mut count = 0usize;
-
-    for variant_idx in 0..params.variation_count {
-        let mut sys = System::new();
-        let variant_seed = if variant_idx == 0 {
-            // First variant uses the editor's exact seed for an identical result
-            params.seed
-        } else {
-            use std::hash::{Hash, Hasher};
-            let mut hasher = std::collections::hash_map::DefaultHasher::new();
-            params.seed.hash(&mut hasher);
-            variant_idx.hash(&mut hasher);
-            hasher.finish()
-        };
-        sys.set_seed(variant_seed);
+/// Seed variations of the editor plant.
+fn editor_plants(
+    lsystem_config: &LSystemConfig,
+    material_settings: &MaterialSettingsMap,
+    gradients: &MaterialGradients,
+    cross_sections: &CrossSections,
+    prop_config: &PropConfig,
+    export_config: &ExportConfig,
+    includes: &IncludeLibrary,
+) -> Result<Vec<ExportPlant>, String> {
+    let (source, _, iterations) = timed::resolve(
+        &lsystem_config.source_code,
+        "",
+        lsystem_config.iterations,
+        lsystem_config.clock,
+    )?;
+    let grammar = includes
+        .expand(&source)
+        .and_then(|source| split_systems(&source))?;
+    let count = export_config.variation_count;
+    let sweep = &export_config.sweep;
+    let genotype = PlantGenotype::from_editor(lsystem_config, material_settings, prop_config);
+
+    Ok((0..count)
+        .map(|variant_idx| {
+            let mut plant = ExportPlant {
+                file_stem: format!("{}_{:02}", export_config.base_filename, variant_idx + 1),
+                description: format!("Variant {} of {}", variant_idx + 1, count),
+                source_code: grammar.main.clone(),
+                sections: grammar.sections.clone(),
+                iterations,
+                growth: lsystem_config.growth,
+                seed: lsystem_config.seed,
+                step_size: lsystem_config.step_size,
+                default_angle: lsystem_config.default_angle,
+                default_width: lsystem_config.default_width,
+                tropism: lsystem_config.tropism,
+                elasticity: lsystem_config.elasticity,
+                material_settings: material_settings.settings.clone(),
+                gradients: gradients.gradients.clone(),
+                profiles: cross_sections.profiles.clone(),
+                prop_meshes: prop_config.prop_meshes.clone(),
+                translation: Vec3::ZERO,
+                rotation: Quat::IDENTITY,
+                scale: 1.0,
+                genotype: genotype.clone(),
+            };
+            let value = sweep.value(variant_idx, count);
+            match &sweep.parameter {
+                // First variant uses the editor's exact seed for an identical result
+                SweepParameter::Seed => {
+                    plant.seed = variation_seed(lsystem_config.seed, variant_idx);
+                    plant.genotype.seed = plant.seed;
+                }
+                SweepParameter::Constant(name) => {
+                    plant.source_code = with_define(&plant.source_code, name, value);
+                    plant.genotype.source_code =
+                        with_define(&plant.genotype.source_code, name, value);
+                }
+                // Animations grow smoothly through the fractional steps
+                SweepParameter::Iterations if export_config.animate => {
+                    let value = sweep.continuous_value(variant_idx, count).max(0.0);
+                    plant.iterations = value.floor() as usize;
+                    plant.growth = value.fract();
+                    plant.genotype.iterations = plant.iterations;
+                }
+                SweepParameter::Iterations => {
+                    plant.iterations = value as usize;
+                    plant.growth = 0.0;
+                    plant.genotype.iterations = plant.iterations;
+                }
+            }
+            if sweep.parameter != SweepParameter::Seed {
+                let suffix = sweep.suffix(value);
+                plant.file_stem = format!("{}_{}", export_config.base_filename, suffix);
+                plant.description = format!("{} = {}", sweep.parameter.name(), value);
+            }
+            // Frame numbers that DCC sequence importers pick up
+            if export_config.animate {
+                plant.file_stem = format!("{}_{:04}", export_config.base_filename, variant_idx + 1);
+            }
+            plant
+        })
+        .collect())
+}
 
-        let mut axiom_set = false;
+/// Sets a `#define` constant in `source`, adding the definition at the top
+/// when the grammar does not have it yet.
+fn with_define(source: &str, name: &str, value: f32) -> String {
+    let defined = source.lines().any(|line| {
+        let mut parts = line.split_whitespace();
+        parts.next() == Some("#define") && parts.next() == Some(name)
+    });
+    if defined {
+        update_define_in_source(source, name, value)
+    } else {
+        format!("#define {} {}\n{}", name, value, source)
+    }
+}
 
-        for line in params.source_code.lines() {
-            let trimmed = line.trim();
-            if trimmed.is_empty() || trimmed.starts_with("//") {
-                continue;
-            }
+/// The selected nursery champions, named by generation and population index.
+#[cfg(feature = "nursery")]
+fn nursery_plants(
+    nursery: &NurseryState,
+    base_filename: &str,
+    includes: &IncludeLibrary,
+) -> Result<Vec<ExportPlant>, String> {
+    let mut selected: Vec<usize> = nursery.selected.iter().copied().collect();
+    selected.sort_unstable();
+    selected
+        .into_iter()
+        .filter_map(|index| Some((index, nursery.population.get(index)?)))
+        .map(|(index, phenotype)| {
+            ExportPlant::from_genotype(
+                &phenotype.genotype,
+                includes,
+                format!(
+                    "{}_gen{:03}_{:02}",
+                    base_filename,
+                    nursery.generation,
+                    index + 1
+                ),
+                format!(
+                    "Nursery generation {}, individual {}",
+                    nursery.generation,
+                    index + 1
+                ),
+            )
+        })
+        .collect()
+}
 
-            if trimmed.starts_with('#') {
-                let _ = sys.add_directive(trimmed);
-                continue;
-            }
+/// The whole nursery population, each plant at its position in the 3D grid.
+#[cfg(feature = "nursery")]
+fn nursery_scene_plants(
+    nursery: &NurseryState,
+    base_filename: &str,
+    includes: &IncludeLibrary,
+) -> Result<Vec<ExportPlant>, String> {
+    nursery
+        .population
+        .iter()
+        .enumerate()
+        .map(|(index, phenotype)| {
+            let mut plant = ExportPlant::from_genotype(
+                &phenotype.genotype,
+                includes,
+                format!("{}_{:02}", base_filename, index + 1),
+                format!(
+                    "Nursery generation {}, individual {}",
+                    nursery.generation,
+                    index + 1
+                ),
+            )?;
+            plant.translation = nursery.cell_position(index);
+            Ok(plant)
+        })
+        .collect()
+}
 
-            if trimmed.starts_with("omega:") {
-                let axiom_src = trimmed.trim_start_matches("omega:").trim();
-                if sys.set_axiom(axiom_src).is_ok() {
-                    axiom_set = true;
-                }
-                continue;
-            }
+/// The garden's plants at their placements on the terrain, numbered in
+/// garden order.
+fn garden_plants(
+    garden: &Garden,
+    terrain: &Terrain,
+    base_filename: &str,
+    includes: &IncludeLibrary,
+) -> Result<Vec<ExportPlant>, String> {
+    garden
+        .plants
+        .iter()
+        .enumerate()
+        .map(|(index, garden_plant)| {
+            let mut plant = ExportPlant::from_genotype(
+                &garden_plant.genotype,
+                includes,
+                format!("{}_{:02}", base_filename, index + 1),
+                format!("Garden plant {}: {}", index + 1, garden_plant.name),
+            )?;
+            let transform = terrain.place(garden_plant.transform());
+            plant.translation = transform.translation;
+            plant.rotation = transform.rotation;
+            plant.scale = garden_plant.scale;
+            Ok(plant)
+        })
+        .collect()
+}
 
-            let _ = sys.add_rule(trimmed);
+/// Derives one grammar, with a partial growth step if `growth` is above zero,
+/// and interprets it into a jittered skeleton. Lines that fail to parse are skipped;
+/// returns `None` without an axiom or on derivation errors.
+fn build_skeleton(
+    source: &str,
+    iterations: usize,
+    growth: f32,
+    seed: u64,
+    plant: &ExportPlant,
+) -> Option<Skeleton> {
+    let mut sys = System::new();
+    sys.set_seed(seed);
+    let mut axiom_set = false;
+
+    for line in source.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with("//") {
+            continue;
         }
 
-        if !axiom_set {
-            progress.fetch_add(1, Ordering::Relaxed);
+        if trimmed.starts_with('#') {
+            let _ = sys.add_directive(trimmed);
             continue;
         }
 
-        if sys.derive(params.iterations).is_err() {
-            progress.fetch_add(1, Ordering::Relaxed);
+        if trimmed.starts_with("omega:") {
+            let axiom_src = trimmed.trim_start_matches("omega:").trim();
+            if sys.set_axiom(axiom_src).is_ok() {
+                axiom_set = true;
+            }
             continue;
         }
 
-        // Configure turtle interpreter
-        let default_step = sys
-            .constants
-            .get("step")
-            .map(|&s| s as f32)
-            .unwrap_or(params.step_size);
-
-        let default_angle = sys
-            .constants
-            .get("angle")
-            .map(|&a| a as f32)
-            .unwrap_or(params.default_angle)
-            .to_radians();
-
-        let initial_width = sys
-            .constants
-            .get("width")
-            .map(|&w| w as f32)
-            .unwrap_or(params.default_width);
-
-        let turtle_config = TurtleConfig {
-            default_step,
-            default_angle,
-            initial_width,
-            tropism: params.tropism,
-            elasticity: params.elasticity,
-            max_stack_depth: 1024,
-        };
+        let _ = sys.add_rule(trimmed);
+    }
 
-        let mut interpreter = TurtleInterpreter::new(turtle_config);
-        interpreter.populate_standard_symbols(&sys.interner);
+    if !axiom_set || sys.derive(iterations).is_err() {
+        return None;
+    }
+    if growth > 0.0 {
+        sys.rules.clear();
+        let step = GrowthStep {
+            fraction: growth,
+            default_step: plant.step_size,
+        };
+        for rule in interpolate_rules(source, step) {
+            let _ = sys.add_rule(&rule);
+        }
+        sys.derive(1).ok()?;
+    }
 
-        let skeleton = interpreter.build_skeleton(&sys.state);
-        let builder = LSystemMeshBuilder::new().with_resolution(8);
-        let mut mesh_buckets = builder.build(&skeleton);
+    let turtle_config = turtle_config_with_defaults(
+        &sys,
+        plant.step_size,
+        plant.default_angle,
+        plant.default_width,
+        plant.tropism,
+        plant.elasticity,
+    );
+    let mut skeleton = interpret_skeleton(&sys, turtle_config);
+    jitter_skeleton(&mut skeleton, seed);
+    Some(skeleton)
+}
 
-        // Merge props using pre-extracted mesh data
-        for prop in &skeleton.props {
-            let mesh_type = params
-                .prop_meshes
-                .get(&prop.prop_id)
-                .copied()
-                .unwrap_or_default();
+/// Derives one plant with its grafted sections, jitter, width tapers, and
+/// gradients. Returns `None` when the grammar has no axiom or fails to derive.
+fn build_plant_skeleton(params: &BatchExportParams, plant: &ExportPlant) -> Option<Skeleton> {
+    let mut skeleton = build_skeleton(
+        &plant.source_code,
+        plant.iterations,
+        plant.growth,
+        plant.seed,
+        plant,
+    )?;
+    if !plant.sections.is_empty() {
+        // Sections derive with the same seed offsets as in the editor
+        let sections: Vec<Option<Skeleton>> = plant
+            .sections
+            .iter()
+            .enumerate()
+            .map(|(i, section)| {
+                build_skeleton(
+                    &section.source,
+                    section.iterations.unwrap_or(plant.iterations),
+                    if section.iterations.is_none() {
+                        plant.growth
+                    } else {
+                        0.0
+                    },
+                    plant.seed.wrapping_add(i as u64 + 1),
+                    plant,
+                )
+            })
+            .collect();
+        graft_calls(&mut skeleton, &sections);
+    }
+    jitter_props(&mut skeleton.props, &params.prop_jitter, plant.seed);
+    thin_props(&mut skeleton.props, params.prop_density, plant.seed);
+    taper_width_changes(&mut skeleton);
+    apply_material_gradients(&mut skeleton, &plant.gradients);
+    Some(skeleton)
+}
 
-            if let Some(source_mesh) = params.extracted_prop_meshes.get(&mesh_type) {
-                merge_prop_into_bucket(&mut mesh_buckets, source_mesh, prop, params.prop_scale);
+/// Derives, meshes, axis-converts, and optionally welds and decimates one
+/// plant. Returns `None` when the grammar has no axiom or fails to derive.
+fn build_plant_groups(
+    params: &BatchExportParams,
+    plant: &ExportPlant,
+    format: ExportFormat,
+) -> Option<(Vec<MeshGroup>, MeshStats)> {
+    let skeleton = build_plant_skeleton(params, plant)?;
+    // GLB always nests geometry by branch order; OBJ has no hierarchy, so
+    // splitting there is opt-in to keep single-object imports simple.
+    let mut groups = match format {
+        // Skeleton files skip meshing, see export_plant_skeleton
+        ExportFormat::Obj | ExportFormat::Skeleton => build_export_groups(
+            &skeleton,
+            params,
+            plant,
+            params.split_by_branch_order,
+            false,
+            false,
+        ),
+        ExportFormat::Glb => {
+            build_export_groups(&skeleton, params, plant, true, true, params.skinned)
+        }
+    };
+    let mut stats = MeshStats::default();
+    for group in &mut groups {
+        if let Some(rig) = &mut group.rig {
+            rig.convert_axes(params.axes);
+        }
+        for mesh in group.buckets.values_mut() {
+            convert_mesh_axes(mesh, params.axes);
+            if let Some(settings) = params.weld {
+                stats.weld.add(weld_mesh(mesh, settings));
+            }
+            if let Some(target) = params.decimation {
+                stats.decimation.add(decimate_mesh(mesh, target));
             }
         }
+    }
+    Some((groups, stats))
+}
 
-        let filename = format!(
-            "{}_{:02}.{}",
-            params.base_filename,
-            variant_idx + 1,
-            params.format.extension()
-        );
+/// Weld and decimation counts of one plant's meshes.
+#[derive(Clone, Copy, Default)]
+struct MeshStats {
+    weld: WeldStats,
+    decimation: DecimationStats,
+}
+
+/// Running weld and decimation totals of a batch, for the steps enabled.
+struct BatchStats {
+    weld: Option<WeldStats>,
+    decimation: Option<DecimationStats>,
+}
+
+impl BatchStats {
+    fn new(params: &BatchExportParams) -> Self {
+        Self {
+            weld: params.weld.map(|_| WeldStats::default()),
+            decimation: params.decimation.map(|_| DecimationStats::default()),
+        }
+    }
+
+    fn add(&mut self, stats: MeshStats) {
+        if let Some(total) = &mut self.weld {
+            total.add(stats.weld);
+        }
+        if let Some(total) = &mut self.decimation {
+            total.add(stats.decimation);
+        }
+    }
+}
 
-        let save_result = match params.format {
-            ExportFormat::Obj => {
-                let mut combined_obj = String::new();
-                combined_obj.push_str("# Exported from L-System Explorer\n");
-                combined_obj.push_str(&format!(
-                    "# Variant {} of {}\n\n",
-                    variant_idx + 1,
-                    params.variation_count
-                ));
-
-                let mut vertex_offset = 0u32;
-                for (material_id, mesh) in &mesh_buckets {
-                    let object_name = format!(
-                        "{}_{:02}_mat{}",
-                        params.base_filename,
-                        variant_idx + 1,
-                        material_id
-                    );
-                    combined_obj.push_str(&mesh_to_obj(mesh, &object_name, vertex_offset));
-                    vertex_offset += mesh.count_vertices() as u32;
+/// Output file name for `stem` under the overwrite policy, or `None` when
+/// the file exists and is to be skipped.
+fn output_filename(params: &BatchExportParams, stem: &str, format: ExportFormat) -> Option<String> {
+    let filename = format!("{}.{}", stem, format.extension());
+    match params.overwrite {
+        OverwritePolicy::Rename => Some(available_name(&params.output_dir, &filename)),
+        OverwritePolicy::Overwrite => Some(filename),
+        OverwritePolicy::Skip => (!params.output_dir.join(&filename).exists()).then_some(filename),
+    }
+}
+
+fn perform_batch_export(
+    params: &BatchExportParams,
+    progress: &Arc<AtomicUsize>,
+    running: &AtomicBool,
+) -> ExportResult {
+    if let Some(scene) = &params.scene {
+        return perform_scene_export(params, scene, progress, running);
+    }
+    if let Some(animation) = &params.animation {
+        return perform_animation_export(params, animation, progress, running);
+    }
+
+    let mut count = 0usize;
+    let mut skipped = 0usize;
+    let mut failures = Vec::new();
+    let mut cancelled = false;
+    let mut stats = BatchStats::new(params);
+
+    for plant in &params.plants {
+        if !running.load(Ordering::Relaxed) {
+            cancelled = true;
+            break;
+        }
+        let result = match params.format {
+            ExportFormat::Skeleton => export_plant_skeleton(params, plant),
+            _ => export_plant_mesh(params, plant, &mut stats),
+        };
+        match result {
+            Ok(None) => skipped += 1,
+            Ok(Some(filename)) => {
+                count += 1;
+                if params.write_metadata
+                    && let Err(error) = write_metadata(params, &filename, &[plant])
+                {
+                    warn!("Failed to write metadata for {}: {}", filename, error);
+                    failures.push(ExportFailure {
+                        file_stem: plant.file_stem.clone(),
+                        error: format!("Metadata sidecar: {}", error),
+                    });
                 }
+            }
+            Err(error) => {
+                warn!("Failed to export {}: {}", plant.file_stem, error);
+                failures.push(ExportFailure {
+                    file_stem: plant.file_stem.clone(),
+                    error,
+                });
+            }
+        }
+        progress.fetch_add(1, Ordering::Relaxed);
+    }
 
-                save_file(&filename, &combined_obj)
+    // Partial failures are listed per plant; only a batch with nothing written fails
+    let error = match failures.as_slice() {
+        [] => None,
+        _ if count > 0 || skipped > 0 || cancelled => None,
+        [only] => Some(only.error.clone()),
+        [first, ..] => Some(format!(
+            "All {} plants failed, e.g. {}: {}",
+            failures.len(),
+            first.file_stem,
+            first.error
+        )),
+    };
+    ExportResult {
+        count,
+        skipped,
+        error,
+        failures,
+        cancelled,
+        weld_stats: stats.weld,
+        decimation_stats: stats.decimation,
+    }
+}
+
+/// Meshes one plant and writes it as OBJ or GLB, adding its weld and
+/// decimation counts. Returns the name of the written file, or `None` if it
+/// was skipped.
+fn export_plant_mesh(
+    params: &BatchExportParams,
+    plant: &ExportPlant,
+    stats: &mut BatchStats,
+) -> Result<Option<String>, String> {
+    let Some(filename) = output_filename(params, &plant.file_stem, params.format) else {
+        return Ok(None);
+    };
+    let (groups, variant_stats) = build_plant_groups(params, plant, params.format)
+        .ok_or_else(|| "The grammar has no axiom or failed to derive".to_string())?;
+    stats.add(variant_stats);
+    if groups.is_empty() {
+        return Err("Nothing to export: all geometry is excluded by the filter".into());
+    }
+
+    let object_prefix = &plant.file_stem;
+    save_file_streamed(&params.output_dir, &filename, |out| match params.format {
+        ExportFormat::Obj => {
+            writeln!(out, "# Exported from L-System Explorer")?;
+            writeln!(out, "# {}", plant.description)?;
+            if params.weld.is_some() {
+                writeln!(
+                    out,
+                    "# Welded {} -> {} vertices",
+                    variant_stats.weld.vertices_before, variant_stats.weld.vertices_after
+                )?;
+            }
+            if params.decimation.is_some() {
+                writeln!(
+                    out,
+                    "# Decimated {} -> {} triangles",
+                    variant_stats.decimation.triangles_before,
+                    variant_stats.decimation.triangles_after
+                )?;
             }
-            ExportFormat::Glb => {
-                let glb_data = meshes_to_glb(&mesh_buckets, &params.material_settings);
-                save_file_binary(&filename, &glb_data)
+            writeln!(out)?;
+            write_groups_obj(out, &groups, object_prefix, params.obj_decimals)
+        }
+        ExportFormat::Glb => write_groups_glb(
+            out,
+            &groups,
+            object_prefix,
+            &plant.material_settings,
+            params.glb_options,
+            params.leaf_texture_png.as_deref(),
+        ),
+        ExportFormat::Skeleton => unreachable!("skeletons are written unmeshed"),
+    })?;
+    Ok(Some(filename))
+}
+
+/// Writes one plant's unmeshed skeleton as JSON, returning the file name
+/// or `None` if it was skipped.
+fn export_plant_skeleton(
+    params: &BatchExportParams,
+    plant: &ExportPlant,
+) -> Result<Option<String>, String> {
+    let Some(filename) = output_filename(params, &plant.file_stem, ExportFormat::Skeleton) else {
+        return Ok(None);
+    };
+    let skeleton = build_plant_skeleton(params, plant)
+        .ok_or_else(|| "The grammar has no axiom or failed to derive".to_string())?;
+    let document = skeleton_json(
+        &skeleton,
+        &params.filter,
+        params.axes,
+        params.prop_scale,
+        &plant.description,
+    );
+    if skeleton_json::is_empty(&document) {
+        return Err("Nothing to export: all geometry is excluded by the filter".into());
+    }
+    save_file_streamed(&params.output_dir, &filename, |out| {
+        write_skeleton_json(out, &document)
+    })?;
+    Ok(Some(filename))
+}
+
+/// Writes the `.meta.json` sidecar of an exported file.
+fn write_metadata(
+    params: &BatchExportParams,
+    filename: &str,
+    plants: &[&ExportPlant],
+) -> Result<(), String> {
+    let document = metadata_json(params, filename, plants);
+    let stem = filename.rsplit_once('.').map_or(filename, |(stem, _)| stem);
+    save_file_streamed(&params.output_dir, &format!("{}.meta.json", stem), |out| {
+        serde_json::to_writer_pretty(&mut *out, &document)?;
+        writeln!(out)
+    })
+}
+
+/// Sidecar document recording everything needed to regenerate `filename`:
+/// each plant's genotype (grammar, parameters, seed, materials, props), the
+/// vertex color and cross-section settings it was meshed with, and the
+/// export options. The genotype can be loaded back with the genotype importer.
+fn metadata_json(params: &BatchExportParams, filename: &str, plants: &[&ExportPlant]) -> Value {
+    let plants: Vec<Value> = plants
+        .iter()
+        .map(|plant| {
+            let gradients: BTreeMap<u8, &MaterialGradient> =
+                plant.gradients.iter().map(|(&slot, g)| (slot, g)).collect();
+            let profiles: BTreeMap<u8, &CrossSection> =
+                plant.profiles.iter().map(|(&slot, p)| (slot, p)).collect();
+            json!({
+                "name": plant.file_stem,
+                "description": plant.description,
+                "translation": plant.translation.to_array(),
+                "genotype": plant.genotype,
+                "gradients": gradients,
+                "cross_sections": profiles,
+            })
+        })
+        .collect();
+    // Scenes are always GLB, whatever the selected format
+    let format = if params.scene.is_some() || params.animation.is_some() {
+        ExportFormat::Glb
+    } else {
+        params.format
+    };
+    json!({
+        "generator": "L-System Explorer",
+        "version": env!("CARGO_PKG_VERSION"),
+        "file": filename,
+        "exported_at": chrono::Local::now().to_rfc3339(),
+        "export": {
+            "format": format.name(),
+            "axes": params.axes.name(),
+            "split_by_branch_order": params.split_by_branch_order,
+            "branch_order_levels": params.branch_order_levels,
+            "skinned": params.skinned,
+            "weld": params.weld.is_some(),
+            "decimation": params.decimation.map(|target| match target {
+                DecimationTarget::Triangles(max) => json!({ "max_triangles": max }),
+                DecimationTarget::Error(max) => json!({ "max_error": max }),
+            }),
+            "prop_scale": params.prop_scale,
+            "prop_density": params.prop_density,
+        },
+        "plants": plants,
+    })
+}
+
+/// Writes every plant into one GLB named `scene`, each under its own root
+/// node at its translation and with its own material set.
+fn perform_scene_export(
+    params: &BatchExportParams,
+    scene: &str,
+    progress: &Arc<AtomicUsize>,
+    running: &AtomicBool,
+) -> ExportResult {
+    let Some(filename) = output_filename(params, scene, ExportFormat::Glb) else {
+        return ExportResult {
+            count: 0,
+            skipped: 1,
+            error: None,
+            failures: Vec::new(),
+            cancelled: false,
+            weld_stats: None,
+            decimation_stats: None,
+        };
+    };
+    let mut stats = BatchStats::new(params);
+    let mut plants = Vec::new();
+    let mut failures = Vec::new();
+    for plant in &params.plants {
+        if !running.load(Ordering::Relaxed) {
+            // A partial scene is not worth writing
+            return ExportResult {
+                count: 0,
+                skipped: 0,
+                error: None,
+                failures,
+                cancelled: true,
+                weld_stats: stats.weld,
+                decimation_stats: stats.decimation,
+            };
+        }
+        match build_plant_groups(params, plant, ExportFormat::Glb) {
+            Some((groups, plant_stats)) if !groups.is_empty() => {
+                stats.add(plant_stats);
+                plants.push((plant, groups));
             }
+            result => failures.push(ExportFailure {
+                file_stem: plant.file_stem.clone(),
+                error: if result.is_none() {
+                    "The grammar has no axiom or failed to derive".into()
+                } else {
+                    "All geometry is excluded by the filter".into()
+                },
+            }),
+        }
+        progress.fetch_add(1, Ordering::Relaxed);
+    }
+    if plants.is_empty() {
+        return ExportResult {
+            count: 0,
+            skipped: 0,
+            error: Some("Nothing to export: no individual produced geometry".into()),
+            failures,
+            cancelled: false,
+            weld_stats: stats.weld,
+            decimation_stats: stats.decimation,
         };
+    }
 
-        match save_result {
-            Ok(()) => {
-                count += 1;
+    let save_result = save_file_streamed(&params.output_dir, &filename, |out| {
+        let mut glb = GlbBuilder::with_options(params.glb_options);
+        if let Some(png) = &params.leaf_texture_png {
+            glb.set_cutout_texture(png);
+        }
+        let mut roots = Vec::new();
+        for (set, (plant, groups)) in plants.iter().enumerate() {
+            glb.use_material_set(set);
+            let root = add_plant_node(&mut glb, groups, &plant.file_stem);
+            glb.set_translation(root, params.axes.convert(plant.translation));
+            if plant.rotation != Quat::IDENTITY {
+                glb.set_rotation(root, params.axes.convert_rotation(plant.rotation));
             }
-            Err(e) => {
-                progress.fetch_add(1, Ordering::Relaxed);
-                return ExportResult {
-                    count,
-                    error: Some(e),
-                };
+            if plant.scale != 1.0 {
+                glb.set_scale(root, Vec3::splat(plant.scale));
             }
+            roots.push(root);
         }
+        let scene_root = glb.add_node(scene, None, &roots);
+        let material_sets: Vec<&HashMap<u8, MaterialSettings>> = plants
+            .iter()
+            .map(|(plant, _)| &plant.material_settings)
+            .collect();
+        glb.write_scene_to(out, &[scene_root], &material_sets)
+    });
+
+    if save_result.is_ok() && params.write_metadata {
+        let scene_plants: Vec<&ExportPlant> = plants.iter().map(|(plant, _)| *plant).collect();
+        if let Err(error) = write_metadata(params, &filename, &scene_plants) {
+            warn!("Failed to write metadata for {}: {}", filename, error);
+            failures.push(ExportFailure {
+                file_stem: scene.to_string(),
+                error: format!("Metadata sidecar: {}", error),
+            });
+        }
+    }
 
+    ExportResult {
+        count: usize::from(save_result.is_ok()),
+        skipped: 0,
+        error: save_result.err(),
+        failures,
+        cancelled: false,
+        weld_stats: stats.weld,
+        decimation_stats: stats.decimation,
+    }
+}
+
+/// Writes the plants into one GLB named `animation` as consecutive frames:
+/// each plant is a frame node that a stepped scale track shows for
+/// `frame_duration` seconds and hides otherwise. The first frame is visible
+/// in viewers that do not play animations; the last stays visible at the end.
+fn perform_animation_export(
+    params: &BatchExportParams,
+    animation: &str,
+    progress: &Arc<AtomicUsize>,
+    running: &AtomicBool,
+) -> ExportResult {
+    let Some(filename) = output_filename(params, animation, ExportFormat::Glb) else {
+        return ExportResult {
+            count: 0,
+            skipped: 1,
+            error: None,
+            failures: Vec::new(),
+            cancelled: false,
+            weld_stats: None,
+            decimation_stats: None,
+        };
+    };
+    let mut stats = BatchStats::new(params);
+    let mut frames = Vec::new();
+    let mut failures = Vec::new();
+    for plant in &params.plants {
+        if !running.load(Ordering::Relaxed) {
+            return ExportResult {
+                count: 0,
+                skipped: 0,
+                error: None,
+                failures,
+                cancelled: true,
+                weld_stats: stats.weld,
+                decimation_stats: stats.decimation,
+            };
+        }
+        // A frame without geometry, like a bare axiom, stays an empty node
+        let groups = match build_plant_groups(params, plant, ExportFormat::Glb) {
+            Some((groups, plant_stats)) => {
+                stats.add(plant_stats);
+                groups
+            }
+            None => {
+                failures.push(ExportFailure {
+                    file_stem: plant.file_stem.clone(),
+                    error: "The grammar has no axiom or failed to derive".into(),
+                });
+                Vec::new()
+            }
+        };
+        frames.push((plant, groups));
         progress.fetch_add(1, Ordering::Relaxed);
     }
+    if frames.iter().all(|(_, groups)| groups.is_empty()) {
+        return ExportResult {
+            count: 0,
+            skipped: 0,
+            error: Some("Nothing to export: no frame produced geometry".into()),
+            failures,
+            cancelled: false,
+            weld_stats: stats.weld,
+            decimation_stats: stats.decimation,
+        };
+    }
+
+    let duration = params.frame_duration.max(0.01);
+    let last = frames.len() - 1;
+    let save_result = save_file_streamed(&params.output_dir, &filename, |out| {
+        let mut glb = GlbBuilder::with_options(params.glb_options);
+        if let Some(png) = &params.leaf_texture_png {
+            glb.set_cutout_texture(png);
+        }
+        let mut nodes = Vec::new();
+        let mut tracks = Vec::new();
+        for (index, (plant, groups)) in frames.iter().enumerate() {
+            let node = add_plant_node(&mut glb, groups, &plant.file_stem);
+            let start = index as f32 * duration;
+            let mut keys = Vec::new();
+            if index > 0 {
+                glb.set_scale(node, Vec3::ZERO);
+                keys.push((0.0, 0.0));
+            }
+            keys.push((start, 1.0));
+            if index < last {
+                keys.push((start + duration, 0.0));
+            }
+            tracks.push((node, keys));
+            nodes.push(node);
+        }
+        glb.add_step_scale_animation("Growth", &tracks);
+        let root = glb.add_node(animation, None, &nodes);
+        // Frames share the editor's materials
+        glb.write_to(out, &[root], &frames[0].0.material_settings)
+    });
+
+    if save_result.is_ok() && params.write_metadata {
+        let frame_plants: Vec<&ExportPlant> = frames.iter().map(|(plant, _)| *plant).collect();
+        if let Err(error) = write_metadata(params, &filename, &frame_plants) {
+            warn!("Failed to write metadata for {}: {}", filename, error);
+            failures.push(ExportFailure {
+                file_stem: animation.to_string(),
+                error: format!("Metadata sidecar: {}", error),
+            });
+        }
+    }
+
+    ExportResult {
+        count: usize::from(save_result.is_ok()),
+        skipped: 0,
+        error: save_result.err(),
+        failures,
+        cancelled: false,
+        weld_stats: stats.weld,
+        decimation_stats: stats.decimation,
+    }
+}
+
+/// Converts mesh positions and normals into the target axis convention,
+/// reversing triangle winding when the conversion mirrors the geometry.
+fn convert_mesh_axes(mesh: &mut Mesh, axes: AxisConvention) {
+    if axes == AxisConvention::YUpRightHanded {
+        return;
+    }
+    for attribute in [Mesh::ATTRIBUTE_POSITION, Mesh::ATTRIBUTE_NORMAL] {
+        if let Some(VertexAttributeValues::Float32x3(values)) = mesh.attribute_mut(attribute) {
+            for v in values.iter_mut() {
+                *v = axes.convert(Vec3::from_array(*v)).to_array();
+            }
+        }
+    }
+    if let Some(VertexAttributeValues::Float32x4(tangents)) =
+        mesh.attribute_mut(Mesh::ATTRIBUTE_TANGENT)
+    {
+        for t in tangents.iter_mut() {
+            let converted = axes.convert(Vec3::new(t[0], t[1], t[2]));
+            // Mirroring flips the bitangent sign stored in w
+            let w = if axes.mirrors() { -t[3] } else { t[3] };
+            *t = [converted.x, converted.y, converted.z, w];
+        }
+    }
+    if axes.mirrors() {
+        match mesh.indices_mut() {
+            Some(Indices::U16(indices)) => {
+                indices.chunks_exact_mut(3).for_each(|tri| tri.swap(1, 2))
+            }
+            Some(Indices::U32(indices)) => {
+                indices.chunks_exact_mut(3).for_each(|tri| tri.swap(1, 2))
+            }
+            None => {}
+        }
+    }
+}
+
+/// A named set of material buckets that becomes one object group in the export.
+struct MeshGroup {
+    /// Group label used in object and node names; empty for the unsplit plant.
+    name: String,
+    /// Prop ID when the group holds the props of a single ID.
+    prop_id: Option<u16>,
+    /// Whether the group holds card props, exported with alpha-tested,
+    /// double-sided materials.
+    cutout: bool,
+    buckets: HashMap<u8, Mesh>,
+    /// Joints the buckets are skinned to, for GLB armature exports.
+    rig: Option<PlantRig>,
+}
+
+/// Meshes a skeleton into export groups, applying the export filter.
+///
+/// Without branch-order splitting, props are merged into the material buckets of a
+/// single unnamed group. With splitting, each order level forms its own group so
+/// engines can strip twigs for distant LODs, and props go into one `Props` group,
+/// or one group per prop ID when `props_by_id` is set. Card props need their own
+/// materials, so outside per-ID groups they go into a separate `Cutouts` group.
+///
+/// When `skinned` is set, the branches instead form one `Armature` group skinned
+/// to a joint per strand point; branch orders beyond the split levels follow
+/// their parent joint rigidly. Props are not skinned.
+fn build_export_groups(
+    skeleton: &Skeleton,
+    params: &BatchExportParams,
+    plant: &ExportPlant,
+    split_levels: bool,
+    props_by_id: bool,
+    skinned: bool,
+) -> Vec<MeshGroup> {
+    let mesher = SkeletonMesher::new(8)
+        .with_profiles(plant.profiles.clone())
+        .with_ring_spacing(params.ring_spacing);
+
+    let skinned = skinned
+        .then(|| build_skinned(skeleton, &mesher, Some(params.branch_order_levels)))
+        .and_then(|result| {
+            result
+                .inspect_err(|e| warn!("Exporting {} without armature: {}", plant.file_stem, e))
+                .ok()
+        });
+    let mut groups: Vec<MeshGroup> = if let Some((rig, buckets)) = skinned {
+        vec![MeshGroup {
+            name: "Armature".to_string(),
+            prop_id: None,
+            cutout: false,
+            buckets,
+            rig: Some(rig),
+        }]
+    } else if split_levels {
+        let levels = params.branch_order_levels;
+        split_skeleton_by_level(skeleton, levels)
+            .into_iter()
+            .map(|(level, part)| MeshGroup {
+                name: level_name(level, levels),
+                prop_id: None,
+                cutout: false,
+                buckets: mesher.build(&part),
+                rig: None,
+            })
+            .collect()
+    } else {
+        vec![MeshGroup {
+            name: String::new(),
+            prop_id: None,
+            cutout: false,
+            buckets: mesher.build(skeleton),
+            rig: None,
+        }]
+    };
+    for group in &mut groups {
+        group
+            .buckets
+            .retain(|material_id, _| params.filter.includes_branch(*material_id));
+    }
+
+    // Merge props using pre-extracted mesh data, keyed by (prop ID or 0, cutout)
+    let mut prop_buckets: BTreeMap<(u16, bool), HashMap<u8, Mesh>> = BTreeMap::new();
+    for prop in &skeleton.props {
+        if !params.filter.includes_prop(prop.material_id) {
+            continue;
+        }
+
+        let mesh_type = plant
+            .prop_meshes
+            .get(&prop.prop_id)
+            .copied()
+            .unwrap_or_default();
+        let Some(source_mesh) = params.extracted_prop_meshes.get(&mesh_type) else {
+            continue;
+        };
+
+        let cutout = mesh_type.is_cutout();
+        let target = if !split_levels && !cutout {
+            &mut groups[0].buckets
+        } else if split_levels && props_by_id {
+            prop_buckets.entry((prop.prop_id, cutout)).or_default()
+        } else {
+            prop_buckets.entry((0, cutout)).or_default()
+        };
+        merge_prop_into_bucket(target, source_mesh, prop, params.prop_scale);
+    }
+    for ((prop_id, cutout), buckets) in prop_buckets {
+        let (name, prop_id) = if split_levels && props_by_id {
+            let mesh_type = plant.prop_meshes.get(&prop_id).copied().unwrap_or_default();
+            (format!("{}_{}", mesh_type.name(), prop_id), Some(prop_id))
+        } else if cutout {
+            ("Cutouts".to_string(), None)
+        } else {
+            ("Props".to_string(), None)
+        };
+        groups.push(MeshGroup {
+            name,
+            prop_id,
+            cutout,
+            buckets,
+            rig: None,
+        });
+    }
+
+    groups.retain(|group| !group.buckets.is_empty());
+    groups
+}
+
+/// Object name for a material bucket within a group.
+fn group_object_name(prefix: &str, group: &MeshGroup, material_id: u8) -> String {
+    if group.name.is_empty() {
+        format!("{}_mat{}", prefix, material_id)
+    } else {
+        format!("{}_{}_mat{}", prefix, group.name, material_id)
+    }
+}
+
+/// Streams all groups as OBJ objects sharing one vertex index space.
+fn write_groups_obj(
+    out: &mut dyn Write,
+    groups: &[MeshGroup],
+    prefix: &str,
+    decimals: usize,
+) -> io::Result<()> {
+    let mut vertex_offset = 0usize;
+    for group in groups {
+        for (material_id, mesh) in &group.buckets {
+            let object_name = group_object_name(prefix, group, *material_id);
+            write_mesh_obj(out, mesh, &object_name, vertex_offset, decimals)?;
+            vertex_offset += mesh.count_vertices();
+        }
+    }
+    Ok(())
+}
+
+/// Formats an OBJ number with at most `decimals` fractional digits,
+/// dropping trailing zeros.
+fn obj_float(value: f32, decimals: usize) -> String {
+    let mut text = format!("{:.*}", decimals, value);
+    if text.contains('.') {
+        text.truncate(text.trim_end_matches('0').trim_end_matches('.').len());
+    }
+    if text == "-0" {
+        text.remove(0);
+    }
+    text
+}
+
+/// Streams one mesh as an OBJ object. Face indices are 1-based and shifted by
+/// `vertex_offset`, the number of vertices written by earlier objects.
+fn write_mesh_obj(
+    out: &mut dyn Write,
+    mesh: &Mesh,
+    name: &str,
+    vertex_offset: usize,
+    decimals: usize,
+) -> io::Result<()> {
+    let f = |value: f32| obj_float(value, decimals);
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return Ok(());
+    };
+    let normals = match mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
+        Some(VertexAttributeValues::Float32x3(normals)) => Some(normals),
+        _ => None,
+    };
+    let uvs = match mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+        Some(VertexAttributeValues::Float32x2(uvs)) => Some(uvs),
+        _ => None,
+    };
+    // Vertex colors use the common `v x y z r g b` extension, written only when
+    // a `'` command tinted the mesh so plain exports stay minimal
+    let colors = match mesh.attribute(Mesh::ATTRIBUTE_COLOR) {
+        Some(VertexAttributeValues::Float32x4(colors))
+            if colors.len() == positions.len()
+                && colors.iter().any(|c| c[..3].iter().any(|&v| v != 1.0)) =>
+        {
+            Some(colors)
+        }
+        _ => None,
+    };
+
+    writeln!(out, "o {}", name)?;
+    for (i, [x, y, z]) in positions.iter().enumerate() {
+        match colors {
+            Some(colors) => {
+                let [r, g, b, _] = colors[i];
+                writeln!(
+                    out,
+                    "v {} {} {} {} {} {}",
+                    f(*x),
+                    f(*y),
+                    f(*z),
+                    f(r),
+                    f(g),
+                    f(b)
+                )?;
+            }
+            None => writeln!(out, "v {} {} {}", f(*x), f(*y), f(*z))?,
+        }
+    }
+    for [u, v] in uvs.into_iter().flatten() {
+        // OBJ texture coordinates have their origin at the bottom left
+        writeln!(out, "vt {} {}", f(*u), f(1.0 - v))?;
+    }
+    for [x, y, z] in normals.into_iter().flatten() {
+        writeln!(out, "vn {} {} {}", f(*x), f(*y), f(*z))?;
+    }
+
+    let corner = |i: usize| {
+        let index = vertex_offset + i + 1;
+        match (uvs.is_some(), normals.is_some()) {
+            (true, true) => format!("{0}/{0}/{0}", index),
+            (true, false) => format!("{0}/{0}", index),
+            (false, true) => format!("{0}//{0}", index),
+            (false, false) => index.to_string(),
+        }
+    };
+    let indices: Box<dyn Iterator<Item = usize>> = match mesh.indices() {
+        Some(indices) => Box::new(indices.iter()),
+        None => Box::new(0..positions.len()),
+    };
+    let mut triangle = [0usize; 3];
+    for (n, index) in indices.enumerate() {
+        triangle[n % 3] = index;
+        if n % 3 == 2 {
+            writeln!(
+                out,
+                "f {} {} {}",
+                corner(triangle[0]),
+                corner(triangle[1]),
+                corner(triangle[2])
+            )?;
+        }
+    }
+    writeln!(out)
+}
+
+/// Adds a group node whose children are one mesh node per material bucket,
+/// plus the joint tree when the group is skinned.
+fn add_group_node<'a>(glb: &mut GlbBuilder<'a>, group: &'a MeshGroup) -> Option<usize> {
+    glb.use_cutout(group.cutout);
+    let mut material_ids: Vec<u8> = group.buckets.keys().copied().collect();
+    material_ids.sort_unstable();
+    let mut children: Vec<usize> = material_ids
+        .into_iter()
+        .filter_map(|material_id| {
+            let name = format!("{}_mat{}", group.name, material_id);
+            let mesh = glb.add_mesh(&name, &group.buckets[&material_id], material_id)?;
+            Some(glb.add_node(&name, Some(mesh), &[]))
+        })
+        .collect();
+    if children.is_empty() {
+        return None;
+    }
+    if let Some(rig) = &group.rig {
+        let (skin, roots) = add_rig_nodes(glb, rig);
+        for &node in &children {
+            glb.set_skin(node, skin);
+        }
+        children.extend(roots);
+    }
+    Some(glb.add_node(&group.name, None, &children))
+}
+
+/// Adds one node per joint, nested like the rig, and the skin binding them.
+/// Returns the skin and the root joint nodes.
+fn add_rig_nodes(glb: &mut GlbBuilder<'_>, rig: &PlantRig) -> (usize, Vec<usize>) {
+    let mut children = vec![Vec::new(); rig.joints.len()];
+    for (index, joint) in rig.joints.iter().enumerate() {
+        if let Some(parent) = joint.parent {
+            children[parent].push(index);
+        }
+    }
+    // Children always follow their parent, so a reverse pass has them ready
+    let mut nodes = vec![0; rig.joints.len()];
+    for index in (0..rig.joints.len()).rev() {
+        let child_nodes: Vec<usize> = children[index].iter().map(|&c| nodes[c]).collect();
+        let node = glb.add_node(&format!("Joint{}", index), None, &child_nodes);
+        let (translation, rotation) = rig.local_transform(index);
+        glb.set_translation(node, translation);
+        glb.set_rotation(node, rotation);
+        nodes[index] = node;
+    }
+    let skin = glb.add_skin(&nodes, &rig.inverse_bind_matrices());
+    let roots = rig.roots().map(|index| nodes[index]).collect();
+    (skin, roots)
+}
+
+/// Streams all groups into one GLB as a node hierarchy:
+/// `Plant → Trunk / Branches / Twigs → material meshes` and
+/// `Plant → Props → one node per prop ID → material meshes`.
+fn write_groups_glb(
+    out: &mut dyn Write,
+    groups: &[MeshGroup],
+    root_name: &str,
+    material_settings: &HashMap<u8, MaterialSettings>,
+    options: GlbOptions,
+    cutout_texture: Option<&[u8]>,
+) -> io::Result<()> {
+    let mut glb = GlbBuilder::with_options(options);
+    if let Some(png) = cutout_texture {
+        glb.set_cutout_texture(png);
+    }
+    let root = add_plant_node(&mut glb, groups, root_name);
+    glb.write_to(out, &[root], material_settings)
+}
 
-    ExportResult { count, error: None }
+/// Adds one plant's groups under a root node named `root_name`.
+fn add_plant_node<'a>(glb: &mut GlbBuilder<'a>, groups: &'a [MeshGroup], root_name: &str) -> usize {
+    let mut plant_children = Vec::new();
+    let mut prop_children = Vec::new();
+    for group in groups {
+        let Some(node) = add_group_node(glb, group) else {
+            continue;
+        };
+        if group.prop_id.is_some() {
+            prop_children.push(node);
+        } else {
+            plant_children.push(node);
+        }
+    }
+    if !prop_children.is_empty() {
+        plant_children.push(glb.add_node("Props", None, &prop_children));
+    }
+    glb.add_node(root_name, None, &plant_children)
 }
 
 /// System that polls for completed background export tasks.
-pub fn poll_export_status(mut export_status: ResMut<ExportStatus>) {
+pub fn poll_export_status(mut export_status: ResMut<ExportStatus>, mut toasts: ResMut<Toasts>) {
     if !export_status.exporting {
         return;
     }
@@ -566,15 +1820,259 @@ pub fn poll_export_status(mut export_status: ResMut<ExportStatus>) {
     drop(guard);
 
     export_status.last_export_count = result.count;
+    export_status.skipped = result.skipped;
     export_status.error = result.error;
+    export_status.failures = result.failures;
+    export_status.cancelled = result.cancelled;
+    export_status.weld_stats = result.weld_stats;
+    export_status.decimation_stats = result.decimation_stats;
     export_status.exporting = false;
     export_status.pending_result = None;
     export_status.progress = None;
+    export_status.cancel_flag = None;
 
-    if export_status.error.is_none() {
-        info!(
-            "Batch export complete: {} files",
+    match &export_status.error {
+        None if export_status.cancelled => info!(
+            "Batch export cancelled after {} files",
             export_status.last_export_count
+        ),
+        None => info!(
+            "Batch export complete: {} files, {} skipped, {} failed",
+            export_status.last_export_count,
+            export_status.skipped,
+            export_status.failures.len()
+        ),
+        Some(e) => {
+            warn!("Export failed: {}", e);
+            toasts.error("Export failed", e.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::ExportSweep;
+
+    #[test]
+    fn test_obj_streaming_offsets_indices() {
+        let mut mesh = Mesh::from(Cuboid::new(0.0, 0.0, 0.0));
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_POSITION,
+            vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 0.0, 1.0]; 3]);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0, 0.25]; 3]);
+        mesh.insert_indices(Indices::U32(vec![0, 1, 2]));
+
+        let mut out = Vec::new();
+        write_mesh_obj(&mut out, &mesh, "plant_mat0", 10, 6).unwrap();
+        let obj = String::from_utf8(out).unwrap();
+
+        assert!(obj.starts_with("o plant_mat0\n"));
+        assert_eq!(obj.lines().filter(|l| l.starts_with("v ")).count(), 3);
+        assert!(obj.contains("vt 0 0.75\n"));
+        assert!(obj.contains("f 11/11/11 12/12/12 13/13/13\n"));
+    }
+
+    #[test]
+    fn test_obj_vertex_colors() {
+        let mut mesh = Mesh::from(Cuboid::new(0.0, 0.0, 0.0));
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_POSITION,
+            vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+        );
+        mesh.remove_attribute(Mesh::ATTRIBUTE_NORMAL);
+        mesh.remove_attribute(Mesh::ATTRIBUTE_UV_0);
+        mesh.insert_indices(Indices::U32(vec![0, 1, 2]));
+
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, vec![[1.0; 4]; 3]);
+        let mut out = Vec::new();
+        write_mesh_obj(&mut out, &mesh, "plain", 0, 6).unwrap();
+        assert!(String::from_utf8(out).unwrap().contains("v 1 0 0\n"));
+
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, vec![[1.0, 0.5, 0.0, 1.0]; 3]);
+        let mut out = Vec::new();
+        write_mesh_obj(&mut out, &mesh, "tinted", 0, 6).unwrap();
+        assert!(
+            String::from_utf8(out)
+                .unwrap()
+                .contains("v 1 0 0 1 0.5 0\n")
+        );
+    }
+
+    #[test]
+    fn test_with_define() {
+        let source = "#define angle 20\nomega: F\np1: F -> F + F";
+        assert_eq!(
+            with_define(source, "angle", 35.0),
+            "#define angle 35\nomega: F\np1: F -> F + F"
+        );
+        assert_eq!(
+            with_define("omega: F", "step", 2.5),
+            "#define step 2.5\nomega: F"
         );
     }
+
+    fn test_params(
+        plants: Vec<ExportPlant>,
+        output_dir: PathBuf,
+        overwrite: OverwritePolicy,
+    ) -> BatchExportParams {
+        BatchExportParams {
+            plants,
+            format: ExportFormat::Glb,
+            filter: ExportFilter::default(),
+            split_by_branch_order: false,
+            branch_order_levels: 3,
+            axes: AxisConvention::default(),
+            obj_decimals: 6,
+            glb_options: GlbOptions::default(),
+            skinned: false,
+            weld: None,
+            decimation: None,
+            ring_spacing: None,
+            write_metadata: true,
+            prop_scale: 1.0,
+            prop_jitter: HashMap::new(),
+            prop_density: 1.0,
+            leaf_texture_png: None,
+            output_dir,
+            overwrite,
+            scene: None,
+            animation: None,
+            frame_duration: 0.5,
+            extracted_prop_meshes: HashMap::new(),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_overwrite_policies() {
+        let dir = std::env::temp_dir().join(format!("lsystem_policy_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("tree.glb"), b"").unwrap();
+        let filename = |overwrite| {
+            output_filename(
+                &test_params(Vec::new(), dir.clone(), overwrite),
+                "tree",
+                ExportFormat::Glb,
+            )
+        };
+        let (rename, overwrite, skip) = (
+            filename(OverwritePolicy::Rename),
+            filename(OverwritePolicy::Overwrite),
+            filename(OverwritePolicy::Skip),
+        );
+        let fresh = output_filename(
+            &test_params(Vec::new(), dir.clone(), OverwritePolicy::Skip),
+            "bush",
+            ExportFormat::Glb,
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(rename.as_deref(), Some("tree_2.glb"));
+        assert_eq!(overwrite.as_deref(), Some("tree.glb"));
+        assert_eq!(skip, None);
+        assert_eq!(fresh.as_deref(), Some("bush.glb"));
+    }
+
+    #[test]
+    fn test_metadata_records_swept_genotype() {
+        let lsystem_config = LSystemConfig {
+            source_code: "#define angle 20\nomega: F\np1: F -> F + F".into(),
+            ..default()
+        };
+        let export_config = ExportConfig {
+            variation_count: 2,
+            sweep: ExportSweep {
+                parameter: SweepParameter::Constant("angle".into()),
+                start: 10.0,
+                end: 30.0,
+            },
+            ..default()
+        };
+        let plants = editor_plants(
+            &lsystem_config,
+            &MaterialSettingsMap::default(),
+            &MaterialGradients::default(),
+            &CrossSections::default(),
+            &PropConfig::default(),
+            &export_config,
+            &IncludeLibrary::default(),
+        )
+        .unwrap();
+        let params = test_params(plants, PathBuf::new(), OverwritePolicy::Overwrite);
+
+        let plant = &params.plants[1];
+        let document = metadata_json(&params, "plant.glb", &[plant]);
+        assert_eq!(document["file"], "plant.glb");
+        assert_eq!(document["export"]["format"], ExportFormat::Glb.name());
+        // The recorded genotype carries the swept value and regenerates the plant
+        let genotype: PlantGenotype =
+            serde_json::from_value(document["plants"][0]["genotype"].clone()).unwrap();
+        assert!(genotype.source_code.starts_with("#define angle 30\n"));
+        assert_eq!(genotype.seed, plant.seed);
+        assert_eq!(genotype.iterations, plant.iterations);
+    }
+
+    #[test]
+    fn test_genotype_plants_compile_timed_grammars() {
+        let mut genotype = PlantGenotype::new("#timed A 1\n#tick 0.5\nomega: A".into());
+        genotype.clock = 1.0;
+        let plant = ExportPlant::from_genotype(
+            &genotype,
+            &IncludeLibrary::default(),
+            "plant".into(),
+            String::new(),
+        )
+        .unwrap();
+        assert_eq!(plant.iterations, 2, "the clock sets the step count");
+        assert!(plant.source_code.contains("omega: A(0)"));
+        assert_eq!(plant.genotype.source_code, genotype.source_code);
+    }
+
+    #[test]
+    fn test_growth_frames_are_numbered() {
+        let lsystem_config = LSystemConfig {
+            source_code: "omega: F\np1: F -> F[+F]F".into(),
+            ..default()
+        };
+        let export_config = ExportConfig {
+            base_filename: "grow".into(),
+            variation_count: 3,
+            sweep: ExportSweep {
+                parameter: SweepParameter::Iterations,
+                start: 0.0,
+                end: 2.0,
+            },
+            animate: true,
+            ..default()
+        };
+        let plants = editor_plants(
+            &lsystem_config,
+            &MaterialSettingsMap::default(),
+            &MaterialGradients::default(),
+            &CrossSections::default(),
+            &PropConfig::default(),
+            &export_config,
+            &IncludeLibrary::default(),
+        )
+        .unwrap();
+        let stems: Vec<&str> = plants
+            .iter()
+            .map(|plant| plant.file_stem.as_str())
+            .collect();
+        assert_eq!(stems, ["grow_0001", "grow_0002", "grow_0003"]);
+        let iterations: Vec<usize> = plants.iter().map(|plant| plant.iterations).collect();
+        assert_eq!(iterations, [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_obj_float_precision() {
+        assert_eq!(obj_float(1.0, 6), "1");
+        assert_eq!(obj_float(0.123_456, 4), "0.1235");
+        assert_eq!(obj_float(2.5, 4), "2.5");
+        assert_eq!(obj_float(-0.00001, 4), "0");
+        assert_eq!(obj_float(-1.25, 0), "-1");
+    }
 }
@@ -0,0 +1,200 @@
+//! 3D rendering of the garden.
+//!
+//! Every garden plant is derived in the background once, when added, then
+//! spawned as the children of a root entity holding its placement, so moving,
+//! turning, or scaling a plant only updates the root's transform.
+
+use crate::core::config::{LSystemConfig, PropConfig};
+use crate::core::garden::Garden;
+use crate::core::genotype::PlantGenotype;
+use crate::core::includes::IncludeLibrary;
+use crate::core::terrain::Terrain;
+#[cfg(feature = "nursery")]
+use crate::ui::nursery::{NurseryMode, NurseryState};
+use crate::visuals::assets::PropMeshAssets;
+use crate::visuals::genotype_render::{
+    CachedGenotypeMesh, PendingDerivation, PlantDetail, build_plant_assets,
+    spawn_genotype_derivation, spawn_plant_assets,
+};
+use crate::visuals::turtle::{LSystemMeshTag, LSystemPropTag};
+use bevy::platform::collections::{HashMap, HashSet};
+use bevy::prelude::*;
+use bevy_symbios::materials::ProceduralTextures;
+
+/// Root of a spawned garden plant, by plant id.
+#[derive(Component)]
+pub struct GardenPlantRoot(pub u64);
+
+/// Branch mesh or prop of a garden plant.
+#[derive(Component, Clone)]
+pub struct GardenPart;
+
+/// Spawned and in-flight garden plants. A plant's genotype never changes
+/// once it is in the garden, so plants are tracked by id alone.
+#[derive(Resource, Default)]
+pub struct GardenRender {
+    /// Root entity of each spawned plant.
+    roots: HashMap<u64, Entity>,
+    /// Derivations in flight by plant id, with the genotype to mesh.
+    pending: HashMap<u64, (PlantGenotype, PendingDerivation)>,
+    /// Plants that failed to derive.
+    pub failed: HashSet<u64>,
+}
+
+/// Starts derivations for new plants, despawns removed ones, and applies
+/// placements on the terrain and visibility. Runs when the garden or the
+/// terrain changes.
+pub fn sync_garden(
+    mut commands: Commands,
+    garden: Res<Garden>,
+    terrain: Res<Terrain>,
+    includes: Res<IncludeLibrary>,
+    mut render: ResMut<GardenRender>,
+    mut roots: Query<(&GardenPlantRoot, &mut Transform, &mut Visibility)>,
+) {
+    if !garden.is_changed() && !terrain.is_changed() {
+        return;
+    }
+
+    let removed: Vec<u64> = render
+        .roots
+        .keys()
+        .filter(|id| garden.get(**id).is_none())
+        .copied()
+        .collect();
+    for id in removed {
+        if let Some(entity) = render.roots.remove(&id) {
+            commands.entity(entity).despawn();
+        }
+    }
+    render.pending.retain(|id, _| garden.get(*id).is_some());
+    render.failed.retain(|id| garden.get(*id).is_some());
+
+    for plant in &garden.plants {
+        if render.roots.contains_key(&plant.id)
+            || render.pending.contains_key(&plant.id)
+            || render.failed.contains(&plant.id)
+        {
+            continue;
+        }
+        let slot = spawn_genotype_derivation(&plant.genotype, &includes);
+        render
+            .pending
+            .insert(plant.id, (plant.genotype.clone(), slot));
+    }
+
+    let visibility = garden_visibility(&garden);
+    for (root, mut transform, mut root_visibility) in &mut roots {
+        if let Some(plant) = garden.get(root.0) {
+            transform.set_if_neq(terrain.place(plant.transform()));
+        }
+        root_visibility.set_if_neq(visibility);
+    }
+}
+
+/// Garden plants are only shown in garden mode.
+fn garden_visibility(garden: &Garden) -> Visibility {
+    if garden.enabled {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    }
+}
+
+/// Meshes and spawns the plants whose derivation finished.
+#[allow(clippy::too_many_arguments)]
+pub fn poll_garden_derivations(
+    mut commands: Commands,
+    garden: Res<Garden>,
+    terrain: Res<Terrain>,
+    mut render: ResMut<GardenRender>,
+    config: Res<LSystemConfig>,
+    prop_config: Res<PropConfig>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    proc_textures: Res<ProceduralTextures>,
+    prop_assets: Res<PropMeshAssets>,
+) {
+    let finished: Vec<u64> = render
+        .pending
+        .iter()
+        .filter(|(_, (_, slot))| slot.lock().is_ok_and(|guard| guard.is_some()))
+        .map(|(id, _)| *id)
+        .collect();
+
+    for id in finished {
+        let Some((genotype, slot)) = render.pending.remove(&id) else {
+            continue;
+        };
+        let derived = slot
+            .lock()
+            .ok()
+            .and_then(|mut guard| guard.take())
+            .flatten();
+        let cached = CachedGenotypeMesh::new(&genotype, derived, 0.0, None);
+        let Some(assets) = build_plant_assets(
+            &cached,
+            PlantDetail::full(config.mesh_resolution),
+            &prop_config,
+            &mut meshes,
+            &mut materials,
+            &proc_textures,
+            &prop_assets,
+        ) else {
+            warn!("Garden plant {} failed to derive", id);
+            render.failed.insert(id);
+            continue;
+        };
+
+        let Some(plant) = garden.get(id) else {
+            continue;
+        };
+        let root = commands
+            .spawn((
+                GardenPlantRoot(id),
+                terrain.place(plant.transform()),
+                garden_visibility(&garden),
+                Name::new(plant.name.clone()),
+            ))
+            .id();
+        spawn_plant_assets(
+            &mut commands,
+            &assets,
+            Vec3::ZERO,
+            (GardenPart, ChildOf(root)),
+            (GardenPart, ChildOf(root)),
+        );
+        render.roots.insert(id, root);
+    }
+}
+
+/// Hides the editor plant while garden mode is on, including plants meshed
+/// after it was turned on. Turning it off shows the editor plant again
+/// unless the nursery hides it.
+#[allow(clippy::type_complexity)]
+pub fn toggle_editor_visibility_in_garden(
+    garden: Res<Garden>,
+    #[cfg(feature = "nursery")] nursery: Option<Res<NurseryState>>,
+    added: Query<(), Or<(Added<LSystemMeshTag>, Added<LSystemPropTag>)>>,
+    mut parts: Query<&mut Visibility, Or<(With<LSystemMeshTag>, With<LSystemPropTag>)>>,
+) {
+    let visibility = if garden.enabled {
+        if !garden.is_changed() && added.is_empty() {
+            return;
+        }
+        Visibility::Hidden
+    } else {
+        if !garden.is_changed() {
+            return;
+        }
+        #[cfg(feature = "nursery")]
+        if nursery.is_some_and(|nursery| nursery.mode == NurseryMode::Enabled) {
+            return;
+        }
+        Visibility::Inherited
+    };
+
+    for mut part in &mut parts {
+        part.set_if_neq(visibility);
+    }
+}
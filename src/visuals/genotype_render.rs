@@ -0,0 +1,470 @@
+//! Deriving and spawning plants from genotypes outside the editor pipeline.
+//!
+//! Used for the nursery grid, the pinned comparison plant, and offscreen
+//! thumbnails. Each plant gets its own materials from the genotype's settings.
+
+use crate::core::config::{MaterialSettings, PropConfig, PropMeshType, TextureType};
+use crate::core::genotype::PlantGenotype;
+use crate::core::includes::IncludeLibrary;
+use crate::logic::composition::{ComposedGrammar, graft_calls, split_systems};
+use crate::logic::jitter::{jitter_props, jitter_skeleton, thin_props};
+use crate::logic::taper::taper_width_changes;
+use crate::logic::timed;
+use crate::visuals::assets::PropMeshAssets;
+use crate::visuals::mesher::SkeletonMesher;
+use crate::visuals::turtle::{
+    extend_bounds, extend_bounds_with_mesh, interpret_skeleton, prop_material,
+    turtle_config_with_defaults,
+};
+use bevy::math::{Affine2, Vec2};
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy::tasks::AsyncComputeTaskPool;
+use bevy_symbios::materials::ProceduralTextures;
+use std::sync::{Arc, Mutex};
+use symbios::System;
+use symbios_turtle_3d::Skeleton;
+
+/// A derived genotype: its main grammar and `#system` sections.
+pub struct DerivedGenotype {
+    pub system: System,
+    /// Derived `#system` sections, indexed like their call IDs.
+    pub subsystems: Vec<System>,
+}
+
+/// Derived state of a genotype, with the parameters needed to interpret it.
+pub struct CachedGenotypeMesh {
+    /// The derived L-system state (None if derivation failed).
+    pub system: Option<System>,
+    /// Derived `#system` sections, grafted onto the plant's calls.
+    pub subsystems: Vec<System>,
+    /// Fitness value for display.
+    pub fitness: f32,
+    /// Individual's default turn angle in degrees.
+    pub angle: f32,
+    /// Individual's step size.
+    pub step: f32,
+    /// Individual's default branch width.
+    pub width: f32,
+    /// Individual's elasticity factor for tropism bending.
+    pub elasticity: f32,
+    /// Individual's tropism direction vector.
+    pub tropism: Option<Vec3>,
+    /// Individual's random seed, which also drives turtle jitter.
+    pub seed: u64,
+    /// Individual's material settings by slot ID.
+    pub materials: HashMap<u8, MaterialSettings>,
+    /// Individual's prop ID to mesh type mapping.
+    pub prop_mappings: HashMap<u16, PropMeshType>,
+    /// Error message if derivation failed.
+    pub error: Option<String>,
+}
+
+impl CachedGenotypeMesh {
+    /// Bundles a derivation result with the genotype's interpretation parameters.
+    pub fn new(
+        genotype: &PlantGenotype,
+        derived: Option<DerivedGenotype>,
+        fitness: f32,
+        error: Option<String>,
+    ) -> Self {
+        let (system, subsystems) = match derived {
+            Some(derived) => (Some(derived.system), derived.subsystems),
+            None => (None, Vec::new()),
+        };
+        Self {
+            system,
+            subsystems,
+            fitness,
+            angle: genotype.angle,
+            step: genotype.step,
+            width: genotype.width,
+            elasticity: genotype.elasticity,
+            tropism: genotype.tropism.map(|t| Vec3::new(t[0], t[1], t[2])),
+            seed: genotype.seed,
+            materials: genotype.get_material_settings(),
+            prop_mappings: genotype.prop_mappings.clone(),
+            error,
+        }
+    }
+}
+
+/// Creates a StandardMaterial from a MaterialSettings, using procedural textures if available.
+fn material_from_settings(
+    settings: &MaterialSettings,
+    proc_textures: &ProceduralTextures,
+) -> StandardMaterial {
+    let emission_linear =
+        Color::srgb_from_array(settings.emission_color).to_linear() * settings.emission_strength;
+
+    let base_color_texture = match settings.texture {
+        TextureType::None => None,
+        other => proc_textures.textures.get(&other).cloned(),
+    };
+
+    StandardMaterial {
+        base_color: Color::srgb_from_array(settings.base_color),
+        perceptual_roughness: settings.roughness,
+        metallic: settings.metallic,
+        emissive: emission_linear,
+        base_color_texture,
+        uv_transform: Affine2::from_scale(Vec2::splat(settings.uv_scale)),
+        ..default()
+    }
+}
+
+/// Creates per-genotype material handles from the cached material settings.
+fn create_genotype_materials(
+    cached_materials: &HashMap<u8, MaterialSettings>,
+    proc_textures: &ProceduralTextures,
+    materials: &mut Assets<StandardMaterial>,
+) -> (
+    HashMap<u8, Handle<StandardMaterial>>,
+    Handle<StandardMaterial>,
+) {
+    let mut handles = HashMap::new();
+    let mut primary = None;
+
+    for (&slot, settings) in cached_materials {
+        let handle = materials.add(material_from_settings(settings, proc_textures));
+        if primary.is_none() {
+            primary = Some(handle.clone());
+        }
+        handles.insert(slot, handle);
+    }
+
+    let fallback = primary.unwrap_or_else(|| {
+        materials.add(StandardMaterial {
+            base_color: Color::srgb(0.55, 0.27, 0.07),
+            perceptual_roughness: 0.8,
+            ..default()
+        })
+    });
+
+    (handles, fallback)
+}
+
+/// Derives a PlantGenotype into Systems with full state.
+///
+/// As in the editor, timed grammars are compiled for the genotype's clock and
+/// `#include` lines are expanded from `includes` first. Each `#system` section
+/// is derived on its own, with its own iteration count if given and a seed
+/// offset by its index.
+pub fn derive_genotype(
+    genotype: &PlantGenotype,
+    includes: &IncludeLibrary,
+) -> Option<DerivedGenotype> {
+    let (source, finalization, iterations) = timed::resolve(
+        &genotype.source_code,
+        &genotype.finalization_code,
+        genotype.iterations,
+        genotype.clock,
+    )
+    .ok()?;
+    let source = includes.expand(&source).ok()?;
+    let finalization = includes.expand(&finalization).ok()?;
+    let grammar = split_systems(&source).ok()?;
+    let system = derive_grammar(&grammar, &finalization, iterations, genotype.seed)?;
+    let subsystems = grammar
+        .sections
+        .iter()
+        .enumerate()
+        .map(|(i, section)| {
+            derive_grammar(
+                &split_systems(&section.source).ok()?,
+                "",
+                section.iterations.unwrap_or(iterations),
+                genotype.seed.wrapping_add(i as u64 + 1),
+            )
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(DerivedGenotype { system, subsystems })
+}
+
+/// Result slot of a background derivation; the inner `None` means it failed.
+pub type PendingDerivation = Arc<Mutex<Option<Option<DerivedGenotype>>>>;
+
+/// Derives `genotype` on the async compute pool, filling the returned slot
+/// when done.
+pub fn spawn_genotype_derivation(
+    genotype: &PlantGenotype,
+    includes: &IncludeLibrary,
+) -> PendingDerivation {
+    let slot: PendingDerivation = Arc::new(Mutex::new(None));
+    let result = slot.clone();
+    let genotype = genotype.clone();
+    let includes = includes.clone();
+    AsyncComputeTaskPool::get()
+        .spawn(async move {
+            let derived = derive_genotype(&genotype, &includes);
+            if let Ok(mut guard) = result.lock() {
+                *guard = Some(derived);
+            }
+        })
+        .detach();
+    slot
+}
+
+/// Derives the main part of a split grammar, then applies `finalization` once.
+///
+/// NOTE: Always creates a fresh `System::new()` to guarantee clean derivation state.
+/// This prevents cumulative derivation issues where calling `sys.derive(n)` on an
+/// already-derived system would result in double-growth.
+fn derive_grammar(
+    grammar: &ComposedGrammar,
+    finalization: &str,
+    iterations: usize,
+    seed: u64,
+) -> Option<System> {
+    let mut sys = System::new();
+    sys.set_seed(seed);
+
+    let mut axiom_set = false;
+    let finalization = grammar.rewrite(finalization).ok()?;
+
+    // Parse the source code
+    for line in grammar.main.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with("//") {
+            continue;
+        }
+
+        if trimmed.starts_with("omega:") {
+            let axiom = trimmed.strip_prefix("omega:")?.trim();
+            sys.set_axiom(axiom).ok()?;
+            axiom_set = true;
+        } else if trimmed.starts_with('#') {
+            sys.add_directive(trimmed).ok()?;
+        } else if trimmed.contains("->") {
+            sys.add_rule(trimmed).ok()?;
+        }
+    }
+
+    if !axiom_set {
+        return None;
+    }
+
+    // Derive growth phase
+    sys.derive(iterations).ok()?;
+
+    // Apply finalization if present
+    if !finalization.trim().is_empty() {
+        // Clear rules but keep constants and state
+        sys.rules.clear();
+        sys.ignored_symbols.clear();
+
+        // Parse finalization rules
+        for line in finalization.lines() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with("//") || trimmed.starts_with("omega:") {
+                continue;
+            }
+
+            if trimmed.starts_with('#') {
+                sys.add_directive(trimmed).ok()?;
+            } else if trimmed.contains("->") {
+                sys.add_rule(trimmed).ok()?;
+            }
+        }
+
+        // Execute single decomposition pass
+        sys.derive(1).ok()?;
+    }
+
+    Some(sys)
+}
+
+/// How much geometry a plant is spawned with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlantDetail {
+    /// Vertices per branch ring.
+    pub mesh_resolution: u32,
+    /// Props whose largest scaled extent is below this size (world units) are skipped.
+    pub min_prop_size: f32,
+}
+
+impl PlantDetail {
+    /// Full detail at the given ring resolution, keeping every prop.
+    pub fn full(mesh_resolution: u32) -> Self {
+        Self {
+            mesh_resolution,
+            min_prop_size: 0.0,
+        }
+    }
+}
+
+/// Mesh and material handles of a meshed plant, relative to its origin.
+///
+/// Built once per genotype and level of detail, then spawned any number of
+/// times; spawned entities share the handles.
+#[derive(Clone)]
+pub struct PlantAssets {
+    pub branches: Vec<(Handle<Mesh>, Handle<StandardMaterial>)>,
+    pub props: Vec<(Handle<Mesh>, Handle<StandardMaterial>, Transform)>,
+    /// Bounds of branch vertices and prop anchors, including culled props.
+    pub bounds: Option<(Vec3, Vec3)>,
+}
+
+/// Meshes a derived genotype into mesh and material assets.
+/// Returns `None` if the genotype failed to derive.
+#[allow(clippy::too_many_arguments)]
+pub fn build_plant_assets(
+    cached: &CachedGenotypeMesh,
+    detail: PlantDetail,
+    prop_config: &PropConfig,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    proc_textures: &ProceduralTextures,
+    prop_assets: &PropMeshAssets,
+) -> Option<PlantAssets> {
+    let system = cached.system.as_ref()?;
+    let mut assets = PlantAssets {
+        branches: Vec::new(),
+        props: Vec::new(),
+        bounds: None,
+    };
+
+    // Genotype parameters are the fallbacks for the grammar's constants
+    let build = |sys: &System, seed: u64| {
+        let turtle_config = turtle_config_with_defaults(
+            sys,
+            cached.step,
+            cached.angle,
+            cached.width,
+            cached.tropism,
+            cached.elasticity,
+        );
+        let mut skeleton = interpret_skeleton(sys, turtle_config);
+        jitter_skeleton(&mut skeleton, seed);
+        skeleton
+    };
+
+    // Build skeleton and meshes, grafting the #system sections like the editor
+    let mut skeleton = build(system, cached.seed);
+    let sections: Vec<Option<Skeleton>> = cached
+        .subsystems
+        .iter()
+        .enumerate()
+        .map(|(i, sys)| Some(build(sys, cached.seed.wrapping_add(i as u64 + 1))))
+        .collect();
+    graft_calls(&mut skeleton, &sections);
+    jitter_props(&mut skeleton.props, &prop_config.prop_jitter, cached.seed);
+    thin_props(&mut skeleton.props, prop_config.prop_density, cached.seed);
+    taper_width_changes(&mut skeleton);
+    let mesh_buckets = SkeletonMesher::new(detail.mesh_resolution).build(&skeleton);
+
+    // Create per-genotype material handles from the individual's settings
+    let (geno_materials, geno_fallback) =
+        create_genotype_materials(&cached.materials, proc_textures, materials);
+
+    for (material_id, mesh) in mesh_buckets {
+        let material = geno_materials
+            .get(&material_id)
+            .unwrap_or(&geno_fallback)
+            .clone();
+        extend_bounds_with_mesh(&mut assets.bounds, &mesh);
+        assets.branches.push((meshes.add(mesh), material));
+    }
+
+    // Props (leaves, flowers, etc.)
+    for prop in &skeleton.props {
+        extend_bounds(&mut assets.bounds, prop.position);
+        let scale = prop.scale * prop_config.prop_scale;
+        if scale.max_element() < detail.min_prop_size {
+            continue;
+        }
+
+        // Use per-genotype prop mapping first, fall back to global PropConfig
+        let mesh_type = cached
+            .prop_mappings
+            .get(&prop.prop_id)
+            .or_else(|| prop_config.prop_meshes.get(&prop.prop_id))
+            .copied()
+            .unwrap_or(PropMeshType::Leaf);
+
+        let Some(handle) = prop_assets.meshes.get(&mesh_type) else {
+            continue;
+        };
+
+        // Create prop material by blending genotype material with prop color
+        let base_handle = geno_materials
+            .get(&prop.material_id)
+            .unwrap_or(&geno_fallback);
+        let base_mat = materials.get(base_handle).cloned().unwrap_or_default();
+        let cutout = mesh_type.is_cutout().then_some(&prop_assets.leaf_texture);
+        let prop_material = materials.add(prop_material(base_mat, prop.color, cutout));
+
+        assets.props.push((
+            handle.clone(),
+            prop_material,
+            Transform {
+                translation: prop.position,
+                rotation: prop.rotation,
+                scale,
+            },
+        ));
+    }
+
+    Some(assets)
+}
+
+/// Spawns a meshed plant at `origin`. `mesh_tag` and `prop_tag` are added to
+/// every spawned branch and prop entity.
+pub fn spawn_plant_assets(
+    commands: &mut Commands,
+    assets: &PlantAssets,
+    origin: Vec3,
+    mesh_tag: impl Bundle + Clone,
+    prop_tag: impl Bundle + Clone,
+) {
+    for (mesh, material) in &assets.branches {
+        commands.spawn((
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(origin),
+            mesh_tag.clone(),
+        ));
+    }
+    for (mesh, material, transform) in &assets.props {
+        commands.spawn((
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform {
+                translation: transform.translation + origin,
+                ..*transform
+            },
+            prop_tag.clone(),
+        ));
+    }
+}
+
+/// Meshes a derived genotype and spawns its branches and props at `origin`.
+/// `mesh_tag` and `prop_tag` are added to every spawned branch and prop entity.
+/// Returns the plant's bounds relative to `origin` (branch vertices and prop
+/// anchors), or `None` if the genotype failed to derive.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_genotype_plant(
+    commands: &mut Commands,
+    cached: &CachedGenotypeMesh,
+    origin: Vec3,
+    detail: PlantDetail,
+    prop_config: &PropConfig,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    proc_textures: &ProceduralTextures,
+    prop_assets: &PropMeshAssets,
+    mesh_tag: impl Bundle + Clone,
+    prop_tag: impl Bundle + Clone,
+) -> Option<(Vec3, Vec3)> {
+    let assets = build_plant_assets(
+        cached,
+        detail,
+        prop_config,
+        meshes,
+        materials,
+        proc_textures,
+        prop_assets,
+    )?;
+    spawn_plant_assets(commands, &assets, origin, mesh_tag, prop_tag);
+    assets.bounds
+}
@@ -0,0 +1,1024 @@
+//! Minimal binary glTF 2.0 writer with named nodes.
+//!
+//! `bevy_symbios::export::meshes_to_glb` writes one flat node per material bucket.
+//! This writer lets the exporter lay out its own node hierarchy (plant, branch
+//! orders, props) while reusing the same PBR material mapping.
+//!
+//! The binary chunk is not copied while building: buffer views borrow the mesh
+//! data and are serialized straight into the output writer, so huge exports can
+//! stream to disk without holding a second copy of the geometry in memory.
+//!
+//! Scenes with several plants give each plant its own material set, so
+//! individuals with different materials keep them in one file.
+//!
+//! Meshes carrying joint indices and weights are written skinned; see
+//! [`GlbBuilder::add_skin`].
+//!
+//! Growth animations switch frame nodes on and off with stepped scale
+//! tracks; see [`GlbBuilder::add_step_scale_animation`].
+//!
+//! [`GlbOptions`] trade precision for size: 16-bit indices where the vertex
+//! count allows, and 16-bit positions via `KHR_mesh_quantization`.
+
+use bevy::mesh::{Indices, VertexAttributeValues};
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy_symbios::materials::MaterialSettings;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+const GLB_MAGIC: u32 = 0x4654_6C67;
+const GLB_VERSION: u32 = 2;
+const CHUNK_JSON: u32 = 0x4E4F_534A;
+const CHUNK_BIN: u32 = 0x004E_4942;
+
+const TARGET_ARRAY_BUFFER: u32 = 34962;
+const TARGET_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const COMPONENT_FLOAT: u32 = 5126;
+const COMPONENT_UNSIGNED_SHORT: u32 = 5123;
+const COMPONENT_UNSIGNED_INT: u32 = 5125;
+const FILTER_LINEAR: u32 = 9729;
+const FILTER_LINEAR_MIPMAP_LINEAR: u32 = 9987;
+const WRAP_CLAMP_TO_EDGE: u32 = 33071;
+
+/// Largest quantized position component.
+const QUANTIZED_MAX: f32 = u16::MAX as f32;
+
+/// Size options for GLB output.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GlbOptions {
+    /// Write 16-bit indices for meshes with fewer than 65,536 vertices.
+    pub compact_indices: bool,
+    /// Store positions as 16-bit integers (`KHR_mesh_quantization`), with the
+    /// dequantization folded into each mesh node's transform.
+    pub quantize_positions: bool,
+}
+
+/// Mapping from positions to the quantized 0..=65535 range.
+///
+/// The scale is uniform, set by the largest axis extent: viewers transform the
+/// float normals by the node's inverse transpose, which skews them under a
+/// non-uniform scale.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Quantization {
+    offset: Vec3,
+    scale: f32,
+}
+
+impl Quantization {
+    fn from_bounds(min: Vec3, max: Vec3) -> Self {
+        let extent = (max - min).max_element();
+        let scale = if extent > 0.0 {
+            extent / QUANTIZED_MAX
+        } else {
+            1.0
+        };
+        Self { offset: min, scale }
+    }
+
+    fn quantize(&self, position: [f32; 3]) -> [u16; 3] {
+        let q = ((Vec3::from_array(position) - self.offset) / self.scale)
+            .round()
+            .clamp(Vec3::ZERO, Vec3::splat(QUANTIZED_MAX));
+        [q.x as u16, q.y as u16, q.z as u16]
+    }
+}
+
+/// Borrowed geometry backing one buffer view.
+enum BinSource<'a> {
+    F32(&'a [f32]),
+    U16(&'a [u16]),
+    U32(&'a [u32]),
+    /// 32-bit indices narrowed to 16 bits on write.
+    U32AsU16(&'a [u32]),
+    /// Positions quantized on write, padded to 8 bytes per vertex for alignment.
+    QuantizedPositions(&'a [[f32; 3]], Quantization),
+    /// Data computed while building, such as inverse bind matrices.
+    OwnedF32(Vec<f32>),
+    /// Encoded image data.
+    Bytes(&'a [u8]),
+}
+
+impl BinSource<'_> {
+    fn byte_len(&self) -> usize {
+        match self {
+            BinSource::F32(values) => values.len() * 4,
+            BinSource::U16(values) => values.len() * 2,
+            BinSource::U32(values) => values.len() * 4,
+            BinSource::U32AsU16(values) => values.len() * 2,
+            BinSource::QuantizedPositions(positions, _) => positions.len() * 8,
+            BinSource::OwnedF32(values) => values.len() * 4,
+            BinSource::Bytes(bytes) => bytes.len(),
+        }
+    }
+
+    fn write_to<W: Write + ?Sized>(&self, out: &mut W) -> io::Result<()> {
+        match self {
+            BinSource::F32(values) => values
+                .iter()
+                .try_for_each(|v| out.write_all(&v.to_le_bytes())),
+            BinSource::OwnedF32(values) => values
+                .iter()
+                .try_for_each(|v| out.write_all(&v.to_le_bytes())),
+            BinSource::Bytes(bytes) => out.write_all(bytes),
+            BinSource::U16(values) => values
+                .iter()
+                .try_for_each(|v| out.write_all(&v.to_le_bytes())),
+            BinSource::U32(values) => values
+                .iter()
+                .try_for_each(|v| out.write_all(&v.to_le_bytes())),
+            BinSource::U32AsU16(values) => values
+                .iter()
+                .try_for_each(|&v| out.write_all(&(v as u16).to_le_bytes())),
+            BinSource::QuantizedPositions(positions, quantization) => {
+                positions.iter().try_for_each(|&p| {
+                    let [x, y, z] = quantization.quantize(p);
+                    let mut bytes = [0u8; 8];
+                    bytes[0..2].copy_from_slice(&x.to_le_bytes());
+                    bytes[2..4].copy_from_slice(&y.to_le_bytes());
+                    bytes[4..6].copy_from_slice(&z.to_le_bytes());
+                    out.write_all(&bytes)
+                })
+            }
+        }
+    }
+}
+
+/// Incrementally assembles a GLB file from Bevy meshes and a node tree.
+#[derive(Default)]
+pub struct GlbBuilder<'a> {
+    options: GlbOptions,
+    /// Buffer views in order, each with its byte offset in the binary chunk.
+    bin_views: Vec<(usize, BinSource<'a>)>,
+    /// Length of the binary chunk so far, excluding trailing padding.
+    bin_len: usize,
+    buffer_views: Vec<Value>,
+    accessors: Vec<Value>,
+    meshes: Vec<Value>,
+    nodes: Vec<Value>,
+    skins: Vec<Value>,
+    animations: Vec<Value>,
+    /// (Material set, slot ID, cutout) -> index into the glTF materials array.
+    material_indices: BTreeMap<(usize, u8, bool), usize>,
+    /// Material set used by meshes added from now on.
+    material_set: usize,
+    /// Whether meshes added from now on use the cutout variant of their slot.
+    cutout: bool,
+    /// PNG texture of the cutout materials.
+    cutout_texture: Option<&'a [u8]>,
+    /// Dequantization transform of each mesh, applied to the nodes that use it.
+    mesh_quantization: Vec<Option<Quantization>>,
+}
+
+impl<'a> GlbBuilder<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_options(options: GlbOptions) -> Self {
+        Self {
+            options,
+            ..default()
+        }
+    }
+
+    /// Adds a buffer view; `target` is `None` for data that is not vertex
+    /// or index data, such as inverse bind matrices.
+    fn push_view(&mut self, source: BinSource<'a>, target: Option<u32>) -> usize {
+        let offset = self.bin_len.next_multiple_of(4);
+        let byte_len = source.byte_len();
+        self.bin_len = offset + byte_len;
+        self.bin_views.push((offset, source));
+        let mut view = json!({
+            "buffer": 0,
+            "byteOffset": offset,
+            "byteLength": byte_len,
+        });
+        if let Some(target) = target {
+            view["target"] = json!(target);
+        }
+        self.buffer_views.push(view);
+        self.buffer_views.len() - 1
+    }
+
+    fn push_accessor(&mut self, accessor: Value) -> usize {
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    fn push_float_attribute<const N: usize>(
+        &mut self,
+        values: &'a [[f32; N]],
+        kind: &str,
+        with_bounds: bool,
+    ) -> usize {
+        let view = self.push_view(
+            BinSource::F32(values.as_flattened()),
+            Some(TARGET_ARRAY_BUFFER),
+        );
+        let mut accessor = json!({
+            "bufferView": view,
+            "componentType": COMPONENT_FLOAT,
+            "count": values.len(),
+            "type": kind,
+        });
+        if with_bounds {
+            let mut min = [f32::MAX; N];
+            let mut max = [f32::MIN; N];
+            for v in values {
+                for (c, &component) in v.iter().enumerate() {
+                    min[c] = min[c].min(component);
+                    max[c] = max[c].max(component);
+                }
+            }
+            accessor["min"] = json!(min.to_vec());
+            accessor["max"] = json!(max.to_vec());
+        }
+        self.push_accessor(accessor)
+    }
+
+    /// Makes meshes added from now on use material set `set`, i.e. the
+    /// `set`-th settings map passed to [`GlbBuilder::write_scene_to`].
+    pub fn use_material_set(&mut self, set: usize) {
+        self.material_set = set;
+    }
+
+    /// Switches meshes added from now on to the cutout variant of their
+    /// material slot: alpha-tested (`MASK`), double-sided, and textured with
+    /// the PNG from [`GlbBuilder::set_cutout_texture`], for leaf cards.
+    pub fn use_cutout(&mut self, cutout: bool) {
+        self.cutout = cutout;
+    }
+
+    /// Embeds `png` as the base color texture of the cutout materials. It is
+    /// only written when a cutout material is used.
+    pub fn set_cutout_texture(&mut self, png: &'a [u8]) {
+        self.cutout_texture = Some(png);
+    }
+
+    /// Adds a mesh using the given material slot. Returns the glTF mesh index,
+    /// or `None` if the mesh has no positions.
+    pub fn add_mesh(&mut self, name: &str, mesh: &'a Mesh, material_id: u8) -> Option<usize> {
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            return None;
+        };
+        if positions.is_empty() {
+            return None;
+        }
+
+        let mut attributes = serde_json::Map::new();
+        // A skinned mesh's node transform is ignored, so it cannot dequantize
+        let skinned = mesh.contains_attribute(Mesh::ATTRIBUTE_JOINT_INDEX);
+        let quantization = (self.options.quantize_positions && !skinned)
+            .then(|| self.push_quantized_positions(positions));
+        let position = match quantization {
+            Some((accessor, _)) => accessor,
+            None => self.push_float_attribute(positions, "VEC3", true),
+        };
+        attributes.insert("POSITION".into(), json!(position));
+
+        if let Some(VertexAttributeValues::Float32x3(normals)) =
+            mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+        {
+            let normal = self.push_float_attribute(normals, "VEC3", false);
+            attributes.insert("NORMAL".into(), json!(normal));
+        }
+        if let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+            let uv = self.push_float_attribute(uvs, "VEC2", false);
+            attributes.insert("TEXCOORD_0".into(), json!(uv));
+        }
+        if let Some(VertexAttributeValues::Float32x4(colors)) =
+            mesh.attribute(Mesh::ATTRIBUTE_COLOR)
+        {
+            let color = self.push_float_attribute(colors, "VEC4", false);
+            attributes.insert("COLOR_0".into(), json!(color));
+        }
+        if let (
+            Some(VertexAttributeValues::Uint16x4(joints)),
+            Some(VertexAttributeValues::Float32x4(weights)),
+        ) = (
+            mesh.attribute(Mesh::ATTRIBUTE_JOINT_INDEX),
+            mesh.attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT),
+        ) {
+            let view = self.push_view(
+                BinSource::U16(joints.as_flattened()),
+                Some(TARGET_ARRAY_BUFFER),
+            );
+            let joint = self.push_accessor(json!({
+                "bufferView": view,
+                "componentType": COMPONENT_UNSIGNED_SHORT,
+                "count": joints.len(),
+                "type": "VEC4",
+            }));
+            attributes.insert("JOINTS_0".into(), json!(joint));
+            let weight = self.push_float_attribute(weights, "VEC4", false);
+            attributes.insert("WEIGHTS_0".into(), json!(weight));
+        }
+
+        let next_material = self.material_indices.len();
+        let material = *self
+            .material_indices
+            .entry((self.material_set, material_id, self.cutout))
+            .or_insert(next_material);
+
+        let mut primitive = json!({
+            "attributes": attributes,
+            "material": material,
+            "mode": 4,
+        });
+
+        if let Some(indices) = mesh.indices() {
+            let (source, component, count) = match indices {
+                Indices::U16(idx) => (BinSource::U16(idx), COMPONENT_UNSIGNED_SHORT, idx.len()),
+                // 65535 is reserved for primitive restart, so the largest index must stay below it
+                Indices::U32(idx)
+                    if self.options.compact_indices && positions.len() <= u16::MAX as usize =>
+                {
+                    (
+                        BinSource::U32AsU16(idx),
+                        COMPONENT_UNSIGNED_SHORT,
+                        idx.len(),
+                    )
+                }
+                Indices::U32(idx) => (BinSource::U32(idx), COMPONENT_UNSIGNED_INT, idx.len()),
+            };
+            let view = self.push_view(source, Some(TARGET_ELEMENT_ARRAY_BUFFER));
+            let accessor = self.push_accessor(json!({
+                "bufferView": view,
+                "componentType": component,
+                "count": count,
+                "type": "SCALAR",
+            }));
+            primitive["indices"] = json!(accessor);
+        }
+
+        self.meshes.push(json!({
+            "name": name,
+            "primitives": [primitive],
+        }));
+        self.mesh_quantization
+            .push(quantization.map(|(_, quantization)| quantization));
+        Some(self.meshes.len() - 1)
+    }
+
+    /// Adds 16-bit quantized positions. Returns the accessor and its quantization.
+    fn push_quantized_positions(&mut self, positions: &'a [[f32; 3]]) -> (usize, Quantization) {
+        let (min, max) = positions.iter().fold(
+            (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+            |(min, max), &p| {
+                let p = Vec3::from_array(p);
+                (min.min(p), max.max(p))
+            },
+        );
+        let quantization = Quantization::from_bounds(min, max);
+        let view = self.push_view(
+            BinSource::QuantizedPositions(positions, quantization),
+            Some(TARGET_ARRAY_BUFFER),
+        );
+        self.buffer_views[view]["byteStride"] = json!(8);
+        let accessor = self.push_accessor(json!({
+            "bufferView": view,
+            "componentType": COMPONENT_UNSIGNED_SHORT,
+            "count": positions.len(),
+            "type": "VEC3",
+            "min": quantization.quantize(min.to_array()),
+            "max": quantization.quantize(max.to_array()),
+        }));
+        (accessor, quantization)
+    }
+
+    /// Adds a node referencing an optional mesh and child nodes. Returns the node index.
+    pub fn add_node(&mut self, name: &str, mesh: Option<usize>, children: &[usize]) -> usize {
+        let mut node = json!({ "name": name });
+        if let Some(mesh) = mesh {
+            node["mesh"] = json!(mesh);
+            if let Some(Some(quantization)) = self.mesh_quantization.get(mesh) {
+                node["translation"] = json!(quantization.offset.to_array());
+                node["scale"] = json!([quantization.scale; 3].to_vec());
+            }
+        }
+        if !children.is_empty() {
+            node["children"] = json!(children);
+        }
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    /// Places a node without a mesh, e.g. a plant root in a scene.
+    pub fn set_translation(&mut self, node: usize, translation: Vec3) {
+        if let Some(node) = self.nodes.get_mut(node) {
+            node["translation"] = json!(translation.to_array());
+        }
+    }
+
+    /// Orients a node without a mesh, e.g. a joint.
+    pub fn set_rotation(&mut self, node: usize, rotation: Quat) {
+        if let Some(node) = self.nodes.get_mut(node) {
+            node["rotation"] = json!(rotation.to_array());
+        }
+    }
+
+    /// Scales a node and everything below it.
+    pub fn set_scale(&mut self, node: usize, scale: Vec3) {
+        if let Some(node) = self.nodes.get_mut(node) {
+            node["scale"] = json!(scale.to_array());
+        }
+    }
+
+    /// Adds a skin over the given joint nodes, with one inverse bind matrix
+    /// per joint. Returns the skin index for [`GlbBuilder::set_skin`].
+    pub fn add_skin(&mut self, joints: &[usize], inverse_bind_matrices: &[Mat4]) -> usize {
+        let matrices: Vec<f32> = inverse_bind_matrices
+            .iter()
+            .flat_map(Mat4::to_cols_array)
+            .collect();
+        let view = self.push_view(BinSource::OwnedF32(matrices), None);
+        let accessor = self.push_accessor(json!({
+            "bufferView": view,
+            "componentType": COMPONENT_FLOAT,
+            "count": inverse_bind_matrices.len(),
+            "type": "MAT4",
+        }));
+        self.skins.push(json!({
+            "joints": joints,
+            "inverseBindMatrices": accessor,
+        }));
+        self.skins.len() - 1
+    }
+
+    /// Binds a mesh node to a skin.
+    pub fn set_skin(&mut self, node: usize, skin: usize) {
+        if let Some(node) = self.nodes.get_mut(node) {
+            node["skin"] = json!(skin);
+        }
+    }
+
+    /// Adds an animation of node scales held between keys (`STEP`
+    /// interpolation). Each track is a node and its `(time, uniform scale)`
+    /// keys in increasing time; a scale of zero hides the node. Returns the
+    /// animation index.
+    pub fn add_step_scale_animation(
+        &mut self,
+        name: &str,
+        tracks: &[(usize, Vec<(f32, f32)>)],
+    ) -> usize {
+        let mut samplers = Vec::new();
+        let mut channels = Vec::new();
+        for (node, keys) in tracks {
+            if keys.is_empty() {
+                continue;
+            }
+            let times: Vec<f32> = keys.iter().map(|(time, _)| *time).collect();
+            let scales: Vec<f32> = keys.iter().flat_map(|(_, scale)| [*scale; 3]).collect();
+            let (min, max) = (times[0], times[times.len() - 1]);
+            let input_view = self.push_view(BinSource::OwnedF32(times), None);
+            let input = self.push_accessor(json!({
+                "bufferView": input_view,
+                "componentType": COMPONENT_FLOAT,
+                "count": keys.len(),
+                "type": "SCALAR",
+                "min": [min],
+                "max": [max],
+            }));
+            let output_view = self.push_view(BinSource::OwnedF32(scales), None);
+            let output = self.push_accessor(json!({
+                "bufferView": output_view,
+                "componentType": COMPONENT_FLOAT,
+                "count": keys.len(),
+                "type": "VEC3",
+            }));
+            channels.push(json!({
+                "sampler": samplers.len(),
+                "target": { "node": node, "path": "scale" },
+            }));
+            samplers.push(json!({
+                "input": input,
+                "output": output,
+                "interpolation": "STEP",
+            }));
+        }
+        self.animations.push(json!({
+            "name": name,
+            "samplers": samplers,
+            "channels": channels,
+        }));
+        self.animations.len() - 1
+    }
+
+    /// Serializes the scene with the given root nodes into GLB bytes.
+    pub fn finish(
+        self,
+        roots: &[usize],
+        material_settings: &HashMap<u8, MaterialSettings>,
+    ) -> Vec<u8> {
+        let mut out = Vec::new();
+        // Writing into a Vec cannot fail
+        let _ = self.write_to(&mut out, roots, material_settings);
+        out
+    }
+
+    /// Streams the scene with the given root nodes to a writer as GLB.
+    pub fn write_to<W: Write + ?Sized>(
+        self,
+        out: &mut W,
+        roots: &[usize],
+        material_settings: &HashMap<u8, MaterialSettings>,
+    ) -> io::Result<()> {
+        self.write_scene_to(out, roots, &[material_settings])
+    }
+
+    /// Streams the scene to a writer as GLB, with one settings map per
+    /// material set (see [`GlbBuilder::use_material_set`]).
+    pub fn write_scene_to<W: Write + ?Sized>(
+        mut self,
+        out: &mut W,
+        roots: &[usize],
+        material_sets: &[&HashMap<u8, MaterialSettings>],
+    ) -> io::Result<()> {
+        // Image data goes into the binary chunk like the geometry
+        let uses_cutout = self.material_indices.keys().any(|&(_, _, cutout)| cutout);
+        let cutout_image = match self.cutout_texture {
+            Some(png) if uses_cutout => Some(self.push_view(BinSource::Bytes(png), None)),
+            _ => None,
+        };
+
+        let mut uses_emissive_strength = false;
+        let mut materials = vec![Value::Null; self.material_indices.len()];
+        for (&(set, material_id, cutout), &index) in &self.material_indices {
+            let settings = material_sets
+                .get(set)
+                .and_then(|settings| settings.get(&material_id))
+                .cloned()
+                .unwrap_or_default();
+            let mut name = if material_sets.len() > 1 {
+                format!("plant{}_mat{}", set + 1, material_id)
+            } else {
+                format!("mat{}", material_id)
+            };
+            if cutout {
+                name.push_str("_cutout");
+            }
+            let (mut material, strength_ext) = material_json(&name, &settings);
+            if cutout {
+                material["alphaMode"] = json!("MASK");
+                material["alphaCutoff"] = json!(0.5);
+                material["doubleSided"] = json!(true);
+                if cutout_image.is_some() {
+                    material["pbrMetallicRoughness"]["baseColorTexture"] = json!({ "index": 0 });
+                }
+            }
+            uses_emissive_strength |= strength_ext;
+            materials[index] = material;
+        }
+
+        let bin_len = self.bin_len.next_multiple_of(4);
+
+        let mut root = json!({
+            "asset": {
+                "version": "2.0",
+                "generator": concat!("L-System Explorer ", env!("CARGO_PKG_VERSION")),
+            },
+            "scene": 0,
+            "scenes": [{ "nodes": roots }],
+            "nodes": self.nodes,
+            "meshes": self.meshes,
+            "materials": materials,
+            "accessors": self.accessors,
+            "bufferViews": self.buffer_views,
+            "buffers": [{ "byteLength": bin_len }],
+        });
+        if let Some(view) = cutout_image {
+            root["images"] = json!([{ "bufferView": view, "mimeType": "image/png" }]);
+            root["samplers"] = json!([{
+                "magFilter": FILTER_LINEAR,
+                "minFilter": FILTER_LINEAR_MIPMAP_LINEAR,
+                "wrapS": WRAP_CLAMP_TO_EDGE,
+                "wrapT": WRAP_CLAMP_TO_EDGE,
+            }]);
+            root["textures"] = json!([{ "source": 0, "sampler": 0 }]);
+        }
+        if !self.skins.is_empty() {
+            root["skins"] = json!(self.skins);
+        }
+        if !self.animations.is_empty() {
+            root["animations"] = json!(self.animations);
+        }
+        let mut extensions_used = Vec::new();
+        if uses_emissive_strength {
+            extensions_used.push("KHR_materials_emissive_strength");
+        }
+        if self.mesh_quantization.iter().any(Option::is_some) {
+            // Integer positions are unreadable without the extension
+            extensions_used.push("KHR_mesh_quantization");
+            root["extensionsRequired"] = json!(["KHR_mesh_quantization"]);
+        }
+        if !extensions_used.is_empty() {
+            root["extensionsUsed"] = json!(extensions_used);
+        }
+
+        let mut json_bytes = serde_json::to_vec(&root).unwrap_or_default();
+        while !json_bytes.len().is_multiple_of(4) {
+            json_bytes.push(b' ');
+        }
+
+        let total_len = 12 + 8 + json_bytes.len() + 8 + bin_len;
+        if u32::try_from(total_len).is_err() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "GLB exceeds the 4 GiB format limit",
+            ));
+        }
+        out.write_all(&GLB_MAGIC.to_le_bytes())?;
+        out.write_all(&GLB_VERSION.to_le_bytes())?;
+        out.write_all(&(total_len as u32).to_le_bytes())?;
+        out.write_all(&(json_bytes.len() as u32).to_le_bytes())?;
+        out.write_all(&CHUNK_JSON.to_le_bytes())?;
+        out.write_all(&json_bytes)?;
+        out.write_all(&(bin_len as u32).to_le_bytes())?;
+        out.write_all(&CHUNK_BIN.to_le_bytes())?;
+
+        let mut written = 0;
+        for (offset, source) in &self.bin_views {
+            out.write_all(&[0; 3][..offset - written])?;
+            source.write_to(out)?;
+            written = offset + source.byte_len();
+        }
+        out.write_all(&[0; 3][..bin_len - written])?;
+        Ok(())
+    }
+}
+
+/// Builds a glTF PBR material from a material slot.
+/// Returns the material JSON and whether it needs `KHR_materials_emissive_strength`.
+fn material_json(name: &str, settings: &MaterialSettings) -> (Value, bool) {
+    let base = Color::srgb_from_array(settings.base_color).to_linear();
+    let emission = Color::srgb_from_array(settings.emission_color).to_linear();
+    let emissive = [
+        emission.red * settings.emission_strength,
+        emission.green * settings.emission_strength,
+        emission.blue * settings.emission_strength,
+    ];
+
+    // glTF clamps emissiveFactor to [0, 1]; larger intensities go into the extension
+    let peak = emissive.iter().copied().fold(0.0_f32, f32::max);
+    let (factor, strength) = if peak > 1.0 {
+        (emissive.map(|c| c / peak), Some(peak))
+    } else {
+        (emissive, None)
+    };
+
+    let mut material = json!({
+        "name": name,
+        "pbrMetallicRoughness": {
+            "baseColorFactor": [base.red, base.green, base.blue, 1.0],
+            "metallicFactor": settings.metallic,
+            "roughnessFactor": settings.roughness,
+        },
+        "emissiveFactor": factor,
+    });
+    if let Some(strength) = strength {
+        material["extensions"] = json!({
+            "KHR_materials_emissive_strength": { "emissiveStrength": strength }
+        });
+    }
+    (material, strength.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triangle() -> Mesh {
+        let mut mesh = Mesh::from(Cuboid::new(0.0, 0.0, 0.0));
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_POSITION,
+            vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 0.0, 1.0]; 3]);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0, 0.0]; 3]);
+        mesh.insert_indices(Indices::U32(vec![0, 1, 2]));
+        mesh
+    }
+
+    fn parse_json_chunk(glb: &[u8]) -> Value {
+        let json_len = u32::from_le_bytes(glb[12..16].try_into().unwrap()) as usize;
+        serde_json::from_slice(&glb[20..20 + json_len]).unwrap()
+    }
+
+    #[test]
+    fn test_skinned_mesh() {
+        let mut skinned = triangle();
+        skinned.insert_attribute(
+            Mesh::ATTRIBUTE_JOINT_INDEX,
+            VertexAttributeValues::Uint16x4(vec![[0, 0, 0, 0], [0, 1, 0, 0], [1, 0, 0, 0]]),
+        );
+        skinned.insert_attribute(
+            Mesh::ATTRIBUTE_JOINT_WEIGHT,
+            vec![
+                [1.0, 0.0, 0.0, 0.0],
+                [0.5, 0.5, 0.0, 0.0],
+                [1.0, 0.0, 0.0, 0.0],
+            ],
+        );
+        let mut builder = GlbBuilder::with_options(GlbOptions {
+            quantize_positions: true,
+            ..default()
+        });
+        let tip = builder.add_node("Joint1", None, &[]);
+        builder.set_translation(tip, Vec3::Y);
+        let base = builder.add_node("Joint0", None, &[tip]);
+        builder.set_rotation(base, Quat::from_rotation_z(0.5));
+        let skin = builder.add_skin(&[base, tip], &[Mat4::IDENTITY, Mat4::IDENTITY]);
+        let mesh = builder.add_mesh("Skinned_mat0", &skinned, 0).unwrap();
+        let node = builder.add_node("Skinned_mat0", Some(mesh), &[]);
+        builder.set_skin(node, skin);
+        let glb = builder.finish(&[base, node], &HashMap::new());
+
+        let json = parse_json_chunk(&glb);
+        assert_eq!(json["skins"][0]["joints"], json!([base, tip]));
+        let matrices =
+            &json["accessors"][json["skins"][0]["inverseBindMatrices"].as_u64().unwrap() as usize];
+        assert_eq!(matrices["type"], "MAT4");
+        assert!(
+            json["bufferViews"][matrices["bufferView"].as_u64().unwrap() as usize]
+                .get("target")
+                .is_none()
+        );
+        let attributes = &json["meshes"][0]["primitives"][0]["attributes"];
+        assert!(attributes.get("JOINTS_0").is_some());
+        assert!(attributes.get("WEIGHTS_0").is_some());
+        assert_eq!(json["nodes"][node]["skin"], 0);
+        // Skinned positions stay float, as the node cannot dequantize them
+        assert!(json.get("extensionsRequired").is_none());
+    }
+
+    #[test]
+    fn test_step_scale_animation() {
+        let mut builder = GlbBuilder::new();
+        let first = builder.add_node("Frame1", None, &[]);
+        let second = builder.add_node("Frame2", None, &[]);
+        builder.add_step_scale_animation(
+            "Growth",
+            &[
+                (first, vec![(0.0, 1.0), (0.5, 0.0)]),
+                (second, vec![(0.0, 0.0), (0.5, 1.0)]),
+            ],
+        );
+        let glb = builder.finish(&[first, second], &HashMap::new());
+
+        let json = parse_json_chunk(&glb);
+        let animation = &json["animations"][0];
+        assert_eq!(animation["name"], "Growth");
+        assert_eq!(
+            animation["channels"][1]["target"],
+            json!({ "node": second, "path": "scale" })
+        );
+        let sampler = &animation["samplers"][1];
+        assert_eq!(sampler["interpolation"], "STEP");
+        let input = &json["accessors"][sampler["input"].as_u64().unwrap() as usize];
+        assert_eq!(input["max"], json!([0.5]));
+        let output = &json["accessors"][sampler["output"].as_u64().unwrap() as usize];
+        assert_eq!(
+            (output["type"].as_str(), output["count"].as_u64()),
+            (Some("VEC3"), Some(2))
+        );
+    }
+
+    #[test]
+    fn test_glb_header_and_layout() {
+        let triangle = triangle();
+        let mut builder = GlbBuilder::new();
+        let mesh = builder.add_mesh("Trunk_mat0", &triangle, 0).unwrap();
+        let node = builder.add_node("Trunk_mat0", Some(mesh), &[]);
+        let glb = builder.finish(&[node], &HashMap::new());
+
+        assert_eq!(&glb[0..4], b"glTF");
+        assert_eq!(
+            u32::from_le_bytes(glb[8..12].try_into().unwrap()) as usize,
+            glb.len()
+        );
+        assert_eq!(glb.len() % 4, 0);
+
+        let json = parse_json_chunk(&glb);
+        assert_eq!(json["nodes"][0]["name"], "Trunk_mat0");
+        assert_eq!(json["meshes"].as_array().unwrap().len(), 1);
+        assert_eq!(json["accessors"][0]["max"][0], 1.0);
+        assert_eq!(json["materials"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_shared_material_slots() {
+        let triangle = triangle();
+        let mut builder = GlbBuilder::new();
+        let a = builder.add_mesh("a", &triangle, 2).unwrap();
+        let b = builder.add_mesh("b", &triangle, 2).unwrap();
+        let na = builder.add_node("a", Some(a), &[]);
+        let nb = builder.add_node("b", Some(b), &[]);
+        let root = builder.add_node("Plant", None, &[na, nb]);
+        let json = parse_json_chunk(&builder.finish(&[root], &HashMap::new()));
+
+        assert_eq!(json["materials"].as_array().unwrap().len(), 1);
+        assert_eq!(json["materials"][0]["name"], "mat2");
+        assert_eq!(json["nodes"][2]["children"], json!([0, 1]));
+    }
+
+    #[test]
+    fn test_cutout_materials_embed_texture() {
+        let triangle = triangle();
+        let png = [1u8, 2, 3, 4, 5];
+        let mut builder = GlbBuilder::new();
+        builder.set_cutout_texture(&png);
+        let branch = builder.add_mesh("branch", &triangle, 1).unwrap();
+        builder.use_cutout(true);
+        let leaf = builder.add_mesh("leaf", &triangle, 1).unwrap();
+        let nb = builder.add_node("branch", Some(branch), &[]);
+        let nl = builder.add_node("leaf", Some(leaf), &[]);
+        let root = builder.add_node("Plant", None, &[nb, nl]);
+        let json = parse_json_chunk(&builder.finish(&[root], &HashMap::new()));
+
+        let materials = json["materials"].as_array().unwrap();
+        assert_eq!(materials.len(), 2, "the slot has a separate cutout variant");
+        assert_eq!(materials[1]["name"], "mat1_cutout");
+        assert_eq!(materials[1]["alphaMode"], "MASK");
+        assert_eq!(materials[1]["doubleSided"], true);
+        assert_eq!(
+            materials[1]["pbrMetallicRoughness"]["baseColorTexture"]["index"],
+            0
+        );
+        assert!(materials[0].get("alphaMode").is_none());
+        let view = json["images"][0]["bufferView"].as_u64().unwrap() as usize;
+        assert_eq!(json["bufferViews"][view]["byteLength"], png.len());
+
+        // Without cutout meshes the texture is left out
+        let mut builder = GlbBuilder::new();
+        builder.set_cutout_texture(&png);
+        let mesh = builder.add_mesh("branch", &triangle, 1).unwrap();
+        let node = builder.add_node("branch", Some(mesh), &[]);
+        let json = parse_json_chunk(&builder.finish(&[node], &HashMap::new()));
+        assert!(json.get("images").is_none());
+    }
+
+    #[test]
+    fn test_scene_material_sets() {
+        let triangle = triangle();
+        let mut red = HashMap::new();
+        red.insert(
+            0,
+            MaterialSettings {
+                base_color: [1.0, 0.0, 0.0],
+                ..default()
+            },
+        );
+        let green = HashMap::new();
+
+        let mut builder = GlbBuilder::new();
+        let mut roots = Vec::new();
+        for (set, x) in [(0, -5.0), (1, 5.0)] {
+            builder.use_material_set(set);
+            let mesh = builder.add_mesh("plant", &triangle, 0).unwrap();
+            let node = builder.add_node("plant", Some(mesh), &[]);
+            let root = builder.add_node("Plant", None, &[node]);
+            builder.set_translation(root, Vec3::new(x, 0.0, 0.0));
+            roots.push(root);
+        }
+        let mut glb = Vec::new();
+        builder
+            .write_scene_to(&mut glb, &roots, &[&red, &green])
+            .unwrap();
+        let json = parse_json_chunk(&glb);
+
+        assert_eq!(json["materials"].as_array().unwrap().len(), 2);
+        assert_eq!(json["materials"][0]["name"], "plant1_mat0");
+        assert_eq!(
+            json["materials"][0]["pbrMetallicRoughness"]["baseColorFactor"][0],
+            1.0
+        );
+        assert_eq!(json["meshes"][1]["primitives"][0]["material"], 1);
+        assert_eq!(json["nodes"][3]["translation"], json!([5.0, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn test_binary_chunk_alignment() {
+        let mut odd = triangle();
+        odd.insert_indices(Indices::U16(vec![0, 1, 2]));
+        let even = triangle();
+        let mut builder = GlbBuilder::new();
+        let a = builder.add_mesh("odd", &odd, 0).unwrap();
+        let b = builder.add_mesh("even", &even, 0).unwrap();
+        let na = builder.add_node("odd", Some(a), &[]);
+        let nb = builder.add_node("even", Some(b), &[]);
+        let glb = builder.finish(&[na, nb], &HashMap::new());
+
+        let json = parse_json_chunk(&glb);
+        let json_len = u32::from_le_bytes(glb[12..16].try_into().unwrap()) as usize;
+        let bin_start = 20 + json_len + 8;
+        let bin_len = u32::from_le_bytes(glb[20 + json_len..24 + json_len].try_into().unwrap());
+        assert_eq!(bin_len % 4, 0);
+        assert_eq!(bin_start + bin_len as usize, glb.len());
+        assert_eq!(json["buffers"][0]["byteLength"], bin_len);
+
+        // The second mesh's positions start on a 4-byte boundary after the
+        // 6-byte u16 index view and hold the triangle's second vertex at x = 1.0
+        let position_view =
+            &json["bufferViews"][json["accessors"][4]["bufferView"].as_u64().unwrap() as usize];
+        let offset = position_view["byteOffset"].as_u64().unwrap() as usize;
+        assert_eq!(offset % 4, 0);
+        let x1 = bin_start + offset + 12;
+        assert_eq!(f32::from_le_bytes(glb[x1..x1 + 4].try_into().unwrap()), 1.0);
+    }
+
+    #[test]
+    fn test_compact_indices_and_quantized_positions() {
+        let triangle = triangle();
+        let mut builder = GlbBuilder::with_options(GlbOptions {
+            compact_indices: true,
+            quantize_positions: true,
+        });
+        let mesh = builder.add_mesh("a", &triangle, 0).unwrap();
+        let node = builder.add_node("a", Some(mesh), &[]);
+        let glb = builder.finish(&[node], &HashMap::new());
+        let json = parse_json_chunk(&glb);
+
+        assert_eq!(json["extensionsRequired"], json!(["KHR_mesh_quantization"]));
+        let position = &json["accessors"][0];
+        assert_eq!(position["componentType"], COMPONENT_UNSIGNED_SHORT);
+        assert_eq!(position["max"], json!([65535, 65535, 0]));
+        assert_eq!(json["bufferViews"][0]["byteStride"], 8);
+        assert_eq!(json["nodes"][0]["translation"], json!([0.0, 0.0, 0.0]));
+        assert_eq!(json["nodes"][0]["scale"][0], f64::from(1.0_f32 / 65535.0));
+
+        let indices = json["meshes"][0]["primitives"][0]["indices"]
+            .as_u64()
+            .unwrap() as usize;
+        assert_eq!(
+            json["accessors"][indices]["componentType"],
+            COMPONENT_UNSIGNED_SHORT
+        );
+    }
+
+    #[test]
+    fn test_quantized_positions_keep_normals_on_non_cubic_bounds() {
+        // A tall, thin triangle with a slanted normal
+        let positions = [[0.0, 0.0, 0.0], [0.5, 0.0, 0.25], [0.0, 8.0, 0.0]];
+        let normal = Vec3::new(-1.0, 0.0, 2.0).normalize();
+        let mut mesh = triangle();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions.to_vec());
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![normal.to_array(); 3]);
+        let mut builder = GlbBuilder::with_options(GlbOptions {
+            compact_indices: false,
+            quantize_positions: true,
+        });
+        let mesh = builder.add_mesh("a", &mesh, 0).unwrap();
+        let node = builder.add_node("a", Some(mesh), &[]);
+        let glb = builder.finish(&[node], &HashMap::new());
+        let json = parse_json_chunk(&glb);
+
+        let vec3 = |value: &Value| {
+            let v: Vec<f32> = value
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|c| c.as_f64().unwrap() as f32)
+                .collect();
+            Vec3::new(v[0], v[1], v[2])
+        };
+        let translation = vec3(&json["nodes"][node]["translation"]);
+        let scale = vec3(&json["nodes"][node]["scale"]);
+        assert_eq!(
+            scale,
+            Vec3::splat(scale.x),
+            "dequantization scale is uniform"
+        );
+
+        // Decode the quantized positions through the node transform
+        let json_len = u32::from_le_bytes(glb[12..16].try_into().unwrap()) as usize;
+        let bin_start = 20 + json_len + 8;
+        let view =
+            &json["bufferViews"][json["accessors"][0]["bufferView"].as_u64().unwrap() as usize];
+        let offset = bin_start + view["byteOffset"].as_u64().unwrap_or(0) as usize;
+        for (i, expected) in positions.iter().enumerate() {
+            let at = offset + i * 8;
+            let q = [0, 1, 2].map(|c| {
+                u16::from_le_bytes(glb[at + c * 2..at + c * 2 + 2].try_into().unwrap()) as f32
+            });
+            let decoded = translation + Vec3::from_array(q) * scale;
+            assert!(
+                decoded.distance(Vec3::from_array(*expected)) < 1e-3,
+                "vertex {} decoded to {}",
+                i,
+                decoded
+            );
+        }
+
+        // Viewers transform normals by the inverse transpose of the node scale
+        let shaded = (normal / scale).normalize();
+        assert!(
+            shaded.distance(normal) < 1e-5,
+            "normal skewed to {}",
+            shaded
+        );
+    }
+}
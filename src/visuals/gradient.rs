@@ -0,0 +1,57 @@
+//! Material gradients along the branches.
+//!
+//! A gradient tints the skeleton point colors before meshing, so the mesh
+//! builder writes it into the vertex colors and the slot's material multiplies
+//! it with the base color. Trunks can darken toward the tips without spending
+//! extra material slots.
+
+use crate::core::config::{GradientMode, MaterialGradient};
+use crate::visuals::branch_order::{branch_orders, root_distances};
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use symbios_turtle_3d::Skeleton;
+
+/// Multiplies the color of every point whose material has a gradient by the
+/// gradient's tint at that point: white at the root, `tip_tint` at the tips.
+pub fn apply_material_gradients(
+    skeleton: &mut Skeleton,
+    gradients: &HashMap<u8, MaterialGradient>,
+) {
+    if gradients.is_empty() {
+        return;
+    }
+    let uses = |mode: GradientMode| gradients.values().any(|g| g.mode == mode);
+    let strands: Vec<Vec<Vec3>> = skeleton
+        .strands
+        .iter()
+        .map(|strand| strand.iter().map(|point| point.position).collect())
+        .collect();
+
+    let orders = if uses(GradientMode::BranchDepth) {
+        branch_orders(&strands)
+    } else {
+        Vec::new()
+    };
+    let max_order = orders.iter().copied().max().unwrap_or(0);
+    let distances = if uses(GradientMode::RootDistance) {
+        root_distances(&strands)
+    } else {
+        Vec::new()
+    };
+    let max_distance = distances.iter().flatten().copied().fold(0.0_f32, f32::max);
+
+    for (s, strand) in skeleton.strands.iter_mut().enumerate() {
+        for (k, point) in strand.iter_mut().enumerate() {
+            let Some(gradient) = gradients.get(&point.material_id) else {
+                continue;
+            };
+            let t = match gradient.mode {
+                GradientMode::BranchDepth if max_order > 0 => orders[s] as f32 / max_order as f32,
+                GradientMode::RootDistance if max_distance > 0.0 => distances[s][k] / max_distance,
+                _ => 0.0,
+            };
+            let tint = Vec3::ONE.lerp(Vec3::from_array(gradient.tip_tint), t);
+            point.color *= tint.extend(1.0);
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! Grammar to meshes in one call, without an app or ECS resources.
+//!
+//! [`derive_and_mesh`] runs the same steps as the viewport: derivation with
+//! finalization and `#system` sections, turtle interpretation, grafting,
+//! width tapering, and meshing. Props are not meshed.
+
+use crate::logic::composition::graft_calls;
+use crate::logic::derivation::derive_blocking;
+use crate::logic::jitter::jitter_skeleton;
+use crate::logic::taper::taper_width_changes;
+use crate::visuals::mesher::SkeletonMesher;
+use crate::visuals::turtle::{interpret_skeleton, turtle_config_with_defaults};
+use bevy::prelude::*;
+use symbios::System;
+use symbios_turtle_3d::Skeleton;
+
+/// Settings of [`derive_and_mesh`]. Turtle settings are fallbacks for the
+/// grammar's `step`, `angle`, and `width` constants, as in the editor.
+#[derive(Clone, Debug)]
+pub struct MeshParams {
+    /// Decomposition rules applied once after growth.
+    pub finalization: String,
+    pub iterations: usize,
+    pub seed: u64,
+    pub step: f32,
+    /// Turn angle in degrees.
+    pub angle: f32,
+    pub width: f32,
+    pub tropism: Option<Vec3>,
+    pub elasticity: f32,
+    /// Resolution, cross-sections, welding, and shading of the meshes.
+    pub mesher: SkeletonMesher,
+}
+
+impl Default for MeshParams {
+    fn default() -> Self {
+        Self {
+            finalization: String::new(),
+            iterations: 4,
+            seed: 0,
+            step: 1.0,
+            angle: 90.0,
+            width: 0.1,
+            tropism: None,
+            elasticity: 0.0,
+            mesher: SkeletonMesher::default(),
+        }
+    }
+}
+
+/// Derives `source` and meshes the plant, one mesh per material ID in
+/// ascending order. Fails with the derivation's error message, e.g. a parse
+/// error with its line number.
+pub fn derive_and_mesh(source: &str, params: &MeshParams) -> Result<Vec<(u8, Mesh)>, String> {
+    let derived = derive_blocking(source, &params.finalization, params.iterations, params.seed)?;
+    let build = |sys: &System, seed: u64| {
+        let mut skeleton = interpret_skeleton(
+            sys,
+            turtle_config_with_defaults(
+                sys,
+                params.step,
+                params.angle,
+                params.width,
+                params.tropism,
+                params.elasticity,
+            ),
+        );
+        jitter_skeleton(&mut skeleton, seed);
+        skeleton
+    };
+
+    let mut skeleton = build(&derived.system, params.seed);
+    let sections: Vec<Option<Skeleton>> = derived
+        .subsystems
+        .iter()
+        .enumerate()
+        .map(|(i, sys)| Some(build(sys, params.seed.wrapping_add(i as u64 + 1))))
+        .collect();
+    graft_calls(&mut skeleton, &sections);
+    taper_width_changes(&mut skeleton);
+
+    let mut meshes: Vec<(u8, Mesh)> = params.mesher.build(&skeleton).into_iter().collect();
+    meshes.sort_by_key(|(material_id, _)| *material_id);
+    Ok(meshes)
+}
@@ -0,0 +1,315 @@
+//! Turtle interpreter with per-branch tropism.
+//!
+//! Follows [`symbios_turtle_3d::TurtleInterpreter`] op for op, and adds
+//! `T(x, y, z, e)`, which sets the tropism vector and elasticity for the rest
+//! of the branch. Both are part of the state `[` saves and `]` restores, so
+//! roots can grow down while shoots grow up. Until a branch sets its own, the
+//! turtle bends by the [`TurtleConfig`] values. `T` with any other number of
+//! arguments does nothing, leaving the symbol free for grammars that use it
+//! otherwise.
+
+use bevy::prelude::*;
+use std::f32::consts::PI;
+use symbios::{SymbiosState, SymbolTable};
+use symbios_turtle_3d::{
+    Skeleton, SkeletonPoint, SkeletonProp, TurtleConfig, TurtleOp, TurtleState,
+};
+
+/// Module name of the tropism command.
+pub const TROPISM_SYMBOL: &str = "T";
+
+/// What a symbol does to the turtle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PlantOp {
+    Turtle(TurtleOp),
+    /// Set the branch's tropism vector and elasticity.
+    Tropism,
+}
+
+/// Turtle state saved and restored by the branch stack.
+#[derive(Clone, Copy, Debug)]
+struct BranchState {
+    turtle: TurtleState,
+    tropism: Option<Vec3>,
+    elasticity: f32,
+}
+
+impl BranchState {
+    fn point(&self) -> SkeletonPoint {
+        SkeletonPoint {
+            position: self.turtle.position,
+            rotation: self.turtle.rotation,
+            radius: self.turtle.width / 2.0,
+            color: self.turtle.color,
+            material_id: self.turtle.material_id,
+            uv_scale: self.turtle.uv_scale,
+        }
+    }
+}
+
+/// Interprets derived systems into skeletons; see the module docs.
+pub struct PlantInterpreter {
+    op_map: Vec<PlantOp>,
+    config: TurtleConfig,
+}
+
+impl PlantInterpreter {
+    pub fn new(config: TurtleConfig) -> Self {
+        Self {
+            op_map: Vec::new(),
+            config,
+        }
+    }
+
+    fn set_op(&mut self, sym_id: u16, op: PlantOp) {
+        let idx = sym_id as usize;
+        if idx >= self.op_map.len() {
+            self.op_map
+                .resize(idx + 1, PlantOp::Turtle(TurtleOp::Ignore));
+        }
+        self.op_map[idx] = op;
+    }
+
+    /// Maps the standard turtle symbols and `T` in `interner`.
+    pub fn populate_standard_symbols(&mut self, interner: &SymbolTable) {
+        let mappings = [
+            ("F", TurtleOp::Draw),
+            ("f", TurtleOp::Move),
+            ("+", TurtleOp::Yaw(1.0)),
+            ("-", TurtleOp::Yaw(-1.0)),
+            ("&", TurtleOp::Pitch(1.0)),
+            ("^", TurtleOp::Pitch(-1.0)),
+            ("\\", TurtleOp::Roll(1.0)),
+            ("/", TurtleOp::Roll(-1.0)),
+            ("|", TurtleOp::TurnAround),
+            ("$", TurtleOp::Vertical),
+            ("!", TurtleOp::SetWidth),
+            ("[", TurtleOp::Push),
+            ("]", TurtleOp::Pop),
+            ("~", TurtleOp::Spawn(0)),
+            ("'", TurtleOp::SetColor),
+            (",", TurtleOp::SetMaterial),
+            (";", TurtleOp::SetUVScale),
+        ];
+        for (sym, op) in mappings {
+            if let Some(id) = interner.resolve_id(sym) {
+                self.set_op(id, PlantOp::Turtle(op));
+            }
+        }
+        if let Some(id) = interner.resolve_id(TROPISM_SYMBOL) {
+            self.set_op(id, PlantOp::Tropism);
+        }
+    }
+
+    /// Runs the turtle over `state`.
+    pub fn build_skeleton(&self, state: &SymbiosState) -> Skeleton {
+        let mut skeleton = Skeleton::new();
+        let mut branch = BranchState {
+            turtle: TurtleState {
+                width: self.config.initial_width,
+                ..Default::default()
+            },
+            tropism: self.config.tropism,
+            elasticity: self.config.elasticity,
+        };
+        let mut stack: Vec<BranchState> = Vec::new();
+        let default_degrees = self.config.default_angle.to_degrees();
+
+        for i in 0..state.len() {
+            let Some(view) = state.get_view(i) else {
+                break;
+            };
+            let op = self
+                .op_map
+                .get(view.sym as usize)
+                .copied()
+                .unwrap_or(PlantOp::Turtle(TurtleOp::Ignore));
+            let p = |idx: usize, def: f32| view.params.get(idx).map_or(def, |&x| x as f32);
+            let turtle = &mut branch.turtle;
+
+            let op = match op {
+                PlantOp::Tropism => {
+                    if view.params.len() == 4 {
+                        branch.tropism = Some(Vec3::new(p(0, 0.0), p(1, 0.0), p(2, 0.0)));
+                        branch.elasticity = p(3, 0.0);
+                    }
+                    continue;
+                }
+                PlantOp::Turtle(op) => op,
+            };
+            match op {
+                TurtleOp::Draw | TurtleOp::Move => {
+                    if skeleton.strands.is_empty() {
+                        skeleton.add_node(branch.point(), true);
+                    }
+                    let turtle = &mut branch.turtle;
+                    turtle.position += turtle.up() * p(0, self.config.default_step);
+                    let is_move = op == TurtleOp::Move;
+                    if !is_move
+                        && let Some(tropism) = branch.tropism
+                        && branch.elasticity > 0.0
+                    {
+                        let bend = turtle.up().cross(tropism);
+                        let strength = bend.length();
+                        if strength > 0.0001 {
+                            turtle.rotate_axis(bend / strength, branch.elasticity * strength);
+                        }
+                    }
+                    skeleton.add_node(branch.point(), is_move);
+                }
+                TurtleOp::Yaw(sign) => {
+                    turtle.rotate_local_z(p(0, default_degrees).to_radians() * sign);
+                }
+                TurtleOp::Pitch(sign) => {
+                    turtle.rotate_local_x(p(0, default_degrees).to_radians() * sign);
+                }
+                TurtleOp::Roll(sign) => {
+                    turtle.rotate_local_y(p(0, default_degrees).to_radians() * sign);
+                }
+                TurtleOp::TurnAround => turtle.rotate_local_z(PI),
+                TurtleOp::Vertical => {
+                    let heading = turtle.up();
+                    let left = Vec3::Y.cross(heading).normalize_or_zero();
+                    if left.length_squared() > 0.001 {
+                        let up = heading.cross(left).normalize();
+                        turtle.rotation = Quat::from_mat3(&Mat3::from_cols(-left, heading, up));
+                    }
+                }
+                TurtleOp::SetWidth => turtle.width = p(0, turtle.width),
+                TurtleOp::SetColor => match view.params.len() {
+                    1 => turtle.color = Vec4::new(p(0, 0.0), p(0, 0.0), p(0, 0.0), 1.0),
+                    3 => turtle.color = Vec4::new(p(0, 0.0), p(1, 0.0), p(2, 0.0), 1.0),
+                    4 => turtle.color = Vec4::new(p(0, 0.0), p(1, 0.0), p(2, 0.0), p(3, 1.0)),
+                    _ => {}
+                },
+                TurtleOp::SetMaterial => turtle.material_id = p(0, 0.0) as u8,
+                TurtleOp::SetUVScale => turtle.uv_scale = p(0, 1.0).max(0.0),
+                TurtleOp::Push => {
+                    if stack.len() < self.config.max_stack_depth {
+                        stack.push(branch);
+                        skeleton.add_node(branch.point(), true);
+                    }
+                }
+                TurtleOp::Pop => {
+                    if let Some(saved) = stack.pop() {
+                        branch = saved;
+                        skeleton.add_node(branch.point(), true);
+                    }
+                }
+                TurtleOp::Spawn(default_id) => {
+                    skeleton.add_prop(SkeletonProp {
+                        prop_id: view.params.first().map_or(default_id, |&x| x as u16),
+                        position: turtle.position,
+                        rotation: turtle.rotation,
+                        scale: Vec3::splat(p(1, 1.0)),
+                        color: turtle.color,
+                        material_id: turtle.material_id,
+                    });
+                }
+                TurtleOp::Ignore => {}
+            }
+        }
+        skeleton
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visuals::turtle::axiom_skeleton;
+    use symbios_turtle_3d::TurtleInterpreter;
+
+    fn config(tropism: Option<Vec3>, elasticity: f32) -> TurtleConfig {
+        TurtleConfig {
+            default_step: 1.0,
+            default_angle: 30f32.to_radians(),
+            initial_width: 0.2,
+            tropism,
+            elasticity,
+            max_stack_depth: 1024,
+        }
+    }
+
+    fn skeleton(axiom: &str, tropism: Option<Vec3>, elasticity: f32) -> Skeleton {
+        axiom_skeleton(axiom, 30.0, 0.2, tropism, elasticity)
+    }
+
+    /// Tip of the strand drawn last in the first branch, i.e. the end of
+    /// `skeleton.strands[k]`.
+    fn tip(skeleton: &Skeleton, k: usize) -> Vec3 {
+        skeleton.strands[k].last().unwrap().position
+    }
+
+    #[test]
+    fn test_matches_upstream_interpreter() {
+        let axiom = "F !(0.3) [ + F(2) ~(1, 0.5) ] & F '(0.5) ,(1) [ ^ / f F ] \\ | $ F";
+        let gravity = Some(Vec3::new(0.0, -1.0, 0.0));
+        let ours = skeleton(axiom, gravity, 0.2);
+
+        let mut sys = symbios::System::new();
+        sys.set_axiom(axiom).unwrap();
+        sys.derive(0).unwrap();
+        let mut upstream = TurtleInterpreter::new(config(gravity, 0.2));
+        upstream.populate_standard_symbols(&sys.interner);
+        let theirs = upstream.build_skeleton(&sys.state);
+
+        assert_eq!(ours.strands.len(), theirs.strands.len());
+        for (a, b) in ours
+            .strands
+            .iter()
+            .flatten()
+            .zip(theirs.strands.iter().flatten())
+        {
+            assert!(a.position.distance(b.position) < 1e-5);
+            assert!(a.rotation.dot(b.rotation).abs() > 1.0 - 1e-6);
+            assert_eq!(a.radius, b.radius);
+            assert_eq!(a.material_id, b.material_id);
+        }
+        assert_eq!(ours.props.len(), theirs.props.len());
+    }
+
+    #[test]
+    fn test_tropism_stays_in_its_branch() {
+        let gravity = Some(Vec3::new(0.0, -1.0, 0.0));
+        let plain = skeleton("F [ + F F F ] [ - F F F ] F F", gravity, 0.2);
+        let local = skeleton(
+            "F [ + T(1, 0, 0, 0.8) F F F ] [ - F F F ] F F",
+            gravity,
+            0.2,
+        );
+        assert_eq!(plain.strands.len(), local.strands.len());
+
+        // Strands: trunk, marked branch, the point `]` returns to, sibling,
+        // continuation
+        let (bent, unbent) = (tip(&local, 1), tip(&plain, 1));
+        assert!(
+            bent.x > unbent.x + 0.5,
+            "the marked branch bends toward +X: {} vs {}",
+            bent,
+            unbent
+        );
+        let heading = local.strands[1].last().unwrap().rotation * Vec3::Y;
+        let before = plain.strands[1].last().unwrap().rotation * Vec3::Y;
+        assert!(heading.x > before.x, "{} vs {}", heading, before);
+
+        // The trunk, the sibling, and the continuation after `]` keep the
+        // plant's tropism
+        for k in [0, 2, 3, 4] {
+            assert!(
+                tip(&local, k).distance(tip(&plain, k)) < 1e-5,
+                "strand {}",
+                k
+            );
+        }
+    }
+
+    #[test]
+    fn test_tropism_needs_four_arguments() {
+        let plain = skeleton("F F T(1) F T F", None, 0.0);
+        let straight = skeleton("F F F F", None, 0.0);
+        assert!(tip(&plain, 0).distance(tip(&straight, 0)) < 1e-5);
+
+        let bent = skeleton("F T(1, 0, 0, 0.5) F F", None, 0.0);
+        assert!(tip(&bent, 0).x > 0.1);
+    }
+}
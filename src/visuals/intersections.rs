@@ -0,0 +1,325 @@
+//! Self-intersection analysis of the editor skeleton.
+//!
+//! Every segment between two consecutive strand points is a capsule whose
+//! radius runs linearly from one point's radius to the other's. Segments are
+//! binned into a uniform spatial hash, and each pair sharing a cell is tested
+//! by the distance between the closest points of the two segments. Pairs that
+//! share an endpoint (consecutive segments, a branch and its parent, sibling
+//! branches) always touch and are skipped.
+
+use crate::core::config::{LSystemConfig, LSystemEngine, SubSystemEngines};
+use crate::visuals::turtle::{TurtleRenderState, editor_skeleton};
+use bevy::platform::collections::{HashMap, HashSet};
+use bevy::prelude::*;
+use symbios_turtle_3d::Skeleton;
+
+/// Endpoints closer than this are treated as shared.
+const SHARED_ENDPOINT_TOLERANCE: f32 = 1e-4;
+
+/// Upper bound on located contacts kept in a report, to bound gizmo drawing.
+pub const MAX_CONTACT_MARKERS: usize = 20_000;
+
+/// One capsule of the skeleton.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Capsule {
+    pub start: Vec3,
+    pub end: Vec3,
+    pub start_radius: f32,
+    pub end_radius: f32,
+}
+
+impl Capsule {
+    fn radius_at(&self, t: f32) -> f32 {
+        self.start_radius + (self.end_radius - self.start_radius) * t
+    }
+
+    fn max_radius(&self) -> f32 {
+        self.start_radius.max(self.end_radius)
+    }
+
+    fn shares_endpoint(&self, other: &Capsule) -> bool {
+        let tolerance = SHARED_ENDPOINT_TOLERANCE * SHARED_ENDPOINT_TOLERANCE;
+        [self.start, self.end].iter().any(|a| {
+            [other.start, other.end]
+                .iter()
+                .any(|b| a.distance_squared(*b) <= tolerance)
+        })
+    }
+}
+
+/// Two overlapping capsules.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Contact {
+    /// Midpoint between the closest points of the two segments.
+    pub point: Vec3,
+    /// How far the capsules overlap.
+    pub depth: f32,
+}
+
+/// Result of an intersection check.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IntersectionReport {
+    /// Capsules in the skeleton.
+    pub capsules: Vec<Capsule>,
+    /// Intersecting pairs found.
+    pub pairs: usize,
+    /// Intersecting pairs each capsule takes part in, by capsule index.
+    pub hits: Vec<u32>,
+    /// Located contacts, capped at [`MAX_CONTACT_MARKERS`].
+    pub contacts: Vec<Contact>,
+    /// Deepest overlap found.
+    pub max_depth: f32,
+}
+
+impl IntersectionReport {
+    /// Number of capsules that intersect at least one other.
+    pub fn intersecting_segments(&self) -> usize {
+        self.hits.iter().filter(|hits| **hits > 0).count()
+    }
+}
+
+/// Splits every strand into capsules, skipping zero-length segments.
+pub fn skeleton_capsules(skeleton: &Skeleton) -> Vec<Capsule> {
+    skeleton
+        .strands
+        .iter()
+        .flat_map(|strand| strand.windows(2))
+        .filter(|pair| pair[0].position.distance_squared(pair[1].position) > 0.0)
+        .map(|pair| Capsule {
+            start: pair[0].position,
+            end: pair[1].position,
+            start_radius: pair[0].radius,
+            end_radius: pair[1].radius,
+        })
+        .collect()
+}
+
+/// Finds the overlapping capsule pairs of a skeleton.
+pub fn find_intersections(skeleton: &Skeleton) -> IntersectionReport {
+    let capsules = skeleton_capsules(skeleton);
+    let mut report = IntersectionReport {
+        hits: vec![0; capsules.len()],
+        ..default()
+    };
+    if capsules.len() < 2 {
+        report.capsules = capsules;
+        return report;
+    }
+
+    // Cells about twice the size of an average capsule
+    let average_extent = capsules
+        .iter()
+        .map(|c| c.start.distance(c.end) + 2.0 * c.max_radius())
+        .sum::<f32>()
+        / capsules.len() as f32;
+    let cell_size = (average_extent * 2.0).max(f32::EPSILON);
+    let cell_of = |p: Vec3| (p / cell_size).floor().as_ivec3();
+
+    let mut grid: HashMap<IVec3, Vec<u32>> = HashMap::default();
+    for (index, capsule) in capsules.iter().enumerate() {
+        let margin = Vec3::splat(capsule.max_radius());
+        let min = cell_of(capsule.start.min(capsule.end) - margin);
+        let max = cell_of(capsule.start.max(capsule.end) + margin);
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                for z in min.z..=max.z {
+                    grid.entry(IVec3::new(x, y, z))
+                        .or_default()
+                        .push(index as u32);
+                }
+            }
+        }
+    }
+
+    let mut tested: HashSet<(u32, u32)> = HashSet::default();
+    for members in grid.values() {
+        for (i, &a) in members.iter().enumerate() {
+            for &b in &members[i + 1..] {
+                if !tested.insert((a.min(b), a.max(b))) {
+                    continue;
+                }
+                let (ca, cb) = (&capsules[a as usize], &capsules[b as usize]);
+                if ca.shares_endpoint(cb) {
+                    continue;
+                }
+                let (s, t, pa, pb) = closest_points(ca.start, ca.end, cb.start, cb.end);
+                let depth = ca.radius_at(s) + cb.radius_at(t) - pa.distance(pb);
+                if depth <= 0.0 {
+                    continue;
+                }
+                report.pairs += 1;
+                report.hits[a as usize] += 1;
+                report.hits[b as usize] += 1;
+                report.max_depth = report.max_depth.max(depth);
+                if report.contacts.len() < MAX_CONTACT_MARKERS {
+                    report.contacts.push(Contact {
+                        point: (pa + pb) * 0.5,
+                        depth,
+                    });
+                }
+            }
+        }
+    }
+    report.capsules = capsules;
+    report
+}
+
+/// Closest points of segments `p1-q1` and `p2-q2`, with their parameters
+/// along each segment (Ericson, Real-Time Collision Detection 5.1.9).
+fn closest_points(p1: Vec3, q1: Vec3, p2: Vec3, q2: Vec3) -> (f32, f32, Vec3, Vec3) {
+    let d1 = q1 - p1;
+    let d2 = q2 - p2;
+    let r = p1 - p2;
+    let a = d1.length_squared();
+    let e = d2.length_squared();
+    let f = d2.dot(r);
+
+    let (s, t) = if a <= f32::EPSILON && e <= f32::EPSILON {
+        (0.0, 0.0)
+    } else if a <= f32::EPSILON {
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = d1.dot(r);
+        if e <= f32::EPSILON {
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = d1.dot(d2);
+            let denom = a * e - b * b;
+            let mut s = if denom > f32::EPSILON {
+                ((b * f - c * e) / denom).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let mut t = (b * s + f) / e;
+            if t < 0.0 {
+                t = 0.0;
+                s = (-c / a).clamp(0.0, 1.0);
+            } else if t > 1.0 {
+                t = 1.0;
+                s = ((b - c) / a).clamp(0.0, 1.0);
+            }
+            (s, t)
+        }
+    };
+    (s, t, p1 + d1 * s, p2 + d2 * t)
+}
+
+/// Intersection check requests, results, and the heatmap overlay.
+#[derive(Resource, Default)]
+pub struct IntersectionCheck {
+    /// Set by the UI to analyze the current editor skeleton.
+    pub requested: bool,
+    /// Result of the last check, cleared when the meshes are regenerated.
+    pub report: Option<IntersectionReport>,
+    /// Color intersecting segments by how many others they hit.
+    pub heatmap: bool,
+    /// `TurtleRenderState::mesh_revision` the report was made for.
+    revision: u64,
+}
+
+/// Interprets the editor plant and checks it on request; drops stale reports
+/// after remeshing.
+pub fn run_intersection_check(
+    mut check: ResMut<IntersectionCheck>,
+    engine: Res<LSystemEngine>,
+    subsystems: Res<SubSystemEngines>,
+    config: Res<LSystemConfig>,
+    render_state: Res<TurtleRenderState>,
+) {
+    if check.revision != render_state.mesh_revision {
+        check.revision = render_state.mesh_revision;
+        check.report = None;
+    }
+    if !check.requested {
+        return;
+    }
+    check.requested = false;
+
+    let report = find_intersections(&editor_skeleton(&engine, &subsystems, &config));
+    info!(
+        "Intersection check: {} pairs among {} segments",
+        report.pairs,
+        report.capsules.len()
+    );
+    check.report = Some(report);
+}
+
+/// Draws the intersecting segments from yellow (one hit) to red (the most
+/// hits), and a cross at every contact.
+pub fn draw_intersection_gizmos(check: Res<IntersectionCheck>, mut gizmos: Gizmos) {
+    if !check.heatmap {
+        return;
+    }
+    let Some(report) = &check.report else {
+        return;
+    };
+    let most = report.hits.iter().copied().max().unwrap_or(0).max(1) as f32;
+    for (capsule, hits) in report.capsules.iter().zip(&report.hits) {
+        if *hits == 0 {
+            continue;
+        }
+        let heat = if most > 1.0 {
+            (*hits as f32 - 1.0) / (most - 1.0)
+        } else {
+            1.0
+        };
+        gizmos.line(
+            capsule.start,
+            capsule.end,
+            Color::srgb(1.0, 0.9 * (1.0 - heat), 0.1),
+        );
+    }
+    for contact in &report.contacts {
+        let size = contact.depth.max(f32::EPSILON);
+        for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+            gizmos.line(
+                contact.point - axis * size,
+                contact.point + axis * size,
+                Color::WHITE,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closest_points_of_crossing_segments() {
+        let (s, t, pa, pb) = closest_points(
+            Vec3::new(-1.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.5),
+            Vec3::new(0.0, 1.0, 0.5),
+        );
+        assert!((s - 0.5).abs() < 1e-6 && (t - 0.5).abs() < 1e-6);
+        assert!((pa.distance(pb) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_parallel_segments() {
+        let (_, _, pa, pb) = closest_points(
+            Vec3::ZERO,
+            Vec3::X,
+            Vec3::new(0.5, 0.2, 0.0),
+            Vec3::new(2.0, 0.2, 0.0),
+        );
+        assert!((pa.distance(pb) - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_shared_endpoints_are_not_intersections() {
+        let capsule = |start: Vec3, end: Vec3| Capsule {
+            start,
+            end,
+            start_radius: 0.1,
+            end_radius: 0.1,
+        };
+        let a = capsule(Vec3::ZERO, Vec3::Y);
+        let b = capsule(Vec3::Y, Vec3::new(0.0, 2.0, 0.0));
+        assert!(a.shares_endpoint(&b));
+        let c = capsule(Vec3::new(0.05, 0.5, -1.0), Vec3::new(0.05, 0.5, 1.0));
+        assert!(!a.shares_endpoint(&c));
+    }
+}
@@ -0,0 +1,329 @@
+//! Manifold / watertightness analysis of the editor meshes.
+//!
+//! Vertices are matched by position (within [`POSITION_TOLERANCE`]), so UV and
+//! normal seams do not count as holes. Every edge of a closed, manifold surface
+//! is shared by exactly two triangles: edges used once are open (holes, tube
+//! ends), edges used three or more times are non-manifold. Triangles with
+//! repeated vertices or near-zero area are reported as degenerate.
+
+use crate::visuals::turtle::{BucketMaterialId, LSystemMeshTag, TurtleRenderState};
+use bevy::mesh::VertexAttributeValues;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use std::collections::BTreeMap;
+
+/// Positions closer than this are treated as the same vertex.
+pub const POSITION_TOLERANCE: f32 = 1e-4;
+
+/// Triangles with less area than this are degenerate.
+const MIN_TRIANGLE_AREA: f32 = 1e-10;
+
+/// Upper bound on problem markers kept per material, to bound gizmo drawing.
+pub const MAX_PROBLEM_MARKERS: usize = 20_000;
+
+/// Kind of topology defect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProblemKind {
+    OpenEdge,
+    NonManifoldEdge,
+    DegenerateTriangle,
+}
+
+impl ProblemKind {
+    pub fn color(&self) -> Color {
+        match self {
+            ProblemKind::OpenEdge => Color::srgb(1.0, 0.2, 0.2),
+            ProblemKind::NonManifoldEdge => Color::srgb(1.0, 0.2, 1.0),
+            ProblemKind::DegenerateTriangle => Color::srgb(1.0, 0.9, 0.1),
+        }
+    }
+}
+
+/// A defect located by a world-space segment (the edge, or the longest edge
+/// of a degenerate triangle).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TopologyProblem {
+    pub kind: ProblemKind,
+    pub start: Vec3,
+    pub end: Vec3,
+}
+
+/// Topology counts of one mesh or material bucket.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MeshTopology {
+    pub triangles: usize,
+    pub open_edges: usize,
+    pub non_manifold_edges: usize,
+    pub degenerate_triangles: usize,
+    /// Located defects, capped at [`MAX_PROBLEM_MARKERS`].
+    pub problems: Vec<TopologyProblem>,
+}
+
+impl MeshTopology {
+    /// True if the surface is closed, manifold, and free of degenerate triangles.
+    pub fn is_watertight(&self) -> bool {
+        self.open_edges == 0 && self.non_manifold_edges == 0 && self.degenerate_triangles == 0
+    }
+
+    fn push_problem(&mut self, problem: TopologyProblem) {
+        if self.problems.len() < MAX_PROBLEM_MARKERS {
+            self.problems.push(problem);
+        }
+    }
+
+    /// Merges another mesh's counts into this one.
+    pub fn add(&mut self, other: MeshTopology) {
+        self.triangles += other.triangles;
+        self.open_edges += other.open_edges;
+        self.non_manifold_edges += other.non_manifold_edges;
+        self.degenerate_triangles += other.degenerate_triangles;
+        for problem in other.problems {
+            self.push_problem(problem);
+        }
+    }
+}
+
+/// Analyzes a triangle-list mesh, transforming problem locations by `transform`.
+pub fn analyze_mesh(mesh: &Mesh, transform: &GlobalTransform) -> MeshTopology {
+    let mut topology = MeshTopology::default();
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return topology;
+    };
+
+    // Canonical vertex per distinct position
+    let mut ids_by_cell: HashMap<[i64; 3], u32> = HashMap::default();
+    let canonical: Vec<u32> = positions
+        .iter()
+        .map(|p| {
+            let cell = p.map(|c| (c / POSITION_TOLERANCE).round() as i64);
+            let next = ids_by_cell.len() as u32;
+            *ids_by_cell.entry(cell).or_insert(next)
+        })
+        .collect();
+
+    let indices: Vec<usize> = match mesh.indices() {
+        Some(indices) => indices.iter().collect(),
+        None => (0..positions.len()).collect(),
+    };
+    let world = |i: usize| transform.transform_point(Vec3::from_array(positions[i]));
+
+    // Edge (low id, high id) -> (use count, one vertex index per end)
+    let mut edges: HashMap<(u32, u32), (u32, usize, usize)> = HashMap::default();
+    for triangle in indices.chunks_exact(3) {
+        topology.triangles += 1;
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]];
+        let [pa, pb, pc] = [a, b, c].map(|i| Vec3::from_array(positions[i]));
+        let repeated = canonical[a] == canonical[b]
+            || canonical[b] == canonical[c]
+            || canonical[a] == canonical[c];
+        if repeated || (pb - pa).cross(pc - pa).length() * 0.5 < MIN_TRIANGLE_AREA {
+            topology.degenerate_triangles += 1;
+            let (start, end) = [(a, b), (b, c), (c, a)]
+                .into_iter()
+                .max_by(|x, y| {
+                    let lx = world(x.0).distance_squared(world(x.1));
+                    let ly = world(y.0).distance_squared(world(y.1));
+                    lx.total_cmp(&ly)
+                })
+                .unwrap_or((a, b));
+            topology.push_problem(TopologyProblem {
+                kind: ProblemKind::DegenerateTriangle,
+                start: world(start),
+                end: world(end),
+            });
+            continue;
+        }
+
+        for (u, v) in [(a, b), (b, c), (c, a)] {
+            let key = if canonical[u] < canonical[v] {
+                (canonical[u], canonical[v])
+            } else {
+                (canonical[v], canonical[u])
+            };
+            edges.entry(key).or_insert((0, u, v)).0 += 1;
+        }
+    }
+
+    for (count, u, v) in edges.into_values() {
+        let kind = match count {
+            1 => ProblemKind::OpenEdge,
+            2 => continue,
+            _ => ProblemKind::NonManifoldEdge,
+        };
+        match kind {
+            ProblemKind::OpenEdge => topology.open_edges += 1,
+            _ => topology.non_manifold_edges += 1,
+        }
+        topology.push_problem(TopologyProblem {
+            kind,
+            start: world(u),
+            end: world(v),
+        });
+    }
+    topology
+}
+
+/// Manifold check requests, results, and viewport highlighting.
+#[derive(Resource, Default)]
+pub struct ManifoldCheck {
+    /// Set by the UI to analyze the current editor meshes.
+    pub requested: bool,
+    /// Results per material ID, cleared when the meshes are regenerated.
+    pub report: Option<BTreeMap<u8, MeshTopology>>,
+    /// Draw problem edges in the viewport.
+    pub highlight: bool,
+    /// `TurtleRenderState::mesh_revision` the report was made for.
+    revision: u64,
+}
+
+impl ManifoldCheck {
+    /// Totals across all materials.
+    pub fn totals(&self) -> Option<MeshTopology> {
+        let report = self.report.as_ref()?;
+        let mut total = MeshTopology::default();
+        for topology in report.values() {
+            total.add(topology.clone());
+        }
+        Some(total)
+    }
+}
+
+/// Analyzes the editor meshes on request and drops stale reports after remeshing.
+pub fn run_manifold_check(
+    mut check: ResMut<ManifoldCheck>,
+    mesh_assets: Res<Assets<Mesh>>,
+    meshes: Query<(&Mesh3d, &GlobalTransform, &BucketMaterialId), With<LSystemMeshTag>>,
+    render_state: Res<TurtleRenderState>,
+) {
+    if check.revision != render_state.mesh_revision {
+        check.revision = render_state.mesh_revision;
+        check.report = None;
+    }
+    if !check.requested {
+        return;
+    }
+    check.requested = false;
+
+    let mut report: BTreeMap<u8, MeshTopology> = BTreeMap::new();
+    for (handle, transform, material) in &meshes {
+        let Some(mesh) = mesh_assets.get(&handle.0) else {
+            continue;
+        };
+        report
+            .entry(material.0)
+            .or_default()
+            .add(analyze_mesh(mesh, transform));
+    }
+    check.report = Some(report);
+}
+
+/// Draws the located defects of the last report as colored gizmo lines.
+pub fn draw_manifold_gizmos(check: Res<ManifoldCheck>, mut gizmos: Gizmos) {
+    if !check.highlight {
+        return;
+    }
+    let Some(report) = &check.report else {
+        return;
+    };
+    for problem in report.values().flat_map(|topology| &topology.problems) {
+        gizmos.line(problem.start, problem.end, problem.kind.color());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::asset::RenderAssetUsages;
+    use bevy::mesh::{Indices, PrimitiveTopology};
+
+    fn mesh(positions: Vec<[f32; 3]>, indices: Vec<u32>) -> Mesh {
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_indices(Indices::U32(indices));
+        mesh
+    }
+
+    fn tetrahedron() -> Mesh {
+        mesh(
+            vec![
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [0.0, 0.0, 1.0],
+            ],
+            vec![0, 2, 1, 0, 1, 3, 1, 2, 3, 0, 3, 2],
+        )
+    }
+
+    #[test]
+    fn test_closed_mesh_is_watertight() {
+        let topology = analyze_mesh(&tetrahedron(), &GlobalTransform::IDENTITY);
+        assert_eq!(topology.triangles, 4);
+        assert!(topology.is_watertight(), "{:?}", topology);
+    }
+
+    #[test]
+    fn test_duplicated_seam_vertices_are_not_holes() {
+        // Same tetrahedron with vertex 0 duplicated for one face, as at a UV seam
+        let seamed = mesh(
+            vec![
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [0.0, 0.0, 1.0],
+                [0.0, 0.0, 0.0],
+            ],
+            vec![4, 2, 1, 0, 1, 3, 1, 2, 3, 0, 3, 2],
+        );
+        assert!(analyze_mesh(&seamed, &GlobalTransform::IDENTITY).is_watertight());
+    }
+
+    #[test]
+    fn test_open_non_manifold_and_degenerate() {
+        let open = analyze_mesh(
+            &mesh(
+                vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+                vec![0, 1, 2],
+            ),
+            &GlobalTransform::IDENTITY,
+        );
+        assert_eq!(open.open_edges, 3);
+        assert_eq!(open.problems.len(), 3);
+
+        // Three triangles sharing the edge 0-1
+        let fin = analyze_mesh(
+            &mesh(
+                vec![
+                    [0.0, 0.0, 0.0],
+                    [1.0, 0.0, 0.0],
+                    [0.0, 1.0, 0.0],
+                    [0.0, -1.0, 0.0],
+                    [0.0, 0.0, 1.0],
+                ],
+                vec![0, 1, 2, 1, 0, 3, 0, 1, 4],
+            ),
+            &GlobalTransform::IDENTITY,
+        );
+        assert_eq!(fin.non_manifold_edges, 1);
+        assert_eq!(fin.open_edges, 6);
+
+        let sliver = analyze_mesh(
+            &mesh(
+                vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [2.0, 0.0, 0.0]],
+                vec![0, 1, 2],
+            ),
+            &GlobalTransform::IDENTITY,
+        );
+        assert_eq!(sliver.degenerate_triangles, 1);
+        assert_eq!(sliver.open_edges, 0);
+        assert_eq!(
+            sliver.problems[0].start.distance(sliver.problems[0].end),
+            2.0
+        );
+    }
+}
@@ -0,0 +1,597 @@
+//! Skeleton meshing with selectable cross-sections.
+//!
+//! [`SkeletonMesher`] is the single entry point that turns a skeleton into one
+//! mesh per material. Materials with the default [`CrossSection::Circle`] go
+//! through bevy_symbios' `LSystemMeshBuilder`; the others are swept here along
+//! their strands, with one flat-shaded face per profile edge so square, ribbon,
+//! and n-gon branches keep their hard edges. The finished buckets can be
+//! welded and have their normals recomputed, see [`Shading`].
+//!
+//! Rings sit at the skeleton's points unless a ring spacing is set, in which
+//! case [`respace_rings`] first evens them out along each strand.
+
+use crate::visuals::branch_order::strand_attachments;
+use crate::visuals::normals::recompute_normals;
+use crate::visuals::weld::{WeldSettings, WeldStats, weld_mesh};
+use bevy::asset::RenderAssetUsages;
+use bevy::mesh::{Indices, PrimitiveTopology};
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy_symbios::LSystemMeshBuilder;
+use std::borrow::Cow;
+use symbios_turtle_3d::{Skeleton, SkeletonPoint};
+
+/// Most rings inserted into one segment by [`respace_rings`].
+const MAX_RINGS_PER_SEGMENT: usize = 64;
+
+/// Points closer than this are width or material steps, which are kept.
+const COINCIDENT_DISTANCE: f32 = 1e-5;
+
+/// Shape of a branch cross-section, in units of the branch radius.
+#[derive(Debug, Clone, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub enum CrossSection {
+    /// Smooth round tube with the mesh resolution's number of sides.
+    #[default]
+    Circle,
+    /// Regular polygon with the given number of sides.
+    NGon(u32),
+    /// Flat strip as wide as the branch, e.g. for grass blades.
+    Ribbon,
+    /// Star with the given number of points; `inner` is the radius of the notches.
+    Star { points: u32, inner: f32 },
+    /// User polygon, counter-clockwise, in the branch's local XZ plane.
+    Custom(Vec<[f32; 2]>),
+}
+
+impl CrossSection {
+    /// Kinds offered in the UI, with default parameters.
+    pub fn presets() -> [CrossSection; 6] {
+        [
+            CrossSection::Circle,
+            CrossSection::NGon(4),
+            CrossSection::NGon(6),
+            CrossSection::Ribbon,
+            CrossSection::Star {
+                points: 5,
+                inner: 0.5,
+            },
+            CrossSection::Custom(vec![[1.0, 0.0], [-0.5, 0.87], [-0.5, -0.87]]),
+        ]
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            CrossSection::Circle => "Circle".to_string(),
+            CrossSection::NGon(4) => "Square".to_string(),
+            CrossSection::NGon(sides) => format!("{}-gon", sides),
+            CrossSection::Ribbon => "Ribbon".to_string(),
+            CrossSection::Star { points, .. } => format!("{}-point star", points),
+            CrossSection::Custom(_) => "Custom".to_string(),
+        }
+    }
+
+    /// The closed profile polygon, or `None` for the builder's circle.
+    pub fn outline(&self) -> Option<Vec<Vec2>> {
+        let regular = |count: u32, radius: &dyn Fn(u32) -> f32| -> Vec<Vec2> {
+            (0..count)
+                .map(|i| {
+                    let angle = i as f32 / count as f32 * std::f32::consts::TAU;
+                    Vec2::from_angle(angle) * radius(i)
+                })
+                .collect()
+        };
+        let outline = match self {
+            CrossSection::Circle => return None,
+            CrossSection::NGon(sides) => {
+                // Rotated so a square has flat sides facing the local axes
+                let sides = (*sides).max(3);
+                let offset = std::f32::consts::PI / sides as f32;
+                (0..sides)
+                    .map(|i| {
+                        Vec2::from_angle(i as f32 / sides as f32 * std::f32::consts::TAU + offset)
+                            / offset.cos()
+                    })
+                    .collect()
+            }
+            CrossSection::Ribbon => vec![
+                Vec2::new(1.0, -0.05),
+                Vec2::new(1.0, 0.05),
+                Vec2::new(-1.0, 0.05),
+                Vec2::new(-1.0, -0.05),
+            ],
+            CrossSection::Star { points, inner } => {
+                let inner = inner.clamp(0.05, 1.0);
+                regular(points.max(&2) * 2, &|i| {
+                    if i % 2 == 0 { 1.0 } else { inner }
+                })
+            }
+            CrossSection::Custom(points) => points.iter().map(|&p| Vec2::from_array(p)).collect(),
+        };
+        (outline.len() >= 3).then_some(outline)
+    }
+}
+
+/// How the mesher's normals are shaded.
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub enum Shading {
+    /// Keep the normals of the tube builder and profile sweep.
+    #[default]
+    Smooth,
+    /// One normal per triangle, for hard-edged geometric shapes.
+    Flat,
+    /// Recompute normals, smoothing across edges up to the given angle in
+    /// degrees and splitting sharper ones.
+    AutoSmooth(f32),
+}
+
+impl Shading {
+    pub const DEFAULT_ANGLE: f32 = 30.0;
+
+    /// Kinds offered in the UI, with the default angle.
+    pub fn presets() -> [Shading; 3] {
+        [
+            Shading::Smooth,
+            Shading::Flat,
+            Shading::AutoSmooth(Self::DEFAULT_ANGLE),
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Shading::Smooth => "Smooth",
+            Shading::Flat => "Flat",
+            Shading::AutoSmooth(_) => "Auto Smooth",
+        }
+    }
+
+    /// Recomputes the mesh's normals, unless the shading keeps them.
+    pub fn apply(&self, mesh: &mut Mesh) {
+        match *self {
+            Shading::Smooth => {}
+            Shading::Flat => recompute_normals(mesh, 0.0),
+            Shading::AutoSmooth(angle) => recompute_normals(mesh, angle),
+        }
+    }
+}
+
+/// Parses a custom profile written as `x,y; x,y; ...`, with at least 3 points.
+pub fn parse_profile(text: &str) -> Result<Vec<[f32; 2]>, String> {
+    let points = text
+        .split(';')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (x, y) = pair
+                .split_once(',')
+                .ok_or_else(|| format!("Expected x,y but got \"{}\"", pair))?;
+            let parse = |v: &str| {
+                v.trim()
+                    .parse::<f32>()
+                    .map_err(|_| format!("Invalid number \"{}\"", v.trim()))
+            };
+            Ok([parse(x)?, parse(y)?])
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    if points.len() < 3 {
+        return Err("A profile needs at least 3 points".into());
+    }
+    Ok(points)
+}
+
+/// Formats a custom profile the way [`parse_profile`] reads it.
+pub fn format_profile(points: &[[f32; 2]]) -> String {
+    points
+        .iter()
+        .map(|[x, y]| format!("{},{}", x, y))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Meshes skeletons into one mesh per material.
+#[derive(Clone, Debug)]
+pub struct SkeletonMesher {
+    /// Sides of circular cross-sections.
+    pub resolution: u32,
+    /// Cross-section per material; missing materials are circles.
+    pub profiles: HashMap<u8, CrossSection>,
+    /// Weld tolerances applied to the finished buckets, or `None` to keep the
+    /// builder's duplicated ring and restart vertices.
+    pub weld: Option<WeldSettings>,
+    /// Normals of the finished buckets, applied after welding.
+    pub shading: Shading,
+    /// Largest distance between rings along a strand, or `None` for one ring
+    /// per skeleton point; see [`respace_rings`].
+    pub ring_spacing: Option<f32>,
+}
+
+impl Default for SkeletonMesher {
+    fn default() -> Self {
+        Self::new(8)
+    }
+}
+
+impl SkeletonMesher {
+    pub fn new(resolution: u32) -> Self {
+        Self {
+            resolution,
+            profiles: HashMap::new(),
+            weld: None,
+            shading: Shading::Smooth,
+            ring_spacing: None,
+        }
+    }
+
+    pub fn with_profiles(mut self, profiles: HashMap<u8, CrossSection>) -> Self {
+        self.profiles = profiles;
+        self
+    }
+
+    pub fn with_weld(mut self, weld: Option<WeldSettings>) -> Self {
+        self.weld = weld;
+        self
+    }
+
+    pub fn with_shading(mut self, shading: Shading) -> Self {
+        self.shading = shading;
+        self
+    }
+
+    pub fn with_ring_spacing(mut self, ring_spacing: Option<f32>) -> Self {
+        self.ring_spacing = ring_spacing;
+        self
+    }
+
+    /// The skeleton with the mesher's ring spacing applied. Callers meshing a
+    /// skeleton in parts respace it whole first, so branch attachments are
+    /// seen, and mesh the parts without spacing.
+    pub fn respace<'a>(&self, skeleton: &'a Skeleton) -> Cow<'a, Skeleton> {
+        match self.ring_spacing {
+            Some(spacing) => Cow::Owned(respace_rings(skeleton, spacing)),
+            None => Cow::Borrowed(skeleton),
+        }
+    }
+
+    /// Welds every bucket with the mesher's tolerances, if welding is on, then
+    /// applies the shading.
+    pub fn finish_buckets(&self, buckets: &mut HashMap<u8, Mesh>) -> WeldStats {
+        let mut stats = WeldStats::default();
+        for mesh in buckets.values_mut() {
+            if let Some(settings) = self.weld {
+                stats.add(weld_mesh(mesh, settings));
+            }
+            self.shading.apply(mesh);
+        }
+        stats
+    }
+
+    fn outline(&self, material_id: u8) -> Option<Vec<Vec2>> {
+        self.profiles.get(&material_id)?.outline()
+    }
+
+    /// Meshes the skeleton's strands, then welds and shades the result; props
+    /// are not included. A strand uses the cross-section of its first point's
+    /// material.
+    pub fn build(&self, skeleton: &Skeleton) -> HashMap<u8, Mesh> {
+        let mut buckets = self.build_unfinished(skeleton);
+        self.finish_buckets(&mut buckets);
+        buckets
+    }
+
+    /// [`SkeletonMesher::build`] without welding or shading, for meshing in
+    /// chunks that are finished once merged.
+    pub fn build_unfinished(&self, skeleton: &Skeleton) -> HashMap<u8, Mesh> {
+        let skeleton = &*self.respace(skeleton);
+        let builder = LSystemMeshBuilder::new().with_resolution(self.resolution);
+        let outlines: HashMap<u8, Vec<Vec2>> = self
+            .profiles
+            .keys()
+            .filter_map(|&id| Some((id, self.outline(id)?)))
+            .collect();
+        if outlines.is_empty() {
+            return builder.build(skeleton);
+        }
+
+        let profiled = |strand: &Vec<SkeletonPoint>| {
+            strand
+                .first()
+                .and_then(|point| outlines.get(&point.material_id))
+        };
+        let mut round = skeleton.clone();
+        round.props.clear();
+        round.strands.retain(|strand| profiled(strand).is_none());
+        let mut buckets = if round.strands.is_empty() {
+            HashMap::new()
+        } else {
+            builder.build(&round)
+        };
+
+        let mut swept: HashMap<u8, SweepBuffers> = HashMap::new();
+        for strand in &skeleton.strands {
+            if let Some(outline) = profiled(strand) {
+                swept
+                    .entry(strand[0].material_id)
+                    .or_default()
+                    .add_strand(strand, outline);
+            }
+        }
+        for (material_id, buffers) in swept {
+            let mesh = buffers.into_mesh();
+            match buckets.get_mut(&material_id) {
+                Some(existing) => {
+                    if let Err(e) = existing.merge(&mesh) {
+                        warn!("Failed to merge profiled mesh: {}", e);
+                    }
+                }
+                None => {
+                    buckets.insert(material_id, mesh);
+                }
+            }
+        }
+        buckets
+    }
+}
+
+/// Evens out the rings along every strand to about `spacing` apart.
+///
+/// Segments longer than `spacing` get intermediate points on a cubic Hermite
+/// curve through their neighbors, so straight runs have rings to bend at when
+/// deformed and curved runs stay smooth. Interior points less than half
+/// `spacing` from the previous ring are dropped, unless a branch starts there
+/// or they step width or material.
+pub fn respace_rings(skeleton: &Skeleton, spacing: f32) -> Skeleton {
+    let mut respaced = skeleton.clone();
+    if spacing.is_nan() || spacing <= 0.0 {
+        return respaced;
+    }
+    let positions: Vec<Vec<Vec3>> = skeleton
+        .strands
+        .iter()
+        .map(|strand| strand.iter().map(|point| point.position).collect())
+        .collect();
+    let mut pinned: Vec<Vec<bool>> = positions
+        .iter()
+        .map(|strand| vec![false; strand.len()])
+        .collect();
+    for (parent, k) in strand_attachments(&positions).into_iter().flatten() {
+        pinned[parent][k] = true;
+    }
+    for (strand, pinned) in respaced.strands.iter_mut().zip(&pinned) {
+        let kept = coalesce_strand(strand, pinned, spacing);
+        *strand = subdivide_strand(&kept, spacing);
+    }
+    respaced
+}
+
+fn coalesce_strand(strand: &[SkeletonPoint], pinned: &[bool], spacing: f32) -> Vec<SkeletonPoint> {
+    let last = strand.len().saturating_sub(1);
+    let mut kept: Vec<SkeletonPoint> = Vec::with_capacity(strand.len());
+    for (k, point) in strand.iter().enumerate() {
+        let droppable = k > 0 && k < last && !pinned[k] && {
+            let previous = kept.last().unwrap_or(&strand[0]);
+            let next = &strand[k + 1];
+            let gap = previous.position.distance(point.position);
+            gap > COINCIDENT_DISTANCE
+                && gap < spacing * 0.5
+                && point.position.distance(next.position) > COINCIDENT_DISTANCE
+                && previous.material_id == point.material_id
+                && next.material_id == point.material_id
+        };
+        if !droppable {
+            kept.push(*point);
+        }
+    }
+    kept
+}
+
+fn subdivide_strand(strand: &[SkeletonPoint], spacing: f32) -> Vec<SkeletonPoint> {
+    let Some(last) = strand.last() else {
+        return Vec::new();
+    };
+    let mut out = Vec::with_capacity(strand.len());
+    for i in 0..strand.len() - 1 {
+        let (a, b) = (&strand[i], &strand[i + 1]);
+        out.push(*a);
+        let length = a.position.distance(b.position);
+        let rings = ((length / spacing).ceil() as usize)
+            .saturating_sub(1)
+            .min(MAX_RINGS_PER_SEGMENT);
+        if rings == 0 {
+            continue;
+        }
+        // Catmull-Rom style tangents scaled to the segment, which keep
+        // straight runs straight
+        let before = strand[i.saturating_sub(1)].position;
+        let after = strand[(i + 2).min(strand.len() - 1)].position;
+        let tangent_a = (b.position - before).normalize_or_zero() * length;
+        let tangent_b = (after - a.position).normalize_or_zero() * length;
+        for r in 1..=rings {
+            let t = r as f32 / (rings + 1) as f32;
+            let (t2, t3) = (t * t, t * t * t);
+            let mut point = *a;
+            point.position = a.position * (2.0 * t3 - 3.0 * t2 + 1.0)
+                + tangent_a * (t3 - 2.0 * t2 + t)
+                + b.position * (3.0 * t2 - 2.0 * t3)
+                + tangent_b * (t3 - t2);
+            point.rotation = a.rotation.slerp(b.rotation, t);
+            point.radius = a.radius + (b.radius - a.radius) * t;
+            point.color = a.color.lerp(b.color, t);
+            out.push(point);
+        }
+    }
+    out.push(*last);
+    out
+}
+
+/// Vertex data of swept strands for one material.
+#[derive(Default)]
+struct SweepBuffers {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    colors: Vec<[f32; 4]>,
+    indices: Vec<u32>,
+}
+
+impl SweepBuffers {
+    /// Sweeps `outline` along the strand, one quad strip per profile edge,
+    /// and closes both ends with a fan.
+    fn add_strand(&mut self, strand: &[SkeletonPoint], outline: &[Vec2]) {
+        if strand.len() < 2 {
+            return;
+        }
+        let perimeter: f32 = (0..outline.len())
+            .map(|e| outline[e].distance(outline[(e + 1) % outline.len()]))
+            .sum();
+        let ring = |point: &SkeletonPoint, p: Vec2| {
+            point.position + point.rotation * Vec3::new(p.x, 0.0, p.y) * point.radius
+        };
+
+        let mut along = vec![0.0_f32; strand.len()];
+        for k in 1..strand.len() {
+            along[k] = along[k - 1] + strand[k].position.distance(strand[k - 1].position);
+        }
+
+        let mut u = 0.0;
+        for e in 0..outline.len() {
+            let (a, b) = (outline[e], outline[(e + 1) % outline.len()]);
+            let edge = b - a;
+            let normal_2d = Vec2::new(edge.y, -edge.x).normalize_or_zero();
+            let u_next = u + edge.length() / perimeter.max(f32::EPSILON);
+            let base = self.positions.len() as u32;
+            for (k, point) in strand.iter().enumerate() {
+                let normal =
+                    (point.rotation * Vec3::new(normal_2d.x, 0.0, normal_2d.y)).normalize_or_zero();
+                for (p, tex_u) in [(a, u), (b, u_next)] {
+                    self.positions.push(ring(point, p).to_array());
+                    self.normals.push(normal.to_array());
+                    self.uvs.push([tex_u, along[k]]);
+                    self.colors.push(point.color.to_array());
+                }
+            }
+            for k in 0..strand.len() as u32 - 1 {
+                let a0 = base + 2 * k;
+                let (b0, a1, b1) = (a0 + 1, a0 + 2, a0 + 3);
+                // Counter-clockwise seen from outside
+                self.indices.extend([a0, a1, b0, b0, a1, b1]);
+            }
+            u = u_next;
+        }
+
+        for (point, flip) in [(&strand[0], true), (&strand[strand.len() - 1], false)] {
+            let heading = point.rotation * Vec3::Y;
+            let normal = if flip { -heading } else { heading };
+            let center = self.positions.len() as u32;
+            self.positions.push(point.position.to_array());
+            for p in outline {
+                self.positions.push(ring(point, *p).to_array());
+            }
+            for p in std::iter::once(&Vec2::ZERO).chain(outline) {
+                self.normals.push(normal.to_array());
+                self.uvs.push([p.x * 0.5 + 0.5, p.y * 0.5 + 0.5]);
+                self.colors.push(point.color.to_array());
+            }
+            let n = outline.len() as u32;
+            for i in 0..n {
+                let (a, b) = (center + 1 + i, center + 1 + (i + 1) % n);
+                if flip {
+                    self.indices.extend([center, a, b]);
+                } else {
+                    self.indices.extend([center, b, a]);
+                }
+            }
+        }
+    }
+
+    fn into_mesh(self) -> Mesh {
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, self.positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, self.normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, self.uvs)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, self.colors)
+        .with_inserted_indices(Indices::U32(self.indices))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visuals::turtle::axiom_skeleton;
+
+    #[test]
+    fn test_outlines() {
+        assert!(CrossSection::Circle.outline().is_none());
+        let square = CrossSection::NGon(4).outline().unwrap();
+        assert_eq!(square.len(), 4);
+        // Flat sides at unit distance from the center
+        assert!(square.iter().all(|p| (p.x.abs() - 1.0).abs() < 1e-5));
+        let star = CrossSection::Star {
+            points: 5,
+            inner: 0.5,
+        };
+        assert_eq!(star.outline().unwrap().len(), 10);
+        assert!(
+            CrossSection::Custom(vec![[0.0, 0.0], [1.0, 0.0]])
+                .outline()
+                .is_none()
+        );
+    }
+
+    fn skeleton(axiom: &str) -> Skeleton {
+        axiom_skeleton(axiom, 45.0, 0.2, None, 0.0)
+    }
+
+    #[test]
+    fn test_respace_subdivides_long_segments() {
+        let original = skeleton("F");
+        let respaced = respace_rings(&original, 0.25);
+        let strand = &respaced.strands[0];
+        assert_eq!(strand.len(), 5);
+        for pair in strand.windows(2) {
+            let gap = pair[0].position.distance(pair[1].position);
+            assert!((gap - 0.25).abs() < 1e-4, "even spacing, got {}", gap);
+        }
+        // Straight segments stay on the line
+        assert!(strand.iter().all(|p| p.position.x.abs() < 1e-5));
+        assert_eq!(
+            strand.last().unwrap().position,
+            original.strands[0][1].position
+        );
+    }
+
+    #[test]
+    fn test_respace_coalesces_but_keeps_branch_points() {
+        let original = skeleton("F F [ + F ] F F");
+        let respaced = respace_rings(&original, 4.0);
+        let trunk = &respaced.strands[0];
+        assert!(trunk.len() < original.strands[0].len());
+        assert_eq!(
+            trunk.first().unwrap().position,
+            original.strands[0][0].position
+        );
+        assert_eq!(
+            trunk.last().unwrap().position,
+            original.strands[0].last().unwrap().position
+        );
+        let branch_start = respaced.strands[1][0].position;
+        assert!(
+            trunk
+                .iter()
+                .any(|p| p.position.distance(branch_start) < 1e-5),
+            "the ring the branch starts on is kept"
+        );
+        assert_eq!(respaced.strands[1].len(), original.strands[1].len());
+    }
+
+    #[test]
+    fn test_parse_profile() {
+        let points = parse_profile("1,0; -0.5, 0.87 ;-0.5,-0.87").unwrap();
+        assert_eq!(points.len(), 3);
+        assert_eq!(parse_profile(&format_profile(&points)).unwrap(), points);
+        assert!(parse_profile("1,0; 2,0").is_err());
+        assert!(parse_profile("1,0; 2; 3,3").is_err());
+        assert!(parse_profile("1,x; 2,0; 3,3").is_err());
+    }
+}
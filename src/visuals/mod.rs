@@ -1,5 +1,35 @@
 pub mod assets;
+pub mod branch_order;
+pub mod camera;
+#[cfg(feature = "nursery")]
+pub mod comparison;
+pub mod debug;
+pub mod decimate;
+#[cfg(feature = "export")]
 pub mod export;
+pub mod garden;
+pub mod genotype_render;
+#[cfg(feature = "export")]
+pub mod gltf;
+pub mod gradient;
+pub mod headless;
+pub mod interpreter;
+pub mod intersections;
+pub mod mesh_check;
+pub mod mesher;
+pub mod normals;
+#[cfg(feature = "nursery")]
 pub mod nursery_render;
+#[cfg(feature = "physics")]
+pub mod physics;
+pub mod scatter;
 pub mod scene;
+#[cfg(feature = "export")]
+pub mod skeleton_json;
+#[cfg(feature = "export")]
+pub mod skinning;
+pub mod textures;
+pub mod thumbnail;
+pub mod tropism;
 pub mod turtle;
+pub mod weld;
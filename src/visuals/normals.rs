@@ -0,0 +1,157 @@
+//! Normal recomputation with an angle threshold.
+//!
+//! The tube builder emits smooth normals around each ring, which makes sharp
+//! geometric presets (the Koch island, square spirals) look rounded. This pass
+//! rebuilds the normals from the triangles: around every position, faces whose
+//! normals differ by at most the threshold angle share one averaged normal, and
+//! corners across a sharper edge get their own vertex. A threshold of zero
+//! gives flat shading.
+
+use crate::visuals::weld::{float_components, select_vertices};
+use bevy::mesh::{Indices, MeshVertexAttribute, VertexAttributeValues};
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+
+/// Positions closer than this share a smoothing neighbourhood.
+const POSITION_TOLERANCE: f32 = 1e-4;
+
+/// Recomputes the normals of a triangle-list mesh in place; see the module docs.
+///
+/// Meshes without positions, or with non-float attributes, are left untouched.
+pub fn recompute_normals(mesh: &mut Mesh, max_angle_degrees: f32) {
+    let Some(positions) = mesh
+        .attribute(Mesh::ATTRIBUTE_POSITION)
+        .and_then(VertexAttributeValues::as_float3)
+        .map(<[[f32; 3]]>::to_vec)
+    else {
+        return;
+    };
+    let attributes: Vec<(MeshVertexAttribute, VertexAttributeValues)> = mesh
+        .attributes()
+        .filter(|(attribute, _)| attribute.id != Mesh::ATTRIBUTE_NORMAL.id)
+        .map(|(attribute, values)| (*attribute, values.clone()))
+        .collect();
+    if attributes
+        .iter()
+        .any(|(_, values)| float_components(values).is_none())
+    {
+        return;
+    }
+    let corners: Vec<usize> = match mesh.indices() {
+        Some(indices) => indices.iter().collect(),
+        None => (0..positions.len()).collect(),
+    };
+    let triangle_count = corners.len() / 3;
+
+    // Area-weighted face normals, and the faces touching each position
+    let position_key = |p: [f32; 3]| p.map(|v| (v / POSITION_TOLERANCE).round() as i64);
+    let mut face_normals = Vec::with_capacity(triangle_count);
+    let mut faces_at: HashMap<[i64; 3], Vec<usize>> = HashMap::default();
+    for (t, triangle) in corners.chunks_exact(3).enumerate() {
+        let [a, b, c] = [0, 1, 2].map(|i| Vec3::from_array(positions[triangle[i]]));
+        face_normals.push((b - a).cross(c - a));
+        for &vertex in triangle {
+            faces_at
+                .entry(position_key(positions[vertex]))
+                .or_default()
+                .push(t);
+        }
+    }
+
+    // Slack so coplanar faces still smooth together at a zero threshold; only
+    // rounding error, as the two halves of a twisted quad are a fraction of a
+    // degree apart
+    let min_cos = max_angle_degrees.to_radians().cos() - 1e-6;
+    let mut new_vertex: HashMap<(usize, [i32; 3]), u32> = HashMap::default();
+    let mut kept = Vec::new();
+    let mut normals = Vec::new();
+    let mut indices = Vec::with_capacity(triangle_count * 3);
+    for (t, triangle) in corners.chunks_exact(3).enumerate() {
+        let own = face_normals[t].normalize_or_zero();
+        for &vertex in triangle {
+            let normal = faces_at[&position_key(positions[vertex])]
+                .iter()
+                .map(|&other| face_normals[other])
+                .filter(|other| other.normalize_or_zero().dot(own) >= min_cos)
+                .sum::<Vec3>()
+                .normalize_or(own);
+            // Corners of the same vertex with the same normal stay shared
+            let key = (vertex, (normal * 1e4).round().as_ivec3().to_array());
+            let index = *new_vertex.entry(key).or_insert_with(|| {
+                kept.push(vertex);
+                normals.push(normal.to_array());
+                kept.len() as u32 - 1
+            });
+            indices.push(index);
+        }
+    }
+
+    for (attribute, values) in attributes {
+        mesh.insert_attribute(attribute, select_vertices(&values, &kept));
+    }
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.remove_attribute(Mesh::ATTRIBUTE_TANGENT);
+    mesh.insert_indices(Indices::U32(indices));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::asset::RenderAssetUsages;
+    use bevy::mesh::PrimitiveTopology;
+
+    /// Two quads folded 90° along the x axis, sharing the fold vertices.
+    fn folded_quads() -> Mesh {
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        );
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_POSITION,
+            vec![
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [1.0, 1.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [1.0, 0.0, 1.0],
+                [0.0, 0.0, 1.0],
+            ],
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 1.0, 0.0]; 6]);
+        // Floor quad (facing -z) and wall quad (facing -y) meeting along 0-1
+        mesh.insert_indices(Indices::U32(vec![0, 2, 1, 0, 3, 2, 0, 1, 4, 0, 4, 5]));
+        mesh
+    }
+
+    fn normals(mesh: &Mesh) -> Vec<Vec3> {
+        let values = mesh.attribute(Mesh::ATTRIBUTE_NORMAL).unwrap();
+        values
+            .as_float3()
+            .unwrap()
+            .iter()
+            .map(|&n| Vec3::from_array(n))
+            .collect()
+    }
+
+    #[test]
+    fn test_sharp_fold_is_split() {
+        let mut mesh = folded_quads();
+        recompute_normals(&mut mesh, 30.0);
+        // The two fold vertices are duplicated, one copy per face
+        assert_eq!(mesh.count_vertices(), 8);
+        assert!(
+            normals(&mesh)
+                .iter()
+                .all(|n| n.abs_diff_eq(Vec3::NEG_Z, 1e-5) || n.abs_diff_eq(Vec3::NEG_Y, 1e-5))
+        );
+    }
+
+    #[test]
+    fn test_wide_threshold_smooths_fold() {
+        let mut mesh = folded_quads();
+        recompute_normals(&mut mesh, 100.0);
+        assert_eq!(mesh.count_vertices(), 6);
+        let fold = normals(&mesh)[0];
+        assert!(fold.dot(Vec3::NEG_Z) > 0.1 && fold.dot(Vec3::NEG_Y) > 0.1);
+    }
+}
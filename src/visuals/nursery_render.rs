@@ -3,22 +3,62 @@
 //! This module provides systems to render the 9-individual population
 //! as a 3D grid when nursery mode is active.
 
-use crate::core::config::{LSystemConfig, MaterialSettings, PropConfig, PropMeshType, TextureType};
+use crate::core::config::{LSystemConfig, PropConfig};
 use crate::core::genotype::PlantGenotype;
+use crate::core::includes::IncludeLibrary;
 use crate::ui::nursery::{
-    CachedGenotypeMesh, NurseryLabelTag, NurseryMeshTag, NurseryMode, NurseryPropTag, NurseryState,
-    PopulationMeshCache,
+    NurseryLabelTag, NurseryMeshTag, NurseryMode, NurseryPropTag, NurseryState,
+    PopulationMeshCache, poll_auto_evolve,
 };
 use crate::visuals::assets::PropMeshAssets;
-use bevy::math::{Affine2, Vec2};
-use bevy::platform::collections::HashMap;
+use crate::visuals::camera::frame_bounds;
+use crate::visuals::comparison::ComparisonCamera;
+use crate::visuals::genotype_render::{
+    CachedGenotypeMesh, DerivedGenotype, build_plant_assets, derive_genotype, spawn_plant_assets,
+};
+use crate::visuals::thumbnail::{ThumbnailRenderer, ThumbnailSlot};
+use crate::visuals::turtle::{poll_meshing, sync_prop_materials, toggle_editor_visibility};
 use bevy::prelude::*;
 use bevy::tasks::AsyncComputeTaskPool;
-use bevy_symbios::LSystemMeshBuilder;
+use bevy_panorbit_camera::PanOrbitCamera;
 use bevy_symbios::materials::ProceduralTextures;
 use std::sync::{Arc, Mutex};
-use symbios::System;
-use symbios_turtle_3d::{TurtleConfig, TurtleInterpreter};
+
+/// Registers the nursery state and the systems that derive, render, and pick
+/// the population grid. Only built with the `nursery` feature.
+pub struct NurseryRenderPlugin;
+
+impl Plugin for NurseryRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NurseryState>()
+            .init_resource::<PopulationMeshCache>()
+            .init_resource::<NurseryDerivationTask>()
+            .add_systems(
+                Startup,
+                setup_nursery_materials.after(bevy_symbios::materials::setup_material_assets),
+            )
+            .add_systems(
+                Update,
+                (
+                    toggle_editor_visibility,
+                    rebuild_nursery_cache,
+                    poll_nursery_derivation,
+                    render_nursery_population,
+                    sync_nursery_selection_visuals,
+                    sync_nursery_solo,
+                    handle_panel_clicks,
+                )
+                    .chain()
+                    .after(poll_meshing)
+                    .before(sync_prop_materials),
+            )
+            // Auto-evolve results are applied before the grid is rebuilt
+            .add_systems(Update, poll_auto_evolve.before(rebuild_nursery_cache));
+    }
+}
+
+/// Maximum time between the two clicks of a double-click on a panel.
+const DOUBLE_CLICK_SECONDS: f32 = 0.4;
 
 /// Cached material handles for nursery selection panels.
 /// Created once at startup to avoid per-frame allocations.
@@ -76,128 +116,10 @@ pub fn setup_nursery_materials(
     commands.insert_resource(NurseryMaterials::new(&mut materials));
 }
 
-/// Creates a StandardMaterial from a MaterialSettings, using procedural textures if available.
-fn material_from_settings(
-    settings: &MaterialSettings,
-    proc_textures: &ProceduralTextures,
-) -> StandardMaterial {
-    let emission_linear =
-        Color::srgb_from_array(settings.emission_color).to_linear() * settings.emission_strength;
-
-    let base_color_texture = match settings.texture {
-        TextureType::None => None,
-        other => proc_textures.textures.get(&other).cloned(),
-    };
-
-    StandardMaterial {
-        base_color: Color::srgb_from_array(settings.base_color),
-        perceptual_roughness: settings.roughness,
-        metallic: settings.metallic,
-        emissive: emission_linear,
-        base_color_texture,
-        uv_transform: Affine2::from_scale(Vec2::splat(settings.uv_scale)),
-        ..default()
-    }
-}
-
-/// Creates per-genotype material handles from the cached material settings.
-fn create_genotype_materials(
-    cached_materials: &HashMap<u8, MaterialSettings>,
-    proc_textures: &ProceduralTextures,
-    materials: &mut Assets<StandardMaterial>,
-) -> (
-    HashMap<u8, Handle<StandardMaterial>>,
-    Handle<StandardMaterial>,
-) {
-    let mut handles = HashMap::new();
-    let mut primary = None;
-
-    for (&slot, settings) in cached_materials {
-        let handle = materials.add(material_from_settings(settings, proc_textures));
-        if primary.is_none() {
-            primary = Some(handle.clone());
-        }
-        handles.insert(slot, handle);
-    }
-
-    let fallback = primary.unwrap_or_else(|| {
-        materials.add(StandardMaterial {
-            base_color: Color::srgb(0.55, 0.27, 0.07),
-            perceptual_roughness: 0.8,
-            ..default()
-        })
-    });
-
-    (handles, fallback)
-}
-
-/// Derives a PlantGenotype into a System with full state.
-///
-/// NOTE: Always creates a fresh `System::new()` to guarantee clean derivation state.
-/// This prevents cumulative derivation issues where calling `sys.derive(n)` on an
-/// already-derived system would result in double-growth.
-fn derive_genotype(genotype: &PlantGenotype) -> Option<System> {
-    let mut sys = System::new();
-    sys.set_seed(genotype.seed);
-
-    let mut axiom_set = false;
-
-    // Parse the source code
-    for line in genotype.source_code.lines() {
-        let trimmed = line.trim();
-        if trimmed.is_empty() || trimmed.starts_with("//") {
-            continue;
-        }
-
-        if trimmed.starts_with("omega:") {
-            let axiom = trimmed.strip_prefix("omega:")?.trim();
-            sys.set_axiom(axiom).ok()?;
-            axiom_set = true;
-        } else if trimmed.starts_with('#') {
-            sys.add_directive(trimmed).ok()?;
-        } else if trimmed.contains("->") {
-            sys.add_rule(trimmed).ok()?;
-        }
-    }
-
-    if !axiom_set {
-        return None;
-    }
-
-    // Derive growth phase
-    sys.derive(genotype.iterations).ok()?;
-
-    // Apply finalization if present
-    if !genotype.finalization_code.trim().is_empty() {
-        // Clear rules but keep constants and state
-        sys.rules.clear();
-        sys.ignored_symbols.clear();
-
-        // Parse finalization rules
-        for line in genotype.finalization_code.lines() {
-            let trimmed = line.trim();
-            if trimmed.is_empty() || trimmed.starts_with("//") || trimmed.starts_with("omega:") {
-                continue;
-            }
-
-            if trimmed.starts_with('#') {
-                sys.add_directive(trimmed).ok()?;
-            } else if trimmed.contains("->") {
-                sys.add_rule(trimmed).ok()?;
-            }
-        }
-
-        // Execute single decomposition pass
-        sys.derive(1).ok()?;
-    }
-
-    Some(sys)
-}
-
 /// Result from a single async genotype derivation.
 struct GenotypeDerivedResult {
-    index: usize,
-    system: Option<System>,
+    hash: u64,
+    derived: Option<DerivedGenotype>,
     fitness: f32,
     genotype: PlantGenotype,
     error: Option<String>,
@@ -208,7 +130,7 @@ struct GenotypeDerivedResult {
 pub struct NurseryDerivationTask {
     /// Shared container for results from all background derivation tasks.
     pending: Option<Arc<Mutex<Vec<GenotypeDerivedResult>>>>,
-    /// Total number of derivations dispatched (to know when all are done).
+    /// Number of distinct genotypes dispatched (to know when all are done).
     expected_count: usize,
     /// Generation number this task corresponds to.
     generation: usize,
@@ -219,24 +141,29 @@ pub fn rebuild_nursery_cache(
     mut nursery: ResMut<NurseryState>,
     mut cache: ResMut<PopulationMeshCache>,
     mut task: ResMut<NurseryDerivationTask>,
+    includes: Res<IncludeLibrary>,
 ) {
     if !nursery.needs_3d_rebuild || nursery.mode != NurseryMode::Enabled {
         return;
     }
 
     nursery.needs_3d_rebuild = false;
-    cache.entries.clear();
     nursery.errors.clear();
 
-    if nursery.population.is_empty() {
-        return;
-    }
-
-    let population: Vec<(usize, PlantGenotype, f32)> = nursery
+    // Only genotypes not derived for an earlier generation are dispatched;
+    // identical individuals share one derivation.
+    let hashes: Vec<u64> = nursery
+        .population
+        .iter()
+        .map(|p| p.genotype.content_hash())
+        .collect();
+    let mut missing = cache.retain_population(&hashes);
+    let population: Vec<(u64, PlantGenotype, f32)> = nursery
         .population
         .iter()
-        .enumerate()
-        .map(|(i, p)| (i, p.genotype.clone(), p.fitness))
+        .zip(&hashes)
+        .filter(|(_, hash)| missing.remove(*hash))
+        .map(|(p, &hash)| (hash, p.genotype.clone(), p.fitness))
         .collect();
 
     let results: Arc<Mutex<Vec<GenotypeDerivedResult>>> = Arc::new(Mutex::new(Vec::new()));
@@ -245,11 +172,12 @@ pub fn rebuild_nursery_cache(
     task.expected_count = population.len();
     task.generation = nursery.generation;
 
-    for (index, genotype, fitness) in population {
+    for (hash, genotype, fitness) in population {
         let results = results.clone();
+        let includes = includes.clone();
         pool.spawn(async move {
-            let (system, error) = match derive_genotype(&genotype) {
-                Some(sys) => (Some(sys), None),
+            let (derived, error) = match derive_genotype(&genotype, &includes) {
+                Some(derived) => (Some(derived), None),
                 None => (
                     None,
                     Some("Derivation failed: invalid L-system syntax".to_string()),
@@ -258,8 +186,8 @@ pub fn rebuild_nursery_cache(
 
             if let Ok(mut guard) = results.lock() {
                 guard.push(GenotypeDerivedResult {
-                    index,
-                    system,
+                    hash,
+                    derived,
                     fitness,
                     genotype,
                     error,
@@ -277,6 +205,7 @@ pub fn poll_nursery_derivation(
     mut nursery: ResMut<NurseryState>,
     mut cache: ResMut<PopulationMeshCache>,
     mut task: ResMut<NurseryDerivationTask>,
+    mut thumbnails: ResMut<ThumbnailRenderer>,
 ) {
     let Some(results) = &task.pending else {
         return;
@@ -292,37 +221,43 @@ pub fn poll_nursery_derivation(
 
     // All derivations complete — consume results
     drop(guard);
-    let results_arc = task.pending.take().unwrap();
+    let Some(results_arc) = task.pending.take() else {
+        return;
+    };
     let completed: Vec<GenotypeDerivedResult> = match Arc::try_unwrap(results_arc) {
         Ok(mutex) => mutex.into_inner().unwrap_or_default(),
-        Err(arc) => {
-            let mut guard = arc.lock().unwrap();
-            std::mem::take(&mut *guard)
-        }
+        Err(arc) => arc
+            .lock()
+            .map(|mut guard| std::mem::take(&mut *guard))
+            .unwrap_or_default(),
     };
 
     for result in completed {
-        if let Some(ref err) = result.error {
-            nursery.errors.insert(result.index, err.clone());
-        }
-
-        cache.entries.insert(
-            result.index,
-            CachedGenotypeMesh {
-                system: result.system,
-                fitness: result.fitness,
-                angle: result.genotype.angle,
-                step: result.genotype.step,
-                width: result.genotype.width,
-                elasticity: result.genotype.elasticity,
-                tropism: result.genotype.tropism.map(|t| Vec3::new(t[0], t[1], t[2])),
-                materials: result.genotype.get_material_settings(),
-                prop_mappings: result.genotype.prop_mappings.clone(),
-                error: result.error,
-            },
+        cache.derived.insert(
+            result.hash,
+            CachedGenotypeMesh::new(
+                &result.genotype,
+                result.derived,
+                result.fitness,
+                result.error,
+            ),
         );
     }
 
+    // Report every cell, including those reusing an earlier derivation
+    let nursery = &mut *nursery;
+    for (index, individual) in nursery.population.iter().enumerate() {
+        match cache.entry(index) {
+            Some(CachedGenotypeMesh {
+                error: Some(err), ..
+            }) => {
+                nursery.errors.insert(index, err.clone());
+            }
+            Some(_) => thumbnails.request(ThumbnailSlot::Nursery(index), &individual.genotype),
+            None => {}
+        }
+    }
+
     cache.cached_generation = task.generation;
     cache.dirty = true;
 }
@@ -354,15 +289,22 @@ pub fn render_nursery_population(
         {
             commands.entity(entity).despawn();
         }
-        cache.entries.clear();
+        if !cache.cells.is_empty() {
+            cache.clear();
+        }
         return;
     }
 
-    // Only rebuild meshes when cache is dirty
-    if !cache.dirty {
+    // Only rebuild meshes when the cache or the level of detail changed
+    let detail = nursery.lod.detail(config.mesh_resolution);
+    if !cache.dirty && cache.detail == Some(detail) {
         return;
     }
     cache.dirty = false;
+    if cache.detail != Some(detail) {
+        cache.assets.clear();
+        cache.detail = Some(detail);
+    }
 
     // Despawn old entities
     for entity in old_meshes
@@ -373,128 +315,50 @@ pub fn render_nursery_population(
         commands.entity(entity).despawn();
     }
 
-    // Calculate grid positions
     let spacing = nursery.grid_spacing;
-    let grid_size = nursery.grid_size;
     let pop_size = nursery.population_size();
-    let grid_offset = (grid_size as f32 - 1.0) * spacing / 2.0;
 
-    // Spawn meshes for each cached genotype
+    // Spawn meshes for each cached genotype, meshing each distinct genotype once
+    let cache = &mut *cache;
     for i in 0..pop_size {
-        let Some(cached) = cache.entries.get(&i) else {
+        let Some(&hash) = cache.cells.get(&i) else {
+            continue;
+        };
+        let Some(cached) = cache.derived.get(&hash) else {
             continue;
         };
 
-        // Calculate grid position (NxN in XZ plane)
-        let row = i / grid_size;
-        let col = i % grid_size;
-        let x = col as f32 * spacing - grid_offset;
-        let z = row as f32 * spacing - grid_offset;
-        let grid_pos = Vec3::new(x, 0.0, z);
+        let grid_pos = nursery.cell_position(i);
+        let visibility = if nursery.is_cell_visible(i) {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
 
         let is_selected = nursery.selected.contains(&i);
         let has_error = cached.error.is_some();
 
-        // Only render meshes if derivation succeeded
-        if let Some(ref system) = cached.system {
-            // Configure turtle interpreter using individual genotype parameters as fallbacks
-            let default_step = system
-                .constants
-                .get("step")
-                .map(|&s| s as f32)
-                .unwrap_or(cached.step);
-
-            let default_angle = system
-                .constants
-                .get("angle")
-                .map(|&a| a as f32)
-                .unwrap_or(cached.angle)
-                .to_radians();
-
-            let initial_width = system
-                .constants
-                .get("width")
-                .map(|&w| w as f32)
-                .unwrap_or(cached.width);
-
-            let turtle_config = TurtleConfig {
-                default_step,
-                default_angle,
-                initial_width,
-                tropism: cached.tropism,
-                elasticity: cached.elasticity,
-                max_stack_depth: 1024,
-            };
-
-            let mut interpreter = TurtleInterpreter::new(turtle_config);
-            interpreter.populate_standard_symbols(&system.interner);
-
-            // Build skeleton and meshes
-            let skeleton = interpreter.build_skeleton(&system.state);
-            let builder = LSystemMeshBuilder::new().with_resolution(config.mesh_resolution);
-            let mesh_buckets = builder.build(&skeleton);
-
-            // Create per-genotype material handles from the individual's settings
-            let (geno_materials, geno_fallback) =
-                create_genotype_materials(&cached.materials, &proc_textures, &mut materials);
-
-            // Spawn branch meshes
-            for (material_id, mesh) in mesh_buckets {
-                let material = geno_materials
-                    .get(&material_id)
-                    .unwrap_or(&geno_fallback)
-                    .clone();
-
-                commands.spawn((
-                    Mesh3d(meshes.add(mesh)),
-                    MeshMaterial3d(material),
-                    Transform::from_translation(grid_pos),
-                    NurseryMeshTag { index: i },
-                ));
-            }
-
-            // Spawn props (leaves, flowers, etc.)
-            for prop in &skeleton.props {
-                // Use per-genotype prop mapping first, fall back to global PropConfig
-                let mesh_type = cached
-                    .prop_mappings
-                    .get(&prop.prop_id)
-                    .or_else(|| prop_config.prop_meshes.get(&prop.prop_id))
-                    .copied()
-                    .unwrap_or(PropMeshType::Leaf);
-
-                let mesh_handle = prop_assets.meshes.get(&mesh_type);
-
-                if let Some(handle) = mesh_handle {
-                    // Create prop material by blending genotype material with prop color
-                    let base_handle = geno_materials
-                        .get(&prop.material_id)
-                        .unwrap_or(&geno_fallback);
-                    let base_mat = materials.get(base_handle).cloned().unwrap_or_default();
-                    let base_srgba = base_mat.base_color.to_srgba();
-                    let blended = Color::srgba(
-                        base_srgba.red * prop.color.x,
-                        base_srgba.green * prop.color.y,
-                        base_srgba.blue * prop.color.z,
-                        base_srgba.alpha * prop.color.w,
-                    );
-                    let prop_material = materials.add(StandardMaterial {
-                        base_color: blended,
-                        ..base_mat
-                    });
-
-                    commands.spawn((
-                        Mesh3d(handle.clone()),
-                        MeshMaterial3d(prop_material),
-                        Transform {
-                            translation: prop.position + grid_pos,
-                            rotation: prop.rotation,
-                            scale: prop.scale * prop_config.prop_scale,
-                        },
-                        NurseryPropTag { index: i },
-                    ));
-                }
-            }
+        if !cache.assets.contains_key(&hash)
+            && let Some(assets) = build_plant_assets(
+                cached,
+                detail,
+                &prop_config,
+                &mut meshes,
+                &mut materials,
+                &proc_textures,
+                &prop_assets,
+            )
+        {
+            cache.assets.insert(hash, assets);
+        }
+        if let Some(assets) = cache.assets.get(&hash) {
+            spawn_plant_assets(
+                &mut commands,
+                assets,
+                grid_pos,
+                (NurseryMeshTag { index: i }, visibility),
+                (NurseryPropTag { index: i }, visibility),
+            );
         }
 
         // Create a translucent horizontal panel below each plant
@@ -507,6 +371,7 @@ pub fn render_nursery_population(
             MeshMaterial3d(panel_material),
             Transform::from_translation(grid_pos + Vec3::new(0.0, -1.0, 0.0)),
             NurseryLabelTag { index: i },
+            visibility,
         ));
     }
 }
@@ -525,10 +390,7 @@ pub fn sync_nursery_selection_visuals(
 
     for (tag, mut mat_handle) in labels.iter_mut() {
         let is_selected = nursery.selected.contains(&tag.index);
-        let has_error = cache
-            .entries
-            .get(&tag.index)
-            .is_some_and(|c| c.error.is_some());
+        let has_error = cache.entry(tag.index).is_some_and(|c| c.error.is_some());
         let desired = nursery_materials.for_state(is_selected, has_error);
         if mat_handle.0 != desired {
             mat_handle.0 = desired;
@@ -536,16 +398,89 @@ pub fn sync_nursery_selection_visuals(
     }
 }
 
+/// System that shows only the solo individual and frames the camera on it.
+/// Closing the solo view shows the whole grid again and restores the view
+/// the camera had before it opened.
+#[allow(clippy::type_complexity)]
+pub fn sync_nursery_solo(
+    nursery: Res<NurseryState>,
+    cache: Res<PopulationMeshCache>,
+    mut cells: Query<(
+        AnyOf<(&NurseryMeshTag, &NurseryPropTag, &NurseryLabelTag)>,
+        &mut Visibility,
+    )>,
+    mut cameras: Query<&mut PanOrbitCamera, Without<ComparisonCamera>>,
+    mut shown: Local<Option<usize>>,
+    mut grid_view: Local<Option<(Vec3, f32)>>,
+) {
+    let solo = nursery
+        .solo
+        .filter(|_| nursery.mode == NurseryMode::Enabled);
+    if solo == *shown {
+        return;
+    }
+    *shown = solo;
+
+    for ((mesh, prop, label), mut visibility) in cells.iter_mut() {
+        let index = mesh
+            .map(|tag| tag.index)
+            .or(prop.map(|tag| tag.index))
+            .or(label.map(|tag| tag.index))
+            .unwrap_or_default();
+        let desired = if solo.is_none_or(|solo| solo == index) {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        visibility.set_if_neq(desired);
+    }
+
+    for mut camera in cameras.iter_mut() {
+        match solo {
+            Some(index) => {
+                if grid_view.is_none() {
+                    *grid_view = Some((camera.target_focus, camera.target_radius));
+                }
+                // Plant bounds once meshed, otherwise the cell's panel
+                let origin = nursery.cell_position(index);
+                let half_panel = nursery.grid_spacing * 0.45;
+                let (min, max) = cache
+                    .cells
+                    .get(&index)
+                    .and_then(|hash| cache.assets.get(hash)?.bounds)
+                    .unwrap_or((
+                        Vec3::new(-half_panel, 0.0, -half_panel),
+                        Vec3::new(half_panel, half_panel, half_panel),
+                    ));
+                frame_bounds(&mut camera, origin + min, origin + max);
+            }
+            None => {
+                if nursery.mode == NurseryMode::Enabled
+                    && let Some((focus, radius)) = *grid_view
+                {
+                    camera.target_focus = focus;
+                    camera.target_radius = radius;
+                    camera.force_update = true;
+                }
+                *grid_view = None;
+            }
+        }
+    }
+}
+
 /// System that handles clicking on nursery selection panels via ray-plane intersection.
 ///
 /// Uses camera raycasting against the y=0 ground plane to determine which grid cell
 /// was clicked, bypassing the picking message pipeline to avoid conflicts with bevy_egui.
+#[allow(clippy::type_complexity)]
 pub fn handle_panel_clicks(
     mouse: Res<ButtonInput<MouseButton>>,
     windows: Query<&Window>,
-    cameras: Query<(&Camera, &GlobalTransform)>,
+    cameras: Query<(&Camera, &GlobalTransform), (With<PanOrbitCamera>, Without<ComparisonCamera>)>,
     mut nursery: ResMut<NurseryState>,
     egui_wants: Res<bevy_egui::input::EguiWantsInput>,
+    time: Res<Time<Real>>,
+    mut last_click: Local<Option<(usize, f32)>>,
 ) {
     if !mouse.just_pressed(MouseButton::Left) || nursery.mode != NurseryMode::Enabled {
         return;
@@ -581,21 +516,28 @@ pub fn handle_panel_clicks(
     }
     let hit_point = ray.origin + *ray.direction * t;
 
-    // Map hit point to grid cell
-    let spacing = nursery.grid_spacing;
-    let grid_size = nursery.grid_size;
-    let grid_offset = (grid_size as f32 - 1.0) * spacing / 2.0;
-    let half_panel = spacing * 0.45; // panel is spacing * 0.9 wide
-
-    for i in 0..nursery.population_size() {
-        let row = i / grid_size;
-        let col = i % grid_size;
-        let cx = col as f32 * spacing - grid_offset;
-        let cz = row as f32 * spacing - grid_offset;
-
-        if (hit_point.x - cx).abs() <= half_panel && (hit_point.z - cz).abs() <= half_panel {
-            nursery.toggle_selection(i);
-            return;
-        }
+    // Map hit point to a visible grid cell
+    let half_panel = nursery.grid_spacing * 0.45; // panel is spacing * 0.9 wide
+    let hit = (0..nursery.population_size()).find(|&i| {
+        let center = nursery.cell_position(i);
+        nursery.is_cell_visible(i)
+            && (hit_point.x - center.x).abs() <= half_panel
+            && (hit_point.z - center.z).abs() <= half_panel
+    });
+    let Some(i) = hit else {
+        return;
+    };
+
+    // Both clicks of a double-click toggle, leaving the selection as it was
+    nursery.toggle_selection(i);
+    let now = time.elapsed_secs();
+    if let Some((last, at)) = *last_click
+        && last == i
+        && now - at <= DOUBLE_CLICK_SECONDS
+    {
+        nursery.solo = Some(i);
+        *last_click = None;
+    } else {
+        *last_click = Some((i, now));
     }
 }
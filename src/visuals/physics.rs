@@ -0,0 +1,250 @@
+//! Rigid-body preview of the editor plant. Only built with the `physics`
+//! feature.
+//!
+//! Every branch segment becomes a dynamic capsule body, joined to the segment
+//! it grows from by a spherical joint whose angular motors pull it back to its
+//! grown orientation. Segments at the plant's base are fixed. The bodies
+//! settle under gravity, showing where a design sags or collapses.
+
+use crate::core::config::{LSystemConfig, LSystemEngine, SubSystemEngines};
+use crate::visuals::intersections::{Capsule, skeleton_capsules};
+use crate::visuals::turtle::{LSystemMeshTag, LSystemPropTag, editor_skeleton};
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// Most segments simulated; bigger plants are refused.
+pub const MAX_PHYSICS_SEGMENTS: usize = 2_000;
+
+/// Endpoint positions are matched on a grid of this size.
+const JOINT_TOLERANCE: f32 = 1e-3;
+
+/// Color of the simulated segments.
+const BODY_COLOR: Color = Color::srgb(0.55, 0.4, 0.25);
+
+/// Collision group of plant segments; they only collide with the ground.
+const SEGMENT_GROUP: Group = Group::GROUP_1;
+const GROUND_GROUP: Group = Group::GROUP_2;
+
+/// Registers the physics engine and the preview systems.
+pub struct PhysicsPreviewPlugin;
+
+impl Plugin for PhysicsPreviewPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<RapierPhysicsPlugin<NoUserData>>() {
+            app.add_plugins(RapierPhysicsPlugin::<NoUserData>::default());
+        }
+        app.init_resource::<PhysicsPreview>()
+            .add_systems(Update, sync_physics_preview);
+    }
+}
+
+/// Physics preview settings and state.
+#[derive(Resource)]
+pub struct PhysicsPreview {
+    /// Whether the preview is running; the editor plant is hidden meanwhile.
+    pub enabled: bool,
+    /// Angular motor stiffness holding each joint at its grown angle.
+    pub stiffness: f32,
+    /// Angular motor damping of each joint.
+    pub damping: f32,
+    /// Gravity multiplier, so plants hundreds of units tall settle in seconds.
+    pub gravity_scale: f32,
+    /// Set by the UI to rebuild the bodies from the current plant.
+    pub restart_requested: bool,
+    /// Segments simulated, or why the preview could not start.
+    pub status: Option<Result<usize, String>>,
+}
+
+impl Default for PhysicsPreview {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            stiffness: 1.0e4,
+            damping: 1.0e3,
+            gravity_scale: 20.0,
+            restart_requested: false,
+            status: None,
+        }
+    }
+}
+
+/// A simulated segment.
+#[derive(Component)]
+pub struct PhysicsSegment;
+
+/// The ground the plant stands on during the preview.
+#[derive(Component)]
+pub struct PhysicsGround;
+
+/// Joint parents of the capsules: each capsule hangs from the first capsule
+/// ending where it starts. `None` marks a root.
+pub fn joint_parents(capsules: &[Capsule]) -> Vec<Option<usize>> {
+    let key = |p: Vec3| (p / JOINT_TOLERANCE).round().as_ivec3();
+    let mut ends: HashMap<IVec3, usize> = HashMap::default();
+    for (index, capsule) in capsules.iter().enumerate() {
+        ends.entry(key(capsule.end)).or_insert(index);
+    }
+    capsules
+        .iter()
+        .enumerate()
+        .map(|(index, capsule)| {
+            ends.get(&key(capsule.start))
+                .copied()
+                .filter(|parent| *parent != index)
+        })
+        .collect()
+}
+
+/// Starts, restarts, and stops the preview.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn sync_physics_preview(
+    mut commands: Commands,
+    mut preview: ResMut<PhysicsPreview>,
+    engine: Res<LSystemEngine>,
+    subsystems: Res<SubSystemEngines>,
+    config: Res<LSystemConfig>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    spawned: Query<Entity, Or<(With<PhysicsSegment>, With<PhysicsGround>)>>,
+    mut editor_parts: Query<&mut Visibility, Or<(With<LSystemMeshTag>, With<LSystemPropTag>)>>,
+    mut running: Local<bool>,
+) {
+    let start = preview.enabled && (!*running || preview.restart_requested);
+    let stop = !preview.enabled && *running;
+    preview.restart_requested = false;
+    if !start && !stop {
+        return;
+    }
+
+    for entity in &spawned {
+        commands.entity(entity).despawn();
+    }
+    *running = preview.enabled;
+    let visibility = if preview.enabled {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    };
+    for mut part in &mut editor_parts {
+        part.set_if_neq(visibility);
+    }
+    if stop {
+        return;
+    }
+
+    let capsules = skeleton_capsules(&editor_skeleton(&engine, &subsystems, &config));
+    if capsules.len() > MAX_PHYSICS_SEGMENTS {
+        preview.status = Some(Err(format!(
+            "{} segments; the preview handles at most {}",
+            capsules.len(),
+            MAX_PHYSICS_SEGMENTS
+        )));
+        // Turned back off, which restores the editor plant next frame
+        preview.enabled = false;
+        return;
+    }
+
+    let material = materials.add(StandardMaterial {
+        base_color: BODY_COLOR,
+        perceptual_roughness: 0.9,
+        ..default()
+    });
+    let lowest = capsules
+        .iter()
+        .map(|capsule| capsule.start.y.min(capsule.end.y))
+        .fold(f32::INFINITY, f32::min);
+    let extent = capsules
+        .iter()
+        .map(|capsule| capsule.start.length().max(capsule.end.length()))
+        .fold(1.0, f32::max);
+
+    // Ground just below the plant
+    commands.spawn((
+        PhysicsGround,
+        RigidBody::Fixed,
+        Collider::cuboid(extent * 4.0, extent * 0.01, extent * 4.0),
+        CollisionGroups::new(GROUND_GROUP, SEGMENT_GROUP),
+        Transform::from_xyz(0.0, lowest - extent * 0.01, 0.0),
+    ));
+
+    let parents = joint_parents(&capsules);
+    let mut bodies: Vec<Entity> = Vec::with_capacity(capsules.len());
+    for (capsule, parent) in capsules.iter().zip(&parents) {
+        let axis = capsule.end - capsule.start;
+        let half_length = axis.length() * 0.5;
+        let radius = capsule.start_radius.max(capsule.end_radius).max(1e-3);
+        let transform = Transform {
+            translation: (capsule.start + capsule.end) * 0.5,
+            rotation: Quat::from_rotation_arc(Vec3::Y, axis.normalize()),
+            ..default()
+        };
+        let mut body = commands.spawn((
+            PhysicsSegment,
+            Mesh3d(meshes.add(Capsule3d::new(radius, half_length * 2.0))),
+            MeshMaterial3d(material.clone()),
+            transform,
+            Collider::capsule_y(half_length, radius),
+            CollisionGroups::new(SEGMENT_GROUP, GROUND_GROUP),
+            GravityScale(preview.gravity_scale),
+        ));
+        match parent {
+            // Parents come before their children in strand order
+            Some(parent) if *parent < bodies.len() => {
+                let parent_capsule = &capsules[*parent];
+                let parent_half = parent_capsule.start.distance(parent_capsule.end) * 0.5;
+                let parent_rotation = Quat::from_rotation_arc(
+                    Vec3::Y,
+                    (parent_capsule.end - parent_capsule.start).normalize(),
+                );
+                // The child's rest orientation relative to its parent
+                let rest = parent_rotation.inverse() * transform.rotation;
+                let mut joint = GenericJointBuilder::new(JointAxesMask::LOCKED_SPHERICAL_AXES)
+                    .local_anchor1(Vec3::Y * parent_half)
+                    .local_anchor2(Vec3::NEG_Y * half_length)
+                    .local_basis1(rest);
+                for axis in [JointAxis::AngX, JointAxis::AngY, JointAxis::AngZ] {
+                    joint = joint.motor_position(axis, 0.0, preview.stiffness, preview.damping);
+                }
+                body.insert((
+                    RigidBody::Dynamic,
+                    ImpulseJoint::new(bodies[*parent], TypedJoint::GenericJoint(joint.build())),
+                ));
+            }
+            _ => {
+                body.insert(RigidBody::Fixed);
+            }
+        }
+        bodies.push(body.id());
+    }
+    info!("Physics preview: {} segments", capsules.len());
+    preview.status = Some(Ok(capsules.len()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capsule(start: Vec3, end: Vec3) -> Capsule {
+        Capsule {
+            start,
+            end,
+            start_radius: 0.1,
+            end_radius: 0.1,
+        }
+    }
+
+    #[test]
+    fn test_joint_parents_follow_branches() {
+        let up = Vec3::Y;
+        let capsules = [
+            capsule(Vec3::ZERO, up),
+            capsule(up, up * 2.0),
+            // A branch from the end of the first segment
+            capsule(up, up + Vec3::X),
+            // A separate stem
+            capsule(Vec3::X * 5.0, Vec3::X * 5.0 + up),
+        ];
+        assert_eq!(joint_parents(&capsules), vec![None, Some(0), Some(0), None]);
+    }
+}
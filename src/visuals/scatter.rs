@@ -0,0 +1,175 @@
+//! 3D rendering of the scattered forest.
+//!
+//! Each seed variation is derived in the background and meshed once; every
+//! scattered plant spawns the meshes of its variation, sharing the mesh and
+//! material handles so Bevy batches the instances. Layout changes respawn
+//! the instances without deriving again.
+
+use crate::core::config::{LSystemConfig, PropConfig};
+use crate::core::garden::Garden;
+use crate::core::genotype::PlantGenotype;
+use crate::core::includes::IncludeLibrary;
+use crate::core::scatter::Scatter;
+use crate::core::terrain::Terrain;
+use crate::visuals::assets::PropMeshAssets;
+use crate::visuals::genotype_render::{
+    CachedGenotypeMesh, PendingDerivation, PlantAssets, PlantDetail, build_plant_assets,
+    spawn_genotype_derivation, spawn_plant_assets,
+};
+use bevy::prelude::*;
+use bevy_symbios::materials::ProceduralTextures;
+
+/// Root of a scattered plant.
+#[derive(Component)]
+pub struct ScatterRoot;
+
+/// Branch mesh or prop of a scattered plant.
+#[derive(Component, Clone)]
+pub struct ScatterPart;
+
+/// Meshed variations and spawned instances of the forest.
+#[derive(Resource, Default)]
+pub struct ScatterRender {
+    /// Content hash of the scattered genotype and the variation count the
+    /// variations were derived for.
+    source: Option<(u64, usize)>,
+    /// Derivations in flight, one per variation.
+    pending: Vec<(PlantGenotype, PendingDerivation)>,
+    /// Meshed variations; `None` for variations that failed to derive.
+    pub variations: Vec<Option<PlantAssets>>,
+    /// Set when the instances must be respawned.
+    layout_dirty: bool,
+}
+
+/// Starts deriving the variations when the scattered plant or the number of
+/// variations changes, and flags a relayout on any other change, including
+/// terrain changes.
+pub fn sync_scatter(
+    scatter: Res<Scatter>,
+    terrain: Res<Terrain>,
+    includes: Res<IncludeLibrary>,
+    mut render: ResMut<ScatterRender>,
+) {
+    if terrain.is_changed() {
+        render.layout_dirty = true;
+    }
+    if !scatter.is_changed() {
+        return;
+    }
+    render.layout_dirty = true;
+
+    let source = scatter
+        .genotype
+        .as_ref()
+        .map(|genotype| (genotype.content_hash(), scatter.settings.variations));
+    if source == render.source {
+        return;
+    }
+    render.source = source;
+    render.variations.clear();
+    render.pending = scatter
+        .variation_genotypes()
+        .into_iter()
+        .map(|genotype| {
+            let slot = spawn_genotype_derivation(&genotype, &includes);
+            (genotype, slot)
+        })
+        .collect();
+}
+
+/// Meshes the variations once all of them are derived.
+#[allow(clippy::too_many_arguments)]
+pub fn poll_scatter_derivations(
+    mut render: ResMut<ScatterRender>,
+    config: Res<LSystemConfig>,
+    prop_config: Res<PropConfig>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    proc_textures: Res<ProceduralTextures>,
+    prop_assets: Res<PropMeshAssets>,
+) {
+    if render.pending.is_empty()
+        || !render
+            .pending
+            .iter()
+            .all(|(_, slot)| slot.lock().is_ok_and(|guard| guard.is_some()))
+    {
+        return;
+    }
+
+    let pending = std::mem::take(&mut render.pending);
+    render.variations = pending
+        .into_iter()
+        .map(|(genotype, slot)| {
+            let derived = slot
+                .lock()
+                .ok()
+                .and_then(|mut guard| guard.take())
+                .flatten();
+            let cached = CachedGenotypeMesh::new(&genotype, derived, 0.0, None);
+            build_plant_assets(
+                &cached,
+                PlantDetail::full(config.mesh_resolution),
+                &prop_config,
+                &mut meshes,
+                &mut materials,
+                &proc_textures,
+                &prop_assets,
+            )
+        })
+        .collect();
+    if render.variations.iter().all(Option::is_none) {
+        warn!("Scattered plant failed to derive");
+    }
+    render.layout_dirty = true;
+}
+
+/// Respawns the instances on the terrain after a layout change or new
+/// variations, and shows them only in garden mode.
+pub fn layout_scatter(
+    mut commands: Commands,
+    scatter: Res<Scatter>,
+    garden: Res<Garden>,
+    terrain: Res<Terrain>,
+    mut render: ResMut<ScatterRender>,
+    mut roots: Query<(Entity, &mut Visibility), With<ScatterRoot>>,
+) {
+    let visibility = if garden.enabled {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+
+    if !render.layout_dirty || !render.pending.is_empty() {
+        if garden.is_changed() {
+            for (_, mut root_visibility) in &mut roots {
+                root_visibility.set_if_neq(visibility);
+            }
+        }
+        return;
+    }
+    render.layout_dirty = false;
+
+    for (entity, _) in &roots {
+        commands.entity(entity).despawn();
+    }
+    if scatter.genotype.is_none() {
+        return;
+    }
+
+    for instance in scatter.settings.layout() {
+        let Some(Some(assets)) = render.variations.get(instance.variation) else {
+            continue;
+        };
+        let root = commands
+            .spawn((ScatterRoot, terrain.place(instance.transform()), visibility))
+            .id();
+        spawn_plant_assets(
+            &mut commands,
+            assets,
+            Vec3::ZERO,
+            (ScatterPart, ChildOf(root)),
+            (ScatterPart, ChildOf(root)),
+        );
+    }
+}
@@ -1,9 +1,26 @@
 use std::f32::consts::TAU;
 
+use bevy::asset::RenderAssetUsages;
+use bevy::camera::visibility::RenderLayers;
+use bevy::mesh::{Indices, PrimitiveTopology};
 use bevy::post_process::bloom::Bloom;
 use bevy::prelude::*;
+use bevy_egui::PrimaryEguiContext;
 use bevy_panorbit_camera::PanOrbitCamera;
 
+use crate::core::garden::Garden;
+use crate::core::terrain::Terrain;
+#[cfg(feature = "nursery")]
+use crate::visuals::comparison::COMPARISON_LAYER;
+use crate::visuals::thumbnail::THUMBNAIL_LAYER;
+
+/// Render layers lit by the sun: the editor plant, the pinned comparison plant,
+/// and the plant being captured as a thumbnail.
+#[cfg(feature = "nursery")]
+const LIT_LAYERS: &[usize] = &[0, COMPARISON_LAYER, THUMBNAIL_LAYER];
+#[cfg(not(feature = "nursery"))]
+const LIT_LAYERS: &[usize] = &[0, THUMBNAIL_LAYER];
+
 pub fn setup_scene(mut commands: Commands) {
     // Directional Light (Sunlight)
     commands.spawn((
@@ -20,6 +37,7 @@ pub fn setup_scene(mut commands: Commands) {
                 .mul_quat(Quat::from_rotation_y(-std::f32::consts::PI / 6.)),
             ..default()
         },
+        RenderLayers::from_layers(LIT_LAYERS),
     ));
 
     // Camera with Bloom
@@ -36,4 +54,106 @@ pub fn setup_scene(mut commands: Commands) {
         Camera3d::default(),
         Bloom::NATURAL, // Enable Bloom
     ));
+
+    // Dedicated UI camera, so the egui overlay keeps covering the whole window
+    // when the 3D cameras are split into viewports by the comparison view
+    commands.spawn((
+        Camera2d,
+        Camera {
+            order: 10,
+            clear_color: ClearColorConfig::None,
+            ..default()
+        },
+        RenderLayers::none(),
+        PrimaryEguiContext,
+    ));
+}
+
+/// The terrain mesh entity.
+#[derive(Component)]
+pub struct TerrainMesh;
+
+/// Color of the terrain material.
+const TERRAIN_COLOR: Color = Color::srgb(0.32, 0.36, 0.22);
+
+/// Meshes the terrain's heightmap, one vertex per sample.
+pub fn terrain_mesh(terrain: &Terrain) -> Mesh {
+    let heightmap = &terrain.heightmap;
+    let (width, depth) = (heightmap.width, heightmap.depth);
+    let mut positions = Vec::with_capacity(width * depth);
+    let mut normals = Vec::with_capacity(width * depth);
+    let mut uvs = Vec::with_capacity(width * depth);
+    for row in 0..depth {
+        for column in 0..width {
+            let u = column as f32 / (width - 1) as f32;
+            let v = row as f32 / (depth - 1) as f32;
+            let x = (u - 0.5) * terrain.size;
+            let z = (v - 0.5) * terrain.size;
+            positions.push([x, terrain.height_at(x, z), z]);
+            normals.push(terrain.normal_at(x, z).to_array());
+            uvs.push([u, v]);
+        }
+    }
+
+    let mut indices = Vec::with_capacity((width - 1) * (depth - 1) * 6);
+    for row in 0..depth - 1 {
+        for column in 0..width - 1 {
+            let corner = (row * width + column) as u32;
+            let below = corner + width as u32;
+            // Counter-clockwise seen from above
+            indices.extend([corner, below, corner + 1, corner + 1, below, below + 1]);
+        }
+    }
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// Rebuilds the terrain mesh when the terrain changes. The terrain is shown
+/// in garden mode while it is enabled.
+pub fn sync_terrain(
+    mut commands: Commands,
+    terrain: Res<Terrain>,
+    garden: Res<Garden>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut existing: Query<(Entity, &mut Visibility), With<TerrainMesh>>,
+) {
+    let visibility = if terrain.enabled && garden.enabled {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    if !terrain.is_changed() {
+        if garden.is_changed() {
+            for (_, mut terrain_visibility) in &mut existing {
+                terrain_visibility.set_if_neq(visibility);
+            }
+        }
+        return;
+    }
+
+    for (entity, _) in &existing {
+        commands.entity(entity).despawn();
+    }
+    if !terrain.enabled {
+        return;
+    }
+    commands.spawn((
+        Mesh3d(meshes.add(terrain_mesh(&terrain))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: TERRAIN_COLOR,
+            perceptual_roughness: 0.95,
+            ..default()
+        })),
+        Transform::IDENTITY,
+        visibility,
+        TerrainMesh,
+    ));
 }
@@ -0,0 +1,144 @@
+//! Raw skeleton export as JSON.
+//!
+//! Writes the turtle output before meshing, so external tools and game engines
+//! can run their own tube, billboard, or bone generators on it. Each strand is
+//! a polyline of points with position, orientation, radius, color, and
+//! material; the strand's direction of growth is the local +Y axis of each
+//! point's rotation. Props are listed with their transform.
+
+use crate::core::config::{AxisConvention, ExportFilter};
+use bevy::prelude::*;
+use serde_json::{Value, json};
+use std::io::{self, Write};
+use symbios_turtle_3d::Skeleton;
+
+/// Version of the JSON layout, bumped on incompatible changes.
+pub const SKELETON_FORMAT_VERSION: u32 = 1;
+
+/// Builds the JSON document for a skeleton, keeping only the strands and
+/// props the filter includes. A strand is filtered by its first point's material.
+pub fn skeleton_json(
+    skeleton: &Skeleton,
+    filter: &ExportFilter,
+    axes: AxisConvention,
+    prop_scale: f32,
+    description: &str,
+) -> Value {
+    let position = |v: Vec3| axes.convert(v).to_array();
+    let rotation = |q: Quat| axes.convert_rotation(q).to_array();
+
+    let strands: Vec<Value> = skeleton
+        .strands
+        .iter()
+        .filter(|strand| {
+            strand
+                .first()
+                .is_some_and(|point| filter.includes_branch(point.material_id))
+        })
+        .map(|strand| {
+            let points: Vec<Value> = strand
+                .iter()
+                .map(|point| {
+                    json!({
+                        "position": position(point.position),
+                        "rotation": rotation(point.rotation),
+                        "radius": point.radius,
+                        "color": point.color.to_array(),
+                        "material": point.material_id,
+                    })
+                })
+                .collect();
+            json!({ "points": points })
+        })
+        .collect();
+
+    let props: Vec<Value> = skeleton
+        .props
+        .iter()
+        .filter(|prop| filter.includes_prop(prop.material_id))
+        .map(|prop| {
+            json!({
+                "id": prop.prop_id,
+                "position": position(prop.position),
+                "rotation": rotation(prop.rotation),
+                "scale": (prop.scale * prop_scale).to_array(),
+                "color": prop.color.to_array(),
+                "material": prop.material_id,
+            })
+        })
+        .collect();
+
+    json!({
+        "format": "lsystem-skeleton",
+        "version": SKELETON_FORMAT_VERSION,
+        "generator": "L-System Explorer",
+        "description": description,
+        "axes": axes.name(),
+        "strands": strands,
+        "props": props,
+    })
+}
+
+/// Writes [`skeleton_json`] pretty-printed to `out`.
+pub fn write_skeleton_json(out: &mut dyn Write, document: &Value) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *out, document)?;
+    writeln!(out)
+}
+
+/// Whether the document holds any strand or prop.
+pub fn is_empty(document: &Value) -> bool {
+    ["strands", "props"]
+        .iter()
+        .all(|key| document[key].as_array().is_none_or(Vec::is_empty))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visuals::turtle::axiom_skeleton;
+
+    fn skeleton() -> Skeleton {
+        axiom_skeleton("F(2) [ + F(1) ] F(1)", 30.0, 0.5, None, 0.0)
+    }
+
+    #[test]
+    fn test_skeleton_json_lists_strands() {
+        let skeleton = skeleton();
+        let document = skeleton_json(
+            &skeleton,
+            &ExportFilter::default(),
+            AxisConvention::default(),
+            1.0,
+            "test",
+        );
+        assert_eq!(document["version"], SKELETON_FORMAT_VERSION);
+        let strands = document["strands"].as_array().unwrap();
+        assert_eq!(strands.len(), skeleton.strands.len());
+        let first = &strands[0]["points"][0];
+        assert_eq!(first["rotation"].as_array().unwrap().len(), 4);
+        assert!(first["radius"].as_f64().unwrap() > 0.0);
+        assert!(!is_empty(&document));
+
+        let filter = ExportFilter {
+            include_branches: false,
+            ..default()
+        };
+        let document = skeleton_json(&skeleton, &filter, AxisConvention::default(), 1.0, "");
+        assert!(is_empty(&document));
+    }
+
+    #[test]
+    fn test_write_skeleton_json_round_trips() {
+        let document = skeleton_json(
+            &skeleton(),
+            &ExportFilter::default(),
+            AxisConvention::ZUpRightHanded,
+            1.0,
+            "round trip",
+        );
+        let mut out = Vec::new();
+        write_skeleton_json(&mut out, &document).unwrap();
+        let parsed: Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(parsed, document);
+    }
+}
@@ -0,0 +1,306 @@
+//! Joint rig and skinning weights for animated exports.
+//!
+//! Every strand point becomes a joint, chained along its strand, and each
+//! strand's first joint hangs off the parent joint it branches from (see
+//! [`strand_attachments`]). Rotating a joint bends everything above it, which
+//! is what wind and interaction rigs in Blender, Unity, and Godot expect.
+//! Strands at or beyond the rig's order limit get no joints of their own and
+//! follow the joint they grow from rigidly, keeping joint counts manageable.
+//!
+//! Tube vertices are bound to the two joints of the segment they lie on,
+//! blended by their position along it. Strands are meshed one at a time so
+//! every vertex is known to belong to its strand.
+
+use crate::core::config::AxisConvention;
+use crate::visuals::branch_order::{branch_orders, strand_attachments};
+use crate::visuals::mesher::SkeletonMesher;
+use bevy::mesh::VertexAttributeValues;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use symbios_turtle_3d::{Skeleton, SkeletonPoint};
+
+/// Joint indices are written as 16-bit `JOINTS_0`.
+pub const MAX_JOINTS: usize = u16::MAX as usize;
+
+/// Points closer than this along a strand share one joint.
+const SAME_POSITION_EPSILON: f32 = 1e-5;
+
+/// One joint in the rest pose, in world space.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RigJoint {
+    pub parent: Option<usize>,
+    pub position: Vec3,
+    pub rotation: Quat,
+}
+
+/// Joint hierarchy of a plant; parents always come before their children.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlantRig {
+    pub joints: Vec<RigJoint>,
+}
+
+impl PlantRig {
+    /// Joints without a parent.
+    pub fn roots(&self) -> impl Iterator<Item = usize> + '_ {
+        self.joints
+            .iter()
+            .enumerate()
+            .filter(|(_, joint)| joint.parent.is_none())
+            .map(|(index, _)| index)
+    }
+
+    /// Joints whose parent is `joint`.
+    pub fn children(&self, joint: usize) -> impl Iterator<Item = usize> + '_ {
+        self.joints
+            .iter()
+            .enumerate()
+            .filter(move |(_, other)| other.parent == Some(joint))
+            .map(|(index, _)| index)
+    }
+
+    /// Translation and rotation of a joint relative to its parent.
+    pub fn local_transform(&self, joint: usize) -> (Vec3, Quat) {
+        let this = &self.joints[joint];
+        match this.parent.map(|parent| &self.joints[parent]) {
+            Some(parent) => {
+                let inverse = parent.rotation.inverse();
+                (
+                    inverse * (this.position - parent.position),
+                    (inverse * this.rotation).normalize(),
+                )
+            }
+            None => (this.position, this.rotation),
+        }
+    }
+
+    /// Inverse of each joint's rest transform, as glTF inverse bind matrices.
+    pub fn inverse_bind_matrices(&self) -> Vec<Mat4> {
+        self.joints
+            .iter()
+            .map(|joint| Mat4::from_rotation_translation(joint.rotation, joint.position).inverse())
+            .collect()
+    }
+
+    /// Maps the rest pose into another axis convention, like the meshes.
+    pub fn convert_axes(&mut self, axes: AxisConvention) {
+        for joint in &mut self.joints {
+            joint.position = axes.convert(joint.position);
+            joint.rotation = axes.convert_rotation(joint.rotation);
+        }
+    }
+}
+
+/// Meshes the skeleton's strands with joint indices and weights, and builds
+/// the rig they are bound to. Strands of order `max_order` and above follow
+/// their parent joint rigidly; `None` gives every strand its own joints.
+///
+/// The buckets are neither welded nor reshaded, since both would have to
+/// merge vertices bound to different joints.
+pub fn build_skinned(
+    skeleton: &Skeleton,
+    mesher: &SkeletonMesher,
+    max_order: Option<u32>,
+) -> Result<(PlantRig, HashMap<u8, Mesh>), String> {
+    // Joints follow the respaced rings, so long segments bend along their length
+    let skeleton = &*mesher.respace(skeleton);
+    let mesher = &mesher.clone().with_ring_spacing(None);
+    let strands: Vec<Vec<Vec3>> = skeleton
+        .strands
+        .iter()
+        .map(|strand| strand.iter().map(|point| point.position).collect())
+        .collect();
+    let orders = branch_orders(&strands);
+    let attachments = strand_attachments(&strands);
+
+    let mut rig = PlantRig::default();
+    let mut point_joints: Vec<Vec<usize>> = Vec::with_capacity(strands.len());
+    for (s, strand) in skeleton.strands.iter().enumerate() {
+        let base = attachments[s].map(|(parent, k)| point_joints[parent][k]);
+        if let Some(base) = base
+            && max_order.is_some_and(|max_order| orders[s] >= max_order)
+        {
+            point_joints.push(vec![base; strand.len()]);
+            continue;
+        }
+        let mut joints = Vec::with_capacity(strand.len());
+        let mut previous = base;
+        for (k, point) in strand.iter().enumerate() {
+            // Width and material changes repeat a point; they bend as one joint
+            if k > 0
+                && let Some(previous) = previous
+                && rig.joints[previous]
+                    .position
+                    .distance_squared(point.position)
+                    <= SAME_POSITION_EPSILON * SAME_POSITION_EPSILON
+            {
+                joints.push(previous);
+                continue;
+            }
+            rig.joints.push(RigJoint {
+                parent: previous,
+                position: point.position,
+                rotation: point.rotation,
+            });
+            previous = Some(rig.joints.len() - 1);
+            joints.push(rig.joints.len() - 1);
+        }
+        point_joints.push(joints);
+    }
+    if rig.joints.len() > MAX_JOINTS {
+        return Err(format!(
+            "{} joints exceed the limit of {}; lower the branch order levels",
+            rig.joints.len(),
+            MAX_JOINTS
+        ));
+    }
+
+    let mut part = skeleton.clone();
+    part.strands.clear();
+    part.props.clear();
+    let mut buckets: HashMap<u8, Mesh> = HashMap::new();
+    for (strand, joints) in skeleton.strands.iter().zip(&point_joints) {
+        part.strands.clear();
+        part.strands.push(strand.clone());
+        for (material_id, mut mesh) in mesher.build_unfinished(&part) {
+            bind_to_strand(&mut mesh, strand, joints);
+            match buckets.get_mut(&material_id) {
+                Some(existing) => {
+                    if let Err(e) = existing.merge(&mesh) {
+                        warn!("Failed to merge skinned strand: {}", e);
+                    }
+                }
+                None => {
+                    buckets.insert(material_id, mesh);
+                }
+            }
+        }
+    }
+    Ok((rig, buckets))
+}
+
+/// Adds joint indices and weights binding each vertex to the nearest segment
+/// of its strand.
+fn bind_to_strand(mesh: &mut Mesh, strand: &[SkeletonPoint], joints: &[usize]) {
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return;
+    };
+    if strand.is_empty() {
+        return;
+    }
+    let mut indices = Vec::with_capacity(positions.len());
+    let mut weights = Vec::with_capacity(positions.len());
+    for &position in positions {
+        let (k, t) = nearest_segment(strand, Vec3::from_array(position));
+        let (a, b) = (joints[k], joints[(k + 1).min(joints.len() - 1)]);
+        if a == b || t <= 0.0 {
+            indices.push([a as u16, 0, 0, 0]);
+            weights.push([1.0, 0.0, 0.0, 0.0]);
+        } else if t >= 1.0 {
+            indices.push([b as u16, 0, 0, 0]);
+            weights.push([1.0, 0.0, 0.0, 0.0]);
+        } else {
+            indices.push([a as u16, b as u16, 0, 0]);
+            weights.push([1.0 - t, t, 0.0, 0.0]);
+        }
+    }
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_JOINT_INDEX,
+        VertexAttributeValues::Uint16x4(indices),
+    );
+    mesh.insert_attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT, weights);
+}
+
+/// The segment `(k, k + 1)` closest to `position`, with the position's
+/// parameter along it in `0.0..=1.0`.
+fn nearest_segment(strand: &[SkeletonPoint], position: Vec3) -> (usize, f32) {
+    let mut best = (0, 0.0, f32::MAX);
+    for k in 0..strand.len().saturating_sub(1) {
+        let (a, b) = (strand[k].position, strand[k + 1].position);
+        let segment = b - a;
+        let length_squared = segment.length_squared();
+        let t = if length_squared > 0.0 {
+            ((position - a).dot(segment) / length_squared).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let distance = position.distance_squared(a + segment * t);
+        if distance < best.2 {
+            best = (k, t, distance);
+        }
+    }
+    (best.0, best.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visuals::turtle::axiom_skeleton;
+
+    fn skeleton(axiom: &str) -> Skeleton {
+        axiom_skeleton(axiom, 45.0, 0.2, None, 0.0)
+    }
+
+    #[test]
+    fn test_rig_follows_branches() {
+        let skeleton = skeleton("F F [ + F ] F");
+        let (rig, buckets) = build_skinned(&skeleton, &SkeletonMesher::new(6), None).unwrap();
+        assert_eq!(rig.roots().count(), 1, "one trunk root");
+        for (index, joint) in rig.joints.iter().enumerate() {
+            assert!(joint.parent.is_none_or(|parent| parent < index));
+        }
+        // The branch and the trunk continuation both hang off the branch point
+        assert!(
+            (0..rig.joints.len()).any(|joint| rig.children(joint).count() == 2),
+            "the branch point has two child joints"
+        );
+
+        let mesh = &buckets[&0];
+        let Some(VertexAttributeValues::Float32x4(weights)) =
+            mesh.attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT)
+        else {
+            panic!("skinned meshes have joint weights");
+        };
+        assert_eq!(weights.len(), mesh.count_vertices());
+        assert!(
+            weights
+                .iter()
+                .all(|w| (w.iter().sum::<f32>() - 1.0).abs() < 1e-5)
+        );
+    }
+
+    #[test]
+    fn test_order_limit_binds_branches_rigidly() {
+        let skeleton = skeleton("F F [ + F F ] F");
+        let (full, _) = build_skinned(&skeleton, &SkeletonMesher::new(6), None).unwrap();
+        let (trunk_only, _) = build_skinned(&skeleton, &SkeletonMesher::new(6), Some(1)).unwrap();
+        assert!(trunk_only.joints.len() < full.joints.len());
+    }
+
+    #[test]
+    fn test_inverse_bind_matrices_undo_rest_pose() {
+        let skeleton = skeleton("F + F");
+        let (mut rig, _) = build_skinned(&skeleton, &SkeletonMesher::new(6), None).unwrap();
+        rig.convert_axes(AxisConvention::ZUpRightHanded);
+        for (joint, inverse) in rig.joints.iter().zip(rig.inverse_bind_matrices()) {
+            let rest = Mat4::from_rotation_translation(joint.rotation, joint.position);
+            assert!((rest * inverse).abs_diff_eq(Mat4::IDENTITY, 1e-4));
+        }
+        // Local transforms compose back into the rest pose
+        for index in 0..rig.joints.len() {
+            let (translation, rotation) = rig.local_transform(index);
+            if let Some(parent) = rig.joints[index].parent {
+                let parent = &rig.joints[parent];
+                let position = parent.position + parent.rotation * translation;
+                assert!(position.abs_diff_eq(rig.joints[index].position, 1e-4));
+                assert!(
+                    (parent.rotation * rotation)
+                        .dot(rig.joints[index].rotation)
+                        .abs()
+                        > 0.9999
+                );
+            }
+        }
+    }
+}
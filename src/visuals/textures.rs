@@ -0,0 +1,326 @@
+//! User texture images for the material palette.
+//!
+//! PNG/JPG files imported by the user are decoded into [`TextureLibrary`] and
+//! can be assigned per material slot as base color, normal, or roughness map.
+//! The maps are layered on top of the palette materials that
+//! `sync_material_properties` builds from [`MaterialSettingsMap`]: a base color
+//! map replaces the slot's procedural texture, and slots without maps keep it.
+
+use crate::core::config::{MaterialSettingsMap, TextureType};
+use crate::visuals::turtle::{BucketMaterialId, LSystemMeshTag};
+use bevy::asset::RenderAssetUsages;
+use bevy::image::{ImageAddressMode, ImageSampler, ImageSamplerDescriptor};
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy_symbios::materials::{MaterialPalette, ProceduralTextures};
+
+/// File extensions accepted for import.
+pub const TEXTURE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg"];
+
+/// The map a texture is used as on a material slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextureMapKind {
+    BaseColor,
+    Normal,
+    Roughness,
+}
+
+impl TextureMapKind {
+    pub const ALL: &'static [TextureMapKind] = &[
+        TextureMapKind::BaseColor,
+        TextureMapKind::Normal,
+        TextureMapKind::Roughness,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TextureMapKind::BaseColor => "Base color",
+            TextureMapKind::Normal => "Normal",
+            TextureMapKind::Roughness => "Roughness",
+        }
+    }
+}
+
+/// Imported texture maps assigned to one material slot, by texture name.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SlotTextureMaps {
+    pub base_color: Option<String>,
+    pub normal: Option<String>,
+    pub roughness: Option<String>,
+}
+
+impl SlotTextureMaps {
+    pub fn get_mut(&mut self, kind: TextureMapKind) -> &mut Option<String> {
+        match kind {
+            TextureMapKind::BaseColor => &mut self.base_color,
+            TextureMapKind::Normal => &mut self.normal,
+            TextureMapKind::Roughness => &mut self.roughness,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.base_color.is_none() && self.normal.is_none() && self.roughness.is_none()
+    }
+}
+
+/// A decoded user image, uploaded once as color data and once as linear data.
+pub struct ImportedTexture {
+    /// File name the texture was imported from; unique within the library.
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    /// sRGB view for base color maps.
+    pub srgb: Handle<Image>,
+    /// Linear view for normal and roughness maps, which hold data rather than color.
+    pub linear: Handle<Image>,
+}
+
+/// Imported textures and their assignment to material slots.
+#[derive(Resource, Default)]
+pub struct TextureLibrary {
+    pub textures: Vec<ImportedTexture>,
+    pub slots: HashMap<u8, SlotTextureMaps>,
+    /// Files queued by the UI as `(name, bytes)`, decoded by [`import_textures`].
+    pub pending: Vec<(String, Vec<u8>)>,
+    /// File path typed into the import field (native builds).
+    pub path_input: String,
+    /// Name of the last imported file, or why the import failed.
+    pub status: Option<Result<String, String>>,
+}
+
+impl TextureLibrary {
+    pub fn get(&self, name: &str) -> Option<&ImportedTexture> {
+        self.textures.iter().find(|texture| texture.name == name)
+    }
+
+    /// Decodes `bytes` and adds the texture, replacing one of the same name.
+    pub fn import(
+        &mut self,
+        name: String,
+        bytes: &[u8],
+        images: &mut Assets<Image>,
+    ) -> Result<(), String> {
+        let decoded = image::load_from_memory(bytes)
+            .map_err(|e| format!("Failed to decode {}: {}", name, e))?
+            .to_rgba8();
+        let (width, height) = decoded.dimensions();
+        let data = decoded.into_raw();
+        let texture = ImportedTexture {
+            srgb: images.add(texture_image(
+                width,
+                height,
+                data.clone(),
+                TextureFormat::Rgba8UnormSrgb,
+            )),
+            linear: images.add(texture_image(
+                width,
+                height,
+                data,
+                TextureFormat::Rgba8Unorm,
+            )),
+            name,
+            width,
+            height,
+        };
+        match self.textures.iter_mut().find(|t| t.name == texture.name) {
+            Some(existing) => *existing = texture,
+            None => self.textures.push(texture),
+        }
+        Ok(())
+    }
+
+    /// Removes a texture and unassigns it from every slot.
+    pub fn remove(&mut self, name: &str) {
+        self.textures.retain(|texture| texture.name != name);
+        for maps in self.slots.values_mut() {
+            for &kind in TextureMapKind::ALL {
+                let map = maps.get_mut(kind);
+                if map.as_deref() == Some(name) {
+                    *map = None;
+                }
+            }
+        }
+        self.slots.retain(|_, maps| !maps.is_empty());
+    }
+
+    /// Whether `name` has an accepted image extension.
+    pub fn accepts(name: &str) -> bool {
+        name.rsplit_once('.').is_some_and(|(_, extension)| {
+            TEXTURE_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str())
+        })
+    }
+
+    fn handle(&self, name: Option<&String>, kind: TextureMapKind) -> Option<Handle<Image>> {
+        let texture = self.get(name?)?;
+        Some(match kind {
+            TextureMapKind::BaseColor => texture.srgb.clone(),
+            TextureMapKind::Normal | TextureMapKind::Roughness => texture.linear.clone(),
+        })
+    }
+}
+
+/// A repeating texture, so `uv_scale` tiles it along the branches.
+fn texture_image(width: u32, height: u32, data: Vec<u8>, format: TextureFormat) -> Image {
+    let mut image = Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        format,
+        RenderAssetUsages::default(),
+    );
+    image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+        address_mode_u: ImageAddressMode::Repeat,
+        address_mode_v: ImageAddressMode::Repeat,
+        ..ImageSamplerDescriptor::linear()
+    });
+    image
+}
+
+/// System that decodes the files queued by the UI.
+pub fn import_textures(mut library: ResMut<TextureLibrary>, mut images: ResMut<Assets<Image>>) {
+    if library.pending.is_empty() {
+        return;
+    }
+    for (name, bytes) in std::mem::take(&mut library.pending) {
+        let result = library
+            .import(name.clone(), &bytes, &mut images)
+            .map(|()| name);
+        match &result {
+            Ok(name) => info!("Imported texture {}", name),
+            Err(e) => warn!("{}", e),
+        }
+        library.status = Some(result);
+    }
+}
+
+/// System that applies the assigned maps to the palette materials.
+///
+/// Runs after `sync_material_properties`, so the maps survive palette updates.
+/// A roughness map is read from its green channel (glTF metallic-roughness
+/// layout); normal maps need mesh tangents, see [`generate_mesh_tangents`].
+pub fn apply_texture_maps(
+    library: Res<TextureLibrary>,
+    settings: Res<MaterialSettingsMap>,
+    palette: Res<MaterialPalette>,
+    proc_textures: Res<ProceduralTextures>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if !library.is_changed() && !settings.is_changed() && !palette.is_changed() {
+        return;
+    }
+    let no_maps = SlotTextureMaps::default();
+    for (material_id, handle) in &palette.materials {
+        let maps = library.slots.get(material_id).unwrap_or(&no_maps);
+        let procedural = match settings.settings.get(material_id).map(|s| s.texture) {
+            None | Some(TextureType::None) => None,
+            Some(other) => proc_textures.textures.get(&other).cloned(),
+        };
+        let base_color = library
+            .handle(maps.base_color.as_ref(), TextureMapKind::BaseColor)
+            .or(procedural);
+        let normal = library.handle(maps.normal.as_ref(), TextureMapKind::Normal);
+        let roughness = library.handle(maps.roughness.as_ref(), TextureMapKind::Roughness);
+
+        // Compare first so untouched materials are not re-uploaded
+        let Some(material) = materials.get(handle) else {
+            continue;
+        };
+        if material.base_color_texture == base_color
+            && material.normal_map_texture == normal
+            && material.metallic_roughness_texture == roughness
+        {
+            continue;
+        }
+        if let Some(material) = materials.get_mut(handle) {
+            material.base_color_texture = base_color;
+            material.normal_map_texture = normal;
+            material.metallic_roughness_texture = roughness;
+        }
+    }
+}
+
+/// System that adds tangents to editor meshes whose slot has a normal map.
+/// The mesher does not generate them, and without tangents the map is ignored.
+pub fn generate_mesh_tangents(
+    library: Res<TextureLibrary>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    query: Query<(&Mesh3d, &BucketMaterialId), With<LSystemMeshTag>>,
+) {
+    for (mesh, material_id) in &query {
+        let normal_mapped = library
+            .slots
+            .get(&material_id.0)
+            .is_some_and(|maps| maps.normal.is_some());
+        if !normal_mapped
+            || meshes
+                .get(&mesh.0)
+                .is_none_or(|mesh| mesh.contains_attribute(Mesh::ATTRIBUTE_TANGENT))
+        {
+            continue;
+        }
+        if let Some(mesh) = meshes.get_mut(&mesh.0)
+            && let Err(e) = mesh.generate_tangents()
+        {
+            warn!(
+                "Failed to generate tangents for material {}: {}",
+                material_id.0, e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png_bytes() -> Vec<u8> {
+        let mut png = Vec::new();
+        image::RgbaImage::from_pixel(4, 2, image::Rgba([10, 20, 30, 255]))
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        png
+    }
+
+    #[test]
+    fn test_import_and_remove_texture() {
+        let mut images = Assets::<Image>::default();
+        let mut library = TextureLibrary::default();
+        library
+            .import("bark.png".into(), &png_bytes(), &mut images)
+            .unwrap();
+        library
+            .import("bark.png".into(), &png_bytes(), &mut images)
+            .unwrap();
+        assert_eq!(library.textures.len(), 1, "same name replaces");
+        let texture = library.get("bark.png").unwrap();
+        assert_eq!((texture.width, texture.height), (4, 2));
+        assert_eq!(
+            images.get(&texture.srgb).unwrap().texture_descriptor.format,
+            TextureFormat::Rgba8UnormSrgb
+        );
+
+        library.slots.entry(0).or_default().normal = Some("bark.png".into());
+        library.remove("bark.png");
+        assert!(library.textures.is_empty());
+        assert!(library.slots.is_empty());
+
+        assert!(
+            library
+                .import("x.png".into(), b"not an image", &mut images)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_accepts_image_extensions() {
+        assert!(TextureLibrary::accepts("bark.PNG"));
+        assert!(TextureLibrary::accepts("leaf.jpeg"));
+        assert!(!TextureLibrary::accepts("tree.obj"));
+        assert!(!TextureLibrary::accepts("png"));
+    }
+}
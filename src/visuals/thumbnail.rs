@@ -0,0 +1,417 @@
+//! Offscreen thumbnail rendering.
+//!
+//! Plants are rendered one at a time by a dedicated camera into an image on
+//! [`THUMBNAIL_LAYER`], read back to the CPU, and box-filtered down to
+//! `THUMBNAIL_SIZE` pixels. Requests are keyed by a [`ThumbnailSlot`]; a slot
+//! is only rendered again when its genotype changes.
+
+use crate::core::config::{LSystemConfig, MaterialSettingsMap, PropConfig};
+use crate::core::genotype::PlantGenotype;
+use crate::core::herbarium::{Herbarium, THUMBNAIL_SIZE, downsample_center, encode_thumbnail};
+use crate::core::includes::IncludeLibrary;
+use crate::core::presets::PRESETS;
+use crate::ui::preset_browser::PresetBrowser;
+use crate::ui::variations::{VARIATION_COUNT, VariationPanel};
+use crate::visuals::assets::PropMeshAssets;
+use crate::visuals::camera::framing_for_bounds;
+use crate::visuals::genotype_render::{
+    CachedGenotypeMesh, PendingDerivation, PlantDetail, spawn_genotype_derivation,
+    spawn_genotype_plant,
+};
+use bevy::asset::RenderAssetUsages;
+use bevy::camera::RenderTarget;
+use bevy::camera::visibility::RenderLayers;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
+use bevy::render::view::screenshot::{Screenshot, ScreenshotCaptured};
+use bevy_symbios::materials::ProceduralTextures;
+use std::collections::VecDeque;
+use std::f32::consts::TAU;
+
+/// Render layer holding the plant being captured.
+pub const THUMBNAIL_LAYER: usize = 2;
+
+/// Edge length of the render target; twice the thumbnail size, so the box
+/// filter smooths edges.
+const RENDER_SIZE: u32 = THUMBNAIL_SIZE as u32 * 2;
+
+/// Frames rendered before capturing, so meshes and materials are on the GPU.
+const SETTLE_FRAMES: u32 = 2;
+
+/// Viewing angle of the thumbnail camera, matching the default editor view.
+const THUMBNAIL_YAW: f32 = TAU / 5.0;
+const THUMBNAIL_PITCH: f32 = TAU / 32.0;
+
+const THUMBNAIL_BACKGROUND: Color = Color::srgb(0.14, 0.14, 0.16);
+
+/// Registers the thumbnail camera and the systems that render queued
+/// thumbnails and store them in the herbarium.
+pub struct ThumbnailPlugin;
+
+impl Plugin for ThumbnailPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ThumbnailRenderer>()
+            .add_systems(Startup, setup_thumbnail_camera)
+            .add_systems(
+                Update,
+                (
+                    sync_herbarium_thumbnails,
+                    sync_preset_thumbnails,
+                    sync_variation_thumbnails,
+                    drive_thumbnail_renderer,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Where a thumbnail is shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ThumbnailSlot {
+    /// Herbarium entry, by id.
+    Herbarium(u64),
+    /// Nursery grid cell, by population index.
+    #[cfg(feature = "nursery")]
+    Nursery(usize),
+    /// Preset browser card, by index into [`PRESETS`].
+    Preset(usize),
+    /// Variations grid card, by index.
+    Variation(usize),
+}
+
+/// A rendered thumbnail: `THUMBNAIL_SIZE`² RGBA pixels.
+pub struct Thumbnail {
+    pub rgba: Vec<u8>,
+    /// Distinguishes successive renders of the same slot, for texture caching.
+    pub revision: u64,
+}
+
+enum ThumbnailStage {
+    Deriving(PendingDerivation),
+    /// Plant spawned; frames left before the capture.
+    Settling(u32),
+    /// Waiting for the GPU readback.
+    Capturing,
+}
+
+struct ThumbnailJob {
+    slot: ThumbnailSlot,
+    hash: u64,
+    genotype: PlantGenotype,
+    stage: ThumbnailStage,
+}
+
+/// Queue of thumbnail requests and the finished thumbnails.
+#[derive(Resource, Default)]
+pub struct ThumbnailRenderer {
+    /// Genotype hash each slot was last requested with.
+    requested: HashMap<ThumbnailSlot, u64>,
+    queue: VecDeque<(ThumbnailSlot, u64, PlantGenotype)>,
+    finished: HashMap<ThumbnailSlot, Thumbnail>,
+    job: Option<ThumbnailJob>,
+    target: Option<Handle<Image>>,
+    next_revision: u64,
+}
+
+impl ThumbnailRenderer {
+    /// Queues a render of `genotype` for `slot`, unless the slot already shows
+    /// (or is waiting for) this genotype. A pending render of an older genotype
+    /// for the same slot is dropped.
+    pub fn request(&mut self, slot: ThumbnailSlot, genotype: &PlantGenotype) {
+        let hash = genotype.content_hash();
+        if self.requested.get(&slot) == Some(&hash) {
+            return;
+        }
+        self.requested.insert(slot, hash);
+        self.queue.retain(|(queued, _, _)| *queued != slot);
+        self.queue.push_back((slot, hash, genotype.clone()));
+    }
+
+    /// True if the slot was requested, whether or not its render has finished.
+    pub fn is_requested(&self, slot: ThumbnailSlot) -> bool {
+        self.requested.contains_key(&slot)
+    }
+
+    pub fn get(&self, slot: ThumbnailSlot) -> Option<&Thumbnail> {
+        self.finished.get(&slot)
+    }
+
+    /// Removes a finished thumbnail, e.g. to persist it elsewhere. The slot
+    /// stays requested, so it is not rendered again for the same genotype.
+    pub fn take(&mut self, slot: ThumbnailSlot) -> Option<Thumbnail> {
+        self.finished.remove(&slot)
+    }
+
+    /// Stores a finished render, unless the slot was re-requested meanwhile.
+    fn finish(&mut self, slot: ThumbnailSlot, hash: u64, rgba: Vec<u8>) {
+        if self.requested.get(&slot) != Some(&hash) {
+            return;
+        }
+        self.next_revision += 1;
+        self.finished.insert(
+            slot,
+            Thumbnail {
+                rgba,
+                revision: self.next_revision,
+            },
+        );
+    }
+}
+
+/// Transform of a camera looking at the bounds from the thumbnail angle.
+pub fn thumbnail_camera_transform(min: Vec3, max: Vec3) -> Transform {
+    let (focus, distance) = framing_for_bounds(min, max);
+    let direction = Vec3::new(
+        THUMBNAIL_YAW.sin() * THUMBNAIL_PITCH.cos(),
+        THUMBNAIL_PITCH.sin(),
+        THUMBNAIL_YAW.cos() * THUMBNAIL_PITCH.cos(),
+    );
+    Transform::from_translation(focus + direction * distance).looking_at(focus, Vec3::Y)
+}
+
+/// Marks the offscreen thumbnail camera.
+#[derive(Component)]
+pub struct ThumbnailCamera;
+
+/// Marks meshes and props of the plant being captured.
+#[derive(Component, Clone)]
+pub struct ThumbnailPlantTag;
+
+/// Startup system creating the render target and the (inactive) camera.
+pub fn setup_thumbnail_camera(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut renderer: ResMut<ThumbnailRenderer>,
+) {
+    let size = Extent3d {
+        width: RENDER_SIZE,
+        height: RENDER_SIZE,
+        depth_or_array_layers: 1,
+    };
+    let mut image = Image::new_fill(
+        size,
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
+        | TextureUsages::COPY_SRC
+        | TextureUsages::COPY_DST
+        | TextureUsages::RENDER_ATTACHMENT;
+    let target = images.add(image);
+
+    commands.spawn((
+        Camera3d::default(),
+        Camera {
+            order: -1,
+            is_active: false,
+            clear_color: ClearColorConfig::Custom(THUMBNAIL_BACKGROUND),
+            ..default()
+        },
+        RenderTarget::Image(target.clone().into()),
+        RenderLayers::layer(THUMBNAIL_LAYER),
+        ThumbnailCamera,
+    ));
+    renderer.target = Some(target);
+}
+
+/// Requests thumbnails for herbarium entries without one, and stores finished
+/// renders in their entries.
+pub fn sync_herbarium_thumbnails(
+    mut herbarium: ResMut<Herbarium>,
+    mut renderer: ResMut<ThumbnailRenderer>,
+) {
+    let mut stored = false;
+    for i in 0..herbarium.entries.len() {
+        let entry = &herbarium.entries[i];
+        if entry.thumbnail.is_some() {
+            continue;
+        }
+        let slot = ThumbnailSlot::Herbarium(entry.id);
+        if !renderer.is_requested(slot) {
+            renderer.request(slot, &entry.genotype);
+        } else if let Some(thumbnail) = renderer.take(slot) {
+            herbarium.entries[i].thumbnail = Some(encode_thumbnail(&thumbnail.rgba));
+            stored = true;
+        }
+    }
+    if stored && let Err(e) = herbarium.save() {
+        warn!("Failed to save herbarium: {}", e);
+    }
+}
+
+/// Requests thumbnails of every preset the first time the preset browser
+/// opens; they are kept for the rest of the session.
+pub fn sync_preset_thumbnails(
+    browser: Option<Res<PresetBrowser>>,
+    mut renderer: ResMut<ThumbnailRenderer>,
+) {
+    if !browser.is_some_and(|browser| browser.open) {
+        return;
+    }
+    for (index, preset) in PRESETS.iter().enumerate() {
+        let slot = ThumbnailSlot::Preset(index);
+        if !renderer.is_requested(slot) {
+            renderer.request(slot, &PlantGenotype::from_preset(preset));
+        }
+    }
+}
+
+/// Requests thumbnails of the editor plant with each seed of the variations
+/// grid while it is open. The renderer skips seeds whose genotype is unchanged,
+/// so only edits or a new batch of seeds render again.
+pub fn sync_variation_thumbnails(
+    panel: Option<Res<VariationPanel>>,
+    config: Res<LSystemConfig>,
+    materials: Res<MaterialSettingsMap>,
+    prop_config: Res<PropConfig>,
+    mut renderer: ResMut<ThumbnailRenderer>,
+) {
+    let Some(panel) = panel.filter(|panel| panel.open) else {
+        return;
+    };
+    let mut genotype = PlantGenotype::from_editor(&config, &materials, &prop_config);
+    for index in 0..VARIATION_COUNT {
+        genotype.seed = panel.seed(index);
+        renderer.request(ThumbnailSlot::Variation(index), &genotype);
+    }
+}
+
+/// Renders queued thumbnails one at a time: derives the genotype in the
+/// background, spawns it on the thumbnail layer, frames the camera, lets it
+/// render for a few frames, and reads the image back.
+#[allow(clippy::too_many_arguments)]
+pub fn drive_thumbnail_renderer(
+    mut commands: Commands,
+    mut renderer: ResMut<ThumbnailRenderer>,
+    config: Res<LSystemConfig>,
+    includes: Res<IncludeLibrary>,
+    prop_config: Res<PropConfig>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    proc_textures: Res<ProceduralTextures>,
+    prop_assets: Res<PropMeshAssets>,
+    mut cameras: Query<(&mut Camera, &mut Transform), With<ThumbnailCamera>>,
+) {
+    let renderer = &mut *renderer;
+    let Some(job) = &mut renderer.job else {
+        let Some((slot, hash, genotype)) = renderer.queue.pop_front() else {
+            return;
+        };
+        let shared = spawn_genotype_derivation(&genotype, &includes);
+        renderer.job = Some(ThumbnailJob {
+            slot,
+            hash,
+            genotype,
+            stage: ThumbnailStage::Deriving(shared),
+        });
+        return;
+    };
+
+    match &mut job.stage {
+        ThumbnailStage::Deriving(shared) => {
+            let Some(derived) = shared.lock().ok().and_then(|mut guard| guard.take()) else {
+                return;
+            };
+            let cached = CachedGenotypeMesh::new(&job.genotype, derived, 0.0, None);
+            let tag = (ThumbnailPlantTag, RenderLayers::layer(THUMBNAIL_LAYER));
+            let bounds = spawn_genotype_plant(
+                &mut commands,
+                &cached,
+                Vec3::ZERO,
+                PlantDetail::full(config.mesh_resolution),
+                &prop_config,
+                &mut meshes,
+                &mut materials,
+                &proc_textures,
+                &prop_assets,
+                tag.clone(),
+                tag,
+            );
+            let (Some((min, max)), Ok((mut camera, mut transform))) =
+                (bounds, cameras.single_mut())
+            else {
+                // Failed derivations keep their placeholder
+                renderer.job = None;
+                return;
+            };
+            *transform = thumbnail_camera_transform(min, max);
+            camera.is_active = true;
+            job.stage = ThumbnailStage::Settling(SETTLE_FRAMES);
+        }
+        ThumbnailStage::Settling(0) => {
+            let Some(target) = renderer.target.clone() else {
+                return;
+            };
+            let (slot, hash) = (job.slot, job.hash);
+            job.stage = ThumbnailStage::Capturing;
+            commands.spawn(Screenshot::image(target)).observe(
+                move |captured: On<ScreenshotCaptured>,
+                      mut commands: Commands,
+                      mut renderer: ResMut<ThumbnailRenderer>,
+                      mut cameras: Query<&mut Camera, With<ThumbnailCamera>>,
+                      plants: Query<Entity, With<ThumbnailPlantTag>>| {
+                    match captured.image.clone().try_into_dynamic() {
+                        Ok(image) => {
+                            let image = image.to_rgba8();
+                            let (width, height) = image.dimensions();
+                            let pixels =
+                                downsample_center(image.as_raw(), width as usize, height as usize);
+                            renderer.finish(slot, hash, pixels);
+                        }
+                        Err(e) => warn!("Failed to read thumbnail: {:?}", e),
+                    }
+                    for entity in &plants {
+                        commands.entity(entity).despawn();
+                    }
+                    for mut camera in &mut cameras {
+                        camera.is_active = false;
+                    }
+                    renderer.job = None;
+                },
+            );
+        }
+        ThumbnailStage::Settling(frames) => *frames -= 1,
+        ThumbnailStage::Capturing => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_dedup_and_supersede() {
+        let mut renderer = ThumbnailRenderer::default();
+        let slot = ThumbnailSlot::Herbarium(1);
+        let a = PlantGenotype::new("omega: F".to_string());
+        let b = PlantGenotype::new("omega: FF".to_string());
+
+        renderer.request(slot, &a);
+        renderer.request(slot, &a);
+        assert_eq!(renderer.queue.len(), 1);
+
+        // A newer genotype replaces the queued render, and late results for
+        // the old one are discarded
+        renderer.request(slot, &b);
+        assert_eq!(renderer.queue.len(), 1);
+        assert_eq!(renderer.queue[0].1, b.content_hash());
+        renderer.finish(slot, a.content_hash(), vec![0; 4]);
+        assert!(renderer.get(slot).is_none());
+
+        renderer.finish(slot, b.content_hash(), vec![1; 4]);
+        assert_eq!(renderer.take(slot).unwrap().rgba, vec![1; 4]);
+        assert!(renderer.is_requested(slot));
+    }
+
+    #[test]
+    fn test_camera_looks_at_bounds() {
+        let (min, max) = (Vec3::new(-10.0, 0.0, -10.0), Vec3::new(10.0, 100.0, 10.0));
+        let transform = thumbnail_camera_transform(min, max);
+        let to_center = ((min + max) * 0.5 - transform.translation).normalize();
+        assert!(transform.forward().dot(to_center) > 0.999);
+        assert!(transform.translation.y > 50.0, "Camera looks slightly down");
+    }
+}
@@ -0,0 +1,229 @@
+//! Tropism gizmo and presets.
+//!
+//! The tropism vector is drawn as an arrow above the editor plant, and a light
+//! gizmo marks the target of the phototropism preset. Both can be dragged in
+//! the viewport: the arrow tip sets the tropism direction and strength, the
+//! light moves the target (and the tropism with it while phototropism is on).
+//! Dragging moves the handle in the plane facing the camera.
+
+use crate::core::config::LSystemConfig;
+use crate::visuals::turtle::TurtleRenderState;
+use bevy::prelude::*;
+use bevy_egui::input::EguiWantsInput;
+use bevy_panorbit_camera::PanOrbitCamera;
+
+/// Screen distance within which a click grabs a handle, in pixels.
+const PICK_RADIUS_PX: f32 = 14.0;
+
+const ARROW_COLOR: Color = Color::srgb(1.0, 0.55, 0.1);
+const LIGHT_COLOR: Color = Color::srgb(1.0, 0.9, 0.3);
+
+/// A common tropism.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TropismPreset {
+    /// Branches bend down.
+    Gravity,
+    /// Wind blowing from +X bends branches toward -X.
+    Wind,
+    /// Branches bend toward the light gizmo.
+    Phototropism,
+}
+
+impl TropismPreset {
+    pub const ALL: [Self; 3] = [Self::Gravity, Self::Wind, Self::Phototropism];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Gravity => "Gravity",
+            Self::Wind => "Wind (+X)",
+            Self::Phototropism => "Phototropism",
+        }
+    }
+}
+
+/// Handle of the gizmo being dragged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum GizmoHandle {
+    Arrow,
+    Light,
+}
+
+/// Gizmo state and the light position.
+#[derive(Resource)]
+pub struct TropismGizmo {
+    pub show: bool,
+    /// Target of phototropism, in world space.
+    pub light: Vec3,
+    /// Set by the phototropism preset: the tropism keeps pointing at the light
+    /// while it moves.
+    pub follow_light: bool,
+    drag: Option<GizmoHandle>,
+}
+
+impl Default for TropismGizmo {
+    fn default() -> Self {
+        Self {
+            show: false,
+            light: Vec3::new(300.0, 600.0, 0.0),
+            follow_light: false,
+            drag: None,
+        }
+    }
+}
+
+impl TropismGizmo {
+    /// Sets `tropism` to `preset`, keeping its strength.
+    pub fn apply_preset(&mut self, preset: TropismPreset, tropism: &mut Option<Vec3>) {
+        let strength = tropism.map_or(1.0, |t| t.length()).max(0.01);
+        let direction = match preset {
+            TropismPreset::Gravity => Vec3::NEG_Y,
+            TropismPreset::Wind => Vec3::NEG_X,
+            // Plants grow from the origin
+            TropismPreset::Phototropism => self.light.normalize_or(Vec3::Y),
+        };
+        self.follow_light = preset == TropismPreset::Phototropism;
+        *tropism = Some(direction * strength);
+    }
+}
+
+/// Start and length of a unit tropism arrow: above the plant, scaled to its
+/// size.
+fn arrow_anchor(render_state: &TurtleRenderState) -> (Vec3, f32) {
+    match render_state.bounds {
+        Some((min, max)) => {
+            let center = (min + max) * 0.5;
+            let size = (max - min).max_element();
+            (Vec3::new(center.x, max.y, center.z), (size * 0.3).max(2.0))
+        }
+        None => (Vec3::ZERO, 5.0),
+    }
+}
+
+/// System that draws the tropism arrow and the light.
+pub fn draw_tropism_gizmo(
+    gizmo: Res<TropismGizmo>,
+    config: Res<LSystemConfig>,
+    render_state: Res<TurtleRenderState>,
+    mut gizmos: Gizmos,
+) {
+    if !gizmo.show {
+        return;
+    }
+    let (start, length) = arrow_anchor(&render_state);
+    if let Some(tropism) = config.tropism {
+        gizmos.arrow(start, start + tropism * length, ARROW_COLOR);
+    }
+    if gizmo.follow_light {
+        gizmos.sphere(
+            Isometry3d::from_translation(gizmo.light),
+            length * 0.1,
+            LIGHT_COLOR,
+        );
+        gizmos.line(Vec3::ZERO, gizmo.light, LIGHT_COLOR.with_alpha(0.3));
+    }
+}
+
+/// System that drags the arrow tip or the light with the left mouse button
+/// (the camera orbits with the right one). The plant is reinterpreted once the
+/// handle is released.
+pub fn drag_tropism_gizmo(
+    mouse: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window>,
+    cameras: Query<(&Camera, &GlobalTransform), With<PanOrbitCamera>>,
+    egui_wants: Res<EguiWantsInput>,
+    render_state: Res<TurtleRenderState>,
+    mut gizmo: ResMut<TropismGizmo>,
+    mut config: ResMut<LSystemConfig>,
+) {
+    // The editor camera; the comparison view's second camera renders after it
+    let Some((camera, camera_transform)) = cameras.iter().find(|(camera, _)| camera.order == 0)
+    else {
+        return;
+    };
+    if gizmo.drag.is_some() && !mouse.pressed(MouseButton::Left) {
+        gizmo.drag = None;
+        config.recompile_requested = true;
+        return;
+    }
+    if !gizmo.show {
+        return;
+    }
+    let Some(cursor) = windows
+        .single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+    else {
+        return;
+    };
+    let (start, length) = arrow_anchor(&render_state);
+
+    if mouse.just_pressed(MouseButton::Left) && !egui_wants.is_pointer_over_area() {
+        let mut handles = Vec::new();
+        if let Some(tropism) = config.tropism {
+            handles.push((GizmoHandle::Arrow, start + tropism * length));
+        }
+        if gizmo.follow_light {
+            handles.push((GizmoHandle::Light, gizmo.light));
+        }
+        gizmo.drag = handles
+            .into_iter()
+            .filter_map(|(handle, position)| {
+                let screen = camera.world_to_viewport(camera_transform, position).ok()?;
+                let distance = screen.distance(cursor);
+                (distance <= PICK_RADIUS_PX).then_some((handle, distance))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(handle, _)| handle);
+    }
+
+    let Some(handle) = gizmo.drag else {
+        return;
+    };
+    let held = match handle {
+        GizmoHandle::Arrow => start + config.tropism.unwrap_or(Vec3::NEG_Y) * length,
+        GizmoHandle::Light => gizmo.light,
+    };
+    let Ok(ray) = camera.viewport_to_world(camera_transform, cursor) else {
+        return;
+    };
+    let plane = InfinitePlane3d::new(camera_transform.forward());
+    let Some(distance) = ray.intersect_plane(held, plane) else {
+        return;
+    };
+    let point = ray.get_point(distance);
+    match handle {
+        GizmoHandle::Arrow => {
+            config.tropism = Some((point - start) / length);
+            gizmo.follow_light = false;
+        }
+        GizmoHandle::Light => {
+            gizmo.light = point;
+            let mut tropism = config.tropism;
+            gizmo.apply_preset(TropismPreset::Phototropism, &mut tropism);
+            config.tropism = tropism;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_keep_strength() {
+        let mut gizmo = TropismGizmo::default();
+        let mut tropism = Some(Vec3::new(0.0, -0.5, 0.0));
+        gizmo.apply_preset(TropismPreset::Wind, &mut tropism);
+        assert_eq!(tropism, Some(Vec3::new(-0.5, 0.0, 0.0)));
+        assert!(!gizmo.follow_light);
+
+        gizmo.light = Vec3::new(0.0, 0.0, 10.0);
+        gizmo.apply_preset(TropismPreset::Phototropism, &mut tropism);
+        assert_eq!(tropism, Some(Vec3::new(0.0, 0.0, 0.5)));
+        assert!(gizmo.follow_light);
+
+        let mut off = None;
+        gizmo.apply_preset(TropismPreset::Gravity, &mut off);
+        assert_eq!(off, Some(Vec3::NEG_Y));
+    }
+}
@@ -1,12 +1,30 @@
-use crate::core::config::{DirtyFlags, LSystemConfig, LSystemEngine, PropConfig, PropMeshType};
+use crate::core::config::{
+    CancellationFlag, CrossSections, DirtyFlags, LSystemConfig, LSystemEngine, MaterialGradients,
+    PropConfig, PropMeshType, SubSystemEngines,
+};
+use crate::logic::composition::{graft_calls, graft_calls_pooled};
+use crate::logic::jitter::{jitter_props, jitter_skeleton, thin_props};
+use crate::logic::strand_pool::StrandPool;
+use crate::logic::taper::taper_width_changes;
+#[cfg(feature = "nursery")]
 use crate::ui::nursery::{NurseryMode, NurseryState};
 use crate::visuals::assets::PropMeshAssets;
+use crate::visuals::gradient::apply_material_gradients;
+use crate::visuals::interpreter::PlantInterpreter;
+use crate::visuals::mesher::SkeletonMesher;
+use crate::visuals::weld::WeldSettings;
+use bevy::ecs::system::SystemParam;
+use bevy::mesh::VertexAttributeValues;
 use bevy::platform::collections::HashMap;
 use bevy::platform::time::Instant;
 use bevy::prelude::*;
-use bevy_symbios::LSystemMeshBuilder;
+use bevy::tasks::AsyncComputeTaskPool;
 use bevy_symbios::materials::MaterialPalette;
-use symbios_turtle_3d::{TurtleConfig, TurtleInterpreter};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use symbios::System;
+use symbios_turtle_3d::{Skeleton, TurtleConfig};
 
 /// Component tag for the main editor L-system meshes.
 #[derive(Component)]
@@ -15,22 +33,29 @@ pub struct LSystemMeshTag;
 #[derive(Component)]
 pub struct LSystemPropTag;
 
+/// Material ID of an editor mesh's bucket.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BucketMaterialId(pub u8);
+
 /// Component storing the tint data for a prop, enabling material reactivity.
 #[derive(Component)]
 pub struct PropTint {
     pub material_id: u8,
     pub color: Vec4,
+    /// Leaf texture of a card prop, see [`get_or_create_prop_material`].
+    pub cutout: Option<Handle<Image>>,
 }
 
-/// Cache key for prop materials: (material_id, color as [u8; 4]).
+/// Cache key for prop materials: (material_id, color as [u8; 4], cutout texture).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct PropMaterialKey {
     pub material_id: u8,
     pub color_rgba: [u8; 4],
+    pub cutout: Option<AssetId<Image>>,
 }
 
 impl PropMaterialKey {
-    pub fn new(material_id: u8, color: Vec4) -> Self {
+    pub fn new(material_id: u8, color: Vec4, cutout: Option<&Handle<Image>>) -> Self {
         Self {
             material_id,
             color_rgba: [
@@ -39,19 +64,52 @@ impl PropMaterialKey {
                 (color.z.clamp(0.0, 1.0) * 255.0) as u8,
                 (color.w.clamp(0.0, 1.0) * 255.0) as u8,
             ],
+            cutout: cutout.map(Handle::id),
         }
     }
 }
 
+/// A prop material: the slot material tinted by the prop color. With a
+/// `cutout` texture the material is alpha-tested and double-sided, for card
+/// props.
+pub fn prop_material(
+    base_mat: StandardMaterial,
+    color: Vec4,
+    cutout: Option<&Handle<Image>>,
+) -> StandardMaterial {
+    let base_srgba = base_mat.base_color.to_srgba();
+    let blended = Color::srgba(
+        base_srgba.red * color.x,
+        base_srgba.green * color.y,
+        base_srgba.blue * color.z,
+        base_srgba.alpha * color.w,
+    );
+    match cutout {
+        Some(texture) => StandardMaterial {
+            base_color: blended,
+            base_color_texture: Some(texture.clone()),
+            alpha_mode: AlphaMode::Mask(0.5),
+            double_sided: true,
+            cull_mode: None,
+            ..base_mat
+        },
+        None => StandardMaterial {
+            base_color: blended,
+            ..base_mat
+        },
+    }
+}
+
 /// Creates or retrieves a cached prop material.
 pub fn get_or_create_prop_material(
     cache: &mut PropMaterialCache,
     materials: &mut Assets<StandardMaterial>,
     palette: &MaterialPalette,
-    key: PropMaterialKey,
     material_id: u8,
     color: Vec4,
+    cutout: Option<&Handle<Image>>,
 ) -> Handle<StandardMaterial> {
+    let key = PropMaterialKey::new(material_id, color, cutout);
     if let Some(handle) = cache.cache.get(&key) {
         return handle.clone();
     }
@@ -62,22 +120,10 @@ pub fn get_or_create_prop_material(
         .unwrap_or(&palette.primary_material);
 
     let base_mat = materials.get(base_handle).cloned().unwrap_or_default();
+    let handle = materials.add(prop_material(base_mat, color, cutout));
 
-    let base_srgba = base_mat.base_color.to_srgba();
-    let blended = Color::srgba(
-        base_srgba.red * color.x,
-        base_srgba.green * color.y,
-        base_srgba.blue * color.z,
-        base_srgba.alpha * color.w,
-    );
-
-    let prop_material = materials.add(StandardMaterial {
-        base_color: blended,
-        ..base_mat
-    });
-
-    cache.cache.insert(key, prop_material.clone());
-    prop_material
+    cache.cache.insert(key, handle.clone());
+    handle
 }
 
 /// Resource caching prop materials by (material_id, color) to avoid duplication.
@@ -86,152 +132,696 @@ pub struct PropMaterialCache {
     pub cache: HashMap<PropMaterialKey, Handle<StandardMaterial>>,
 }
 
+/// Vertex and triangle counts for a single material bucket.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MaterialMeshStats {
+    pub vertices: usize,
+    pub triangles: usize,
+}
+
 #[derive(Resource, Default)]
 pub struct TurtleRenderState {
     pub total_vertices: usize,
+    pub total_triangles: usize,
+    /// Branch geometry counts per material ID (props excluded).
+    pub material_stats: BTreeMap<u8, MaterialMeshStats>,
+    /// Number of strands (unbroken branch polylines) in the skeleton.
+    pub strand_count: usize,
+    /// Number of props spawned from the skeleton.
+    pub prop_count: usize,
+    /// Axis-aligned bounds (min, max) of branch geometry and prop anchors.
+    pub bounds: Option<(Vec3, Vec3)>,
     pub meshing_time_ms: f32,
     pub derivation_time_ms: f32,
+    /// Incremented whenever the editor meshes are replaced or updated in place.
+    pub mesh_revision: u64,
 }
 
-#[allow(clippy::too_many_arguments)]
-pub fn render_turtle(
-    mut commands: Commands,
-    mut dirty: ResMut<DirtyFlags>,
-    engine: Res<LSystemEngine>,
-    config: Res<LSystemConfig>,
-    prop_config: Res<PropConfig>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    palette: Res<MaterialPalette>,
-    mut prop_material_cache: ResMut<PropMaterialCache>,
-    prop_assets: Res<PropMeshAssets>,
-    mut render_state: ResMut<TurtleRenderState>,
-    old_meshes: Query<Entity, With<LSystemMeshTag>>,
-    old_props: Query<Entity, With<LSystemPropTag>>,
-) {
-    if !dirty.geometry {
+/// True if `new` can replace `old` in the same asset without changing its
+/// vertex layout or topology: same attributes, vertex count, and index count.
+///
+/// This holds when only radii or widths changed, e.g. while scrubbing a slider.
+pub fn same_mesh_layout(old: &Mesh, new: &Mesh) -> bool {
+    old.count_vertices() == new.count_vertices()
+        && old.indices().map(|i| i.len()) == new.indices().map(|i| i.len())
+        && old
+            .attributes()
+            .map(|(attribute, _)| attribute.id)
+            .eq(new.attributes().map(|(attribute, _)| attribute.id))
+}
+
+/// Returns the number of triangles in a triangle-list mesh.
+pub fn mesh_triangle_count(mesh: &Mesh) -> usize {
+    match mesh.indices() {
+        Some(indices) => indices.len() / 3,
+        None => mesh.count_vertices() / 3,
+    }
+}
+
+/// Grows `bounds` to include every vertex position of `mesh`.
+pub fn extend_bounds_with_mesh(bounds: &mut Option<(Vec3, Vec3)>, mesh: &Mesh) {
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
         return;
+    };
+    for pos in positions {
+        extend_bounds(bounds, Vec3::from_array(*pos));
     }
-    dirty.geometry = false;
+}
 
-    let sys = &engine.0;
+/// Grows `bounds` to include `point`.
+pub fn extend_bounds(bounds: &mut Option<(Vec3, Vec3)>, point: Vec3) {
+    *bounds = Some(match *bounds {
+        Some((min, max)) => (min.min(point), max.max(point)),
+        None => (point, point),
+    });
+}
 
-    // 1. Cleanup (retain prop material cache across rebuilds to avoid asset churn)
-    for entity in &old_meshes {
-        commands.entity(entity).despawn();
+/// Skeletons with at least this many strands are meshed on a background thread
+/// with progress reporting; smaller ones are meshed inline to avoid a frame of latency.
+pub const ASYNC_MESHING_STRAND_THRESHOLD: usize = 2_000;
+
+/// Strands meshed per chunk between cancellation checks and progress updates.
+const MESHING_CHUNK_STRANDS: usize = 256;
+
+/// Result from a background meshing task.
+struct MeshingResult {
+    skeleton: Skeleton,
+    buckets: HashMap<u8, Mesh>,
+    meshing_time_ms: f32,
+}
+
+/// Tracks the background meshing task for large skeletons.
+#[derive(Resource, Default)]
+pub struct MeshingTask {
+    shared: Option<Arc<Mutex<Option<MeshingResult>>>>,
+    /// Cancellation flag for the current task. Set to false to cancel.
+    cancel_flag: Option<CancellationFlag>,
+    /// Number of strands meshed so far.
+    pub progress: Option<Arc<AtomicUsize>>,
+    /// Total number of strands in the skeleton being meshed.
+    pub total_strands: usize,
+    /// True if the user cancelled the last meshing task.
+    pub cancelled: bool,
+}
+
+impl MeshingTask {
+    /// True while a background meshing task is running.
+    pub fn is_running(&self) -> bool {
+        self.shared.is_some()
     }
-    for entity in &old_props {
-        commands.entity(entity).despawn();
+
+    /// Number of strands meshed so far by the running task.
+    pub fn completed_strands(&self) -> usize {
+        self.progress
+            .as_ref()
+            .map(|p| p.load(Ordering::Relaxed))
+            .unwrap_or(0)
     }
 
-    if sys.state.is_empty() {
-        return;
+    /// Stops the running task, keeping the previously displayed plant.
+    pub fn cancel(&mut self) {
+        if let Some(flag) = self.cancel_flag.take() {
+            flag.store(false, Ordering::Relaxed);
+        }
+        self.shared = None;
+        self.progress = None;
     }
+}
 
-    let start_time = Instant::now();
+/// Meshes a skeleton in chunks of strands, merging the chunks per material.
+///
+/// Checks `cancel_flag` between chunks (returning `None` once it is cleared) and
+/// adds the number of meshed strands to `progress`.
+pub fn mesh_skeleton_chunked(
+    skeleton: &Skeleton,
+    mesher: &SkeletonMesher,
+    cancel_flag: &AtomicBool,
+    progress: &AtomicUsize,
+) -> Option<HashMap<u8, Mesh>> {
+    // Respaced whole, as the chunks cannot see which rings branches start on
+    let skeleton = &*mesher.respace(skeleton);
+    let mesher = &mesher.clone().with_ring_spacing(None);
+    let mut part = skeleton.clone();
+    part.strands.clear();
+    part.props.clear();
+
+    // Chunk buffers are recycled from one chunk to the next
+    let mut pool = StrandPool::default();
+    let mut merged: HashMap<u8, Mesh> = HashMap::new();
+    for chunk in skeleton.strands.chunks(MESHING_CHUNK_STRANDS) {
+        if !cancel_flag.load(Ordering::Relaxed) {
+            return None;
+        }
 
-    // 2. Configure Interpreter
-    let default_step = sys
-        .constants
-        .get("step")
-        .map(|&s| s as f32)
-        .unwrap_or(config.step_size);
-
-    let default_angle = sys
-        .constants
-        .get("angle")
-        .map(|&a| a as f32)
-        .unwrap_or(config.default_angle)
-        .to_radians();
-
-    let initial_width = sys
-        .constants
-        .get("width")
-        .map(|&w| w as f32)
-        .unwrap_or(config.default_width);
-
-    let turtle_config = TurtleConfig {
-        default_step,
-        default_angle,
-        initial_width,
-        tropism: config.tropism,
-        elasticity: config.elasticity,
-        max_stack_depth: 1024,
-    };
+        pool.recycle(&mut part);
+        part.strands
+            .extend(chunk.iter().map(|strand| pool.copy_of(strand)));
+        for (material_id, mesh) in mesher.build_unfinished(&part) {
+            match merged.get_mut(&material_id) {
+                Some(existing) => {
+                    if let Err(e) = existing.merge(&mesh) {
+                        warn!("Failed to merge mesh chunk: {}", e);
+                    }
+                }
+                None => {
+                    merged.insert(material_id, mesh);
+                }
+            }
+        }
+        progress.fetch_add(chunk.len(), Ordering::Relaxed);
+    }
+    // Finished after merging so vertices on chunk seams are joined and shaded too
+    mesher.finish_buckets(&mut merged);
+    Some(merged)
+}
 
-    let mut interpreter = TurtleInterpreter::new(turtle_config);
-    interpreter.populate_standard_symbols(&sys.interner);
+/// Prop entities spawned or despawned per frame by [`drain_prop_queue`].
+pub const PROP_ENTITY_BUDGET: usize = 2_000;
 
-    // 3. Build Skeleton (Geometry + Props)
-    let skeleton = interpreter.build_skeleton(&sys.state);
+/// A prop entity waiting to be spawned.
+struct PendingProp {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+    transform: Transform,
+    tint: PropTint,
+}
 
-    // 4. Mesh Branches (Multi-Material Support)
-    let builder = LSystemMeshBuilder::new().with_resolution(config.mesh_resolution);
-    let mesh_buckets = builder.build(&skeleton);
+/// Spreads prop spawning and despawning over several frames, so replacing tens
+/// of thousands of props does not stall a single frame.
+///
+/// Old props are despawned before any new prop is spawned. Plants with fewer
+/// props than the budget are still replaced within one frame.
+#[derive(Resource)]
+pub struct PropSpawnQueue {
+    despawn: Vec<Entity>,
+    spawn: VecDeque<PendingProp>,
+    /// Maximum number of entities spawned or despawned per frame.
+    pub budget: usize,
+}
 
-    let mut total_verts = 0;
+impl Default for PropSpawnQueue {
+    fn default() -> Self {
+        Self {
+            despawn: Vec::new(),
+            spawn: VecDeque::new(),
+            budget: PROP_ENTITY_BUDGET,
+        }
+    }
+}
 
-    for (material_id, mesh) in mesh_buckets {
-        total_verts += mesh.count_vertices();
+impl PropSpawnQueue {
+    /// Number of prop entities still to be spawned or despawned.
+    pub fn pending(&self) -> usize {
+        self.despawn.len() + self.spawn.len()
+    }
+}
 
-        let material = palette
-            .materials
-            .get(&material_id)
-            .unwrap_or(&palette.primary_material)
-            .clone();
+/// Applies up to `budget` queued prop despawns and spawns.
+pub fn drain_prop_queue(
+    mut commands: Commands,
+    mut queue: ResMut<PropSpawnQueue>,
+    #[cfg(feature = "nursery")] nursery: Res<NurseryState>,
+) {
+    if queue.pending() == 0 {
+        return;
+    }
+    let mut budget = queue.budget.max(1);
+
+    let count = budget.min(queue.despawn.len());
+    let start = queue.despawn.len() - count;
+    for entity in queue.despawn.drain(start..) {
+        if let Ok(mut entity) = commands.get_entity(entity) {
+            entity.despawn();
+        }
+    }
+    budget -= count;
 
+    // Props spawned while the nursery is open stay hidden like the rest of the plant
+    #[cfg(feature = "nursery")]
+    let visibility = match nursery.mode {
+        NurseryMode::Disabled => Visibility::Inherited,
+        NurseryMode::Enabled => Visibility::Hidden,
+    };
+    #[cfg(not(feature = "nursery"))]
+    let visibility = Visibility::Inherited;
+    let count = budget.min(queue.spawn.len());
+    for prop in queue.spawn.drain(..count) {
         commands.spawn((
-            Mesh3d(meshes.add(mesh)),
-            MeshMaterial3d(material),
-            Transform::IDENTITY,
-            LSystemMeshTag,
+            Mesh3d(prop.mesh),
+            MeshMaterial3d(prop.material),
+            prop.transform,
+            visibility,
+            LSystemPropTag,
+            prop.tint,
         ));
     }
+}
+
+/// Resources needed to replace the editor plant's entities.
+#[derive(SystemParam)]
+pub struct TurtleSpawner<'w, 's> {
+    commands: Commands<'w, 's>,
+    prop_config: Res<'w, PropConfig>,
+    meshes: ResMut<'w, Assets<Mesh>>,
+    materials: ResMut<'w, Assets<StandardMaterial>>,
+    palette: Res<'w, MaterialPalette>,
+    prop_material_cache: ResMut<'w, PropMaterialCache>,
+    prop_assets: Res<'w, PropMeshAssets>,
+    render_state: ResMut<'w, TurtleRenderState>,
+    subsystems: Res<'w, SubSystemEngines>,
+    strand_pool: ResMut<'w, StrandPool>,
+    prop_queue: ResMut<'w, PropSpawnQueue>,
+    old_meshes:
+        Query<'w, 's, (Entity, &'static Mesh3d, &'static BucketMaterialId), With<LSystemMeshTag>>,
+    old_props: Query<'w, 's, Entity, With<LSystemPropTag>>,
+}
+
+impl TurtleSpawner<'_, '_> {
+    /// Despawns the current plant (retaining the prop material cache to avoid asset churn).
+    /// Props are handed to the [`PropSpawnQueue`], superseding any still queued.
+    fn clear(&mut self) {
+        for (entity, _, _) in self.old_meshes.iter() {
+            self.commands.entity(entity).despawn();
+        }
+        self.clear_props();
+    }
 
-    // 5. Spawn Props (with inherited material ID and color, using cache)
-    for prop in &skeleton.props {
-        let mesh_type = prop_config
-            .prop_meshes
-            .get(&prop.prop_id)
-            .copied()
-            .unwrap_or(PropMeshType::Leaf);
+    fn clear_props(&mut self) {
+        self.prop_queue.despawn = self.old_props.iter().collect();
+        self.prop_queue.spawn.clear();
+    }
+
+    /// The existing mesh handles by material, if `buckets` can be written into
+    /// them in place: one bucket per existing mesh, each with the same layout.
+    fn reusable_meshes(&self, buckets: &HashMap<u8, Mesh>) -> Option<HashMap<u8, Handle<Mesh>>> {
+        if self.old_meshes.iter().count() != buckets.len() {
+            return None;
+        }
+        let mut handles = HashMap::new();
+        for (_, handle, material_id) in self.old_meshes.iter() {
+            let old = self.meshes.get(&handle.0)?;
+            let new = buckets.get(&material_id.0)?;
+            if !same_mesh_layout(old, new) {
+                return None;
+            }
+            handles.insert(material_id.0, handle.0.clone());
+        }
+        Some(handles)
+    }
 
-        let mesh_handle = prop_assets.meshes.get(&mesh_type);
+    /// Replaces the current plant with freshly meshed branches and the skeleton's props.
+    /// With `#system` sections, the skeleton's strand buffers are returned to the
+    /// pool for the next rebuild's grafting.
+    ///
+    /// Meshes with an unchanged layout are updated in their existing assets, so
+    /// the entities and GPU pipelines are kept.
+    fn spawn(&mut self, mut skeleton: Skeleton, buckets: HashMap<u8, Mesh>, meshing_time_ms: f32) {
+        let reusable = self.reusable_meshes(&buckets);
+        if reusable.is_some() {
+            self.clear_props();
+        } else {
+            self.clear();
+        }
 
-        if let Some(handle) = mesh_handle {
-            if let Some(mesh) = meshes.get(handle) {
-                total_verts += mesh.count_vertices();
+        let mut total_verts = 0;
+        let mut total_triangles = 0;
+        let mut material_stats = BTreeMap::new();
+        let mut bounds = None;
+
+        for (material_id, mesh) in buckets {
+            let stats = MaterialMeshStats {
+                vertices: mesh.count_vertices(),
+                triangles: mesh_triangle_count(&mesh),
+            };
+            total_verts += stats.vertices;
+            total_triangles += stats.triangles;
+            material_stats.insert(material_id, stats);
+            extend_bounds_with_mesh(&mut bounds, &mesh);
+
+            if let Some(handle) = reusable.as_ref().and_then(|r| r.get(&material_id))
+                && let Some(existing) = self.meshes.get_mut(handle)
+            {
+                *existing = mesh;
+                continue;
             }
 
-            let key = PropMaterialKey::new(prop.material_id, prop.color);
-            let prop_material = get_or_create_prop_material(
-                &mut prop_material_cache,
-                &mut materials,
-                &palette,
-                key,
-                prop.material_id,
-                prop.color,
-            );
-
-            commands.spawn((
-                Mesh3d(handle.clone()),
-                MeshMaterial3d(prop_material),
-                Transform {
-                    translation: prop.position,
-                    rotation: prop.rotation,
-                    scale: prop.scale * prop_config.prop_scale,
-                },
-                LSystemPropTag,
-                PropTint {
-                    material_id: prop.material_id,
-                    color: prop.color,
-                },
+            let material = self
+                .palette
+                .materials
+                .get(&material_id)
+                .unwrap_or(&self.palette.primary_material)
+                .clone();
+
+            self.commands.spawn((
+                Mesh3d(self.meshes.add(mesh)),
+                MeshMaterial3d(material),
+                Transform::IDENTITY,
+                LSystemMeshTag,
+                BucketMaterialId(material_id),
             ));
         }
+
+        // Spawn Props (with inherited material ID and color, using cache)
+        for prop in &skeleton.props {
+            let mesh_type = self
+                .prop_config
+                .prop_meshes
+                .get(&prop.prop_id)
+                .copied()
+                .unwrap_or(PropMeshType::Leaf);
+
+            let mesh_handle = self.prop_assets.meshes.get(&mesh_type);
+
+            extend_bounds(&mut bounds, prop.position);
+
+            if let Some(handle) = mesh_handle {
+                if let Some(mesh) = self.meshes.get(handle) {
+                    total_verts += mesh.count_vertices();
+                    total_triangles += mesh_triangle_count(mesh);
+                }
+
+                let cutout = mesh_type
+                    .is_cutout()
+                    .then(|| self.prop_assets.leaf_texture.clone());
+                let prop_material = get_or_create_prop_material(
+                    &mut self.prop_material_cache,
+                    &mut self.materials,
+                    &self.palette,
+                    prop.material_id,
+                    prop.color,
+                    cutout.as_ref(),
+                );
+
+                self.prop_queue.spawn.push_back(PendingProp {
+                    mesh: handle.clone(),
+                    material: prop_material,
+                    transform: Transform {
+                        translation: prop.position,
+                        rotation: prop.rotation,
+                        scale: prop.scale * self.prop_config.prop_scale,
+                    },
+                    tint: PropTint {
+                        material_id: prop.material_id,
+                        color: prop.color,
+                        cutout,
+                    },
+                });
+            }
+        }
+
+        let render_state = &mut self.render_state;
+        render_state.total_vertices = total_verts;
+        render_state.total_triangles = total_triangles;
+        render_state.material_stats = material_stats;
+        render_state.strand_count = skeleton.strands.len();
+        render_state.prop_count = skeleton.props.len();
+        render_state.bounds = bounds;
+        render_state.meshing_time_ms = meshing_time_ms;
+        render_state.mesh_revision += 1;
+        info!(
+            "Meshed {} vertices, {} triangles in {:.1} ms",
+            total_verts, total_triangles, meshing_time_ms
+        );
+
+        // Only grafting draws from the pool
+        if !self.subsystems.0.is_empty() {
+            self.strand_pool.recycle(&mut skeleton);
+        }
+    }
+}
+
+/// Turtle settings for a derived system: its `step`, `angle`, and `width`
+/// constants, falling back to the editor settings.
+pub fn turtle_config_for(sys: &System, config: &LSystemConfig) -> TurtleConfig {
+    turtle_config_with_defaults(
+        sys,
+        config.step_size,
+        config.default_angle,
+        config.default_width,
+        config.tropism,
+        config.elasticity,
+    )
+}
+
+/// Turtle settings for a derived system from explicit defaults (angle in
+/// degrees), overridden by the system's `step`, `angle`, and `width` constants.
+/// The editor, nursery, and export all configure their turtles through this.
+pub fn turtle_config_with_defaults(
+    sys: &System,
+    step: f32,
+    angle_degrees: f32,
+    width: f32,
+    tropism: Option<Vec3>,
+    elasticity: f32,
+) -> TurtleConfig {
+    let constant = |name: &str| sys.constants.get(name).map(|&v| v as f32);
+    TurtleConfig {
+        default_step: constant("step").unwrap_or(step),
+        default_angle: constant("angle").unwrap_or(angle_degrees).to_radians(),
+        initial_width: constant("width").unwrap_or(width),
+        tropism,
+        elasticity,
+        max_stack_depth: 1024,
+    }
+}
+
+/// Interprets a derived system into a skeleton with the standard symbol set
+/// and per-branch tropism.
+pub fn interpret_skeleton(sys: &System, turtle_config: TurtleConfig) -> Skeleton {
+    let mut interpreter = PlantInterpreter::new(turtle_config);
+    interpreter.populate_standard_symbols(&sys.interner);
+    interpreter.build_skeleton(&sys.state)
+}
+
+/// Skeleton of `axiom` as written, with a unit step: the fixture of the
+/// interpreter, mesher, and skeleton tests.
+#[cfg(test)]
+pub(crate) fn axiom_skeleton(
+    axiom: &str,
+    angle_degrees: f32,
+    width: f32,
+    tropism: Option<Vec3>,
+    elasticity: f32,
+) -> Skeleton {
+    let mut sys = System::new();
+    sys.set_axiom(axiom).unwrap();
+    sys.derive(0).unwrap();
+    interpret_skeleton(
+        &sys,
+        turtle_config_with_defaults(&sys, 1.0, angle_degrees, width, tropism, elasticity),
+    )
+}
+
+/// A populated interpreter with the turtle settings it was built for.
+struct CachedInterpreter {
+    turtle_config: TurtleConfig,
+    interpreter: PlantInterpreter,
+}
+
+/// Populated interpreters of the editor grammar (slot 0) and its `#system`
+/// sections (slot `i + 1`), so geometry rebuilds that do not re-derive skip
+/// `populate_standard_symbols`. [`render_turtle`] invalidates the cache
+/// whenever the derived systems, and with them their interners, change.
+#[derive(Resource, Default)]
+pub struct InterpreterCache {
+    slots: Vec<Option<CachedInterpreter>>,
+}
+
+impl InterpreterCache {
+    /// Drops every cached interpreter; call when the symbol tables change.
+    pub fn invalidate(&mut self) {
+        self.slots.clear();
+    }
+
+    /// Interprets `sys` like [`interpret_skeleton`] with
+    /// [`turtle_config_for`], reusing the interpreter of `slot` unless the
+    /// turtle settings changed since it was populated.
+    pub fn skeleton(&mut self, slot: usize, sys: &System, config: &LSystemConfig) -> Skeleton {
+        if self.slots.len() <= slot {
+            self.slots.resize_with(slot + 1, || None);
+        }
+        let turtle_config = turtle_config_for(sys, config);
+        let cached = match &mut self.slots[slot] {
+            Some(cached) if same_turtle_config(&cached.turtle_config, &turtle_config) => cached,
+            entry => {
+                let mut interpreter = PlantInterpreter::new(turtle_config_for(sys, config));
+                interpreter.populate_standard_symbols(&sys.interner);
+                entry.insert(CachedInterpreter {
+                    turtle_config,
+                    interpreter,
+                })
+            }
+        };
+        cached.interpreter.build_skeleton(&sys.state)
+    }
+}
+
+fn same_turtle_config(a: &TurtleConfig, b: &TurtleConfig) -> bool {
+    a.default_step == b.default_step
+        && a.default_angle == b.default_angle
+        && a.initial_width == b.initial_width
+        && a.tropism == b.tropism
+        && a.elasticity == b.elasticity
+        && a.max_stack_depth == b.max_stack_depth
+}
+
+/// The editor plant's skeleton as [`render_turtle`] meshes it: `#system`
+/// sections grafted, jitter applied, and width changes tapered, without
+/// material gradients.
+pub fn editor_skeleton(
+    engine: &LSystemEngine,
+    subsystems: &SubSystemEngines,
+    config: &LSystemConfig,
+) -> Skeleton {
+    let build = |sys: &System, seed: u64| {
+        let mut skeleton = interpret_skeleton(sys, turtle_config_for(sys, config));
+        jitter_skeleton(&mut skeleton, seed);
+        skeleton
+    };
+    let mut skeleton = build(&engine.0, config.seed);
+    if !subsystems.0.is_empty() {
+        let sections: Vec<Option<Skeleton>> = subsystems
+            .0
+            .iter()
+            .enumerate()
+            .map(|(i, sys)| Some(build(sys, config.seed.wrapping_add(i as u64 + 1))))
+            .collect();
+        graft_calls(&mut skeleton, &sections);
+    }
+    taper_width_changes(&mut skeleton);
+    skeleton
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn render_turtle(
+    mut dirty: ResMut<DirtyFlags>,
+    engine: Res<LSystemEngine>,
+    subsystems: Res<SubSystemEngines>,
+    config: Res<LSystemConfig>,
+    gradients: Res<MaterialGradients>,
+    cross_sections: Res<CrossSections>,
+    mut meshing: ResMut<MeshingTask>,
+    mut interpreters: ResMut<InterpreterCache>,
+    mut spawner: TurtleSpawner,
+) {
+    // A new derivation brings new interners, so the op tables are stale
+    if engine.is_changed() || subsystems.is_changed() {
+        interpreters.invalidate();
+    }
+    if !dirty.geometry {
+        return;
+    }
+    dirty.geometry = false;
+
+    // A newer derivation supersedes any mesh still being built
+    meshing.cancel();
+    meshing.cancelled = false;
+
+    let sys = &engine.0;
+
+    if sys.state.is_empty() {
+        spawner.clear();
+        *spawner.render_state = TurtleRenderState {
+            derivation_time_ms: spawner.render_state.derivation_time_ms,
+            mesh_revision: spawner.render_state.mesh_revision + 1,
+            ..default()
+        };
+        return;
+    }
+
+    let start_time = Instant::now();
+
+    // 1. Build Skeleton (Geometry + Props), grafting called #system sections
+    // Each grammar is jittered with its derivation seed before grafting
+    let mut skeleton = interpreters.skeleton(0, sys, &config);
+    jitter_skeleton(&mut skeleton, config.seed);
+    if !subsystems.0.is_empty() {
+        let sections: Vec<Option<Skeleton>> = subsystems
+            .0
+            .iter()
+            .enumerate()
+            .map(|(i, sys)| {
+                let mut section = interpreters.skeleton(i + 1, sys, &config);
+                jitter_skeleton(&mut section, config.seed.wrapping_add(i as u64 + 1));
+                Some(section)
+            })
+            .collect();
+        graft_calls_pooled(&mut skeleton, &sections, &mut spawner.strand_pool);
+        for mut section in sections.into_iter().flatten() {
+            spawner.strand_pool.recycle(&mut section);
+        }
     }
+    jitter_props(
+        &mut skeleton.props,
+        &spawner.prop_config.prop_jitter,
+        config.seed,
+    );
+    thin_props(
+        &mut skeleton.props,
+        spawner.prop_config.prop_density,
+        config.seed,
+    );
+    taper_width_changes(&mut skeleton);
+    apply_material_gradients(&mut skeleton, &gradients.gradients);
+    let mesher = SkeletonMesher::new(config.mesh_resolution)
+        .with_profiles(cross_sections.profiles.clone())
+        .with_weld(config.weld_vertices.then(WeldSettings::default))
+        .with_shading(config.shading)
+        .with_ring_spacing(config.ring_spacing);
+
+    // 2. Mesh Branches (Multi-Material Support)
+    if skeleton.strands.len() < ASYNC_MESHING_STRAND_THRESHOLD {
+        let buckets = mesher.build(&skeleton);
+        let meshing_time_ms = start_time.elapsed().as_secs_f32() * 1000.0;
+        spawner.spawn(skeleton, buckets, meshing_time_ms);
+        return;
+    }
+
+    // Large skeleton: mesh in the background so the UI stays responsive and cancellable
+    let shared: Arc<Mutex<Option<MeshingResult>>> = Arc::new(Mutex::new(None));
+    let cancel_flag: CancellationFlag = Arc::new(AtomicBool::new(true));
+    let progress = Arc::new(AtomicUsize::new(0));
+
+    meshing.shared = Some(shared.clone());
+    meshing.cancel_flag = Some(cancel_flag.clone());
+    meshing.progress = Some(progress.clone());
+    meshing.total_strands = skeleton.strands.len();
+
+    AsyncComputeTaskPool::get()
+        .spawn(async move {
+            let Some(buckets) = mesh_skeleton_chunked(&skeleton, &mesher, &cancel_flag, &progress)
+            else {
+                return;
+            };
+            let meshing_time_ms = start_time.elapsed().as_secs_f32() * 1000.0;
+            if cancel_flag.load(Ordering::Relaxed)
+                && let Ok(mut guard) = shared.lock()
+            {
+                *guard = Some(MeshingResult {
+                    skeleton,
+                    buckets,
+                    meshing_time_ms,
+                });
+            }
+        })
+        .detach();
+}
+
+/// Polls the background meshing task and spawns the plant once it completes.
+pub fn poll_meshing(mut meshing: ResMut<MeshingTask>, mut spawner: TurtleSpawner) {
+    let Some(shared) = &meshing.shared else {
+        return;
+    };
+    let Some(result) = shared.lock().ok().and_then(|mut guard| guard.take()) else {
+        return;
+    };
+    meshing.shared = None;
+    meshing.cancel_flag = None;
+    meshing.progress = None;
 
-    render_state.total_vertices = total_verts;
-    render_state.meshing_time_ms = start_time.elapsed().as_secs_f32() * 1000.0;
+    spawner.spawn(result.skeleton, result.buckets, result.meshing_time_ms);
 }
 
 /// System that updates prop materials when the MaterialPalette changes.
@@ -250,14 +840,13 @@ pub fn sync_prop_materials(
     prop_material_cache.cache.clear();
 
     for (tint, mut mat_handle) in &mut props {
-        let key = PropMaterialKey::new(tint.material_id, tint.color);
         let new_handle = get_or_create_prop_material(
             &mut prop_material_cache,
             &mut materials,
             &palette,
-            key,
             tint.material_id,
             tint.color,
+            tint.cutout.as_ref(),
         );
         mat_handle.0 = new_handle;
     }
@@ -265,6 +854,7 @@ pub fn sync_prop_materials(
 
 /// System that toggles visibility of editor meshes based on nursery mode.
 /// When nursery is enabled, the editor's single plant is hidden.
+#[cfg(feature = "nursery")]
 pub fn toggle_editor_visibility(
     nursery: Res<NurseryState>,
     mut meshes: Query<&mut Visibility, With<LSystemMeshTag>>,
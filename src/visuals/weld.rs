@@ -0,0 +1,235 @@
+//! Vertex welding before export.
+//!
+//! Adjacent tube rings and strand restarts emit coincident vertices. Welding
+//! merges vertices whose positions and normals agree within a tolerance (and
+//! whose other attributes match), then drops triangles that collapse.
+//!
+//! Vertices are bucketed by quantizing each attribute to its tolerance, so two
+//! vertices closer than the tolerance can still land in neighbouring cells and
+//! stay separate. This keeps the pass linear and never merges vertices further
+//! apart than one cell.
+
+use bevy::mesh::{Indices, MeshVertexAttribute, VertexAttributeValues};
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+
+/// Quantization step for attributes other than position and normal (UVs, colors, tangents).
+const ATTRIBUTE_TOLERANCE: f32 = 1e-4;
+
+/// Tolerances for merging vertices.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WeldSettings {
+    /// Maximum distance between merged positions, in world units.
+    pub position_tolerance: f32,
+    /// Maximum per-component difference between merged unit normals.
+    pub normal_tolerance: f32,
+}
+
+impl Default for WeldSettings {
+    fn default() -> Self {
+        Self {
+            position_tolerance: 1e-4,
+            normal_tolerance: 0.01,
+        }
+    }
+}
+
+/// Vertex and triangle counts before and after welding.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WeldStats {
+    pub vertices_before: usize,
+    pub vertices_after: usize,
+    /// Triangles removed because welding collapsed them.
+    pub degenerate_triangles: usize,
+}
+
+impl WeldStats {
+    pub fn add(&mut self, other: WeldStats) {
+        self.vertices_before += other.vertices_before;
+        self.vertices_after += other.vertices_after;
+        self.degenerate_triangles += other.degenerate_triangles;
+    }
+
+    /// Fraction of vertices removed, in `0.0..=1.0`.
+    pub fn reduction(&self) -> f32 {
+        if self.vertices_before == 0 {
+            0.0
+        } else {
+            1.0 - self.vertices_after as f32 / self.vertices_before as f32
+        }
+    }
+}
+
+/// Welds a triangle-list mesh in place.
+///
+/// Meshes with vertex attributes in formats other than 32-bit float vectors are
+/// left untouched, since their values cannot be compared reliably.
+pub fn weld_mesh(mesh: &mut Mesh, settings: WeldSettings) -> WeldStats {
+    let vertex_count = mesh.count_vertices();
+    let unchanged = WeldStats {
+        vertices_before: vertex_count,
+        vertices_after: vertex_count,
+        degenerate_triangles: 0,
+    };
+
+    let attributes: Vec<(MeshVertexAttribute, VertexAttributeValues)> = mesh
+        .attributes()
+        .map(|(attribute, values)| (*attribute, values.clone()))
+        .collect();
+    let mut columns = Vec::with_capacity(attributes.len());
+    for (attribute, values) in &attributes {
+        let Some((components, data)) = float_components(values) else {
+            return unchanged;
+        };
+        let tolerance = if attribute.id == Mesh::ATTRIBUTE_POSITION.id {
+            settings.position_tolerance
+        } else if attribute.id == Mesh::ATTRIBUTE_NORMAL.id {
+            settings.normal_tolerance
+        } else {
+            ATTRIBUTE_TOLERANCE
+        };
+        columns.push((components, data, tolerance.max(f32::EPSILON)));
+    }
+
+    // Map each vertex to the first vertex sharing its quantized key
+    let mut first_by_key: HashMap<Vec<i64>, u32> = HashMap::default();
+    let mut remap = Vec::with_capacity(vertex_count);
+    let mut kept = Vec::new();
+    for vertex in 0..vertex_count {
+        let key: Vec<i64> = columns
+            .iter()
+            .flat_map(|(components, data, tolerance)| {
+                data[vertex * components..(vertex + 1) * components]
+                    .iter()
+                    .map(move |value| (value / tolerance).round() as i64)
+            })
+            .collect();
+        let index = *first_by_key.entry(key).or_insert_with(|| {
+            kept.push(vertex);
+            kept.len() as u32 - 1
+        });
+        remap.push(index);
+    }
+
+    let old_indices: Vec<usize> = match mesh.indices() {
+        Some(indices) => indices.iter().collect(),
+        None => (0..vertex_count).collect(),
+    };
+    let mut indices = Vec::with_capacity(old_indices.len());
+    let mut degenerate_triangles = 0;
+    for triangle in old_indices.chunks_exact(3) {
+        let [a, b, c] = [remap[triangle[0]], remap[triangle[1]], remap[triangle[2]]];
+        if a == b || b == c || a == c {
+            degenerate_triangles += 1;
+        } else {
+            indices.extend([a, b, c]);
+        }
+    }
+
+    for (attribute, values) in attributes {
+        mesh.insert_attribute(attribute, select_vertices(&values, &kept));
+    }
+    mesh.insert_indices(Indices::U32(indices));
+
+    WeldStats {
+        vertices_before: vertex_count,
+        vertices_after: kept.len(),
+        degenerate_triangles,
+    }
+}
+
+/// Flattened float data of an attribute with its component count.
+pub(crate) fn float_components(values: &VertexAttributeValues) -> Option<(usize, &[f32])> {
+    match values {
+        VertexAttributeValues::Float32(v) => Some((1, v.as_slice())),
+        VertexAttributeValues::Float32x2(v) => Some((2, v.as_flattened())),
+        VertexAttributeValues::Float32x3(v) => Some((3, v.as_flattened())),
+        VertexAttributeValues::Float32x4(v) => Some((4, v.as_flattened())),
+        _ => None,
+    }
+}
+
+/// Gathers the given vertices of a float attribute.
+pub(crate) fn select_vertices(
+    values: &VertexAttributeValues,
+    kept: &[usize],
+) -> VertexAttributeValues {
+    match values {
+        VertexAttributeValues::Float32(v) => {
+            VertexAttributeValues::Float32(kept.iter().map(|&i| v[i]).collect())
+        }
+        VertexAttributeValues::Float32x2(v) => {
+            VertexAttributeValues::Float32x2(kept.iter().map(|&i| v[i]).collect())
+        }
+        VertexAttributeValues::Float32x3(v) => {
+            VertexAttributeValues::Float32x3(kept.iter().map(|&i| v[i]).collect())
+        }
+        VertexAttributeValues::Float32x4(v) => {
+            VertexAttributeValues::Float32x4(kept.iter().map(|&i| v[i]).collect())
+        }
+        // Rejected by float_components before any vertex is selected
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::asset::RenderAssetUsages;
+    use bevy::mesh::PrimitiveTopology;
+
+    /// Two triangles forming a quad, with the shared edge duplicated.
+    fn split_quad() -> Mesh {
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        );
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_POSITION,
+            vec![
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [1.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0],
+                [1.0, 1.0, 0.00001],
+                [0.0, 1.0, 0.0],
+            ],
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 0.0, 1.0]; 6]);
+        mesh.insert_indices(Indices::U32(vec![0, 1, 2, 3, 4, 5]));
+        mesh
+    }
+
+    #[test]
+    fn test_weld_merges_shared_edge() {
+        let mut mesh = split_quad();
+        let stats = weld_mesh(&mut mesh, WeldSettings::default());
+        assert_eq!(stats.vertices_before, 6);
+        assert_eq!(stats.vertices_after, 4);
+        assert_eq!(stats.degenerate_triangles, 0);
+        assert_eq!(mesh.count_vertices(), 4);
+        let indices: Vec<usize> = mesh.indices().unwrap().iter().collect();
+        assert_eq!(indices, vec![0, 1, 2, 0, 2, 3]);
+    }
+
+    #[test]
+    fn test_weld_keeps_differing_normals_and_drops_collapsed() {
+        let mut mesh = split_quad();
+        let mut normals = vec![[0.0, 0.0, 1.0]; 6];
+        normals[3] = [0.0, 1.0, 0.0];
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        let stats = weld_mesh(&mut mesh, WeldSettings::default());
+        assert_eq!(stats.vertices_after, 5, "Hard edge stays split");
+
+        let mut sliver = split_quad();
+        let stats = weld_mesh(
+            &mut sliver,
+            WeldSettings {
+                position_tolerance: 10.0,
+                ..default()
+            },
+        );
+        assert_eq!(stats.degenerate_triangles, 2);
+        assert!(sliver.indices().unwrap().is_empty());
+    }
+}
@@ -2,8 +2,14 @@
 use bevy::ecs::system::RunSystemOnce;
 use bevy::prelude::*;
 use lsystem_explorer::core::config::*;
-use lsystem_explorer::visuals::export::ExportStatus;
-use lsystem_explorer::visuals::turtle::{PropMaterialCache, TurtleRenderState};
+use lsystem_explorer::core::garden::Garden;
+use lsystem_explorer::core::history::DerivationHistory;
+use lsystem_explorer::core::includes::IncludeLibrary;
+use lsystem_explorer::core::terrain::Terrain;
+use lsystem_explorer::logic::strand_pool::StrandPool;
+use lsystem_explorer::visuals::turtle::{
+    InterpreterCache, MeshingTask, PropMaterialCache, PropSpawnQueue, TurtleRenderState,
+};
 
 /// Creates a minimal headless Bevy app with necessary resources and plugins
 pub fn setup_headless_app() -> App {
@@ -23,17 +29,30 @@ pub fn setup_headless_app() -> App {
     // Initialize L-System Explorer Resources
     app.init_resource::<LSystemConfig>()
         .init_resource::<LSystemEngine>()
+        .init_resource::<SubSystemEngines>()
         .init_resource::<DerivationStatus>()
         .init_resource::<DerivationDebounce>()
         .init_resource::<DerivationTask>()
+        .init_resource::<DerivationHistory>()
         .init_resource::<DirtyFlags>()
         .init_resource::<LSystemAnalysis>()
         .init_resource::<PropConfig>()
         .init_resource::<MaterialSettingsMap>()
+        .init_resource::<MaterialGradients>()
+        .init_resource::<CrossSections>()
         .init_resource::<ExportConfig>()
-        .init_resource::<ExportStatus>()
+        .init_resource::<Garden>()
+        .init_resource::<Terrain>()
         .init_resource::<TurtleRenderState>()
-        .init_resource::<PropMaterialCache>();
+        .init_resource::<MeshingTask>()
+        .init_resource::<InterpreterCache>()
+        .init_resource::<StrandPool>()
+        .init_resource::<PropSpawnQueue>()
+        .init_resource::<PropMaterialCache>()
+        // Start from an empty include library rather than the user's saved buffers
+        .insert_resource(IncludeLibrary::empty());
+    #[cfg(feature = "export")]
+    app.init_resource::<lsystem_explorer::visuals::export::ExportStatus>();
 
     // Mock the asset setup usually done in main.rs
     // run_system_once takes the function directly
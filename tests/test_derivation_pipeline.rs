@@ -1,7 +1,12 @@
 mod common;
+use bevy::ecs::system::RunSystemOnce;
 use bevy::prelude::*;
 use common::setup_headless_app;
-use lsystem_explorer::core::config::{DerivationStatus, DirtyFlags, LSystemConfig, LSystemEngine};
+use lsystem_explorer::core::config::{
+    DerivationStatus, DirtyFlags, LSystemConfig, LSystemEngine, SubSystemEngines,
+};
+use lsystem_explorer::core::history::DerivationHistory;
+use lsystem_explorer::core::includes::IncludeLibrary;
 use lsystem_explorer::logic::derivation::{poll_derivation, start_derivation};
 
 #[test]
@@ -14,11 +19,9 @@ fn test_async_derivation_flow() {
     config.iterations = 2;
     config.recompile_requested = true;
 
-    // Add the derivation systems
-    app.add_systems(Update, (start_derivation, poll_derivation).chain());
-
-    // 2. First Update: Should Trigger Start
-    app.update();
+    // 2. Start the task on its own, so it cannot be polled to completion
+    // before the check below
+    app.world_mut().run_system_once(start_derivation).unwrap();
 
     // Verify task started
     let status = app.world().resource::<DerivationStatus>();
@@ -28,6 +31,9 @@ fn test_async_derivation_flow() {
     );
     assert!(status.error.is_none(), "Should be no error initially");
 
+    // Add the derivation systems
+    app.add_systems(Update, (start_derivation, poll_derivation).chain());
+
     // 3. Subsequent Updates: Wait for Async Task
     // We loop briefly to allow the thread pool to finish the simple derivation
     let mut done = false;
@@ -57,4 +63,137 @@ fn test_async_derivation_flow() {
         dirty.geometry,
         "Geometry dirty flag should be set after derivation"
     );
+
+    // 5. Successful derivation is recorded in the history
+    let history = app.world().resource::<DerivationHistory>();
+    assert_eq!(history.entries.len(), 1);
+    assert_eq!(history.entries[0].source_code, "omega: F\np1: F -> F+F");
+    assert_eq!(history.entries[0].iterations, 2);
+}
+
+#[test]
+fn test_derivation_expands_includes() {
+    let mut app = setup_headless_app();
+    app.world_mut()
+        .resource_mut::<IncludeLibrary>()
+        .buffers
+        .insert("grow.ls".into(), "p1: F -> F F".into());
+
+    let mut config = app.world_mut().resource_mut::<LSystemConfig>();
+    config.source_code = "omega: F\n#include \"grow.ls\"".to_string();
+    config.iterations = 3;
+    config.recompile_requested = true;
+
+    app.add_systems(Update, (start_derivation, poll_derivation).chain());
+    app.update();
+    for _ in 0..100 {
+        app.update();
+        if !app.world().resource::<DerivationStatus>().generating {
+            break;
+        }
+        std::thread::sleep(chrono::Duration::milliseconds(10).to_std().unwrap());
+    }
+
+    let status = app.world().resource::<DerivationStatus>();
+    assert!(
+        status.error.is_none(),
+        "Unexpected error: {:?}",
+        status.error
+    );
+    let engine = app.world().resource::<LSystemEngine>();
+    assert_eq!(engine.0.state.len(), 8, "F doubles each iteration");
+}
+
+#[test]
+fn test_genotype_derivation_expands_includes() {
+    use lsystem_explorer::core::genotype::PlantGenotype;
+    use lsystem_explorer::visuals::genotype_render::derive_genotype;
+
+    let mut includes = IncludeLibrary::empty();
+    includes
+        .buffers
+        .insert("grow.ls".into(), "p1: F -> F F".into());
+    let mut genotype = PlantGenotype::new("omega: F\n#include \"grow.ls\"".to_string());
+    genotype.iterations = 3;
+
+    let derived = derive_genotype(&genotype, &includes).expect("includes expand");
+    assert_eq!(derived.system.state.len(), 8, "F doubles each iteration");
+    assert!(derive_genotype(&genotype, &IncludeLibrary::empty()).is_none());
+}
+
+#[test]
+fn test_derivation_splits_named_systems() {
+    let mut app = setup_headless_app();
+    let mut config = app.world_mut().resource_mut::<LSystemConfig>();
+    config.source_code =
+        "omega: A\np1: A -> F @tip\n#system tip 2\nomega: T\np1: T -> F T".to_string();
+    config.iterations = 1;
+    config.recompile_requested = true;
+
+    app.add_systems(Update, (start_derivation, poll_derivation).chain());
+    app.update();
+    for _ in 0..100 {
+        app.update();
+        if !app.world().resource::<DerivationStatus>().generating {
+            break;
+        }
+        std::thread::sleep(chrono::Duration::milliseconds(10).to_std().unwrap());
+    }
+
+    let status = app.world().resource::<DerivationStatus>();
+    assert!(
+        status.error.is_none(),
+        "Unexpected error: {:?}",
+        status.error
+    );
+    let engine = app.world().resource::<LSystemEngine>();
+    assert_eq!(engine.0.state.len(), 2, "F followed by the call prop");
+    let subsystems = app.world().resource::<SubSystemEngines>();
+    assert_eq!(subsystems.0.len(), 1);
+    assert_eq!(
+        subsystems.0[0].state.len(),
+        3,
+        "Section uses its own iterations"
+    );
+}
+
+#[test]
+fn test_parallel_systems_match_sequential() {
+    let derive = |parallel: bool| {
+        let mut app = setup_headless_app();
+        let mut config = app.world_mut().resource_mut::<LSystemConfig>();
+        config.source_code = "omega: @a @b\n\
+                              #system a 3\nomega: A\np1: A : 0.5 -> F A A\np2: A : 0.5 -> F\n\
+                              #system b 3\nomega: B\np1: B : 0.5 -> F B B\np2: B : 0.5 -> F"
+            .to_string();
+        config.iterations = 1;
+        config.parallel_derivation = parallel;
+        config.recompile_requested = true;
+
+        app.add_systems(Update, (start_derivation, poll_derivation).chain());
+        app.update();
+        for _ in 0..100 {
+            app.update();
+            if !app.world().resource::<DerivationStatus>().generating {
+                break;
+            }
+            std::thread::sleep(chrono::Duration::milliseconds(10).to_std().unwrap());
+        }
+        let status = app.world().resource::<DerivationStatus>();
+        assert!(
+            status.error.is_none(),
+            "Unexpected error: {:?}",
+            status.error
+        );
+        let subsystems = app.world().resource::<SubSystemEngines>();
+        subsystems
+            .0
+            .iter()
+            .map(|sys| sys.state.len())
+            .collect::<Vec<_>>()
+    };
+
+    let sequential = derive(false);
+    assert_eq!(sequential.len(), 2);
+    assert_eq!(derive(true), sequential, "seeds do not depend on the order");
 }
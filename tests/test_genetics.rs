@@ -0,0 +1,348 @@
+//! Statistical tests for the nursery's genetic operators.
+//!
+//! Each test runs many seeded trials so a regression in mutation, crossover,
+//! or breeding shows up as a shifted frequency rather than a single flaky case.
+//! All RNGs are seeded, so the results are deterministic.
+#![cfg(feature = "nursery")]
+
+mod common;
+use bevy::platform::collections::HashSet;
+use bevy::prelude::*;
+use common::setup_headless_app;
+use lsystem_explorer::core::config::{LSystemConfig, MaterialSettingsMap, PropConfig};
+use lsystem_explorer::core::genotype::{PlantGenotype, SerializableMaterial};
+use lsystem_explorer::core::presets::PRESETS;
+use lsystem_explorer::ui::nursery::{
+    NurseryState, PopulationMeshCache, SelectionStrategy, poll_auto_evolve,
+};
+use lsystem_explorer::visuals::genotype_render::CachedGenotypeMesh;
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+use symbios_genetics::Genotype;
+
+const TRIALS: u64 = 200;
+
+fn fingerprint(genotype: &PlantGenotype) -> String {
+    serde_json::to_string(genotype).expect("genotype serializes")
+}
+
+/// Presets whose source round-trips through the parser, as evolvable genotypes.
+fn parseable_presets() -> Vec<PlantGenotype> {
+    PRESETS
+        .iter()
+        .map(PlantGenotype::from_preset)
+        .filter(|genotype| genotype.parse().is_some())
+        .collect()
+}
+
+/// Fraction of trials in which mutation at `rate` changed the genotype.
+fn change_frequency(genotype: &PlantGenotype, rate: f32) -> f32 {
+    let original = fingerprint(genotype);
+    let changed = (0..TRIALS)
+        .filter(|&seed| {
+            let mut mutated = genotype.clone();
+            mutated.mutate(&mut Pcg64::seed_from_u64(seed), rate);
+            fingerprint(&mutated) != original
+        })
+        .count();
+    changed as f32 / TRIALS as f32
+}
+
+#[test]
+fn test_mutation_at_rate_zero_is_noop() {
+    let genotypes = parseable_presets();
+    assert!(!genotypes.is_empty());
+    for genotype in &genotypes {
+        assert_eq!(
+            change_frequency(genotype, 0.0),
+            0.0,
+            "Rate 0 mutated {}",
+            genotype.source_code
+        );
+    }
+}
+
+#[test]
+fn test_mutation_frequency_follows_rate() {
+    let mut genotype = PlantGenotype::new(
+        "#define len 2.0\nomega: A\np1: A -> F(len) [ + A ] [ - A ]\np2: F(x) -> F(x * 1.1)".into(),
+    );
+    // Start from the canonical form that mutation writes back, with the
+    // material slot it would otherwise add, so only real mutations count
+    genotype.source_code = genotype.parse().expect("source parses").to_source();
+    genotype
+        .materials
+        .insert(0, SerializableMaterial::default());
+
+    // The seed alone is redrawn with probability `rate`, so at least that
+    // fraction of trials must differ (with slack for sampling noise).
+    let low = change_frequency(&genotype, 0.05);
+    let high = change_frequency(&genotype, 0.5);
+    assert!(low > 0.0, "Rate 0.05 never mutated");
+    assert!(
+        high >= 0.4,
+        "Rate 0.5 changed only {:.0}% of trials",
+        high * 100.0
+    );
+    assert!(
+        high > low,
+        "Higher rate should mutate more often ({} <= {})",
+        high,
+        low
+    );
+
+    // Mutated genotypes remain parseable
+    let mut rng = Pcg64::seed_from_u64(7);
+    let parseable = (0..TRIALS)
+        .filter(|_| {
+            let mut mutated = genotype.clone();
+            mutated.mutate(&mut rng, 0.5);
+            mutated.parse().is_some()
+        })
+        .count();
+    assert!(
+        parseable as f32 / TRIALS as f32 >= 0.95,
+        "Only {}/{} mutants parse",
+        parseable,
+        TRIALS
+    );
+}
+
+#[test]
+fn test_crossover_preserves_parseability() {
+    let genotypes = parseable_presets();
+    let mut rng = Pcg64::seed_from_u64(42);
+    let mut total = 0;
+    let mut parseable = 0;
+    for a in &genotypes {
+        for b in &genotypes {
+            for _ in 0..10 {
+                let offspring = a.crossover(b, &mut rng);
+                total += 1;
+                if offspring.parse().is_some() {
+                    parseable += 1;
+                }
+            }
+        }
+    }
+    assert!(total > 0);
+    assert!(
+        parseable as f32 / total as f32 >= 0.9,
+        "Only {}/{} offspring parse",
+        parseable,
+        total
+    );
+}
+
+#[test]
+fn test_breed_preserves_champions_exactly() {
+    let config = LSystemConfig::default();
+    let materials = MaterialSettingsMap::default();
+    let props = PropConfig::default();
+
+    for seed in 0..20 {
+        let mut nursery = NurseryState {
+            seed,
+            ..Default::default()
+        };
+        nursery.initialize_from_editor(&config, &materials, &props);
+        let size = nursery.population.len();
+        let champions = [2, 5, (seed as usize) % size];
+        nursery.selected = champions.into_iter().collect();
+
+        let mut expected: Vec<(String, f32)> = nursery
+            .selected
+            .iter()
+            .map(|&i| {
+                let phenotype = &nursery.population[i];
+                (fingerprint(&phenotype.genotype), phenotype.fitness)
+            })
+            .collect();
+        let generation = nursery.generation;
+
+        nursery.breed();
+
+        assert_eq!(nursery.population.len(), size);
+        assert_eq!(nursery.generation, generation + 1);
+        let count = expected.len();
+        let mut preserved: Vec<(String, f32)> = nursery.population[..count]
+            .iter()
+            .map(|phenotype| (fingerprint(&phenotype.genotype), phenotype.fitness))
+            .collect();
+        expected.sort_by(|a, b| a.0.cmp(&b.0));
+        preserved.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(preserved, expected, "Champions changed with seed {}", seed);
+        assert_eq!(
+            nursery.selected,
+            (0..count).collect(),
+            "Selection should follow the champions"
+        );
+    }
+}
+
+/// Counts how often each index is picked as a parent over many draws.
+fn parent_histogram(nursery: &NurseryState, champions: &[usize]) -> Vec<usize> {
+    let mut rng = Pcg64::seed_from_u64(1);
+    let mut counts = vec![0; nursery.population.len()];
+    for _ in 0..TRIALS * 10 {
+        counts[nursery.select_parent(champions, &mut rng)] += 1;
+    }
+    counts
+}
+
+#[test]
+fn test_selection_strategies_favor_fitness() {
+    let mut nursery = NurseryState::default();
+    nursery.initialize_from_editor(
+        &LSystemConfig::default(),
+        &MaterialSettingsMap::default(),
+        &PropConfig::default(),
+    );
+    let size = nursery.population.len();
+    for (i, phenotype) in nursery.population.iter_mut().enumerate() {
+        phenotype.fitness = i as f32;
+    }
+    let best = size - 1;
+
+    nursery.selection = SelectionStrategy::Manual;
+    let counts = parent_histogram(&nursery, &[1, 3]);
+    assert_eq!(counts[1] + counts[3], (TRIALS * 10) as usize);
+
+    // Index 0 has zero fitness and can never win the roulette
+    nursery.selection = SelectionStrategy::Roulette;
+    let counts = parent_histogram(&nursery, &[]);
+    assert_eq!(counts[0], 0);
+    assert!(counts[best] > counts[1] * 4, "{:?}", counts);
+
+    nursery.selection = SelectionStrategy::Rank;
+    let counts = parent_histogram(&nursery, &[]);
+    assert!(counts[best] > counts[0] * 4, "{:?}", counts);
+
+    // A tournament over many contestants almost always finds the best
+    nursery.selection = SelectionStrategy::Tournament;
+    nursery.tournament_size = size * 4;
+    let counts = parent_histogram(&nursery, &[]);
+    assert!(
+        counts[best] as f32 > TRIALS as f32 * 10.0 * 0.9,
+        "{:?}",
+        counts
+    );
+
+    // Breeding without champions still fills the population
+    nursery.selected.clear();
+    nursery.breed();
+    assert_eq!(nursery.population.len(), size);
+    assert!(nursery.selected.is_empty());
+}
+
+#[test]
+fn test_auto_evolve_runs_in_background() {
+    let mut app = setup_headless_app();
+    let mut nursery = NurseryState {
+        selection: SelectionStrategy::Tournament,
+        ..Default::default()
+    };
+    nursery.initialize_from_editor(
+        &LSystemConfig::default(),
+        &MaterialSettingsMap::default(),
+        &PropConfig::default(),
+    );
+    nursery.start_auto_evolve(5);
+    assert!(nursery.auto_evolve.is_running());
+    app.insert_resource(nursery);
+    app.add_systems(Update, poll_auto_evolve);
+
+    for _ in 0..500 {
+        app.update();
+        if !app
+            .world()
+            .resource::<NurseryState>()
+            .auto_evolve
+            .is_running()
+        {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+
+    let nursery = app.world().resource::<NurseryState>();
+    assert!(
+        !nursery.auto_evolve.is_running(),
+        "Auto-evolve did not finish"
+    );
+    assert_eq!(nursery.generation, 5);
+    assert_eq!(nursery.population.len(), nursery.population_size());
+    assert!(nursery.needs_3d_rebuild);
+}
+
+#[test]
+fn test_breed_with_editor_fills_unselected() {
+    let mut nursery = NurseryState::default();
+    nursery.initialize_from_editor(
+        &LSystemConfig::default(),
+        &MaterialSettingsMap::default(),
+        &PropConfig::default(),
+    );
+    nursery.selected = [0, 4].into_iter().collect();
+    let before: Vec<String> = nursery
+        .population
+        .iter()
+        .map(|p| fingerprint(&p.genotype))
+        .collect();
+
+    let editor = PlantGenotype::new("omega: A\np1: A -> F [ & A ] [ ^ A ] F A".into());
+    nursery.breed_with_editor(2, editor);
+
+    assert_eq!(nursery.population.len(), before.len());
+    for i in [0, 2, 4] {
+        assert_eq!(
+            fingerprint(&nursery.population[i].genotype),
+            before[i],
+            "Individual {} should be kept",
+            i
+        );
+    }
+    for i in (0..before.len()).filter(|i| ![0, 2, 4].contains(i)) {
+        assert!(nursery.parents.contains_key(&i));
+        assert!(nursery.population[i].genotype.parse().is_some());
+    }
+}
+
+#[test]
+fn test_mesh_cache_reuses_champion_derivations() {
+    let mut nursery = NurseryState::default();
+    nursery.initialize_from_editor(
+        &LSystemConfig::default(),
+        &MaterialSettingsMap::default(),
+        &PropConfig::default(),
+    );
+    let hashes = |nursery: &NurseryState| -> Vec<u64> {
+        nursery
+            .population
+            .iter()
+            .map(|p| p.genotype.content_hash())
+            .collect()
+    };
+
+    let mut cache = PopulationMeshCache::default();
+    let first = hashes(&nursery);
+    let missing = cache.retain_population(&first);
+    assert_eq!(missing.len(), first.iter().collect::<HashSet<_>>().len());
+    for (individual, hash) in nursery.population.iter().zip(&first) {
+        cache.derived.insert(
+            *hash,
+            CachedGenotypeMesh::new(&individual.genotype, None, 0.0, None),
+        );
+    }
+
+    nursery.selected = [1, 3].into_iter().collect();
+    nursery.breed();
+    let second = hashes(&nursery);
+    let missing = cache.retain_population(&second);
+    for (index, hash) in second.iter().enumerate().take(2) {
+        assert!(!missing.contains(hash), "Champion {} re-derived", index);
+        assert!(cache.entry(index).is_some());
+    }
+    assert!(cache.derived.len() <= second.len());
+    assert!(cache.derived.keys().all(|hash| second.contains(hash)));
+}
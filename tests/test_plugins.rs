@@ -0,0 +1,41 @@
+use bevy::prelude::*;
+use lsystem_explorer::core::config::{DerivationStatus, LSystemConfig, LSystemEngine};
+use lsystem_explorer::core::includes::IncludeLibrary;
+use lsystem_explorer::plugins::{CorePlugin, DerivationPlugin};
+
+#[test]
+fn test_core_and_derivation_plugins_embed_headless() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()));
+    app.init_asset::<Mesh>();
+    app.init_asset::<StandardMaterial>();
+    app.init_asset::<Image>();
+    // Start from an empty include library rather than the user's saved buffers
+    app.insert_resource(IncludeLibrary::empty());
+    app.add_plugins((CorePlugin, DerivationPlugin));
+
+    // Startup applies the preset; replace it with a known grammar
+    app.update();
+    let mut config = app.world_mut().resource_mut::<LSystemConfig>();
+    config.source_code = "omega: F\np1: F -> F+F".to_string();
+    config.finalization_code.clear();
+    config.iterations = 2;
+    config.recompile_requested = true;
+
+    for _ in 0..100 {
+        app.update();
+        let status = app.world().resource::<DerivationStatus>();
+        if !status.generating && app.world().resource::<LSystemEngine>().0.state.len() == 7 {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+
+    let status = app.world().resource::<DerivationStatus>();
+    assert!(
+        status.error.is_none(),
+        "Unexpected error: {:?}",
+        status.error
+    );
+    assert_eq!(app.world().resource::<LSystemEngine>().0.state.len(), 7);
+}
@@ -1,50 +1,72 @@
-use lsystem_explorer::core::presets::PRESETS;
+use bevy::mesh::VertexAttributeValues;
+use bevy::prelude::*;
+use bevy_symbios::LSystemMeshBuilder;
+use lsystem_explorer::core::presets::{LSystemPreset, PRESETS};
+use lsystem_explorer::logic::format::format_grammar;
+use lsystem_explorer::logic::generators::{GeneratorSettings, Pattern, generate};
+use lsystem_explorer::logic::wizard::{PlantTraits, synthesize};
+use lsystem_explorer::visuals::turtle::{interpret_skeleton, turtle_config_with_defaults};
+use std::time::{Duration, Instant};
 use symbios::System;
 
-#[test]
-fn test_presets_validity() {
-    for preset in PRESETS {
-        println!("Testing preset: {}", preset.name);
+/// Wall-clock budget for deriving, interpreting, and meshing one preset at its
+/// advertised iteration count (generous, since tests run unoptimized).
+const PRESET_TIME_BUDGET: Duration = Duration::from_secs(30);
 
-        let mut sys = System::new();
-        let source = preset.code;
+/// Parses a preset's source, asserting every line is accepted.
+fn parse_preset(preset: &LSystemPreset) -> System {
+    parse_source(preset.name, preset.code)
+}
 
-        // 1. Simulate Parsing
-        let mut axiom_set = false;
-        for line in source.lines() {
-            let trimmed = line.trim();
-            if trimmed.is_empty() || trimmed.starts_with("//") {
-                continue;
-            }
+/// Parses grammar source, asserting every line is accepted.
+fn parse_source(name: &str, source: &str) -> System {
+    let mut sys = System::new();
 
-            if trimmed.starts_with('#') {
-                assert!(
-                    sys.add_directive(trimmed).is_ok(),
-                    "Failed to add directive in {}",
-                    preset.name
-                );
-                continue;
-            }
+    // 1. Simulate Parsing
+    let mut axiom_set = false;
+    for line in source.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with("//") {
+            continue;
+        }
 
-            if trimmed.starts_with("omega:") {
-                let axiom_src = trimmed.trim_start_matches("omega:").trim();
-                assert!(
-                    sys.set_axiom(axiom_src).is_ok(),
-                    "Failed to set axiom in {}",
-                    preset.name
-                );
-                axiom_set = true;
-                continue;
-            }
+        if trimmed.starts_with('#') {
+            assert!(
+                sys.add_directive(trimmed).is_ok(),
+                "Failed to add directive in {}",
+                name
+            );
+            continue;
+        }
 
+        if trimmed.starts_with("omega:") {
+            let axiom_src = trimmed.trim_start_matches("omega:").trim();
             assert!(
-                sys.add_rule(trimmed).is_ok(),
-                "Failed to add rule in {}",
-                preset.name
+                sys.set_axiom(axiom_src).is_ok(),
+                "Failed to set axiom in {}",
+                name
             );
+            axiom_set = true;
+            continue;
         }
 
-        assert!(axiom_set, "Preset {} has no axiom", preset.name);
+        assert!(
+            sys.add_rule(trimmed).is_ok(),
+            "Failed to add rule in {}",
+            name
+        );
+    }
+
+    assert!(axiom_set, "Preset {} has no axiom", name);
+    sys
+}
+
+#[test]
+fn test_presets_validity() {
+    for preset in PRESETS {
+        println!("Testing preset: {}", preset.name);
+
+        let mut sys = parse_preset(preset);
 
         // 2. Simulate Derivation
         // Use a small iteration count to verify logic without burning CPU
@@ -64,3 +86,119 @@ fn test_presets_validity() {
         );
     }
 }
+
+#[test]
+fn test_presets_interpret_and_mesh() {
+    for preset in PRESETS {
+        let start = Instant::now();
+        let mut sys = parse_preset(preset);
+        sys.derive(preset.iterations)
+            .unwrap_or_else(|e| panic!("Derivation failed for {}: {}", preset.name, e));
+
+        let skeleton = interpret_skeleton(
+            &sys,
+            turtle_config_with_defaults(
+                &sys,
+                preset.step,
+                preset.angle,
+                preset.width,
+                preset.tropism,
+                preset.elasticity,
+            ),
+        );
+        let buckets = LSystemMeshBuilder::new().build(&skeleton);
+
+        let mut vertex_count = 0;
+        let mut min = Vec3::splat(f32::INFINITY);
+        let mut max = Vec3::splat(f32::NEG_INFINITY);
+        for (material_id, mesh) in &buckets {
+            let Some(VertexAttributeValues::Float32x3(positions)) =
+                mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+            else {
+                panic!("{}: material {} has no positions", preset.name, material_id);
+            };
+            for p in positions {
+                let p = Vec3::from_array(*p);
+                assert!(
+                    p.is_finite(),
+                    "{}: non-finite vertex {:?} in material {}",
+                    preset.name,
+                    p,
+                    material_id
+                );
+                min = min.min(p);
+                max = max.max(p);
+            }
+            vertex_count += positions.len();
+        }
+        for prop in &skeleton.props {
+            assert!(
+                prop.position.is_finite(),
+                "{}: non-finite prop position",
+                preset.name
+            );
+        }
+
+        assert!(
+            vertex_count > 0,
+            "Preset {} produced no geometry",
+            preset.name
+        );
+        assert!(
+            min.is_finite() && max.is_finite() && (max - min).length() > 0.0,
+            "Preset {} has degenerate bounds {:?}..{:?}",
+            preset.name,
+            min,
+            max
+        );
+        let elapsed = start.elapsed();
+        assert!(
+            elapsed < PRESET_TIME_BUDGET,
+            "Preset {} took {:?} (budget {:?})",
+            preset.name,
+            elapsed,
+            PRESET_TIME_BUDGET
+        );
+    }
+}
+
+#[test]
+fn test_generated_grammars_derive() {
+    for pattern in Pattern::ALL {
+        let generated = generate(&GeneratorSettings {
+            pattern,
+            count: *pattern.count_range().start(),
+            ..Default::default()
+        });
+        let mut sys = parse_source(pattern.name(), &generated.source);
+        sys.derive(generated.iterations)
+            .unwrap_or_else(|e| panic!("Derivation failed for {}: {}", pattern.name(), e));
+        assert!(sys.state.len() > 1, "{} did not develop", pattern.name());
+    }
+}
+
+#[test]
+fn test_wizard_grammar_derives() {
+    let grammar = synthesize(&PlantTraits::default());
+    // Finalization rules only rewrite leaf buds, so one pass over both is fine
+    let source = format!("{}\n{}", grammar.source, grammar.finalization);
+    let mut sys = parse_source("Plant wizard", &source);
+    sys.derive(grammar.iterations)
+        .unwrap_or_else(|e| panic!("Derivation failed for the wizard grammar: {}", e));
+    assert!(sys.state.len() > 1);
+}
+
+#[test]
+fn test_presets_survive_formatting() {
+    for preset in PRESETS {
+        let formatted = format_grammar(preset.code)
+            .unwrap_or_else(|e| panic!("Formatting failed for {}: {}", preset.name, e));
+        assert_eq!(
+            format_grammar(&formatted).unwrap(),
+            formatted,
+            "Formatting {} twice changed it",
+            preset.name
+        );
+        parse_source(preset.name, &formatted);
+    }
+}
@@ -1,8 +1,19 @@
 mod common;
+use bevy::mesh::VertexAttributeValues;
 use bevy::prelude::*;
 use common::setup_headless_app;
-use lsystem_explorer::core::config::{DirtyFlags, LSystemEngine};
-use lsystem_explorer::visuals::turtle::{LSystemMeshTag, render_turtle};
+use lsystem_explorer::core::config::{
+    DirtyFlags, GradientMode, LSystemConfig, LSystemEngine, MaterialGradient, MaterialGradients,
+};
+use lsystem_explorer::core::includes::IncludeLibrary;
+use lsystem_explorer::logic::taper::taper_width_changes;
+#[cfg(feature = "nursery")]
+use lsystem_explorer::ui::nursery::NurseryState;
+use lsystem_explorer::visuals::mesher::Shading;
+use lsystem_explorer::visuals::turtle::{
+    LSystemMeshTag, LSystemPropTag, PropSpawnQueue, TurtleRenderState, drain_prop_queue,
+    interpret_skeleton, render_turtle, turtle_config_with_defaults,
+};
 use symbios::System;
 
 #[test]
@@ -45,3 +56,803 @@ fn test_mesh_generation() {
     let count = mesh.count_vertices();
     assert!(count > 0, "Generated mesh should have vertices");
 }
+
+#[test]
+fn test_material_gradient_tints_vertex_colors() {
+    let mut app = setup_headless_app();
+    app.world_mut()
+        .resource_mut::<MaterialGradients>()
+        .gradients
+        .insert(
+            0,
+            MaterialGradient {
+                mode: GradientMode::RootDistance,
+                tip_tint: [0.0, 0.0, 0.0],
+            },
+        );
+
+    let mut sys = System::new();
+    sys.set_axiom("F(10) F(10)").unwrap();
+    sys.derive(0).unwrap();
+    app.world_mut().resource_mut::<LSystemEngine>().0 = sys;
+    app.world_mut().resource_mut::<DirtyFlags>().geometry = true;
+    app.add_systems(Update, render_turtle);
+    app.update();
+
+    let mut query = app
+        .world_mut()
+        .query_filtered::<&Mesh3d, With<LSystemMeshTag>>();
+    let handle = query
+        .single(app.world())
+        .expect("one mesh entity")
+        .0
+        .clone();
+    let mesh = app.world().resource::<Assets<Mesh>>().get(&handle).unwrap();
+    let Some(VertexAttributeValues::Float32x4(colors)) = mesh.attribute(Mesh::ATTRIBUTE_COLOR)
+    else {
+        panic!("Mesh should have vertex colors");
+    };
+    let brightness: Vec<f32> = colors.iter().map(|c| c[0]).collect();
+    let darkest = brightness.iter().copied().fold(f32::MAX, f32::min);
+    let brightest = brightness.iter().copied().fold(f32::MIN, f32::max);
+    assert!(brightest > 0.9, "The root keeps its color");
+    assert!(darkest < 0.1, "The tip reaches the tip tint");
+}
+
+#[test]
+fn test_render_statistics() {
+    let mut app = setup_headless_app();
+
+    let mut sys = System::new();
+    sys.set_axiom("F(10) [ + F(5) ] F(10)").unwrap();
+    sys.derive(0).unwrap();
+
+    app.world_mut().resource_mut::<LSystemEngine>().0 = sys;
+    app.world_mut().resource_mut::<DirtyFlags>().geometry = true;
+
+    app.add_systems(Update, render_turtle);
+    app.update();
+
+    let state = app.world().resource::<TurtleRenderState>();
+    assert!(state.total_triangles > 0, "Triangles should be counted");
+    assert!(state.strand_count >= 2, "Branch should start a new strand");
+    assert_eq!(state.prop_count, 0);
+
+    let material_verts: usize = state.material_stats.values().map(|s| s.vertices).sum();
+    assert_eq!(
+        material_verts, state.total_vertices,
+        "Without props, per-material vertices should add up to the total"
+    );
+
+    let (min, max) = state.bounds.expect("Bounds should be computed");
+    assert!(max.y - min.y >= 19.0, "Bounds should span the trunk height");
+}
+
+#[test]
+fn test_interpreter_cache_follows_new_derivations() {
+    let mut app = setup_headless_app();
+    app.add_systems(Update, render_turtle);
+    let height = |app: &mut App, axiom: Option<&str>| {
+        if let Some(axiom) = axiom {
+            let mut sys = System::new();
+            sys.set_axiom(axiom).unwrap();
+            sys.derive(0).unwrap();
+            app.world_mut().resource_mut::<LSystemEngine>().0 = sys;
+        }
+        app.world_mut().resource_mut::<DirtyFlags>().geometry = true;
+        app.update();
+        let (min, max) = app
+            .world()
+            .resource::<TurtleRenderState>()
+            .bounds
+            .expect("Bounds should be computed");
+        max.y - min.y
+    };
+
+    let first = height(&mut app, Some("F(10)"));
+    assert!((9.5..10.5).contains(&first), "got {}", first);
+    // Rebuilding without a new derivation reuses the populated interpreter
+    assert_eq!(height(&mut app, None), first);
+    // `A` takes the symbol ID `F` had, so a stale op table would draw it
+    let second = height(&mut app, Some("A F(3)"));
+    assert!((2.5..3.5).contains(&second), "got {}", second);
+}
+
+#[test]
+fn test_strand_pool_only_fills_with_sections() {
+    use lsystem_explorer::core::config::SubSystemEngines;
+    use lsystem_explorer::logic::derivation::derive_blocking;
+    use lsystem_explorer::logic::strand_pool::StrandPool;
+
+    let mut app = setup_headless_app();
+    app.add_systems(Update, render_turtle);
+    let render = |app: &mut App, source: &str| {
+        let derived = derive_blocking(source, "", 1, 0).unwrap();
+        app.world_mut().resource_mut::<LSystemEngine>().0 = derived.system;
+        app.world_mut().resource_mut::<SubSystemEngines>().0 = derived.subsystems;
+        app.world_mut().resource_mut::<DirtyFlags>().geometry = true;
+        app.update();
+        app.world().resource::<StrandPool>().len()
+    };
+
+    // Nothing would draw from the pool, so a plain plant does not fill it
+    assert_eq!(render(&mut app, "omega: F [ + F ] F"), 0);
+    let pooled = render(
+        &mut app,
+        "omega: F [ + F @tip ] F\n#system tip 1\nomega: F\np1: F -> F F",
+    );
+    assert!(pooled > 0);
+}
+
+#[test]
+fn test_weld_vertices_reduces_viewport_mesh() {
+    fn vertices(weld: bool) -> (usize, usize) {
+        let mut app = setup_headless_app();
+        app.world_mut()
+            .resource_mut::<LSystemConfig>()
+            .weld_vertices = weld;
+        let mut sys = System::new();
+        sys.set_axiom("F(10) F(10) [ + F(5) ] F(10)").unwrap();
+        sys.derive(0).unwrap();
+        app.world_mut().resource_mut::<LSystemEngine>().0 = sys;
+        app.world_mut().resource_mut::<DirtyFlags>().geometry = true;
+        app.add_systems(Update, render_turtle);
+        app.update();
+        let state = app.world().resource::<TurtleRenderState>();
+        (state.total_vertices, state.total_triangles)
+    }
+
+    let (plain_vertices, plain_triangles) = vertices(false);
+    let (welded_vertices, welded_triangles) = vertices(true);
+    assert!(
+        welded_vertices < plain_vertices,
+        "Welding should merge ring vertices"
+    );
+    assert!(welded_triangles <= plain_triangles);
+    assert!(welded_triangles > 0);
+}
+
+#[test]
+fn test_flat_shading_gives_one_normal_per_triangle() {
+    let mut app = setup_headless_app();
+    app.world_mut().resource_mut::<LSystemConfig>().shading = Shading::Flat;
+    let mut sys = System::new();
+    sys.set_axiom("F(10) + F(10) + F(10)").unwrap();
+    sys.derive(0).unwrap();
+    app.world_mut().resource_mut::<LSystemEngine>().0 = sys;
+    app.world_mut().resource_mut::<DirtyFlags>().geometry = true;
+    app.add_systems(Update, render_turtle);
+    app.update();
+
+    let mut query = app
+        .world_mut()
+        .query_filtered::<&Mesh3d, With<LSystemMeshTag>>();
+    let handle = query.single(app.world()).unwrap().0.clone();
+    let meshes = app.world().resource::<Assets<Mesh>>();
+    let mesh = meshes.get(&handle).unwrap();
+    let normals = mesh
+        .attribute(Mesh::ATTRIBUTE_NORMAL)
+        .and_then(VertexAttributeValues::as_float3)
+        .unwrap();
+    let indices: Vec<usize> = mesh.indices().unwrap().iter().collect();
+    assert!(!indices.is_empty());
+    for triangle in indices.chunks_exact(3) {
+        let n = Vec3::from_array(normals[triangle[0]]);
+        for &corner in &triangle[1..] {
+            assert!(
+                Vec3::from_array(normals[corner]).abs_diff_eq(n, 1e-4),
+                "Flat shading should not interpolate normals across a triangle"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_width_change_updates_mesh_in_place() {
+    let mut app = setup_headless_app();
+    app.add_systems(Update, render_turtle);
+
+    fn render(app: &mut App, axiom: &str) -> (Entity, Handle<Mesh>, (Vec3, Vec3)) {
+        let mut sys = System::new();
+        sys.set_axiom(axiom).unwrap();
+        sys.derive(0).unwrap();
+        app.world_mut().resource_mut::<LSystemEngine>().0 = sys;
+        app.world_mut().resource_mut::<DirtyFlags>().geometry = true;
+        app.update();
+        let mut query = app
+            .world_mut()
+            .query_filtered::<(Entity, &Mesh3d), With<LSystemMeshTag>>();
+        let (entity, handle) = query.single(app.world()).expect("one mesh entity");
+        let handle = handle.0.clone();
+        let extent = app.world().resource::<TurtleRenderState>().bounds.unwrap();
+        (entity, handle, extent)
+    }
+
+    let (entity, handle, thin) = render(&mut app, "!(1) F(10) [ + F(5) ]");
+    let revision = app.world().resource::<TurtleRenderState>().mesh_revision;
+    let (same_entity, same_handle, thick) = render(&mut app, "!(3) F(10) [ + F(5) ]");
+    assert_eq!(entity, same_entity, "Width change should keep the entity");
+    assert_eq!(
+        handle, same_handle,
+        "Width change should keep the mesh asset"
+    );
+    assert!(
+        thick.1.x - thick.0.x > thin.1.x - thin.0.x,
+        "Mesh should be updated"
+    );
+    assert!(app.world().resource::<TurtleRenderState>().mesh_revision > revision);
+
+    // A topology change replaces the mesh
+    let (new_entity, _, _) = render(&mut app, "!(3) F(10) [ + F(5) ] [ - F(5) ]");
+    assert_ne!(entity, new_entity);
+}
+
+#[test]
+fn test_props_spawn_within_frame_budget() {
+    let mut app = setup_headless_app();
+    #[cfg(feature = "nursery")]
+    app.init_resource::<NurseryState>();
+    app.world_mut().resource_mut::<PropSpawnQueue>().budget = 2;
+
+    let mut sys = System::new();
+    sys.set_axiom("F(1) ~(1) F(1) ~(1) F(1) ~(1) F(1) ~(1) F(1) ~(1)")
+        .unwrap();
+    sys.derive(0).unwrap();
+    app.world_mut().resource_mut::<LSystemEngine>().0 = sys;
+    app.world_mut().resource_mut::<DirtyFlags>().geometry = true;
+    app.add_systems(Update, (render_turtle, drain_prop_queue).chain());
+
+    let mut counts = Vec::new();
+    for _ in 0..4 {
+        app.update();
+        let mut query = app
+            .world_mut()
+            .query_filtered::<Entity, With<LSystemPropTag>>();
+        counts.push(query.iter(app.world()).count());
+    }
+    assert_eq!(app.world().resource::<TurtleRenderState>().prop_count, 5);
+    assert_eq!(counts, vec![2, 4, 5, 5]);
+
+    // A rebuild despawns the old props before spawning the new ones
+    app.world_mut().resource_mut::<DirtyFlags>().geometry = true;
+    app.update();
+    assert_eq!(app.world().resource::<PropSpawnQueue>().pending(), 8);
+    for _ in 0..5 {
+        app.update();
+    }
+    let mut query = app
+        .world_mut()
+        .query_filtered::<Entity, With<LSystemPropTag>>();
+    assert_eq!(query.iter(app.world()).count(), 5);
+    assert_eq!(app.world().resource::<PropSpawnQueue>().pending(), 0);
+}
+
+#[test]
+fn test_width_change_tapers_along_segment() {
+    let mut sys = System::new();
+    sys.set_axiom("F(1) F(1) !(3) F(1)").unwrap();
+    sys.derive(0).unwrap();
+    let mut skeleton = interpret_skeleton(
+        &sys,
+        turtle_config_with_defaults(&sys, 1.0, 30.0, 1.0, None, 0.0),
+    );
+    taper_width_changes(&mut skeleton);
+
+    let strand = &skeleton.strands[0];
+    for pair in strand.windows(2) {
+        assert!(
+            pair[0].position.distance(pair[1].position) > 1e-4,
+            "No zero-length step should remain"
+        );
+    }
+    let first = strand.first().unwrap().radius;
+    let last = strand.last().unwrap().radius;
+    assert!(last > first, "The last segment should widen toward its end");
+}
+
+#[test]
+fn test_square_cross_section_sweeps_flat_faces() {
+    use lsystem_explorer::visuals::mesher::{CrossSection, SkeletonMesher};
+
+    let mut sys = System::new();
+    sys.set_axiom("F(1) + F(1)").unwrap();
+    sys.derive(0).unwrap();
+    let skeleton = interpret_skeleton(
+        &sys,
+        turtle_config_with_defaults(&sys, 1.0, 30.0, 0.2, None, 0.0),
+    );
+    let points: usize = skeleton.strands.iter().map(Vec::len).sum();
+
+    let mut profiles = bevy::platform::collections::HashMap::new();
+    profiles.insert(0, CrossSection::NGon(4));
+    let buckets = SkeletonMesher::new(8)
+        .with_profiles(profiles)
+        .build(&skeleton);
+    let mesh = &buckets[&0];
+    // Two vertices per side and point, plus a center and 4 rim vertices per cap
+    assert_eq!(
+        mesh.count_vertices(),
+        8 * points + 10 * skeleton.strands.len()
+    );
+    assert!(mesh.attribute(Mesh::ATTRIBUTE_COLOR).is_some());
+
+    let round = SkeletonMesher::new(8).build(&skeleton);
+    assert_ne!(round[&0].count_vertices(), mesh.count_vertices());
+}
+
+#[test]
+fn test_chunked_meshing_matches_and_cancels() {
+    use bevy_symbios::LSystemMeshBuilder;
+    use lsystem_explorer::visuals::mesher::SkeletonMesher;
+    use lsystem_explorer::visuals::turtle::mesh_skeleton_chunked;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    // Many short branches so the skeleton spans several chunks
+    let mut sys = System::new();
+    sys.set_axiom("A").unwrap();
+    sys.add_rule("p1: A -> F [ + F ] [ - F ] A").unwrap();
+    sys.derive(400).unwrap();
+
+    let skeleton = interpret_skeleton(
+        &sys,
+        turtle_config_with_defaults(&sys, 1.0, 30.0, 0.1, None, 0.0),
+    );
+    assert!(skeleton.strands.len() > 300);
+
+    let direct: usize = LSystemMeshBuilder::new()
+        .with_resolution(8)
+        .build(&skeleton)
+        .values()
+        .map(|mesh| mesh.count_vertices())
+        .sum();
+
+    let running = AtomicBool::new(true);
+    let progress = AtomicUsize::new(0);
+    let chunked = mesh_skeleton_chunked(&skeleton, &SkeletonMesher::new(8), &running, &progress)
+        .expect("Meshing should complete when not cancelled");
+    let chunked_vertices: usize = chunked.values().map(|mesh| mesh.count_vertices()).sum();
+    assert_eq!(chunked_vertices, direct);
+    assert_eq!(progress.load(Ordering::Relaxed), skeleton.strands.len());
+
+    let cancelled = AtomicBool::new(false);
+    assert!(
+        mesh_skeleton_chunked(
+            &skeleton,
+            &SkeletonMesher::new(8),
+            &cancelled,
+            &AtomicUsize::new(0)
+        )
+        .is_none()
+    );
+}
+
+#[test]
+fn test_plant_detail_reduces_geometry() {
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy_symbios::materials::ProceduralTextures;
+    use lsystem_explorer::core::config::PropConfig;
+    use lsystem_explorer::core::genotype::PlantGenotype;
+    use lsystem_explorer::visuals::assets::PropMeshAssets;
+    use lsystem_explorer::visuals::genotype_render::{
+        CachedGenotypeMesh, PlantDetail, derive_genotype, spawn_genotype_plant,
+    };
+
+    #[derive(Component, Clone)]
+    struct Branch;
+    #[derive(Component, Clone)]
+    struct Prop;
+
+    /// Spawns the plant with `detail`, returning (branch vertices, props).
+    fn spawn_with(app: &mut App, detail: PlantDetail) -> (usize, usize) {
+        let genotype = PlantGenotype::new("omega: F(10) ~(1) F(10) ~(1)".to_string());
+        let derived = derive_genotype(&genotype, &IncludeLibrary::empty());
+        assert!(derived.is_some());
+        let cached = CachedGenotypeMesh::new(&genotype, derived, 0.0, None);
+        let world = app.world_mut();
+        world
+            .run_system_once(
+                move |mut commands: Commands,
+                      prop_config: Res<PropConfig>,
+                      mut meshes: ResMut<Assets<Mesh>>,
+                      mut materials: ResMut<Assets<StandardMaterial>>,
+                      proc_textures: Res<ProceduralTextures>,
+                      prop_assets: Res<PropMeshAssets>| {
+                    spawn_genotype_plant(
+                        &mut commands,
+                        &cached,
+                        Vec3::ZERO,
+                        detail,
+                        &prop_config,
+                        &mut meshes,
+                        &mut materials,
+                        &proc_textures,
+                        &prop_assets,
+                        Branch,
+                        Prop,
+                    );
+                },
+            )
+            .unwrap();
+
+        let mut branches = world.query_filtered::<(Entity, &Mesh3d), With<Branch>>();
+        let spawned: Vec<(Entity, Handle<Mesh>)> = branches
+            .iter(world)
+            .map(|(entity, mesh)| (entity, mesh.0.clone()))
+            .collect();
+        let vertices = spawned
+            .iter()
+            .map(|(_, handle)| world.resource::<Assets<Mesh>>().get(handle).unwrap())
+            .map(|mesh| mesh.count_vertices())
+            .sum();
+        let mut props = world.query_filtered::<Entity, With<Prop>>();
+        let prop_entities: Vec<Entity> = props.iter(world).collect();
+        for entity in spawned
+            .iter()
+            .map(|(e, _)| *e)
+            .chain(prop_entities.iter().copied())
+        {
+            world.despawn(entity);
+        }
+        (vertices, prop_entities.len())
+    }
+
+    let mut app = setup_headless_app();
+    let (full_vertices, full_props) = spawn_with(&mut app, PlantDetail::full(16));
+    let (lod_vertices, lod_props) = spawn_with(
+        &mut app,
+        PlantDetail {
+            mesh_resolution: 4,
+            min_prop_size: 1000.0,
+        },
+    );
+    assert_eq!(full_props, 2);
+    assert_eq!(lod_props, 0, "Props below the size threshold are culled");
+    assert!(lod_vertices < full_vertices);
+}
+
+#[test]
+fn test_genotype_plants_graft_sections() {
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy_symbios::materials::ProceduralTextures;
+    use lsystem_explorer::core::config::PropConfig;
+    use lsystem_explorer::core::genotype::PlantGenotype;
+    use lsystem_explorer::visuals::assets::PropMeshAssets;
+    use lsystem_explorer::visuals::genotype_render::{
+        CachedGenotypeMesh, PlantDetail, build_plant_assets, derive_genotype,
+    };
+
+    /// Height of the garden, scatter, or thumbnail plant of `source`.
+    fn height(app: &mut App, source: &str) -> f32 {
+        let mut genotype = PlantGenotype::new(source.to_string());
+        genotype.iterations = 1;
+        let derived = derive_genotype(&genotype, &IncludeLibrary::empty());
+        let cached = CachedGenotypeMesh::new(&genotype, derived, 0.0, None);
+        let (min, max) = app
+            .world_mut()
+            .run_system_once(
+                move |prop_config: Res<PropConfig>,
+                      mut meshes: ResMut<Assets<Mesh>>,
+                      mut materials: ResMut<Assets<StandardMaterial>>,
+                      proc_textures: Res<ProceduralTextures>,
+                      prop_assets: Res<PropMeshAssets>| {
+                    build_plant_assets(
+                        &cached,
+                        PlantDetail::full(8),
+                        &prop_config,
+                        &mut meshes,
+                        &mut materials,
+                        &proc_textures,
+                        &prop_assets,
+                    )
+                    .and_then(|assets| assets.bounds)
+                },
+            )
+            .unwrap()
+            .expect("the plant derives");
+        max.y - min.y
+    }
+
+    let mut app = setup_headless_app();
+    let plain = height(&mut app, "omega: F");
+    let grafted = height(
+        &mut app,
+        "omega: F @tip\n#system tip 1\nomega: F\np1: F -> F F",
+    );
+    assert!(
+        grafted > plain + 1.5,
+        "the section's two segments sit on the tip: {} vs {}",
+        grafted,
+        plain
+    );
+}
+
+#[test]
+fn test_grammar_constants_override_turtle_defaults() {
+    use lsystem_explorer::visuals::turtle::turtle_config_for;
+
+    let mut sys = System::new();
+    sys.add_directive("#define step 2").unwrap();
+    sys.set_axiom("F").unwrap();
+    sys.derive(0).unwrap();
+
+    let config = turtle_config_with_defaults(&sys, 1.0, 45.0, 0.3, None, 0.0);
+    assert_eq!(config.default_step, 2.0, "the grammar's step wins");
+    assert!((config.default_angle - 45f32.to_radians()).abs() < 1e-6);
+    assert_eq!(config.initial_width, 0.3);
+
+    // The editor path resolves to the same settings as the nursery and export
+    let editor = LSystemConfig {
+        step_size: 1.0,
+        default_angle: 45.0,
+        default_width: 0.3,
+        tropism: None,
+        elasticity: 0.0,
+        ..default()
+    };
+    let from_editor = turtle_config_for(&sys, &editor);
+    assert_eq!(from_editor.default_step, config.default_step);
+    assert_eq!(from_editor.default_angle, config.default_angle);
+
+    let skeleton = interpret_skeleton(&sys, config);
+    let strand = &skeleton.strands[0];
+    let length = strand
+        .first()
+        .unwrap()
+        .position
+        .distance(strand.last().unwrap().position);
+    assert!((length - 2.0).abs() < 1e-4);
+}
+
+#[test]
+fn test_roll_to_vertical_ignores_prior_roll() {
+    // Orientation at the tip of the single strand
+    let tip = |axiom: &str| {
+        let mut sys = System::new();
+        sys.set_axiom(axiom).unwrap();
+        sys.derive(0).unwrap();
+        let config = turtle_config_with_defaults(&sys, 1.0, 30.0, 0.1, None, 0.0);
+        let skeleton = interpret_skeleton(&sys, config);
+        skeleton.strands[0].last().unwrap().rotation
+    };
+
+    // `$` rolls the left vector horizontal, whatever the turtle's roll was
+    let golden = tip("F & F $ F");
+    for axiom in [
+        "F & F / $ F",
+        "F & F / / / $ F",
+        "F & F \\ $ F",
+        "F & F $ $ F",
+    ] {
+        let rotation = tip(axiom);
+        assert!(
+            rotation.angle_between(golden) < 1e-4,
+            "{} ends at {:?}, expected {:?}",
+            axiom,
+            rotation,
+            golden
+        );
+    }
+    assert!(
+        tip("F & F / F").angle_between(tip("F & F F")) > 1e-3,
+        "without `$` the roll persists"
+    );
+}
+
+#[test]
+fn test_editor_and_nursery_skeletons_match() {
+    use lsystem_explorer::core::config::split_source_code;
+    use lsystem_explorer::core::genotype::PlantGenotype;
+    use lsystem_explorer::core::presets::PRESETS;
+    use lsystem_explorer::logic::derivation::derive_blocking;
+    use lsystem_explorer::visuals::genotype_render::derive_genotype;
+    use lsystem_explorer::visuals::turtle::turtle_config_for;
+
+    // Golden parity for every preset rolling to vertical with `$`: the editor
+    // and the nursery must build the same skeleton from the same plant
+    let presets: Vec<_> = PRESETS.iter().filter(|p| p.code.contains('$')).collect();
+    assert!(!presets.is_empty());
+    for preset in presets {
+        let genotype = PlantGenotype::from_preset(preset);
+
+        let (growth, finalization) = split_source_code(preset.code);
+        let editor_sys = derive_blocking(&growth, &finalization, preset.iterations, genotype.seed)
+            .unwrap_or_else(|e| panic!("{}: {}", preset.name, e))
+            .system;
+        let editor_config = LSystemConfig {
+            step_size: preset.step,
+            default_angle: preset.angle,
+            default_width: preset.width,
+            tropism: preset.tropism,
+            elasticity: preset.elasticity,
+            ..default()
+        };
+        let editor =
+            interpret_skeleton(&editor_sys, turtle_config_for(&editor_sys, &editor_config));
+
+        let nursery_sys = derive_genotype(&genotype, &IncludeLibrary::empty())
+            .unwrap()
+            .system;
+        let nursery = interpret_skeleton(
+            &nursery_sys,
+            turtle_config_with_defaults(
+                &nursery_sys,
+                genotype.step,
+                genotype.angle,
+                genotype.width,
+                genotype.tropism.map(Vec3::from_array),
+                genotype.elasticity,
+            ),
+        );
+
+        assert_eq!(
+            editor.strands.len(),
+            nursery.strands.len(),
+            "{}",
+            preset.name
+        );
+        for (a, b) in editor.strands.iter().zip(&nursery.strands) {
+            assert_eq!(a.len(), b.len(), "{}", preset.name);
+            for (p, q) in a.iter().zip(b) {
+                assert!(
+                    p.position.distance(q.position) < 1e-4
+                        && p.rotation.dot(q.rotation).abs() > 1.0 - 1e-6,
+                    "{}: editor point {:?} differs from nursery point {:?}",
+                    preset.name,
+                    p.position,
+                    q.position
+                );
+            }
+        }
+        assert_eq!(editor.props.len(), nursery.props.len(), "{}", preset.name);
+    }
+}
+
+#[test]
+fn test_timed_genotype_matches_editor() {
+    use lsystem_explorer::core::genotype::PlantGenotype;
+    use lsystem_explorer::logic::derivation::derive_blocking;
+    use lsystem_explorer::logic::timed;
+    use lsystem_explorer::visuals::genotype_render::derive_genotype;
+
+    let mut genotype =
+        PlantGenotype::new("#timed A 1\n#tick 0.5\nomega: A\np1: A -> F(1)[+A]A".to_string())
+            .with_finalization("A -> F(age)".to_string());
+    genotype.clock = 2.5;
+
+    let (source, finalization, iterations) = timed::resolve(
+        &genotype.source_code,
+        &genotype.finalization_code,
+        genotype.iterations,
+        genotype.clock,
+    )
+    .unwrap();
+    assert_eq!(iterations, 5);
+    let editor = derive_blocking(&source, &finalization, iterations, genotype.seed)
+        .unwrap()
+        .system;
+    let nursery = derive_genotype(&genotype, &IncludeLibrary::empty())
+        .expect("timed grammars derive")
+        .system;
+
+    let skeleton = |sys: &System| {
+        interpret_skeleton(
+            sys,
+            turtle_config_with_defaults(sys, 1.0, 25.0, 0.1, None, 0.0),
+        )
+    };
+    let (editor, nursery) = (skeleton(&editor), skeleton(&nursery));
+    assert!(editor.strands.len() > 1, "the plant branches as it ages");
+    assert_eq!(editor.strands.len(), nursery.strands.len());
+    for (a, b) in editor
+        .strands
+        .iter()
+        .flatten()
+        .zip(nursery.strands.iter().flatten())
+    {
+        assert!(a.position.distance(b.position) < 1e-4);
+    }
+}
+
+#[test]
+fn test_derive_and_mesh_headless() {
+    use lsystem_explorer::visuals::headless::{MeshParams, derive_and_mesh};
+
+    let params = MeshParams {
+        iterations: 2,
+        ..default()
+    };
+    let meshes = derive_and_mesh("omega: F\np1: F -> F[+F],(1)F", &params).unwrap();
+    let ids: Vec<u8> = meshes.iter().map(|(id, _)| *id).collect();
+    assert_eq!(ids, [0, 1], "one mesh per material, in order");
+    assert!(meshes.iter().all(|(_, mesh)| mesh.count_vertices() > 0));
+
+    // #system sections are grafted like in the viewport
+    let grafted = derive_and_mesh(
+        "omega: F @tip\n#system tip 1\nomega: F\np1: F -> F F",
+        &params,
+    )
+    .unwrap();
+    let plain = derive_and_mesh("omega: F", &params).unwrap();
+    assert!(grafted[0].1.count_vertices() > plain[0].1.count_vertices());
+
+    let error = derive_and_mesh("omega: F\np1: F(x -> F", &params).unwrap_err();
+    assert!(error.starts_with("Line 2"), "got {}", error);
+}
+
+#[test]
+fn test_garden_plants_spawn_at_their_placements() {
+    use lsystem_explorer::core::garden::Garden;
+    use lsystem_explorer::core::genotype::PlantGenotype;
+    use lsystem_explorer::visuals::garden::{
+        GardenPart, GardenPlantRoot, GardenRender, poll_garden_derivations, sync_garden,
+    };
+
+    let mut app = setup_headless_app();
+    app.init_resource::<GardenRender>();
+    app.add_systems(Update, (sync_garden, poll_garden_derivations).chain());
+
+    let genotype = PlantGenotype::new("omega: F F".into());
+    let mut garden = app.world_mut().resource_mut::<Garden>();
+    garden.enabled = true;
+    let first = garden.add("First".into(), genotype.clone());
+    let second = garden.add("Second".into(), genotype);
+    let plant = garden.get_mut(second).unwrap();
+    plant.yaw = 90.0;
+    plant.scale = 2.0;
+
+    let mut roots = 0;
+    for _ in 0..100 {
+        app.update();
+        let mut query = app.world_mut().query::<&GardenPlantRoot>();
+        roots = query.iter(app.world()).count();
+        if roots == 2 {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    assert_eq!(roots, 2);
+
+    let mut query = app.world_mut().query::<(&GardenPlantRoot, &Transform)>();
+    for (root, transform) in query.iter(app.world()) {
+        let plant = app.world().resource::<Garden>().get(root.0).unwrap();
+        assert_eq!(*transform, plant.transform());
+    }
+    let mut parts = app
+        .world_mut()
+        .query_filtered::<&ChildOf, With<GardenPart>>();
+    assert!(parts.iter(app.world()).count() >= 2);
+
+    // Removing a plant despawns its root and meshes
+    app.world_mut().resource_mut::<Garden>().remove(first);
+    app.update();
+    let mut query = app.world_mut().query::<&GardenPlantRoot>();
+    let remaining: Vec<u64> = query.iter(app.world()).map(|root| root.0).collect();
+    assert_eq!(remaining, vec![second]);
+}
+
+#[test]
+fn test_intersection_check_finds_crossing_branch() {
+    use lsystem_explorer::visuals::intersections::find_intersections;
+
+    let skeleton_of = |axiom: &str| {
+        let mut sys = System::new();
+        sys.set_axiom(axiom).unwrap();
+        sys.derive(0).unwrap();
+        interpret_skeleton(
+            &sys,
+            turtle_config_with_defaults(&sys, 1.0, 90.0, 0.1, None, 0.0),
+        )
+    };
+
+    // The last segment doubles back across the first one
+    let report = find_intersections(&skeleton_of("F(2) + F(1) + F(1) + F(2)"));
+    assert_eq!(report.capsules.len(), 4);
+    assert_eq!(report.pairs, 1);
+    assert_eq!(report.intersecting_segments(), 2);
+    assert!(report.max_depth > 0.0);
+    assert_eq!(report.contacts.len(), 1);
+
+    // Touching neighbours and a closed square are not intersections
+    let report = find_intersections(&skeleton_of("F + F + F + F [+ F] F"));
+    assert_eq!(report.pairs, 0);
+}
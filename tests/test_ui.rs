@@ -0,0 +1,229 @@
+//! Headless egui tests for the editor and nursery panels.
+//!
+//! Each test renders a panel with `egui_kittest`, clicks widgets by label, and
+//! asserts that the expected resource state changed.
+
+use bevy_egui::egui;
+use egui_kittest::Harness;
+use egui_kittest::kittest::Queryable;
+use lsystem_explorer::core::config::{
+    LSystemConfig, MaterialSettingsMap, PropConfig, split_source_code,
+};
+use lsystem_explorer::core::herbarium::Herbarium;
+use lsystem_explorer::core::presets::PRESETS;
+use lsystem_explorer::ui::editor::{apply_preset_to_editor, iterations_ui, preset_picker_ui};
+#[cfg(feature = "nursery")]
+use lsystem_explorer::ui::nursery::{NurseryMode, NurseryState, nursery_ui};
+use lsystem_explorer::ui::preset_browser::{PresetBrowser, PresetPick, preset_browser_ui};
+use lsystem_explorer::visuals::thumbnail::ThumbnailRenderer;
+
+/// The resources the editor panels read and write.
+struct EditorState {
+    config: LSystemConfig,
+    materials: MaterialSettingsMap,
+    props: PropConfig,
+    #[cfg(feature = "nursery")]
+    nursery: NurseryState,
+    browser: PresetBrowser,
+    thumbnails: ThumbnailRenderer,
+    herbarium: Herbarium,
+}
+
+impl Default for EditorState {
+    fn default() -> Self {
+        Self {
+            config: LSystemConfig::default(),
+            materials: MaterialSettingsMap::default(),
+            props: PropConfig::default(),
+            #[cfg(feature = "nursery")]
+            nursery: NurseryState::default(),
+            browser: PresetBrowser::default(),
+            thumbnails: ThumbnailRenderer::default(),
+            // Saved plants of the developer's session must not leak in
+            herbarium: Herbarium::empty(),
+        }
+    }
+}
+
+fn preset_harness(state: EditorState) -> Harness<'static, EditorState> {
+    Harness::new_ui_state(
+        |ui: &mut egui::Ui, state: &mut EditorState| {
+            // Same routing as the editor panel: nursery selection first, else the editor
+            preset_picker_ui(ui, &mut state.browser);
+            let pick = preset_browser_ui(
+                ui.ctx(),
+                &mut state.browser,
+                &state.thumbnails,
+                &state.herbarium,
+            );
+            let Some(PresetPick::Preset(preset)) = pick else {
+                return;
+            };
+            #[cfg(feature = "nursery")]
+            if state.nursery.inject_preset(preset) {
+                return;
+            }
+            apply_preset_to_editor(
+                preset,
+                &mut state.config,
+                &mut state.materials,
+                &mut state.props,
+            );
+        },
+        state,
+    )
+}
+
+/// Frames to run while the browser is open; its thumbnail spinners repaint
+/// forever, so `Harness::run` never settles.
+const BROWSER_STEPS: usize = 4;
+
+/// Opens the preset browser filtered down to the preset `name`.
+fn open_browser_at(harness: &mut Harness<'static, EditorState>, name: &str) {
+    harness.get_by_label("📚 Browse…").click();
+    harness.run_steps(BROWSER_STEPS);
+    harness.state_mut().browser.search = name.to_string();
+    harness.run_steps(BROWSER_STEPS);
+}
+
+#[test]
+fn test_preset_click_applies_all_fields() {
+    let preset = &PRESETS[0];
+    let mut harness = preset_harness(EditorState::default());
+
+    open_browser_at(&mut harness, preset.name);
+    harness.get_by_label(preset.name).click();
+    harness.run_steps(BROWSER_STEPS);
+
+    let state = harness.state();
+    let (growth, finalization) = split_source_code(preset.code);
+    assert_eq!(state.config.source_code, growth);
+    assert_eq!(state.config.finalization_code, finalization);
+    assert_eq!(state.config.iterations, preset.iterations);
+    assert_eq!(state.config.default_angle, preset.angle);
+    assert_eq!(state.config.step_size, preset.step);
+    assert_eq!(state.config.default_width, preset.width);
+    assert_eq!(state.config.elasticity, preset.elasticity);
+    assert_eq!(state.config.tropism, preset.tropism);
+    assert!(state.config.recompile_requested);
+    assert_eq!(state.materials.settings.len(), preset.materials.len());
+    for (slot, material) in preset.materials {
+        assert_eq!(
+            state.materials.settings[slot].base_color,
+            material.base_color
+        );
+    }
+    assert_eq!(state.props.prop_meshes.len(), preset.prop_meshes.len());
+}
+
+#[test]
+#[cfg(feature = "nursery")]
+fn test_preset_click_injects_into_nursery_selection() {
+    let mut state = EditorState::default();
+    state
+        .nursery
+        .initialize_from_editor(&state.config, &state.materials, &state.props);
+    state.nursery.mode = NurseryMode::Enabled;
+    let source_before = state.config.source_code.clone();
+
+    let preset = PRESETS.last().unwrap();
+    let mut harness = preset_harness(state);
+    open_browser_at(&mut harness, preset.name);
+    harness.get_by_label(preset.name).click();
+    harness.run_steps(BROWSER_STEPS);
+
+    let state = harness.state();
+    assert_eq!(state.config.source_code, source_before, "Editor untouched");
+    let (growth, _) = split_source_code(preset.code);
+    for &index in &state.nursery.selected {
+        assert_eq!(state.nursery.population[index].genotype.source_code, growth);
+    }
+    assert!(state.nursery.needs_3d_rebuild);
+}
+
+#[test]
+fn test_iteration_buttons_request_recompile() {
+    let mut harness = Harness::new_ui_state(
+        |ui: &mut egui::Ui, config: &mut LSystemConfig| {
+            iterations_ui(ui, config);
+        },
+        LSystemConfig::default(),
+    );
+    let start = harness.state().iterations;
+
+    harness.get_by_label("➕").click();
+    harness.run();
+    assert_eq!(harness.state().iterations, start + 1);
+    assert!(harness.state().recompile_requested);
+
+    harness.state_mut().recompile_requested = false;
+    harness.get_by_label("➖").click();
+    harness.run();
+    assert_eq!(harness.state().iterations, start);
+    assert!(harness.state().recompile_requested);
+}
+
+#[test]
+#[cfg(feature = "nursery")]
+fn test_nursery_toggle_and_breed() {
+    let mut harness = Harness::new_ui_state(
+        |ui: &mut egui::Ui, state: &mut EditorState| {
+            nursery_ui(
+                ui,
+                &mut state.nursery,
+                &ThumbnailRenderer::default(),
+                &state.config,
+                &state.materials,
+                &state.props,
+            );
+        },
+        EditorState::default(),
+    );
+
+    harness.get_by_label("🌱 Open Nursery").click();
+    harness.run();
+    let nursery = &harness.state().nursery;
+    assert_eq!(nursery.mode, NurseryMode::Enabled);
+    assert_eq!(nursery.population.len(), nursery.population_size());
+    assert!(nursery.needs_3d_rebuild);
+
+    harness.get_by_label("Breed").click();
+    harness.run();
+    assert_eq!(harness.state().nursery.generation, 1);
+}
+
+#[test]
+#[cfg(feature = "nursery")]
+fn test_nursery_solo_view_buttons() {
+    let mut harness = Harness::new_ui_state(
+        |ui: &mut egui::Ui, state: &mut EditorState| {
+            nursery_ui(
+                ui,
+                &mut state.nursery,
+                &ThumbnailRenderer::default(),
+                &state.config,
+                &state.materials,
+                &state.props,
+            );
+        },
+        EditorState::default(),
+    );
+
+    harness.get_by_label("🌱 Open Nursery").click();
+    harness.run();
+    harness.state_mut().nursery.solo = Some(2);
+    harness.run();
+    assert!(!harness.state().nursery.is_cell_visible(0));
+    harness.get_by_label("↩ Back to nursery").click();
+    harness.run();
+    assert_eq!(harness.state().nursery.solo, None);
+    assert!(harness.state().nursery.is_cell_visible(0));
+
+    harness.state_mut().nursery.solo = Some(2);
+    harness.run();
+    harness.get_by_label("📥 Adopt into editor").click();
+    harness.run();
+    let nursery = &harness.state().nursery;
+    assert_eq!(nursery.mode, NurseryMode::Disabled);
+    assert_eq!(nursery.solo, None);
+}